@@ -1,5 +1,7 @@
 use bumpalo::Bump;
-use roc_load::{ExecutionMode, LoadConfig, LoadedModule, Threading};
+use roc_load::{
+    ExecutionMode, LoadConfig, LoadedModule, MonoDiagnostics, MonoOptimizations, Threading,
+};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::DEFAULT_PALETTE;
 use roc_target::TargetInfo;
@@ -16,6 +18,8 @@ pub fn load_module(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        mono_optimizations: MonoOptimizations::Disabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
 
     let arena = Bump::new();
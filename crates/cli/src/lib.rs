@@ -13,6 +13,7 @@ use roc_build::program::{
 use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
+use roc_gen_llvm::llvm::sanitize::Sanitizers;
 use roc_load::{ExpectMetadata, Threading};
 use roc_mono::ir::OptLevel;
 use roc_packaging::cache::RocCacheDir;
@@ -62,7 +63,12 @@ pub const FLAG_TIME: &str = "time";
 pub const FLAG_LINKER: &str = "linker";
 pub const FLAG_PREBUILT: &str = "prebuilt-platform";
 pub const FLAG_CHECK: &str = "check";
+pub const FLAG_VERIFY: &str = "verify";
 pub const FLAG_WASM_STACK_SIZE_KB: &str = "wasm-stack-size-kb";
+pub const FLAG_PROFILE_ALLOC: &str = "profile-alloc";
+pub const FLAG_MISSED_UPDATES: &str = "missed-updates";
+pub const FLAG_SANITIZE: &str = "sanitize";
+pub const FLAG_INTERPRET: &str = "interpret";
 pub const ROC_FILE: &str = "ROC_FILE";
 pub const ROC_DIR: &str = "ROC_DIR";
 pub const GLUE_DIR: &str = "GLUE_DIR";
@@ -100,6 +106,29 @@ pub fn build_app<'a>() -> Command<'a> {
         .help("Store LLVM debug information in the generated program")
         .required(false);
 
+    let flag_profile_alloc = Arg::new(FLAG_PROFILE_ALLOC)
+        .long(FLAG_PROFILE_ALLOC)
+        .help("Make the compiled program print a summary of its allocations and refcount changes when it exits\n(Only the LLVM backend is instrumented. The assembly and wasm backends that --dev uses do not support this flag.)")
+        .required(false);
+
+    let flag_sanitize = Arg::new(FLAG_SANITIZE)
+        .long(FLAG_SANITIZE)
+        .help("Instrument the compiled program to catch memory errors at runtime\n(`address` uses LLVM's AddressSanitizer, `refcount` catches refcount changes of freed values. This is not supported together with --dev.)")
+        .takes_value(true)
+        .multiple_occurrences(true)
+        .possible_values(["address", "refcount"])
+        .required(false);
+
+    let flag_missed_updates = Arg::new(FLAG_MISSED_UPDATES)
+        .long(FLAG_MISSED_UPDATES)
+        .help("Warn where a value like a List or Dict is copied instead of updated in place, because it is still used afterwards")
+        .required(false);
+
+    let flag_interpret = Arg::new(FLAG_INTERPRET)
+        .long(FLAG_INTERPRET)
+        .help("Run the code in an interpreter instead of compiling it\n(This does not need LLVM, but it is much slower, and programs cannot call their platform.)")
+        .required(false);
+
     let flag_time = Arg::new(FLAG_TIME)
         .long(FLAG_TIME)
         .help("Print detailed compilation time information")
@@ -148,6 +177,9 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_profile_alloc.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_missed_updates.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
@@ -197,6 +229,7 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_interpret.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
@@ -211,6 +244,7 @@ pub fn build_app<'a>() -> Command<'a> {
         )
         .subcommand(Command::new(CMD_REPL)
             .about("Launch the interactive Read Eval Print Loop (REPL)")
+            .arg(flag_interpret.clone())
         )
         .subcommand(Command::new(CMD_RUN)
             .about("Run a .roc file even if it has build errors")
@@ -219,6 +253,9 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_profile_alloc.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_missed_updates.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
@@ -232,6 +269,9 @@ pub fn build_app<'a>() -> Command<'a> {
             .arg(flag_opt_size.clone())
             .arg(flag_dev.clone())
             .arg(flag_debug.clone())
+            .arg(flag_profile_alloc.clone())
+            .arg(flag_sanitize.clone())
+            .arg(flag_missed_updates.clone())
             .arg(flag_time.clone())
             .arg(flag_linker.clone())
             .arg(flag_prebuilt.clone())
@@ -281,6 +321,12 @@ pub fn build_app<'a>() -> Command<'a> {
         .subcommand(Command::new(CMD_GLUE)
             .about("Generate glue code between a platform's Roc API and its host language")
             .arg(&flag_dev)
+            .arg(
+                Arg::new(FLAG_VERIFY)
+                    .long(FLAG_VERIFY)
+                    .help("Instead of generating glue, check that the glue previously generated into GLUE_DIR still matches the platform's types\n(If it doesn't, return a non-zero exit code.)")
+                    .required(false),
+            )
             .arg(
                 Arg::new(GLUE_SPEC)
                     .help("The specification for how to translate Roc types into output files.")
@@ -326,6 +372,9 @@ pub fn build_app<'a>() -> Command<'a> {
         .arg(flag_opt_size)
         .arg(flag_dev)
         .arg(flag_debug)
+        .arg(flag_profile_alloc)
+        .arg(flag_sanitize)
+        .arg(flag_missed_updates)
         .arg(flag_time)
         .arg(flag_linker)
         .arg(flag_prebuilt)
@@ -383,7 +432,9 @@ pub fn test(_matches: &ArgMatches, _triple: Triple) -> io::Result<i32> {
 #[cfg(not(windows))]
 pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     use roc_build::program::report_problems_monomorphized;
-    use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError};
+    use roc_load::{
+        ExecutionMode, LoadConfig, LoadMonomorphizedError, MonoDiagnostics, MonoOptimizations,
+    };
     use roc_packaging::cache;
     use roc_target::TargetInfo;
 
@@ -435,6 +486,8 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Test,
+        mono_optimizations: MonoOptimizations::Disabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
     let load_result = roc_load::load_and_monomorphize(
         arena,
@@ -452,20 +505,29 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
             return handle_error_module(module, start_time.elapsed(), filename, false);
         }
     };
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems = report_problems_monomorphized(&mut loaded, false);
 
     let mut expectations = std::mem::take(&mut loaded.expectations);
 
     let interns = loaded.interns.clone();
 
-    let (lib, expects, layout_interner) = roc_repl_expect::run::expect_mono_module_to_dylib(
-        arena,
-        target.clone(),
-        loaded,
-        opt_level,
-        LlvmBackendMode::CliTest,
-    )
-    .unwrap();
+    // When interpreting, the expects run on the mono IR directly
+    let mut interpreted = None;
+    let mut compiled = None;
+    if matches.is_present(FLAG_INTERPRET) {
+        interpreted = Some(loaded);
+    } else {
+        compiled = Some(
+            roc_repl_expect::run::expect_mono_module_to_dylib(
+                arena,
+                target.clone(),
+                loaded,
+                opt_level,
+                LlvmBackendMode::CliTest,
+            )
+            .unwrap(),
+        );
+    }
 
     // Print warnings before running tests.
     {
@@ -485,16 +547,27 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
 
     let mut writer = std::io::stdout();
 
-    let (failed, passed) = roc_repl_expect::run::run_toplevel_expects(
-        &mut writer,
-        roc_reporting::report::RenderTarget::ColorTerminal,
-        arena,
-        interns,
-        &layout_interner.into_global(),
-        &lib,
-        &mut expectations,
-        expects,
-    )
+    let (failed, passed) = match (compiled, interpreted) {
+        (Some((lib, expects, layout_interner)), _) => roc_repl_expect::run::run_toplevel_expects(
+            &mut writer,
+            roc_reporting::report::RenderTarget::ColorTerminal,
+            arena,
+            interns,
+            &layout_interner.into_global(),
+            &lib,
+            &mut expectations,
+            expects,
+        ),
+        (None, Some(loaded)) => roc_repl_expect::interp::run_toplevel_expects(
+            &mut writer,
+            roc_reporting::report::RenderTarget::ColorTerminal,
+            arena,
+            interns,
+            loaded,
+            &mut expectations,
+        ),
+        (None, None) => unreachable!(),
+    }
     .unwrap();
 
     let total_time = start_time.elapsed();
@@ -525,6 +598,15 @@ pub fn test(matches: &ArgMatches, triple: Triple) -> io::Result<i32> {
     }
 }
 
+/// The instrumentation flags are implemented only by the LLVM backend
+fn non_llvm_backend_name(backend: CodeGenBackend) -> Option<&'static str> {
+    match backend {
+        CodeGenBackend::Llvm(_) => None,
+        CodeGenBackend::Assembly(_) => Some("assembly backend"),
+        CodeGenBackend::Wasm => Some("wasm backend"),
+    }
+}
+
 pub fn build(
     matches: &ArgMatches,
     config: BuildConfig,
@@ -630,6 +712,43 @@ pub fn build(
     };
 
     let emit_debug_info = matches.is_present(FLAG_DEBUG);
+    let profile_alloc = matches.is_present(FLAG_PROFILE_ALLOC);
+
+    if profile_alloc {
+        if let Some(backend) = non_llvm_backend_name(code_gen_backend) {
+            user_error!(
+                "--{} is not supported by the {} that --{} uses",
+                FLAG_PROFILE_ALLOC,
+                backend,
+                FLAG_DEV
+            );
+        }
+    }
+
+    let mut sanitizers = Sanitizers::default();
+    for sanitizer in matches.values_of(FLAG_SANITIZE).into_iter().flatten() {
+        match sanitizer {
+            "address" => sanitizers.address = true,
+            "refcount" => sanitizers.refcount = true,
+            _ => unreachable!(),
+        }
+    }
+
+    if sanitizers.any() {
+        if let Some(backend) = non_llvm_backend_name(code_gen_backend) {
+            user_error!(
+                "--{} is not supported by the {} that --{} uses",
+                FLAG_SANITIZE,
+                backend,
+                FLAG_DEV
+            );
+        }
+    }
+
+    if sanitizers.address && matches!(triple.architecture, Architecture::Wasm32) {
+        user_error!("--{} address is not supported for wasm32", FLAG_SANITIZE);
+    }
+
     let emit_timings = matches.is_present(FLAG_TIME);
 
     let threading = match matches
@@ -648,6 +767,10 @@ pub fn build(
         LinkingStrategy::Additive
     } else if !roc_linker::supported(link_type, &triple)
         || matches.value_of(FLAG_LINKER) == Some("legacy")
+        // the profiler writes its summary at exit, which needs libc
+        || profile_alloc
+        // the AddressSanitizer runtime is a shared library
+        || sanitizers.address
     {
         LinkingStrategy::Legacy
     } else {
@@ -679,9 +802,12 @@ pub fn build(
         backend: code_gen_backend,
         opt_level,
         emit_debug_info,
+        profile_alloc,
+        report_missed_updates: matches.is_present(FLAG_MISSED_UPDATES),
+        sanitizers,
     };
 
-    let load_config = standard_load_config(&triple, build_ordering, threading);
+    let load_config = standard_load_config(&triple, build_ordering, threading, &code_gen_options);
 
     let res_binary_path = build_file(
         &arena,
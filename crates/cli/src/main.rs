@@ -4,8 +4,8 @@ use roc_build::program::{check_file, CodeGenBackend};
 use roc_cli::{
     build_app, format, test, BuildConfig, FormatMode, Target, CMD_BUILD, CMD_CHECK, CMD_DEV,
     CMD_DOCS, CMD_EDIT, CMD_FORMAT, CMD_GEN_STUB_LIB, CMD_GLUE, CMD_REPL, CMD_RUN, CMD_TEST,
-    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_LIB, FLAG_NO_LINK, FLAG_TARGET,
-    FLAG_TIME, GLUE_DIR, GLUE_SPEC, ROC_FILE,
+    CMD_VERSION, DIRECTORY_OR_FILES, FLAG_CHECK, FLAG_DEV, FLAG_INTERPRET, FLAG_LIB, FLAG_NO_LINK,
+    FLAG_TARGET, FLAG_TIME, FLAG_VERIFY, GLUE_DIR, GLUE_SPEC, ROC_FILE,
 };
 use roc_docs::generate_docs_html;
 use roc_error_macros::user_error;
@@ -13,6 +13,7 @@ use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::LlvmBackendMode;
 use roc_load::{LoadingProblem, Threading};
 use roc_packaging::cache::{self, RocCacheDir};
+use roc_repl_cli::repl_state::ReplBackend;
 use std::fs::{self, FileType};
 use std::io;
 use std::path::{Path, PathBuf};
@@ -99,7 +100,9 @@ fn main() -> io::Result<()> {
                 false => CodeGenBackend::Llvm(LlvmBackendMode::BinaryGlue),
             };
 
-            if !output_path.exists() || output_path.is_dir() {
+            if matches.is_present(FLAG_VERIFY) {
+                roc_glue::verify(input_path, output_path)
+            } else if !output_path.exists() || output_path.is_dir() {
                 roc_glue::generate(input_path, output_path, spec_path, backend)
             } else {
                 eprintln!("`roc glue` must be given a directory to output into, because the glue might generate multiple files.");
@@ -200,7 +203,15 @@ fn main() -> io::Result<()> {
                 }
             }
         }
-        Some((CMD_REPL, _)) => Ok(roc_repl_cli::main()),
+        Some((CMD_REPL, matches)) => {
+            let backend = if matches.is_present(FLAG_INTERPRET) {
+                ReplBackend::Interpreter
+            } else {
+                ReplBackend::Llvm
+            };
+
+            Ok(roc_repl_cli::main(backend))
+        }
         Some((CMD_EDIT, matches)) => {
             match matches
                 .values_of_os(DIRECTORY_OR_FILES)
@@ -50,7 +50,12 @@ mod cli_run {
 
     const OPTIMIZE_FLAG: &str = concatcp!("--", roc_cli::FLAG_OPTIMIZE);
     const LINKER_FLAG: &str = concatcp!("--", roc_cli::FLAG_LINKER);
+    #[allow(dead_code)]
+    const LIB_FLAG: &str = concatcp!("--", roc_cli::FLAG_LIB);
     const CHECK_FLAG: &str = concatcp!("--", roc_cli::FLAG_CHECK);
+    const INTERPRET_FLAG: &str = concatcp!("--", roc_cli::FLAG_INTERPRET);
+    const SANITIZE_FLAG: &str = concatcp!("--", roc_cli::FLAG_SANITIZE);
+    const PROFILE_ALLOC_FLAG: &str = concatcp!("--", roc_cli::FLAG_PROFILE_ALLOC);
     const PREBUILT_PLATFORM: &str = concatcp!("--", roc_cli::FLAG_PREBUILT, "=true");
     #[allow(dead_code)]
     const TARGET_FLAG: &str = concatcp!("--", roc_cli::FLAG_TARGET);
@@ -640,6 +645,47 @@ mod cli_run {
         );
     }
 
+    #[test]
+    #[cfg_attr(windows, ignore)]
+    fn expects_interpreted() {
+        let path = file_path_from_root("crates/cli_testing_examples/expects", "expects.roc");
+        let out = run_roc([CMD_TEST, INTERPRET_FLAG, path.to_str().unwrap()], &[], &[]);
+
+        // the failed expect shows the same values as when it is compiled
+        let actual = strip_colors(&out.stdout);
+        let expected = indoc!(
+            r#"
+            This expectation failed:
+
+             6│>  expect
+             7│>      a = 1
+             8│>      b = 2
+             9│>
+            10│>      a == b
+
+            When it failed, these variables had these values:
+
+            a : Num *
+            a = 1
+
+            b : Num *
+            b = 2
+
+
+
+            1 failed and 0 passed in "#
+        );
+
+        assert!(
+            actual.contains(expected),
+            "expected output to contain:\n{}\nbut instead got:\n{}\nstderr was:\n{}",
+            expected,
+            actual,
+            out.stderr
+        );
+        assert!(!out.status.success());
+    }
+
     #[test]
     #[cfg_attr(
         windows,
@@ -1320,6 +1366,223 @@ mod cli_run {
         );
     }
 
+    #[test]
+    #[serial(multi_dep_str)]
+    #[cfg_attr(windows, ignore)]
+    fn run_multi_dep_str_sanitize_refcount() {
+        check_output_with_stdin(
+            &fixture_file("multi-dep-str", "Main.roc"),
+            &[],
+            "multi-dep-str",
+            &[SANITIZE_FLAG, "refcount"],
+            &[],
+            &[],
+            "I am Dep2.str2\n",
+            UseValgrind::No,
+            TestCliCommands::Run,
+        );
+    }
+
+    #[test]
+    #[serial(profile_alloc)]
+    #[cfg_attr(windows, ignore)]
+    fn profile_alloc_summary() {
+        let file = fixture_file("profile-alloc", "Main.roc");
+
+        run_roc_on_failure_is_panic(
+            &file,
+            [CMD_BUILD, PROFILE_ALLOC_FLAG, "--max-threads=1"],
+            &[],
+            &[],
+            &[],
+        );
+
+        let out = run_cmd(
+            file.with_file_name("profile-alloc").to_str().unwrap(),
+            iter::empty(),
+            &[],
+            iter::empty(),
+        );
+
+        assert!(out.status.success(), "stderr was:\n{}", out.stderr);
+        assert_eq!(out.stdout, "130\n");
+
+        let summary = out.stderr.as_str();
+
+        let peak_live_bytes: u64 = summary
+            .lines()
+            .find_map(|line| line.strip_prefix("peak live memory:"))
+            .and_then(|rest| rest.trim().strip_suffix(" bytes"))
+            .and_then(|bytes| bytes.parse().ok())
+            .unwrap_or_else(|| panic!("no peak live memory in the summary:\n{}", summary));
+
+        // allocations, bytes, increments, decrements, in-place, copying, then the site
+        let rows: Vec<(Vec<u64>, String)> = summary
+            .lines()
+            .skip_while(|line| !line.trim_start().starts_with("allocations"))
+            .skip(1)
+            .map(|line| {
+                let columns: Vec<&str> = line.split_whitespace().collect();
+                let counts = columns[..6].iter().map(|n| n.parse().unwrap()).collect();
+
+                (counts, columns[6..].join(" "))
+            })
+            .collect();
+
+        for (_, site) in rows.iter() {
+            if site.starts_with("#UserApp.") {
+                assert!(
+                    site.contains("Main.roc:") && site.ends_with(')'),
+                    "site {:?} has no source location",
+                    site
+                );
+            }
+        }
+
+        let total = |proc_name: &str, column: usize| -> u64 {
+            rows.iter()
+                .filter(|(_, site)| site.starts_with(&format!("#UserApp.{}:", proc_name)))
+                .map(|(counts, _)| counts[column])
+                .sum()
+        };
+
+        // every cell is 16 bytes, plus its refcount
+        assert_eq!(total("range", 0), 10);
+        assert!(total("range", 1) >= 10 * 24);
+        assert_eq!(total("increment", 0), 10);
+        assert_eq!(total("increment", 4), 10);
+        assert_eq!(total("increment", 5), 10);
+
+        // `shared` and `copied` are alive at the same time
+        assert!(peak_live_bytes >= 20 * 24, "summary was:\n{}", summary);
+    }
+
+    /// Build the dylib-host fixture as a shared library with a surgically linked host,
+    /// then load it into this process and ask its host for the app's greeting
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    fn surgical_dylib_host_greeting() -> String {
+        let file = fixture_file("dylib-host", "Main.roc");
+
+        run_roc_on_failure_is_panic(
+            &file,
+            [CMD_BUILD, LIB_FLAG, LINKER_FLAG, "surgical"],
+            &[],
+            &[],
+            &[],
+        );
+
+        let mut buffer = [0u8; 128];
+
+        let len = unsafe {
+            let lib = libloading::Library::new(file.with_file_name("libgreeting.so")).unwrap();
+            let greeting: libloading::Symbol<unsafe extern "C" fn(*mut u8, usize) -> usize> =
+                lib.get(b"greeting").unwrap();
+
+            greeting(buffer.as_mut_ptr(), buffer.len())
+        };
+
+        String::from_utf8(buffer[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    fn surgical_dylib_host_x86_64() {
+        assert_eq!(
+            surgical_dylib_host_greeting(),
+            "Hello from a shared library whose host was linked surgically!"
+        );
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    fn surgical_dylib_host_aarch64() {
+        assert_eq!(
+            surgical_dylib_host_greeting(),
+            "Hello from a shared library whose host was linked surgically!"
+        );
+    }
+
+    /// Build the sanitize fixture with `--sanitize <sanitizer>`, and run it with a host that
+    /// misuses the memory that Roc manages in the given way
+    fn run_sanitized(sanitizer: &str, host_misuse: &str) -> Out {
+        let file = fixture_file("sanitize", "Main.roc");
+
+        run_roc_on_failure_is_panic(
+            &file,
+            [CMD_BUILD, SANITIZE_FLAG, sanitizer, "--max-threads=1"],
+            &[],
+            &[],
+            &[],
+        );
+
+        run_cmd(
+            file.with_file_name("sanitize").to_str().unwrap(),
+            iter::empty(),
+            &[host_misuse.to_string()],
+            // these tests are about freed memory, not memory that is never freed
+            [("ASAN_OPTIONS", "detect_leaks=0")],
+        )
+    }
+
+    #[test]
+    #[serial(sanitize)]
+    #[cfg_attr(windows, ignore)]
+    fn sanitize_without_misuse() {
+        for sanitizer in ["address", "refcount"] {
+            let out = run_sanitized(sanitizer, "none");
+
+            assert!(out.status.success(), "stderr was:\n{}", out.stderr);
+            assert_eq!(out.stdout, "63\n");
+        }
+    }
+
+    #[test]
+    #[serial(sanitize)]
+    #[cfg_attr(windows, ignore)]
+    fn sanitize_address_use_after_free() {
+        let out = run_sanitized("address", "use-after-free");
+
+        assert!(!out.status.success());
+        assert!(
+            out.stderr.contains("AddressSanitizer: heap-use-after-free"),
+            "stderr was:\n{}",
+            out.stderr
+        );
+    }
+
+    #[test]
+    #[serial(sanitize)]
+    #[cfg_attr(windows, ignore)]
+    fn sanitize_refcount_double_decrement() {
+        let out = run_sanitized("refcount", "double-decrement");
+
+        assert!(!out.status.success());
+        assert!(
+            out.stderr
+                .contains("Roc tried to change the refcount of a value that was already freed"),
+            "stderr was:\n{}",
+            out.stderr
+        );
+    }
+
+    #[test]
+    #[serial(sanitize)]
+    #[cfg_attr(windows, ignore)]
+    fn sanitize_refcount_write_after_free() {
+        let out = run_sanitized("refcount", "write-after-free");
+
+        assert!(!out.status.success());
+        assert!(
+            out.stderr
+                .contains("A value was written to after Roc freed it"),
+            "stderr was:\n{}",
+            out.stderr
+        );
+    }
+
     #[test]
     #[serial(multi_dep_thunk)]
     #[cfg_attr(windows, ignore)]
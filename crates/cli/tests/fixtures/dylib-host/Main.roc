@@ -0,0 +1,7 @@
+app "libgreeting"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+main : Str
+main = "Hello from a shared library whose host was linked surgically!"
@@ -0,0 +1,62 @@
+// A host that is a shared library: whoever loads it calls `greeting`, which runs the app.
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <sys/types.h>
+
+void* roc_alloc(size_t size, unsigned int alignment) { return malloc(size); }
+
+void* roc_realloc(void* ptr, size_t new_size, size_t old_size, unsigned int alignment) {
+  return realloc(ptr, new_size);
+}
+
+void roc_dealloc(void* ptr, unsigned int alignment) { free(ptr); }
+
+void roc_panic(void* ptr, unsigned int alignment) {
+  char* msg = (char*)ptr;
+  fprintf(stderr,
+          "Application crashed with message\n\n    %s\n\nShutting down\n", msg);
+  exit(1);
+}
+
+void* roc_memcpy(void* dest, const void* src, size_t n) {
+  return memcpy(dest, src, n);
+}
+
+void* roc_memset(void* str, int c, size_t n) { return memset(str, c, n); }
+
+struct RocStr {
+  char* bytes;
+  size_t len;
+  size_t capacity;
+};
+
+bool is_small_str(struct RocStr str) { return ((ssize_t)str.capacity) < 0; }
+
+size_t roc_str_len(struct RocStr str) {
+  if (is_small_str(str)) {
+    char* bytes = (char*)&str;
+    return (size_t)(bytes[sizeof(str) - 1] ^ 0b10000000);
+  } else {
+    return str.len;
+  }
+}
+
+extern void roc__mainForHost_1_exposed_generic(struct RocStr* string);
+
+// Copies as much of the app's string into `buffer` as fits, and returns the string's full length.
+//
+// NOTE: the string is a static string, read from in the binary
+// if you make it a heap-allocated string, it'll be leaked here
+size_t greeting(char* buffer, size_t capacity) {
+  struct RocStr str;
+  roc__mainForHost_1_exposed_generic(&str);
+
+  size_t str_len = roc_str_len(str);
+  char* str_bytes = is_small_str(str) ? (char*)&str : str.bytes;
+
+  memcpy(buffer, str_bytes, str_len < capacity ? str_len : capacity);
+
+  return str_len;
+}
@@ -0,0 +1,37 @@
+app "profile-alloc"
+    packages { pf: "../multi-dep-str/platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+ConsList : [Cons I64 ConsList, Nil]
+
+range : I64 -> ConsList
+range = \n ->
+    if n == 0 then
+        Nil
+    else
+        Cons n (range (n - 1))
+
+increment : ConsList -> ConsList
+increment = \list ->
+    when list is
+        Cons x rest -> Cons (x + 1) (increment rest)
+        Nil -> Nil
+
+sum : ConsList -> I64
+sum = \list ->
+    when list is
+        Cons x rest -> x + sum rest
+        Nil -> 0
+
+main : Str
+main =
+    shared = range 10
+
+    # `shared` is used again below, so every cell is copied
+    copied = increment shared
+
+    # nothing else refers to `copied`, so every cell is updated in place
+    updated = increment copied
+
+    Num.toStr (sum shared + sum updated)
@@ -0,0 +1,15 @@
+app "sanitize"
+    packages { pf: "platform/main.roc" }
+    imports []
+    provides [main] to pf
+
+# This takes ownership of `str`, so it frees `str` unless the host kept a reference
+main : Str -> Nat
+main = \str ->
+    if Str.startsWith str "churn" then
+        # free more values than fit in the quarantine of `--sanitize refcount`
+        List.range { start: At 0, end: Before 2000 }
+        |> List.map \n -> [n]
+        |> List.len
+    else
+        Str.countUtf8Bytes str
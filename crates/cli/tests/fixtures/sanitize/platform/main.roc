@@ -0,0 +1,9 @@
+platform "sanitize"
+    requires {} { main : Str -> Nat }
+    exposes []
+    packages {}
+    imports []
+    provides [mainForHost]
+
+mainForHost : Str -> Nat
+mainForHost = \str -> main str
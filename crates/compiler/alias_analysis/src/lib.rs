@@ -1408,6 +1408,13 @@ fn expr_spec<'a>(
                 builder.add_get_tuple_field(block, variant_id, index)
             }
         },
+        UnionFieldPtrAtIndex { structure, .. } => {
+            // the field is written to later with `PtrStore`, which is modeled as an unknown
+            // function of the pointer and the value; tie the pointer to the structure it points into
+            let pointer_type = layout_spec(env, builder, interner, layout)?;
+
+            builder.add_unknown_with(block, &[env.symbols[structure]], pointer_type)
+        }
         StructAtIndex {
             index, structure, ..
         } => {
@@ -1,7 +1,7 @@
 use crate::target::{arch_str, target_zig_str};
 use libloading::{Error, Library};
 use roc_command_utils::{cargo, clang, rustup, zig};
-use roc_error_macros::internal_error;
+use roc_error_macros::{internal_error, user_error};
 use roc_mono::ir::OptLevel;
 use std::collections::HashMap;
 use std::fs::DirEntry;
@@ -136,6 +136,7 @@ pub fn build_zig_host_native(
     target: &str,
     opt_level: OptLevel,
     shared_lib_path: Option<&Path>,
+    link_type: LinkType,
     builtins_host_path: &Path,
 ) -> Command {
     let mut zig_cmd = zig();
@@ -145,13 +146,20 @@ pub fn build_zig_host_native(
         .env("HOME", env_home);
 
     if let Some(shared_lib_path) = shared_lib_path {
+        match link_type {
+            // a shared library exports roc_alloc and friends anyway
+            LinkType::Dylib => zig_cmd.args(["build-lib", "-dynamic", "-fPIC"]),
+            _ => zig_cmd.args([
+                "build-exe",
+                "-fPIE",
+                "-rdynamic", // make sure roc_alloc and friends are exposed
+            ]),
+        };
+
         // with LLVM, the builtins are already part of the roc app,
         // but with the dev backend, they are missing. To minimize work,
         // we link them as part of the host executable
         zig_cmd.args([
-            "build-exe",
-            "-fPIE",
-            "-rdynamic", // make sure roc_alloc and friends are exposed
             shared_lib_path.to_str().unwrap(),
             builtins_host_path.to_str().unwrap(),
         ]);
@@ -208,6 +216,7 @@ pub fn build_zig_host_native(
     target: &str,
     opt_level: OptLevel,
     shared_lib_path: Option<&Path>,
+    _link_type: LinkType,
     builtins_host_path: &Path,
 ) -> Command {
     // to prevent `clang failed with stderr: zig: error: unable to make temporary file: No such file or directory`
@@ -267,6 +276,7 @@ pub fn build_zig_host_native(
     _target: &str,
     opt_level: OptLevel,
     shared_lib_path: Option<&Path>,
+    _link_type: LinkType,
     builtins_host_path: &Path,
     // For compatibility with the non-macOS def above. Keep these in sync.
 ) -> Command {
@@ -409,6 +419,7 @@ pub fn build_c_host_native(
     sources: &[&str],
     opt_level: OptLevel,
     shared_lib_path: Option<&Path>,
+    link_type: LinkType,
     builtins_host_path: &Path,
 ) -> Command {
     let mut clang_cmd = clang();
@@ -435,10 +446,16 @@ pub fn build_c_host_native(
                     get_target_str(target),
                     opt_level,
                     Some(shared_lib_path),
+                    link_type,
                     builtins_host_path,
                 );
             }
             _ => {
+                match link_type {
+                    LinkType::Dylib => clang_cmd.args(["-fPIC", "-shared"]),
+                    _ => clang_cmd.args(["-fPIE", "-pie"]),
+                };
+
                 clang_cmd.args([
                     shared_lib_path.to_str().unwrap(),
                     // This line is commented out because
@@ -447,8 +464,6 @@ pub fn build_c_host_native(
                     // optimized builds. Disabling until it is needed for dev
                     // builds.
                     // builtins_host_path,
-                    "-fPIE",
-                    "-pie",
                     "-lm",
                     "-lpthread",
                     "-ldl",
@@ -525,6 +540,7 @@ pub fn rebuild_host(
     target: &Triple,
     platform_main_roc: &Path,
     shared_lib_path: Option<&Path>,
+    link_type: LinkType,
 ) -> PathBuf {
     let c_host_src = platform_main_roc.with_file_name("host.c");
     let c_host_dest = platform_main_roc.with_file_name("c_host.o");
@@ -549,9 +565,7 @@ pub fn rebuild_host(
             platform_main_roc.with_extension("bc")
         }
     } else if shared_lib_path.is_some() {
-        platform_main_roc
-            .with_file_name("dynhost")
-            .with_extension(executable_extension)
+        platform_main_roc.with_file_name(roc_linker::dynhost_filename(link_type, target))
     } else {
         platform_main_roc.with_file_name(legacy_host_filename(target).unwrap())
     };
@@ -589,6 +603,7 @@ pub fn rebuild_host(
                 get_target_str(target),
                 opt_level,
                 shared_lib_path,
+                link_type,
                 builtins_host_tempfile.path(),
             ),
             Architecture::X86_32(_) => build_zig_host_native(
@@ -599,6 +614,7 @@ pub fn rebuild_host(
                 "i386-linux-musl",
                 opt_level,
                 shared_lib_path,
+                link_type,
                 builtins_host_tempfile.path(),
             ),
             Architecture::Aarch64(_) => build_zig_host_native(
@@ -609,6 +625,7 @@ pub fn rebuild_host(
                 target_zig_str(target),
                 opt_level,
                 shared_lib_path,
+                link_type,
                 builtins_host_tempfile.path(),
             ),
             _ => internal_error!("Unsupported architecture {:?}", target.architecture),
@@ -619,6 +636,12 @@ pub fn rebuild_host(
         // Compile and link Cargo.toml, if it exists
         let cargo_dir = platform_main_roc.parent().unwrap();
 
+        if shared_lib_path.is_some() && link_type == LinkType::Dylib {
+            user_error!(
+                "The surgical linker cannot build a shared library from a Cargo host yet. Use `--linker=legacy` instead."
+            );
+        }
+
         let mut cargo_cmd = if cfg!(windows) {
             // on windows, we need the nightly toolchain so we can use `-Z export-executable-symbols`
             // using `+nightly` only works when running cargo through rustup
@@ -678,6 +701,7 @@ pub fn rebuild_host(
                 &[c_host_src.to_str().unwrap()],
                 opt_level,
                 shared_lib_path,
+                link_type,
                 builtins_host_tempfile.path(),
             );
 
@@ -733,6 +757,7 @@ pub fn rebuild_host(
                 ],
                 opt_level,
                 shared_lib_path,
+                link_type,
                 builtins_host_tempfile.path(),
             );
             run_build_command(clang_cmd, "host.c", 0);
@@ -746,6 +771,7 @@ pub fn rebuild_host(
                 &[c_host_src.to_str().unwrap()],
                 opt_level,
                 shared_lib_path,
+                link_type,
                 builtins_host_tempfile.path(),
             );
 
@@ -782,6 +808,7 @@ pub fn rebuild_host(
             &[c_host_src.to_str().unwrap()],
             opt_level,
             shared_lib_path,
+            link_type,
             builtins_host_tempfile.path(),
         );
 
@@ -921,6 +948,44 @@ fn look_for_library(lib_dirs: &[&[&str]], lib_filename: &str) -> Option<PathBuf>
         .find(|path| path.exists())
 }
 
+/// Linker arguments for the AddressSanitizer runtime that ships with clang, for apps built with
+/// `roc build --sanitize address`. We link the shared runtime, so that it also intercepts the
+/// allocations of the (uninstrumented) host.
+pub fn address_sanitizer_args(target: &Triple) -> Vec<String> {
+    let runtime_name = match (target.operating_system, target.architecture) {
+        (OperatingSystem::Linux, Architecture::X86_64) => "libclang_rt.asan-x86_64.so",
+        (OperatingSystem::Linux, Architecture::Aarch64(_)) => "libclang_rt.asan-aarch64.so",
+        (OperatingSystem::Darwin, _) => "libclang_rt.asan_osx_dynamic.dylib",
+        _ => user_error!(
+            "--sanitize address is not supported for target {} yet.",
+            target
+        ),
+    };
+
+    let output = clang()
+        .arg(format!("-print-file-name={}", runtime_name))
+        .output()
+        .unwrap_or_else(|err| internal_error!("Failed to run clang: {:?}", err));
+
+    // clang prints the name unchanged if it can't find the file
+    let runtime_path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    if !runtime_path.is_file() {
+        user_error!(
+            "I could not find the AddressSanitizer runtime ({}) that comes with clang.\nYou may need to install the compiler-rt package for your version of clang.",
+            runtime_name
+        );
+    }
+
+    let runtime_dir = runtime_path.parent().unwrap();
+
+    vec![
+        runtime_path.to_str().unwrap().to_string(),
+        // the runtime is a shared library, so the app needs to find it when it starts
+        "-rpath".to_string(),
+        runtime_dir.to_str().unwrap().to_string(),
+    ]
+}
+
 fn link_linux(
     target: &Triple,
     output_path: PathBuf,
@@ -1,15 +1,19 @@
 use crate::link::{
-    legacy_host_filename, link, preprocess_host_wasm32, rebuild_host, LinkType, LinkingStrategy,
+    address_sanitizer_args, legacy_host_filename, link, preprocess_host_wasm32, rebuild_host,
+    LinkType, LinkingStrategy,
 };
 use bumpalo::Bump;
 use inkwell::memory_buffer::MemoryBuffer;
-use roc_error_macros::internal_error;
+use roc_collections::all::MutMap;
+use roc_error_macros::{internal_error, user_error};
 use roc_gen_dev::AssemblyBackendMode;
 use roc_gen_llvm::llvm::build::{module_from_builtins, LlvmBackendMode};
 use roc_gen_llvm::llvm::externs::add_default_roc_externs;
+use roc_gen_llvm::llvm::profile::ProfileSites;
+use roc_gen_llvm::llvm::sanitize::{add_sanitizers, run_address_sanitizer_passes, Sanitizers};
 use roc_load::{
     EntryPoint, ExecutionMode, ExpectMetadata, LoadConfig, LoadMonomorphizedError, LoadedModule,
-    LoadingProblem, MonomorphizedModule, Threading,
+    LoadingProblem, MonoDiagnostics, MonoOptimizations, MonomorphizedModule, Threading,
 };
 use roc_mono::ir::{OptLevel, SingleEntryPoint};
 use roc_packaging::cache::RocCacheDir;
@@ -37,13 +41,24 @@ pub struct CodeGenTiming {
     pub code_gen: Duration,
 }
 
-pub fn report_problems_monomorphized(loaded: &mut MonomorphizedModule) -> Problems {
+/// Missed in-place updates are only reported when `report_missed_updates` is set.
+pub fn report_problems_monomorphized(
+    loaded: &mut MonomorphizedModule,
+    report_missed_updates: bool,
+) -> Problems {
+    let mut no_mono_problems = MutMap::default();
+
     report_problems(
         loaded.total_problems(),
         &loaded.sources,
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        if report_missed_updates {
+            &mut loaded.mono_problems
+        } else {
+            &mut no_mono_problems
+        },
     )
 }
 
@@ -54,6 +69,7 @@ pub fn report_problems_typechecked(loaded: &mut LoadedModule) -> Problems {
         &loaded.interns,
         &mut loaded.can_problems,
         &mut loaded.type_problems,
+        &mut MutMap::default(),
     )
 }
 
@@ -85,6 +101,14 @@ pub struct CodeGenOptions {
     pub backend: CodeGenBackend,
     pub opt_level: OptLevel,
     pub emit_debug_info: bool,
+    /// Instrument the program to report its allocations and refcount changes at exit.
+    /// Only supported by the LLVM backend.
+    pub profile_alloc: bool,
+    /// Warn where a value is copied or allocated because it is shared, where it could have
+    /// been updated in place
+    pub report_missed_updates: bool,
+    /// Checks to instrument the program with. Only supported by the LLVM backend.
+    pub sanitizers: Sanitizers,
 }
 
 type GenFromMono<'a> = (CodeObject, CodeGenTiming, ExpectMetadata<'a>);
@@ -102,6 +126,8 @@ pub fn gen_from_mono_module<'a>(
     let path = roc_file_path;
     let debug = code_gen_options.emit_debug_info;
     let opt = code_gen_options.opt_level;
+    let profile_alloc = code_gen_options.profile_alloc;
+    let sanitizers = code_gen_options.sanitizers;
 
     match code_gen_options.backend {
         CodeGenBackend::Wasm => gen_from_mono_module_dev(
@@ -120,15 +146,24 @@ pub fn gen_from_mono_module<'a>(
             wasm_dev_stack_bytes,
             backend_mode,
         ),
-        CodeGenBackend::Llvm(backend_mode) => {
-            gen_from_mono_module_llvm(arena, loaded, path, target, opt, backend_mode, debug)
-        }
+        CodeGenBackend::Llvm(backend_mode) => gen_from_mono_module_llvm(
+            arena,
+            loaded,
+            path,
+            target,
+            opt,
+            backend_mode,
+            debug,
+            profile_alloc,
+            sanitizers,
+        ),
     }
 }
 
 // TODO how should imported modules factor into this? What if those use builtins too?
 // TODO this should probably use more helper functions
 // TODO make this polymorphic in the llvm functions so it can be reused for another backend.
+#[allow(clippy::too_many_arguments)]
 fn gen_from_mono_module_llvm<'a>(
     arena: &'a bumpalo::Bump,
     mut loaded: MonomorphizedModule<'a>,
@@ -137,6 +172,8 @@ fn gen_from_mono_module_llvm<'a>(
     opt_level: OptLevel,
     backend_mode: LlvmBackendMode,
     emit_debug_info: bool,
+    profile_alloc: bool,
+    sanitizers: Sanitizers,
 ) -> GenFromMono<'a> {
     use crate::target::{self, convert_opt_level};
     use inkwell::attributes::{Attribute, AttributeLoc};
@@ -190,6 +227,15 @@ fn gen_from_mono_module_llvm<'a>(
     let (dibuilder, compile_unit) = roc_gen_llvm::llvm::build::Env::new_debug_info(module);
     let (mpm, _fpm) = roc_gen_llvm::llvm::build::construct_optimization_passes(module, opt_level);
 
+    let profile_sites = if profile_alloc {
+        let symbol_regions = std::mem::take(&mut loaded.symbol_regions);
+        let sites: &ProfileSites = arena.alloc(ProfileSites::new(symbol_regions, &loaded.sources));
+
+        Some(sites)
+    } else {
+        None
+    };
+
     // Compile and add all the Procs before adding main
     let env = roc_gen_llvm::llvm::build::Env {
         arena,
@@ -208,6 +254,8 @@ fn gen_from_mono_module_llvm<'a>(
             .keys()
             .copied()
             .collect(),
+        profile_sites,
+        sanitizers,
     };
 
     // does not add any externs for this mode (we have a host) but cleans up some functions around
@@ -243,11 +291,28 @@ fn gen_from_mono_module_llvm<'a>(
     // we don't use the debug info, and it causes weird errors.
     module.strip_debug_info();
 
+    add_sanitizers(&env);
+
     // Uncomment this to see the module's optimized LLVM instruction output:
     // env.module.print_to_stderr();
 
     mpm.run_on(module);
 
+    if sanitizers.address {
+        let target_machine =
+            target::target_machine(target, convert_opt_level(opt_level), RelocMode::PIC)
+                .unwrap_or_else(|| {
+                    user_error!("--sanitize address is not supported for target {}", target)
+                });
+
+        if let Err(errors) = run_address_sanitizer_passes(module, &target_machine) {
+            internal_error!(
+                "LLVM errors when running the AddressSanitizer passes:\n\n {}",
+                errors.to_string()
+            );
+        }
+    }
+
     // Verify the module
     if let Err(errors) = env.module.verify() {
         // write the ll code to a file, so we can modify it
@@ -706,6 +771,7 @@ pub fn standard_load_config(
     target: &Triple,
     order: BuildOrdering,
     threading: Threading,
+    code_gen_options: &CodeGenOptions,
 ) -> LoadConfig {
     let target_info = TargetInfo::from(target);
 
@@ -714,12 +780,24 @@ pub fn standard_load_config(
         BuildOrdering::AlwaysBuild => ExecutionMode::Executable,
     };
 
+    // LLVM has its own optimizations; the other backends rely on the ones in mono
+    let mono_optimizations = match code_gen_options.backend {
+        CodeGenBackend::Llvm(_) => MonoOptimizations::Disabled,
+        CodeGenBackend::Assembly(_) | CodeGenBackend::Wasm => MonoOptimizations::Enabled,
+    };
+
     LoadConfig {
         target_info,
         render: RenderTarget::ColorTerminal,
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode,
+        mono_optimizations,
+        mono_diagnostics: MonoDiagnostics {
+            // the allocation profile names its sites after the source
+            symbol_regions: code_gen_options.profile_alloc,
+            missed_updates: code_gen_options.report_missed_updates,
+        },
     }
 }
 
@@ -789,6 +867,9 @@ fn build_loaded_file<'a>(
         } else {
             platform_main_roc.with_file_name(legacy_host_filename(target).unwrap())
         }
+    } else if link_type == LinkType::Dylib {
+        platform_main_roc
+            .with_file_name(roc_linker::preprocessed_dylib_host_filename(target).unwrap())
     } else {
         platform_main_roc.with_file_name(roc_linker::preprocessed_host_filename(target).unwrap())
     };
@@ -800,12 +881,18 @@ fn build_loaded_file<'a>(
     let cwd = app_module_path.parent().unwrap();
     let mut output_exe_path = cwd.join(&*loaded.output_path);
 
-    if let Some(extension) = operating_system.executable_file_ext() {
+    if link_type == LinkType::Dylib && linking_strategy == LinkingStrategy::Surgical {
+        // The legacy linker picks its own extension for shared libraries.
+        output_exe_path.set_extension(roc_linker::dylib_file_ext(target));
+    } else if let Some(extension) = operating_system.executable_file_ext() {
         output_exe_path.set_extension(extension);
     }
 
     // We don't need to spawn a rebuild thread when using a prebuilt host.
-    let rebuild_thread = if matches!(link_type, LinkType::Dylib | LinkType::None) {
+    // Without the surgical linker, a shared library is just the app, so there is no host to build.
+    let rebuild_thread = if link_type == LinkType::None
+        || (link_type == LinkType::Dylib && linking_strategy != LinkingStrategy::Surgical)
+    {
         None
     } else if is_platform_prebuilt {
         if !preprocessed_host_path.exists() {
@@ -834,6 +921,7 @@ fn build_loaded_file<'a>(
 
         let join_handle = spawn_rebuild_thread(
             code_gen_options.opt_level,
+            link_type,
             linking_strategy,
             platform_main_roc.clone(),
             preprocessed_host_path.clone(),
@@ -873,7 +961,8 @@ fn build_loaded_file<'a>(
     // This only needs to be mutable for report_problems. This can't be done
     // inside a nested scope without causing a borrow error!
     let mut loaded = loaded;
-    let problems = report_problems_monomorphized(&mut loaded);
+    let problems =
+        report_problems_monomorphized(&mut loaded, code_gen_options.report_missed_updates);
     let loaded = loaded;
 
     enum HostRebuildTiming {
@@ -957,6 +1046,7 @@ fn build_loaded_file<'a>(
         (LinkingStrategy::Surgical, _) => {
             roc_linker::link_preprocessed_host(
                 target,
+                link_type,
                 &platform_main_roc,
                 &roc_app_bytes,
                 &output_exe_path,
@@ -999,6 +1089,13 @@ fn build_loaded_file<'a>(
                 inputs.push(builtins_host_tempfile.path().to_str().unwrap());
             }
 
+            let sanitizer_args = if code_gen_options.sanitizers.address {
+                address_sanitizer_args(target)
+            } else {
+                vec![]
+            };
+            inputs.extend(sanitizer_args.iter().map(|arg| arg.as_str()));
+
             let (mut child, _) = link(target, output_exe_path.clone(), &inputs, link_type)
                 .map_err(|_| todo!("gracefully handle `ld` failing to spawn."))?;
 
@@ -1069,6 +1166,7 @@ fn invalid_prebuilt_platform(prebuilt_requested: bool, preprocessed_host_path: P
 #[allow(clippy::too_many_arguments)]
 fn spawn_rebuild_thread(
     opt_level: OptLevel,
+    link_type: LinkType,
     linking_strategy: LinkingStrategy,
     platform_main_roc: PathBuf,
     preprocessed_host_path: PathBuf,
@@ -1092,6 +1190,7 @@ fn spawn_rebuild_thread(
                     &thread_local_target,
                     platform_main_roc.as_path(),
                     None,
+                    link_type,
                 );
 
                 preprocess_host_wasm32(host_dest.as_path(), &preprocessed_host_path);
@@ -1099,6 +1198,7 @@ fn spawn_rebuild_thread(
             LinkingStrategy::Surgical => {
                 build_and_preprocess_host_lowlevel(
                     opt_level,
+                    link_type,
                     &thread_local_target,
                     platform_main_roc.as_path(),
                     preprocessed_host_path.as_path(),
@@ -1115,6 +1215,7 @@ fn spawn_rebuild_thread(
                     &thread_local_target,
                     platform_main_roc.as_path(),
                     None,
+                    link_type,
                 );
             }
         }
@@ -1134,6 +1235,7 @@ pub fn build_and_preprocess_host(
 
     build_and_preprocess_host_lowlevel(
         opt_level,
+        LinkType::Executable,
         target,
         platform_main_roc,
         preprocessed_host_path,
@@ -1143,6 +1245,7 @@ pub fn build_and_preprocess_host(
 
 fn build_and_preprocess_host_lowlevel(
     opt_level: OptLevel,
+    link_type: LinkType,
     target: &Triple,
     platform_main_roc: &Path,
     preprocessed_host_path: &Path,
@@ -1153,10 +1256,17 @@ fn build_and_preprocess_host_lowlevel(
 
     debug_assert!(stub_lib.exists());
 
-    rebuild_host(opt_level, target, platform_main_roc, Some(&stub_lib));
+    rebuild_host(
+        opt_level,
+        target,
+        platform_main_roc,
+        Some(&stub_lib),
+        link_type,
+    );
 
     roc_linker::preprocess_host(
         target,
+        link_type,
         platform_main_roc,
         preprocessed_host_path,
         &stub_lib,
@@ -1187,6 +1297,8 @@ pub fn check_file<'a>(
         palette: DEFAULT_PALETTE,
         threading,
         exec_mode: ExecutionMode::Check,
+        mono_optimizations: MonoOptimizations::Disabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
     let mut loaded =
         roc_load::load_and_typecheck(arena, roc_file_path, roc_cache_dir, load_config)?;
@@ -1249,6 +1361,9 @@ pub fn build_str_test<'a>(
         backend: CodeGenBackend::Llvm(LlvmBackendMode::Binary),
         opt_level: OptLevel::Normal,
         emit_debug_info: false,
+        profile_alloc: false,
+        report_missed_updates: false,
+        sanitizers: Sanitizers::default(),
     };
 
     let emit_timings = false;
@@ -1260,7 +1375,7 @@ pub fn build_str_test<'a>(
     let build_ordering = BuildOrdering::AlwaysBuild;
     let threading = Threading::AtMost(2);
 
-    let load_config = standard_load_config(&triple, build_ordering, threading);
+    let load_config = standard_load_config(&triple, build_ordering, threading, &code_gen_options);
 
     let compilation_start = std::time::Instant::now();
 
@@ -44,6 +44,10 @@ pub const RocDec = extern struct {
         return ret;
     }
 
+    pub fn toF64(dec: RocDec) f64 {
+        return @intToFloat(f64, dec.num) / comptime @intToFloat(f64, one_point_zero_i128);
+    }
+
     pub fn fromStr(roc_str: RocStr) ?RocDec {
         if (roc_str.isEmpty()) {
             return null;
@@ -742,6 +746,11 @@ test "fromF64 overflow" {
     try expectEqual(dec, null);
 }
 
+test "toF64" {
+    var dec = RocDec{ .num = 25500000000000000000 };
+    try expectEqual(@as(f64, 25.5), dec.toF64());
+}
+
 test "fromStr: empty" {
     var roc_str = RocStr.init("", 0);
     var dec = RocDec.fromStr(roc_str);
@@ -1108,6 +1117,33 @@ pub fn fromF64C(arg: f64) callconv(.C) i128 {
     return if (@call(.{ .modifier = always_inline }, RocDec.fromF64, .{arg})) |dec| dec.num else @panic("TODO runtime exception failing convert f64 to RocDec");
 }
 
+pub fn exportFromInt(comptime T: type, comptime name: []const u8) void {
+    comptime var f = struct {
+        fn func(self: T) callconv(.C) i128 {
+            // the only integers that do not fit in an i128
+            if (T == u128) {
+                if (self > math.maxInt(i128)) {
+                    roc_panic("Decimal conversion from integer failed!", 0);
+                    unreachable;
+                }
+            }
+
+            var result: i128 = undefined;
+            if (@mulWithOverflow(i128, @intCast(i128, self), RocDec.one_point_zero_i128, &result)) {
+                roc_panic("Decimal conversion from integer failed!", 0);
+                unreachable;
+            }
+
+            return result;
+        }
+    }.func;
+    @export(f, .{ .name = name ++ @typeName(T), .linkage = .Strong });
+}
+
+pub fn toF64C(arg: RocDec) callconv(.C) f64 {
+    return @call(.{ .modifier = always_inline }, RocDec.toF64, .{arg});
+}
+
 pub fn eqC(arg1: RocDec, arg2: RocDec) callconv(.C) bool {
     return @call(.{ .modifier = always_inline }, RocDec.eq, .{ arg1, arg2 });
 }
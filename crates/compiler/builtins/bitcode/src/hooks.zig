@@ -0,0 +1,43 @@
+//! Hooks for instrumented builds, e.g. `roc build --profile-alloc`.
+//!
+//! The builtins call these on every allocation and refcount change. Here they do nothing, and the
+//! LLVM backend inlines them away. For an instrumented program, it replaces their bodies with calls
+//! into the instrumentation instead, see `gen_llvm/src/llvm/instrument.rs`. They are exported with
+//! weak linkage, so that zig does not optimize the calls away before that.
+const std = @import("std");
+
+pub fn onAlloc(ptr: ?[*]u8, size: usize) callconv(.C) void {
+    _ = ptr;
+    _ = size;
+}
+
+pub fn onDealloc(ptr: [*]u8) callconv(.C) void {
+    _ = ptr;
+}
+
+/// `refcount` is the value before the increment
+pub fn onIncref(refcount: isize, amount: isize) callconv(.C) void {
+    _ = refcount;
+    _ = amount;
+}
+
+/// `refcount` is the value before the decrement
+pub fn onDecref(refcount: isize) callconv(.C) void {
+    _ = refcount;
+}
+
+/// Instrumentation state is global, and the builtins may be built with atomic refcounts, so it is
+/// only accessed while holding one of these.
+pub const SpinLock = struct {
+    locked: bool = false,
+
+    pub fn lock(self: *SpinLock) void {
+        while (@atomicRmw(bool, &self.locked, .Xchg, true, .Acquire)) {
+            std.atomic.spinLoopHint();
+        }
+    }
+
+    pub fn unlock(self: *SpinLock) void {
+        @atomicStore(bool, &self.locked, false, .Release);
+    }
+};
@@ -4,6 +4,9 @@ const math = std.math;
 const utils = @import("utils.zig");
 const expect = @import("expect.zig");
 const panic_utils = @import("panic.zig");
+const profile = @import("profile.zig");
+const hooks = @import("hooks.zig");
+const sanitize = @import("sanitize.zig");
 
 const ROC_BUILTINS = "roc_builtins";
 const NUM = "num";
@@ -16,6 +19,7 @@ comptime {
     exportDecFn(dec.fromStr, "from_str");
     exportDecFn(dec.toStr, "to_str");
     exportDecFn(dec.fromF64C, "from_f64");
+    exportDecFn(dec.toF64C, "to_f64");
     exportDecFn(dec.eqC, "eq");
     exportDecFn(dec.neqC, "neq");
     exportDecFn(dec.negateC, "negate");
@@ -32,6 +36,10 @@ comptime {
     exportDecFn(dec.mulC, "mul_with_overflow");
     exportDecFn(dec.mulOrPanicC, "mul_or_panic");
     exportDecFn(dec.mulSaturatedC, "mul_saturated");
+
+    inline for (INTEGERS) |T| {
+        dec.exportFromInt(T, ROC_BUILTINS ++ ".dec.from_int.");
+    }
 }
 
 // List Module
@@ -188,9 +196,24 @@ comptime {
     exportUtilsFn(utils.isUnique, "is_unique");
     exportUtilsFn(utils.decrefCheckNullC, "decref_check_null");
     exportUtilsFn(utils.allocateWithRefcountC, "allocate_with_refcount");
+    exportUtilsFn(profile.setSite, "profile_set_site");
+    exportUtilsFn(profile.recordUpdate, "profile_record_update");
+    exportUtilsFn(profile.recordAlloc, "profile_record_alloc");
+    exportUtilsFn(profile.recordDealloc, "profile_record_dealloc");
+    exportUtilsFn(profile.recordIncrement, "profile_record_increment");
+    exportUtilsFn(profile.recordDecrement, "profile_record_decrement");
+    exportUtilsFn(sanitize.deallocQuarantined, "dealloc_quarantined");
+    exportUtilsFn(sanitize.checkIncref, "sanitize_check_incref");
+    exportUtilsFn(sanitize.checkDecref, "sanitize_check_decref");
 
     @export(panic_utils.panic, .{ .name = "roc_builtins.utils." ++ "panic", .linkage = .Weak });
 
+    // weak, so that their calls survive until the LLVM backend has decided what to do with them
+    @export(hooks.onAlloc, .{ .name = "roc_builtins.utils." ++ "hook_alloc", .linkage = .Weak });
+    @export(hooks.onDealloc, .{ .name = "roc_builtins.utils." ++ "hook_dealloc", .linkage = .Weak });
+    @export(hooks.onIncref, .{ .name = "roc_builtins.utils." ++ "hook_incref", .linkage = .Weak });
+    @export(hooks.onDecref, .{ .name = "roc_builtins.utils." ++ "hook_decref", .linkage = .Weak });
+
     if (builtin.target.cpu.arch != .wasm32) {
         exportUtilsFn(expect.expectFailedStartSharedBuffer, "expect_failed_start_shared_buffer");
         exportUtilsFn(expect.expectFailedStartSharedFile, "expect_failed_start_shared_file");
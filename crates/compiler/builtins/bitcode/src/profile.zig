@@ -0,0 +1,163 @@
+//! Allocation and refcount profiling, for programs built with `roc build --profile-alloc`.
+//!
+//! Before anything that may allocate or change a refcount, the generated code tells us which
+//! site of the Roc program it is executing: a proc, and the value it is working on. Allocations,
+//! refcount changes and the outcome of in-place update checks are attributed to the current
+//! site, and a summary is written to stderr when the program exits.
+const std = @import("std");
+const builtin = @import("builtin");
+const SpinLock = @import("hooks.zig").SpinLock;
+
+// we need libc to write the summary at exit
+const supported = builtin.os.tag == .macos or builtin.os.tag == .linux;
+
+/// A site in the Roc program. The generated code defines one global per site, with everything
+/// but the name zeroed.
+pub const Site = extern struct {
+    name: [*:0]const u8,
+    next: ?*Site,
+    registered: u64,
+    allocations: u64,
+    bytes: u64,
+    increments: u64,
+    decrements: u64,
+    in_place_updates: u64,
+    copying_updates: u64,
+};
+
+var lock = SpinLock{};
+var enabled: bool = false;
+var current_site: ?*Site = null;
+var first_site: ?*Site = null;
+
+var live_bytes: usize = 0;
+var peak_live_bytes: usize = 0;
+var allocation_sizes: std.AutoHashMapUnmanaged(usize, usize) = .{};
+
+extern fn atexit(function: fn () callconv(.C) void) c_int;
+extern fn write(fd: c_int, buf: [*]const u8, count: usize) isize;
+
+pub fn setSite(site: *Site) callconv(.C) void {
+    if (supported) {
+        lock.lock();
+        defer lock.unlock();
+
+        if (site.registered == 0) {
+            site.registered = 1;
+            site.next = first_site;
+            first_site = site;
+        }
+
+        if (!enabled) {
+            enabled = true;
+            _ = atexit(report);
+        }
+
+        current_site = site;
+    }
+}
+
+/// The outcome of a `reset`: whether the allocation could be updated in place
+pub fn recordUpdate(in_place: bool) callconv(.C) void {
+    lock.lock();
+    defer lock.unlock();
+
+    const site = current_site orelse return;
+
+    if (in_place) {
+        site.in_place_updates += 1;
+    } else {
+        site.copying_updates += 1;
+    }
+}
+
+// The functions below are called through the hooks in `hooks.zig`, so only by profiled programs.
+
+pub fn recordAlloc(allocation: ?[*]u8, size: usize) callconv(.C) void {
+    const ptr = allocation orelse return;
+
+    lock.lock();
+    defer lock.unlock();
+
+    if (current_site) |site| {
+        site.allocations += 1;
+        site.bytes += size;
+    }
+
+    allocation_sizes.put(std.heap.page_allocator, @ptrToInt(ptr), size) catch return;
+    live_bytes += size;
+    peak_live_bytes = std.math.max(peak_live_bytes, live_bytes);
+}
+
+pub fn recordDealloc(ptr: [*]u8) callconv(.C) void {
+    lock.lock();
+    defer lock.unlock();
+
+    if (allocation_sizes.fetchRemove(@ptrToInt(ptr))) |entry| {
+        live_bytes -= entry.value;
+    }
+}
+
+pub fn recordIncrement(refcount: isize, amount: isize) callconv(.C) void {
+    _ = refcount;
+
+    lock.lock();
+    defer lock.unlock();
+
+    if (current_site) |site| {
+        site.increments += @intCast(u64, amount);
+    }
+}
+
+pub fn recordDecrement(refcount: isize) callconv(.C) void {
+    _ = refcount;
+
+    lock.lock();
+    defer lock.unlock();
+
+    if (current_site) |site| {
+        site.decrements += 1;
+    }
+}
+
+fn report() callconv(.C) void {
+    var sites = std.ArrayList(*Site).init(std.heap.page_allocator);
+    defer sites.deinit();
+
+    var it = first_site;
+    while (it) |site| : (it = site.next) {
+        const counts = [_]u64{ site.allocations, site.increments, site.decrements, site.in_place_updates, site.copying_updates };
+        if (std.mem.max(u64, &counts) > 0) {
+            sites.append(site) catch return;
+        }
+    }
+
+    std.sort.sort(*Site, sites.items, {}, moreBytes);
+
+    print("\nRoc allocation profile\n\n", .{});
+    print("peak live memory: {d} bytes\n", .{peak_live_bytes});
+    print("live at exit:     {d} bytes\n\n", .{live_bytes});
+    print("{s:>12} {s:>12} {s:>12} {s:>12} {s:>9} {s:>9}  {s}\n", .{ "allocations", "bytes", "increments", "decrements", "in-place", "copying", "site" });
+
+    for (sites.items) |site| {
+        print("{d:>12} {d:>12} {d:>12} {d:>12} {d:>9} {d:>9}  {s}\n", .{
+            site.allocations,
+            site.bytes,
+            site.increments,
+            site.decrements,
+            site.in_place_updates,
+            site.copying_updates,
+            std.mem.span(site.name),
+        });
+    }
+}
+
+fn moreBytes(_: void, a: *Site, b: *Site) bool {
+    return a.bytes > b.bytes;
+}
+
+fn print(comptime format: []const u8, args: anytype) void {
+    var buf: [1024]u8 = undefined;
+    const line = std.fmt.bufPrint(&buf, format, args) catch return;
+    _ = write(2, line.ptr, line.len);
+}
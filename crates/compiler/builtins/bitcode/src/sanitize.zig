@@ -0,0 +1,91 @@
+//! Refcount checks, for programs built with `roc build --sanitize refcount`.
+//!
+//! The generated code wraps `roc_dealloc`, so that freed allocations are not handed back to the
+//! platform right away. Their refcount is overwritten with a poison value, and they are kept in a
+//! quarantine for a while. Changing the refcount of a quarantined allocation (e.g. because host
+//! code decremented a value one time too many) then panics, instead of corrupting whatever the
+//! allocator would have put in that memory. Roc checks every refcount it changes, and writes
+//! from elsewhere are caught when the allocation leaves the quarantine.
+const std = @import("std");
+const panic_help = @import("panic.zig").panic_help;
+const SpinLock = @import("hooks.zig").SpinLock;
+
+/// Live refcounts are negative, and 0 marks a value that lives for the whole program, so a
+/// positive refcount means that the allocation was freed.
+pub const REFCOUNT_POISON_ISIZE: isize = 0x0BAD_BEEF;
+
+/// How many freed allocations are kept around before the oldest is really deallocated
+const QUARANTINE_CAPACITY = 1024;
+
+const Quarantined = struct {
+    allocation: *anyopaque,
+    alignment: u32,
+};
+
+pub const Dealloc = fn (*anyopaque, u32) callconv(.C) void;
+
+// the builtins may be built with atomic refcounts, so values can be freed from several threads
+var lock = SpinLock{};
+var quarantine: [QUARANTINE_CAPACITY]Quarantined = undefined;
+var quarantine_len: usize = 0;
+var quarantine_next: usize = 0;
+
+/// Called by the generated `roc_dealloc` wrapper, with the platform's `roc_dealloc`
+pub fn deallocQuarantined(allocation: *anyopaque, alignment: u32, platform_dealloc: Dealloc) callconv(.C) void {
+    refcountPtr(allocation, alignment).* = REFCOUNT_POISON_ISIZE;
+
+    const evicted = evict: {
+        lock.lock();
+        defer lock.unlock();
+
+        var oldest: ?Quarantined = null;
+        if (quarantine_len == QUARANTINE_CAPACITY) {
+            oldest = quarantine[quarantine_next];
+        } else {
+            quarantine_len += 1;
+        }
+
+        quarantine[quarantine_next] = .{ .allocation = allocation, .alignment = alignment };
+        quarantine_next = (quarantine_next + 1) % QUARANTINE_CAPACITY;
+
+        break :evict oldest;
+    };
+
+    if (evicted) |oldest| {
+        // only a write through a dangling pointer changes the poison, e.g. host code that
+        // decremented the refcount of a value that Roc had already freed
+        if (refcountPtr(oldest.allocation, oldest.alignment).* != REFCOUNT_POISON_ISIZE) {
+            panic_help("A value was written to after Roc freed it. This usually means that host code kept using a value that it had passed to Roc.", 0);
+        }
+
+        platform_dealloc(oldest.allocation, oldest.alignment);
+    }
+}
+
+// The checks below are called through the hooks in `hooks.zig`, so only by sanitized programs.
+
+/// `refcount` is the value before the increment
+pub fn checkIncref(refcount: isize, amount: isize) callconv(.C) void {
+    _ = amount;
+    checkRefcount(refcount);
+}
+
+/// `refcount` is the value before the decrement
+pub fn checkDecref(refcount: isize) callconv(.C) void {
+    checkRefcount(refcount);
+}
+
+/// Panic if `refcount` was read from an allocation that has been freed
+fn checkRefcount(refcount: isize) void {
+    if (refcount > 0) {
+        panic_help("Roc tried to change the refcount of a value that was already freed. This usually means that the platform and the app disagree about the layout of a value, or that host code decremented a refcount one time too many.", 0);
+    }
+}
+
+/// The refcount is stored right before the data, see `allocateWithRefcount`
+fn refcountPtr(allocation: *anyopaque, alignment: u32) *isize {
+    const extra_bytes = std.math.max(alignment, @sizeOf(usize));
+    const bytes = @ptrCast([*]u8, allocation);
+
+    return @ptrCast(*isize, @alignCast(@alignOf(isize), bytes + (extra_bytes - @sizeOf(usize))));
+}
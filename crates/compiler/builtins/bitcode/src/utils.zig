@@ -1,4 +1,5 @@
 const std = @import("std");
+const hooks = @import("hooks.zig");
 const always_inline = std.builtin.CallOptions.Modifier.always_inline;
 const Monotonic = std.builtin.AtomicOrder.Monotonic;
 
@@ -90,14 +91,20 @@ fn testing_roc_memcpy(dest: *anyopaque, src: *anyopaque, bytes: usize) callconv(
 }
 
 pub fn alloc(size: usize, alignment: u32) ?[*]u8 {
-    return @ptrCast(?[*]u8, roc_alloc(size, alignment));
+    const ptr = @ptrCast(?[*]u8, roc_alloc(size, alignment));
+    hooks.onAlloc(ptr, size);
+    return ptr;
 }
 
 pub fn realloc(c_ptr: [*]u8, new_size: usize, old_size: usize, alignment: u32) [*]u8 {
-    return @ptrCast([*]u8, roc_realloc(c_ptr, new_size, old_size, alignment));
+    hooks.onDealloc(c_ptr);
+    const ptr = @ptrCast([*]u8, roc_realloc(c_ptr, new_size, old_size, alignment));
+    hooks.onAlloc(ptr, new_size);
+    return ptr;
 }
 
 pub fn dealloc(c_ptr: [*]u8, alignment: u32) void {
+    hooks.onDealloc(c_ptr);
     return roc_dealloc(c_ptr, alignment);
 }
 
@@ -152,6 +159,8 @@ pub fn increfRcPtrC(ptr_to_refcount: *isize, amount: isize) callconv(.C) void {
     if (RC_TYPE == Refcount.none) return;
     // Ensure that the refcount is not whole program lifetime.
     if (ptr_to_refcount.* != REFCOUNT_MAX_ISIZE) {
+        hooks.onIncref(ptr_to_refcount.*, amount);
+
         // Note: we assume that a refcount will never overflow.
         // As such, we do not need to cap incrementing.
         switch (RC_TYPE) {
@@ -244,6 +253,8 @@ inline fn decref_ptr_to_refcount(
     // Ensure that the refcount is not whole program lifetime.
     const refcount: isize = refcount_ptr[0];
     if (refcount != REFCOUNT_MAX_ISIZE) {
+        hooks.onDecref(refcount);
+
         switch (RC_TYPE) {
             Refcount.normal => {
                 refcount_ptr[0] = refcount -% 1;
@@ -368,6 +368,8 @@ pub const LIST_RELEASE_EXCESS_CAPACITY: &str = "roc_builtins.list.release_excess
 pub const DEC_FROM_STR: &str = "roc_builtins.dec.from_str";
 pub const DEC_TO_STR: &str = "roc_builtins.dec.to_str";
 pub const DEC_FROM_F64: &str = "roc_builtins.dec.from_f64";
+pub const DEC_FROM_INT: IntrinsicName = int_intrinsic!("roc_builtins.dec.from_int");
+pub const DEC_TO_F64: &str = "roc_builtins.dec.to_f64";
 pub const DEC_EQ: &str = "roc_builtins.dec.eq";
 pub const DEC_NEQ: &str = "roc_builtins.dec.neq";
 pub const DEC_NEGATE: &str = "roc_builtins.dec.negate";
@@ -390,6 +392,19 @@ pub const UTILS_INCREF_DATA_PTR: &str = "roc_builtins.utils.incref_data_ptr";
 pub const UTILS_DECREF_DATA_PTR: &str = "roc_builtins.utils.decref_data_ptr";
 pub const UTILS_IS_UNIQUE: &str = "roc_builtins.utils.is_unique";
 pub const UTILS_DECREF_CHECK_NULL: &str = "roc_builtins.utils.decref_check_null";
+pub const UTILS_PROFILE_SET_SITE: &str = "roc_builtins.utils.profile_set_site";
+pub const UTILS_PROFILE_RECORD_UPDATE: &str = "roc_builtins.utils.profile_record_update";
+pub const UTILS_PROFILE_RECORD_ALLOC: &str = "roc_builtins.utils.profile_record_alloc";
+pub const UTILS_PROFILE_RECORD_DEALLOC: &str = "roc_builtins.utils.profile_record_dealloc";
+pub const UTILS_PROFILE_RECORD_INCREMENT: &str = "roc_builtins.utils.profile_record_increment";
+pub const UTILS_PROFILE_RECORD_DECREMENT: &str = "roc_builtins.utils.profile_record_decrement";
+pub const UTILS_HOOK_ALLOC: &str = "roc_builtins.utils.hook_alloc";
+pub const UTILS_HOOK_DEALLOC: &str = "roc_builtins.utils.hook_dealloc";
+pub const UTILS_HOOK_INCREF: &str = "roc_builtins.utils.hook_incref";
+pub const UTILS_HOOK_DECREF: &str = "roc_builtins.utils.hook_decref";
+pub const UTILS_DEALLOC_QUARANTINED: &str = "roc_builtins.utils.dealloc_quarantined";
+pub const UTILS_SANITIZE_CHECK_INCREF: &str = "roc_builtins.utils.sanitize_check_incref";
+pub const UTILS_SANITIZE_CHECK_DECREF: &str = "roc_builtins.utils.sanitize_check_decref";
 
 pub const UTILS_EXPECT_FAILED_START_SHARED_BUFFER: &str =
     "roc_builtins.utils.expect_failed_start_shared_buffer";
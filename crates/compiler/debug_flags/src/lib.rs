@@ -118,12 +118,16 @@ flags! {
 
     // ===Mono===
 
-    /// Type-checks the mono IR after specialization.
+    /// Type-checks the mono IR after specialization, and checks that its reference counting
+    /// operations are balanced after drop specialization.
     ROC_CHECK_MONO_IR
 
     /// Writes a pretty-printed mono IR to stderr after function specialization.
     ROC_PRINT_IR_AFTER_SPECIALIZATION
 
+    /// Writes a pretty-printed mono IR to stderr after inlining small and single-use procs.
+    ROC_PRINT_IR_AFTER_INLINING
+
     /// Writes a pretty-printed mono IR to stderr after insertion of reset/reuse
     /// instructions.
     ROC_PRINT_IR_AFTER_RESET_REUSE
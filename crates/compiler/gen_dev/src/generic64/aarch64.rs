@@ -1,11 +1,15 @@
 use crate::generic64::{storage::StorageManager, Assembler, CallConv, RegTrait};
-use crate::Relocation;
+use crate::{
+    single_register_floats, single_register_int_builtins, single_register_integers,
+    single_register_layouts, ProcFrame, Relocation, SavedReg,
+};
 use bumpalo::collections::Vec;
+use object::{BinaryFormat, RelocationKind};
 use packed_struct::prelude::*;
-use roc_builtins::bitcode::FloatWidth;
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
 use roc_error_macros::internal_error;
 use roc_module::symbol::Symbol;
-use roc_mono::layout::{InLayout, STLayoutInterner};
+use roc_mono::layout::{Builtin, InLayout, Layout, LayoutInterner, STLayoutInterner, UnionLayout};
 
 use super::{CompareOperation, RegisterWidth};
 
@@ -193,6 +197,40 @@ impl AArch64FloatReg {
     }
 }
 
+/// An operand of an instruction that can work with either general or float registers.
+#[derive(Copy, Clone)]
+enum AArch64Reg {
+    General(AArch64GeneralReg),
+    Float(AArch64FloatReg),
+}
+
+impl AArch64Reg {
+    #[inline(always)]
+    fn id(&self) -> u8 {
+        match self {
+            AArch64Reg::General(reg) => reg.id(),
+            AArch64Reg::Float(reg) => reg.id(),
+        }
+    }
+
+    #[inline(always)]
+    fn is_float(&self) -> bool {
+        matches!(self, AArch64Reg::Float(_))
+    }
+}
+
+impl From<AArch64GeneralReg> for AArch64Reg {
+    fn from(reg: AArch64GeneralReg) -> Self {
+        AArch64Reg::General(reg)
+    }
+}
+
+impl From<AArch64FloatReg> for AArch64Reg {
+    fn from(reg: AArch64FloatReg) -> Self {
+        AArch64Reg::Float(reg)
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct AArch64Assembler {}
 
@@ -227,6 +265,8 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         // Don't use platform register: AArch64GeneralReg::PR,
         // Don't use link register: AArch64GeneralReg::LR,
         // Don't use zero register/stack pointer: AArch64GeneralReg::ZRSP,
+        // Don't use intra-procedure-call scratch registers, the assembler uses them as temporaries:
+        // AArch64GeneralReg::IP0, AArch64GeneralReg::IP1,
 
         // Use callee saved regs last.
         AArch64GeneralReg::X19,
@@ -255,12 +295,57 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         AArch64GeneralReg::X13,
         AArch64GeneralReg::X14,
         AArch64GeneralReg::X15,
-        AArch64GeneralReg::IP0,
-        AArch64GeneralReg::IP1,
     ];
-    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = &[];
+    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = &[
+        AArch64FloatReg::V0,
+        AArch64FloatReg::V1,
+        AArch64FloatReg::V2,
+        AArch64FloatReg::V3,
+        AArch64FloatReg::V4,
+        AArch64FloatReg::V5,
+        AArch64FloatReg::V6,
+        AArch64FloatReg::V7,
+    ];
     const FLOAT_RETURN_REGS: &'static [AArch64FloatReg] = Self::FLOAT_PARAM_REGS;
-    const FLOAT_DEFAULT_FREE_REGS: &'static [AArch64FloatReg] = &[];
+    const FLOAT_DEFAULT_FREE_REGS: &'static [AArch64FloatReg] = &[
+        // The regs we want to use first should be at the end of this vec.
+        // We will use pop to get which reg to use next
+
+        // Use callee saved regs last.
+        AArch64FloatReg::V8,
+        AArch64FloatReg::V9,
+        AArch64FloatReg::V10,
+        AArch64FloatReg::V11,
+        AArch64FloatReg::V12,
+        AArch64FloatReg::V13,
+        AArch64FloatReg::V14,
+        AArch64FloatReg::V15,
+        // Use caller saved regs first.
+        AArch64FloatReg::V0,
+        AArch64FloatReg::V1,
+        AArch64FloatReg::V2,
+        AArch64FloatReg::V3,
+        AArch64FloatReg::V4,
+        AArch64FloatReg::V5,
+        AArch64FloatReg::V6,
+        AArch64FloatReg::V7,
+        AArch64FloatReg::V16,
+        AArch64FloatReg::V17,
+        AArch64FloatReg::V18,
+        AArch64FloatReg::V19,
+        AArch64FloatReg::V20,
+        AArch64FloatReg::V21,
+        AArch64FloatReg::V22,
+        AArch64FloatReg::V23,
+        AArch64FloatReg::V24,
+        AArch64FloatReg::V25,
+        AArch64FloatReg::V26,
+        AArch64FloatReg::V27,
+        AArch64FloatReg::V28,
+        AArch64FloatReg::V29,
+        AArch64FloatReg::V30,
+        AArch64FloatReg::V31,
+    ];
 
     const SHADOW_SPACE_SIZE: u8 = 0;
 
@@ -281,21 +366,57 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         )
     }
     #[inline(always)]
-    fn float_callee_saved(_reg: &AArch64FloatReg) -> bool {
-        todo!("AArch64 FloatRegs");
+    fn float_callee_saved(reg: &AArch64FloatReg) -> bool {
+        // Only the bottom 64 bits of these are callee saved, which is all we ever use.
+        matches!(
+            reg,
+            AArch64FloatReg::V8
+                | AArch64FloatReg::V9
+                | AArch64FloatReg::V10
+                | AArch64FloatReg::V11
+                | AArch64FloatReg::V12
+                | AArch64FloatReg::V13
+                | AArch64FloatReg::V14
+                | AArch64FloatReg::V15
+        )
     }
 
     #[inline(always)]
-    fn setup_stack(
-        buf: &mut Vec<'_, u8>,
+    fn setup_stack<'a>(
+        buf: &mut Vec<'a, u8>,
         saved_general_regs: &[AArch64GeneralReg],
         saved_float_regs: &[AArch64FloatReg],
         requested_stack_size: i32,
         fn_call_stack_size: i32,
-    ) -> i32 {
+    ) -> ProcFrame<'a> {
+        let start = buf.len();
+        // Save the frame pointer and link register, then point the frame pointer at them.
+        // Like on x86_64, this puts the arguments passed on the stack at FP + 16.
+        stp_reg64_reg64_reg64_imm7(
+            buf,
+            AArch64GeneralReg::FP,
+            AArch64GeneralReg::LR,
+            AArch64GeneralReg::ZRSP,
+            -2,
+        );
+        let frame_pointer_saved = (buf.len() - start) as u32;
+        // `MOV FP, SP` is an alias of `ADD FP, SP, #0`.
+        add_reg64_reg64_imm12(buf, AArch64GeneralReg::FP, AArch64GeneralReg::ZRSP, 0);
+        let frame_pointer_set = (buf.len() - start) as u32;
+
+        let mut frame = ProcFrame {
+            frame_pointer_saved,
+            frame_pointer_set,
+            stack_allocated: frame_pointer_set,
+            stack_size: 0,
+            saved_regs: bumpalo::vec![in buf.bump()],
+            prologue_size: frame_pointer_set,
+            frame_pointer_restored: 0,
+        };
+
         // Full size is upcast to i64 to make sure we don't overflow here.
         let full_stack_size = match requested_stack_size
-            .checked_add(8 * (saved_general_regs.len() + saved_float_regs.len()) as i32 + 8) // The extra 8 is space to store the frame pointer.
+            .checked_add(8 * (saved_general_regs.len() + saved_float_regs.len()) as i32)
             .and_then(|size| size.checked_add(fn_call_stack_size))
         {
             Some(size) => size,
@@ -313,38 +434,40 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         };
         if let Some(aligned_stack_size) = full_stack_size.checked_add(offset as i32) {
             if aligned_stack_size > 0 {
-                AArch64Assembler::mov_reg64_reg64(
-                    buf,
-                    AArch64GeneralReg::FP,
-                    AArch64GeneralReg::ZRSP,
-                );
                 AArch64Assembler::sub_reg64_reg64_imm32(
                     buf,
                     AArch64GeneralReg::ZRSP,
                     AArch64GeneralReg::ZRSP,
                     aligned_stack_size,
                 );
+                frame.stack_allocated = (buf.len() - start) as u32;
+                frame.stack_size = aligned_stack_size as u32;
 
-                // All the following stores could be optimized by using `STP` to store pairs.
-                let mut offset = aligned_stack_size;
-                offset -= 8;
-                AArch64Assembler::mov_stack32_reg64(buf, offset, AArch64GeneralReg::LR);
-                offset -= 8;
-                AArch64Assembler::mov_stack32_reg64(buf, offset, AArch64GeneralReg::FP);
-
-                offset = aligned_stack_size - fn_call_stack_size;
+                // Put values at the top of the stack to avoid conflicts with previously saved variables.
+                let mut offset = aligned_stack_size - fn_call_stack_size;
                 for reg in saved_general_regs {
+                    AArch64Assembler::mov_base32_reg64(buf, -offset, *reg);
+                    frame.saved_regs.push(SavedReg {
+                        reg: reg.value(),
+                        is_float: false,
+                        base_offset: -offset,
+                        code_offset: (buf.len() - start) as u32,
+                    });
                     offset -= 8;
-                    AArch64Assembler::mov_base32_reg64(buf, offset, *reg);
                 }
                 for reg in saved_float_regs {
+                    AArch64Assembler::mov_base32_freg64(buf, -offset, *reg);
+                    frame.saved_regs.push(SavedReg {
+                        reg: reg.value(),
+                        is_float: true,
+                        base_offset: -offset,
+                        code_offset: (buf.len() - start) as u32,
+                    });
                     offset -= 8;
-                    AArch64Assembler::mov_base32_freg64(buf, offset, *reg);
                 }
-                aligned_stack_size
-            } else {
-                0
+                frame.prologue_size = (buf.len() - start) as u32;
             }
+            frame
         } else {
             internal_error!("Ran out of stack space");
         }
@@ -359,21 +482,14 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
         fn_call_stack_size: i32,
     ) {
         if aligned_stack_size > 0 {
-            // All the following stores could be optimized by using `STP` to store pairs.
-            let mut offset = aligned_stack_size;
-            offset -= 8;
-            AArch64Assembler::mov_reg64_stack32(buf, AArch64GeneralReg::LR, offset);
-            offset -= 8;
-            AArch64Assembler::mov_reg64_stack32(buf, AArch64GeneralReg::FP, offset);
-
-            offset = aligned_stack_size - fn_call_stack_size;
+            let mut offset = aligned_stack_size - fn_call_stack_size;
             for reg in saved_general_regs {
+                AArch64Assembler::mov_reg64_base32(buf, *reg, -offset);
                 offset -= 8;
-                AArch64Assembler::mov_reg64_base32(buf, *reg, offset);
             }
             for reg in saved_float_regs {
+                AArch64Assembler::mov_freg64_base32(buf, *reg, -offset);
                 offset -= 8;
-                AArch64Assembler::mov_freg64_base32(buf, *reg, offset);
             }
             AArch64Assembler::add_reg64_reg64_imm32(
                 buf,
@@ -382,12 +498,19 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
                 aligned_stack_size,
             );
         }
+        ldp_reg64_reg64_reg64_imm7(
+            buf,
+            AArch64GeneralReg::FP,
+            AArch64GeneralReg::LR,
+            AArch64GeneralReg::ZRSP,
+            2,
+        );
     }
 
     #[inline(always)]
     fn load_args<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             AArch64GeneralReg,
@@ -395,17 +518,32 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
             AArch64Assembler,
             AArch64Call,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _args: &'a [(InLayout<'a>, Symbol)],
-        _ret_layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        args: &'a [(InLayout<'a>, Symbol)],
+        ret_layout: &InLayout<'a>,
     ) {
-        todo!("Loading args for AArch64");
+        if Self::returns_via_arg_pointer(layout_interner, ret_layout) {
+            // The address to write the result to is passed in the indirect result register,
+            // so unlike x86_64 it does not take up a parameter register.
+            storage_manager.ret_pointer_arg(AArch64GeneralReg::XR);
+        }
+
+        let mut state = AArch64CallLoadArgs {
+            general_i: 0,
+            float_i: 0,
+            // 16 is the size of the saved frame pointer and link register.
+            argument_offset: 16,
+        };
+
+        for (in_layout, sym) in args.iter() {
+            state.load_arg(buf, storage_manager, layout_interner, *sym, *in_layout);
+        }
     }
 
     #[inline(always)]
     fn store_args<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             AArch64GeneralReg,
@@ -413,18 +551,41 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
             AArch64Assembler,
             AArch64Call,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _dst: &Symbol,
-        _args: &[Symbol],
-        _arg_layouts: &[InLayout<'a>],
-        _ret_layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        dst: &Symbol,
+        args: &[Symbol],
+        arg_layouts: &[InLayout<'a>],
+        ret_layout: &InLayout<'a>,
     ) {
-        todo!("Storing args for AArch64");
+        if Self::returns_via_arg_pointer(layout_interner, ret_layout) {
+            // Save space on the stack for the result we will be return.
+            let base_offset =
+                storage_manager.claim_stack_area(dst, layout_interner.stack_size(*ret_layout));
+            // Set the indirect result register to the address base + offset.
+            AArch64Assembler::add_reg64_reg64_imm32(
+                buf,
+                AArch64GeneralReg::XR,
+                AArch64GeneralReg::FP,
+                base_offset,
+            );
+        }
+
+        let mut state = AArch64CallStoreArgs {
+            general_i: 0,
+            float_i: 0,
+            tmp_stack_offset: 0,
+        };
+
+        for (sym, in_layout) in args.iter().zip(arg_layouts.iter()) {
+            state.store_arg(buf, storage_manager, layout_interner, *sym, *in_layout);
+        }
+
+        storage_manager.update_fn_call_stack_size(state.tmp_stack_offset as u32);
     }
 
     fn return_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             AArch64GeneralReg,
@@ -432,16 +593,35 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
             AArch64Assembler,
             AArch64Call,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
     ) {
-        todo!("Returning complex symbols for AArch64");
+        match *layout {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            x if layout_interner.stack_size(x) == 0 => {}
+            x if !Self::returns_via_arg_pointer(layout_interner, &x) => {
+                let (base_offset, size) = storage_manager.stack_offset_and_size(sym);
+                for i in 0..(size as usize + 7) / 8 {
+                    AArch64Assembler::mov_reg64_base32(
+                        buf,
+                        Self::GENERAL_RETURN_REGS[i],
+                        base_offset + 8 * i as i32,
+                    );
+                }
+            }
+            _ => {
+                // This is a large type returned via the arg pointer.
+                storage_manager.copy_symbol_to_arg_pointer(buf, sym, layout);
+            }
+        }
     }
 
     fn load_returned_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             AArch64GeneralReg,
@@ -449,11 +629,383 @@ impl CallConv<AArch64GeneralReg, AArch64FloatReg, AArch64Assembler> for AArch64C
             AArch64Assembler,
             AArch64Call,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
+    ) {
+        match *layout {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            x if layout_interner.stack_size(x) == 0 => {
+                storage_manager.no_data(sym);
+            }
+            x if !Self::returns_via_arg_pointer(layout_interner, &x) => {
+                let size = layout_interner.stack_size(*layout);
+                let offset = storage_manager.claim_stack_area(sym, size);
+                for i in 0..(size as usize + 7) / 8 {
+                    AArch64Assembler::mov_base32_reg64(
+                        buf,
+                        offset + 8 * i as i32,
+                        Self::GENERAL_RETURN_REGS[i],
+                    );
+                }
+            }
+            _ => {
+                // This should have been recieved via an arg pointer.
+                // That means the value is already loaded onto the stack area we allocated before the call.
+                // Nothing to do.
+            }
+        }
+    }
+}
+
+impl AArch64Call {
+    fn returns_via_arg_pointer<'a>(
+        interner: &STLayoutInterner<'a>,
+        ret_layout: &InLayout<'a>,
+    ) -> bool {
+        // Composites of up to 16 bytes are returned in X0 and X1, anything larger goes through XR.
+        interner.stack_size(*ret_layout) > 16
+    }
+}
+
+type AArch64StorageManager<'a, 'r> =
+    StorageManager<'a, 'r, AArch64GeneralReg, AArch64FloatReg, AArch64Assembler, AArch64Call>;
+
+struct AArch64CallStoreArgs {
+    general_i: usize,
+    float_i: usize,
+    tmp_stack_offset: i32,
+}
+
+impl AArch64CallStoreArgs {
+    const GENERAL_PARAM_REGS: &'static [AArch64GeneralReg] = AArch64Call::GENERAL_PARAM_REGS;
+    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = AArch64Call::FLOAT_PARAM_REGS;
+
+    fn store_arg<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: Symbol,
+        in_layout: InLayout<'a>,
+    ) {
+        let stack_size = layout_interner.stack_size(in_layout);
+        match in_layout {
+            single_register_integers!() => self.store_arg_general(buf, storage_manager, sym),
+            single_register_floats!() => self.store_arg_float(buf, storage_manager, sym),
+            _ if stack_size == 0 => {}
+            _ if stack_size > 16 => self.store_arg_by_reference(buf, storage_manager, sym),
+            other => match layout_interner.get(other) {
+                Layout::Boxed(_) => {
+                    // treat boxed like a 64-bit integer
+                    self.store_arg_general(buf, storage_manager, sym)
+                }
+                Layout::LambdaSet(lambda_set) => self.store_arg(
+                    buf,
+                    storage_manager,
+                    layout_interner,
+                    sym,
+                    lambda_set.runtime_representation(),
+                ),
+                Layout::Builtin(
+                    Builtin::Int(IntWidth::I128 | IntWidth::U128) | Builtin::Decimal,
+                )
+                | Layout::Struct { .. }
+                | Layout::Union(UnionLayout::NonRecursive(_)) => {
+                    // TODO: structs of only floats should be passed in float registers.
+                    let alignment = layout_interner.alignment_bytes(other);
+                    self.store_arg_composite(buf, storage_manager, sym, alignment)
+                }
+                _ => {
+                    todo!("calling with arg type, {:?}", layout_interner.dbg(other));
+                }
+            },
+        }
+    }
+
+    fn store_arg_general<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+    ) {
+        if self.general_i < Self::GENERAL_PARAM_REGS.len() {
+            storage_manager.load_to_specified_general_reg(
+                buf,
+                &sym,
+                Self::GENERAL_PARAM_REGS[self.general_i],
+            );
+            self.general_i += 1;
+        } else {
+            // Copy to stack using IP0 as buffer.
+            storage_manager.load_to_specified_general_reg(buf, &sym, AArch64GeneralReg::IP0);
+            AArch64Assembler::mov_stack32_reg64(buf, self.tmp_stack_offset, AArch64GeneralReg::IP0);
+            self.tmp_stack_offset += 8;
+        }
+    }
+
+    fn store_arg_float<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
     ) {
-        todo!("Loading returned complex symbols for AArch64");
+        if self.float_i < Self::FLOAT_PARAM_REGS.len() {
+            storage_manager.load_to_specified_float_reg(
+                buf,
+                &sym,
+                Self::FLOAT_PARAM_REGS[self.float_i],
+            );
+            self.float_i += 1;
+        } else {
+            // Copy to stack using V16 as buffer.
+            // It is caller saved, so it was freed before the call, and it is not a parameter register.
+            storage_manager.load_to_specified_float_reg(buf, &sym, AArch64FloatReg::V16);
+            AArch64Assembler::mov_stack32_freg64(buf, self.tmp_stack_offset, AArch64FloatReg::V16);
+            self.tmp_stack_offset += 8;
+        }
+    }
+
+    fn store_arg_composite<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+        alignment: u32,
+    ) {
+        let (base_offset, size) = storage_manager.stack_offset_and_size(&sym);
+        let regs_needed = (size as usize + 7) / 8;
+        // 16 byte aligned composites start at an even register and an even stack slot.
+        let even = alignment == 16;
+        if even {
+            self.general_i += self.general_i % 2;
+        }
+
+        if self.general_i + regs_needed <= Self::GENERAL_PARAM_REGS.len() {
+            for i in 0..regs_needed {
+                AArch64Assembler::mov_reg64_base32(
+                    buf,
+                    Self::GENERAL_PARAM_REGS[self.general_i + i],
+                    base_offset + 8 * i as i32,
+                );
+            }
+            self.general_i += regs_needed;
+        } else {
+            // Once a composite goes on the stack, so does every argument after it.
+            self.general_i = Self::GENERAL_PARAM_REGS.len();
+            if even {
+                self.tmp_stack_offset += self.tmp_stack_offset % 16;
+            }
+            // Copy to stack using IP0 as buffer.
+            for i in 0..regs_needed as i32 {
+                AArch64Assembler::mov_reg64_base32(
+                    buf,
+                    AArch64GeneralReg::IP0,
+                    base_offset + 8 * i,
+                );
+                AArch64Assembler::mov_stack32_reg64(
+                    buf,
+                    self.tmp_stack_offset + 8 * i,
+                    AArch64GeneralReg::IP0,
+                );
+            }
+            self.tmp_stack_offset += 8 * regs_needed as i32;
+        }
+    }
+
+    fn store_arg_by_reference<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+    ) {
+        // Large values are passed as a pointer to a copy.
+        // The callee owns that copy and may modify it, so it can't be the symbol itself.
+        let (base_offset, size) = storage_manager.stack_offset_and_size(&sym);
+        let copy_offset = storage_manager.claim_stack_area_for_proc(size);
+        AArch64Assembler::copy_memory(
+            buf,
+            AArch64GeneralReg::FP,
+            copy_offset,
+            AArch64GeneralReg::FP,
+            base_offset,
+            size,
+        );
+
+        if self.general_i < Self::GENERAL_PARAM_REGS.len() {
+            AArch64Assembler::add_reg64_reg64_imm32(
+                buf,
+                Self::GENERAL_PARAM_REGS[self.general_i],
+                AArch64GeneralReg::FP,
+                copy_offset,
+            );
+            self.general_i += 1;
+        } else {
+            AArch64Assembler::add_reg64_reg64_imm32(
+                buf,
+                AArch64GeneralReg::IP0,
+                AArch64GeneralReg::FP,
+                copy_offset,
+            );
+            AArch64Assembler::mov_stack32_reg64(buf, self.tmp_stack_offset, AArch64GeneralReg::IP0);
+            self.tmp_stack_offset += 8;
+        }
+    }
+}
+
+struct AArch64CallLoadArgs {
+    general_i: usize,
+    float_i: usize,
+    argument_offset: i32,
+}
+
+impl AArch64CallLoadArgs {
+    const GENERAL_PARAM_REGS: &'static [AArch64GeneralReg] = AArch64Call::GENERAL_PARAM_REGS;
+    const FLOAT_PARAM_REGS: &'static [AArch64FloatReg] = AArch64Call::FLOAT_PARAM_REGS;
+
+    fn load_arg<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: Symbol,
+        in_layout: InLayout<'a>,
+    ) {
+        let stack_size = layout_interner.stack_size(in_layout);
+        match in_layout {
+            single_register_integers!() => self.load_arg_general(storage_manager, sym),
+            single_register_floats!() => self.load_arg_float(storage_manager, sym),
+            _ if stack_size == 0 => {
+                storage_manager.no_data(&sym);
+            }
+            _ if stack_size > 16 => {
+                self.load_arg_by_reference(buf, storage_manager, sym, stack_size)
+            }
+            other => match layout_interner.get(other) {
+                Layout::Boxed(_) => {
+                    // boxed layouts are pointers, which we treat as 64-bit integers
+                    self.load_arg_general(storage_manager, sym)
+                }
+                Layout::LambdaSet(lambda_set) => self.load_arg(
+                    buf,
+                    storage_manager,
+                    layout_interner,
+                    sym,
+                    lambda_set.runtime_representation(),
+                ),
+                Layout::Builtin(
+                    Builtin::Int(IntWidth::I128 | IntWidth::U128) | Builtin::Decimal,
+                )
+                | Layout::Struct { .. }
+                | Layout::Union(UnionLayout::NonRecursive(_)) => {
+                    let alignment = layout_interner.alignment_bytes(other);
+                    self.load_arg_composite(buf, storage_manager, sym, stack_size, alignment)
+                }
+                _ => {
+                    todo!("Loading args with layout {:?}", layout_interner.dbg(other));
+                }
+            },
+        }
+    }
+
+    fn load_arg_general(
+        &mut self,
+        storage_manager: &mut AArch64StorageManager<'_, '_>,
+        sym: Symbol,
+    ) {
+        if self.general_i < Self::GENERAL_PARAM_REGS.len() {
+            let reg = Self::GENERAL_PARAM_REGS[self.general_i];
+            storage_manager.general_reg_arg(&sym, reg);
+            self.general_i += 1;
+        } else {
+            storage_manager.primitive_stack_arg(&sym, self.argument_offset);
+            self.argument_offset += 8;
+        }
+    }
+
+    fn load_arg_float(&mut self, storage_manager: &mut AArch64StorageManager<'_, '_>, sym: Symbol) {
+        if self.float_i < Self::FLOAT_PARAM_REGS.len() {
+            let reg = Self::FLOAT_PARAM_REGS[self.float_i];
+            storage_manager.float_reg_arg(&sym, reg);
+            self.float_i += 1;
+        } else {
+            storage_manager.primitive_stack_arg(&sym, self.argument_offset);
+            self.argument_offset += 8;
+        }
+    }
+
+    fn load_arg_composite<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+        size: u32,
+        alignment: u32,
+    ) {
+        let regs_needed = (size as usize + 7) / 8;
+        let even = alignment == 16;
+        if even {
+            self.general_i += self.general_i % 2;
+        }
+
+        if self.general_i + regs_needed <= Self::GENERAL_PARAM_REGS.len() {
+            // Spill the registers to the stack, where complex symbols are expected to live.
+            let base_offset = storage_manager.claim_stack_area(&sym, size);
+            for i in 0..regs_needed {
+                AArch64Assembler::mov_base32_reg64(
+                    buf,
+                    base_offset + 8 * i as i32,
+                    Self::GENERAL_PARAM_REGS[self.general_i + i],
+                );
+            }
+            self.general_i += regs_needed;
+        } else {
+            self.general_i = Self::GENERAL_PARAM_REGS.len();
+            if even {
+                self.argument_offset += self.argument_offset % 16;
+            }
+            storage_manager.complex_stack_arg(&sym, self.argument_offset, size);
+            self.argument_offset += 8 * regs_needed as i32;
+        }
+    }
+
+    fn load_arg_by_reference<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut AArch64StorageManager<'a, '_>,
+        sym: Symbol,
+        size: u32,
+    ) {
+        // The caller passes a pointer to a copy of the value. Copy it into this stack frame.
+        let base_offset = storage_manager.claim_stack_area(&sym, size);
+        if self.general_i < Self::GENERAL_PARAM_REGS.len() {
+            let ptr_reg = Self::GENERAL_PARAM_REGS[self.general_i];
+            AArch64Assembler::copy_memory(
+                buf,
+                AArch64GeneralReg::FP,
+                base_offset,
+                ptr_reg,
+                0,
+                size,
+            );
+            self.general_i += 1;
+        } else {
+            let argument_offset = self.argument_offset;
+            storage_manager.with_tmp_general_reg(buf, |_storage_manager, buf, ptr_reg| {
+                AArch64Assembler::mov_reg64_base32(buf, ptr_reg, argument_offset);
+                AArch64Assembler::copy_memory(
+                    buf,
+                    AArch64GeneralReg::FP,
+                    base_offset,
+                    ptr_reg,
+                    0,
+                    size,
+                );
+            });
+            self.argument_offset += 8;
+        }
     }
 }
 
@@ -474,6 +1026,16 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         fabs_freg_freg(buf, FloatWidth::F64, dst, src);
     }
 
+    #[inline(always)]
+    fn abs_freg32_freg32(
+        buf: &mut Vec<'_, u8>,
+        _relocs: &mut Vec<'_, Relocation>,
+        dst: AArch64FloatReg,
+        src: AArch64FloatReg,
+    ) {
+        fabs_freg_freg(buf, FloatWidth::F32, dst, src);
+    }
+
     #[inline(always)]
     fn add_reg64_reg64_imm32(
         buf: &mut Vec<'_, u8>,
@@ -481,13 +1043,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         imm32: i32,
     ) {
-        if imm32 < 0 {
-            todo!("immediate addition with values less than 0");
-        } else if imm32 < 0xFFF {
-            add_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
-        } else {
-            todo!("immediate additions with values greater than 12bits");
-        }
+        Self::add_or_sub_reg64_reg64_imm32(buf, false, dst, src, imm32);
     }
     #[inline(always)]
     fn add_reg64_reg64_reg64(
@@ -496,7 +1052,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
-        add_reg64_reg64_reg64(buf, dst, src1, src2);
+        // Set the flags so that `set_if_overflow` can be used afterwards.
+        adds_reg64_reg64_reg64(buf, dst, src1, src2);
     }
     #[inline(always)]
     fn add_freg32_freg32_freg32(
@@ -518,18 +1075,33 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     #[inline(always)]
-    fn call(_buf: &mut Vec<'_, u8>, _relocs: &mut Vec<'_, Relocation>, _fn_name: String) {
-        todo!("calling functions literal for AArch64");
+    fn call(buf: &mut Vec<'_, u8>, relocs: &mut Vec<'_, Relocation>, fn_name: String) {
+        relocs.push(Relocation::LinkedFunction {
+            offset: buf.len() as u64,
+            name: fn_name,
+        });
+        bl_imm26(buf, 0);
     }
 
     #[inline(always)]
     fn function_pointer(
-        _buf: &mut Vec<'_, u8>,
-        _relocs: &mut Vec<'_, Relocation>,
-        _fn_name: String,
-        _dst: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        fn_name: String,
+        dst: AArch64GeneralReg,
     ) {
-        todo!("calling functions literal for AArch64");
+        // ADRP loads the address of the page containing the function,
+        // then ADD adds the offset of the function within that page.
+        relocs.push(Relocation::LinkedFunction {
+            offset: buf.len() as u64,
+            name: fn_name.clone(),
+        });
+        adrp_reg64_imm21(buf, dst, 0);
+        relocs.push(Relocation::LinkedFunction {
+            offset: buf.len() as u64,
+            name: fn_name,
+        });
+        add_reg64_reg64_imm12(buf, dst, dst, 0);
     }
 
     #[inline(always)]
@@ -582,29 +1154,33 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     fn irem_reg64_reg64_reg64<'a, ASM, CC>(
-        _buf: &mut Vec<'a, u8>,
+        buf: &mut Vec<'a, u8>,
         _storage_manager: &mut StorageManager<'a, '_, AArch64GeneralReg, AArch64FloatReg, ASM, CC>,
-        _dst: AArch64GeneralReg,
-        _src1: AArch64GeneralReg,
-        _src2: AArch64GeneralReg,
+        dst: AArch64GeneralReg,
+        src1: AArch64GeneralReg,
+        src2: AArch64GeneralReg,
     ) where
         ASM: Assembler<AArch64GeneralReg, AArch64FloatReg>,
         CC: CallConv<AArch64GeneralReg, AArch64FloatReg, ASM>,
     {
-        todo!()
+        // There is no remainder instruction: src1 - (src1 / src2) * src2.
+        sdiv_reg64_reg64_reg64(buf, AArch64GeneralReg::IP0, src1, src2);
+        msub_reg64_reg64_reg64_reg64(buf, dst, AArch64GeneralReg::IP0, src2, src1);
     }
 
     fn urem_reg64_reg64_reg64<'a, ASM, CC>(
-        _buf: &mut Vec<'a, u8>,
+        buf: &mut Vec<'a, u8>,
         _storage_manager: &mut StorageManager<'a, '_, AArch64GeneralReg, AArch64FloatReg, ASM, CC>,
-        _dst: AArch64GeneralReg,
-        _src1: AArch64GeneralReg,
-        _src2: AArch64GeneralReg,
+        dst: AArch64GeneralReg,
+        src1: AArch64GeneralReg,
+        src2: AArch64GeneralReg,
     ) where
         ASM: Assembler<AArch64GeneralReg, AArch64FloatReg>,
         CC: CallConv<AArch64GeneralReg, AArch64FloatReg, ASM>,
     {
-        todo!()
+        // There is no remainder instruction: src1 - (src1 / src2) * src2.
+        udiv_reg64_reg64_reg64(buf, AArch64GeneralReg::IP0, src1, src2);
+        msub_reg64_reg64_reg64_reg64(buf, dst, AArch64GeneralReg::IP0, src2, src1);
     }
 
     #[inline(always)]
@@ -647,10 +1223,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
 
     #[inline(always)]
     fn jmp_imm32(buf: &mut Vec<'_, u8>, offset: i32) -> usize {
+        // The offset is relative to the end of the jump, but branches are relative to their start.
+        let offset = offset + 4;
         if (-(1 << 27)..(1 << 27)).contains(&offset) {
             b_imm26(buf, offset);
         } else {
-            todo!("jump offsets over 27 bits for AArch64: {:#x}", offset);
+            internal_error!("jump offsets over 27 bits for AArch64: {:#x}", offset);
         }
 
         buf.len()
@@ -658,8 +1236,9 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
 
     #[inline(always)]
     fn tail_call(buf: &mut Vec<'_, u8>) -> u64 {
-        Self::jmp_imm32(buf, 0);
-        buf.len() as u64 - 4 // TODO is 4 the correct offset in ARM?
+        // The linker fills in the offset of the function to jump to.
+        b_imm26(buf, 0);
+        buf.len() as u64 - 4
     }
 
     #[inline(always)]
@@ -672,19 +1251,14 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         if imm < (1 << 12) {
             cmp_reg64_imm12(buf, reg, imm as u16);
         } else {
-            todo!(
-                "cmp immediate with value over 12 bits for AArch64: {:#x}",
-                imm
-            );
+            Self::mov_reg64_imm64(buf, AArch64GeneralReg::IP0, imm as i64);
+            cmp_reg64_reg64(buf, reg, AArch64GeneralReg::IP0);
         }
 
-        if (-(1 << 20)..(1 << 20)).contains(&offset) {
-            b_cond_imm19(buf, ConditionCode::NE, offset);
-        } else {
-            todo!("jump offsets over 20 bits for AArch64: {:#x}", offset);
-        }
-
-        buf.len()
+        // B.cond only reaches 1MB, so skip over an unconditional branch instead.
+        // This also keeps the size of the jump the same no matter the offset.
+        b_cond_imm19(buf, ConditionCode::EQ, 8);
+        Self::jmp_imm32(buf, offset)
     }
 
     #[inline(always)]
@@ -704,7 +1278,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
                 fmov_freg_imm8(buf, FloatWidth::F32, dst, imm8);
             }
             None => {
-                todo!("loading f32 literal over 8 bits for AArch64");
+                Self::mov_reg64_imm64(buf, AArch64GeneralReg::IP0, imm.to_bits() as i64);
+                fmov_freg_reg(buf, FloatWidth::F32, dst, AArch64GeneralReg::IP0);
             }
         }
     }
@@ -725,7 +1300,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
                 fmov_freg_imm8(buf, FloatWidth::F64, dst, imm8);
             }
             None => {
-                todo!("loading f64 literal over 8 bits for AArch64");
+                Self::mov_reg64_imm64(buf, AArch64GeneralReg::IP0, imm.to_bits() as i64);
+                fmov_freg_reg(buf, FloatWidth::F64, dst, AArch64GeneralReg::IP0);
             }
         }
     }
@@ -752,12 +1328,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 
     #[inline(always)]
-    fn mov_reg32_freg32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _src: AArch64FloatReg) {
-        unimplemented!();
+    fn mov_reg32_freg32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64FloatReg) {
+        fmov_reg_freg(buf, FloatWidth::F32, dst, src);
     }
     #[inline(always)]
-    fn mov_reg64_freg64(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _src: AArch64FloatReg) {
-        unimplemented!();
+    fn mov_reg64_freg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64FloatReg) {
+        fmov_reg_freg(buf, FloatWidth::F64, dst, src);
     }
 
     #[inline(always)]
@@ -768,87 +1344,75 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
     ) {
         match register_width {
-            RegisterWidth::W8 => todo!(),
-            RegisterWidth::W16 => todo!(),
-            RegisterWidth::W32 => todo!(),
             RegisterWidth::W64 => mov_reg64_reg64(buf, dst, src),
+            // Narrower values are zero extended to keep the upper bits clean.
+            _ => uxt_reg64_reg64(buf, register_width, dst, src),
         }
     }
 
     #[inline(always)]
     fn movsx_reg_reg(
-        _buf: &mut Vec<'_, u8>,
-        _input_width: RegisterWidth,
-        _dst: AArch64GeneralReg,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        input_width: RegisterWidth,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
     ) {
-        todo!("move with sign extension");
+        match input_width {
+            RegisterWidth::W64 => mov_reg64_reg64(buf, dst, src),
+            _ => sxt_reg64_reg64(buf, input_width, dst, src),
+        }
     }
 
     #[inline(always)]
-    fn mov_freg64_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64FloatReg, _offset: i32) {
-        todo!("loading floating point reg from base offset for AArch64");
+    fn mov_freg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64FloatReg, offset: i32) {
+        Self::mov_freg_mem_offset32(buf, FloatWidth::F64, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
     fn mov_reg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
-        if offset < 0 {
-            todo!("negative base offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, AArch64GeneralReg::FP, (offset as u16) >> 3);
-        } else {
-            todo!("base offsets over 32k for AArch64");
-        }
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W64, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_reg32_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _offset: i32) {
-        todo!()
+    fn mov_reg32_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W32, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_reg16_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _offset: i32) {
-        todo!()
+    fn mov_reg16_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W16, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_reg8_base32(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg, _offset: i32) {
-        todo!()
+    fn mov_reg8_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W8, dst, AArch64GeneralReg::FP, offset);
     }
     #[inline(always)]
-    fn mov_base32_freg64(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64FloatReg) {
-        todo!("saving floating point reg to base offset for AArch64");
+    fn mov_base32_freg64(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64FloatReg) {
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F64, AArch64GeneralReg::FP, offset, src);
     }
     #[inline(always)]
     fn movesd_mem64_offset32_freg64(
-        _buf: &mut Vec<'_, u8>,
-        _ptr: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64FloatReg,
+        buf: &mut Vec<'_, u8>,
+        ptr: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64FloatReg,
     ) {
-        todo!()
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F64, ptr, offset, src);
     }
 
     #[inline(always)]
     fn mov_base32_reg64(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
-        if offset < 0 {
-            todo!("negative base offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            str_reg64_reg64_imm12(buf, src, AArch64GeneralReg::FP, (offset as u16) >> 3);
-        } else {
-            todo!("base offsets over 32k for AArch64");
-        }
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W64, AArch64GeneralReg::FP, offset, src);
     }
 
     #[inline(always)]
-    fn mov_base32_reg32(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64GeneralReg) {
-        todo!()
+    fn mov_base32_reg32(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W32, AArch64GeneralReg::FP, offset, src);
     }
     #[inline(always)]
-    fn mov_base32_reg16(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64GeneralReg) {
-        todo!()
+    fn mov_base32_reg16(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W16, AArch64GeneralReg::FP, offset, src);
     }
     #[inline(always)]
-    fn mov_base32_reg8(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64GeneralReg) {
-        todo!()
+    fn mov_base32_reg8(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64GeneralReg) {
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W8, AArch64GeneralReg::FP, offset, src);
     }
 
     #[inline(always)]
@@ -858,14 +1422,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
-        if offset < 0 {
-            todo!("negative mem offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
-        } else {
-            todo!("mem offsets over 32k for AArch64");
-        }
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W64, dst, src, offset);
     }
     #[inline(always)]
     fn mov_reg32_mem32_offset32(
@@ -874,32 +1431,25 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         offset: i32,
     ) {
-        if offset < 0 {
-            todo!("negative mem offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, src, (offset as u16) >> 3);
-        } else {
-            todo!("mem offsets over 32k for AArch64");
-        }
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W32, dst, src, offset);
     }
     #[inline(always)]
     fn mov_reg16_mem16_offset32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _src: AArch64GeneralReg,
-        _offset: i32,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        offset: i32,
     ) {
-        todo!()
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W16, dst, src, offset);
     }
     #[inline(always)]
     fn mov_reg8_mem8_offset32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _src: AArch64GeneralReg,
-        _offset: i32,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        offset: i32,
     ) {
-        todo!()
+        Self::mov_reg_mem_offset32(buf, RegisterWidth::W8, dst, src, offset);
     }
 
     #[inline(always)]
@@ -909,95 +1459,90 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         offset: i32,
         src: AArch64GeneralReg,
     ) {
-        if offset < 0 {
-            todo!("negative mem offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            str_reg64_reg64_imm12(buf, src, dst, (offset as u16) >> 3);
-        } else {
-            todo!("mem offsets over 32k for AArch64");
-        }
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W64, dst, offset, src);
     }
 
     #[inline(always)]
     fn mov_mem32_offset32_reg32(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
     ) {
-        todo!()
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W32, dst, offset, src);
     }
 
     #[inline(always)]
     fn mov_mem16_offset32_reg16(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
     ) {
-        todo!()
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W16, dst, offset, src);
     }
 
     #[inline(always)]
     fn mov_mem8_offset32_reg8(
-        _buf: &mut Vec<'_, u8>,
-        _dst: AArch64GeneralReg,
-        _offset: i32,
-        _src: AArch64GeneralReg,
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
     ) {
-        todo!()
+        Self::mov_mem_offset32_reg(buf, RegisterWidth::W8, dst, offset, src);
     }
 
     #[inline(always)]
     fn movsx_reg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32, size: u8) {
         debug_assert!(size <= 8);
-        if size == 8 {
-            Self::mov_reg64_base32(buf, dst, offset);
-        } else if size == 4 {
-            todo!("sign extending 4 byte values");
-        } else if size == 2 {
-            todo!("sign extending 2 byte values");
-        } else if size == 1 {
-            todo!("sign extending 1 byte values");
-        } else {
-            internal_error!("Invalid size for sign extension: {}", size);
-        }
+        let width = match size {
+            8 => return Self::mov_reg64_base32(buf, dst, offset),
+            4 => RegisterWidth::W32,
+            2 => RegisterWidth::W16,
+            1 => RegisterWidth::W8,
+            _ => internal_error!("Invalid size for sign extension: {}", size),
+        };
+        let base = AArch64GeneralReg::FP;
+        Self::with_mem_offset32(
+            buf,
+            base,
+            offset,
+            1 << encode_register_width(width),
+            |buf, base, imm12| ldrs_reg64_reg64_imm12(buf, width, dst, base, imm12),
+            |buf, base, imm9| ldurs_reg64_reg64_imm9(buf, width, dst, base, imm9),
+        );
     }
     #[inline(always)]
     fn movzx_reg64_base32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32, size: u8) {
         debug_assert!(size <= 8);
-        if size == 8 {
-            Self::mov_reg64_base32(buf, dst, offset);
-        } else if size == 4 {
-            todo!("zero extending 4 byte values");
-        } else if size == 2 {
-            todo!("zero extending 2 byte values");
-        } else if size == 1 {
-            todo!("zero extending 1 byte values");
-        } else {
-            internal_error!("Invalid size for zero extension: {}", size);
-        }
+        let width = match size {
+            8 => RegisterWidth::W64,
+            4 => RegisterWidth::W32,
+            2 => RegisterWidth::W16,
+            1 => RegisterWidth::W8,
+            _ => internal_error!("Invalid size for zero extension: {}", size),
+        };
+        // Loads of less than 64 bits always zero extend.
+        Self::mov_reg_mem_offset32(buf, width, dst, AArch64GeneralReg::FP, offset);
     }
 
     #[inline(always)]
-    fn mov_freg64_stack32(_buf: &mut Vec<'_, u8>, _dst: AArch64FloatReg, _offset: i32) {
-        todo!("loading floating point reg from stack for AArch64");
+    fn mov_freg64_stack32(buf: &mut Vec<'_, u8>, dst: AArch64FloatReg, offset: i32) {
+        Self::mov_freg_mem_offset32(buf, FloatWidth::F64, dst, AArch64GeneralReg::ZRSP, offset);
     }
     #[inline(always)]
     fn mov_reg64_stack32(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, offset: i32) {
-        if offset < 0 {
-            todo!("negative stack offsets for AArch64");
-        } else if offset < (0xFFF << 8) {
-            debug_assert!(offset % 8 == 0);
-            ldr_reg64_reg64_imm12(buf, dst, AArch64GeneralReg::ZRSP, (offset as u16) >> 3);
-        } else {
-            todo!("stack offsets over 32k for AArch64");
-        }
+        Self::mov_reg_mem_offset32(
+            buf,
+            RegisterWidth::W64,
+            dst,
+            AArch64GeneralReg::ZRSP,
+            offset,
+        );
     }
     #[inline(always)]
-    fn mov_stack32_freg64(_buf: &mut Vec<'_, u8>, _offset: i32, _src: AArch64FloatReg) {
-        todo!("saving floating point reg to stack for AArch64");
+    fn mov_stack32_freg64(buf: &mut Vec<'_, u8>, offset: i32, src: AArch64FloatReg) {
+        Self::mov_mem_offset32_freg(buf, FloatWidth::F64, AArch64GeneralReg::ZRSP, offset, src);
     }
     #[inline(always)]
     fn mov_stack32_reg(
@@ -1006,21 +1551,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         offset: i32,
         src: AArch64GeneralReg,
     ) {
-        match register_width {
-            RegisterWidth::W8 => todo!(),
-            RegisterWidth::W16 => todo!(),
-            RegisterWidth::W32 => todo!(),
-            RegisterWidth::W64 => {
-                if offset < 0 {
-                    todo!("negative stack offsets for AArch64");
-                } else if offset < (0xFFF << 8) {
-                    debug_assert!(offset % 8 == 0);
-                    str_reg64_reg64_imm12(buf, src, AArch64GeneralReg::ZRSP, (offset as u16) >> 3);
-                } else {
-                    todo!("stack offsets over 32k for AArch64");
-                }
-            }
-        }
+        Self::mov_mem_offset32_reg(buf, register_width, AArch64GeneralReg::ZRSP, offset, src);
     }
     #[inline(always)]
     fn neg_reg64_reg64(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, src: AArch64GeneralReg) {
@@ -1034,13 +1565,7 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src: AArch64GeneralReg,
         imm32: i32,
     ) {
-        if imm32 < 0 {
-            todo!("immediate subtractions with values less than 0");
-        } else if imm32 < 0xFFF {
-            sub_reg64_reg64_imm12(buf, dst, src, imm32 as u16);
-        } else {
-            todo!("immediate subtractions with values greater than 12bits");
-        }
+        Self::add_or_sub_reg64_reg64_imm32(buf, true, dst, src, imm32);
     }
     #[inline(always)]
     fn sub_reg64_reg64_reg64(
@@ -1049,17 +1574,37 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
-        sub_reg64_reg64_reg64(buf, dst, src1, src2);
+        // Set the flags so that `set_if_overflow` can be used afterwards.
+        subs_reg64_reg64_reg64(buf, dst, src1, src2);
+    }
+    #[inline(always)]
+    fn sub_freg32_freg32_freg32(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64FloatReg,
+        src1: AArch64FloatReg,
+        src2: AArch64FloatReg,
+    ) {
+        fsub_freg_freg_freg(buf, FloatWidth::F32, dst, src1, src2);
+    }
+    #[inline(always)]
+    fn sub_freg64_freg64_freg64(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64FloatReg,
+        src1: AArch64FloatReg,
+        src2: AArch64FloatReg,
+    ) {
+        fsub_freg_freg_freg(buf, FloatWidth::F64, dst, src1, src2);
     }
 
     #[inline(always)]
     fn eq_reg_reg_reg(
         buf: &mut Vec<'_, u8>,
-        _register_width: RegisterWidth,
+        register_width: RegisterWidth,
         dst: AArch64GeneralReg,
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
+        let (src1, src2) = Self::extend_operands(buf, register_width, false, src1, src2);
         cmp_reg64_reg64(buf, src1, src2);
         cset_reg64_cond(buf, dst, ConditionCode::EQ);
     }
@@ -1067,11 +1612,12 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     #[inline(always)]
     fn neq_reg64_reg64_reg64(
         buf: &mut Vec<'_, u8>,
-        _register_width: RegisterWidth,
+        register_width: RegisterWidth,
         dst: AArch64GeneralReg,
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
+        let (src1, src2) = Self::extend_operands(buf, register_width, false, src1, src2);
         cmp_reg64_reg64(buf, src1, src2);
         cset_reg64_cond(buf, dst, ConditionCode::NE);
     }
@@ -1127,8 +1673,8 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
         fcvt_freg64_freg32(buf, dst, src);
     }
 
-    fn set_if_overflow(_buf: &mut Vec<'_, u8>, _dst: AArch64GeneralReg) {
-        todo!("set if overflow for AArch64");
+    fn set_if_overflow(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg) {
+        cset_reg64_cond(buf, dst, ConditionCode::VS);
     }
 
     #[inline(always)]
@@ -1212,12 +1758,13 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
 
     fn signed_compare_reg64(
         buf: &mut Vec<'_, u8>,
-        _register_width: RegisterWidth,
+        register_width: RegisterWidth,
         operation: CompareOperation,
         dst: AArch64GeneralReg,
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
+        let (src1, src2) = Self::extend_operands(buf, register_width, true, src1, src2);
         cmp_reg64_reg64(buf, src1, src2);
         let cond = match operation {
             CompareOperation::LessThan => ConditionCode::LT,
@@ -1230,12 +1777,13 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
 
     fn unsigned_compare_reg64(
         buf: &mut Vec<'_, u8>,
-        _register_width: RegisterWidth,
+        register_width: RegisterWidth,
         operation: CompareOperation,
         dst: AArch64GeneralReg,
         src1: AArch64GeneralReg,
         src2: AArch64GeneralReg,
     ) {
+        let (src1, src2) = Self::extend_operands(buf, register_width, false, src1, src2);
         cmp_reg64_reg64(buf, src1, src2);
         let cond = match operation {
             CompareOperation::LessThan => ConditionCode::CCLO,
@@ -1247,7 +1795,242 @@ impl Assembler<AArch64GeneralReg, AArch64FloatReg> for AArch64Assembler {
     }
 }
 
-impl AArch64Assembler {}
+impl AArch64Assembler {
+    /// Adds or subtracts any 32 bit immediate.
+    /// Immediates are only 12 bits, optionally shifted left by 12,
+    /// so larger values take two instructions, and values over 24 bits go through IP0.
+    fn add_or_sub_reg64_reg64_imm32(
+        buf: &mut Vec<'_, u8>,
+        subtract: bool,
+        dst: AArch64GeneralReg,
+        src: AArch64GeneralReg,
+        imm32: i32,
+    ) {
+        let subtract = subtract != (imm32 < 0);
+        let imm = imm32.unsigned_abs();
+
+        if imm > 0xFF_FFFF {
+            if dst == AArch64GeneralReg::ZRSP || src == AArch64GeneralReg::ZRSP {
+                internal_error!("stack offsets over 24 bits for AArch64: {:#x}", imm32);
+            }
+            // Prefer dst as the temporary, so memory accesses through IP1 don't clobber IP0.
+            let tmp = if dst != src {
+                dst
+            } else {
+                AArch64GeneralReg::IP0
+            };
+            Self::mov_reg64_imm64(buf, tmp, imm as i64);
+            if subtract {
+                sub_reg64_reg64_reg64(buf, dst, src, tmp);
+            } else {
+                add_reg64_reg64_reg64(buf, dst, src, tmp);
+            }
+            return;
+        }
+
+        let high = (imm >> 12) as u16;
+        let low = (imm & 0xFFF) as u16;
+        let mut src = src;
+        if high != 0 {
+            if subtract {
+                sub_reg64_reg64_imm12_lsl12(buf, dst, src, high);
+            } else {
+                add_reg64_reg64_imm12_lsl12(buf, dst, src, high);
+            }
+            src = dst;
+        }
+        if low != 0 || high == 0 {
+            if subtract {
+                sub_reg64_reg64_imm12(buf, dst, src, low);
+            } else {
+                add_reg64_reg64_imm12(buf, dst, src, low);
+            }
+        }
+    }
+
+    /// Registers hold values narrower than 64 bits with undefined upper bits.
+    /// To compare them, extend both operands into the scratch registers.
+    fn extend_operands(
+        buf: &mut Vec<'_, u8>,
+        register_width: RegisterWidth,
+        signed: bool,
+        src1: AArch64GeneralReg,
+        src2: AArch64GeneralReg,
+    ) -> (AArch64GeneralReg, AArch64GeneralReg) {
+        if let RegisterWidth::W64 = register_width {
+            return (src1, src2);
+        }
+
+        let (tmp1, tmp2) = (AArch64GeneralReg::IP0, AArch64GeneralReg::IP1);
+        if signed {
+            sxt_reg64_reg64(buf, register_width, tmp1, src1);
+            sxt_reg64_reg64(buf, register_width, tmp2, src2);
+        } else {
+            uxt_reg64_reg64(buf, register_width, tmp1, src1);
+            uxt_reg64_reg64(buf, register_width, tmp2, src2);
+        }
+        (tmp1, tmp2)
+    }
+
+    /// Emits a load or store at `base + offset` of a value of `size` bytes.
+    /// `scaled` emits the form with an unsigned offset in multiples of `size`,
+    /// and `unscaled` the form with a signed 9 bit offset in bytes.
+    /// If neither fits, the address is computed into IP1 first.
+    fn with_mem_offset32<'a>(
+        buf: &mut Vec<'a, u8>,
+        base: AArch64GeneralReg,
+        offset: i32,
+        size: i32,
+        scaled: impl FnOnce(&mut Vec<'a, u8>, AArch64GeneralReg, u16),
+        unscaled: impl FnOnce(&mut Vec<'a, u8>, AArch64GeneralReg, i16),
+    ) {
+        if offset >= 0 && offset % size == 0 && offset / size <= 0xFFF {
+            scaled(buf, base, (offset / size) as u16);
+        } else if (-256..256).contains(&offset) {
+            unscaled(buf, base, offset as i16);
+        } else {
+            Self::add_reg64_reg64_imm32(buf, AArch64GeneralReg::IP1, base, offset);
+            scaled(buf, AArch64GeneralReg::IP1, 0);
+        }
+    }
+
+    fn mov_reg_mem_offset32(
+        buf: &mut Vec<'_, u8>,
+        register_width: RegisterWidth,
+        dst: AArch64GeneralReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        Self::with_mem_offset32(
+            buf,
+            base,
+            offset,
+            1 << encode_register_width(register_width),
+            |buf, base, imm12| ldr_reg_reg64_imm12(buf, register_width, dst, base, imm12),
+            |buf, base, imm9| ldur_reg_reg64_imm9(buf, register_width, dst, base, imm9),
+        );
+    }
+
+    fn mov_mem_offset32_reg(
+        buf: &mut Vec<'_, u8>,
+        register_width: RegisterWidth,
+        base: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64GeneralReg,
+    ) {
+        Self::with_mem_offset32(
+            buf,
+            base,
+            offset,
+            1 << encode_register_width(register_width),
+            |buf, base, imm12| str_reg_reg64_imm12(buf, register_width, src, base, imm12),
+            |buf, base, imm9| stur_reg_reg64_imm9(buf, register_width, src, base, imm9),
+        );
+    }
+
+    fn mov_freg_mem_offset32(
+        buf: &mut Vec<'_, u8>,
+        ftype: FloatWidth,
+        dst: AArch64FloatReg,
+        base: AArch64GeneralReg,
+        offset: i32,
+    ) {
+        Self::with_mem_offset32(
+            buf,
+            base,
+            offset,
+            ftype.stack_size() as i32,
+            |buf, base, imm12| ldr_freg_reg64_imm12(buf, ftype, dst, base, imm12),
+            |buf, base, imm9| ldur_freg_reg64_imm9(buf, ftype, dst, base, imm9),
+        );
+    }
+
+    fn mov_mem_offset32_freg(
+        buf: &mut Vec<'_, u8>,
+        ftype: FloatWidth,
+        base: AArch64GeneralReg,
+        offset: i32,
+        src: AArch64FloatReg,
+    ) {
+        Self::with_mem_offset32(
+            buf,
+            base,
+            offset,
+            ftype.stack_size() as i32,
+            |buf, base, imm12| str_freg_reg64_imm12(buf, ftype, src, base, imm12),
+            |buf, base, imm9| stur_freg_reg64_imm9(buf, ftype, src, base, imm9),
+        );
+    }
+
+    /// Copies `size` bytes from `src + src_offset` to `dst + dst_offset`, using IP0 as buffer.
+    fn copy_memory(
+        buf: &mut Vec<'_, u8>,
+        dst: AArch64GeneralReg,
+        dst_offset: i32,
+        src: AArch64GeneralReg,
+        src_offset: i32,
+        size: u32,
+    ) {
+        let tmp = AArch64GeneralReg::IP0;
+        let size = size as i32;
+        let mut copied = 0;
+        for register_width in [
+            RegisterWidth::W64,
+            RegisterWidth::W32,
+            RegisterWidth::W16,
+            RegisterWidth::W8,
+        ] {
+            let step = 1 << encode_register_width(register_width);
+            while size - copied >= step {
+                Self::mov_reg_mem_offset32(buf, register_width, tmp, src, src_offset + copied);
+                Self::mov_mem_offset32_reg(buf, register_width, dst, dst_offset + copied, tmp);
+                copied += step;
+            }
+        }
+    }
+}
+
+/// The relocation kind for a `Relocation::LinkedFunction` pointing at `inst`.
+/// Calls, tail calls and function pointers each patch a different instruction format.
+pub(crate) fn linked_function_relocation_kind(format: BinaryFormat, inst: u32) -> RelocationKind {
+    use object::{elf, macho};
+
+    let (elf_kind, macho_kind, relative) = if inst & 0xFC00_0000 == 0x9400_0000 {
+        // BL
+        (elf::R_AARCH64_CALL26, macho::ARM64_RELOC_BRANCH26, true)
+    } else if inst & 0xFC00_0000 == 0x1400_0000 {
+        // B
+        (elf::R_AARCH64_JUMP26, macho::ARM64_RELOC_BRANCH26, true)
+    } else if inst & 0x9F00_0000 == 0x9000_0000 {
+        // ADRP
+        (
+            elf::R_AARCH64_ADR_PREL_PG_HI21,
+            macho::ARM64_RELOC_PAGE21,
+            true,
+        )
+    } else if inst & 0xFFC0_0000 == 0x9100_0000 {
+        // ADD (immediate)
+        (
+            elf::R_AARCH64_ADD_ABS_LO12_NC,
+            macho::ARM64_RELOC_PAGEOFF12,
+            false,
+        )
+    } else {
+        internal_error!(
+            "no relocation for linked function instruction: {:#010x}",
+            inst
+        )
+    };
+
+    match format {
+        BinaryFormat::Elf => RelocationKind::Elf(elf_kind),
+        BinaryFormat::MachO => RelocationKind::MachO {
+            value: macho_kind,
+            relative,
+        },
+        _ => internal_error!("unsupported binary format for aarch64: {:?}", format),
+    }
+}
 
 // Instructions
 // ARM manual section C3
@@ -1681,39 +2464,95 @@ pub struct DataProcessingThreeSource {
     rd: Integer<u8, packed_bits::Bits<5>>,
 }
 
-impl Aarch64Bytes for DataProcessingThreeSource {}
+impl Aarch64Bytes for DataProcessingThreeSource {}
+
+pub struct DataProcessingThreeSourceParams {
+    op31: u8,
+    o0: bool,
+    rm: AArch64GeneralReg,
+    ra: AArch64GeneralReg,
+    rn: AArch64GeneralReg,
+    rd: AArch64GeneralReg,
+}
+
+impl DataProcessingThreeSource {
+    #[inline(always)]
+    fn new(
+        DataProcessingThreeSourceParams {
+            op31,
+            o0,
+            rm,
+            ra,
+            rn,
+            rd,
+        }: DataProcessingThreeSourceParams,
+    ) -> Self {
+        debug_assert!(op31 <= 0b111);
+
+        Self {
+            sf: true,
+            op54: 0b00.into(),
+            fixed: 0b011011.into(),
+            op31: op31.into(),
+            rm: rm.id().into(),
+            o0,
+            ra: ra.id().into(),
+            rn: rn.id().into(),
+            rd: rd.id().into(),
+        }
+    }
+}
+
+#[derive(PackedStruct)]
+#[packed_struct(endian = "msb")]
+pub struct Bitfield {
+    sf: bool,
+    opc: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<6>>, // = 0b100110,
+    n: bool,
+    immr: Integer<u8, packed_bits::Bits<6>>,
+    imms: Integer<u8, packed_bits::Bits<6>>,
+    reg_n: Integer<u8, packed_bits::Bits<5>>,
+    reg_d: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for Bitfield {}
 
-pub struct DataProcessingThreeSourceParams {
-    op31: u8,
-    rm: AArch64GeneralReg,
-    ra: AArch64GeneralReg,
+pub struct BitfieldParams {
+    opc: u8,
+    immr: u8,
+    imms: u8,
     rn: AArch64GeneralReg,
     rd: AArch64GeneralReg,
 }
 
-impl DataProcessingThreeSource {
+impl Bitfield {
     #[inline(always)]
     fn new(
-        DataProcessingThreeSourceParams {
-            op31,
-            rm,
-            ra,
+        BitfieldParams {
+            opc,
+            immr,
+            imms,
             rn,
             rd,
-        }: DataProcessingThreeSourceParams,
+        }: BitfieldParams,
     ) -> Self {
-        debug_assert!(op31 <= 0b111);
+        debug_assert!(opc <= 0b11);
+        debug_assert!(immr <= 0b111111);
+        debug_assert!(imms <= 0b111111);
 
         Self {
+            reg_d: rd.id().into(),
+            reg_n: rn.id().into(),
+            imms: imms.into(),
+            immr: immr.into(),
+            // n must match sf
+            n: true,
+            fixed: 0b100110.into(),
+            opc: opc.into(),
+            // true for 64 bit operations
+            // false for 32 bit operations
             sf: true,
-            op54: 0b00.into(),
-            fixed: 0b011011.into(),
-            op31: op31.into(),
-            rm: rm.id().into(),
-            o0: false,
-            ra: ra.id().into(),
-            rn: rn.id().into(),
-            rd: rd.id().into(),
         }
     }
 }
@@ -1869,15 +2708,49 @@ impl UnconditionalBranchImmediate {
     }
 }
 
+#[derive(PackedStruct)]
+#[packed_struct(endian = "msb")]
+pub struct PcRelativeAddressing {
+    op: bool, // false=ADR, true=ADRP
+    immlo: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<5>>, // = 0b10000,
+    immhi: Integer<u32, packed_bits::Bits<19>>,
+    rd: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for PcRelativeAddressing {}
+
+pub struct PcRelativeAddressingParams {
+    op: bool,
+    imm21: u32,
+    rd: AArch64GeneralReg,
+}
+
+impl PcRelativeAddressing {
+    #[inline(always)]
+    fn new(PcRelativeAddressingParams { op, imm21, rd }: PcRelativeAddressingParams) -> Self {
+        debug_assert!(imm21 >> 21 == 0);
+
+        Self {
+            op,
+            immlo: ((imm21 & 0b11) as u8).into(),
+            fixed: 0b10000.into(),
+            immhi: (imm21 >> 2).into(),
+            rd: rd.id().into(),
+        }
+    }
+}
+
 // Uses unsigned Offset
 // opc = 0b01 means load
 // opc = 0b00 means store
+// opc = 0b10 means load and sign extend to 64 bits
 #[derive(PackedStruct, Debug)]
 #[packed_struct(endian = "msb")]
 pub struct LoadStoreRegisterImmediate {
     size: Integer<u8, packed_bits::Bits<2>>,
     fixed: Integer<u8, packed_bits::Bits<3>>, // = 0b111,
-    fixed2: bool,
+    v: bool,                                  // float register
     fixed3: Integer<u8, packed_bits::Bits<2>>,
     opc: Integer<u8, packed_bits::Bits<2>>,
     imm12: Integer<u16, packed_bits::Bits<12>>,
@@ -1891,7 +2764,7 @@ pub struct LoadStoreRegisterImmediateParams {
     size: u8,
     imm12: u16,
     rn: AArch64GeneralReg,
-    rt: AArch64GeneralReg,
+    rt: AArch64Reg,
 }
 
 impl LoadStoreRegisterImmediate {
@@ -1914,7 +2787,7 @@ impl LoadStoreRegisterImmediate {
             imm12: imm12.into(),
             opc: opc.into(),
             fixed3: 0b01.into(),
-            fixed2: false,
+            v: rt.is_float(),
             fixed: 0b111.into(),
             size: size.into(),
         }
@@ -1925,12 +2798,146 @@ impl LoadStoreRegisterImmediate {
         Self::new(0b01, params)
     }
 
+    #[inline(always)]
+    fn new_load_signed(params: LoadStoreRegisterImmediateParams) -> Self {
+        Self::new(0b10, params)
+    }
+
     #[inline(always)]
     fn new_store(params: LoadStoreRegisterImmediateParams) -> Self {
         Self::new(0b00, params)
     }
 }
 
+// Uses signed unscaled offset
+// opc = 0b01 means load
+// opc = 0b00 means store
+// opc = 0b10 means load and sign extend to 64 bits
+#[derive(PackedStruct, Debug)]
+#[packed_struct(endian = "msb")]
+pub struct LoadStoreRegisterUnscaledImmediate {
+    size: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<3>>, // = 0b111,
+    v: bool,                                  // float register
+    fixed2: Integer<u8, packed_bits::Bits<2>>,
+    opc: Integer<u8, packed_bits::Bits<2>>,
+    fixed3: bool,
+    imm9: Integer<u16, packed_bits::Bits<9>>,
+    fixed4: Integer<u8, packed_bits::Bits<2>>,
+    rn: Integer<u8, packed_bits::Bits<5>>,
+    rt: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for LoadStoreRegisterUnscaledImmediate {}
+
+pub struct LoadStoreRegisterUnscaledImmediateParams {
+    size: u8,
+    imm9: i16,
+    rn: AArch64GeneralReg,
+    rt: AArch64Reg,
+}
+
+impl LoadStoreRegisterUnscaledImmediate {
+    #[inline(always)]
+    fn new(
+        opc: u8,
+        LoadStoreRegisterUnscaledImmediateParams {
+            size,
+            imm9,
+            rn,
+            rt,
+        }: LoadStoreRegisterUnscaledImmediateParams,
+    ) -> Self {
+        debug_assert!(size <= 0b11);
+        debug_assert!((-256..256).contains(&imm9));
+
+        Self {
+            rt: rt.id().into(),
+            rn: rn.id().into(),
+            fixed4: 0b00.into(),
+            imm9: (imm9 as u16 & 0x1FF).into(),
+            fixed3: false,
+            opc: opc.into(),
+            fixed2: 0b00.into(),
+            v: rt.is_float(),
+            fixed: 0b111.into(),
+            size: size.into(),
+        }
+    }
+
+    #[inline(always)]
+    fn new_load(params: LoadStoreRegisterUnscaledImmediateParams) -> Self {
+        Self::new(0b01, params)
+    }
+
+    #[inline(always)]
+    fn new_load_signed(params: LoadStoreRegisterUnscaledImmediateParams) -> Self {
+        Self::new(0b10, params)
+    }
+
+    #[inline(always)]
+    fn new_store(params: LoadStoreRegisterUnscaledImmediateParams) -> Self {
+        Self::new(0b00, params)
+    }
+}
+
+// Only 64 bit registers
+// index = 0b001 means post-index
+// index = 0b011 means pre-index
+#[derive(PackedStruct, Debug)]
+#[packed_struct(endian = "msb")]
+pub struct LoadStoreRegisterPair {
+    opc: Integer<u8, packed_bits::Bits<2>>,
+    fixed: Integer<u8, packed_bits::Bits<3>>, // = 0b101,
+    v: bool,
+    index: Integer<u8, packed_bits::Bits<3>>,
+    l: bool, // true=load, false=store
+    imm7: Integer<u8, packed_bits::Bits<7>>,
+    rt2: Integer<u8, packed_bits::Bits<5>>,
+    rn: Integer<u8, packed_bits::Bits<5>>,
+    rt: Integer<u8, packed_bits::Bits<5>>,
+}
+
+impl Aarch64Bytes for LoadStoreRegisterPair {}
+
+pub struct LoadStoreRegisterPairParams {
+    index: u8,
+    l: bool,
+    imm7: i8,
+    rt2: AArch64GeneralReg,
+    rn: AArch64GeneralReg,
+    rt: AArch64GeneralReg,
+}
+
+impl LoadStoreRegisterPair {
+    #[inline(always)]
+    fn new(
+        LoadStoreRegisterPairParams {
+            index,
+            l,
+            imm7,
+            rt2,
+            rn,
+            rt,
+        }: LoadStoreRegisterPairParams,
+    ) -> Self {
+        debug_assert!(index <= 0b111);
+        debug_assert!((-64..64).contains(&imm7));
+
+        Self {
+            rt: rt.id().into(),
+            rn: rn.id().into(),
+            rt2: rt2.id().into(),
+            imm7: (imm7 as u8 & 0x7F).into(),
+            l,
+            index: index.into(),
+            v: false,
+            fixed: 0b101.into(),
+            opc: 0b10.into(),
+        }
+    }
+}
+
 #[derive(PackedStruct)]
 #[packed_struct(endian = "msb")]
 pub struct AdvancedSimdModifiedImmediate {
@@ -1985,6 +2992,16 @@ fn encode_float_width(width: FloatWidth) -> u8 {
     }
 }
 
+/// The size field of loads and stores, which is log2 of the number of bytes.
+fn encode_register_width(width: RegisterWidth) -> u8 {
+    match width {
+        RegisterWidth::W8 => 0b00,
+        RegisterWidth::W16 => 0b01,
+        RegisterWidth::W32 => 0b10,
+        RegisterWidth::W64 => 0b11,
+    }
+}
+
 #[derive(PackedStruct)]
 #[packed_struct(endian = "msb")]
 pub struct ConversionBetweenFloatingPointAndInteger {
@@ -2004,17 +3021,19 @@ pub struct ConversionBetweenFloatingPointAndInteger {
 impl Aarch64Bytes for ConversionBetweenFloatingPointAndInteger {}
 
 pub struct ConversionBetweenFloatingPointAndIntegerParams {
+    sf: bool,
     ptype: FloatWidth,
     rmode: u8,
     opcode: u8,
-    rn: AArch64GeneralReg,
-    rd: AArch64FloatReg,
+    rn: AArch64Reg,
+    rd: AArch64Reg,
 }
 
 impl ConversionBetweenFloatingPointAndInteger {
     #[inline(always)]
     fn new(
         ConversionBetweenFloatingPointAndIntegerParams {
+            sf,
             ptype,
             rmode,
             opcode,
@@ -2026,7 +3045,7 @@ impl ConversionBetweenFloatingPointAndInteger {
         debug_assert!(opcode <= 0b111);
 
         Self {
-            sf: true,
+            sf,
             fixed: false,
             s: false,
             fixed2: 0b11110.into(),
@@ -2270,6 +3289,26 @@ fn add_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `ADD Xd, Xn, imm12, LSL #12` -> Add Xn and imm12 shifted left by 12 and place the result into Xd.
+#[inline(always)]
+fn add_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: false,
+        s: false,
+        rd: dst,
+        rn: src,
+        imm12,
+        sh: true,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `ADD Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd.
 #[inline(always)]
 fn add_reg64_reg64_reg64(
@@ -2291,6 +3330,42 @@ fn add_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `ADDS Xd, Xm, Xn` -> Add Xm and Xn and place the result into Xd, setting condition flags.
+#[inline(always)]
+fn adds_reg64_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+) {
+    let inst = ArithmeticShifted::new(ArithmeticShiftedParams {
+        op: false,
+        s: true,
+        shift: ShiftType::LSL,
+        imm6: 0,
+        rm: src2,
+        rn: src1,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `ADRP Xd, imm21` -> Place the address of the 4KB page at PC + imm21 pages into Xd.
+#[inline(always)]
+fn adrp_reg64_imm21(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, imm21: i32) {
+    // Check that imm21 fits into 21 signed bits
+    debug_assert!((-(1 << 20)..(1 << 20)).contains(&imm21));
+
+    let inst = PcRelativeAddressing::new(PcRelativeAddressingParams {
+        op: true,
+        imm21: (imm21 as u32) & 0x1F_FFFF,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `AND Xd, Xn, Xm` -> Bitwise AND Xn and Xm and place the result into Xd.
 #[inline(always)]
 fn and_reg64_reg64_reg64(
@@ -2358,6 +3433,28 @@ fn b_cond_imm19(buf: &mut Vec<'_, u8>, cond: ConditionCode, imm19: i32) {
 /// `B imm26` -> Jump to PC + imm26.
 #[inline(always)]
 fn b_imm26(buf: &mut Vec<'_, u8>, imm26: i32) {
+    let inst = UnconditionalBranchImmediate::new(UnconditionalBranchImmediateParams {
+        op: false,
+        imm26: encode_imm26(imm26),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `BL imm26` -> Jump to PC + imm26 and place the return address into LR.
+#[inline(always)]
+fn bl_imm26(buf: &mut Vec<'_, u8>, imm26: i32) {
+    let inst = UnconditionalBranchImmediate::new(UnconditionalBranchImmediateParams {
+        op: true,
+        imm26: encode_imm26(imm26),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// Encode a byte offset into the 26-bit word offset used by B and BL.
+#[inline(always)]
+fn encode_imm26(imm26: i32) -> u32 {
     // Since instructions are 4 bytes, the branch instructions assume the last 2 bits are 0
     debug_assert!(imm26 & 0b11 == 0, "branch location must be 4-byte aligned");
     let shifted = imm26 >> 2;
@@ -2373,12 +3470,7 @@ fn b_imm26(buf: &mut Vec<'_, u8>, imm26: i32) {
         debug_assert!(left_removed | 0b1111_1110_0000_0000_0000_0000_0000_0000 == unsigned);
     }
 
-    let inst = UnconditionalBranchImmediate::new(UnconditionalBranchImmediateParams {
-        op: false,
-        imm26: left_removed,
-    });
-
-    buf.extend(inst.bytes());
+    left_removed
 }
 
 /// `CMP Xn, imm12` -> Compare Xn and imm12, setting condition flags.
@@ -2460,41 +3552,131 @@ fn csneg_reg64_reg64_reg64_cond(
     buf.extend(inst.bytes());
 }
 
-/// `EOR Xd, Xn, Xm` -> Bitwise XOR Xn and Xm and place the result into Xd.
+/// `EOR Xd, Xn, Xm` -> Bitwise XOR Xn and Xm and place the result into Xd.
+#[inline(always)]
+fn eor_reg64_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+) {
+    let inst = LogicalShiftedRegister::new(LogicalShiftedRegisterParams {
+        op: LogicalOp::EOR,
+        shift: ShiftType::LSL,
+        imm6: 0,
+        rm: src2,
+        rn: src1,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDP Xt1, Xt2, [Xn], #offset` -> Load Xt1 and Xt2 from Xn, then add offset to Xn. ZRSP is SP.
+/// Note: imm7 is the offset divided by 8.
+#[inline(always)]
+fn ldp_reg64_reg64_reg64_imm7(
+    buf: &mut Vec<'_, u8>,
+    dst1: AArch64GeneralReg,
+    dst2: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm7: i8,
+) {
+    let inst = LoadStoreRegisterPair::new(LoadStoreRegisterPairParams {
+        index: 0b001,
+        l: true,
+        imm7,
+        rt2: dst2,
+        rn: base,
+        rt: dst1,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDR(B/H) Xt/Wt, [Xn, #offset]` -> Load Xn + Offset into Xt, zero extending. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn ldr_reg_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = LoadStoreRegisterImmediate::new_load(LoadStoreRegisterImmediateParams {
+        size: encode_register_width(width),
+        imm12,
+        rn: base,
+        rt: dst.into(),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDRS(B/H/W) Xt, [Xn, #offset]` -> Load Xn + Offset into Xt, sign extending. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn ldrs_reg64_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    debug_assert!(!matches!(width, RegisterWidth::W64));
+
+    let inst = LoadStoreRegisterImmediate::new_load_signed(LoadStoreRegisterImmediateParams {
+        size: encode_register_width(width),
+        imm12,
+        rn: base,
+        rt: dst.into(),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDUR(B/H) Xt/Wt, [Xn, #offset]` -> Load Xn + Offset into Xt, zero extending. ZRSP is SP.
+/// Note: imm9 is the unscaled, signed offset.
 #[inline(always)]
-fn eor_reg64_reg64_reg64(
+fn ldur_reg_reg64_imm9(
     buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
     dst: AArch64GeneralReg,
-    src1: AArch64GeneralReg,
-    src2: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
 ) {
-    let inst = LogicalShiftedRegister::new(LogicalShiftedRegisterParams {
-        op: LogicalOp::EOR,
-        shift: ShiftType::LSL,
-        imm6: 0,
-        rm: src2,
-        rn: src1,
-        rd: dst,
-    });
+    let inst =
+        LoadStoreRegisterUnscaledImmediate::new_load(LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_register_width(width),
+            imm9,
+            rn: base,
+            rt: dst.into(),
+        });
 
     buf.extend(inst.bytes());
 }
 
-/// `LDR Xt, [Xn, #offset]` -> Load Xn + Offset Xt. ZRSP is SP.
-/// Note: imm12 is the offest divided by 8.
+/// `LDURS(B/H/W) Xt, [Xn, #offset]` -> Load Xn + Offset into Xt, sign extending. ZRSP is SP.
+/// Note: imm9 is the unscaled, signed offset.
 #[inline(always)]
-fn ldr_reg64_reg64_imm12(
+fn ldurs_reg64_reg64_imm9(
     buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
     dst: AArch64GeneralReg,
     base: AArch64GeneralReg,
-    imm12: u16,
+    imm9: i16,
 ) {
-    let inst = LoadStoreRegisterImmediate::new_load(LoadStoreRegisterImmediateParams {
-        size: 0b11,
-        imm12,
-        rn: base,
-        rt: dst,
-    });
+    debug_assert!(!matches!(width, RegisterWidth::W64));
+
+    let inst = LoadStoreRegisterUnscaledImmediate::new_load_signed(
+        LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_register_width(width),
+            imm9,
+            rn: base,
+            rt: dst.into(),
+        },
+    );
 
     buf.extend(inst.bytes());
 }
@@ -2546,6 +3728,7 @@ fn madd_reg64_reg64_reg64_reg64(
 ) {
     let inst = DataProcessingThreeSource::new(DataProcessingThreeSourceParams {
         op31: 0b000000,
+        o0: false,
         rm: src2,
         ra: src3,
         rn: src1,
@@ -2590,6 +3773,27 @@ fn movz_reg64_imm16(buf: &mut Vec<'_, u8>, dst: AArch64GeneralReg, imm16: u16, h
     buf.extend(inst.bytes());
 }
 
+/// `MSUB Xd, Xn, Xm, Xa` -> Multiply Xn and Xm, subtract the result from Xa, and place the result into Xd.
+#[inline(always)]
+fn msub_reg64_reg64_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+    src3: AArch64GeneralReg,
+) {
+    let inst = DataProcessingThreeSource::new(DataProcessingThreeSourceParams {
+        op31: 0b000000,
+        o0: true,
+        rm: src2,
+        ra: src3,
+        rn: src1,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `MUL Xd, Xn, Xm` -> Multiply Xn and Xm and place the result into Xd.
 #[inline(always)]
 fn mul_reg64_reg64_reg64(
@@ -2646,25 +3850,69 @@ fn sdiv_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
-/// `STR Xt, [Xn, #offset]` -> Store Xt to Xn + Offset. ZRSP is SP.
-/// Note: imm12 is the offest divided by 8.
+/// `STP Xt1, Xt2, [Xn, #offset]!` -> Add offset to Xn, then store Xt1 and Xt2 to Xn. ZRSP is SP.
+/// Note: imm7 is the offset divided by 8.
+#[inline(always)]
+fn stp_reg64_reg64_reg64_imm7(
+    buf: &mut Vec<'_, u8>,
+    src1: AArch64GeneralReg,
+    src2: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm7: i8,
+) {
+    let inst = LoadStoreRegisterPair::new(LoadStoreRegisterPairParams {
+        index: 0b011,
+        l: false,
+        imm7,
+        rt2: src2,
+        rn: base,
+        rt: src1,
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `STR(B/H) Xt/Wt, [Xn, #offset]` -> Store Xt to Xn + Offset. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
 #[inline(always)]
-fn str_reg64_reg64_imm12(
+fn str_reg_reg64_imm12(
     buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
     src: AArch64GeneralReg,
     base: AArch64GeneralReg,
     imm12: u16,
 ) {
     let inst = LoadStoreRegisterImmediate::new_store(LoadStoreRegisterImmediateParams {
-        size: 0b11,
+        size: encode_register_width(width),
         imm12,
         rn: base,
-        rt: src,
+        rt: src.into(),
     });
 
     buf.extend(inst.bytes());
 }
 
+/// `STUR(B/H) Xt/Wt, [Xn, #offset]` -> Store Xt to Xn + Offset. ZRSP is SP.
+/// Note: imm9 is the unscaled, signed offset.
+#[inline(always)]
+fn stur_reg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    src: AArch64GeneralReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst =
+        LoadStoreRegisterUnscaledImmediate::new_store(LoadStoreRegisterUnscaledImmediateParams {
+            size: encode_register_width(width),
+            imm9,
+            rn: base,
+            rt: src.into(),
+        });
+
+    buf.extend(inst.bytes());
+}
+
 /// `SUB Xd, Xn, imm12` -> Subtract Xn and imm12 and place the result into Xd.
 #[inline(always)]
 fn sub_reg64_reg64_imm12(
@@ -2685,6 +3933,26 @@ fn sub_reg64_reg64_imm12(
     buf.extend(inst.bytes());
 }
 
+/// `SUB Xd, Xn, imm12, LSL #12` -> Subtract imm12 shifted left by 12 from Xn and place the result into Xd.
+#[inline(always)]
+fn sub_reg64_reg64_imm12_lsl12(
+    buf: &mut Vec<'_, u8>,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = ArithmeticImmediate::new(ArithmeticImmediateParams {
+        op: true,
+        s: false,
+        rd: dst,
+        rn: src,
+        imm12,
+        sh: true,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `SUB Xd, Xm, Xn` -> Subtract Xm and Xn and place the result into Xd.
 #[inline(always)]
 fn sub_reg64_reg64_reg64(
@@ -2747,6 +4015,26 @@ fn subs_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `SXT(B/H/W) Xd, Wn` -> Sign extend the low bits of Wn and place the result into Xd.
+#[inline(always)]
+fn sxt_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+) {
+    // SXT is equivalent to `SBFM Xd, Xn, #0, #(bits - 1)` in AARCH64.
+    let inst = Bitfield::new(BitfieldParams {
+        opc: 0b00,
+        immr: 0,
+        imms: (8 << encode_register_width(width)) - 1,
+        rn: src,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 /// `RET Xn` -> Return to the address stored in Xn.
 #[inline(always)]
 fn ret_reg64(buf: &mut Vec<'_, u8>, xn: AArch64GeneralReg) {
@@ -2775,6 +4063,26 @@ fn udiv_reg64_reg64_reg64(
     buf.extend(inst.bytes());
 }
 
+/// `UXT(B/H/W) Xd, Xn` -> Zero extend the low bits of Xn and place the result into Xd.
+#[inline(always)]
+fn uxt_reg64_reg64(
+    buf: &mut Vec<'_, u8>,
+    width: RegisterWidth,
+    dst: AArch64GeneralReg,
+    src: AArch64GeneralReg,
+) {
+    // UXT is equivalent to `UBFM Xd, Xn, #0, #(bits - 1)` in AARCH64.
+    let inst = Bitfield::new(BitfieldParams {
+        opc: 0b10,
+        immr: 0,
+        imms: (8 << encode_register_width(width)) - 1,
+        rn: src,
+        rd: dst,
+    });
+
+    buf.extend(inst.bytes());
+}
+
 // Floating point (and advanced SIMD) instructions
 // ARM manual section C7
 
@@ -3011,6 +4319,50 @@ fn fmov_freg_imm8(buf: &mut Vec<'_, u8>, ftype: FloatWidth, dst: AArch64FloatReg
     buf.extend(inst.bytes());
 }
 
+/// `FMOV Sd/Dd, Wn/Xn` -> Move the bits of Wn/Xn to Sd/Dd.
+#[inline(always)]
+fn fmov_freg_reg(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    src: AArch64GeneralReg,
+) {
+    let inst = ConversionBetweenFloatingPointAndInteger::new(
+        ConversionBetweenFloatingPointAndIntegerParams {
+            sf: ftype == FloatWidth::F64,
+            opcode: 0b111,
+            rmode: 0b00,
+            ptype: ftype,
+            rd: dst.into(),
+            rn: src.into(),
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
+/// `FMOV Wd/Xd, Sn/Dn` -> Move the bits of Sn/Dn to Wd/Xd.
+#[inline(always)]
+fn fmov_reg_freg(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64GeneralReg,
+    src: AArch64FloatReg,
+) {
+    let inst = ConversionBetweenFloatingPointAndInteger::new(
+        ConversionBetweenFloatingPointAndIntegerParams {
+            sf: ftype == FloatWidth::F64,
+            opcode: 0b110,
+            rmode: 0b00,
+            ptype: ftype,
+            rd: dst.into(),
+            rn: src.into(),
+        },
+    );
+
+    buf.extend(inst.bytes());
+}
+
 /// `FMUL Sd/Dd, Sn/Dn, Sm/Dm` -> Multiply Sn/Dn by Sm/Dm and store the result in Sd/Dd.
 #[inline(always)]
 fn fmul_freg_freg_freg(
@@ -3051,6 +4403,68 @@ fn fsqrt_freg_freg(
     buf.extend(inst.bytes());
 }
 
+/// `FSUB Sd/Dd, Sn/Dn, Sm/Dm` -> Subtract Sm/Dm from Sn/Dn and place the result into Sd/Dd.
+#[inline(always)]
+fn fsub_freg_freg_freg(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    src1: AArch64FloatReg,
+    src2: AArch64FloatReg,
+) {
+    let inst =
+        FloatingPointDataProcessingTwoSource::new(FloatingPointDataProcessingTwoSourceParams {
+            opcode: 0b0011,
+            ptype: ftype,
+            rd: dst,
+            rn: src1,
+            rm: src2,
+        });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDR Sd/Dd, [Xn, #offset]` -> Load Xn + Offset into Sd/Dd. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn ldr_freg_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = LoadStoreRegisterImmediate::new_load(LoadStoreRegisterImmediateParams {
+        size: 0b10 | encode_float_width(ftype),
+        imm12,
+        rn: base,
+        rt: dst.into(),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `LDUR Sd/Dd, [Xn, #offset]` -> Load Xn + Offset into Sd/Dd. ZRSP is SP.
+/// Note: imm9 is the unscaled, signed offset.
+#[inline(always)]
+fn ldur_freg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    dst: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst =
+        LoadStoreRegisterUnscaledImmediate::new_load(LoadStoreRegisterUnscaledImmediateParams {
+            size: 0b10 | encode_float_width(ftype),
+            imm9,
+            rn: base,
+            rt: dst.into(),
+        });
+
+    buf.extend(inst.bytes());
+}
+
 /// Currently, we're only using MOVI to set a float register to 0.0.
 /// `MOVI Dd, #0.0` -> Move 0.0 to Dd
 #[inline(always)]
@@ -3070,17 +4484,59 @@ fn scvtf_freg_reg64(
 ) {
     let inst = ConversionBetweenFloatingPointAndInteger::new(
         ConversionBetweenFloatingPointAndIntegerParams {
+            sf: true,
             opcode: 0b010,
             rmode: 0b00,
             ptype: ftype,
-            rd: dst,
-            rn: src,
+            rd: dst.into(),
+            rn: src.into(),
         },
     );
 
     buf.extend(inst.bytes());
 }
 
+/// `STR Sd/Dd, [Xn, #offset]` -> Store Sd/Dd to Xn + Offset. ZRSP is SP.
+/// Note: imm12 is the offset divided by the width in bytes.
+#[inline(always)]
+fn str_freg_reg64_imm12(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    src: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm12: u16,
+) {
+    let inst = LoadStoreRegisterImmediate::new_store(LoadStoreRegisterImmediateParams {
+        size: 0b10 | encode_float_width(ftype),
+        imm12,
+        rn: base,
+        rt: src.into(),
+    });
+
+    buf.extend(inst.bytes());
+}
+
+/// `STUR Sd/Dd, [Xn, #offset]` -> Store Sd/Dd to Xn + Offset. ZRSP is SP.
+/// Note: imm9 is the unscaled, signed offset.
+#[inline(always)]
+fn stur_freg_reg64_imm9(
+    buf: &mut Vec<'_, u8>,
+    ftype: FloatWidth,
+    src: AArch64FloatReg,
+    base: AArch64GeneralReg,
+    imm9: i16,
+) {
+    let inst =
+        LoadStoreRegisterUnscaledImmediate::new_store(LoadStoreRegisterUnscaledImmediateParams {
+            size: 0b10 | encode_float_width(ftype),
+            imm9,
+            rn: base,
+            rt: src.into(),
+        });
+
+    buf.extend(inst.bytes());
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -3109,6 +4565,40 @@ mod tests {
                 _ => format!("{}", self),
             }
         }
+
+        fn capstone_string_32bit(&self, zrsp_kind: ZRSPKind) -> String {
+            match self {
+                AArch64GeneralReg::ZRSP => match zrsp_kind {
+                    UsesZR => "wzr".to_owned(),
+                    UsesSP => "wsp".to_owned(),
+                },
+                _ => format!("w{}", self.id()),
+            }
+        }
+
+        fn capstone_string_for_width(&self, width: RegisterWidth, zrsp_kind: ZRSPKind) -> String {
+            match width {
+                RegisterWidth::W64 => self.capstone_string(zrsp_kind),
+                _ => self.capstone_string_32bit(zrsp_kind),
+            }
+        }
+    }
+
+    fn signed_hex(imm: i64) -> String {
+        if imm < 0 {
+            format!("-0x{:x}", -imm)
+        } else {
+            format!("0x{:x}", imm)
+        }
+    }
+
+    fn width_suffix(width: RegisterWidth) -> &'static str {
+        match width {
+            RegisterWidth::W8 => "b",
+            RegisterWidth::W16 => "h",
+            RegisterWidth::W32 => "w",
+            RegisterWidth::W64 => "",
+        }
     }
 
     impl AArch64FloatReg {
@@ -3196,6 +4686,30 @@ mod tests {
 
     const ALL_FLOAT_TYPES: &[FloatWidth] = &[FloatWidth::F32, FloatWidth::F64];
 
+    const ALL_REGISTER_WIDTHS: &[RegisterWidth] = &[
+        RegisterWidth::W8,
+        RegisterWidth::W16,
+        RegisterWidth::W32,
+        RegisterWidth::W64,
+    ];
+
+    const EXTEND_REGISTER_WIDTHS: &[RegisterWidth] =
+        &[RegisterWidth::W8, RegisterWidth::W16, RegisterWidth::W32];
+
+    // Pair instructions with writeback are unpredictable when registers overlap,
+    // so these lists are kept disjoint.
+    const PAIR_FIRST_REGS: &[AArch64GeneralReg] = &[
+        AArch64GeneralReg::X0,
+        AArch64GeneralReg::X19,
+        AArch64GeneralReg::FP,
+    ];
+    const PAIR_SECOND_REGS: &[AArch64GeneralReg] = &[
+        AArch64GeneralReg::X1,
+        AArch64GeneralReg::X20,
+        AArch64GeneralReg::LR,
+    ];
+    const PAIR_BASE_REGS: &[AArch64GeneralReg] = &[AArch64GeneralReg::X9, AArch64GeneralReg::ZRSP];
+
     const ALL_CONDITIONS: &[ConditionCode] = &[
         ConditionCode::EQ,
         ConditionCode::NE,
@@ -3244,16 +4758,32 @@ mod tests {
             ),
             ALL_GENERAL_REGS,
             ALL_GENERAL_REGS,
-            ALL_GENERAL_REGS
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_add_reg64_reg64_imm12() {
+        disassembler_test!(
+            add_reg64_reg64_imm12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "add {}, {}, #0x{:x}",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
         );
     }
 
     #[test]
-    fn test_add_reg64_reg64_imm12() {
+    fn test_add_reg64_reg64_imm12_lsl12() {
         disassembler_test!(
-            add_reg64_reg64_imm12,
+            add_reg64_reg64_imm12_lsl12,
             |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
-                "add {}, {}, #0x{:x}",
+                "add {}, {}, #0x{:x}, lsl #12",
                 reg1.capstone_string(UsesSP),
                 reg2.capstone_string(UsesSP),
                 imm
@@ -3264,6 +4794,48 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_adds_reg64_reg64_reg64() {
+        disassembler_test!(
+            adds_reg64_reg64_reg64,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, reg3: AArch64GeneralReg| {
+                if reg1 == AArch64GeneralReg::ZRSP {
+                    // When the destination is ZR, it gets disassembled as cmn,
+                    // which is an alias for adds.
+                    format!(
+                        "cmn {}, {}",
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR)
+                    )
+                } else {
+                    format!(
+                        "adds {}, {}, {}",
+                        reg1.capstone_string(UsesZR),
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR)
+                    )
+                }
+            },
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_adrp_reg64_imm21() {
+        disassembler_test!(
+            adrp_reg64_imm21,
+            |reg1: AArch64GeneralReg, imm: i32| format!(
+                "adrp {}, #0x{:x}",
+                reg1.capstone_string(UsesZR),
+                (imm as i64) << 12
+            ),
+            ALL_GENERAL_REGS,
+            [0x123, -0x123, (1 << 20) - 1, -(1 << 20)]
+        );
+    }
+
     #[test]
     fn test_and_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3315,6 +4887,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bl_imm26() {
+        disassembler_test!(
+            bl_imm26,
+            |imm| format!("bl #0x{:x}", imm as i64),
+            [0x120, -0x120, (1 << 27) - 4, -(1 << 27)]
+        );
+    }
+
     #[test]
     fn test_cmp_reg64_imm12() {
         disassembler_test!(
@@ -3481,21 +5062,113 @@ mod tests {
     }
 
     #[test]
-    fn test_ldr_reg64_reg64_imm12() {
+    fn test_ldp_reg64_reg64_reg64_imm7() {
         disassembler_test!(
-            ldr_reg64_reg64_imm12,
-            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
-                "ldr {}, [{}, #0x{:x}]",
-                reg1.capstone_string(UsesZR),
-                reg2.capstone_string(UsesSP),
-                imm << 3
-            ),
+            ldp_reg64_reg64_reg64_imm7,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, reg3: AArch64GeneralReg, imm: i8| {
+                format!(
+                    "ldp {}, {}, [{}], #{}",
+                    reg1.capstone_string(UsesZR),
+                    reg2.capstone_string(UsesZR),
+                    reg3.capstone_string(UsesSP),
+                    signed_hex((imm as i64) << 3)
+                )
+            },
+            PAIR_FIRST_REGS,
+            PAIR_SECOND_REGS,
+            PAIR_BASE_REGS,
+            [2, -2, 63, -64]
+        );
+    }
+
+    #[test]
+    fn test_ldr_reg_reg64_imm12() {
+        disassembler_test!(
+            ldr_reg_reg64_imm12,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: u16| {
+                let suffix = match width {
+                    RegisterWidth::W32 => "",
+                    _ => width_suffix(width),
+                };
+                format!(
+                    "ldr{} {}, [{}, #0x{:x}]",
+                    suffix,
+                    reg1.capstone_string_for_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    imm << encode_register_width(width)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_ldrs_reg64_reg64_imm12() {
+        disassembler_test!(
+            ldrs_reg64_reg64_imm12,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: u16| {
+                format!(
+                    "ldrs{} {}, [{}, #0x{:x}]",
+                    width_suffix(width),
+                    reg1.capstone_string(UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    imm << encode_register_width(width)
+                )
+            },
+            EXTEND_REGISTER_WIDTHS,
             ALL_GENERAL_REGS,
             ALL_GENERAL_REGS,
             [0x123]
         );
     }
 
+    #[test]
+    fn test_ldur_reg_reg64_imm9() {
+        disassembler_test!(
+            ldur_reg_reg64_imm9,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i16| {
+                let suffix = match width {
+                    RegisterWidth::W32 => "",
+                    _ => width_suffix(width),
+                };
+                format!(
+                    "ldur{} {}, [{}, #{}]",
+                    suffix,
+                    reg1.capstone_string_for_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    signed_hex(imm as i64)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x12, -0x12, 0xff, -0x100]
+        );
+    }
+
+    #[test]
+    fn test_ldurs_reg64_reg64_imm9() {
+        disassembler_test!(
+            ldurs_reg64_reg64_imm9,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i16| {
+                format!(
+                    "ldurs{} {}, [{}, #{}]",
+                    width_suffix(width),
+                    reg1.capstone_string(UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    signed_hex(imm as i64)
+                )
+            },
+            EXTEND_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x12, -0x12, 0xff, -0x100]
+        );
+    }
+
     #[test]
     fn test_lsl_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3610,6 +5283,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_msub_reg64_reg64_reg64_reg64() {
+        disassembler_test!(
+            msub_reg64_reg64_reg64_reg64,
+            |reg1: AArch64GeneralReg,
+             reg2: AArch64GeneralReg,
+             reg3: AArch64GeneralReg,
+             reg4: AArch64GeneralReg| {
+                if reg4 == AArch64GeneralReg::ZRSP {
+                    format!(
+                        "mneg {}, {}, {}",
+                        reg1.capstone_string(UsesZR),
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR)
+                    )
+                } else {
+                    format!(
+                        "msub {}, {}, {}, {}",
+                        reg1.capstone_string(UsesZR),
+                        reg2.capstone_string(UsesZR),
+                        reg3.capstone_string(UsesZR),
+                        reg4.capstone_string(UsesZR)
+                    )
+                }
+            },
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     #[test]
     fn test_mul_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3683,21 +5388,73 @@ mod tests {
     }
 
     #[test]
-    fn test_str_reg64_reg64_imm12() {
+    fn test_stp_reg64_reg64_reg64_imm7() {
         disassembler_test!(
-            str_reg64_reg64_imm12,
-            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
-                "str {}, [{}, #0x{:x}]",
-                reg1.capstone_string(UsesZR),
-                reg2.capstone_string(UsesSP),
-                imm << 3
-            ),
+            stp_reg64_reg64_reg64_imm7,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, reg3: AArch64GeneralReg, imm: i8| {
+                format!(
+                    "stp {}, {}, [{}, #{}]!",
+                    reg1.capstone_string(UsesZR),
+                    reg2.capstone_string(UsesZR),
+                    reg3.capstone_string(UsesSP),
+                    signed_hex((imm as i64) << 3)
+                )
+            },
+            PAIR_FIRST_REGS,
+            PAIR_SECOND_REGS,
+            PAIR_BASE_REGS,
+            [2, -2, 63, -64]
+        );
+    }
+
+    #[test]
+    fn test_str_reg_reg64_imm12() {
+        disassembler_test!(
+            str_reg_reg64_imm12,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: u16| {
+                let suffix = match width {
+                    RegisterWidth::W32 => "",
+                    _ => width_suffix(width),
+                };
+                format!(
+                    "str{} {}, [{}, #0x{:x}]",
+                    suffix,
+                    reg1.capstone_string_for_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    imm << encode_register_width(width)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
             ALL_GENERAL_REGS,
             ALL_GENERAL_REGS,
             [0x123]
         );
     }
 
+    #[test]
+    fn test_stur_reg_reg64_imm9() {
+        disassembler_test!(
+            stur_reg_reg64_imm9,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm: i16| {
+                let suffix = match width {
+                    RegisterWidth::W32 => "",
+                    _ => width_suffix(width),
+                };
+                format!(
+                    "stur{} {}, [{}, #{}]",
+                    suffix,
+                    reg1.capstone_string_for_width(width, UsesZR),
+                    reg2.capstone_string(UsesSP),
+                    signed_hex(imm as i64)
+                )
+            },
+            ALL_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x12, -0x12, 0xff, -0x100]
+        );
+    }
+
     #[test]
     fn test_sub_reg64_reg64_imm12() {
         disassembler_test!(
@@ -3714,6 +5471,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sub_reg64_reg64_imm12_lsl12() {
+        disassembler_test!(
+            sub_reg64_reg64_imm12_lsl12,
+            |reg1: AArch64GeneralReg, reg2: AArch64GeneralReg, imm| format!(
+                "sub {}, {}, #0x{:x}, lsl #12",
+                reg1.capstone_string(UsesSP),
+                reg2.capstone_string(UsesSP),
+                imm
+            ),
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
     #[test]
     fn test_sub_reg64_reg64_reg64() {
         disassembler_test!(
@@ -3803,6 +5576,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_sxt_reg64_reg64() {
+        disassembler_test!(
+            sxt_reg64_reg64,
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg| format!(
+                "sxt{} {}, {}",
+                width_suffix(width),
+                reg1.capstone_string(UsesZR),
+                reg2.capstone_string_32bit(UsesZR)
+            ),
+            EXTEND_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     #[test]
     fn test_ret_reg64() {
         disassembler_test!(
@@ -3832,6 +5621,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_uxt_reg64_reg64() {
+        disassembler_test!(
+            uxt_reg64_reg64,
+            // The 64 bit UBFM form has no UXT alias, so it disassembles as UBFX.
+            |width: RegisterWidth, reg1: AArch64GeneralReg, reg2: AArch64GeneralReg| format!(
+                "ubfx {}, {}, #0, #{}",
+                reg1.capstone_string(UsesZR),
+                reg2.capstone_string(UsesZR),
+                8 << encode_register_width(width)
+            ),
+            EXTEND_REGISTER_WIDTHS,
+            ALL_GENERAL_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
     // Float instructions
 
     #[test]
@@ -4097,6 +5903,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fmov_freg_reg() {
+        disassembler_test!(
+            fmov_freg_reg,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg| format!(
+                "fmov {}, {}",
+                reg1.capstone_string(ftype),
+                match ftype {
+                    FloatWidth::F32 => reg2.capstone_string_32bit(UsesZR),
+                    FloatWidth::F64 => reg2.capstone_string(UsesZR),
+                }
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS
+        );
+    }
+
+    #[test]
+    fn test_fmov_reg_freg() {
+        disassembler_test!(
+            fmov_reg_freg,
+            |ftype: FloatWidth, reg1: AArch64GeneralReg, reg2: AArch64FloatReg| format!(
+                "fmov {}, {}",
+                match ftype {
+                    FloatWidth::F32 => reg1.capstone_string_32bit(UsesZR),
+                    FloatWidth::F64 => reg1.capstone_string(UsesZR),
+                },
+                reg2.capstone_string(ftype)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_GENERAL_REGS,
+            ALL_FLOAT_REGS
+        );
+    }
+
     #[test]
     fn test_fmul_freg_freg_freg() {
         disassembler_test!(
@@ -4132,6 +5974,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_fsub_freg_freg_freg() {
+        disassembler_test!(
+            fsub_freg_freg_freg,
+            |ftype: FloatWidth,
+             reg1: AArch64FloatReg,
+             reg2: AArch64FloatReg,
+             reg3: AArch64FloatReg| format!(
+                "fsub {}, {}, {}",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(ftype),
+                reg3.capstone_string(ftype)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_FLOAT_REGS,
+            ALL_FLOAT_REGS
+        );
+    }
+
+    #[test]
+    fn test_ldr_freg_reg64_imm12() {
+        disassembler_test!(
+            ldr_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: u16| format!(
+                "ldr {}, [{}, #0x{:x}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                imm as u32 * ftype.stack_size()
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_ldur_freg_reg64_imm9() {
+        disassembler_test!(
+            ldur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: i16| format!(
+                "ldur {}, [{}, #{}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                signed_hex(imm as i64)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0x12, -0x12, 0xff, -0x100]
+        );
+    }
+
     #[test]
     fn test_movi_freg_zero() {
         disassembler_test!(
@@ -4158,4 +6054,38 @@ mod tests {
             ALL_GENERAL_REGS
         );
     }
+
+    #[test]
+    fn test_str_freg_reg64_imm12() {
+        disassembler_test!(
+            str_freg_reg64_imm12,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: u16| format!(
+                "str {}, [{}, #0x{:x}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                imm as u32 * ftype.stack_size()
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0x123]
+        );
+    }
+
+    #[test]
+    fn test_stur_freg_reg64_imm9() {
+        disassembler_test!(
+            stur_freg_reg64_imm9,
+            |ftype: FloatWidth, reg1: AArch64FloatReg, reg2: AArch64GeneralReg, imm: i16| format!(
+                "stur {}, [{}, #{}]",
+                reg1.capstone_string(ftype),
+                reg2.capstone_string(UsesSP),
+                signed_hex(imm as i64)
+            ),
+            ALL_FLOAT_TYPES,
+            ALL_FLOAT_REGS,
+            ALL_GENERAL_REGS,
+            [0x12, -0x12, 0xff, -0x100]
+        );
+    }
 }
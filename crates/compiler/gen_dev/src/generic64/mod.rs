@@ -1,6 +1,7 @@
+use crate::liveness::{JoinPointLiveness, Liveness};
 use crate::{
     single_register_floats, single_register_int_builtins, single_register_integers, Backend, Env,
-    Relocation,
+    ProcFrame, Relocation,
 };
 use bumpalo::collections::{CollectIn, Vec};
 use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
@@ -76,13 +77,15 @@ pub trait CallConv<GeneralReg: RegTrait, FloatReg: RegTrait, ASM: Assembler<Gene
         !Self::float_callee_saved(reg)
     }
 
-    fn setup_stack(
-        buf: &mut Vec<'_, u8>,
+    /// setup_stack emits the prologue and describes the stack frame it sets up.
+    /// The `stack_size` of the frame is the aligned stack size passed to `cleanup_stack`.
+    fn setup_stack<'a>(
+        buf: &mut Vec<'a, u8>,
         general_saved_regs: &[GeneralReg],
         float_saved_regs: &[FloatReg],
         requested_stack_size: i32,
         fn_call_stack_size: i32,
-    ) -> i32;
+    ) -> ProcFrame<'a>;
     fn cleanup_stack(
         buf: &mut Vec<'_, u8>,
         general_saved_regs: &[GeneralReg],
@@ -156,6 +159,12 @@ pub trait Assembler<GeneralReg: RegTrait, FloatReg: RegTrait>: Sized + Copy {
         dst: FloatReg,
         src: FloatReg,
     );
+    fn abs_freg32_freg32(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        dst: FloatReg,
+        src: FloatReg,
+    );
 
     fn add_reg64_reg64_imm32(buf: &mut Vec<'_, u8>, dst: GeneralReg, src1: GeneralReg, imm32: i32);
     fn add_freg32_freg32_freg32(
@@ -485,6 +494,18 @@ pub trait Assembler<GeneralReg: RegTrait, FloatReg: RegTrait>: Sized + Copy {
         src1: GeneralReg,
         src2: GeneralReg,
     );
+    fn sub_freg32_freg32_freg32(
+        buf: &mut Vec<'_, u8>,
+        dst: FloatReg,
+        src1: FloatReg,
+        src2: FloatReg,
+    );
+    fn sub_freg64_freg64_freg64(
+        buf: &mut Vec<'_, u8>,
+        dst: FloatReg,
+        src1: FloatReg,
+        src2: FloatReg,
+    );
 
     fn eq_reg_reg_reg(
         buf: &mut Vec<'_, u8>,
@@ -581,6 +602,8 @@ pub struct Backend64Bit<
     relocs: Vec<'a, Relocation>,
     proc_name: Option<String>,
     is_self_recursive: Option<SelfRecursive>,
+    /// Allocations of the current proc that live in its stack frame
+    stack_allocations: &'a [Symbol],
 
     last_seen_map: MutMap<Symbol, *const Stmt<'a>>,
     layout_map: MutMap<Symbol, InLayout<'a>>,
@@ -588,6 +611,8 @@ pub struct Backend64Bit<
 
     literal_map: MutMap<Symbol, (*const Literal<'a>, *const InLayout<'a>)>,
     join_map: MutMap<JoinPointId, Vec<'a, (u64, u64)>>,
+    join_liveness: MutMap<JoinPointId, JoinPointLiveness<'a>>,
+    stmt_positions: MutMap<*const Stmt<'a>, u32>,
 
     storage_manager: StorageManager<'a, 'r, GeneralReg, FloatReg, ASM, CC>,
 }
@@ -617,6 +642,7 @@ pub fn new_backend_64bit<
         caller_procs: bumpalo::vec![in env.arena],
         proc_name: None,
         is_self_recursive: None,
+        stack_allocations: &[],
         buf: bumpalo::vec![in env.arena],
         relocs: bumpalo::vec![in env.arena],
         last_seen_map: MutMap::default(),
@@ -624,6 +650,8 @@ pub fn new_backend_64bit<
         free_map: MutMap::default(),
         literal_map: MutMap::default(),
         join_map: MutMap::default(),
+        join_liveness: MutMap::default(),
+        stmt_positions: MutMap::default(),
         storage_manager: storage::new_storage_manager(env, target_info),
     }
 }
@@ -689,17 +717,39 @@ impl<
         &self.helper_proc_symbols
     }
 
-    fn reset(&mut self, name: String, is_self_recursive: SelfRecursive) {
+    fn reset(
+        &mut self,
+        name: String,
+        is_self_recursive: SelfRecursive,
+        stack_allocations: &'a [Symbol],
+    ) {
         self.proc_name = Some(name);
         self.is_self_recursive = Some(is_self_recursive);
+        self.stack_allocations = stack_allocations;
         self.last_seen_map.clear();
         self.layout_map.clear();
         self.join_map.clear();
+        self.join_liveness.clear();
+        self.stmt_positions.clear();
         self.free_map.clear();
         self.buf.clear();
         self.storage_manager.reset();
     }
 
+    fn set_liveness(&mut self, liveness: Liveness<'a>) {
+        self.storage_manager
+            .set_uses(liveness.last_use, liveness.uses);
+        self.stmt_positions = liveness.positions;
+        self.join_liveness = liveness.join_points;
+    }
+
+    fn enter_stmt(&mut self, stmt: &Stmt<'a>) {
+        // statements that the backend expands itself, e.g. refcounting, are not in the analysis
+        if let Some(position) = self.stmt_positions.get(&(stmt as *const _)) {
+            self.storage_manager.set_position(*position);
+        }
+    }
+
     fn literal_map(&mut self) -> &mut MutMap<Symbol, (*const Literal<'a>, *const InLayout<'a>)> {
         &mut self.literal_map
     }
@@ -720,13 +770,13 @@ impl<
         &mut self.free_map
     }
 
-    fn finalize(&mut self) -> (Vec<u8>, Vec<Relocation>) {
+    fn finalize(&mut self) -> (Vec<u8>, Vec<Relocation>, ProcFrame<'a>) {
         let mut out = bumpalo::vec![in self.env.arena];
 
         // Setup stack.
         let used_general_regs = self.storage_manager.general_used_callee_saved_regs();
         let used_float_regs = self.storage_manager.float_used_callee_saved_regs();
-        let aligned_stack_size = CC::setup_stack(
+        let mut frame = CC::setup_stack(
             &mut out,
             &used_general_regs,
             &used_float_regs,
@@ -784,9 +834,10 @@ impl<
             &mut out,
             &used_general_regs,
             &used_float_regs,
-            aligned_stack_size,
+            frame.stack_size as i32,
             self.storage_manager.fn_call_stack_size() as i32,
         );
+        frame.frame_pointer_restored = out.len() as u32;
         ASM::ret(&mut out);
 
         // Update other relocs to include stack setup offset.
@@ -811,7 +862,7 @@ impl<
                     Relocation::JmpToReturn { .. } => unreachable!(),
                 }),
         );
-        (out, out_relocs)
+        (out, out_relocs, frame)
     }
 
     fn load_args(&mut self, args: &'a [(InLayout<'a>, Symbol)], ret_layout: &InLayout<'a>) {
@@ -880,7 +931,7 @@ impl<
                 let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
                 ASM::mov_freg64_freg64(&mut self.buf, dst_reg, CC::FLOAT_RETURN_REGS[0]);
             }
-            Layout::I128 | Layout::U128 => {
+            Layout::I128 | Layout::U128 | Layout::DEC => {
                 let offset = self.storage_manager.claim_stack_area(dst, 16);
 
                 ASM::mov_base32_reg64(&mut self.buf, offset, CC::GENERAL_RETURN_REGS[0]);
@@ -931,7 +982,6 @@ impl<
         let mut base_storage = self.storage_manager.clone();
         let base_literal_map = self.literal_map.clone();
 
-        let mut max_branch_stack_size = 0;
         let mut ret_jumps = bumpalo::vec![in self.env.arena];
         let mut tmp = bumpalo::vec![in self.env.arena];
         for (val, _branch_info, stmt) in branches.iter() {
@@ -950,7 +1000,7 @@ impl<
             // Build unconditional jump to the end of this switch.
             // Since we don't know the offset yet, set it to 0 and overwrite later.
             let jmp_location = self.buf.len();
-            let jmp_offset = ASM::jmp_imm32(&mut self.buf, 0x1234_5678);
+            let jmp_offset = ASM::jmp_imm32(&mut self.buf, 0);
             ret_jumps.push((jmp_location, jmp_offset));
 
             // Overwrite the original jne with the correct offset.
@@ -962,14 +1012,10 @@ impl<
             }
 
             // Update important storage information to avoid overwrites.
-            max_branch_stack_size =
-                std::cmp::max(max_branch_stack_size, self.storage_manager.stack_size());
-            base_storage.update_fn_call_stack_size(self.storage_manager.fn_call_stack_size());
+            base_storage.merge_branch_state(&self.storage_manager);
         }
         self.storage_manager = base_storage;
         self.literal_map = base_literal_map;
-        self.storage_manager
-            .update_stack_size(max_branch_stack_size);
         let (_branch_info, stmt) = default_branch;
         self.build_stmt(stmt, ret_layout);
 
@@ -993,24 +1039,48 @@ impl<
         remainder: &'a Stmt<'a>,
         ret_layout: &InLayout<'a>,
     ) {
-        // Free everything to the stack to make sure they don't get messed up when looping back to this point.
-        // TODO: look into a nicer solution.
-        self.storage_manager.free_all_to_stack(&mut self.buf);
+        let JoinPointLiveness { is_loop, live_in } =
+            self.join_liveness
+                .remove(id)
+                .unwrap_or_else(|| JoinPointLiveness {
+                    is_loop: false,
+                    live_in: bumpalo::vec![in self.env.arena],
+                });
+
+        // Values the body needs from before the join point live on the stack,
+        // so every jump to the body finds them in the same place.
+        self.storage_manager.spill_to_stack(&mut self.buf, &live_in);
 
         // Ensure all the joinpoint parameters have storage locations.
         // On jumps to the joinpoint, we will overwrite those locations as a way to "pass parameters" to the joinpoint.
-        self.storage_manager
-            .setup_joinpoint(self.layout_interner, &mut self.buf, id, parameters);
+        self.storage_manager.setup_joinpoint(
+            self.layout_interner,
+            &mut self.buf,
+            id,
+            parameters,
+            is_loop,
+        );
 
         self.join_map.insert(*id, bumpalo::vec![in self.env.arena]);
 
+        // The body starts from this state, not from wherever the remainder ends up.
+        // Only the values it can refer to keep their registers.
+        let mut live = live_in;
+        live.extend(parameters.iter().map(|param| param.symbol));
+        let mut body_storage = self.storage_manager.clone();
+        body_storage.release_registers_except(&live);
+        let body_literal_map = self.literal_map.clone();
+
         // Build remainder of function first. It is what gets run and jumps to join.
         self.build_stmt(remainder, ret_layout);
 
         let join_location = self.buf.len() as u64;
 
         // Build all statements in body.
+        let remainder_storage = std::mem::replace(&mut self.storage_manager, body_storage);
+        self.literal_map = body_literal_map;
         self.build_stmt(body, ret_layout);
+        self.storage_manager.merge_branch_state(&remainder_storage);
 
         // Overwrite the all jumps to the joinpoint with the correct offset.
         let mut tmp = bumpalo::vec![in self.env.arena];
@@ -1035,7 +1105,7 @@ impl<
             .setup_jump(self.layout_interner, &mut self.buf, id, args, arg_layouts);
 
         let jmp_location = self.buf.len();
-        let start_offset = ASM::jmp_imm32(&mut self.buf, 0x1234_5678);
+        let start_offset = ASM::jmp_imm32(&mut self.buf, 0);
 
         if let Some(vec) = self.join_map.get_mut(id) {
             vec.push((jmp_location as u64, start_offset as u64))
@@ -1045,18 +1115,95 @@ impl<
     }
 
     fn build_num_abs(&mut self, dst: &Symbol, src: &Symbol, layout: &InLayout<'a>) {
-        match self.interner().get(*layout) {
-            Layout::Builtin(Builtin::Int(IntWidth::I64 | IntWidth::U64)) => {
+        match *layout {
+            Layout::I64 => {
                 let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
                 ASM::abs_reg64_reg64(&mut self.buf, dst_reg, src_reg);
             }
-            Layout::Builtin(Builtin::Float(FloatWidth::F64)) => {
+            Layout::I8 | Layout::I16 | Layout::I32 => {
+                let width = RegisterWidth::try_from_layout(*layout).unwrap();
+
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
+                let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
+
+                // the upper bits of the register are not guaranteed to match the sign
+                self.storage_manager
+                    .with_tmp_general_reg(&mut self.buf, |_, buf, tmp_reg| {
+                        ASM::movsx_reg_reg(buf, width, tmp_reg, src_reg);
+                        ASM::abs_reg64_reg64(buf, dst_reg, tmp_reg);
+                    });
+            }
+            Layout::U8 | Layout::U16 | Layout::U32 | Layout::U64 => {
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
+                let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
+                ASM::mov_reg64_reg64(&mut self.buf, dst_reg, src_reg);
+            }
+            Layout::I128 | Layout::DEC => {
+                let lo_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+                let hi_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP2);
+                let mask_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP3);
+                let tmp_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP4);
+
+                self.load_int128_words(src, lo_reg, hi_reg);
+
+                // mask is all ones for negative numbers and zero otherwise;
+                // abs(x) is then (x ^ mask) - mask
+                ASM::mov_reg64_imm64(&mut self.buf, tmp_reg, 0);
+                ASM::signed_compare_reg64(
+                    &mut self.buf,
+                    RegisterWidth::W64,
+                    CompareOperation::LessThan,
+                    mask_reg,
+                    hi_reg,
+                    tmp_reg,
+                );
+                ASM::neg_reg64_reg64(&mut self.buf, mask_reg, mask_reg);
+
+                ASM::xor_reg64_reg64_reg64(&mut self.buf, lo_reg, lo_reg, mask_reg);
+                ASM::xor_reg64_reg64_reg64(&mut self.buf, hi_reg, hi_reg, mask_reg);
+                self.add_or_sub_reg128(true, lo_reg, hi_reg, mask_reg, mask_reg, tmp_reg);
+
+                self.store_int128_words(dst, lo_reg, hi_reg);
+
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+                self.free_symbol(&Symbol::DEV_TMP3);
+                self.free_symbol(&Symbol::DEV_TMP4);
+            }
+            Layout::U128 => {
+                let lo_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+                let hi_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP2);
+
+                self.load_int128_words(src, lo_reg, hi_reg);
+                self.store_int128_words(dst, lo_reg, hi_reg);
+
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+            }
+            Layout::F64 => {
                 let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
                 let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
                 ASM::abs_freg64_freg64(&mut self.buf, &mut self.relocs, dst_reg, src_reg);
             }
-            x => todo!("NumAbs: layout, {:?}", x),
+            Layout::F32 => {
+                let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
+                let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
+                ASM::abs_freg32_freg32(&mut self.buf, &mut self.relocs, dst_reg, src_reg);
+            }
+            x => internal_error!("NumAbs is not defined for {:?}", x),
         }
     }
 
@@ -1084,6 +1231,20 @@ impl<
                 let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
                 ASM::add_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
+            Layout::Builtin(Builtin::Int(IntWidth::I128 | IntWidth::U128)) => {
+                self.build_int128_add_or_sub(false, dst, src1, src2);
+            }
+            Layout::Builtin(Builtin::Decimal) => {
+                // for the time being, `num_add` on integers is implemented as wrapping addition,
+                // but decimals have no wrapping variant, so we always use the panicking one
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_ADD_OR_PANIC.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
             x => todo!("NumAdd: layout, {:?}", x),
         }
     }
@@ -1098,14 +1259,14 @@ impl<
     ) {
         use Builtin::Int;
 
-        let buf = &mut self.buf;
+        let function_name = match self.interner().get(*num_layout) {
+            Layout::Builtin(Int(IntWidth::I64)) => {
+                let buf = &mut self.buf;
 
-        let struct_size = self.layout_interner.stack_size(*return_layout);
+                let struct_size = self.layout_interner.stack_size(*return_layout);
 
-        let base_offset = self.storage_manager.claim_stack_area(dst, struct_size);
+                let base_offset = self.storage_manager.claim_stack_area(dst, struct_size);
 
-        match self.layout_interner.get(*num_layout) {
-            Layout::Builtin(Int(IntWidth::I64 | IntWidth::I32 | IntWidth::I16 | IntWidth::I8)) => {
                 let dst_reg = self
                     .storage_manager
                     .claim_general_reg(buf, &Symbol::DEV_TMP);
@@ -1125,18 +1286,24 @@ impl<
 
                 self.free_symbol(&Symbol::DEV_TMP);
                 self.free_symbol(&Symbol::DEV_TMP2);
+
+                return;
             }
-            Layout::Builtin(Int(IntWidth::U64 | IntWidth::U32 | IntWidth::U16 | IntWidth::U8)) => {
-                todo!("addChecked for unsigned integers")
-            }
-            Layout::Builtin(Builtin::Float(FloatWidth::F64)) => {
-                todo!("addChecked for f64")
-            }
-            Layout::Builtin(Builtin::Float(FloatWidth::F32)) => {
-                todo!("addChecked for f32")
-            }
-            x => todo!("NumAdd: layout, {:?}", x),
-        }
+            // the overflow flag of a 64-bit addition says nothing about narrower integers,
+            // so everything else goes through zig
+            Layout::Builtin(Int(width)) => &bitcode::NUM_ADD_CHECKED_INT[width],
+            Layout::Builtin(Builtin::Float(width)) => &bitcode::NUM_ADD_CHECKED_FLOAT[width],
+            Layout::Builtin(Builtin::Decimal) => bitcode::DEC_ADD_WITH_OVERFLOW,
+            x => internal_error!("NumAddChecked is not defined for {:?}", x),
+        };
+
+        self.build_fn_call(
+            dst,
+            function_name.to_string(),
+            &[*src1, *src2],
+            &[*num_layout, *num_layout],
+            return_layout,
+        )
     }
 
     fn build_num_sub_checked(
@@ -1221,6 +1388,15 @@ impl<
                 let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
                 ASM::mul_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
+            Layout::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_MUL_OR_PANIC.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
             x => todo!("NumMul: layout, {:?}", x),
         }
     }
@@ -1277,6 +1453,35 @@ impl<
                 let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
                 ASM::div_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
+            Layout::Builtin(Builtin::Int(IntWidth::I128)) => {
+                // from compiler_rt
+                self.build_fn_call(
+                    dst,
+                    "__divti3".to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            Layout::Builtin(Builtin::Int(IntWidth::U128)) => {
+                // from compiler_rt
+                self.build_fn_call(
+                    dst,
+                    "__udivti3".to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            Layout::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_DIV.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
             x => todo!("NumDiv: layout, {:?}", x),
         }
     }
@@ -1321,25 +1526,127 @@ impl<
                     src2_reg,
                 );
             }
-            x => todo!("NumDiv: layout, {:?}", x),
+            Layout::Builtin(Builtin::Int(IntWidth::I128)) => {
+                // from compiler_rt
+                self.build_fn_call(
+                    dst,
+                    "__modti3".to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            Layout::Builtin(Builtin::Int(IntWidth::U128)) => {
+                // from compiler_rt
+                self.build_fn_call(
+                    dst,
+                    "__umodti3".to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            // `Num.rem` only takes integers
+            x => internal_error!("NumRem is not defined for {:?}", x),
         }
     }
 
     fn build_num_neg(&mut self, dst: &Symbol, src: &Symbol, layout: &InLayout<'a>) {
         match self.layout_interner.get(*layout) {
-            Layout::Builtin(Builtin::Int(IntWidth::I64 | IntWidth::U64)) => {
+            Layout::Builtin(Builtin::Int(quadword_and_smaller!())) => {
                 let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
                 ASM::neg_reg64_reg64(&mut self.buf, dst_reg, src_reg);
             }
+            Layout::Builtin(Builtin::Int(IntWidth::I128 | IntWidth::U128)) => {
+                let lo_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+                let hi_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP2);
+                let src_lo_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP3);
+                let src_hi_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP4);
+                let borrow_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP5);
+
+                // -x is computed as 0 - x
+                ASM::mov_reg64_imm64(&mut self.buf, lo_reg, 0);
+                ASM::mov_reg64_imm64(&mut self.buf, hi_reg, 0);
+                self.load_int128_words(src, src_lo_reg, src_hi_reg);
+                self.add_or_sub_reg128(true, lo_reg, hi_reg, src_lo_reg, src_hi_reg, borrow_reg);
+
+                self.store_int128_words(dst, lo_reg, hi_reg);
+
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+                self.free_symbol(&Symbol::DEV_TMP3);
+                self.free_symbol(&Symbol::DEV_TMP4);
+                self.free_symbol(&Symbol::DEV_TMP5);
+            }
+            Layout::Builtin(Builtin::Float(float_width)) => {
+                let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
+                let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
+
+                // -x is computed as -0.0 - x, which also gets the sign of zero right
+                match float_width {
+                    FloatWidth::F32 => {
+                        ASM::mov_freg32_imm32(&mut self.buf, &mut self.relocs, dst_reg, -0.0);
+                        ASM::sub_freg32_freg32_freg32(&mut self.buf, dst_reg, dst_reg, src_reg);
+                    }
+                    FloatWidth::F64 => {
+                        ASM::mov_freg64_imm64(&mut self.buf, &mut self.relocs, dst_reg, -0.0);
+                        ASM::sub_freg64_freg64_freg64(&mut self.buf, dst_reg, dst_reg, src_reg);
+                    }
+                }
+            }
+            Layout::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_NEGATE.to_string(),
+                    &[*src],
+                    &[*layout],
+                    layout,
+                );
+            }
             x => todo!("NumNeg: layout, {:?}", x),
         }
     }
 
     fn build_num_sub(&mut self, dst: &Symbol, src1: &Symbol, src2: &Symbol, layout: &InLayout<'a>) {
-        // for the time being, `num_sub` is implemented as wrapping subtraction. In roc, the normal
-        // `sub` should panic on overflow, but we just don't do that yet
-        self.build_num_sub_wrap(dst, src1, src2, layout)
+        match self.layout_interner.get(*layout) {
+            Layout::Builtin(Builtin::Float(FloatWidth::F64)) => {
+                let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
+                let src1_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src1);
+                let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
+                ASM::sub_freg64_freg64_freg64(&mut self.buf, dst_reg, src1_reg, src2_reg);
+            }
+            Layout::Builtin(Builtin::Float(FloatWidth::F32)) => {
+                let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
+                let src1_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src1);
+                let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
+                ASM::sub_freg32_freg32_freg32(&mut self.buf, dst_reg, src1_reg, src2_reg);
+            }
+            Layout::Builtin(Builtin::Decimal) => {
+                self.build_fn_call(
+                    dst,
+                    bitcode::DEC_SUB_OR_PANIC.to_string(),
+                    &[*src1, *src2],
+                    &[*layout, *layout],
+                    layout,
+                );
+            }
+            _ => {
+                // for the time being, `num_sub` is implemented as wrapping subtraction. In roc, the normal
+                // `sub` should panic on overflow, but we just don't do that yet
+                self.build_num_sub_wrap(dst, src1, src2, layout)
+            }
+        }
     }
 
     fn build_num_sub_wrap(
@@ -1360,6 +1667,9 @@ impl<
                     .load_to_general_reg(&mut self.buf, src2);
                 ASM::sub_reg64_reg64_reg64(&mut self.buf, dst_reg, src1_reg, src2_reg);
             }
+            Layout::Builtin(Builtin::Int(IntWidth::I128 | IntWidth::U128)) => {
+                self.build_int128_add_or_sub(true, dst, src1, src2);
+            }
             x => todo!("NumSubWrap: layout, {:?}", x),
         }
     }
@@ -1384,7 +1694,8 @@ impl<
                     .load_to_general_reg(&mut self.buf, src2);
                 ASM::eq_reg_reg_reg(&mut self.buf, width, dst_reg, src1_reg, src2_reg);
             }
-            Layout::U128 | Layout::I128 => {
+            Layout::U128 | Layout::I128 | Layout::DEC => {
+                // decimals are fixed-point, so they are equal exactly when their bits are
                 let buf = &mut self.buf;
 
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
@@ -1420,9 +1731,7 @@ impl<
                 self.storage_manager.free_symbol(&Symbol::DEV_TMP);
                 self.storage_manager.free_symbol(&Symbol::DEV_TMP2);
             }
-            Layout::F32 => todo!("NumEq: layout, {:?}", self.layout_interner.dbg(Layout::F32)),
-            Layout::F64 => todo!("NumEq: layout, {:?}", self.layout_interner.dbg(Layout::F64)),
-            Layout::DEC => todo!("NumEq: layout, {:?}", self.layout_interner.dbg(Layout::DEC)),
+            Layout::F32 | Layout::F64 => self.build_float_eq(dst, src1, src2, arg_layout, false),
             Layout::STR => {
                 // use a zig call
                 self.build_fn_call(
@@ -1493,6 +1802,36 @@ impl<
                     .load_to_general_reg(&mut self.buf, src2);
                 ASM::neq_reg64_reg64_reg64(&mut self.buf, width, dst_reg, src1_reg, src2_reg);
             }
+            Layout::U128 | Layout::I128 | Layout::DEC => {
+                let buf = &mut self.buf;
+
+                let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
+
+                let (src1_offset, _) = self.storage_manager.stack_offset_and_size(src1);
+                let (src2_offset, _) = self.storage_manager.stack_offset_and_size(src2);
+
+                let tmp1 = self
+                    .storage_manager
+                    .claim_general_reg(buf, &Symbol::DEV_TMP);
+                let tmp2 = self
+                    .storage_manager
+                    .claim_general_reg(buf, &Symbol::DEV_TMP2);
+
+                // the values differ if either of their 8-byte halves differ
+                ASM::mov_reg64_base32(buf, tmp1, src1_offset);
+                ASM::mov_reg64_base32(buf, tmp2, src2_offset);
+                ASM::neq_reg64_reg64_reg64(buf, RegisterWidth::W64, dst_reg, tmp1, tmp2);
+
+                ASM::mov_reg64_base32(buf, tmp1, src1_offset + 8);
+                ASM::mov_reg64_base32(buf, tmp2, src2_offset + 8);
+                ASM::neq_reg64_reg64_reg64(buf, RegisterWidth::W64, tmp1, tmp1, tmp2);
+
+                ASM::or_reg64_reg64_reg64(buf, dst_reg, dst_reg, tmp1);
+
+                self.storage_manager.free_symbol(&Symbol::DEV_TMP);
+                self.storage_manager.free_symbol(&Symbol::DEV_TMP2);
+            }
+            Layout::F32 | Layout::F64 => self.build_float_eq(dst, src1, src2, arg_layout, true),
             Layout::STR => {
                 self.build_fn_call(
                     dst,
@@ -1539,54 +1878,109 @@ impl<
         arg_layout: &InLayout<'a>,
         ret_layout: &InLayout<'a>,
     ) {
+        let float_width = match *ret_layout {
+            Layout::F32 => FloatWidth::F32,
+            Layout::F64 => FloatWidth::F64,
+            _ => internal_error!("NumToFrac: expected a float return layout"),
+        };
+
+        if let (Layout::DEC, FloatWidth::F32) = (*arg_layout, float_width) {
+            // there is no direct conversion, so go through f64
+            let tmp = Symbol::DEV_TMP;
+            self.build_fn_call(
+                &tmp,
+                bitcode::DEC_TO_F64.to_string(),
+                &[*src],
+                &[*arg_layout],
+                &Layout::F64,
+            );
+
+            let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
+            let tmp_reg = self.storage_manager.load_to_float_reg(&mut self.buf, &tmp);
+            ASM::to_float_freg32_freg64(&mut self.buf, dst_reg, tmp_reg);
+            self.free_symbol(&tmp);
+
+            return;
+        }
+
+        // conversions that need more than a single instruction are left to compiler_rt and zig
+        let function_name = match (*arg_layout, float_width) {
+            (Layout::U64, FloatWidth::F32) => Some("__floatundisf"),
+            (Layout::U64, FloatWidth::F64) => Some("__floatundidf"),
+            (Layout::I128, FloatWidth::F32) => Some("__floattisf"),
+            (Layout::I128, FloatWidth::F64) => Some("__floattidf"),
+            (Layout::U128, FloatWidth::F32) => Some("__floatuntisf"),
+            (Layout::U128, FloatWidth::F64) => Some("__floatuntidf"),
+            (Layout::DEC, FloatWidth::F64) => Some(bitcode::DEC_TO_F64),
+            _ => None,
+        };
+
+        if let Some(function_name) = function_name {
+            self.build_fn_call(
+                dst,
+                function_name.to_string(),
+                &[*src],
+                &[*arg_layout],
+                ret_layout,
+            );
+
+            return;
+        }
+
         let dst_reg = self.storage_manager.claim_float_reg(&mut self.buf, dst);
-        match (
-            self.layout_interner.get(*arg_layout),
-            self.layout_interner.get(*ret_layout),
-        ) {
-            (
-                Layout::Builtin(Builtin::Int(IntWidth::I32 | IntWidth::I64)),
-                Layout::Builtin(Builtin::Float(FloatWidth::F64)),
-            ) => {
+        match *arg_layout {
+            Layout::I8 | Layout::I16 | Layout::I32 | Layout::I64 => {
+                let width = RegisterWidth::try_from_layout(*arg_layout).unwrap();
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
-                ASM::to_float_freg64_reg64(&mut self.buf, dst_reg, src_reg);
+
+                // the conversion reads all 64 bits, so narrower integers must be extended first
+                self.storage_manager
+                    .with_tmp_general_reg(&mut self.buf, |_, buf, tmp_reg| {
+                        ASM::movsx_reg_reg(buf, width, tmp_reg, src_reg);
+                        match float_width {
+                            FloatWidth::F32 => ASM::to_float_freg32_reg64(buf, dst_reg, tmp_reg),
+                            FloatWidth::F64 => ASM::to_float_freg64_reg64(buf, dst_reg, tmp_reg),
+                        }
+                    });
             }
-            (
-                Layout::Builtin(Builtin::Int(IntWidth::I32 | IntWidth::I64)),
-                Layout::Builtin(Builtin::Float(FloatWidth::F32)),
-            ) => {
+            Layout::U8 | Layout::U16 | Layout::U32 => {
+                let mask = match *arg_layout {
+                    Layout::U8 => u8::MAX as i64,
+                    Layout::U16 => u16::MAX as i64,
+                    _ => u32::MAX as i64,
+                };
                 let src_reg = self.storage_manager.load_to_general_reg(&mut self.buf, src);
-                ASM::to_float_freg32_reg64(&mut self.buf, dst_reg, src_reg);
-            }
-            (
-                Layout::Builtin(Builtin::Float(FloatWidth::F64)),
-                Layout::Builtin(Builtin::Float(FloatWidth::F32)),
-            ) => {
-                let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
-                ASM::to_float_freg32_freg64(&mut self.buf, dst_reg, src_reg);
-            }
-            (
-                Layout::Builtin(Builtin::Float(FloatWidth::F32)),
-                Layout::Builtin(Builtin::Float(FloatWidth::F64)),
-            ) => {
-                let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
-                ASM::to_float_freg64_freg32(&mut self.buf, dst_reg, src_reg);
+
+                // after masking, the value fits in an i64, so a signed conversion is exact
+                self.storage_manager
+                    .with_tmp_general_reg(&mut self.buf, |_, buf, tmp_reg| {
+                        ASM::mov_reg64_imm64(buf, tmp_reg, mask);
+                        ASM::and_reg64_reg64_reg64(buf, tmp_reg, tmp_reg, src_reg);
+                        match float_width {
+                            FloatWidth::F32 => ASM::to_float_freg32_reg64(buf, dst_reg, tmp_reg),
+                            FloatWidth::F64 => ASM::to_float_freg64_reg64(buf, dst_reg, tmp_reg),
+                        }
+                    });
             }
-            (
-                Layout::Builtin(Builtin::Float(FloatWidth::F64)),
-                Layout::Builtin(Builtin::Float(FloatWidth::F64)),
-            ) => {
+            Layout::F64 => {
                 let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
-                ASM::mov_freg64_freg64(&mut self.buf, dst_reg, src_reg);
+                match float_width {
+                    FloatWidth::F32 => ASM::to_float_freg32_freg64(&mut self.buf, dst_reg, src_reg),
+                    FloatWidth::F64 => ASM::mov_freg64_freg64(&mut self.buf, dst_reg, src_reg),
+                }
             }
-            (
-                Layout::Builtin(Builtin::Float(FloatWidth::F32)),
-                Layout::Builtin(Builtin::Float(FloatWidth::F32)),
-            ) => {
+            Layout::F32 => {
                 let src_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src);
-                ASM::mov_freg64_freg64(&mut self.buf, dst_reg, src_reg);
+                match float_width {
+                    FloatWidth::F32 => ASM::mov_freg64_freg64(&mut self.buf, dst_reg, src_reg),
+                    FloatWidth::F64 => ASM::to_float_freg64_freg32(&mut self.buf, dst_reg, src_reg),
+                }
             }
-            (a, r) => todo!("NumToFrac: layout, arg {:?}, ret {:?}", a, r),
+            x => todo!(
+                "NumToFrac: layout, arg {:?}, ret {:?}",
+                self.layout_interner.dbg(x),
+                float_width
+            ),
         }
     }
 
@@ -1749,146 +2143,167 @@ impl<
             self.env.module_id,
             ident_ids,
             self.layout_interner,
+            &higher_order.op,
             &higher_order.passed_function,
             higher_order.closure_env_layout,
         );
 
-        match higher_order.op {
-            HigherOrder::ListMap { xs } => {
-                let old_element_layout = higher_order.passed_function.argument_layouts[0];
-                let new_element_layout = higher_order.passed_function.return_layout;
+        self.helper_proc_symbols.extend(inc_n_data_linker_data);
 
-                let input_list_layout = Layout::Builtin(Builtin::List(old_element_layout));
-                let input_list_in_layout = self.layout_interner.insert(input_list_layout);
+        let inc_n_data_string = self.function_symbol_to_string(
+            inc_n_data_symbol,
+            std::iter::empty(),
+            None,
+            Layout::UNIT,
+        );
 
-                let caller = self.debug_symbol("caller");
-                let data = self.debug_symbol("data");
-                let alignment = self.debug_symbol("alignment");
-                let old_element_width = self.debug_symbol("old_element_width");
-                let new_element_width = self.debug_symbol("new_element_width");
+        let caller_string = self.function_symbol_to_string(
+            caller_proc.proc_symbol,
+            std::iter::empty(),
+            None,
+            Layout::UNIT,
+        );
 
-                self.load_layout_alignment(new_element_layout, alignment);
+        self.caller_procs.push(caller_proc);
 
-                self.load_layout_stack_size(old_element_layout, old_element_width);
-                self.load_layout_stack_size(new_element_layout, new_element_width);
+        let caller = self.debug_symbol("caller");
+        let data = self.debug_symbol("data");
+        let inc_n_data = Symbol::DEV_TMP;
 
-                self.helper_proc_symbols.extend(inc_n_data_linker_data);
-                self.helper_proc_symbols
-                    .extend([(caller_proc.proc_symbol, caller_proc.proc_layout)]);
+        self.build_fn_pointer(&inc_n_data, inc_n_data_string);
+        self.build_fn_pointer(&caller, caller_string);
 
-                let inc_n_data_string = self.function_symbol_to_string(
-                    inc_n_data_symbol,
-                    std::iter::empty(),
-                    None,
-                    Layout::UNIT,
-                );
+        if let Some(_closure_data_layout) = higher_order.closure_env_layout {
+            let data_symbol = higher_order.passed_function.captured_environment;
+            self.storage_manager
+                .ensure_symbol_on_stack(&mut self.buf, &data_symbol);
+            let (new_elem_offset, _) = self.storage_manager.stack_offset_and_size(&data_symbol);
 
-                let caller_string = self.function_symbol_to_string(
-                    caller_proc.proc_symbol,
-                    std::iter::empty(),
-                    None,
-                    Layout::UNIT,
-                );
+            // Load address of output element into register.
+            let reg = self.storage_manager.claim_general_reg(&mut self.buf, &data);
+            ASM::add_reg64_reg64_imm32(&mut self.buf, reg, CC::BASE_PTR_REG, new_elem_offset);
+        } else {
+            // use a null pointer
+            self.load_literal(&data, &Layout::U64, &Literal::Int(0u128.to_be_bytes()));
+        }
+
+        self.load_literal(
+            &Symbol::DEV_TMP2,
+            &Layout::BOOL,
+            &Literal::Bool(higher_order.passed_function.owns_captured_environment),
+        );
+
+        let ptr = Layout::U64;
+        let usize_ = Layout::U64;
+
+        let arena = self.env.arena;
+        let passed_function = &higher_order.passed_function;
+
+        let (fn_name, lists): (&str, &[Symbol]) = match higher_order.op {
+            HigherOrder::ListMap { xs } => (bitcode::LIST_MAP, arena.alloc([xs])),
+            HigherOrder::ListMap2 { xs, ys } => (bitcode::LIST_MAP2, arena.alloc([xs, ys])),
+            HigherOrder::ListMap3 { xs, ys, zs } => (bitcode::LIST_MAP3, arena.alloc([xs, ys, zs])),
+            HigherOrder::ListMap4 { xs, ys, zs, ws } => {
+                (bitcode::LIST_MAP4, arena.alloc([xs, ys, zs, ws]))
+            }
+            HigherOrder::ListSortWith { xs } => (bitcode::LIST_SORT_WITH, arena.alloc([xs])),
+        };
 
-                self.caller_procs.push(caller_proc);
+        // the n-th list holds the n-th argument of the passed function
+        let element_layouts = &passed_function.argument_layouts[..lists.len()];
 
-                let inc_n_data = Symbol::DEV_TMP;
-                self.build_fn_pointer(&inc_n_data, inc_n_data_string);
+        let mut arguments = Vec::from_iter_in(lists.iter().copied(), arena);
+        let mut layouts = Vec::from_iter_in(
+            element_layouts.iter().map(|e| {
+                self.layout_interner
+                    .insert(Layout::Builtin(Builtin::List(*e)))
+            }),
+            arena,
+        );
 
-                self.build_fn_pointer(&caller, caller_string);
+        arguments.extend([caller, data, inc_n_data, Symbol::DEV_TMP2]);
+        layouts.extend([ptr, ptr, ptr, Layout::BOOL]);
 
-                if let Some(_closure_data_layout) = higher_order.closure_env_layout {
-                    let data_symbol = higher_order.passed_function.captured_environment;
-                    self.storage_manager
-                        .ensure_symbol_on_stack(&mut self.buf, &data_symbol);
-                    let (new_elem_offset, _) =
-                        self.storage_manager.stack_offset_and_size(&data_symbol);
+        if let HigherOrder::ListSortWith { .. } = higher_order.op {
+            //    input: RocList,
+            //    caller: CompareFn,
+            //    data: Opaque,
+            //    inc_n_data: IncN,
+            //    data_is_owned: bool,
+            //    alignment: u32,
+            //    element_width: usize,
 
-                    // Load address of output element into register.
-                    let reg = self.storage_manager.claim_general_reg(&mut self.buf, &data);
-                    ASM::add_reg64_reg64_imm32(
-                        &mut self.buf,
-                        reg,
-                        CC::BASE_PTR_REG,
-                        new_elem_offset,
-                    );
-                } else {
-                    // use a null pointer
-                    self.load_literal(&data, &Layout::U64, &Literal::Int(0u128.to_be_bytes()));
-                }
+            let alignment = self.debug_symbol("alignment");
+            let element_width = self.debug_symbol("element_width");
 
-                self.load_literal(
-                    &Symbol::DEV_TMP2,
-                    &Layout::BOOL,
-                    &Literal::Bool(higher_order.passed_function.owns_captured_environment),
-                );
+            self.load_layout_alignment(element_layouts[0], alignment);
+            self.load_layout_stack_size(element_layouts[0], element_width);
 
-                //    list: RocList,
-                //    caller: Caller1,
-                //    data: Opaque,
-                //    inc_n_data: IncN,
-                //    data_is_owned: bool,
-                //    alignment: u32,
-                //    old_element_width: usize,
-                //    new_element_width: usize,
-
-                let arguments = [
-                    xs,
-                    caller,
-                    data,
-                    inc_n_data,
-                    Symbol::DEV_TMP2,
-                    alignment,
-                    old_element_width,
-                    new_element_width,
-                ];
-
-                let ptr = Layout::U64;
-                let usize_ = Layout::U64;
-
-                let layouts = [
-                    input_list_in_layout,
-                    ptr,
-                    ptr,
-                    ptr,
-                    Layout::BOOL,
-                    Layout::U32,
-                    usize_,
-                    usize_,
-                ];
-
-                // Setup the return location.
-                let base_offset = self
-                    .storage_manager
-                    .claim_stack_area(dst, self.layout_interner.stack_size(ret_layout));
+            arguments.extend([alignment, element_width]);
+            layouts.extend([Layout::U32, usize_]);
+        } else {
+            //    list1: RocList, .., listN: RocList,
+            //    caller: CallerN,
+            //    data: Opaque,
+            //    inc_n_data: IncN,
+            //    data_is_owned: bool,
+            //    alignment: u32,
+            //    a_width: usize, .., (N+1)th_width: usize,
+            //    dec_a: Dec, .., dec_N: Dec, (only when N > 1)
+
+            let new_element_layout = passed_function.return_layout;
+
+            let alignment = self.debug_symbol("alignment");
+            self.load_layout_alignment(new_element_layout, alignment);
+            arguments.push(alignment);
+            layouts.push(Layout::U32);
+
+            for element_layout in element_layouts.iter().chain([&new_element_layout]) {
+                let element_width = self.debug_symbol("element_width");
+                self.load_layout_stack_size(*element_layout, element_width);
+                arguments.push(element_width);
+                layouts.push(usize_);
+            }
 
-                self.build_fn_call(
-                    &Symbol::DEV_TMP3,
-                    bitcode::LIST_MAP.to_string(),
-                    &arguments,
-                    &layouts,
-                    &ret_layout,
-                );
+            // the zig builtin decrements the elements that do not fit in the output
+            if lists.len() > 1 {
+                for element_layout in element_layouts {
+                    let dec_element = self.debug_symbol("dec_element");
+                    self.build_indirect_dec_fn_pointer(&dec_element, *element_layout);
+                    arguments.push(dec_element);
+                    layouts.push(ptr);
+                }
+            }
+        }
 
-                self.free_symbol(&Symbol::DEV_TMP);
-                self.free_symbol(&Symbol::DEV_TMP2);
+        // Setup the return location.
+        let base_offset = self
+            .storage_manager
+            .claim_stack_area(dst, self.layout_interner.stack_size(ret_layout));
 
-                // Return list value from fn call
-                self.storage_manager.copy_symbol_to_stack_offset(
-                    self.layout_interner,
-                    &mut self.buf,
-                    base_offset,
-                    &Symbol::DEV_TMP3,
-                    &ret_layout,
-                );
+        self.build_fn_call(
+            &Symbol::DEV_TMP3,
+            fn_name.to_string(),
+            &arguments,
+            &layouts,
+            &ret_layout,
+        );
 
-                self.free_symbol(&Symbol::DEV_TMP3);
-            }
-            HigherOrder::ListMap2 { .. } => todo!(),
-            HigherOrder::ListMap3 { .. } => todo!(),
-            HigherOrder::ListMap4 { .. } => todo!(),
-            HigherOrder::ListSortWith { .. } => todo!(),
+        // everything but the lists was created just for this call
+        for tmp_symbol in &arguments[lists.len()..] {
+            self.free_symbol(tmp_symbol);
         }
+
+        // Return list value from fn call
+        self.storage_manager.copy_symbol_to_stack_offset(
+            self.layout_interner,
+            &mut self.buf,
+            base_offset,
+            &Symbol::DEV_TMP3,
+            &ret_layout,
+        );
+
+        self.free_symbol(&Symbol::DEV_TMP3);
     }
 
     fn build_list_len(&mut self, dst: &Symbol, list: &Symbol) {
@@ -2388,27 +2803,32 @@ impl<
         let element_layout = self.layout_interner.get(*element_in_layout);
         let element_width = self.layout_interner.stack_size(*element_in_layout) as u64;
 
-        // load the total size of the data we want to store (excludes refcount)
-        let data_bytes_symbol = Symbol::DEV_TMP;
+        // the total size of the data we want to store (excludes refcount)
         let data_bytes = element_width * elements.len() as u64;
-        self.load_literal(
-            &data_bytes_symbol,
-            &Layout::U64,
-            &Literal::Int((data_bytes as i128).to_ne_bytes()),
-        );
 
-        // Load allocation alignment (u32)
-        let element_alignment_symbol = Symbol::DEV_TMP2;
-        self.load_layout_alignment(Layout::U32, element_alignment_symbol);
+        if self.stack_allocations.contains(sym) {
+            self.allocate_on_stack_with_refcount(Symbol::DEV_TMP3, data_bytes as u32);
+        } else {
+            let data_bytes_symbol = Symbol::DEV_TMP;
+            self.load_literal(
+                &data_bytes_symbol,
+                &Layout::U64,
+                &Literal::Int((data_bytes as i128).to_ne_bytes()),
+            );
+
+            // Load allocation alignment (u32)
+            let element_alignment_symbol = Symbol::DEV_TMP2;
+            self.load_layout_alignment(Layout::U32, element_alignment_symbol);
 
-        self.allocate_with_refcount(
-            Symbol::DEV_TMP3,
-            data_bytes_symbol,
-            element_alignment_symbol,
-        );
+            self.allocate_with_refcount(
+                Symbol::DEV_TMP3,
+                data_bytes_symbol,
+                element_alignment_symbol,
+            );
 
-        self.free_symbol(&data_bytes_symbol);
-        self.free_symbol(&element_alignment_symbol);
+            self.free_symbol(&data_bytes_symbol);
+            self.free_symbol(&element_alignment_symbol);
+        }
 
         // The pointer already points to the first element
         let ptr_reg = self
@@ -2549,22 +2969,53 @@ impl<
         ASM::mov_base32_reg64(&mut self.buf, base_offset, ptr_reg);
     }
 
-    fn expr_box(&mut self, sym: Symbol, value: Symbol, element_layout: InLayout<'a>) {
-        let element_width_symbol = Symbol::DEV_TMP;
-        self.load_layout_stack_size(element_layout, element_width_symbol);
+    fn build_ptr_store(
+        &mut self,
+        sym: Symbol,
+        ptr: Symbol,
+        value: Symbol,
+        element_layout: InLayout<'a>,
+    ) {
+        let ptr_reg = self
+            .storage_manager
+            .load_to_general_reg(&mut self.buf, &ptr);
 
-        // Load allocation alignment (u32)
-        let element_alignment_symbol = Symbol::DEV_TMP2;
-        self.load_layout_alignment(Layout::U32, element_alignment_symbol);
+        let element_width = self.layout_interner.stack_size(element_layout) as u64;
 
-        self.allocate_with_refcount(
-            Symbol::DEV_TMP3,
-            element_width_symbol,
-            element_alignment_symbol,
+        Self::ptr_write(
+            &mut self.buf,
+            &mut self.storage_manager,
+            ptr_reg,
+            0,
+            element_width,
+            self.layout_interner.get(element_layout),
+            value,
         );
 
-        self.free_symbol(&element_width_symbol);
-        self.free_symbol(&element_alignment_symbol);
+        self.create_struct(&sym, &Layout::UNIT, &[]);
+    }
+
+    fn expr_box(&mut self, sym: Symbol, value: Symbol, element_layout: InLayout<'a>) {
+        if self.stack_allocations.contains(&sym) {
+            let element_width = self.layout_interner.stack_size(element_layout);
+            self.allocate_on_stack_with_refcount(Symbol::DEV_TMP3, element_width);
+        } else {
+            let element_width_symbol = Symbol::DEV_TMP;
+            self.load_layout_stack_size(element_layout, element_width_symbol);
+
+            // Load allocation alignment (u32)
+            let element_alignment_symbol = Symbol::DEV_TMP2;
+            self.load_layout_alignment(Layout::U32, element_alignment_symbol);
+
+            self.allocate_with_refcount(
+                Symbol::DEV_TMP3,
+                element_width_symbol,
+                element_alignment_symbol,
+            );
+
+            self.free_symbol(&element_width_symbol);
+            self.free_symbol(&element_alignment_symbol);
+        }
 
         self.build_ptr_write(sym, Symbol::DEV_TMP3, value, element_layout);
 
@@ -2792,7 +3243,7 @@ impl<
             )
         }
         let inst_loc = self.buf.len() as u64;
-        let offset = ASM::jmp_imm32(&mut self.buf, 0x1234_5678) as u64;
+        let offset = ASM::jmp_imm32(&mut self.buf, 0) as u64;
         self.relocs.push(Relocation::JmpToReturn {
             inst_loc,
             inst_size: self.buf.len() as u64 - inst_loc,
@@ -2810,7 +3261,9 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                self.build_int128_bitwise(dst, src1, src2, ASM::and_reg64_reg64_reg64)
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2830,7 +3283,9 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                self.build_int128_bitwise(dst, src1, src2, ASM::or_reg64_reg64_reg64)
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2850,7 +3305,9 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                self.build_int128_bitwise(dst, src1, src2, ASM::xor_reg64_reg64_reg64)
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2870,7 +3327,10 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                // from compiler_rt
+                self.build_int128_shift(dst, src1, src2, int_width, "__ashlti3")
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2897,7 +3357,10 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => todo!(),
+            IntWidth::U128 | IntWidth::I128 => {
+                // from compiler_rt; like the narrower integers, unsigned ones are sign-extended too
+                self.build_int128_shift(dst, src1, src2, int_width, "__ashrti3")
+            }
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -2964,21 +3427,13 @@ impl<
         let buf = &mut self.buf;
 
         match int_width {
-            IntWidth::U128 | IntWidth::I128 => {
-                let layout = match int_width {
-                    IntWidth::I128 => Layout::I128,
-                    IntWidth::U128 => Layout::U128,
-                    _ => unreachable!(),
-                };
-
-                self.build_fn_call(
-                    dst,
-                    bitcode::NUM_SHIFT_RIGHT_ZERO_FILL[int_width].to_string(),
-                    &[*src1, *src2],
-                    &[layout, layout],
-                    &layout,
-                );
-            }
+            IntWidth::U128 | IntWidth::I128 => self.build_int128_shift(
+                dst,
+                src1,
+                src2,
+                int_width,
+                bitcode::NUM_SHIFT_RIGHT_ZERO_FILL[int_width],
+            ),
             _ => {
                 let dst_reg = self.storage_manager.claim_general_reg(buf, dst);
                 let src1_reg = self.storage_manager.load_to_general_reg(buf, src1);
@@ -3169,10 +3624,249 @@ impl<
                     op,
                 );
             }
+            Layout::I128 | Layout::U128 | Layout::DEC => {
+                let signed = !matches!(*arg_layout, Layout::U128);
+
+                let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
+
+                let lo1_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+                let hi1_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP2);
+                let lo2_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP3);
+                let hi2_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP4);
+                let tmp_reg = self
+                    .storage_manager
+                    .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP5);
+
+                self.load_int128_words(src1, lo1_reg, hi1_reg);
+                self.load_int128_words(src2, lo2_reg, hi2_reg);
+
+                let buf = &mut self.buf;
+
+                // the high words decide, unless they are equal; then the (unsigned) low words do
+                let strict_op = match op {
+                    CompareOperation::LessThan | CompareOperation::LessThanOrEqual => {
+                        CompareOperation::LessThan
+                    }
+                    CompareOperation::GreaterThan | CompareOperation::GreaterThanOrEqual => {
+                        CompareOperation::GreaterThan
+                    }
+                };
+
+                let width = RegisterWidth::W64;
+                if signed {
+                    ASM::signed_compare_reg64(buf, width, strict_op, dst_reg, hi1_reg, hi2_reg);
+                } else {
+                    ASM::unsigned_compare_reg64(buf, width, strict_op, dst_reg, hi1_reg, hi2_reg);
+                }
+
+                ASM::eq_reg64_reg64_reg64(buf, tmp_reg, hi1_reg, hi2_reg);
+                ASM::unsigned_compare_reg64(buf, width, op, lo1_reg, lo1_reg, lo2_reg);
+                ASM::and_reg64_reg64_reg64(buf, tmp_reg, tmp_reg, lo1_reg);
+                ASM::or_reg64_reg64_reg64(buf, dst_reg, dst_reg, tmp_reg);
+
+                self.free_symbol(&Symbol::DEV_TMP);
+                self.free_symbol(&Symbol::DEV_TMP2);
+                self.free_symbol(&Symbol::DEV_TMP3);
+                self.free_symbol(&Symbol::DEV_TMP4);
+                self.free_symbol(&Symbol::DEV_TMP5);
+            }
             x => todo!("NumLt: layout, {:?}", x),
         }
     }
 
+    /// Compares two floats for equality (or inequality, when `negate` is set).
+    /// NaN is not equal to anything, including itself.
+    fn build_float_eq(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        arg_layout: &InLayout<'a>,
+        negate: bool,
+    ) {
+        let float_width = match *arg_layout {
+            Layout::F32 => FloatWidth::F32,
+            Layout::F64 => FloatWidth::F64,
+            _ => unreachable!(),
+        };
+
+        let dst_reg = self.storage_manager.claim_general_reg(&mut self.buf, dst);
+        let src1_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src1);
+        let src2_reg = self.storage_manager.load_to_float_reg(&mut self.buf, src2);
+
+        // both `<=` and `>=` are false when either side is NaN
+        self.storage_manager
+            .with_tmp_general_reg(&mut self.buf, |_, buf, tmp_reg| {
+                use CompareOperation::*;
+
+                ASM::cmp_freg_freg_reg64(
+                    buf,
+                    dst_reg,
+                    src1_reg,
+                    src2_reg,
+                    float_width,
+                    LessThanOrEqual,
+                );
+                ASM::cmp_freg_freg_reg64(
+                    buf,
+                    tmp_reg,
+                    src1_reg,
+                    src2_reg,
+                    float_width,
+                    GreaterThanOrEqual,
+                );
+                ASM::and_reg64_reg64_reg64(buf, dst_reg, dst_reg, tmp_reg);
+
+                if negate {
+                    ASM::mov_reg64_imm64(buf, tmp_reg, 1);
+                    ASM::xor_reg64_reg64_reg64(buf, dst_reg, dst_reg, tmp_reg);
+                }
+            });
+    }
+
+    /// Loads the low and high 8 bytes of a 128-bit number into two registers.
+    fn load_int128_words(&mut self, src: &Symbol, lo: GeneralReg, hi: GeneralReg) {
+        let (offset, size) = self.storage_manager.stack_offset_and_size(src);
+        debug_assert_eq!(size, 16);
+
+        ASM::mov_reg64_base32(&mut self.buf, lo, offset);
+        ASM::mov_reg64_base32(&mut self.buf, hi, offset + 8);
+    }
+
+    /// Stores a 128-bit number, held as its low and high 8 bytes, in a new stack slot for `dst`.
+    fn store_int128_words(&mut self, dst: &Symbol, lo: GeneralReg, hi: GeneralReg) {
+        let offset = self.storage_manager.claim_stack_area(dst, 16);
+
+        ASM::mov_base32_reg64(&mut self.buf, offset, lo);
+        ASM::mov_base32_reg64(&mut self.buf, offset + 8, hi);
+    }
+
+    /// Adds `(lo2, hi2)` to, or subtracts it from, the 128-bit number in `(lo, hi)` in place.
+    /// `carry` is clobbered and must not be any of the other registers.
+    fn add_or_sub_reg128(
+        &mut self,
+        subtract: bool,
+        lo: GeneralReg,
+        hi: GeneralReg,
+        lo2: GeneralReg,
+        hi2: GeneralReg,
+        carry: GeneralReg,
+    ) {
+        let buf = &mut self.buf;
+        let width = RegisterWidth::W64;
+
+        if subtract {
+            // we borrow from the high word if the low word underflows
+            ASM::unsigned_compare_reg64(buf, width, CompareOperation::LessThan, carry, lo, lo2);
+            ASM::sub_reg64_reg64_reg64(buf, lo, lo, lo2);
+            ASM::sub_reg64_reg64_reg64(buf, hi, hi, hi2);
+            ASM::sub_reg64_reg64_reg64(buf, hi, hi, carry);
+        } else {
+            // the low word overflowed if the sum is smaller than one of the operands
+            ASM::add_reg64_reg64_reg64(buf, lo, lo, lo2);
+            ASM::unsigned_compare_reg64(buf, width, CompareOperation::LessThan, carry, lo, lo2);
+            ASM::add_reg64_reg64_reg64(buf, hi, hi, hi2);
+            ASM::add_reg64_reg64_reg64(buf, hi, hi, carry);
+        }
+    }
+
+    /// Wrapping addition or subtraction of two 128-bit integers.
+    fn build_int128_add_or_sub(
+        &mut self,
+        subtract: bool,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+    ) {
+        let lo1_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+        let hi1_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP2);
+        let lo2_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP3);
+        let hi2_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP4);
+        let carry_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP5);
+
+        self.load_int128_words(src1, lo1_reg, hi1_reg);
+        self.load_int128_words(src2, lo2_reg, hi2_reg);
+        self.add_or_sub_reg128(subtract, lo1_reg, hi1_reg, lo2_reg, hi2_reg, carry_reg);
+        self.store_int128_words(dst, lo1_reg, hi1_reg);
+
+        self.free_symbol(&Symbol::DEV_TMP);
+        self.free_symbol(&Symbol::DEV_TMP2);
+        self.free_symbol(&Symbol::DEV_TMP3);
+        self.free_symbol(&Symbol::DEV_TMP4);
+        self.free_symbol(&Symbol::DEV_TMP5);
+    }
+
+    /// Applies a 64-bit bitwise operation to the low and the high words of two 128-bit numbers.
+    fn build_int128_bitwise(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        op: fn(&mut Vec<'_, u8>, GeneralReg, GeneralReg, GeneralReg),
+    ) {
+        let lo1_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP);
+        let hi1_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP2);
+        let lo2_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP3);
+        let hi2_reg = self
+            .storage_manager
+            .claim_general_reg(&mut self.buf, &Symbol::DEV_TMP4);
+
+        self.load_int128_words(src1, lo1_reg, hi1_reg);
+        self.load_int128_words(src2, lo2_reg, hi2_reg);
+        op(&mut self.buf, lo1_reg, lo1_reg, lo2_reg);
+        op(&mut self.buf, hi1_reg, hi1_reg, hi2_reg);
+        self.store_int128_words(dst, lo1_reg, hi1_reg);
+
+        self.free_symbol(&Symbol::DEV_TMP);
+        self.free_symbol(&Symbol::DEV_TMP2);
+        self.free_symbol(&Symbol::DEV_TMP3);
+        self.free_symbol(&Symbol::DEV_TMP4);
+    }
+
+    /// Shifts a 128-bit number by the `U8` in `src2`, by calling `fn_name`.
+    fn build_int128_shift(
+        &mut self,
+        dst: &Symbol,
+        src1: &Symbol,
+        src2: &Symbol,
+        int_width: IntWidth,
+        fn_name: &str,
+    ) {
+        let layout = Layout::int_width(int_width);
+
+        self.build_fn_call(
+            dst,
+            fn_name.to_string(),
+            &[*src1, *src2],
+            &[layout, Layout::U8],
+            &layout,
+        );
+    }
+
     fn allocate_with_refcount(
         &mut self,
         dst: Symbol,
@@ -3188,6 +3882,20 @@ impl<
         );
     }
 
+    /// Like `allocate_with_refcount`, but in the stack frame of the current proc. The refcount
+    /// marks the memory as read-only, so that refcount operations on it are no-ops.
+    fn allocate_on_stack_with_refcount(&mut self, dst: Symbol, data_bytes: u32) {
+        // stack allocations are at most 8-byte aligned, so the refcount takes 8 bytes
+        let base_offset = self
+            .storage_manager
+            .claim_stack_area_for_proc(8 + data_bytes);
+
+        let reg = self.storage_manager.claim_general_reg(&mut self.buf, &dst);
+        ASM::mov_reg64_imm64(&mut self.buf, reg, 0);
+        ASM::mov_base32_reg64(&mut self.buf, base_offset, reg);
+        ASM::add_reg64_reg64_imm32(&mut self.buf, reg, CC::BASE_PTR_REG, base_offset + 8);
+    }
+
     fn unbox_str_or_list(
         buf: &mut Vec<'a, u8>,
         storage_manager: &mut StorageManager<'a, 'r, GeneralReg, FloatReg, ASM, CC>,
@@ -3458,6 +4166,51 @@ impl<
         }
     }
 
+    /// Loads a pointer to a function that decrements the `layout` value behind its argument.
+    /// Zig builtins use such functions to drop list elements in place.
+    fn build_indirect_dec_fn_pointer(&mut self, dst: &Symbol, layout: InLayout<'a>) {
+        let ident_ids = self
+            .interns
+            .all_ident_ids
+            .get_mut(&self.env.module_id)
+            .unwrap();
+
+        let (dec_symbol, dec_linker_data) = self.helper_proc_gen.gen_refcount_proc(
+            ident_ids,
+            self.layout_interner,
+            layout,
+            HelperOp::Dec,
+        );
+
+        self.helper_proc_symbols.extend(dec_linker_data);
+
+        let dec_wrapper = CallerProc::new_dec_wrapper(
+            self.env.arena,
+            self.env.module_id,
+            ident_ids,
+            self.layout_interner,
+            layout,
+            dec_symbol,
+        );
+
+        let dec_wrapper_string = self.function_symbol_to_string(
+            dec_wrapper.proc_symbol,
+            std::iter::empty(),
+            None,
+            Layout::UNIT,
+        );
+
+        if !self
+            .caller_procs
+            .iter()
+            .any(|cp| cp.proc_symbol == dec_wrapper.proc_symbol)
+        {
+            self.caller_procs.push(dec_wrapper);
+        }
+
+        self.build_fn_pointer(dst, dec_wrapper_string);
+    }
+
     /// Loads the alignment bytes of `layout` into the given `symbol`
     fn load_layout_alignment(&mut self, layout: InLayout<'a>, symbol: Symbol) {
         let u32_layout = Layout::U32;
@@ -126,6 +126,14 @@ pub struct StorageManager<
 
     // The amount of extra stack space needed to pass args for function calling.
     fn_call_stack_size: u32,
+
+    // The position of the last use of each symbol of the current proc, from the liveness analysis.
+    last_use: Rc<MutMap<Symbol, u32>>,
+
+    // The positions of all uses of each symbol, and the position of the statement being built.
+    // When all registers are in use, the one whose symbol is needed furthest in the future is spilled.
+    uses: Rc<MutMap<Symbol, std::vec::Vec<u32>>>,
+    position: u32,
 }
 
 pub fn new_storage_manager<
@@ -156,6 +164,9 @@ pub fn new_storage_manager<
         free_stack_chunks: bumpalo::vec![in env.arena],
         stack_size: 0,
         fn_call_stack_size: 0,
+        last_use: Rc::default(),
+        uses: Rc::default(),
+        position: 0,
     }
 }
 
@@ -185,6 +196,52 @@ impl<
         self.free_stack_chunks.clear();
         self.stack_size = 0;
         self.fn_call_stack_size = 0;
+        self.last_use = Rc::default();
+        self.uses = Rc::default();
+        self.position = 0;
+    }
+
+    /// Sets the positions of the uses of each symbol, as computed by the liveness analysis.
+    pub fn set_uses(
+        &mut self,
+        last_use: MutMap<Symbol, u32>,
+        uses: MutMap<Symbol, std::vec::Vec<u32>>,
+    ) {
+        self.last_use = Rc::new(last_use);
+        self.uses = Rc::new(uses);
+    }
+
+    /// Sets the position of the statement that is being built, as counted by the liveness analysis.
+    pub fn set_position(&mut self, position: u32) {
+        self.position = position;
+    }
+
+    /// The position of the next use of `sym`, at or after the statement being built.
+    /// Symbols unknown to the liveness analysis are backend temporaries that are about to be used.
+    /// A symbol that is not used anymore is needed infinitely far in the future.
+    fn next_use(&self, sym: &Symbol) -> u32 {
+        match self.uses.get(sym) {
+            None => self.position,
+            Some(uses) => {
+                let next = uses.partition_point(|position| *position < self.position);
+                uses.get(next).copied().unwrap_or(u32::MAX)
+            }
+        }
+    }
+
+    /// Index of the used register to spill: the one whose symbol is needed again furthest in the
+    /// future (Belady's choice). Ties go to the register that has been in use the longest.
+    fn spill_candidate<Reg>(&self, used_regs: &[(Reg, Symbol)]) -> usize {
+        let mut candidate = 0;
+        let mut candidate_next_use = None;
+        for (i, (_, sym)) in used_regs.iter().enumerate() {
+            let next_use = self.next_use(sym);
+            if candidate_next_use.map_or(true, |best| next_use > best) {
+                candidate = i;
+                candidate_next_use = Some(next_use);
+            }
+        }
+        candidate
     }
 
     pub fn stack_size(&self) -> u32 {
@@ -224,7 +281,8 @@ impl<
             }
             reg
         } else if !self.general_used_regs.is_empty() {
-            let (reg, sym) = self.general_used_regs.remove(0);
+            let index = self.spill_candidate(&self.general_used_regs);
+            let (reg, sym) = self.general_used_regs.remove(index);
             self.free_to_stack(buf, &sym, General(reg));
             reg
         } else {
@@ -241,7 +299,8 @@ impl<
             }
             reg
         } else if !self.float_used_regs.is_empty() {
-            let (reg, sym) = self.float_used_regs.remove(0);
+            let index = self.spill_candidate(&self.float_used_regs);
+            let (reg, sym) = self.float_used_regs.remove(index);
             self.free_to_stack(buf, &sym, Float(reg));
             reg
         } else {
@@ -911,7 +970,6 @@ impl<
         });
     }
 
-    #[allow(dead_code)]
     /// Ensures that a register is free. If it is not free, data will be moved to make it free.
     pub fn ensure_reg_free(
         &mut self,
@@ -982,22 +1040,20 @@ impl<
         }
     }
 
-    /// Frees all symbols to the stack setuping up a clean slate.
-    pub fn free_all_to_stack(&mut self, buf: &mut Vec<'a, u8>) {
-        let mut free_list = bumpalo::vec![in self.env.arena];
-        for (sym, storage) in self.symbol_storage_map.iter() {
-            match storage {
-                Reg(reg_storage)
-                | Stack(Primitive {
-                    reg: Some(reg_storage),
-                    ..
-                }) => {
-                    free_list.push((*sym, *reg_storage));
-                }
-                _ => {}
-            }
-        }
-        for (sym, reg_storage) in free_list {
+    /// Moves the given symbols out of registers, so they only live on the stack.
+    /// Code that jumps back to this point can rely on finding them there.
+    pub fn spill_to_stack(&mut self, buf: &mut Vec<'a, u8>, syms: &[Symbol]) {
+        for sym in syms {
+            let reg_storage = match self.symbol_storage_map.get(sym) {
+                Some(
+                    Reg(reg_storage)
+                    | Stack(Primitive {
+                        reg: Some(reg_storage),
+                        ..
+                    }),
+                ) => *reg_storage,
+                _ => continue,
+            };
             match reg_storage {
                 General(reg) => {
                     self.general_free_regs.push(reg);
@@ -1008,10 +1064,63 @@ impl<
                     self.float_used_regs.retain(|(r, _)| *r != reg);
                 }
             }
-            self.free_to_stack(buf, &sym, reg_storage);
+            self.free_to_stack(buf, sym, reg_storage);
         }
     }
 
+    /// Releases the registers held by symbols of the proc that are not in `live`.
+    /// This is used when the code built next can not refer to those symbols anymore.
+    /// Their stack storage is kept, so nothing can be overwritten that might still be read.
+    pub fn release_registers_except(&mut self, live: &[Symbol]) {
+        let last_use = self.last_use.clone();
+        let is_released = |sym: &Symbol| last_use.contains_key(sym) && !live.contains(sym);
+
+        let mut released = bumpalo::vec![in self.env.arena];
+        for (reg, sym) in self.general_used_regs.iter() {
+            if is_released(sym) {
+                self.general_free_regs.push(*reg);
+                released.push(*sym);
+            }
+        }
+        self.general_used_regs.retain(|(_, sym)| !is_released(sym));
+        for (reg, sym) in self.float_used_regs.iter() {
+            if is_released(sym) {
+                self.float_free_regs.push(*reg);
+                released.push(*sym);
+            }
+        }
+        self.float_used_regs.retain(|(_, sym)| !is_released(sym));
+
+        for sym in released {
+            match self.remove_storage_for_sym(&sym) {
+                Reg(_) => {}
+                Stack(Primitive { base_offset, .. }) => {
+                    self.symbol_storage_map.insert(
+                        sym,
+                        Stack(Primitive {
+                            base_offset,
+                            reg: None,
+                        }),
+                    );
+                }
+                storage => {
+                    self.symbol_storage_map.insert(sym, storage);
+                }
+            }
+        }
+    }
+
+    /// Keeps what a finished branch of code changed about the whole proc:
+    /// the stack it needed and the callee saved registers it used.
+    pub fn merge_branch_state(&mut self, branch: &Self) {
+        self.update_stack_size(branch.stack_size);
+        self.update_fn_call_stack_size(branch.fn_call_stack_size);
+        self.general_used_callee_saved_regs
+            .extend(branch.general_used_callee_saved_regs.iter().copied());
+        self.float_used_callee_saved_regs
+            .extend(branch.float_used_callee_saved_regs.iter().copied());
+    }
+
     /// Frees `wanted_reg` which is currently owned by `sym` by making sure the value is loaded on the stack.
     /// Note, used and free regs are expected to be updated outside of this function.
     fn free_to_stack(
@@ -1138,12 +1247,14 @@ impl<
     /// To do this, each of the join pionts params are given a storage location.
     /// Then those locations are stored.
     /// Later jumps to the join point can overwrite the stored locations to pass parameters.
+    /// The primitive parameters of loops are kept in registers, the rest lives on the stack.
     pub fn setup_joinpoint(
         &mut self,
         layout_interner: &mut STLayoutInterner<'a>,
         _buf: &mut Vec<'a, u8>,
         id: &JoinPointId,
         params: &'a [Param<'a>],
+        is_loop: bool,
     ) {
         let mut param_storage = bumpalo::vec![in self.env.arena];
         param_storage.reserve(params.len());
@@ -1154,8 +1265,9 @@ impl<
         } in params
         {
             // Claim a location for every join point parameter to be loaded at.
-            // Put everything on the stack for simplicity.
             match *layout {
+                single_register_integers!() if is_loop && self.claim_loop_general_reg(symbol) => {}
+                single_register_floats!() if is_loop && self.claim_loop_float_reg(symbol) => {}
                 single_register_layouts!() => {
                     let base_offset = self.claim_stack_size(8);
                     self.symbol_storage_map.insert(
@@ -1182,8 +1294,54 @@ impl<
         self.join_param_map.insert(*id, param_storage);
     }
 
+    /// Claims a free general register for a loop parameter, if one can be spared.
+    /// Callee saved registers are preferred, they do not have to be spilled around calls in the loop.
+    fn claim_loop_general_reg(&mut self, sym: &Symbol) -> bool {
+        let reg = match self
+            .general_free_regs
+            .iter()
+            .rposition(|reg| CC::general_callee_saved(reg))
+        {
+            Some(position) => self.general_free_regs.remove(position),
+            None if self.general_free_regs.len() > CC::GENERAL_DEFAULT_FREE_REGS.len() / 2 => {
+                self.general_free_regs.pop().unwrap()
+            }
+            None => return false,
+        };
+        if CC::general_callee_saved(&reg) {
+            self.general_used_callee_saved_regs.insert(reg);
+        }
+        self.general_used_regs.push((reg, *sym));
+        self.symbol_storage_map.insert(*sym, Reg(General(reg)));
+        true
+    }
+
+    /// Claims a free float register for a loop parameter, if one can be spared.
+    /// Callee saved registers are preferred, they do not have to be spilled around calls in the loop.
+    fn claim_loop_float_reg(&mut self, sym: &Symbol) -> bool {
+        let reg = match self
+            .float_free_regs
+            .iter()
+            .rposition(|reg| CC::float_callee_saved(reg))
+        {
+            Some(position) => self.float_free_regs.remove(position),
+            None if self.float_free_regs.len() > CC::FLOAT_DEFAULT_FREE_REGS.len() / 2 => {
+                self.float_free_regs.pop().unwrap()
+            }
+            None => return false,
+        };
+        if CC::float_callee_saved(&reg) {
+            self.float_used_callee_saved_regs.insert(reg);
+        }
+        self.float_used_regs.push((reg, *sym));
+        self.symbol_storage_map.insert(*sym, Reg(Float(reg)));
+        true
+    }
+
     /// Setup jump loads the parameters for the joinpoint.
     /// This enables the jump to correctly passe arguments to the joinpoint.
+    /// Arguments are passed in parallel: an argument may live in the storage of another parameter
+    /// (e.g. when two loop variables are swapped), so it is never overwritten before it is read.
     pub fn setup_jump(
         &mut self,
         layout_interner: &mut STLayoutInterner<'a>,
@@ -1199,15 +1357,30 @@ impl<
             None => internal_error!("Jump: unknown point specified to jump to: {:?}", id),
         };
 
+        // Move arguments that live on the stack storage of another parameter out of the way.
+        for (i, (sym, layout)) in args.iter().zip(arg_layouts).enumerate() {
+            let storage = *self.get_storage_for_sym(sym);
+            if is_in_storage(&storage, &param_storage[i]) {
+                continue;
+            }
+            let overlaps = param_storage
+                .iter()
+                .enumerate()
+                .any(|(j, wanted_storage)| j != i && stack_overlaps(&storage, wanted_storage));
+            if overlaps {
+                self.move_to_new_stack_area(layout_interner, buf, sym, layout);
+            }
+        }
+
+        // Stack parameters go first, moving them may use any register.
         let it = args.iter().zip(arg_layouts).zip(param_storage.iter());
         for ((sym, layout), wanted_storage) in it {
-            // Note: it is possible that the storage we want to move to is in use by one of the args we want to pass.
-            if self.get_storage_for_sym(sym) == wanted_storage {
+            if is_in_storage(self.get_storage_for_sym(sym), wanted_storage) {
                 continue;
             }
             match wanted_storage {
                 Reg(_) => {
-                    internal_error!("Register storage is not allowed for jumping to joinpoint")
+                    // Register parameters are passed below.
                 }
                 Stack(Complex { base_offset, .. }) => {
                     // TODO: This might be better not to call.
@@ -1254,9 +1427,245 @@ impl<
                 }
             }
         }
+
+        // Register parameters go last. Writing them claims no other register,
+        // so the only thing that can be overwritten is an argument still to be passed.
+        let mut reg_args = bumpalo::vec![in self.env.arena];
+        for (sym, wanted_storage) in args.iter().zip(param_storage.iter()) {
+            if let Reg(wanted_reg) = wanted_storage {
+                if !is_in_storage(self.get_storage_for_sym(sym), wanted_storage) {
+                    reg_args.push((*sym, *wanted_reg));
+                }
+            }
+        }
+        for (i, (sym, wanted_reg)) in reg_args.iter().enumerate() {
+            let still_needed = reg_args[i + 1..].iter().any(|(later_sym, _)| {
+                reg_of_storage(self.get_storage_for_sym(later_sym)) == Some(*wanted_reg)
+            });
+            if still_needed {
+                let wanted_regs = reg_args.iter().map(|(_, reg)| *reg);
+                self.move_out_of_reg(buf, *wanted_reg, wanted_regs);
+            }
+            self.load_to_unclaimed_reg(buf, sym, *wanted_reg);
+        }
+
         self.join_param_map.insert(*id, param_storage);
     }
 
+    /// Frees `reg` for a jump argument. The value in it moves to a free register that is not
+    /// `reserved` if there is one, so that e.g. swapping two loop parameters stays in registers.
+    /// Otherwise it is spilled to the stack.
+    fn move_out_of_reg(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        reg: RegStorage<GeneralReg, FloatReg>,
+        reserved: impl Iterator<Item = RegStorage<GeneralReg, FloatReg>> + Clone,
+    ) {
+        let moved = match reg {
+            General(reg) => {
+                let free = self
+                    .general_free_regs
+                    .iter()
+                    .rposition(|free| !reserved.clone().any(|r| r == General(*free)));
+                let used = self.general_used_regs.iter().position(|(r, _)| *r == reg);
+                match (free, used) {
+                    (Some(free), Some(used)) => {
+                        let free_reg = self.general_free_regs.remove(free);
+                        if CC::general_callee_saved(&free_reg) {
+                            self.general_used_callee_saved_regs.insert(free_reg);
+                        }
+                        ASM::mov_reg64_reg64(buf, free_reg, reg);
+                        self.general_used_regs[used].0 = free_reg;
+                        self.general_free_regs.push(reg);
+                        Some((self.general_used_regs[used].1, General(free_reg)))
+                    }
+                    _ => None,
+                }
+            }
+            Float(reg) => {
+                let free = self
+                    .float_free_regs
+                    .iter()
+                    .rposition(|free| !reserved.clone().any(|r| r == Float(*free)));
+                let used = self.float_used_regs.iter().position(|(r, _)| *r == reg);
+                match (free, used) {
+                    (Some(free), Some(used)) => {
+                        let free_reg = self.float_free_regs.remove(free);
+                        if CC::float_callee_saved(&free_reg) {
+                            self.float_used_callee_saved_regs.insert(free_reg);
+                        }
+                        ASM::mov_freg64_freg64(buf, free_reg, reg);
+                        self.float_used_regs[used].0 = free_reg;
+                        self.float_free_regs.push(reg);
+                        Some((self.float_used_regs[used].1, Float(free_reg)))
+                    }
+                    _ => None,
+                }
+            }
+        };
+
+        match moved {
+            Some((sym, new_reg)) => {
+                let storage = match self.remove_storage_for_sym(&sym) {
+                    Reg(_) => Reg(new_reg),
+                    Stack(Primitive { base_offset, .. }) => Stack(Primitive {
+                        base_offset,
+                        reg: Some(new_reg),
+                    }),
+                    storage => internal_error!("{} is in a register, not in {:?}", sym, storage),
+                };
+                self.symbol_storage_map.insert(sym, storage);
+            }
+            None => self.ensure_reg_free(buf, reg),
+        }
+    }
+
+    /// Copies a symbol into a register without claiming it or updating the storage of the symbol.
+    /// Used to pass jump arguments, after which the register belongs to the join point parameter.
+    fn load_to_unclaimed_reg(
+        &self,
+        buf: &mut Vec<'a, u8>,
+        sym: &Symbol,
+        wanted_reg: RegStorage<GeneralReg, FloatReg>,
+    ) {
+        match (*self.get_storage_for_sym(sym), wanted_reg) {
+            (
+                Reg(General(reg))
+                | Stack(Primitive {
+                    reg: Some(General(reg)),
+                    ..
+                }),
+                General(dst),
+            ) => {
+                if reg != dst {
+                    ASM::mov_reg64_reg64(buf, dst, reg);
+                }
+            }
+            (
+                Reg(Float(reg))
+                | Stack(Primitive {
+                    reg: Some(Float(reg)),
+                    ..
+                }),
+                Float(dst),
+            ) => {
+                if reg != dst {
+                    ASM::mov_freg64_freg64(buf, dst, reg);
+                }
+            }
+            (
+                Stack(Primitive {
+                    base_offset,
+                    reg: None,
+                }),
+                General(dst),
+            ) => {
+                ASM::mov_reg64_base32(buf, dst, base_offset);
+            }
+            (
+                Stack(Primitive {
+                    base_offset,
+                    reg: None,
+                }),
+                Float(dst),
+            ) => {
+                ASM::mov_freg64_base32(buf, dst, base_offset);
+            }
+            (
+                Stack(ReferencedPrimitive {
+                    base_offset,
+                    size,
+                    sign_extend,
+                }),
+                General(dst),
+            ) => {
+                if sign_extend {
+                    ASM::movsx_reg64_base32(buf, dst, base_offset, size as u8);
+                } else {
+                    ASM::movzx_reg64_base32(buf, dst, base_offset, size as u8);
+                }
+            }
+            (
+                Stack(ReferencedPrimitive {
+                    base_offset, size, ..
+                }),
+                Float(dst),
+            ) if base_offset % 8 == 0 && size == 8 => {
+                ASM::mov_freg64_base32(buf, dst, base_offset);
+            }
+            (storage, wanted_reg) => {
+                internal_error!(
+                    "cannot pass {:?} with storage {:?} in register {:?}",
+                    sym,
+                    storage,
+                    wanted_reg
+                )
+            }
+        }
+    }
+
+    /// Copies the stack data of a symbol to a newly claimed stack area and makes that its storage.
+    fn move_to_new_stack_area(
+        &mut self,
+        layout_interner: &mut STLayoutInterner<'a>,
+        buf: &mut Vec<'a, u8>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
+    ) {
+        match self.remove_storage_for_sym(sym) {
+            Stack(Primitive { base_offset, reg }) => {
+                let new_offset = self.claim_stack_size(8);
+                match reg {
+                    Some(General(reg)) => ASM::mov_base32_reg64(buf, new_offset, reg),
+                    Some(Float(reg)) => ASM::mov_base32_freg64(buf, new_offset, reg),
+                    None => self.copy_to_stack_offset(buf, 8, base_offset, new_offset),
+                }
+                self.symbol_storage_map.insert(
+                    *sym,
+                    Stack(Primitive {
+                        base_offset: new_offset,
+                        reg,
+                    }),
+                );
+            }
+            Stack(ReferencedPrimitive {
+                base_offset,
+                size,
+                sign_extend,
+            }) => {
+                let new_offset = self.claim_stack_size(size);
+                self.copy_to_stack_offset(buf, size, base_offset, new_offset);
+                self.symbol_storage_map.insert(
+                    *sym,
+                    Stack(ReferencedPrimitive {
+                        base_offset: new_offset,
+                        size,
+                        sign_extend,
+                    }),
+                );
+                self.allocation_map
+                    .insert(*sym, Rc::new((new_offset, size)));
+            }
+            Stack(Complex { base_offset, size }) => {
+                debug_assert_eq!(size, layout_interner.stack_size(*layout));
+                let new_offset = self.claim_stack_size(size);
+                self.copy_to_stack_offset(buf, size, base_offset, new_offset);
+                self.symbol_storage_map.insert(
+                    *sym,
+                    Stack(Complex {
+                        base_offset: new_offset,
+                        size,
+                    }),
+                );
+                self.allocation_map
+                    .insert(*sym, Rc::new((new_offset, size)));
+            }
+            storage @ (Reg(_) | NoData) => {
+                self.symbol_storage_map.insert(*sym, storage);
+            }
+        }
+    }
+
     /// claim_stack_area is the public wrapper around claim_stack_size.
     /// It also deals with updating symbol storage.
     /// It returns the base offset of the stack area.
@@ -1270,6 +1679,13 @@ impl<
         base_offset
     }
 
+    /// claim_stack_area_for_proc claims `size` bytes of stack that are not tied to a symbol.
+    /// They are never freed, so they stay valid until the procedure returns.
+    /// It returns base pointer relative offset of the new data.
+    pub fn claim_stack_area_for_proc(&mut self, size: u32) -> i32 {
+        self.claim_stack_size(size)
+    }
+
     /// claim_stack_size claims `amount` bytes from the stack alignind to 8.
     /// This may be free space in the stack or result in increasing the stack size.
     /// It returns base pointer relative offset of the new data.
@@ -1479,3 +1895,181 @@ fn is_primitive(layout_interner: &mut STLayoutInterner<'_>, layout: InLayout<'_>
         },
     }
 }
+
+/// The register holding the value of a symbol with the given storage, if any.
+fn reg_of_storage<GeneralReg: RegTrait, FloatReg: RegTrait>(
+    storage: &Storage<GeneralReg, FloatReg>,
+) -> Option<RegStorage<GeneralReg, FloatReg>> {
+    match storage {
+        Reg(reg) | Stack(Primitive { reg: Some(reg), .. }) => Some(*reg),
+        _ => None,
+    }
+}
+
+/// The stack area holding the value of a symbol with the given storage, if any.
+fn stack_area_of_storage<GeneralReg: RegTrait, FloatReg: RegTrait>(
+    storage: &Storage<GeneralReg, FloatReg>,
+) -> Option<(i32, u32)> {
+    match storage {
+        Stack(Primitive { base_offset, .. }) => Some((*base_offset, 8)),
+        Stack(ReferencedPrimitive {
+            base_offset, size, ..
+        })
+        | Stack(Complex { base_offset, size }) => Some((*base_offset, *size)),
+        Reg(_) | NoData => None,
+    }
+}
+
+/// Whether a value with storage `storage` is already where a join point parameter wants it.
+fn is_in_storage<GeneralReg: RegTrait, FloatReg: RegTrait>(
+    storage: &Storage<GeneralReg, FloatReg>,
+    wanted_storage: &Storage<GeneralReg, FloatReg>,
+) -> bool {
+    match wanted_storage {
+        Reg(wanted_reg) => reg_of_storage(storage) == Some(*wanted_reg),
+        Stack(Primitive { .. } | Complex { .. }) => {
+            matches!(storage, Stack(Primitive { .. } | Complex { .. }))
+                && stack_area_of_storage(storage) == stack_area_of_storage(wanted_storage)
+        }
+        _ => storage == wanted_storage,
+    }
+}
+
+/// Whether writing a join point parameter with storage `wanted_storage` overwrites the stack
+/// data of a value with storage `storage`.
+fn stack_overlaps<GeneralReg: RegTrait, FloatReg: RegTrait>(
+    storage: &Storage<GeneralReg, FloatReg>,
+    wanted_storage: &Storage<GeneralReg, FloatReg>,
+) -> bool {
+    match (
+        stack_area_of_storage(storage),
+        stack_area_of_storage(wanted_storage),
+    ) {
+        (Some((offset, size)), Some((wanted_offset, wanted_size))) => {
+            offset < wanted_offset + wanted_size as i32 && wanted_offset < offset + size as i32
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generic64::x86_64::{
+        X86_64Assembler, X86_64FloatReg, X86_64GeneralReg, X86_64SystemV,
+    };
+    use crate::AssemblyBackendMode;
+    use bumpalo::Bump;
+    use capstone::prelude::*;
+    use roc_module::symbol::ModuleId;
+    use roc_mono::borrow::Ownership;
+
+    type X86_64StorageManager<'a, 'r> =
+        StorageManager<'a, 'r, X86_64GeneralReg, X86_64FloatReg, X86_64Assembler, X86_64SystemV>;
+
+    fn test_env(arena: &Bump) -> Env<'_> {
+        Env {
+            arena,
+            module_id: ModuleId::ATTR,
+            exposed_to_host: MutSet::default(),
+            lazy_literals: false,
+            mode: AssemblyBackendMode::Test,
+        }
+    }
+
+    #[test]
+    fn spill_the_value_needed_furthest_in_the_future() {
+        let arena = Bump::new();
+        let env = test_env(&arena);
+        let mut storage: X86_64StorageManager =
+            new_storage_manager(&env, TargetInfo::default_x86_64());
+        storage.reset();
+
+        let mut uses = MutMap::default();
+        uses.insert(Symbol::ARG_1, vec![1, 5]);
+        uses.insert(Symbol::ARG_2, vec![1, 3, 9]);
+        uses.insert(Symbol::ARG_3, vec![1, 2]);
+        storage.set_uses(MutMap::default(), uses);
+
+        let used_regs = [
+            (X86_64GeneralReg::RAX, Symbol::ARG_1),
+            (X86_64GeneralReg::RBX, Symbol::ARG_2),
+            (X86_64GeneralReg::RCX, Symbol::ARG_3),
+            (X86_64GeneralReg::RDX, Symbol::DEV_TMP),
+        ];
+
+        // ARG_3 is not used anymore
+        storage.set_position(4);
+        assert_eq!(storage.spill_candidate(&used_regs), 2);
+
+        // ARG_2 is used last, but ARG_1 is needed again later
+        storage.set_position(2);
+        assert_eq!(storage.spill_candidate(&used_regs), 0);
+    }
+
+    #[test]
+    fn swapped_loop_params_stay_in_registers_across_a_jump() {
+        let arena = Bump::new();
+        let env = test_env(&arena);
+        let target_info = TargetInfo::default_x86_64();
+        let mut layout_interner = STLayoutInterner::with_capacity(4, target_info);
+        let mut storage: X86_64StorageManager = new_storage_manager(&env, target_info);
+        storage.reset();
+        let mut buf = bumpalo::vec![in &arena];
+
+        // loop = \a, b -> loop b a
+        let id = JoinPointId(Symbol::DEV_TMP);
+        let params = arena.alloc([
+            Param {
+                symbol: Symbol::ARG_1,
+                ownership: Ownership::Owned,
+                layout: Layout::I64,
+            },
+            Param {
+                symbol: Symbol::ARG_2,
+                ownership: Ownership::Owned,
+                layout: Layout::I64,
+            },
+        ]);
+        storage.setup_joinpoint(&mut layout_interner, &mut buf, &id, params, true);
+
+        let a = storage.load_to_general_reg(&mut buf, &Symbol::ARG_1);
+        let b = storage.load_to_general_reg(&mut buf, &Symbol::ARG_2);
+        assert!(buf.is_empty(), "the loop params are not in registers");
+
+        storage.setup_jump(
+            &mut layout_interner,
+            &mut buf,
+            &id,
+            &[Symbol::ARG_2, Symbol::ARG_1],
+            &[Layout::I64, Layout::I64],
+        );
+
+        let cs = Capstone::new()
+            .x86()
+            .mode(arch::x86::ArchMode::Mode64)
+            .syntax(arch::x86::ArchSyntax::Intel)
+            .build()
+            .expect("Failed to create Capstone object");
+        let instructions = cs.disasm_all(&buf, 0).expect("Failed to disassemble");
+
+        // run the moves, starting with each register holding its own name
+        let mut regs: MutMap<String, String> = MutMap::default();
+        for reg in [a, b] {
+            regs.insert(reg.to_string(), reg.to_string());
+        }
+        for instruction in instructions.iter() {
+            let mnemonic = instruction.mnemonic().unwrap();
+            let operands = instruction.op_str().unwrap();
+            assert_eq!(mnemonic, "mov", "not a move: {mnemonic} {operands}");
+            assert!(!operands.contains('['), "went through memory: {operands}");
+
+            let (dst, src) = operands.split_once(", ").unwrap();
+            let value = regs.get(src).cloned().unwrap_or_default();
+            regs.insert(dst.to_string(), value);
+        }
+
+        assert_eq!(regs[&a.to_string()], b.to_string());
+        assert_eq!(regs[&b.to_string()], a.to_string());
+    }
+}
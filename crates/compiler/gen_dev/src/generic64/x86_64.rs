@@ -1,7 +1,7 @@
 use crate::generic64::{storage::StorageManager, Assembler, CallConv, RegTrait};
 use crate::{
     single_register_floats, single_register_int_builtins, single_register_integers,
-    single_register_layouts, Relocation,
+    single_register_layouts, ProcFrame, Relocation, SavedReg,
 };
 use bumpalo::collections::Vec;
 use roc_builtins::bitcode::{FloatWidth, IntWidth};
@@ -216,13 +216,13 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Syste
     }
 
     #[inline(always)]
-    fn setup_stack(
-        buf: &mut Vec<'_, u8>,
+    fn setup_stack<'a>(
+        buf: &mut Vec<'a, u8>,
         saved_general_regs: &[X86_64GeneralReg],
         saved_float_regs: &[X86_64FloatReg],
         requested_stack_size: i32,
         fn_call_stack_size: i32,
-    ) -> i32 {
+    ) -> ProcFrame<'a> {
         x86_64_generic_setup_stack(
             buf,
             saved_general_regs,
@@ -460,7 +460,7 @@ impl X64_64SystemVStoreArgs {
         match in_layout {
             single_register_integers!() => self.store_arg_general(buf, storage_manager, sym),
             single_register_floats!() => self.store_arg_float(buf, storage_manager, sym),
-            Layout::I128 | Layout::U128 => {
+            Layout::I128 | Layout::U128 | Layout::DEC => {
                 let (offset, _) = storage_manager.stack_offset_and_size(&sym);
 
                 if self.general_i + 1 < Self::GENERAL_PARAM_REGS.len() {
@@ -688,7 +688,9 @@ impl X64_64SystemVLoadArgs {
                     storage_manager.complex_stack_arg(&sym, self.argument_offset, stack_size);
                     self.argument_offset += stack_size as i32;
                 }
-                Layout::Builtin(Builtin::Int(IntWidth::U128 | IntWidth::I128)) => {
+                Layout::Builtin(
+                    Builtin::Int(IntWidth::U128 | IntWidth::I128) | Builtin::Decimal,
+                ) => {
                     storage_manager.complex_stack_arg(&sym, self.argument_offset, stack_size);
                     self.argument_offset += stack_size as i32;
                 }
@@ -791,18 +793,9 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
     const FLOAT_DEFAULT_FREE_REGS: &'static [X86_64FloatReg] = &[
         // The regs we want to use first should be at the end of this vec.
         // We will use pop to get which reg to use next
-        // Use callee saved regs last.
-        X86_64FloatReg::XMM15,
-        X86_64FloatReg::XMM15,
-        X86_64FloatReg::XMM13,
-        X86_64FloatReg::XMM12,
-        X86_64FloatReg::XMM11,
-        X86_64FloatReg::XMM10,
-        X86_64FloatReg::XMM9,
-        X86_64FloatReg::XMM8,
-        X86_64FloatReg::XMM7,
-        X86_64FloatReg::XMM6,
-        // Use caller saved regs first.
+
+        // Don't use callee saved regs: XMM6 to XMM15.
+        // All 128 bits of them must be preserved, but we only save and restore 64 bits.
         X86_64FloatReg::XMM5,
         X86_64FloatReg::XMM4,
         X86_64FloatReg::XMM3,
@@ -830,7 +823,7 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
 
     #[inline(always)]
     fn float_callee_saved(reg: &X86_64FloatReg) -> bool {
-        matches!(
+        !matches!(
             reg,
             X86_64FloatReg::XMM0
                 | X86_64FloatReg::XMM1
@@ -842,13 +835,14 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
     }
 
     #[inline(always)]
-    fn setup_stack(
-        buf: &mut Vec<'_, u8>,
+    fn setup_stack<'a>(
+        buf: &mut Vec<'a, u8>,
         saved_general_regs: &[X86_64GeneralReg],
         saved_float_regs: &[X86_64FloatReg],
         requested_stack_size: i32,
         fn_call_stack_size: i32,
-    ) -> i32 {
+    ) -> ProcFrame<'a> {
+        // TODO: frames bigger than a page need a stack probe (`__chkstk`) on Windows.
         x86_64_generic_setup_stack(
             buf,
             saved_general_regs,
@@ -877,45 +871,27 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
 
     #[inline(always)]
     fn load_args<'a>(
-        _buf: &mut Vec<'a, u8>,
+        buf: &mut Vec<'a, u8>,
         storage_manager: &mut X86_64StorageManager<'a, '_, X86_64WindowsFastcall>,
         layout_interner: &mut STLayoutInterner<'a>,
         args: &'a [(InLayout<'a>, Symbol)],
         ret_layout: &InLayout<'a>,
     ) {
-        let mut arg_offset = Self::SHADOW_SPACE_SIZE as i32 + 16; // 16 is the size of the pushed return address and base pointer.
-        let mut i = 0;
-        if X86_64WindowsFastcall::returns_via_arg_pointer(layout_interner, ret_layout) {
-            storage_manager.ret_pointer_arg(Self::GENERAL_PARAM_REGS[i]);
-            i += 1;
+        let returns_via_pointer =
+            X86_64WindowsFastcall::returns_via_arg_pointer(layout_interner, ret_layout);
+
+        let mut state = X64_64WindowsFastcallLoadArgs {
+            arg_i: usize::from(returns_via_pointer),
+            // 16 is the size of the pushed return address and base pointer.
+            argument_offset: X86_64WindowsFastcall::SHADOW_SPACE_SIZE as i32 + 16,
+        };
+
+        if returns_via_pointer {
+            storage_manager.ret_pointer_arg(X86_64WindowsFastcall::GENERAL_PARAM_REGS[0]);
         }
-        for (layout, sym) in args.iter() {
-            if i < Self::GENERAL_PARAM_REGS.len() {
-                match *layout {
-                    single_register_integers!() => {
-                        storage_manager.general_reg_arg(sym, Self::GENERAL_PARAM_REGS[i]);
-                        i += 1;
-                    }
-                    single_register_floats!() => {
-                        storage_manager.float_reg_arg(sym, Self::FLOAT_PARAM_REGS[i]);
-                        i += 1;
-                    }
-                    x if layout_interner.stack_size(x) == 0 => {}
-                    x => {
-                        todo!("Loading args with layout {:?}", x);
-                    }
-                }
-            } else {
-                match *layout {
-                    single_register_layouts!() => {
-                        storage_manager.primitive_stack_arg(sym, arg_offset);
-                        arg_offset += 8;
-                    }
-                    x => {
-                        todo!("Loading args with layout {:?}", x);
-                    }
-                };
-            }
+
+        for (in_layout, sym) in args.iter() {
+            state.load_arg(buf, storage_manager, layout_interner, *sym, *in_layout);
         }
     }
 
@@ -936,70 +912,41 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
         arg_layouts: &[InLayout<'a>],
         ret_layout: &InLayout<'a>,
     ) {
-        let mut tmp_stack_offset = Self::SHADOW_SPACE_SIZE as i32;
-        if Self::returns_via_arg_pointer(layout_interner, ret_layout) {
-            // Save space on the stack for the arg we will return.
-            storage_manager.claim_stack_area(dst, layout_interner.stack_size(*ret_layout));
-            todo!("claim first parama reg for the address");
+        let returns_via_pointer = Self::returns_via_arg_pointer(layout_interner, ret_layout);
+
+        if returns_via_pointer {
+            // Save space on the stack for the result we will be return.
+            let base_offset =
+                storage_manager.claim_stack_area(dst, layout_interner.stack_size(*ret_layout));
+            // Set the first reg to the address base + offset.
+            X86_64Assembler::add_reg64_reg64_imm32(
+                buf,
+                Self::GENERAL_PARAM_REGS[0],
+                X86_64GeneralReg::RBP,
+                base_offset,
+            );
         }
-        for (i, (sym, layout)) in args.iter().zip(arg_layouts.iter()).enumerate() {
-            match *layout {
-                single_register_integers!() => {
-                    if i < Self::GENERAL_PARAM_REGS.len() {
-                        storage_manager.load_to_specified_general_reg(
-                            buf,
-                            sym,
-                            Self::GENERAL_PARAM_REGS[i],
-                        );
-                    } else {
-                        // Copy to stack using return reg as buffer.
-                        storage_manager.load_to_specified_general_reg(
-                            buf,
-                            sym,
-                            Self::GENERAL_RETURN_REGS[0],
-                        );
-                        X86_64Assembler::mov_stack32_reg64(
-                            buf,
-                            tmp_stack_offset,
-                            Self::GENERAL_RETURN_REGS[0],
-                        );
-                        tmp_stack_offset += 8;
-                    }
-                }
-                single_register_floats!() => {
-                    if i < Self::FLOAT_PARAM_REGS.len() {
-                        storage_manager.load_to_specified_float_reg(
-                            buf,
-                            sym,
-                            Self::FLOAT_PARAM_REGS[i],
-                        );
-                    } else {
-                        // Copy to stack using return reg as buffer.
-                        storage_manager.load_to_specified_float_reg(
-                            buf,
-                            sym,
-                            Self::FLOAT_RETURN_REGS[0],
-                        );
-                        X86_64Assembler::mov_stack32_freg64(
-                            buf,
-                            tmp_stack_offset,
-                            Self::FLOAT_RETURN_REGS[0],
-                        );
-                        tmp_stack_offset += 8;
-                    }
-                }
-                x if layout_interner.stack_size(x) == 0 => {}
-                x => {
-                    todo!("calling with arg type, {:?}", x);
-                }
-            }
+
+        // Every argument takes a register or an 8 byte stack slot.
+        // Copies of arguments passed by reference go after the stack slots.
+        let arg_count = args.len() + usize::from(returns_via_pointer);
+        let stack_slots = arg_count.saturating_sub(Self::GENERAL_PARAM_REGS.len()) as i32;
+        let mut state = X64_64WindowsFastcallStoreArgs {
+            arg_i: usize::from(returns_via_pointer),
+            tmp_stack_offset: Self::SHADOW_SPACE_SIZE as i32,
+            copy_stack_offset: Self::SHADOW_SPACE_SIZE as i32 + 8 * stack_slots,
+        };
+
+        for (sym, in_layout) in args.iter().zip(arg_layouts.iter()) {
+            state.store_arg(buf, storage_manager, layout_interner, *sym, *in_layout);
         }
-        storage_manager.update_fn_call_stack_size(tmp_stack_offset as u32);
+
+        storage_manager.update_fn_call_stack_size(state.copy_stack_offset as u32);
     }
 
     fn return_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             X86_64GeneralReg,
@@ -1007,16 +954,36 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
             X86_64Assembler,
             X86_64WindowsFastcall,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
     ) {
-        todo!("Returning complex symbols for X86_64");
+        match *layout {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            x if layout_interner.stack_size(x) == 0 => {}
+            x if !Self::returns_via_arg_pointer(layout_interner, &x) => {
+                let (base_offset, _) = storage_manager.stack_offset_and_size(sym);
+                debug_assert_eq!(base_offset % 8, 0);
+                X86_64Assembler::mov_reg64_base32(buf, Self::GENERAL_RETURN_REGS[0], base_offset);
+            }
+            _ => {
+                // This is a large type returned via the arg pointer.
+                storage_manager.copy_symbol_to_arg_pointer(buf, sym, layout);
+                // Also set the return reg to the arg pointer.
+                storage_manager.load_to_specified_general_reg(
+                    buf,
+                    &Symbol::RET_POINTER,
+                    Self::GENERAL_RETURN_REGS[0],
+                );
+            }
+        }
     }
 
     fn load_returned_complex_symbol<'a>(
-        _buf: &mut Vec<'a, u8>,
-        _storage_manager: &mut StorageManager<
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut StorageManager<
             'a,
             '_,
             X86_64GeneralReg,
@@ -1024,11 +991,28 @@ impl CallConv<X86_64GeneralReg, X86_64FloatReg, X86_64Assembler> for X86_64Windo
             X86_64Assembler,
             X86_64WindowsFastcall,
         >,
-        _layout_interner: &mut STLayoutInterner<'a>,
-        _sym: &Symbol,
-        _layout: &InLayout<'a>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: &Symbol,
+        layout: &InLayout<'a>,
     ) {
-        todo!("Loading returned complex symbols for X86_64");
+        match *layout {
+            single_register_layouts!() => {
+                internal_error!("single register layouts are not complex symbols");
+            }
+            x if layout_interner.stack_size(x) == 0 => {
+                storage_manager.no_data(sym);
+            }
+            x if !Self::returns_via_arg_pointer(layout_interner, &x) => {
+                let size = layout_interner.stack_size(*layout);
+                let offset = storage_manager.claim_stack_area(sym, size);
+                X86_64Assembler::mov_base32_reg64(buf, offset, Self::GENERAL_RETURN_REGS[0]);
+            }
+            _ => {
+                // This should have been recieved via an arg pointer.
+                // That means the value is already loaded onto the stack area we allocated before the call.
+                // Nothing to do.
+            }
+        }
     }
 }
 
@@ -1039,20 +1023,286 @@ impl X86_64WindowsFastcall {
     ) -> bool {
         // TODO: This is not fully correct there are some exceptions for "vector" types.
         // details here: https://docs.microsoft.com/en-us/cpp/build/x64-calling-convention?view=msvc-160#return-values
-        interner.stack_size(*ret_layout) > 8
+        Self::passed_by_reference(interner.stack_size(*ret_layout))
     }
+
+    /// Values that are not 1, 2, 4 or 8 bytes in size are passed and returned by reference.
+    /// This includes I128, U128 and Dec.
+    fn passed_by_reference(size: u32) -> bool {
+        !matches!(size, 0 | 1 | 2 | 4 | 8)
+    }
+}
+
+struct X64_64WindowsFastcallStoreArgs {
+    /// Arguments use the register matching their position, integers and floats share the count.
+    arg_i: usize,
+    tmp_stack_offset: i32,
+    /// Where the next copy of an argument that is passed by reference goes.
+    copy_stack_offset: i32,
+}
+
+impl X64_64WindowsFastcallStoreArgs {
+    const GENERAL_PARAM_REGS: &'static [X86_64GeneralReg] =
+        X86_64WindowsFastcall::GENERAL_PARAM_REGS;
+    const GENERAL_RETURN_REGS: &'static [X86_64GeneralReg] =
+        X86_64WindowsFastcall::GENERAL_RETURN_REGS;
+
+    const FLOAT_PARAM_REGS: &'static [X86_64FloatReg] = X86_64WindowsFastcall::FLOAT_PARAM_REGS;
+    const FLOAT_RETURN_REGS: &'static [X86_64FloatReg] = X86_64WindowsFastcall::FLOAT_RETURN_REGS;
+
+    fn store_arg<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut X86_64StorageManager<'a, '_, X86_64WindowsFastcall>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: Symbol,
+        in_layout: InLayout<'a>,
+    ) {
+        match in_layout {
+            single_register_integers!() => self.store_arg_general(buf, storage_manager, sym),
+            single_register_floats!() => self.store_arg_float(buf, storage_manager, sym),
+            x if layout_interner.stack_size(x) == 0 => {}
+            other => match layout_interner.get(other) {
+                Layout::Boxed(_) => {
+                    // treat boxed like a 64-bit integer
+                    self.store_arg_general(buf, storage_manager, sym)
+                }
+                Layout::LambdaSet(lambda_set) => self.store_arg(
+                    buf,
+                    storage_manager,
+                    layout_interner,
+                    sym,
+                    lambda_set.runtime_representation(),
+                ),
+                _ => {
+                    let (base_offset, size) = storage_manager.stack_offset_and_size(&sym);
+                    debug_assert_eq!(base_offset % 8, 0);
+                    if X86_64WindowsFastcall::passed_by_reference(size) {
+                        self.store_arg_by_reference(buf, base_offset, size);
+                    } else {
+                        // Small values are passed like an integer of the same size.
+                        self.store_arg_with(buf, |buf, reg| {
+                            X86_64Assembler::mov_reg64_base32(buf, reg, base_offset)
+                        });
+                    }
+                }
+            },
+        }
+    }
+
+    /// Copies the value to the outgoing argument area and passes a pointer to the copy.
+    /// The callee is allowed to modify the copy.
+    fn store_arg_by_reference(&mut self, buf: &mut Vec<'_, u8>, base_offset: i32, size: u32) {
+        let tmp_reg = Self::GENERAL_RETURN_REGS[0];
+        let copy_offset = self.copy_stack_offset;
+        for i in (0..size as i32).step_by(8) {
+            X86_64Assembler::mov_reg64_base32(buf, tmp_reg, base_offset + i);
+            X86_64Assembler::mov_stack32_reg64(buf, copy_offset + i, tmp_reg);
+        }
+        self.copy_stack_offset += next_multiple_of_8(size) as i32;
+
+        self.store_arg_with(buf, |buf, reg| {
+            X86_64Assembler::add_reg64_reg64_imm32(buf, reg, X86_64GeneralReg::RSP, copy_offset)
+        });
+    }
+
+    /// Passes a value that `load` puts into a general register.
+    fn store_arg_with<F>(&mut self, buf: &mut Vec<'_, u8>, load: F)
+    where
+        F: FnOnce(&mut Vec<'_, u8>, X86_64GeneralReg),
+    {
+        if self.arg_i < Self::GENERAL_PARAM_REGS.len() {
+            load(buf, Self::GENERAL_PARAM_REGS[self.arg_i]);
+            self.arg_i += 1;
+        } else {
+            // Copy to stack using return reg as buffer.
+            load(buf, Self::GENERAL_RETURN_REGS[0]);
+            X86_64Assembler::mov_stack32_reg64(
+                buf,
+                self.tmp_stack_offset,
+                Self::GENERAL_RETURN_REGS[0],
+            );
+            self.tmp_stack_offset += 8;
+        }
+    }
+
+    fn store_arg_general<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut X86_64StorageManager<'a, '_, X86_64WindowsFastcall>,
+        sym: Symbol,
+    ) {
+        if self.arg_i < Self::GENERAL_PARAM_REGS.len() {
+            storage_manager.load_to_specified_general_reg(
+                buf,
+                &sym,
+                Self::GENERAL_PARAM_REGS[self.arg_i],
+            );
+            self.arg_i += 1;
+        } else {
+            // Copy to stack using return reg as buffer.
+            storage_manager.load_to_specified_general_reg(buf, &sym, Self::GENERAL_RETURN_REGS[0]);
+            X86_64Assembler::mov_stack32_reg64(
+                buf,
+                self.tmp_stack_offset,
+                Self::GENERAL_RETURN_REGS[0],
+            );
+            self.tmp_stack_offset += 8;
+        }
+    }
+
+    fn store_arg_float<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut X86_64StorageManager<'a, '_, X86_64WindowsFastcall>,
+        sym: Symbol,
+    ) {
+        if self.arg_i < Self::FLOAT_PARAM_REGS.len() {
+            storage_manager.load_to_specified_float_reg(
+                buf,
+                &sym,
+                Self::FLOAT_PARAM_REGS[self.arg_i],
+            );
+            self.arg_i += 1;
+        } else {
+            // Copy to stack using return reg as buffer.
+            storage_manager.load_to_specified_float_reg(buf, &sym, Self::FLOAT_RETURN_REGS[0]);
+            X86_64Assembler::mov_stack32_freg64(
+                buf,
+                self.tmp_stack_offset,
+                Self::FLOAT_RETURN_REGS[0],
+            );
+            self.tmp_stack_offset += 8;
+        }
+    }
+}
+
+struct X64_64WindowsFastcallLoadArgs {
+    /// Arguments use the register matching their position, integers and floats share the count.
+    arg_i: usize,
+    argument_offset: i32,
+}
+
+impl X64_64WindowsFastcallLoadArgs {
+    fn load_arg<'a>(
+        &mut self,
+        buf: &mut Vec<'a, u8>,
+        storage_manager: &mut X86_64StorageManager<'a, '_, X86_64WindowsFastcall>,
+        layout_interner: &mut STLayoutInterner<'a>,
+        sym: Symbol,
+        in_layout: InLayout<'a>,
+    ) {
+        let stack_size = layout_interner.stack_size(in_layout);
+        match in_layout {
+            single_register_integers!() => self.load_arg_general(storage_manager, sym),
+            single_register_floats!() => self.load_arg_float(storage_manager, sym),
+            _ if stack_size == 0 => {
+                storage_manager.no_data(&sym);
+            }
+            other => match layout_interner.get(other) {
+                Layout::Boxed(_) => {
+                    // boxed layouts are pointers, which we treat as 64-bit integers
+                    self.load_arg_general(storage_manager, sym)
+                }
+                Layout::LambdaSet(lambda_set) => self.load_arg(
+                    buf,
+                    storage_manager,
+                    layout_interner,
+                    sym,
+                    lambda_set.runtime_representation(),
+                ),
+                _ if X86_64WindowsFastcall::passed_by_reference(stack_size) => {
+                    // Copy the value into our own stack frame, the pointer is not valid after a call.
+                    let pointer = self.load_arg_pointer(buf);
+                    let tmp_reg = X86_64GeneralReg::RAX;
+                    let base_offset = storage_manager.claim_stack_area(&sym, stack_size);
+                    for i in (0..stack_size as i32).step_by(8) {
+                        X86_64Assembler::mov_reg64_mem64_offset32(buf, tmp_reg, pointer, i);
+                        X86_64Assembler::mov_base32_reg64(buf, base_offset + i, tmp_reg);
+                    }
+                }
+                _ => {
+                    // Small values are passed like an integer of the same size.
+                    let reg = self.load_arg_pointer(buf);
+                    let base_offset = storage_manager.claim_stack_area(&sym, stack_size);
+                    X86_64Assembler::mov_base32_reg64(buf, base_offset, reg);
+                }
+            },
+        }
+    }
+
+    /// Gets the next argument into a general register that is not holding any other argument.
+    fn load_arg_pointer(&mut self, buf: &mut Vec<'_, u8>) -> X86_64GeneralReg {
+        if self.arg_i < X86_64WindowsFastcall::GENERAL_PARAM_REGS.len() {
+            let reg = X86_64WindowsFastcall::GENERAL_PARAM_REGS[self.arg_i];
+            self.arg_i += 1;
+            reg
+        } else {
+            let reg = X86_64GeneralReg::R11;
+            X86_64Assembler::mov_reg64_base32(buf, reg, self.argument_offset);
+            self.argument_offset += 8;
+            reg
+        }
+    }
+
+    fn load_arg_general(
+        &mut self,
+        storage_manager: &mut X86_64StorageManager<'_, '_, X86_64WindowsFastcall>,
+        sym: Symbol,
+    ) {
+        if self.arg_i < X86_64WindowsFastcall::GENERAL_PARAM_REGS.len() {
+            let reg = X86_64WindowsFastcall::GENERAL_PARAM_REGS[self.arg_i];
+            storage_manager.general_reg_arg(&sym, reg);
+            self.arg_i += 1;
+        } else {
+            storage_manager.primitive_stack_arg(&sym, self.argument_offset);
+            self.argument_offset += 8;
+        }
+    }
+
+    fn load_arg_float(
+        &mut self,
+        storage_manager: &mut X86_64StorageManager<'_, '_, X86_64WindowsFastcall>,
+        sym: Symbol,
+    ) {
+        if self.arg_i < X86_64WindowsFastcall::FLOAT_PARAM_REGS.len() {
+            let reg = X86_64WindowsFastcall::FLOAT_PARAM_REGS[self.arg_i];
+            storage_manager.float_reg_arg(&sym, reg);
+            self.arg_i += 1;
+        } else {
+            storage_manager.primitive_stack_arg(&sym, self.argument_offset);
+            self.argument_offset += 8;
+        }
+    }
+}
+
+fn next_multiple_of_8(size: u32) -> u32 {
+    (size + 7) & !7
 }
 
 #[inline(always)]
-fn x86_64_generic_setup_stack(
-    buf: &mut Vec<'_, u8>,
+fn x86_64_generic_setup_stack<'a>(
+    buf: &mut Vec<'a, u8>,
     saved_general_regs: &[X86_64GeneralReg],
     saved_float_regs: &[X86_64FloatReg],
     requested_stack_size: i32,
     fn_call_stack_size: i32,
-) -> i32 {
+) -> ProcFrame<'a> {
+    let start = buf.len();
     X86_64Assembler::push_reg64(buf, X86_64GeneralReg::RBP);
+    let frame_pointer_saved = (buf.len() - start) as u32;
     X86_64Assembler::mov_reg64_reg64(buf, X86_64GeneralReg::RBP, X86_64GeneralReg::RSP);
+    let frame_pointer_set = (buf.len() - start) as u32;
+
+    let mut frame = ProcFrame {
+        frame_pointer_saved,
+        frame_pointer_set,
+        stack_allocated: frame_pointer_set,
+        stack_size: 0,
+        saved_regs: bumpalo::vec![in buf.bump()],
+        prologue_size: frame_pointer_set,
+        frame_pointer_restored: 0,
+    };
 
     let full_stack_size = match requested_stack_size
         .checked_add(8 * (saved_general_regs.len() + saved_float_regs.len()) as i32)
@@ -1079,21 +1329,34 @@ fn x86_64_generic_setup_stack(
                 X86_64GeneralReg::RSP,
                 aligned_stack_size,
             );
+            frame.stack_allocated = (buf.len() - start) as u32;
+            frame.stack_size = aligned_stack_size as u32;
 
             // Put values at the top of the stack to avoid conflicts with previously saved variables.
             let mut offset = aligned_stack_size - fn_call_stack_size;
             for reg in saved_general_regs {
                 X86_64Assembler::mov_base32_reg64(buf, -offset, *reg);
+                frame.saved_regs.push(SavedReg {
+                    reg: reg.value(),
+                    is_float: false,
+                    base_offset: -offset,
+                    code_offset: (buf.len() - start) as u32,
+                });
                 offset -= 8;
             }
             for reg in saved_float_regs {
                 X86_64Assembler::mov_base32_freg64(buf, -offset, *reg);
+                frame.saved_regs.push(SavedReg {
+                    reg: reg.value(),
+                    is_float: true,
+                    base_offset: -offset,
+                    code_offset: (buf.len() - start) as u32,
+                });
                 offset -= 8;
             }
-            aligned_stack_size
-        } else {
-            0
+            frame.prologue_size = (buf.len() - start) as u32;
         }
+        frame
     } else {
         internal_error!("Ran out of stack space");
     }
@@ -1173,6 +1436,24 @@ impl Assembler<X86_64GeneralReg, X86_64FloatReg> for X86_64Assembler {
         andpd_freg64_freg64(buf, dst, src);
     }
 
+    #[inline(always)]
+    fn abs_freg32_freg32(
+        buf: &mut Vec<'_, u8>,
+        relocs: &mut Vec<'_, Relocation>,
+        dst: X86_64FloatReg,
+        src: X86_64FloatReg,
+    ) {
+        movss_freg32_rip_offset32(buf, dst, 0);
+
+        // TODO: make sure this constant only loads once instead of every call to abs
+        relocs.push(Relocation::LocalData {
+            offset: buf.len() as u64 - 4,
+            data: 0x7fffffffu32.to_le_bytes().to_vec(),
+        });
+
+        andpd_freg64_freg64(buf, dst, src);
+    }
+
     #[inline(always)]
     fn add_reg64_reg64_imm32(
         buf: &mut Vec<'_, u8>,
@@ -1716,6 +1997,38 @@ impl Assembler<X86_64GeneralReg, X86_64FloatReg> for X86_64Assembler {
         mov_reg64_reg64(buf, dst, src1);
         sub_reg64_reg64(buf, dst, src2);
     }
+    #[inline(always)]
+    fn sub_freg32_freg32_freg32(
+        buf: &mut Vec<'_, u8>,
+        dst: X86_64FloatReg,
+        src1: X86_64FloatReg,
+        src2: X86_64FloatReg,
+    ) {
+        if dst == src1 {
+            subss_freg32_freg32(buf, dst, src2);
+        } else if dst == src2 {
+            internal_error!("subtraction is not commutative, dst cannot be the same as src2");
+        } else {
+            movss_freg32_freg32(buf, dst, src1);
+            subss_freg32_freg32(buf, dst, src2);
+        }
+    }
+    #[inline(always)]
+    fn sub_freg64_freg64_freg64(
+        buf: &mut Vec<'_, u8>,
+        dst: X86_64FloatReg,
+        src1: X86_64FloatReg,
+        src2: X86_64FloatReg,
+    ) {
+        if dst == src1 {
+            subsd_freg64_freg64(buf, dst, src2);
+        } else if dst == src2 {
+            internal_error!("subtraction is not commutative, dst cannot be the same as src2");
+        } else {
+            movsd_freg64_freg64(buf, dst, src1);
+            subsd_freg64_freg64(buf, dst, src2);
+        }
+    }
 
     #[inline(always)]
     fn eq_reg_reg_reg(
@@ -2215,6 +2528,46 @@ fn addss_freg32_freg32(buf: &mut Vec<'_, u8>, dst: X86_64FloatReg, src: X86_64Fl
     }
 }
 
+/// `SUBSD xmm1,xmm2/m64` -> Subtract the low double-precision floating-point value in xmm2/mem from xmm1 and store the result in xmm1.
+#[inline(always)]
+fn subsd_freg64_freg64(buf: &mut Vec<'_, u8>, dst: X86_64FloatReg, src: X86_64FloatReg) {
+    let dst_high = dst as u8 > 7;
+    let dst_mod = dst as u8 % 8;
+    let src_high = src as u8 > 7;
+    let src_mod = src as u8 % 8;
+    if dst_high || src_high {
+        buf.extend([
+            0xF2,
+            0x40 | ((dst_high as u8) << 2) | (src_high as u8),
+            0x0F,
+            0x5C,
+            0xC0 | (dst_mod << 3) | (src_mod),
+        ])
+    } else {
+        buf.extend([0xF2, 0x0F, 0x5C, 0xC0 | (dst_mod << 3) | (src_mod)])
+    }
+}
+
+/// `SUBSS xmm1,xmm2/m32` -> Subtract the low single-precision floating-point value in xmm2/mem from xmm1 and store the result in xmm1.
+#[inline(always)]
+fn subss_freg32_freg32(buf: &mut Vec<'_, u8>, dst: X86_64FloatReg, src: X86_64FloatReg) {
+    let dst_high = dst as u8 > 7;
+    let dst_mod = dst as u8 % 8;
+    let src_high = src as u8 > 7;
+    let src_mod = src as u8 % 8;
+    if dst_high || src_high {
+        buf.extend([
+            0xF3,
+            0x40 | ((dst_high as u8) << 2) | (src_high as u8),
+            0x0F,
+            0x5C,
+            0xC0 | (dst_mod << 3) | (src_mod),
+        ])
+    } else {
+        buf.extend([0xF3, 0x0F, 0x5C, 0xC0 | (dst_mod << 3) | (src_mod)])
+    }
+}
+
 /// `MULSD xmm1,xmm2/m64` -> Multiply the low double-precision floating-point value from xmm2/mem to xmm1 and store the result in xmm1.
 #[inline(always)]
 fn mulsd_freg64_freg64(buf: &mut Vec<'_, u8>, dst: X86_64FloatReg, src: X86_64FloatReg) {
@@ -3489,6 +3842,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_subsd_freg64_freg64() {
+        disassembler_test!(
+            subsd_freg64_freg64,
+            |reg1, reg2| format!("subsd {}, {}", reg1, reg2),
+            ALL_FLOAT_REGS,
+            ALL_FLOAT_REGS
+        );
+    }
+
+    #[test]
+    fn test_subss_freg32_freg32() {
+        disassembler_test!(
+            subss_freg32_freg32,
+            |reg1, reg2| format!("subss {}, {}", reg1, reg2),
+            ALL_FLOAT_REGS,
+            ALL_FLOAT_REGS
+        );
+    }
+
     #[test]
     fn test_andpd_freg64_freg64() {
         disassembler_test!(
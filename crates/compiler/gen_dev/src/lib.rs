@@ -23,7 +23,10 @@ use roc_mono::layout::{
 };
 use roc_mono::list_element_layout;
 
+use crate::liveness::Liveness;
+
 mod generic64;
+mod liveness;
 mod object_builder;
 pub use object_builder::build_module;
 mod run_roc;
@@ -79,6 +82,37 @@ pub enum Relocation {
     },
 }
 
+/// The stack frame that the prologue of a proc sets up.
+/// Object formats with unwind tables use this to tell unwinders how to walk through the proc.
+/// Code offsets are relative to the start of the proc and point just past the instruction they describe.
+#[derive(Debug)]
+pub struct ProcFrame<'a> {
+    /// The caller's frame pointer has been saved on the stack.
+    pub frame_pointer_saved: u32,
+    /// The frame pointer points at the saved frame pointer of the caller.
+    pub frame_pointer_set: u32,
+    /// `stack_size` bytes of stack have been allocated below the saved frame pointer.
+    pub stack_allocated: u32,
+    pub stack_size: u32,
+    pub saved_regs: Vec<'a, SavedReg>,
+    /// The prologue is done, the body of the proc starts here.
+    pub prologue_size: u32,
+    /// The epilogue has restored the caller's frame pointer, only the return is left.
+    /// This is filled in once the proc is finalized.
+    pub frame_pointer_restored: u32,
+}
+
+/// A callee saved register that the prologue of a proc stores in its stack frame.
+#[derive(Debug, Clone, Copy)]
+pub struct SavedReg {
+    /// The register number, as encoded in instructions.
+    pub reg: u8,
+    pub is_float: bool,
+    /// The frame pointer relative offset the register is stored at.
+    pub base_offset: i32,
+    pub code_offset: u32,
+}
+
 trait Backend<'a> {
     fn env(&self) -> &Env<'a>;
     fn interns(&self) -> &Interns;
@@ -169,13 +203,19 @@ trait Backend<'a> {
 
     /// reset resets any registers or other values that may be occupied at the end of a procedure.
     /// It also passes basic procedure information to the builder for setup of the next function.
-    fn reset(&mut self, name: String, is_self_recursive: SelfRecursive);
+    fn reset(
+        &mut self,
+        name: String,
+        is_self_recursive: SelfRecursive,
+        stack_allocations: &'a [Symbol],
+    );
 
     /// finalize does any setup and cleanup that should happen around the procedure.
     /// finalize does setup because things like stack size and jump locations are not know until the function is written.
     /// For example, this can store the frame pointer and setup stack space.
     /// finalize is run at the end of build_proc when all internal code is finalized.
-    fn finalize(&mut self) -> (Vec<u8>, Vec<Relocation>);
+    /// It also describes the stack frame it set up.
+    fn finalize(&mut self) -> (Vec<u8>, Vec<Relocation>, ProcFrame<'a>);
 
     // load_args is used to let the backend know what the args are.
     // The backend should track these args so it can use them as needed.
@@ -185,12 +225,18 @@ trait Backend<'a> {
     fn build_wrapped_jmp(&mut self) -> (&'a [u8], u64);
 
     /// build_proc creates a procedure and outputs it to the wrapped object writer.
-    /// Returns the procedure bytes, its relocations, and the names of the refcounting functions it references.
+    /// Returns the procedure bytes, its relocations, the names of the refcounting functions it references,
+    /// and its stack frame.
     fn build_proc(
         &mut self,
         proc: Proc<'a>,
         layout_ids: &mut LayoutIds<'a>,
-    ) -> (Vec<u8>, Vec<Relocation>, Vec<'a, (Symbol, String)>) {
+    ) -> (
+        Vec<u8>,
+        Vec<Relocation>,
+        Vec<'a, (Symbol, String)>,
+        ProcFrame<'a>,
+    ) {
         let proc_name = self.function_symbol_to_string(
             proc.name.name(),
             proc.args.iter().map(|t| t.0),
@@ -198,7 +244,7 @@ trait Backend<'a> {
             proc.ret_layout,
         );
 
-        self.reset(proc_name, proc.is_self_recursive);
+        self.reset(proc_name, proc.is_self_recursive, proc.stack_allocations);
         self.load_args(proc.args, &proc.ret_layout);
         for (layout, sym) in proc.args {
             self.set_layout_map(*sym, layout);
@@ -215,12 +261,14 @@ trait Backend<'a> {
 
             helper_proc_names.push((*rc_proc_sym, name));
         }
-        let (bytes, relocs) = self.finalize();
-        (bytes, relocs, helper_proc_names)
+        let (bytes, relocs, frame) = self.finalize();
+        (bytes, relocs, helper_proc_names, frame)
     }
 
     /// build_stmt builds a statement and outputs at the end of the buffer.
     fn build_stmt(&mut self, stmt: &Stmt<'a>, ret_layout: &InLayout<'a>) {
+        self.enter_stmt(stmt);
+
         match stmt {
             Stmt::Let(sym, expr, layout, following) => {
                 self.build_expr(sym, expr, layout);
@@ -575,6 +623,27 @@ trait Backend<'a> {
             LowLevel::NumSubChecked => {
                 self.build_num_sub_checked(sym, &args[0], &args[1], &arg_layouts[0], ret_layout)
             }
+            LowLevel::NumAddSaturated => match self.interner().get(*ret_layout) {
+                Layout::Builtin(Builtin::Int(int_width)) => self.build_fn_call(
+                    sym,
+                    bitcode::NUM_ADD_SATURATED_INT[int_width].to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                ),
+                Layout::Builtin(Builtin::Float(_)) => {
+                    // saturated add is just normal add
+                    self.build_num_add(sym, &args[0], &args[1], ret_layout)
+                }
+                Layout::Builtin(Builtin::Decimal) => self.build_fn_call(
+                    sym,
+                    bitcode::DEC_ADD_SATURATED.to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                ),
+                _ => internal_error!("invalid return type"),
+            },
             LowLevel::NumAcos => self.build_fn_call(
                 sym,
                 bitcode::NUM_ACOS[FloatWidth::F64].to_string(),
@@ -598,6 +667,47 @@ trait Backend<'a> {
             ),
             LowLevel::NumMul => self.build_num_mul(sym, &args[0], &args[1], ret_layout),
             LowLevel::NumMulWrap => self.build_num_mul(sym, &args[0], &args[1], ret_layout),
+            LowLevel::NumMulSaturated => match self.interner().get(*ret_layout) {
+                Layout::Builtin(Builtin::Int(int_width)) => self.build_fn_call(
+                    sym,
+                    bitcode::NUM_MUL_SATURATED_INT[int_width].to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                ),
+                Layout::Builtin(Builtin::Float(_)) => {
+                    // saturated mul is just normal mul
+                    self.build_num_mul(sym, &args[0], &args[1], ret_layout)
+                }
+                Layout::Builtin(Builtin::Decimal) => self.build_fn_call(
+                    sym,
+                    bitcode::DEC_MUL_SATURATED.to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                ),
+                _ => internal_error!("invalid return type"),
+            },
+            LowLevel::NumMulChecked => {
+                let function_name = match self.interner().get(arg_layouts[0]) {
+                    Layout::Builtin(Builtin::Int(int_width)) => {
+                        &bitcode::NUM_MUL_CHECKED_INT[int_width]
+                    }
+                    Layout::Builtin(Builtin::Float(float_width)) => {
+                        &bitcode::NUM_MUL_CHECKED_FLOAT[float_width]
+                    }
+                    Layout::Builtin(Builtin::Decimal) => bitcode::DEC_MUL_WITH_OVERFLOW,
+                    x => internal_error!("NumMulChecked is not defined for {:?}", x),
+                };
+
+                self.build_fn_call(
+                    sym,
+                    function_name.to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                )
+            }
             LowLevel::NumDivTruncUnchecked | LowLevel::NumDivFrac => {
                 debug_assert_eq!(
                     2,
@@ -682,10 +792,13 @@ trait Backend<'a> {
                     // saturated sub is just normal sub
                     self.build_num_sub(sym, &args[0], &args[1], ret_layout)
                 }
-                Layout::Builtin(Builtin::Decimal) => {
-                    // self.load_args_and_call_zig(backend, bitcode::DEC_SUB_SATURATED)
-                    todo!()
-                }
+                Layout::Builtin(Builtin::Decimal) => self.build_fn_call(
+                    sym,
+                    bitcode::DEC_SUB_SATURATED.to_string(),
+                    args,
+                    arg_layouts,
+                    ret_layout,
+                ),
                 _ => internal_error!("invalid return type"),
             },
             LowLevel::NumBitwiseAnd => {
@@ -1191,6 +1304,9 @@ trait Backend<'a> {
 
                 self.build_ptr_write(*sym, args[0], args[1], element_layout);
             }
+            LowLevel::PtrStore => {
+                self.build_ptr_store(*sym, args[0], args[1], arg_layouts[1]);
+            }
             LowLevel::RefCountDecRcPtr => self.build_fn_call(
                 sym,
                 bitcode::UTILS_DECREF_RC_PTR.to_string(),
@@ -1636,6 +1752,15 @@ trait Backend<'a> {
         element_layout: InLayout<'a>,
     );
 
+    /// Write `value` to the address in `ptr`, and return unit
+    fn build_ptr_store(
+        &mut self,
+        sym: Symbol,
+        ptr: Symbol,
+        value: Symbol,
+        element_layout: InLayout<'a>,
+    );
+
     /// literal_map gets the map from symbol to literal and layout, used for lazy loading and literal folding.
     fn literal_map(&mut self) -> &mut MutMap<Symbol, (*const Literal<'a>, *const InLayout<'a>)>;
 
@@ -1723,11 +1848,6 @@ trait Backend<'a> {
     /// free_symbol frees any registers or stack space used to hold a symbol.
     fn free_symbol(&mut self, sym: &Symbol);
 
-    /// set_last_seen sets the statement a symbol was last seen in.
-    fn set_last_seen(&mut self, sym: Symbol, stmt: &Stmt<'a>) {
-        self.last_seen_map().insert(sym, stmt);
-    }
-
     /// last_seen_map gets the map from symbol to when it is last seen in the function.
     fn last_seen_map(&mut self) -> &mut MutMap<Symbol, *const Stmt<'a>>;
 
@@ -1768,142 +1888,21 @@ trait Backend<'a> {
     /// set_free_map sets the free map to the given map.
     fn set_free_map(&mut self, map: MutMap<*const Stmt<'a>, Vec<'a, Symbol>>);
 
-    /// scan_ast runs through the ast and fill the last seen map.
-    /// This must iterate through the ast in the same way that build_stmt does. i.e. then before else.
+    /// scan_ast runs the liveness analysis over the body of a proc.
+    /// It fills the last seen map and hands the full results to the backend.
     fn scan_ast(&mut self, stmt: &Stmt<'a>) {
-        // Join map keeps track of join point parameters so that we can keep them around while they still might be jumped to.
-        let mut join_map: MutMap<JoinPointId, &'a [Param<'a>]> = MutMap::default();
-        match stmt {
-            Stmt::Let(sym, expr, _, following) => {
-                self.set_last_seen(*sym, stmt);
-                match expr {
-                    Expr::Literal(_) => {}
-                    Expr::NullPointer => {}
+        let liveness = Liveness::analyze(self.env().arena, stmt);
 
-                    Expr::Call(call) => self.scan_ast_call(call, stmt),
-
-                    Expr::Tag { arguments, .. } => {
-                        for sym in *arguments {
-                            self.set_last_seen(*sym, stmt);
-                        }
-                    }
-                    Expr::ExprBox { symbol } => {
-                        self.set_last_seen(*symbol, stmt);
-                    }
-                    Expr::ExprUnbox { symbol } => {
-                        self.set_last_seen(*symbol, stmt);
-                    }
-                    Expr::Struct(syms) => {
-                        for sym in *syms {
-                            self.set_last_seen(*sym, stmt);
-                        }
-                    }
-                    Expr::StructAtIndex { structure, .. } => {
-                        self.set_last_seen(*structure, stmt);
-                    }
-                    Expr::GetTagId { structure, .. } => {
-                        self.set_last_seen(*structure, stmt);
-                    }
-                    Expr::UnionAtIndex { structure, .. } => {
-                        self.set_last_seen(*structure, stmt);
-                    }
-                    Expr::Array { elems, .. } => {
-                        for elem in *elems {
-                            if let ListLiteralElement::Symbol(sym) = elem {
-                                self.set_last_seen(*sym, stmt);
-                            }
-                        }
-                    }
-                    Expr::Reuse {
-                        symbol, arguments, ..
-                    } => {
-                        self.set_last_seen(*symbol, stmt);
-                        for sym in *arguments {
-                            self.set_last_seen(*sym, stmt);
-                        }
-                    }
-                    Expr::Reset { symbol, .. } | Expr::ResetRef { symbol, .. } => {
-                        self.set_last_seen(*symbol, stmt);
-                    }
-                    Expr::EmptyArray => {}
-                    Expr::RuntimeErrorFunction(_) => {}
-                }
-                self.scan_ast(following);
-            }
-
-            Stmt::Switch {
-                cond_symbol,
-                branches,
-                default_branch,
-                ..
-            } => {
-                self.set_last_seen(*cond_symbol, stmt);
-                for (_, _, branch) in *branches {
-                    self.scan_ast(branch);
-                }
-                self.scan_ast(default_branch.1);
-            }
-            Stmt::Ret(sym) => {
-                self.set_last_seen(*sym, stmt);
-            }
-            Stmt::Refcounting(modify, following) => {
-                let sym = modify.get_symbol();
-
-                self.set_last_seen(sym, stmt);
-                self.scan_ast(following);
-            }
-            Stmt::Join {
-                parameters,
-                body: continuation,
-                remainder,
-                id: JoinPointId(sym),
-                ..
-            } => {
-                self.set_last_seen(*sym, stmt);
-                join_map.insert(JoinPointId(*sym), parameters);
-                for param in *parameters {
-                    self.set_last_seen(param.symbol, stmt);
-                }
-                self.scan_ast(remainder);
-                self.scan_ast(continuation);
-            }
-            Stmt::Jump(JoinPointId(sym), symbols) => {
-                if let Some(parameters) = join_map.get(&JoinPointId(*sym)) {
-                    // Keep the parameters around. They will be overwritten when jumping.
-                    for param in *parameters {
-                        self.set_last_seen(param.symbol, stmt);
-                    }
-                }
-                for sym in *symbols {
-                    self.set_last_seen(*sym, stmt);
-                }
-            }
-
-            Stmt::Dbg { .. } => todo!("dbg not implemented in the dev backend"),
-            Stmt::Expect { .. } => todo!("expect is not implemented in the dev backend"),
-            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the dev backend"),
-
-            Stmt::Crash(msg, _crash_tag) => {
-                self.set_last_seen(*msg, stmt);
-            }
+        for (sym, stmt) in liveness.last_seen.iter() {
+            self.last_seen_map().insert(*sym, *stmt);
         }
+
+        self.set_liveness(liveness);
     }
 
-    fn scan_ast_call(&mut self, call: &roc_mono::ir::Call, stmt: &roc_mono::ir::Stmt<'a>) {
-        let roc_mono::ir::Call {
-            call_type,
-            arguments,
-        } = call;
+    /// set_liveness gives the backend the liveness of the current proc, for register allocation.
+    fn set_liveness(&mut self, liveness: Liveness<'a>);
 
-        for sym in *arguments {
-            self.set_last_seen(*sym, stmt);
-        }
-
-        match call_type {
-            CallType::ByName { .. } => {}
-            CallType::LowLevel { .. } => {}
-            CallType::HigherOrder { .. } => {}
-            CallType::Foreign { .. } => {}
-        }
-    }
+    /// enter_stmt tells the backend which statement it is about to build, for register allocation.
+    fn enter_stmt(&mut self, stmt: &Stmt<'a>);
 }
@@ -0,0 +1,261 @@
+//! Liveness analysis over the mono IR of a single proc.
+//!
+//! The backend builds statements in a fixed order (`Let` before its continuation, switch branches
+//! in order, and the remainder of a join point before its body). The analysis walks the IR in that
+//! same order and records, for every symbol, the statement after which it is no longer needed,
+//! and the positions of all of its uses, so that the register allocator can tell which value is
+//! needed again furthest in the future.
+//!
+//! Join point bodies complicate this: a body that is jumped to from within itself is a loop, so a
+//! symbol it uses must stay alive until the whole join point is built, even if its last use
+//! appears early in the body. The same holds for the join point parameters, whose storage is
+//! written by every jump.
+
+use bumpalo::{collections::Vec, Bump};
+use roc_collections::all::{MutMap, MutSet};
+use roc_module::symbol::Symbol;
+use roc_mono::ir::{Expr, JoinPointId, ListLiteralElement, Stmt};
+
+/// What the body of a join point needs from the code around it.
+#[derive(Debug)]
+pub struct JoinPointLiveness<'a> {
+    /// The body jumps back to its own join point.
+    pub is_loop: bool,
+    /// Symbols defined outside of the body that the body uses.
+    pub live_in: Vec<'a, Symbol>,
+}
+
+pub struct Liveness<'a> {
+    /// The statement after which each symbol is dead.
+    pub last_seen: MutMap<Symbol, *const Stmt<'a>>,
+    /// The position of the last use of each symbol, counting statements in build order.
+    pub last_use: MutMap<Symbol, u32>,
+    /// The positions of all uses of each symbol, in increasing order.
+    pub uses: MutMap<Symbol, std::vec::Vec<u32>>,
+    /// The position of every statement of the proc.
+    pub positions: MutMap<*const Stmt<'a>, u32>,
+    pub join_points: MutMap<JoinPointId, JoinPointLiveness<'a>>,
+}
+
+/// A join point body that is currently being walked.
+struct OpenBody {
+    id: JoinPointId,
+    is_loop: bool,
+    used: MutSet<Symbol>,
+    defined: MutSet<Symbol>,
+}
+
+struct Walker<'a> {
+    arena: &'a Bump,
+    position: u32,
+    open_bodies: std::vec::Vec<OpenBody>,
+    liveness: Liveness<'a>,
+}
+
+impl<'a> Liveness<'a> {
+    pub fn analyze(arena: &'a Bump, stmt: &Stmt<'a>) -> Self {
+        let mut walker = Walker {
+            arena,
+            position: 0,
+            open_bodies: std::vec::Vec::new(),
+            liveness: Liveness {
+                last_seen: MutMap::default(),
+                last_use: MutMap::default(),
+                uses: MutMap::default(),
+                positions: MutMap::default(),
+                join_points: MutMap::default(),
+            },
+        };
+
+        walker.stmt(stmt);
+
+        walker.liveness
+    }
+}
+
+impl<'a> Walker<'a> {
+    fn use_symbol(&mut self, sym: Symbol, stmt: &Stmt<'a>) {
+        self.liveness.last_seen.insert(sym, stmt);
+        self.record_use(sym);
+
+        for body in self.open_bodies.iter_mut() {
+            body.used.insert(sym);
+        }
+    }
+
+    fn record_use(&mut self, sym: Symbol) {
+        self.liveness.last_use.insert(sym, self.position);
+
+        let uses = self.liveness.uses.entry(sym).or_default();
+        if uses.last() != Some(&self.position) {
+            uses.push(self.position);
+        }
+    }
+
+    fn define_symbol(&mut self, sym: Symbol, stmt: &Stmt<'a>) {
+        // a definition that is never used is dead right after the statement
+        self.use_symbol(sym, stmt);
+
+        for body in self.open_bodies.iter_mut() {
+            body.defined.insert(sym);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<'a>) {
+        self.position += 1;
+        self.liveness.positions.insert(stmt, self.position);
+
+        match stmt {
+            Stmt::Let(sym, expr, _, following) => {
+                self.define_symbol(*sym, stmt);
+                self.expr(expr, stmt);
+                self.stmt(following);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                self.use_symbol(*cond_symbol, stmt);
+                for (_, _, branch) in *branches {
+                    self.stmt(branch);
+                }
+                self.stmt(default_branch.1);
+            }
+            Stmt::Ret(sym) => {
+                self.use_symbol(*sym, stmt);
+            }
+            Stmt::Refcounting(modify, following) => {
+                self.use_symbol(modify.get_symbol(), stmt);
+                self.stmt(following);
+            }
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                // the id is freed with the join point, which also forgets where its jumps are
+                self.define_symbol(id.0, stmt);
+                for param in *parameters {
+                    self.define_symbol(param.symbol, stmt);
+                }
+
+                self.stmt(remainder);
+
+                self.open_bodies.push(OpenBody {
+                    id: *id,
+                    is_loop: false,
+                    used: MutSet::default(),
+                    defined: MutSet::default(),
+                });
+                self.stmt(body);
+                let open_body = self.open_bodies.pop().unwrap();
+
+                // Values used by the body, and the parameters that jumps write to, must survive
+                // until the body is done, because the body may run any number of times.
+                let mut live_in = Vec::new_in(self.arena);
+                for sym in open_body.used.iter() {
+                    if !open_body.defined.contains(sym) {
+                        live_in.push(*sym);
+                    }
+                }
+
+                for sym in live_in
+                    .iter()
+                    .chain(parameters.iter().map(|param| &param.symbol))
+                {
+                    self.liveness.last_seen.insert(*sym, stmt);
+                    self.record_use(*sym);
+                }
+
+                live_in.retain(|sym| !parameters.iter().any(|param| param.symbol == *sym));
+
+                self.liveness.join_points.insert(
+                    *id,
+                    JoinPointLiveness {
+                        is_loop: open_body.is_loop,
+                        live_in,
+                    },
+                );
+            }
+            Stmt::Jump(id, symbols) => {
+                for sym in *symbols {
+                    self.use_symbol(*sym, stmt);
+                }
+
+                if let Some(body) = self.open_bodies.iter_mut().find(|body| body.id == *id) {
+                    body.is_loop = true;
+                }
+            }
+
+            Stmt::Dbg { .. } => todo!("dbg not implemented in the dev backend"),
+            Stmt::Expect { .. } => todo!("expect is not implemented in the dev backend"),
+            Stmt::ExpectFx { .. } => todo!("expect-fx is not implemented in the dev backend"),
+
+            Stmt::Crash(msg, _crash_tag) => {
+                self.use_symbol(*msg, stmt);
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr<'a>, stmt: &Stmt<'a>) {
+        match expr {
+            Expr::Literal(_) => {}
+            Expr::NullPointer => {}
+
+            Expr::Call(call) => {
+                for sym in call.arguments {
+                    self.use_symbol(*sym, stmt);
+                }
+            }
+
+            Expr::Tag { arguments, .. } => {
+                for sym in *arguments {
+                    self.use_symbol(*sym, stmt);
+                }
+            }
+            Expr::ExprBox { symbol } => {
+                self.use_symbol(*symbol, stmt);
+            }
+            Expr::ExprUnbox { symbol } => {
+                self.use_symbol(*symbol, stmt);
+            }
+            Expr::Struct(syms) => {
+                for sym in *syms {
+                    self.use_symbol(*sym, stmt);
+                }
+            }
+            Expr::StructAtIndex { structure, .. } => {
+                self.use_symbol(*structure, stmt);
+            }
+            Expr::GetTagId { structure, .. } => {
+                self.use_symbol(*structure, stmt);
+            }
+            Expr::UnionAtIndex { structure, .. } | Expr::UnionFieldPtrAtIndex { structure, .. } => {
+                self.use_symbol(*structure, stmt);
+            }
+            Expr::Array { elems, .. } => {
+                for elem in *elems {
+                    if let ListLiteralElement::Symbol(sym) = elem {
+                        self.use_symbol(*sym, stmt);
+                    }
+                }
+            }
+            Expr::Reuse {
+                symbol, arguments, ..
+            } => {
+                self.use_symbol(*symbol, stmt);
+                for sym in *arguments {
+                    self.use_symbol(*sym, stmt);
+                }
+            }
+            Expr::Reset { symbol, .. } | Expr::ResetRef { symbol, .. } => {
+                self.use_symbol(*symbol, stmt);
+            }
+            Expr::EmptyArray => {}
+            Expr::RuntimeErrorFunction(_) => {}
+        }
+    }
+}
@@ -1,11 +1,11 @@
 use crate::generic64::{aarch64, new_backend_64bit, x86_64};
-use crate::{Backend, Env, Relocation};
+use crate::{Backend, Env, ProcFrame, Relocation};
 use bumpalo::collections::Vec;
 use object::write::{self, SectionId, SymbolId};
 use object::write::{Object, StandardSection, StandardSegment, Symbol, SymbolSection};
 use object::{
-    Architecture, BinaryFormat, Endianness, RelocationEncoding, RelocationKind, SectionKind,
-    SymbolFlags, SymbolKind, SymbolScope,
+    macho, Architecture, BinaryFormat, Endianness, RelocationEncoding, RelocationKind,
+    SectionFlags, SectionKind, SymbolFlags, SymbolKind, SymbolScope,
 };
 use roc_collections::all::MutMap;
 use roc_error_macros::internal_error;
@@ -28,8 +28,10 @@ pub fn build_module<'a, 'r>(
     interns: &'r mut Interns,
     layout_interner: &'r mut STLayoutInterner<'a>,
     target: &Triple,
-    procedures: MutMap<(symbol::Symbol, ProcLayout<'a>), Proc<'a>>,
+    mut procedures: MutMap<(symbol::Symbol, ProcLayout<'a>), Proc<'a>>,
 ) -> Object<'a> {
+    roc_mono::stack_allocation::stack_allocate_procs(env.arena, layout_interner, &mut procedures);
+
     match target {
         Triple {
             architecture: TargetArch::X86_64,
@@ -74,6 +76,23 @@ pub fn build_module<'a, 'r>(
                 ),
             )
         }
+        Triple {
+            architecture: TargetArch::X86_64,
+            binary_format: TargetBF::Coff,
+            ..
+        } if cfg!(feature = "target-x86_64") => {
+            let backend = new_backend_64bit::<
+                x86_64::X86_64GeneralReg,
+                x86_64::X86_64FloatReg,
+                x86_64::X86_64Assembler,
+                x86_64::X86_64WindowsFastcall,
+            >(env, TargetInfo::default_x86_64(), interns, layout_interner);
+            build_object(
+                procedures,
+                backend,
+                Object::new(BinaryFormat::Coff, Architecture::X86_64, Endianness::Little),
+            )
+        }
         Triple {
             architecture: TargetArch::Aarch64(_),
             binary_format: TargetBF::Elf,
@@ -141,26 +160,20 @@ fn generate_wrapper<'a, B: Backend<'a>>(
 
     let name = wraps.as_str().as_bytes();
     // If the symbol is an undefined zig builtin, we need to add it here.
+    // COFF weak externals need a default definition, so there the symbol is a plain external.
     let symbol = Symbol {
         name: name.to_vec(),
         value: 0,
         size: 0,
         kind: SymbolKind::Text,
         scope: SymbolScope::Dynamic,
-        weak: true,
+        weak: output.format() != BinaryFormat::Coff,
         section: SymbolSection::Undefined,
         flags: SymbolFlags::None,
     };
     output.add_symbol(symbol);
     if let Some(sym_id) = output.symbol_id(name) {
-        let reloc = write::Relocation {
-            offset: offset + proc_offset,
-            size: 32,
-            kind: RelocationKind::PltRelative,
-            encoding: RelocationEncoding::X86Branch,
-            symbol: sym_id,
-            addend: -4,
-        };
+        let reloc = linked_function_relocation(output, proc_data, offset, proc_offset, sym_id);
 
         match output.add_relocation(text_section, reloc) {
             Ok(obj) => obj,
@@ -176,7 +189,11 @@ fn build_object<'a, B: Backend<'a>>(
     mut backend: B,
     mut output: Object<'a>,
 ) -> Object<'a> {
-    let data_section = output.section_id(StandardSection::Data);
+    // The PE surgical linker only picks up `.text` and `.rdata`, our data is read-only anyway.
+    let data_section = match output.format() {
+        BinaryFormat::Coff => output.section_id(StandardSection::ReadOnlyData),
+        _ => output.section_id(StandardSection::Data),
+    };
 
     let arena = backend.env().arena;
 
@@ -290,11 +307,13 @@ fn build_object<'a, B: Backend<'a>>(
 
     // Build procedures from user code
     let mut relocations = bumpalo::vec![in arena];
+    let mut frames = bumpalo::vec![in arena];
     for (fn_name, section_id, proc_id, proc) in procs {
         build_proc(
             &mut output,
             &mut backend,
             &mut relocations,
+            &mut frames,
             &mut layout_ids,
             data_section,
             fn_name,
@@ -305,7 +324,7 @@ fn build_object<'a, B: Backend<'a>>(
     }
 
     // Generate IR for specialized helper procs (refcounting & equality)
-    let helper_procs = {
+    let (helper_procs, caller_procs) = {
         let (module_id, _interner, interns, helper_proc_gen, caller_procs) =
             backend.module_interns_helpers_mut();
 
@@ -313,20 +332,30 @@ fn build_object<'a, B: Backend<'a>>(
         std::mem::swap(caller_procs, &mut owned_caller_procs);
 
         let ident_ids = interns.all_ident_ids.get_mut(&module_id).unwrap();
-        let mut helper_procs = helper_proc_gen.take_procs();
-
-        helper_procs.extend(owned_caller_procs.into_iter().map(|cp| cp.proc));
+        let helper_procs = helper_proc_gen.take_procs();
         module_id.register_debug_idents(ident_ids);
 
-        helper_procs
+        (helper_procs, owned_caller_procs)
     };
 
     let empty = bumpalo::collections::Vec::new_in(arena);
     let helper_symbols_and_layouts = std::mem::replace(backend.helper_proc_symbols_mut(), empty);
-    let mut helper_names_symbols_procs = Vec::with_capacity_in(helper_procs.len(), arena);
+    let mut helper_names_symbols_procs =
+        Vec::with_capacity_in(helper_procs.len() + caller_procs.len(), arena);
+
+    // Caller procs carry their own linker data, so they are not part of `helper_proc_symbols`
+    // and must not be zipped with the refcount and equality procs.
+    let helper_symbols_layouts_procs = helper_symbols_and_layouts
+        .into_iter()
+        .zip(helper_procs)
+        .chain(
+            caller_procs
+                .into_iter()
+                .map(|cp| ((cp.proc_symbol, cp.proc_layout), cp.proc)),
+        );
 
     // Names and linker data for helpers
-    for ((sym, layout), proc) in helper_symbols_and_layouts.into_iter().zip(helper_procs) {
+    for ((sym, layout), proc) in helper_symbols_layouts_procs {
         let fn_name = backend.function_symbol_to_string(
             sym,
             layout.arguments.iter().copied(),
@@ -341,11 +370,7 @@ fn build_object<'a, B: Backend<'a>>(
             }
         } else {
             // The symbol isn't defined yet and will just be used by other rc procs.
-            let section_id = output.add_section(
-                output.segment_name(StandardSegment::Text).to_vec(),
-                format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
-                SectionKind::Text,
-            );
+            let section_id = add_proc_section(&mut output, sym);
 
             let rc_symbol = Symbol {
                 name: fn_name.as_bytes().to_vec(),
@@ -370,6 +395,7 @@ fn build_object<'a, B: Backend<'a>>(
             &mut output,
             &mut backend,
             &mut relocations,
+            &mut frames,
             &mut layout_ids,
             data_section,
             fn_name,
@@ -386,6 +412,13 @@ fn build_object<'a, B: Backend<'a>>(
             Err(e) => internal_error!("{:?}", e),
         }
     }
+
+    if output.format() == BinaryFormat::Coff {
+        build_windows_unwind_info(&mut output, frames);
+    } else {
+        build_eh_frame(&mut output, frames);
+    }
+
     output
 }
 
@@ -428,6 +461,7 @@ fn build_exposed_proc<'a, B: Backend<'a>>(backend: &mut B, proc: &Proc<'a>) -> P
         ret_layout: proc.ret_layout,
         is_self_recursive: roc_mono::ir::SelfRecursive::NotSelfRecursive,
         host_exposed_layouts: roc_mono::ir::HostExposedLayouts::NotHostExposed,
+        stack_allocations: &[],
     }
 }
 
@@ -509,6 +543,7 @@ fn build_exposed_generic_proc<'a, B: Backend<'a>>(backend: &mut B, proc: &Proc<'
         ret_layout: roc_mono::layout::Layout::UNIT,
         is_self_recursive: roc_mono::ir::SelfRecursive::NotSelfRecursive,
         host_exposed_layouts: roc_mono::ir::HostExposedLayouts::NotHostExposed,
+        stack_allocations: &[],
     }
 }
 
@@ -530,11 +565,7 @@ fn build_proc_symbol<'a, B: Backend<'a>>(
 ) {
     let sym = proc.name.name();
 
-    let section_id = output.add_section(
-        output.segment_name(StandardSegment::Text).to_vec(),
-        format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
-        SectionKind::Text,
-    );
+    let section_id = add_proc_section(output, sym);
 
     let fn_name = match exposed {
         Exposed::ExposedGeneric => layout_ids
@@ -570,11 +601,493 @@ fn build_proc_symbol<'a, B: Backend<'a>>(
     procs.push((fn_name, section_id, proc_id, proc));
 }
 
+/// Relocates a call or reference to a function.
+/// x86_64 always patches a 32 bit displacement, while aarch64 patches whichever instruction is at `offset`.
+fn linked_function_relocation(
+    output: &Object,
+    proc_data: &[u8],
+    offset: u64,
+    proc_offset: u64,
+    symbol: SymbolId,
+) -> write::Relocation {
+    match output.architecture() {
+        Architecture::Aarch64 => {
+            let start = offset as usize;
+            let mut inst = [0; 4];
+            inst.copy_from_slice(&proc_data[start..start + 4]);
+
+            write::Relocation {
+                offset: offset + proc_offset,
+                size: 32,
+                kind: aarch64::linked_function_relocation_kind(
+                    output.format(),
+                    u32::from_le_bytes(inst),
+                ),
+                encoding: RelocationEncoding::Generic,
+                symbol,
+                addend: 0,
+            }
+        }
+        _ => write::Relocation {
+            offset: offset + proc_offset,
+            size: 32,
+            kind: RelocationKind::PltRelative,
+            encoding: RelocationEncoding::X86Branch,
+            symbol,
+            addend: -4,
+        },
+    }
+}
+
+/// Adds the section that the code of a proc goes into.
+/// Every proc gets its own section so the linker can drop unused ones.
+fn add_proc_section(output: &mut Object, sym: symbol::Symbol) -> SectionId {
+    let name = match output.format() {
+        // The PE surgical linker only picks up sections named `.text`.
+        BinaryFormat::Coff => b".text".to_vec(),
+        _ => format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
+    };
+
+    output.add_section(
+        output.segment_name(StandardSegment::Text).to_vec(),
+        name,
+        SectionKind::Text,
+    )
+}
+
+/// Adds `.pdata` and `.xdata` entries that tell Windows unwinders how to walk through each proc.
+fn build_windows_unwind_info<'a>(
+    output: &mut Object,
+    frames: Vec<'a, (SymbolId, u64, ProcFrame<'a>)>,
+) {
+    if frames.is_empty() {
+        return;
+    }
+
+    let pdata_section = output.add_section(vec![], b".pdata".to_vec(), SectionKind::ReadOnlyData);
+    let xdata_section = output.add_section(vec![], b".xdata".to_vec(), SectionKind::ReadOnlyData);
+    let xdata_symbol = output.section_symbol(xdata_section);
+
+    for (proc_id, proc_size, frame) in frames {
+        let unwind_info_offset =
+            output.append_section_data(xdata_section, &windows_unwind_info(&frame), 4);
+
+        // A RUNTIME_FUNCTION: the start and end of the proc, and its unwind info.
+        let entry_offset = output.append_section_data(pdata_section, &[0; 12], 4);
+        let fields = [
+            (0, proc_id, 0),
+            (4, proc_id, proc_size as i64),
+            (8, xdata_symbol, unwind_info_offset as i64),
+        ];
+        for (field_offset, symbol, addend) in fields {
+            let reloc = write::Relocation {
+                offset: entry_offset + field_offset,
+                size: 32,
+                kind: RelocationKind::ImageOffset,
+                encoding: RelocationEncoding::Generic,
+                symbol,
+                addend,
+            };
+
+            if let Err(e) = output.add_relocation(pdata_section, reloc) {
+                internal_error!("{:?}", e);
+            }
+        }
+    }
+}
+
+/// Encodes the UNWIND_INFO of a proc for x86_64 Windows.
+/// See https://learn.microsoft.com/en-us/cpp/build/exception-handling-x64
+///
+/// The frame pointer is not registered with the unwinder. The stack pointer does not move
+/// after the prologue, so the unwinder can find everything relative to it.
+fn windows_unwind_info(frame: &ProcFrame) -> std::vec::Vec<u8> {
+    const UWOP_PUSH_NONVOL: u8 = 0;
+    const UWOP_ALLOC_LARGE: u8 = 1;
+    const UWOP_ALLOC_SMALL: u8 = 2;
+    const UWOP_SAVE_NONVOL: u8 = 4;
+    const UWOP_SAVE_NONVOL_FAR: u8 = 5;
+    const RBP: u8 = 5;
+
+    let code_offset = |offset: u32| match u8::try_from(offset) {
+        Ok(offset) => offset,
+        Err(_) => internal_error!("the prologue is too long to describe in unwind info"),
+    };
+
+    // Each unwind code takes one or more 2 byte slots.
+    // The codes are listed in the reverse order of the prologue instructions they describe.
+    let mut slots: std::vec::Vec<[u8; 2]> = vec![];
+
+    for saved_reg in frame.saved_regs.iter().rev() {
+        debug_assert!(
+            !saved_reg.is_float,
+            "callee saved float registers are not used on Windows"
+        );
+        let offset = code_offset(saved_reg.code_offset);
+        let stack_offset = (frame.stack_size as i32 + saved_reg.base_offset) as u32;
+        match u16::try_from(stack_offset / 8) {
+            Ok(scaled_offset) => {
+                slots.push([offset, UWOP_SAVE_NONVOL | (saved_reg.reg << 4)]);
+                slots.push(scaled_offset.to_le_bytes());
+            }
+            Err(_) => {
+                let [b0, b1, b2, b3] = stack_offset.to_le_bytes();
+                slots.push([offset, UWOP_SAVE_NONVOL_FAR | (saved_reg.reg << 4)]);
+                slots.push([b0, b1]);
+                slots.push([b2, b3]);
+            }
+        }
+    }
+
+    if frame.stack_size > 0 {
+        let offset = code_offset(frame.stack_allocated);
+        match frame.stack_size {
+            8..=128 => {
+                let info = (frame.stack_size / 8 - 1) as u8;
+                slots.push([offset, UWOP_ALLOC_SMALL | (info << 4)]);
+            }
+            129..=524_280 => {
+                slots.push([offset, UWOP_ALLOC_LARGE]);
+                slots.push(((frame.stack_size / 8) as u16).to_le_bytes());
+            }
+            _ => {
+                let [b0, b1, b2, b3] = frame.stack_size.to_le_bytes();
+                slots.push([offset, UWOP_ALLOC_LARGE | (1 << 4)]);
+                slots.push([b0, b1]);
+                slots.push([b2, b3]);
+            }
+        }
+    }
+
+    slots.push([
+        code_offset(frame.frame_pointer_saved),
+        UWOP_PUSH_NONVOL | (RBP << 4),
+    ]);
+
+    let slot_count = match u8::try_from(slots.len()) {
+        Ok(count) => count,
+        Err(_) => internal_error!("too many unwind codes for one proc"),
+    };
+    // Version 1, no flags; the size of the prologue; the number of slots; no frame register.
+    let mut unwind_info = vec![1, code_offset(frame.prologue_size), slot_count, 0];
+    for slot in slots {
+        unwind_info.extend(slot);
+    }
+    // The slots are padded to a multiple of 4 bytes.
+    if slot_count % 2 == 1 {
+        unwind_info.extend([0, 0]);
+    }
+
+    unwind_info
+}
+
+/// Adds an `.eh_frame` section with a CFI entry for each proc, so that unwinders and debuggers
+/// can walk through Roc frames, for example when the host prints a backtrace in `roc_panic`.
+///
+/// Mach-O has no pc-relative relocation for data, so there the start of each proc is encoded
+/// as the proc minus a symbol at the start of `__eh_frame`, with a subtractor relocation pair.
+fn build_eh_frame<'a>(output: &mut Object, frames: Vec<'a, (SymbolId, u64, ProcFrame<'a>)>) {
+    if frames.is_empty() {
+        return;
+    }
+
+    let regs = match output.architecture() {
+        Architecture::X86_64 => &X86_64_DWARF_REGS,
+        Architecture::Aarch64 => &AARCH64_DWARF_REGS,
+        arch => internal_error!("unwind tables are not supported for {:?}", arch),
+    };
+
+    let eh_frame_section = match output.format() {
+        BinaryFormat::MachO => {
+            let section = output.add_section(
+                output.segment_name(StandardSegment::Text).to_vec(),
+                b"__eh_frame".to_vec(),
+                SectionKind::ReadOnlyData,
+            );
+            // The same flags that ld64 expects from clang's `__eh_frame`.
+            output.section_mut(section).flags = SectionFlags::MachO {
+                flags: macho::S_COALESCED
+                    | macho::S_ATTR_NO_TOC
+                    | macho::S_ATTR_STRIP_STATIC_SYMS
+                    | macho::S_ATTR_LIVE_SUPPORT,
+            };
+            section
+        }
+        _ => output.add_section(vec![], b".eh_frame".to_vec(), SectionKind::ReadOnlyData),
+    };
+    let macho_eh_frame_start = (output.format() == BinaryFormat::MachO).then(|| {
+        output.add_symbol(Symbol {
+            name: b"roc_eh_frame".to_vec(),
+            value: 0,
+            size: 0,
+            kind: SymbolKind::Data,
+            scope: SymbolScope::Compilation,
+            weak: false,
+            section: SymbolSection::Section(eh_frame_section),
+            flags: SymbolFlags::None,
+        })
+    });
+
+    let cie = eh_frame_cie(regs);
+    let cie_offset = output.append_section_data(eh_frame_section, &cie, 8);
+    // Entries are padded to 8 bytes, so they follow each other without gaps.
+    let mut next_offset = cie_offset + cie.len() as u64;
+
+    for (proc_id, proc_size, frame) in frames {
+        let mut fde = eh_frame_fde(regs, &frame, proc_size as u32);
+        // The CIE pointer is the distance back from the field to the CIE.
+        let cie_pointer = (next_offset + 4 - cie_offset) as u32;
+        fde[4..8].copy_from_slice(&cie_pointer.to_le_bytes());
+
+        let fde_offset = output.append_section_data(eh_frame_section, &fde, 8);
+        debug_assert_eq!(fde_offset, next_offset);
+        next_offset += fde.len() as u64;
+
+        // The start of the proc, relative to the field that holds it.
+        let field_offset = fde_offset + 8;
+        let relocs = match macho_eh_frame_start {
+            None => vec![write::Relocation {
+                offset: field_offset,
+                size: 32,
+                kind: RelocationKind::Relative,
+                encoding: RelocationEncoding::Generic,
+                symbol: proc_id,
+                addend: 0,
+            }],
+            Some(eh_frame_start) => {
+                let (subtractor, unsigned) = match output.architecture() {
+                    Architecture::X86_64 => {
+                        (macho::X86_64_RELOC_SUBTRACTOR, macho::X86_64_RELOC_UNSIGNED)
+                    }
+                    _ => (macho::ARM64_RELOC_SUBTRACTOR, macho::ARM64_RELOC_UNSIGNED),
+                };
+                // `proc - eh_frame_start - field_offset`. The subtractor must come first.
+                let macho_reloc = |value, symbol, addend| write::Relocation {
+                    offset: field_offset,
+                    size: 32,
+                    kind: RelocationKind::MachO {
+                        value,
+                        relative: false,
+                    },
+                    encoding: RelocationEncoding::Generic,
+                    symbol,
+                    addend,
+                };
+                vec![
+                    macho_reloc(subtractor, eh_frame_start, 0),
+                    macho_reloc(unsigned, proc_id, -(field_offset as i64)),
+                ]
+            }
+        };
+        for reloc in relocs {
+            if let Err(e) = output.add_relocation(eh_frame_section, reloc) {
+                internal_error!("{:?}", e);
+            }
+        }
+    }
+
+    // A zero length entry terminates the section.
+    output.append_section_data(eh_frame_section, &[0; 4], 4);
+}
+
+/// How a calling convention's registers map onto DWARF register numbers.
+struct DwarfRegs {
+    stack_pointer: u8,
+    frame_pointer: u8,
+    return_address: u8,
+    /// Whether the prologue saves the return address next to the frame pointer.
+    saves_return_address: bool,
+    general: fn(u8) -> u8,
+    float: fn(u8) -> u8,
+}
+
+const X86_64_DWARF_REGS: DwarfRegs = DwarfRegs {
+    stack_pointer: 7,
+    frame_pointer: 6,
+    return_address: 16,
+    saves_return_address: false,
+    general: |reg| match reg {
+        // RAX, RCX, RDX, RBX, RSP, RBP, RSI, RDI are numbered in a different order.
+        0..=7 => [0, 2, 1, 3, 7, 6, 4, 5][reg as usize],
+        _ => reg,
+    },
+    float: |reg| 17 + reg,
+};
+
+const AARCH64_DWARF_REGS: DwarfRegs = DwarfRegs {
+    stack_pointer: 31,
+    frame_pointer: 29,
+    return_address: 30,
+    saves_return_address: true,
+    general: |reg| reg,
+    float: |reg| 64 + reg,
+};
+
+const DW_CFA_ADVANCE_LOC: u8 = 0x40;
+const DW_CFA_OFFSET: u8 = 0x80;
+const DW_CFA_RESTORE: u8 = 0xc0;
+const DW_CFA_ADVANCE_LOC1: u8 = 0x02;
+const DW_CFA_ADVANCE_LOC2: u8 = 0x03;
+const DW_CFA_ADVANCE_LOC4: u8 = 0x04;
+const DW_CFA_OFFSET_EXTENDED: u8 = 0x05;
+const DW_CFA_DEF_CFA: u8 = 0x0c;
+const DW_CFA_DEF_CFA_REGISTER: u8 = 0x0d;
+const DW_CFA_DEF_CFA_OFFSET: u8 = 0x0e;
+const DW_EH_PE_PCREL_SDATA4: u8 = 0x1b;
+/// Offsets of saved registers are stored divided by this.
+const DATA_ALIGNMENT: i32 = -8;
+
+/// Encodes the CIE that all procs share: on entry the canonical frame address is just above
+/// the return address, which sits on top of the stack or in the link register.
+fn eh_frame_cie(regs: &DwarfRegs) -> std::vec::Vec<u8> {
+    let mut cie = vec![];
+    // Length, filled in by `finish_eh_frame_entry`, and the CIE id.
+    cie.extend(0u32.to_le_bytes());
+    cie.extend(0u32.to_le_bytes());
+    // Version and augmentation: the FDEs have a pointer encoding.
+    cie.push(1);
+    cie.extend(b"zR\0");
+    push_uleb128(&mut cie, 1);
+    push_sleb128(&mut cie, DATA_ALIGNMENT as i64);
+    push_uleb128(&mut cie, regs.return_address as u64);
+    push_uleb128(&mut cie, 1);
+    cie.push(DW_EH_PE_PCREL_SDATA4);
+
+    let entry_stack_offset = if regs.saves_return_address { 0 } else { 8 };
+    cie.push(DW_CFA_DEF_CFA);
+    push_uleb128(&mut cie, regs.stack_pointer as u64);
+    push_uleb128(&mut cie, entry_stack_offset);
+    if !regs.saves_return_address {
+        push_cfa_offset(&mut cie, regs.return_address, -8);
+    }
+
+    finish_eh_frame_entry(cie)
+}
+
+/// Encodes the FDE of one proc. The CIE pointer and the start of the proc are left as zeros
+/// for `build_eh_frame` to fill in.
+fn eh_frame_fde(regs: &DwarfRegs, frame: &ProcFrame, proc_size: u32) -> std::vec::Vec<u8> {
+    let mut fde = vec![];
+    // Length, CIE pointer, start of the proc, size of the proc and no augmentation data.
+    fde.extend(0u32.to_le_bytes());
+    fde.extend(0u32.to_le_bytes());
+    fde.extend(0u32.to_le_bytes());
+    fde.extend(proc_size.to_le_bytes());
+    push_uleb128(&mut fde, 0);
+
+    let mut location = 0;
+    let mut advance_to = |fde: &mut std::vec::Vec<u8>, offset: u32| {
+        let delta = offset - location;
+        location = offset;
+        match delta {
+            0 => {}
+            1..=0x3f => fde.push(DW_CFA_ADVANCE_LOC | delta as u8),
+            0x40..=0xff => fde.extend([DW_CFA_ADVANCE_LOC1, delta as u8]),
+            0x100..=0xffff => {
+                fde.push(DW_CFA_ADVANCE_LOC2);
+                fde.extend((delta as u16).to_le_bytes());
+            }
+            _ => {
+                fde.push(DW_CFA_ADVANCE_LOC4);
+                fde.extend(delta.to_le_bytes());
+            }
+        }
+    };
+
+    // The frame pointer (and maybe the return address) are pushed right below the CFA.
+    advance_to(&mut fde, frame.frame_pointer_saved);
+    fde.push(DW_CFA_DEF_CFA_OFFSET);
+    push_uleb128(&mut fde, 16);
+    push_cfa_offset(&mut fde, regs.frame_pointer, -16);
+    if regs.saves_return_address {
+        push_cfa_offset(&mut fde, regs.return_address, -8);
+    }
+
+    // From here on the CFA is found relative to the frame pointer, so moving the stack pointer
+    // needs no further rules.
+    advance_to(&mut fde, frame.frame_pointer_set);
+    fde.push(DW_CFA_DEF_CFA_REGISTER);
+    push_uleb128(&mut fde, regs.frame_pointer as u64);
+
+    for saved_reg in frame.saved_regs.iter() {
+        advance_to(&mut fde, saved_reg.code_offset);
+        let reg = if saved_reg.is_float {
+            (regs.float)(saved_reg.reg)
+        } else {
+            (regs.general)(saved_reg.reg)
+        };
+        push_cfa_offset(&mut fde, reg, saved_reg.base_offset - 16);
+    }
+
+    // Only the return is left once the caller's frame pointer is back.
+    advance_to(&mut fde, frame.frame_pointer_restored);
+    let return_stack_offset = if regs.saves_return_address { 0 } else { 8 };
+    fde.push(DW_CFA_DEF_CFA);
+    push_uleb128(&mut fde, regs.stack_pointer as u64);
+    push_uleb128(&mut fde, return_stack_offset);
+    fde.push(DW_CFA_RESTORE | regs.frame_pointer);
+    if regs.saves_return_address {
+        fde.push(DW_CFA_RESTORE | regs.return_address);
+    }
+
+    finish_eh_frame_entry(fde)
+}
+
+/// Records that `reg` is saved at `offset` bytes from the CFA.
+fn push_cfa_offset(buf: &mut std::vec::Vec<u8>, reg: u8, offset: i32) {
+    debug_assert_eq!(offset % DATA_ALIGNMENT, 0);
+    let factored_offset = (offset / DATA_ALIGNMENT) as u64;
+    if reg < 0x40 {
+        buf.push(DW_CFA_OFFSET | reg);
+    } else {
+        buf.push(DW_CFA_OFFSET_EXTENDED);
+        push_uleb128(buf, reg as u64);
+    }
+    push_uleb128(buf, factored_offset);
+}
+
+/// Pads a CIE or FDE to a multiple of 8 bytes and fills in its length.
+fn finish_eh_frame_entry(mut entry: std::vec::Vec<u8>) -> std::vec::Vec<u8> {
+    // Padding with DW_CFA_nop.
+    while entry.len() % 8 != 0 {
+        entry.push(0);
+    }
+    let length = (entry.len() - 4) as u32;
+    entry[..4].copy_from_slice(&length.to_le_bytes());
+    entry
+}
+
+fn push_uleb128(buf: &mut std::vec::Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+fn push_sleb128(buf: &mut std::vec::Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            buf.push(byte);
+            return;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn build_proc<'a, B: Backend<'a>>(
     output: &mut Object,
     backend: &mut B,
     relocations: &mut Vec<'a, (SectionId, object::write::Relocation)>,
+    frames: &mut Vec<'a, (SymbolId, u64, ProcFrame<'a>)>,
     layout_ids: &mut LayoutIds<'a>,
     data_section: SectionId,
     fn_name: String,
@@ -583,8 +1096,9 @@ fn build_proc<'a, B: Backend<'a>>(
     proc: Proc<'a>,
 ) {
     let mut local_data_index = 0;
-    let (proc_data, relocs, rc_proc_names) = backend.build_proc(proc, layout_ids);
+    let (proc_data, relocs, rc_proc_names, frame) = backend.build_proc(proc, layout_ids);
     let proc_offset = output.add_symbol_data(proc_id, section_id, &proc_data, 16);
+    frames.push((proc_id, proc_data.len() as u64, frame));
     for reloc in relocs.iter() {
         let elfreloc = match reloc {
             Relocation::LocalData { offset, data } => {
@@ -645,11 +1159,7 @@ fn build_proc<'a, B: Backend<'a>>(
                 if output.symbol_id(name.as_bytes()).is_none() {
                     for (sym, rc_name) in rc_proc_names.iter() {
                         if name == rc_name {
-                            let section_id = output.add_section(
-                                output.segment_name(StandardSegment::Text).to_vec(),
-                                format!(".text.{:x}", sym.as_u64()).as_bytes().to_vec(),
-                                SectionKind::Text,
-                            );
+                            let section_id = add_proc_section(output, *sym);
 
                             let rc_symbol = Symbol {
                                 name: name.as_bytes().to_vec(),
@@ -667,14 +1177,7 @@ fn build_proc<'a, B: Backend<'a>>(
                 }
 
                 if let Some(sym_id) = output.symbol_id(name.as_bytes()) {
-                    write::Relocation {
-                        offset: offset + proc_offset,
-                        size: 32,
-                        kind: RelocationKind::PltRelative,
-                        encoding: RelocationEncoding::X86Branch,
-                        symbol: sym_id,
-                        addend: -4,
-                    }
+                    linked_function_relocation(output, &proc_data, *offset, proc_offset, sym_id)
                 } else {
                     internal_error!("failed to find fn symbol for {:?}", name);
                 }
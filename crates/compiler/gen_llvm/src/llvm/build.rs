@@ -58,6 +58,8 @@ use super::intrinsics::{
     LLVM_STACK_SAVE,
 };
 use super::lowlevel::run_higher_order_low_level;
+use super::profile::{self, ProfileSites};
+use super::sanitize::Sanitizers;
 
 pub(crate) trait BuilderExt<'ctx> {
     fn new_build_struct_gep(
@@ -165,6 +167,10 @@ pub struct Scope<'a, 'ctx> {
     symbols: ImMap<Symbol, (InLayout<'a>, BasicValueEnum<'ctx>)>,
     pub top_level_thunks: ImMap<Symbol, (ProcLayout<'a>, FunctionValue<'ctx>)>,
     join_points: ImMap<JoinPointId, (BasicBlock<'ctx>, std::vec::Vec<PhiValue<'ctx>>)>,
+    /// Allocations of the current proc that live in its stack frame
+    stack_allocations: &'a [Symbol],
+    /// The proc being built, to name allocation profiling sites
+    pub(crate) proc_name: Option<Symbol>,
 }
 
 impl<'a, 'ctx> Scope<'a, 'ctx> {
@@ -253,6 +259,10 @@ pub struct Env<'a, 'ctx, 'env> {
     pub target_info: TargetInfo,
     pub mode: LlvmBackendMode,
     pub exposed_to_host: MutSet<Symbol>,
+    /// Instrument allocations and refcount changes for `roc build --profile-alloc`
+    pub profile_sites: Option<&'a ProfileSites>,
+    /// The checks to build into the program, for `roc build --sanitize`
+    pub sanitizers: Sanitizers,
 }
 
 impl<'a, 'ctx, 'env> Env<'a, 'ctx, 'env> {
@@ -1196,6 +1206,10 @@ pub fn build_exp_expr<'a, 'ctx>(
                 UpdateMode::Immutable => refcount_ptr.is_1(env),
             };
 
+            if env.profile_sites.is_some() {
+                profile::record_update(env, is_unique);
+            }
+
             env.builder
                 .build_conditional_branch(is_unique, then_block, else_block);
 
@@ -1257,6 +1271,10 @@ pub fn build_exp_expr<'a, 'ctx>(
                 UpdateMode::Immutable => refcount_ptr.is_1(env),
             };
 
+            if env.profile_sites.is_some() {
+                profile::record_update(env, is_unique);
+            }
+
             let parent_block = env.builder.get_insert_block().unwrap();
 
             env.builder
@@ -1474,6 +1492,61 @@ pub fn build_exp_expr<'a, 'ctx>(
             }
         }
 
+        UnionFieldPtrAtIndex {
+            tag_id,
+            structure,
+            index,
+            union_layout,
+        } => {
+            let argument = load_symbol(scope, structure).into_pointer_value();
+
+            let (field_layouts, data_ptr) = match union_layout {
+                UnionLayout::NonRecursive(_) => {
+                    unreachable!("the fields of a non-recursive tag are not on the heap")
+                }
+                UnionLayout::Recursive(tag_layouts) => (
+                    tag_layouts[*tag_id as usize],
+                    tag_pointer_clear_tag_id(env, argument),
+                ),
+                UnionLayout::NullableWrapped {
+                    nullable_id,
+                    other_tags,
+                } => {
+                    let tag_index = if *tag_id < *nullable_id {
+                        *tag_id
+                    } else {
+                        tag_id - 1
+                    };
+
+                    (
+                        other_tags[tag_index as usize],
+                        tag_pointer_clear_tag_id(env, argument),
+                    )
+                }
+                UnionLayout::NonNullableUnwrapped(field_layouts) => (*field_layouts, argument),
+                UnionLayout::NullableUnwrapped { other_fields, .. } => (*other_fields, argument),
+            };
+
+            let struct_layout = layout_interner.insert(Layout::struct_no_name_order(field_layouts));
+            let struct_type =
+                basic_type_from_layout(env, layout_interner, struct_layout).into_struct_type();
+
+            let data_ptr = env.builder.build_pointer_cast(
+                data_ptr,
+                struct_type.ptr_type(AddressSpace::default()),
+                "cast_field_ptr_at_index",
+            );
+
+            let field_ptr = env
+                .builder
+                .new_build_struct_gep(struct_type, data_ptr, *index as u32, "field_ptr_at_index")
+                .unwrap();
+
+            env.builder
+                .build_ptr_to_int(field_ptr, env.ptr_int(), "field_ptr_to_int")
+                .into()
+        }
+
         GetTagId {
             structure,
             union_layout,
@@ -1775,10 +1848,12 @@ fn build_tag<'a, 'ctx>(
                 build_tag_fields(env, layout_interner, scope, fields, arguments);
 
             // Create the struct_type
-            let data_ptr = reserve_with_refcount_union_as_block_of_memory(
+            let data_ptr = allocate_tag(
                 env,
                 layout_interner,
-                *union_layout,
+                parent,
+                reuse_allocation,
+                union_layout,
                 &[fields],
             );
 
@@ -2139,19 +2214,28 @@ pub fn reserve_with_refcount<'a, 'ctx>(
     reserve_with_refcount_help(env, basic_type, stack_size, alignment_bytes)
 }
 
-fn reserve_with_refcount_union_as_block_of_memory<'a, 'ctx>(
+fn union_as_block_of_memory<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     union_layout: UnionLayout<'a>,
     fields: &[&[InLayout<'a>]],
-) -> PointerValue<'ctx> {
+) -> RocUnion<'ctx> {
     let ptr_bytes = env.target_info;
 
-    let roc_union = if union_layout.stores_tag_id_as_data(ptr_bytes) {
+    if union_layout.stores_tag_id_as_data(ptr_bytes) {
         RocUnion::tagged_from_slices(layout_interner, env.context, fields, env.target_info)
     } else {
         RocUnion::untagged_from_slices(layout_interner, env.context, fields, env.target_info)
-    };
+    }
+}
+
+fn reserve_with_refcount_union_as_block_of_memory<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    union_layout: UnionLayout<'a>,
+    fields: &[&[InLayout<'a>]],
+) -> PointerValue<'ctx> {
+    let roc_union = union_as_block_of_memory(env, layout_interner, union_layout, fields);
 
     reserve_with_refcount_help(
         env,
@@ -2174,6 +2258,148 @@ fn reserve_with_refcount_help<'a, 'ctx, 'env>(
     allocate_with_refcount_help(env, basic_type, alignment_bytes, value_bytes_intvalue)
 }
 
+/// Like [reserve_with_refcount_help], but in the stack frame of `parent`. The refcount marks the
+/// memory as read-only, so that refcount operations on it are no-ops and it is never freed.
+fn reserve_with_refcount_on_stack<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    parent: FunctionValue<'ctx>,
+    basic_type: impl BasicType<'ctx>,
+    stack_size: u32,
+    alignment_bytes: u32,
+) -> PointerValue<'ctx> {
+    // the same layout as `allocateWithRefcount` in the builtins
+    let extra_bytes = alignment_bytes.max(env.target_info.ptr_width() as u32);
+
+    let byte_type = env.context.i8_type();
+    let allocation_type = byte_type.array_type(extra_bytes + stack_size);
+    let alloca = create_entry_block_alloca(env, parent, allocation_type.into(), "stack_allocation");
+    alloca
+        .as_instruction_value()
+        .unwrap()
+        .set_alignment(extra_bytes)
+        .unwrap();
+
+    let offset = env.ptr_int().const_int(extra_bytes as u64, false);
+    let data_ptr = unsafe {
+        env.builder.new_build_in_bounds_gep(
+            byte_type,
+            env.builder.build_pointer_cast(
+                alloca,
+                byte_type.ptr_type(AddressSpace::default()),
+                "to_bytes",
+            ),
+            &[offset],
+            "data_ptr",
+        )
+    };
+
+    PointerToRefcount::from_ptr_to_data(env, data_ptr)
+        .set_refcount(env, env.ptr_int().const_zero());
+
+    env.builder.build_pointer_cast(
+        data_ptr,
+        basic_type.ptr_type(AddressSpace::default()),
+        "stack_cast_to_desired",
+    )
+}
+
+/// Build a box, list literal or tag union node that [roc_mono::stack_allocation] found does not
+/// outlive the current proc, in its stack frame.
+fn build_stack_allocation<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    scope: &Scope<'a, 'ctx>,
+    parent: FunctionValue<'ctx>,
+    layout: InLayout<'a>,
+    expr: &roc_mono::ir::Expr<'a>,
+) -> BasicValueEnum<'ctx> {
+    use roc_mono::ir::Expr::*;
+
+    match expr {
+        ExprBox { symbol } => {
+            let (value, layout) = load_symbol_and_layout(scope, symbol);
+            let basic_type = basic_type_from_layout(env, layout_interner, layout);
+            let allocation = reserve_with_refcount_on_stack(
+                env,
+                parent,
+                basic_type,
+                layout_interner.stack_size(layout),
+                layout_interner.alignment_bytes(layout),
+            );
+
+            store_roc_value(env, layout_interner, layout, allocation, value);
+
+            allocation.into()
+        }
+        Array { elem_layout, elems } => {
+            let element_type = basic_type_from_layout(env, layout_interner, *elem_layout);
+            let (element_size, alignment) = layout_interner.stack_size_and_alignment(*elem_layout);
+            let ptr = reserve_with_refcount_on_stack(
+                env,
+                parent,
+                element_type,
+                element_size * elems.len() as u32,
+                alignment,
+            );
+
+            store_list_literal_elements(
+                env,
+                layout_interner,
+                parent,
+                scope,
+                *elem_layout,
+                elems,
+                ptr,
+            );
+
+            let list_length_intval = env.ptr_int().const_int(elems.len() as _, false);
+            super::build_list::store_list(env, ptr, list_length_intval).into()
+        }
+        Tag {
+            arguments,
+            tag_layout: union_layout,
+            tag_id,
+        } => {
+            let fields: &[&[InLayout<'a>]] = match union_layout {
+                UnionLayout::Recursive(tags) => *tags,
+                UnionLayout::NullableWrapped { other_tags, .. } => *other_tags,
+                UnionLayout::NonNullableUnwrapped(fields) => env.arena.alloc([*fields]),
+                UnionLayout::NullableUnwrapped { other_fields, .. } => {
+                    env.arena.alloc([*other_fields])
+                }
+                UnionLayout::NonRecursive(_) => {
+                    unreachable!("only recursive tag unions are allocated")
+                }
+            };
+
+            let roc_union = union_as_block_of_memory(env, layout_interner, *union_layout, fields);
+            let ptr = reserve_with_refcount_on_stack(
+                env,
+                parent,
+                roc_union.struct_type(),
+                roc_union.tag_width(),
+                roc_union.tag_alignment(),
+            );
+
+            // the tag is built in the stack memory as if it was reused
+            build_tag(
+                env,
+                layout_interner,
+                scope,
+                union_layout,
+                *tag_id,
+                arguments,
+                Some(ptr),
+                parent,
+            )
+        }
+        _ => unreachable!(
+            "{:?} of layout {:?} cannot be stack-allocated",
+            expr, layout
+        ),
+    }
+}
+
 pub fn allocate_with_refcount<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
@@ -2359,26 +2585,49 @@ fn list_literal<'a, 'ctx>(
     } else {
         let ptr = allocate_list(env, layout_interner, element_layout, list_length_intval);
 
-        // Copy the elements from the list literal into the array
-        for (index, element) in elems.iter().enumerate() {
-            let val = match element {
-                ListLiteralElement::Literal(literal) => {
-                    build_exp_literal(env, layout_interner, parent, element_layout, literal)
-                }
-                ListLiteralElement::Symbol(symbol) => load_symbol(scope, symbol),
-            };
-            let index_val = ctx.i64_type().const_int(index as u64, false);
-            let elem_ptr = unsafe {
-                builder.new_build_in_bounds_gep(element_type, ptr, &[index_val], "index")
-            };
-
-            store_roc_value(env, layout_interner, element_layout, elem_ptr, val);
-        }
+        store_list_literal_elements(
+            env,
+            layout_interner,
+            parent,
+            scope,
+            element_layout,
+            elems,
+            ptr,
+        );
 
         super::build_list::store_list(env, ptr, list_length_intval).into()
     }
 }
 
+/// Copy the elements of a list literal into the array at `ptr`
+fn store_list_literal_elements<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    layout_interner: &mut STLayoutInterner<'a>,
+    parent: FunctionValue<'ctx>,
+    scope: &Scope<'a, 'ctx>,
+    element_layout: InLayout<'a>,
+    elems: &[ListLiteralElement],
+    ptr: PointerValue<'ctx>,
+) {
+    let element_type = basic_type_from_layout(env, layout_interner, element_layout);
+
+    for (index, element) in elems.iter().enumerate() {
+        let val = match element {
+            ListLiteralElement::Literal(literal) => {
+                build_exp_literal(env, layout_interner, parent, element_layout, literal)
+            }
+            ListLiteralElement::Symbol(symbol) => load_symbol(scope, symbol),
+        };
+        let index_val = env.context.i64_type().const_int(index as u64, false);
+        let elem_ptr = unsafe {
+            env.builder
+                .new_build_in_bounds_gep(element_type, ptr, &[index_val], "index")
+        };
+
+        store_roc_value(env, layout_interner, element_layout, elem_ptr, val);
+    }
+}
+
 pub fn load_roc_value<'a, 'ctx>(
     env: &Env<'a, 'ctx, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
@@ -2509,16 +2758,24 @@ pub fn build_exp_stmt<'a, 'ctx>(
                     Layout::RecursivePointer(_)
                 ));
 
-                let val = build_exp_expr(
-                    env,
-                    layout_interner,
-                    layout_ids,
-                    func_spec_solutions,
-                    scope,
-                    parent,
-                    *layout,
-                    expr,
-                );
+                let val = if scope.stack_allocations.contains(symbol) {
+                    build_stack_allocation(env, layout_interner, scope, parent, *layout, expr)
+                } else {
+                    if env.profile_sites.is_some() && profile::may_allocate(expr) {
+                        profile::set_profile_site(env, scope, *symbol);
+                    }
+
+                    build_exp_expr(
+                        env,
+                        layout_interner,
+                        layout_ids,
+                        func_spec_solutions,
+                        scope,
+                        parent,
+                        *layout,
+                        expr,
+                    )
+                };
 
                 // Make a new scope which includes the binding we just encountered.
                 // This should be done *after* compiling the bound expr, since any
@@ -2761,6 +3018,10 @@ pub fn build_exp_stmt<'a, 'ctx>(
         Refcounting(modify, cont) => {
             use ModifyRc::*;
 
+            if env.profile_sites.is_some() {
+                profile::set_profile_site(env, scope, modify.get_symbol());
+            }
+
             match modify {
                 Inc(symbol, inc_amount) => {
                     let (value, layout) = load_symbol_and_layout(scope, symbol);
@@ -4833,13 +5094,15 @@ fn build_procedures_help<'a>(
     env: &Env<'a, '_, '_>,
     layout_interner: &mut STLayoutInterner<'a>,
     opt_level: OptLevel,
-    procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
+    mut procedures: MutMap<(Symbol, ProcLayout<'a>), roc_mono::ir::Proc<'a>>,
     entry_point: EntryPoint<'a>,
     debug_output_file: Option<&Path>,
 ) -> &'a ModSolutions {
     let mut layout_ids = roc_mono::layout::LayoutIds::default();
     let mut scope = Scope::default();
 
+    roc_mono::stack_allocation::stack_allocate_procs(env.arena, layout_interner, &mut procedures);
+
     let it = procedures.iter().map(|x| x.1);
 
     let solutions = match roc_alias_analysis::spec_program(
@@ -5340,6 +5603,9 @@ fn build_proc<'a, 'ctx>(
         scope.insert(*arg_symbol, (*layout, arg_val));
     }
 
+    scope.stack_allocations = proc.stack_allocations;
+    scope.proc_name = Some(proc.name.name());
+
     let body = build_exp_stmt(
         env,
         layout_interner,
@@ -2,6 +2,7 @@ use crate::llvm::bitcode::call_void_bitcode_fn;
 use crate::llvm::build::{add_func, get_panic_msg_ptr, get_panic_tag_ptr, BuilderExt, C_CALL_CONV};
 use crate::llvm::build::{CCReturn, Env, FunctionSpec};
 use crate::llvm::convert::zig_str_type;
+use crate::llvm::instrument::define_hooks;
 use inkwell::module::Linkage;
 use inkwell::types::BasicType;
 use inkwell::AddressSpace;
@@ -35,6 +36,9 @@ pub fn add_default_roc_externs(env: &Env<'_, '_, '_>) {
         }
     }
 
+    // the builtins call these on every allocation and refcount change
+    define_hooks(env);
+
     if !env.mode.has_host() {
         // roc_alloc
         {
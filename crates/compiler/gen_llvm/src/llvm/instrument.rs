@@ -0,0 +1,87 @@
+//! The instrumentation hooks of the builtins (`hooks.zig`).
+//!
+//! The builtins call a hook on every allocation and refcount change. Unless the program is
+//! instrumented, the hooks do nothing and are inlined away, so other programs don't pay for them.
+use crate::debug_info_init;
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::Linkage;
+use inkwell::values::BasicValueEnum;
+use roc_builtins::bitcode;
+
+use super::bitcode::call_void_bitcode_fn;
+use super::build::Env;
+
+/// Give every hook its body: calls to the instrumentation that the program is built with.
+/// An implementation gets the first arguments of its hook, as many as it takes.
+pub fn define_hooks(env: &Env<'_, '_, '_>) {
+    let mut alloc = vec![];
+    let mut dealloc = vec![];
+    let mut incref = vec![];
+    let mut decref = vec![];
+
+    if env.sanitizers.refcount {
+        incref.push(bitcode::UTILS_SANITIZE_CHECK_INCREF);
+        decref.push(bitcode::UTILS_SANITIZE_CHECK_DECREF);
+    }
+
+    if env.profile_sites.is_some() {
+        alloc.push(bitcode::UTILS_PROFILE_RECORD_ALLOC);
+        dealloc.push(bitcode::UTILS_PROFILE_RECORD_DEALLOC);
+        incref.push(bitcode::UTILS_PROFILE_RECORD_INCREMENT);
+        decref.push(bitcode::UTILS_PROFILE_RECORD_DECREMENT);
+    }
+
+    let hooks = [
+        (bitcode::UTILS_HOOK_ALLOC, alloc),
+        (bitcode::UTILS_HOOK_DEALLOC, dealloc),
+        (bitcode::UTILS_HOOK_INCREF, incref),
+        (bitcode::UTILS_HOOK_DECREF, decref),
+    ];
+
+    for (hook_name, implementations) in hooks {
+        let hook = match env.module.get_function(hook_name) {
+            Some(hook) => hook,
+            None => continue,
+        };
+
+        if !implementations.is_empty() {
+            for block in hook.get_basic_blocks() {
+                unsafe { block.delete() }.unwrap();
+            }
+
+            let entry = env.context.append_basic_block(hook, "entry");
+            env.builder.position_at_end(entry);
+
+            // the builtins may come with debug info, and then the calls need a location
+            if hook.get_subprogram().is_some() {
+                debug_info_init!(env, hook);
+            }
+
+            let params: Vec<BasicValueEnum> = hook.get_param_iter().collect();
+            for implementation in implementations {
+                let n_params = env
+                    .module
+                    .get_function(implementation)
+                    .unwrap()
+                    .count_params();
+
+                call_void_bitcode_fn(env, &params[..n_params as usize], implementation);
+            }
+
+            env.builder.build_return(None);
+            env.builder.unset_current_debug_location();
+        }
+
+        // The hooks are weak in the builtins, so that zig keeps their calls around.
+        // Now that they are final, they can be inlined.
+        hook.set_linkage(Linkage::Internal);
+
+        let noinline = Attribute::get_named_enum_kind_id("noinline");
+        hook.remove_enum_attribute(AttributeLoc::Function, noinline);
+
+        let kind_id = Attribute::get_named_enum_kind_id("alwaysinline");
+        debug_assert!(kind_id > 0);
+        let attr = env.context.create_enum_attribute(kind_id, 1);
+        hook.add_attribute(AttributeLoc::Function, attr);
+    }
+}
@@ -1,5 +1,5 @@
 use inkwell::{
-    types::{BasicType, IntType},
+    types::{BasicType, BasicTypeEnum, IntType},
     values::{
         BasicValue, BasicValueEnum, FloatValue, FunctionValue, InstructionOpcode, IntValue,
         PointerValue, StructValue,
@@ -25,7 +25,8 @@ use crate::llvm::{
     },
     build::{
         complex_bitcast_check_size, create_entry_block_alloca, function_value_by_func_spec,
-        load_roc_value, roc_function_call, tag_pointer_clear_tag_id, BuilderExt, RocReturn,
+        load_roc_value, roc_function_call, store_roc_value, tag_pointer_clear_tag_id, BuilderExt,
+        RocReturn,
     },
     build_list::{
         list_append_unsafe, list_concat, list_drop_at, list_get_unsafe, list_len, list_map,
@@ -1249,7 +1250,39 @@ pub(crate) fn run_low_level<'a, 'ctx>(
             unreachable!("The {:?} operation is turned into mono Expr", op)
         }
 
-        PtrCast | PtrWrite | RefCountIncRcPtr | RefCountDecRcPtr | RefCountIncDataPtr
+        PtrStore => {
+            arguments_with_layouts!((ptr, _ptr_layout), (value, value_layout));
+
+            let value_type = basic_type_from_layout(env, layout_interner, value_layout);
+            let destination = env.builder.build_int_to_ptr(
+                ptr.into_int_value(),
+                value_type.ptr_type(AddressSpace::default()),
+                "to_field_ptr",
+            );
+
+            store_roc_value(env, layout_interner, value_layout, destination, value);
+
+            env.context.struct_type(&[], false).const_zero().into()
+        }
+
+        PtrCast => {
+            arguments!(data_ptr);
+
+            let ptr = data_ptr.into_pointer_value();
+
+            match basic_type_from_layout(env, layout_interner, layout) {
+                BasicTypeEnum::IntType(int_type) => env
+                    .builder
+                    .build_ptr_to_int(ptr, int_type, "ptr_to_int")
+                    .into(),
+                other => env
+                    .builder
+                    .build_pointer_cast(ptr, other.into_pointer_type(), "ptr_cast")
+                    .into(),
+            }
+        }
+
+        PtrWrite | RefCountIncRcPtr | RefCountDecRcPtr | RefCountIncDataPtr
         | RefCountDecDataPtr => {
             unreachable!("Not used in LLVM backend: {:?}", op);
         }
@@ -6,6 +6,9 @@ pub mod compare;
 pub mod convert;
 mod expect;
 pub mod externs;
+mod instrument;
 mod intrinsics;
 mod lowlevel;
+pub mod profile;
 pub mod refcounting;
+pub mod sanitize;
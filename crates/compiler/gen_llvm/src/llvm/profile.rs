@@ -0,0 +1,170 @@
+//! Instrumentation for `roc build --profile-alloc`.
+//!
+//! Before anything that may allocate or change a refcount, the generated code tells the
+//! profiler in the builtins (`profile.zig`) which site it is executing. A site is a proc and the
+//! symbol it is working on. Mono IR has no source regions, so the site is located with the region
+//! that the symbol was defined at, or failing that, the region of the proc.
+use std::path::PathBuf;
+
+use inkwell::module::Linkage;
+use inkwell::types::BasicTypeEnum;
+use inkwell::values::{BasicValueEnum, IntValue};
+use inkwell::AddressSpace;
+use roc_builtins::bitcode;
+use roc_collections::all::MutMap;
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::ir::{CallType, Expr, SymbolRegions};
+use roc_mono::layout::UnionLayout;
+use roc_region::all::LineInfo;
+
+use super::bitcode::call_void_bitcode_fn;
+use super::build::{Env, Scope};
+
+/// The number of counters in a `Site` in `profile.zig`, after its name and next pointers
+const SITE_COUNTERS: usize = 7;
+
+/// Where in the source the symbols of a program are defined, to name profile sites after
+pub struct ProfileSites {
+    symbol_regions: SymbolRegions,
+    modules: MutMap<ModuleId, (PathBuf, LineInfo)>,
+}
+
+impl ProfileSites {
+    pub fn new(
+        symbol_regions: SymbolRegions,
+        sources: &MutMap<ModuleId, (PathBuf, Box<str>)>,
+    ) -> Self {
+        let mut modules = MutMap::default();
+
+        for symbol in symbol_regions.keys() {
+            let module_id = symbol.module_id();
+
+            if !modules.contains_key(&module_id) {
+                if let Some((path, src)) = sources.get(&module_id) {
+                    modules.insert(module_id, (path.clone(), LineInfo::new(src)));
+                }
+            }
+        }
+
+        Self {
+            symbol_regions,
+            modules,
+        }
+    }
+
+    /// `file:line:column` of the definition of `symbol`, if it is known
+    fn location(&self, symbol: Symbol) -> Option<String> {
+        let region = self.symbol_regions.get(&symbol)?;
+        let (path, line_info) = self.modules.get(&symbol.module_id())?;
+        let start = line_info.convert_region(*region).start;
+
+        Some(format!(
+            "{}:{}:{}",
+            path.display(),
+            start.line + 1,
+            start.column + 1
+        ))
+    }
+}
+
+/// Whether building `expr` may allocate, or change the refcount of an existing allocation
+pub(crate) fn may_allocate(expr: &Expr) -> bool {
+    match expr {
+        Expr::Call(call) => !matches!(call.call_type, CallType::ByName { .. }),
+        Expr::Tag { tag_layout, .. } => !matches!(tag_layout, UnionLayout::NonRecursive(_)),
+        Expr::Array { elems, .. } => !elems.is_empty(),
+        Expr::ExprBox { .. } | Expr::Reuse { .. } | Expr::Reset { .. } | Expr::ResetRef { .. } => {
+            true
+        }
+        _ => false,
+    }
+}
+
+/// Make the site of `symbol` in the current proc the one that allocations and refcount changes
+/// are attributed to
+pub(crate) fn set_profile_site<'a, 'ctx>(
+    env: &Env<'a, 'ctx, '_>,
+    scope: &Scope<'a, 'ctx>,
+    symbol: Symbol,
+) {
+    let mut site_name = match scope.proc_name {
+        Some(proc_name) => format!(
+            "{}: {}",
+            qualified_name(&env.interns, proc_name),
+            qualified_name(&env.interns, symbol)
+        ),
+        None => qualified_name(&env.interns, symbol),
+    };
+
+    if let Some(sites) = env.profile_sites {
+        let location = sites.location(symbol).or_else(|| {
+            scope
+                .proc_name
+                .and_then(|proc_name| sites.location(proc_name))
+        });
+
+        if let Some(location) = location {
+            site_name.push_str(&format!(" ({})", location));
+        }
+    }
+
+    let global_name = format!("roc_profile_site.{}", site_name);
+
+    let site = match env.module.get_global(&global_name) {
+        Some(site) => site,
+        None => {
+            let byte_ptr_type = env.context.i8_type().ptr_type(AddressSpace::default());
+            let counter_type = env.context.i64_type();
+
+            let name = env.context.const_string(site_name.as_bytes(), true);
+            let name_global = env
+                .module
+                .add_global(name.get_type(), None, "profile_site_name");
+            name_global.set_initializer(&name);
+            name_global.set_constant(true);
+            name_global.set_linkage(Linkage::Private);
+
+            let mut field_types: Vec<BasicTypeEnum> =
+                vec![byte_ptr_type.into(), byte_ptr_type.into()];
+            let mut fields: Vec<BasicValueEnum> = vec![
+                name_global
+                    .as_pointer_value()
+                    .const_cast(byte_ptr_type)
+                    .into(),
+                byte_ptr_type.const_null().into(),
+            ];
+            for _ in 0..SITE_COUNTERS {
+                field_types.push(counter_type.into());
+                fields.push(counter_type.const_zero().into());
+            }
+
+            let site_type = env.context.struct_type(&field_types, false);
+            let site = env.module.add_global(site_type, None, &global_name);
+            site.set_initializer(&site_type.const_named_struct(&fields));
+            site.set_linkage(Linkage::Private);
+
+            site
+        }
+    };
+
+    call_void_bitcode_fn(
+        env,
+        &[site.as_pointer_value().into()],
+        bitcode::UTILS_PROFILE_SET_SITE,
+    );
+}
+
+/// Record whether a `reset` found its allocation unique, so that it can be updated in place
+pub(crate) fn record_update<'ctx>(env: &Env<'_, 'ctx, '_>, is_unique: IntValue<'ctx>) {
+    call_void_bitcode_fn(
+        env,
+        &[is_unique.into()],
+        bitcode::UTILS_PROFILE_RECORD_UPDATE,
+    );
+}
+
+fn qualified_name(interns: &Interns, symbol: Symbol) -> String {
+    let module_name = interns.module_ids.get_name(symbol.module_id()).unwrap();
+
+    format!("{}.{}", module_name, symbol.as_str(interns))
+}
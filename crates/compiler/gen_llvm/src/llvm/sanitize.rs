@@ -0,0 +1,113 @@
+//! Instrumentation for `roc build --sanitize`.
+//!
+//! - `address` marks every function for LLVM's AddressSanitizer, and runs its passes after the
+//!   module has been optimized. The runtime is linked in by roc_build.
+//! - `refcount` wraps the platform's `roc_dealloc`, so that freed allocations are poisoned and
+//!   quarantined by the builtins (`sanitize.zig`) rather than freed right away.
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::module::{Linkage, Module};
+use inkwell::passes::PassBuilderOptions;
+use inkwell::support::LLVMString;
+use inkwell::targets::TargetMachine;
+use roc_builtins::bitcode;
+
+use super::bitcode::call_void_bitcode_fn;
+use super::build::{Env, C_CALL_CONV};
+
+/// The checks to build a program with
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sanitizers {
+    /// Detect out-of-bounds accesses and use-after-free with AddressSanitizer
+    pub address: bool,
+    /// Detect refcount changes of freed allocations
+    pub refcount: bool,
+}
+
+impl Sanitizers {
+    pub fn any(&self) -> bool {
+        self.address || self.refcount
+    }
+}
+
+/// Must run after all procs have been built, so that every use of `roc_dealloc` is wrapped and
+/// every function is instrumented. The refcount checks themselves are hooked up by `instrument.rs`.
+pub fn add_sanitizers(env: &Env<'_, '_, '_>) {
+    if env.sanitizers.refcount {
+        wrap_roc_dealloc(env);
+    }
+
+    if env.sanitizers.address {
+        let kind_id = Attribute::get_named_enum_kind_id("sanitize_address");
+        debug_assert!(kind_id > 0);
+        let attribute = env.context.create_enum_attribute(kind_id, 0);
+
+        for function in env.module.get_functions() {
+            // declarations are defined by the host, which we can't instrument
+            if function.count_basic_blocks() > 0 {
+                function.add_attribute(AttributeLoc::Function, attribute);
+            }
+        }
+    }
+}
+
+fn wrap_roc_dealloc(env: &Env<'_, '_, '_>) {
+    let platform_dealloc = match env.module.get_function("roc_dealloc") {
+        Some(function) => function,
+        None => return,
+    };
+
+    let wrapper = env.module.add_function(
+        "roc_dealloc_sanitized",
+        platform_dealloc.get_type(),
+        Some(Linkage::Internal),
+    );
+    wrapper.set_call_conventions(C_CALL_CONV);
+
+    // this also redirects the calls in the builtins, but not the one we are about to add
+    platform_dealloc
+        .as_global_value()
+        .as_pointer_value()
+        .replace_all_uses_with(wrapper.as_global_value().as_pointer_value());
+
+    let entry = env.context.append_basic_block(wrapper, "entry");
+    env.builder.position_at_end(entry);
+
+    let quarantine = env
+        .module
+        .get_function(bitcode::UTILS_DEALLOC_QUARANTINED)
+        .unwrap();
+    let dealloc_ptr_type = quarantine.get_nth_param(2).unwrap().get_type();
+    let platform_dealloc_ptr = env.builder.build_pointer_cast(
+        platform_dealloc.as_global_value().as_pointer_value(),
+        dealloc_ptr_type.into_pointer_type(),
+        "platform_dealloc",
+    );
+
+    call_void_bitcode_fn(
+        env,
+        &[
+            wrapper.get_nth_param(0).unwrap(),
+            wrapper.get_nth_param(1).unwrap(),
+            platform_dealloc_ptr.into(),
+        ],
+        bitcode::UTILS_DEALLOC_QUARANTINED,
+    );
+
+    env.builder.build_return(None);
+}
+
+/// Instrument the loads and stores of the functions marked by [`add_sanitizers`], and the globals
+/// of the module. Like clang, this runs after the optimization passes, so that only the accesses
+/// that survive optimization are checked.
+pub fn run_address_sanitizer_passes(
+    module: &Module<'_>,
+    target_machine: &TargetMachine,
+) -> Result<(), LLVMString> {
+    // With LLVM 13, `asan` instruments functions and `asan-module` instruments globals.
+    // The function pass needs the globals metadata to be computed up front.
+    module.run_passes(
+        "require<asan-globals-md>,function(asan),asan-module",
+        target_machine,
+        PassBuilderOptions::create(),
+    )
+}
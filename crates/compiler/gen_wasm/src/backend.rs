@@ -1,7 +1,7 @@
 use bitvec::vec::BitVec;
 use bumpalo::collections::{String, Vec};
 
-use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_builtins::bitcode::{self, FloatWidth, IntWidth};
 use roc_collections::all::MutMap;
 use roc_error_macros::internal_error;
 use roc_module::low_level::{LowLevel, LowLevelWrapperType};
@@ -31,8 +31,8 @@ use crate::layout::{CallConv, ReturnMethod, WasmLayout};
 use crate::low_level::{call_higher_order_lowlevel, LowLevelCall};
 use crate::storage::{AddressValue, Storage, StoredValue, StoredVarKind};
 use crate::{
-    copy_memory, CopyMemoryConfig, Env, DEBUG_SETTINGS, MEMORY_NAME, PTR_SIZE, PTR_TYPE,
-    TARGET_INFO,
+    copy_memory, load_multi_value, store_multi_value, CopyMemoryConfig, Env, DEBUG_SETTINGS,
+    MEMORY_NAME, PTR_SIZE, PTR_TYPE, TARGET_INFO,
 };
 
 #[derive(Clone, Copy, Debug)]
@@ -325,7 +325,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
         self.module.add_function_signature(Signature {
             param_types: bumpalo::vec![in self.env.arena],
-            ret_type: None,
+            ret_types: bumpalo::vec![in self.env.arena],
         });
 
         self.module.export.append(Export {
@@ -407,19 +407,29 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         }
     }
 
+    /// Procedures exposed to the host must use the C calling convention.
+    /// Calls from Roc to Roc can return small structs as multiple values.
+    fn proc_call_conv(&self, proc_name: Symbol) -> CallConv {
+        if self.env.exposed_to_host.contains(&proc_name) {
+            CallConv::C
+        } else {
+            CallConv::Roc
+        }
+    }
+
     fn start_proc(&mut self, proc: &Proc<'a>) {
         use ReturnMethod::*;
         let ret_layout = WasmLayout::new(self.layout_interner, proc.ret_layout);
-
-        let ret_type = match ret_layout.return_method(CallConv::C) {
-            Primitive(ty, _) => Some(ty),
-            NoReturnValue => None,
-            WriteToPointerArg => {
-                self.storage.arg_types.push(PTR_TYPE);
-                None
-            }
+        let call_conv = self.proc_call_conv(proc.name.name());
+
+        let mut ret_types = Vec::with_capacity_in(2, self.env.arena);
+        match ret_layout.return_method(call_conv) {
+            Primitive(ty, _) => ret_types.push(ty),
+            MultiValue(types) => ret_types.extend_from_slice(types),
+            NoReturnValue => {}
+            WriteToPointerArg => self.storage.arg_types.push(PTR_TYPE),
             ZigPackedStruct => {
-                internal_error!("C calling convention does not return Zig packed structs")
+                internal_error!("Roc procedures do not return Zig packed structs")
             }
         };
 
@@ -434,14 +444,14 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             self.env.arena,
         );
 
-        if let Some(ty) = ret_type {
-            let ret_var = self.storage.create_anonymous_local(ty);
-            self.storage.return_var = Some(ret_var);
+        for ty in ret_types.iter() {
+            let ret_var = self.storage.create_anonymous_local(*ty);
+            self.storage.return_vars.push(ret_var);
         }
 
         self.module.add_function_signature(Signature {
             param_types: self.storage.arg_types.clone(),
-            ret_type,
+            ret_types,
         });
     }
 
@@ -449,8 +459,8 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         // end the block from start_proc, to ensure all paths pop stack memory (if any)
         self.end_block();
 
-        if let Some(ret_var) = self.storage.return_var {
-            self.code_builder.get_local(ret_var);
+        for ret_var in self.storage.return_vars.iter() {
+            self.code_builder.get_local(*ret_var);
         }
 
         // Write local declarations and stack frame push/pop code
@@ -513,8 +523,11 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             x => internal_error!("Higher-order wrapper: invalid return layout {:?}", x),
         };
 
+        let inner_call_conv = self.proc_call_conv(self.proc_lookup[inner_lookup_idx].name);
+        let inner_return_method = inner_ret_layout.return_method(inner_call_conv);
+
         let mut n_inner_wasm_args = 0;
-        let ret_type_and_size = match inner_ret_layout.return_method(CallConv::C) {
+        let ret_type_and_size = match inner_return_method {
             ReturnMethod::NoReturnValue => None,
             ReturnMethod::Primitive(ty, size) => {
                 // If the inner function returns a primitive, load the address to store it at
@@ -528,6 +541,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 n_inner_wasm_args += 1;
                 None
             }
+            ReturnMethod::MultiValue(_) => None, // stored after the call, see below
             x => internal_error!("A Roc function should never use ReturnMethod {:?}", x),
         };
 
@@ -577,9 +591,40 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
         // Call the wrapped inner function
         let inner_wasm_fn_index = self.fn_index_offset + inner_lookup_idx as u32;
-        let has_return_val = ret_type_and_size.is_some();
-        self.code_builder
-            .call(inner_wasm_fn_index, n_inner_wasm_args, has_return_val);
+        let mut local_types: &[ValueType] = &[];
+        if let ReturnMethod::MultiValue(ret_types) = inner_return_method {
+            self.code_builder.call_multi_value(
+                inner_wasm_fn_index,
+                n_inner_wasm_args,
+                ret_types.len(),
+            );
+
+            // Store the returned values to the heap, using temporary locals after the wrapper args
+            let alignment_bytes = match inner_ret_layout {
+                WasmLayout::StackMemory {
+                    alignment_bytes, ..
+                } => alignment_bytes,
+                WasmLayout::Primitive(..) => internal_error!("Primitives are not multi-value"),
+            };
+            let first_tmp_local = wrapper_arg_layouts.len() as u32;
+            let tmp_locals = Vec::from_iter_in(
+                (0..ret_types.len() as u32).map(|i| LocalId(first_tmp_local + i)),
+                self.env.arena,
+            );
+            store_multi_value(
+                &mut self.code_builder,
+                ret_types,
+                &tmp_locals,
+                heap_return_ptr_id,
+                0,
+                alignment_bytes,
+            );
+            local_types = ret_types;
+        } else {
+            let has_return_val = ret_type_and_size.is_some();
+            self.code_builder
+                .call(inner_wasm_fn_index, n_inner_wasm_args, has_return_val);
+        }
 
         // If the inner function returns a primitive, store it to the address we loaded at the very beginning
         if let Some((ty, size)) = ret_type_and_size {
@@ -596,12 +641,13 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             }
         }
 
-        // Write empty function header (local variables array with zero length)
-        self.code_builder.build_fn_header_and_footer(&[], 0, None);
+        // Write function header (no local variables, unless we needed them for a multi-value return)
+        self.code_builder
+            .build_fn_header_and_footer(local_types, 0, None);
 
         self.module.add_function_signature(Signature {
             param_types: bumpalo::vec![in self.env.arena; I32; wrapper_arg_layouts.len()],
-            ret_type: None,
+            ret_types: bumpalo::vec![in self.env.arena],
         });
 
         self.append_proc_debug_name(wrapper_name);
@@ -654,7 +700,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
         self.module.add_function_signature(Signature {
             param_types: bumpalo::vec![in self.env.arena; I32; 3],
-            ret_type: Some(ValueType::I32),
+            ret_types: bumpalo::vec![in self.env.arena; I32],
         });
 
         self.append_proc_debug_name(wrapper_name);
@@ -806,17 +852,39 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             } => {
                 let (from_ptr, from_offset) =
                     location.local_and_offset(self.storage.stack_frame_pointer);
-                copy_memory(
-                    &mut self.code_builder,
-                    CopyMemoryConfig {
+
+                if self.storage.return_vars.is_empty() {
+                    copy_memory(
+                        &mut self.code_builder,
+                        CopyMemoryConfig {
+                            from_ptr,
+                            from_offset,
+                            to_ptr: LocalId(0),
+                            to_offset: 0,
+                            size: *size,
+                            alignment_bytes: *alignment_bytes,
+                        },
+                    );
+                } else {
+                    // Multi-value return: load the chunks of the struct into the return variables
+                    let ret_types = Vec::from_iter_in(
+                        self.storage
+                            .return_vars
+                            .iter()
+                            .map(|var| self.storage.local_type(*var)),
+                        self.env.arena,
+                    );
+                    load_multi_value(
+                        &mut self.code_builder,
+                        &ret_types,
                         from_ptr,
                         from_offset,
-                        to_ptr: LocalId(0),
-                        to_offset: 0,
-                        size: *size,
-                        alignment_bytes: *alignment_bytes,
-                    },
-                );
+                        *alignment_bytes,
+                    );
+                    for ret_var in self.storage.return_vars.iter().rev() {
+                        self.code_builder.set_local(*ret_var);
+                    }
+                }
             }
 
             _ => {
@@ -824,8 +892,8 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
                 // If we have a return value, store it to the return variable
                 // This avoids complications with block result types when returning from nested blocks
-                if let Some(ret_var) = self.storage.return_var {
-                    self.code_builder.set_local(ret_var);
+                if let Some(ret_var) = self.storage.return_vars.first() {
+                    self.code_builder.set_local(*ret_var);
                 }
             }
         }
@@ -888,6 +956,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                         self.code_builder.f64_const(f64::from_bits(*value));
                         self.code_builder.f64_eq();
                     }
+                    ValueType::V128 => internal_error!("Cannot switch on a v128 value"),
                 }
             }
 
@@ -1081,6 +1150,13 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 index,
             } => self.expr_union_at_index(*structure, *tag_id, union_layout, *index, sym),
 
+            Expr::UnionFieldPtrAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => self.expr_union_field_ptr_at_index(*structure, *tag_id, union_layout, *index),
+
             Expr::ExprBox { symbol: arg_sym } => self.expr_box(sym, *arg_sym, layout, storage),
 
             Expr::ExprUnbox { symbol: arg_sym } => self.expr_unbox(sym, *arg_sym),
@@ -1152,18 +1228,15 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                         let upper_bits = (i128::from_ne_bytes(*x) >> 64) as i64;
                         write128(lower_bits, upper_bits);
                     }
-                    Literal::Float(_) => {
-                        // Also not implemented in LLVM backend (nor in Rust!)
-                        todo!("f128 type");
-                    }
                     Literal::Str(string) => {
                         let (local_id, offset) =
                             location.local_and_offset(self.storage.stack_frame_pointer);
 
                         self.expr_string_literal(string, local_id, offset);
                     }
+                    // There is no 128-bit float type, and Dec literals are Literal::Decimal.
                     // Bools and bytes should not be stored in the stack frame
-                    Literal::Bool(_) | Literal::Byte(_) => invalid_error(),
+                    Literal::Float(_) | Literal::Bool(_) | Literal::Byte(_) => invalid_error(),
                 }
             }
 
@@ -1321,6 +1394,12 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
             return self.expr_call_low_level(lowlevel, arguments, ret_sym, ret_layout, ret_storage);
         }
 
+        // Repeating a byte is a memset, which Wasm can do in one instruction
+        if func_sym == Symbol::LIST_REPEAT && self.is_byte_list(ret_layout) {
+            return self.expr_list_repeat_bytes(arguments, ret_sym, ret_storage);
+        }
+
+        let call_conv = self.proc_call_conv(func_sym);
         let (num_wasm_args, has_return_val, ret_zig_packed_struct) =
             self.storage.load_symbols_for_call(
                 self.env.arena,
@@ -1328,7 +1407,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
                 arguments,
                 ret_sym,
                 &wasm_layout,
-                CallConv::C,
+                call_conv,
             );
         debug_assert!(!ret_zig_packed_struct);
 
@@ -1347,8 +1426,15 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
 
         let wasm_fn_index = self.fn_index_offset + roc_proc_index as u32;
 
-        self.code_builder
-            .call(wasm_fn_index, num_wasm_args, has_return_val);
+        if let ReturnMethod::MultiValue(ret_types) = wasm_layout.return_method(call_conv) {
+            self.code_builder
+                .call_multi_value(wasm_fn_index, num_wasm_args, ret_types.len());
+            self.storage
+                .store_multi_value_return(&mut self.code_builder, ret_sym, ret_types);
+        } else {
+            self.code_builder
+                .call(wasm_fn_index, num_wasm_args, has_return_val);
+        }
     }
 
     fn expr_call_low_level(
@@ -1604,19 +1690,66 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         if let StoredValue::StackMemory { location, .. } = storage {
             let (local_id, offset) = location.local_and_offset(self.storage.stack_frame_pointer);
 
-            // Store 12 bytes of zeros { elements: null, length: 0, capacity: 0 }
-            debug_assert_eq!(Builtin::LIST_WORDS, 3);
-            self.code_builder.get_local(local_id);
-            self.code_builder.i64_const(0);
-            self.code_builder.i64_store(Align::Bytes4, offset);
+            // Fill 12 bytes with zeros { elements: null, length: 0, capacity: 0 }
             self.code_builder.get_local(local_id);
+            if offset != 0 {
+                self.code_builder.i32_const(offset as i32);
+                self.code_builder.i32_add();
+            }
             self.code_builder.i32_const(0);
-            self.code_builder.i32_store(Align::Bytes4, offset + 8);
+            self.code_builder
+                .i32_const((Builtin::LIST_WORDS * PTR_SIZE) as i32);
+            self.code_builder.memory_fill();
         } else {
             internal_error!("Unexpected storage for {:?}", sym)
         }
     }
 
+    /// Is this a list of single bytes, whose contents can be handled as raw memory?
+    pub fn is_byte_list(&self, layout: InLayout<'a>) -> bool {
+        match self.layout_interner.runtime_representation(layout) {
+            Layout::Builtin(Builtin::List(elem)) => matches!(
+                self.layout_interner.runtime_representation(elem),
+                Layout::Builtin(Builtin::Int(IntWidth::U8 | IntWidth::I8) | Builtin::Bool)
+            ),
+            _ => false,
+        }
+    }
+
+    /// `List.repeat` for a list of bytes. Allocate the list, then `memory.fill` it.
+    fn expr_list_repeat_bytes(
+        &mut self,
+        arguments: &'a [Symbol],
+        ret_sym: Symbol,
+        ret_storage: &StoredValue,
+    ) {
+        let (value, count) = (arguments[0], arguments[1]);
+        let (local_id, offset) = match ret_storage {
+            StoredValue::StackMemory { location, .. } => {
+                location.local_and_offset(self.storage.stack_frame_pointer)
+            }
+            _ => internal_error!("Unexpected storage for {:?}", ret_sym),
+        };
+
+        // Same Zig arguments as `List.withCapacity`: return pointer, capacity, alignment, element width
+        self.storage
+            .load_symbols(&mut self.code_builder, &[ret_sym, count]);
+        self.code_builder.i32_const(1);
+        self.code_builder.i32_const(1);
+        self.call_host_fn_after_loading_args(bitcode::LIST_WITH_CAPACITY, 4, false);
+
+        self.code_builder.get_local(local_id);
+        self.code_builder.i32_load(Align::Bytes4, offset);
+        self.storage
+            .load_symbols(&mut self.code_builder, &[value, count]);
+        self.code_builder.memory_fill();
+
+        self.code_builder.get_local(local_id);
+        self.storage.load_symbols(&mut self.code_builder, &[count]);
+        self.code_builder
+            .i32_store(Align::Bytes4, offset + 4 * Builtin::WRAPPER_LEN);
+    }
+
     /*******************************************************************
      * Tag Unions
      *******************************************************************/
@@ -1812,34 +1945,7 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         index: u64,
         symbol: Symbol,
     ) {
-        use UnionLayout::*;
-
-        debug_assert!(!union_layout.tag_is_null(tag_id));
-
-        let tag_index = tag_id as usize;
-        let field_layouts = match union_layout {
-            NonRecursive(tags) => tags[tag_index],
-            Recursive(tags) => tags[tag_index],
-            NonNullableUnwrapped(layouts) => *layouts,
-            NullableWrapped {
-                other_tags,
-                nullable_id,
-            } => {
-                let index = if tag_index > *nullable_id as usize {
-                    tag_index - 1
-                } else {
-                    tag_index
-                };
-                other_tags[index]
-            }
-            NullableUnwrapped { other_fields, .. } => *other_fields,
-        };
-
-        let field_offset: u32 = field_layouts
-            .iter()
-            .take(index as usize)
-            .map(|field_layout| self.layout_interner.stack_size(*field_layout))
-            .sum();
+        let field_offset = self.union_field_offset(tag_id, union_layout, index);
 
         // Get pointer and offset to the tag's data
         let structure_storage = self.storage.get(&structure).to_owned();
@@ -1878,6 +1984,65 @@ impl<'a, 'r> WasmBackend<'a, 'r> {
         );
     }
 
+    /// Push the address of a field of a heap-allocated tag
+    fn expr_union_field_ptr_at_index(
+        &mut self,
+        structure: Symbol,
+        tag_id: TagIdIntType,
+        union_layout: &UnionLayout<'a>,
+        index: u64,
+    ) {
+        let field_offset = self.union_field_offset(tag_id, union_layout, index);
+
+        self.storage
+            .load_symbols(&mut self.code_builder, &[structure]);
+
+        if union_layout.stores_tag_id_in_pointer(TARGET_INFO) {
+            self.code_builder.i32_const(-4); // 11111111...1100
+            self.code_builder.i32_and();
+        }
+
+        self.code_builder.i32_const(field_offset as i32);
+        self.code_builder.i32_add();
+    }
+
+    /// The offset of a field from the start of the data of a tag
+    fn union_field_offset(
+        &mut self,
+        tag_id: TagIdIntType,
+        union_layout: &UnionLayout<'a>,
+        index: u64,
+    ) -> u32 {
+        use UnionLayout::*;
+
+        debug_assert!(!union_layout.tag_is_null(tag_id));
+
+        let tag_index = tag_id as usize;
+        let field_layouts = match union_layout {
+            NonRecursive(tags) => tags[tag_index],
+            Recursive(tags) => tags[tag_index],
+            NonNullableUnwrapped(layouts) => *layouts,
+            NullableWrapped {
+                other_tags,
+                nullable_id,
+            } => {
+                let index = if tag_index > *nullable_id as usize {
+                    tag_index - 1
+                } else {
+                    tag_index
+                };
+                other_tags[index]
+            }
+            NullableUnwrapped { other_fields, .. } => *other_fields,
+        };
+
+        field_layouts
+            .iter()
+            .take(index as usize)
+            .map(|field_layout| self.layout_interner.stack_size(*field_layout))
+            .sum()
+    }
+
     /*******************************************************************
      * Box
      *******************************************************************/
@@ -5,7 +5,7 @@ use roc_wasm_module::linking::IndexRelocType;
 
 use roc_error_macros::internal_error;
 use roc_module::symbol::Symbol;
-use roc_wasm_module::opcodes::{OpCode, OpCode::*};
+use roc_wasm_module::opcodes::{MiscOp, OpCode, OpCode::*, SimdOp};
 use roc_wasm_module::serialize::SerialBuffer;
 use roc_wasm_module::{
     round_up_to_alignment, Align, LocalId, RelocationEntry, ValueType, WasmModule,
@@ -74,6 +74,14 @@ macro_rules! instruction_memargs {
     };
 }
 
+macro_rules! simd_instruction_no_args {
+    ($method_name: ident, $opcode: ident, $pops: expr, $push: expr) => {
+        pub fn $method_name(&mut self) {
+            self.inst_simd(SimdOp::$opcode, $pops, $push);
+        }
+    };
+}
+
 #[derive(Debug)]
 pub struct CodeBuilder<'a> {
     pub arena: &'a Bump,
@@ -565,6 +573,35 @@ impl<'a> CodeBuilder<'a> {
         );
     }
 
+    /// Instruction with a prefix byte and a LEB-128 sub-opcode
+    fn inst_prefixed(&mut self, prefix: OpCode, sub_opcode: u32, pops: usize, push: bool) {
+        self.inst_base(prefix, pops, push);
+        self.code.encode_u32(sub_opcode);
+    }
+
+    fn inst_misc(&mut self, op: MiscOp, pops: usize, push: bool) {
+        self.inst_prefixed(MiscOp::PREFIX, op as u32, pops, push);
+        log_instruction!("{:10}\t\t{:?}", format!("{:?}", op), self.vm_block_stack);
+    }
+
+    fn inst_simd(&mut self, op: SimdOp, pops: usize, push: bool) {
+        self.inst_prefixed(SimdOp::PREFIX, op as u32, pops, push);
+        log_instruction!("{:10}\t\t{:?}", format!("{:?}", op), self.vm_block_stack);
+    }
+
+    fn inst_simd_mem(&mut self, op: SimdOp, pops: usize, push: bool, align: Align, offset: u32) {
+        self.inst_prefixed(SimdOp::PREFIX, op as u32, pops, push);
+        self.code.push(align as u8);
+        self.code.encode_u32(offset);
+        log_instruction!(
+            "{:10} {:?} {}\t{:?}",
+            format!("{:?}", op),
+            align,
+            offset,
+            self.vm_block_stack
+        );
+    }
+
     /**********************************************************
 
         INSTRUCTION METHODS
@@ -631,6 +668,14 @@ impl<'a> CodeBuilder<'a> {
         self.call_impl(function_index, n_args, has_return_val, false)
     }
 
+    /// Call a function that returns several values (Wasm multi-value proposal)
+    pub fn call_multi_value(&mut self, function_index: u32, n_args: usize, n_return_vals: usize) {
+        self.call_impl(function_index, n_args, n_return_vals > 0, false);
+        for _ in 1..n_return_vals {
+            self.current_stack_mut().push(Symbol::WASM_TMP);
+        }
+    }
+
     pub fn call_import(&mut self, function_index: u32, n_args: usize, has_return_val: bool) {
         self.call_impl(function_index, n_args, has_return_val, true)
     }
@@ -876,4 +921,54 @@ impl<'a> CodeBuilder<'a> {
     instruction_no_args!(i64_reinterpret_f64, I64REINTERPRETF64, 1, true);
     instruction_no_args!(f32_reinterpret_i32, F32REINTERPRETI32, 1, true);
     instruction_no_args!(f64_reinterpret_i64, F64REINTERPRETI64, 1, true);
+
+    /// Copy `size` bytes from `src` to `dst`. The regions may overlap. Stack: [dst, src, size] -> []
+    pub fn memory_copy(&mut self) {
+        self.inst_misc(MiscOp::MEMORYCOPY, 3, false);
+        self.code.push(0); // destination memory index
+        self.code.push(0); // source memory index
+    }
+    /// Set `size` bytes at `dst` to the low byte of `value`. Stack: [dst, value, size] -> []
+    pub fn memory_fill(&mut self) {
+        self.inst_misc(MiscOp::MEMORYFILL, 3, false);
+        self.code.push(0); // memory index
+    }
+
+    pub fn v128_load(&mut self, align: Align, offset: u32) {
+        self.inst_simd_mem(SimdOp::V128LOAD, 1, true, align, offset);
+    }
+    pub fn v128_store(&mut self, align: Align, offset: u32) {
+        self.inst_simd_mem(SimdOp::V128STORE, 2, false, align, offset);
+    }
+    pub fn v128_const(&mut self, x: u128) {
+        self.inst_prefixed(SimdOp::PREFIX, SimdOp::V128CONST as u32, 0, true);
+        self.code.extend_from_slice(&x.to_le_bytes());
+        log_instruction!(
+            "{:10}\t{:#x}\t{:?}",
+            format!("{:?}", SimdOp::V128CONST),
+            x,
+            self.vm_block_stack
+        );
+    }
+
+    simd_instruction_no_args!(i8x16_splat, I8X16SPLAT, 1, true);
+    simd_instruction_no_args!(i16x8_splat, I16X8SPLAT, 1, true);
+    simd_instruction_no_args!(i32x4_splat, I32X4SPLAT, 1, true);
+    simd_instruction_no_args!(i64x2_splat, I64X2SPLAT, 1, true);
+    simd_instruction_no_args!(i8x16_eq, I8X16EQ, 2, true);
+    simd_instruction_no_args!(i8x16_ne, I8X16NE, 2, true);
+    simd_instruction_no_args!(v128_not, V128NOT, 1, true);
+    simd_instruction_no_args!(v128_and, V128AND, 2, true);
+    simd_instruction_no_args!(v128_andnot, V128ANDNOT, 2, true);
+    simd_instruction_no_args!(v128_or, V128OR, 2, true);
+    simd_instruction_no_args!(v128_xor, V128XOR, 2, true);
+    simd_instruction_no_args!(v128_any_true, V128ANYTRUE, 1, true);
+    simd_instruction_no_args!(i8x16_all_true, I8X16ALLTRUE, 1, true);
+    simd_instruction_no_args!(i8x16_bitmask, I8X16BITMASK, 1, true);
+    simd_instruction_no_args!(i8x16_add, I8X16ADD, 2, true);
+    simd_instruction_no_args!(i8x16_sub, I8X16SUB, 2, true);
+    simd_instruction_no_args!(i32x4_add, I32X4ADD, 2, true);
+    simd_instruction_no_args!(i32x4_sub, I32X4SUB, 2, true);
+    simd_instruction_no_args!(i64x2_add, I64X2ADD, 2, true);
+    simd_instruction_no_args!(i64x2_sub, I64X2SUB, 2, true);
 }
@@ -14,6 +14,10 @@ pub enum ReturnMethod {
     NoReturnValue,
     /// This layout is returned as a packed struct in an integer. Only used by Zig, not C.
     ZigPackedStruct,
+    /// This layout is returned as several Wasm values, using the multi-value proposal.
+    /// The values are loaded from consecutive chunks of the layout's stack memory.
+    /// Only used by the Roc calling convention.
+    MultiValue(&'static [ValueType]),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -137,6 +141,11 @@ pub enum CallConv {
     /// The calling convention that Zig 0.9 generates for Wasm when we *ask* it
     /// for the .C calling convention, due to bugs in the Zig compiler.
     Zig,
+    /// The calling convention for calls from Roc to Roc. Arguments are passed as in C,
+    /// but small structs and 128-bit numbers are returned on the value stack (multi-value)
+    /// rather than through a pointer to stack memory in the caller's frame.
+    /// Procedures exposed to the host always use C instead.
+    Roc,
 }
 
 impl CallConv {
@@ -158,7 +167,7 @@ impl CallConv {
                     return &[];
                 }
                 match self {
-                    CallConv::C => {
+                    CallConv::C | CallConv::Roc => {
                         &[I32] // Always pass structs by reference (pointer to stack memory)
                     }
 
@@ -183,9 +192,13 @@ impl CallConv {
     pub fn stack_memory_return_method(&self, size: u32, format: StackMemoryFormat) -> ReturnMethod {
         use ReturnMethod::*;
         use StackMemoryFormat::*;
+        use ValueType::*;
 
         match format {
-            Int128 | Decimal => WriteToPointerArg,
+            Int128 | Decimal => match self {
+                CallConv::Roc => MultiValue(&[I64, I64]),
+                CallConv::C | CallConv::Zig => WriteToPointerArg,
+            },
 
             DataStructure => {
                 if size == 0 {
@@ -194,6 +207,18 @@ impl CallConv {
                 match self {
                     CallConv::C => WriteToPointerArg,
 
+                    CallConv::Roc => {
+                        // Only whole i32/i64 chunks, so that storing the values in the
+                        // caller's stack memory never writes past the end of the struct.
+                        match size {
+                            4 => MultiValue(&[I32]),
+                            8 => MultiValue(&[I64]),
+                            12 => MultiValue(&[I64, I32]), // e.g. Str and List
+                            16 => MultiValue(&[I64, I64]),
+                            _ => WriteToPointerArg,
+                        }
+                    }
+
                     CallConv::Zig => {
                         if size <= 8 {
                             ZigPackedStruct
@@ -199,6 +199,9 @@ pub struct CopyMemoryConfig {
     alignment_bytes: u32,
 }
 
+/// Above this size, a single `memory.copy` is smaller than an unrolled sequence of loads and stores
+const MEMORY_COPY_MIN_SIZE: u32 = 64;
+
 pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     if config.from_ptr == config.to_ptr && config.from_offset == config.to_offset {
         return;
@@ -207,9 +210,32 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
         return;
     }
 
+    if config.size >= MEMORY_COPY_MIN_SIZE {
+        code_builder.get_local(config.to_ptr);
+        if config.to_offset != 0 {
+            code_builder.i32_const(config.to_offset as i32);
+            code_builder.i32_add();
+        }
+        code_builder.get_local(config.from_ptr);
+        if config.from_offset != 0 {
+            code_builder.i32_const(config.from_offset as i32);
+            code_builder.i32_add();
+        }
+        code_builder.i32_const(config.size as i32);
+        code_builder.memory_copy();
+        return;
+    }
+
     let alignment = Align::from(config.alignment_bytes);
     let mut i = 0;
-    while config.size - i >= 8 {
+    while config.size - i >= 16 {
+        code_builder.get_local(config.to_ptr);
+        code_builder.get_local(config.from_ptr);
+        code_builder.v128_load(alignment, i + config.from_offset);
+        code_builder.v128_store(alignment, i + config.to_offset);
+        i += 16;
+    }
+    if config.size - i >= 8 {
         code_builder.get_local(config.to_ptr);
         code_builder.get_local(config.from_ptr);
         code_builder.i64_load(alignment, i + config.from_offset);
@@ -232,6 +258,72 @@ pub fn copy_memory(code_builder: &mut CodeBuilder, config: CopyMemoryConfig) {
     }
 }
 
+/// Alignment and offset of each Wasm value, for a value returned by `ReturnMethod::MultiValue`
+fn multi_value_chunks(
+    value_types: &[ValueType],
+    alignment_bytes: u32,
+) -> impl Iterator<Item = (ValueType, Align, u32)> + '_ {
+    value_types.iter().scan(0, move |offset, ty| {
+        let size = match ty {
+            ValueType::I32 | ValueType::F32 => 4,
+            ValueType::I64 | ValueType::F64 => 8,
+            ValueType::V128 => 16,
+        };
+        let align = Align::from(alignment_bytes.min(size));
+        let chunk = (*ty, align, *offset);
+        *offset += size;
+        Some(chunk)
+    })
+}
+
+/// Load a value from memory, as the Wasm values for `ReturnMethod::MultiValue`
+pub fn load_multi_value(
+    code_builder: &mut CodeBuilder,
+    value_types: &[ValueType],
+    from_ptr: LocalId,
+    from_offset: u32,
+    alignment_bytes: u32,
+) {
+    for (ty, align, offset) in multi_value_chunks(value_types, alignment_bytes) {
+        code_builder.get_local(from_ptr);
+        match ty {
+            ValueType::I32 => code_builder.i32_load(align, from_offset + offset),
+            ValueType::I64 => code_builder.i64_load(align, from_offset + offset),
+            ValueType::F32 => code_builder.f32_load(align, from_offset + offset),
+            ValueType::F64 => code_builder.f64_load(align, from_offset + offset),
+            ValueType::V128 => code_builder.v128_load(align, from_offset + offset),
+        }
+    }
+}
+
+/// Store the Wasm values for `ReturnMethod::MultiValue` from the top of the VM stack to memory.
+/// Each store needs the address underneath its value, so we first move the values into `tmp_locals`.
+pub fn store_multi_value(
+    code_builder: &mut CodeBuilder,
+    value_types: &[ValueType],
+    tmp_locals: &[LocalId],
+    to_ptr: LocalId,
+    to_offset: u32,
+    alignment_bytes: u32,
+) {
+    debug_assert_eq!(value_types.len(), tmp_locals.len());
+    for tmp in tmp_locals.iter().rev() {
+        code_builder.set_local(*tmp);
+    }
+    let chunks = multi_value_chunks(value_types, alignment_bytes);
+    for ((ty, align, offset), tmp) in chunks.zip(tmp_locals.iter()) {
+        code_builder.get_local(to_ptr);
+        code_builder.get_local(*tmp);
+        match ty {
+            ValueType::I32 => code_builder.i32_store(align, to_offset + offset),
+            ValueType::I64 => code_builder.i64_store(align, to_offset + offset),
+            ValueType::F32 => code_builder.f32_store(align, to_offset + offset),
+            ValueType::F64 => code_builder.f64_store(align, to_offset + offset),
+            ValueType::V128 => code_builder.v128_store(align, to_offset + offset),
+        }
+    }
+}
+
 pub struct WasmDebugSettings {
     proc_start_end: bool,
     user_procs_ir: bool,
@@ -10,6 +10,7 @@ use roc_mono::layout::{Builtin, FieldOrderHash, InLayout, Layout, LayoutInterner
 use roc_mono::low_level::HigherOrder;
 
 use crate::backend::{ProcLookupData, ProcSource, WasmBackend};
+use crate::code_builder::CodeBuilder;
 use crate::layout::{CallConv, StackMemoryFormat, WasmLayout};
 use crate::storage::{AddressValue, StackMemoryLocation, StoredValue};
 use crate::{PTR_TYPE, TARGET_INFO};
@@ -74,6 +75,7 @@ impl From<ValueType> for CodeGenNumType {
             ValueType::I64 => CodeGenNumType::I64,
             ValueType::F32 => CodeGenNumType::F32,
             ValueType::F64 => CodeGenNumType::F64,
+            ValueType::V128 => internal_error!("Roc numbers are never stored as v128"),
         }
     }
 }
@@ -121,6 +123,35 @@ fn symbol_is_signed_int(backend: &WasmBackend<'_, '_>, symbol: Symbol) -> bool {
     layout_is_signed_int(backend.storage.symbol_layouts[&symbol])
 }
 
+/// Dec is a signed 128-bit number under the hood, so only U128 is compared as unsigned
+fn num128_is_signed(backend: &WasmBackend<'_, '_>, symbol: Symbol) -> bool {
+    backend.storage.symbol_layouts[&symbol] != Layout::U128
+}
+
+/// Byte offsets of the two 64-bit halves of a 128-bit number (little-endian)
+const LOW_HALF: u32 = 0;
+const HIGH_HALF: u32 = 8;
+
+/// Find the local holding the base address of a 128-bit number, and the offset from it
+fn num128_location(backend: &WasmBackend<'_, '_>, stored: &StoredValue) -> (LocalId, u32) {
+    match stored {
+        StoredValue::StackMemory { location, .. } => {
+            location.local_and_offset(backend.storage.stack_frame_pointer)
+        }
+        _ => internal_error!(
+            "128-bit numbers should be in stack memory, not {:?}",
+            stored
+        ),
+    }
+}
+
+/// Push one half of a 128-bit number to the value stack, as an i64
+fn load_num128_half(backend: &mut WasmBackend<'_, '_>, symbol: Symbol, half: u32) {
+    let (local_id, offset) = num128_location(backend, backend.storage.get(&symbol));
+    backend.code_builder.get_local(local_id);
+    backend.code_builder.i64_load(Align::Bytes8, offset + half);
+}
+
 pub struct LowLevelCall<'a> {
     pub lowlevel: LowLevel,
     pub arguments: &'a [Symbol],
@@ -193,6 +224,158 @@ impl<'a> LowLevelCall<'a> {
         }
     }
 
+    /// Push `lhs < rhs` for two 128-bit numbers.
+    /// The high halves decide, unless they are equal. Then the low halves decide, as unsigned numbers.
+    fn num128_lt(&self, backend: &mut WasmBackend<'a, '_>, lhs: Symbol, rhs: Symbol) {
+        load_num128_half(backend, lhs, HIGH_HALF);
+        load_num128_half(backend, rhs, HIGH_HALF);
+        if num128_is_signed(backend, lhs) {
+            backend.code_builder.i64_lt_s();
+        } else {
+            backend.code_builder.i64_lt_u();
+        }
+
+        load_num128_half(backend, lhs, HIGH_HALF);
+        load_num128_half(backend, rhs, HIGH_HALF);
+        backend.code_builder.i64_eq();
+        load_num128_half(backend, lhs, LOW_HALF);
+        load_num128_half(backend, rhs, LOW_HALF);
+        backend.code_builder.i64_lt_u();
+        backend.code_builder.i32_and();
+
+        backend.code_builder.i32_or();
+    }
+
+    /// Apply an i64 operation to each half of the two 128-bit arguments, writing the result to the return value.
+    /// Only correct for operations where the halves don't affect each other, like bitwise operations.
+    fn num128_halfwise(&self, backend: &mut WasmBackend<'a, '_>, op: fn(&mut CodeBuilder<'a>)) {
+        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+        for half in [LOW_HALF, HIGH_HALF] {
+            backend.code_builder.get_local(ret_local);
+            load_num128_half(backend, self.arguments[0], half);
+            load_num128_half(backend, self.arguments[1], half);
+            op(&mut backend.code_builder);
+            backend
+                .code_builder
+                .i64_store(Align::Bytes8, ret_offset + half);
+        }
+    }
+
+    /// Wrapping addition or subtraction of two 128-bit numbers, with a carry (or borrow) between the halves.
+    /// Two's complement makes this work the same for signed and unsigned numbers, and for Dec.
+    fn num128_add_or_sub_wrap(&self, backend: &mut WasmBackend<'a, '_>, is_sub: bool) {
+        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+        let [lhs, rhs] = [self.arguments[0], self.arguments[1]];
+
+        backend.code_builder.get_local(ret_local);
+        load_num128_half(backend, lhs, LOW_HALF);
+        load_num128_half(backend, rhs, LOW_HALF);
+        if is_sub {
+            backend.code_builder.i64_sub();
+        } else {
+            backend.code_builder.i64_add();
+        }
+        backend
+            .code_builder
+            .i64_store(Align::Bytes8, ret_offset + LOW_HALF);
+
+        backend.code_builder.get_local(ret_local);
+        load_num128_half(backend, lhs, HIGH_HALF);
+        load_num128_half(backend, rhs, HIGH_HALF);
+        if is_sub {
+            backend.code_builder.i64_sub();
+            // borrow = lhs.low < rhs.low
+            load_num128_half(backend, lhs, LOW_HALF);
+            load_num128_half(backend, rhs, LOW_HALF);
+            backend.code_builder.i64_lt_u();
+            backend.code_builder.i64_extend_u_i32();
+            backend.code_builder.i64_sub();
+        } else {
+            backend.code_builder.i64_add();
+            // carry = result.low < lhs.low
+            backend.code_builder.get_local(ret_local);
+            backend
+                .code_builder
+                .i64_load(Align::Bytes8, ret_offset + LOW_HALF);
+            load_num128_half(backend, lhs, LOW_HALF);
+            backend.code_builder.i64_lt_u();
+            backend.code_builder.i64_extend_u_i32();
+            backend.code_builder.i64_add();
+        }
+        backend
+            .code_builder
+            .i64_store(Align::Bytes8, ret_offset + HIGH_HALF);
+    }
+
+    /// Write the negation of a signed 128-bit number (or Dec) to the return value,
+    /// panicking if the argument is the minimum value.
+    fn num128_neg(&self, backend: &mut WasmBackend<'a, '_>, panic_msg: &'a str) {
+        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+        let arg = self.arguments[0];
+
+        load_num128_half(backend, arg, HIGH_HALF);
+        backend.code_builder.i64_const(i64::MIN);
+        backend.code_builder.i64_eq();
+        load_num128_half(backend, arg, LOW_HALF);
+        backend.code_builder.i64_eqz();
+        backend.code_builder.i32_and();
+        backend.code_builder.if_();
+        backend.stmt_internal_error(panic_msg);
+        backend.code_builder.end();
+
+        // low = 0 - low
+        backend.code_builder.get_local(ret_local);
+        backend.code_builder.i64_const(0);
+        load_num128_half(backend, arg, LOW_HALF);
+        backend.code_builder.i64_sub();
+        backend
+            .code_builder
+            .i64_store(Align::Bytes8, ret_offset + LOW_HALF);
+
+        // high = 0 - high - borrow, where there's a borrow unless low == 0
+        backend.code_builder.get_local(ret_local);
+        backend.code_builder.i64_const(0);
+        load_num128_half(backend, arg, HIGH_HALF);
+        backend.code_builder.i64_sub();
+        load_num128_half(backend, arg, LOW_HALF);
+        backend.code_builder.i64_const(0);
+        backend.code_builder.i64_ne();
+        backend.code_builder.i64_extend_u_i32();
+        backend.code_builder.i64_sub();
+        backend
+            .code_builder
+            .i64_store(Align::Bytes8, ret_offset + HIGH_HALF);
+    }
+
+    /// Copy a 128-bit argument to the return value
+    fn num128_copy(&self, backend: &mut WasmBackend<'a, '_>) {
+        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+        for half in [LOW_HALF, HIGH_HALF] {
+            backend.code_builder.get_local(ret_local);
+            load_num128_half(backend, self.arguments[0], half);
+            backend
+                .code_builder
+                .i64_store(Align::Bytes8, ret_offset + half);
+        }
+    }
+
+    /// Push a 128-bit integer converted to f64, calculated as `high * 2^64 + low`.
+    /// This can round twice, so the last bit may differ from a direct conversion.
+    fn num128_to_f64(&self, backend: &mut WasmBackend<'a, '_>) {
+        let arg = self.arguments[0];
+        load_num128_half(backend, arg, HIGH_HALF);
+        if num128_is_signed(backend, arg) {
+            backend.code_builder.f64_convert_s_i64();
+        } else {
+            backend.code_builder.f64_convert_u_i64();
+        }
+        backend.code_builder.f64_const(18446744073709551616.0); // 2^64
+        backend.code_builder.f64_mul();
+        load_num128_half(backend, arg, LOW_HALF);
+        backend.code_builder.f64_convert_u_i64();
+        backend.code_builder.f64_add();
+    }
+
     ///  Main entrypoint from WasmBackend
     pub fn generate(&self, backend: &mut WasmBackend<'a, '_>) {
         use CodeGenNumType::*;
@@ -834,10 +1017,7 @@ impl<'a> LowLevelCall<'a> {
 
             NumAddWrap => match self.ret_layout_raw {
                 Layout::Builtin(Builtin::Int(width)) => match width {
-                    IntWidth::I128 | IntWidth::U128 => {
-                        // TODO: don't panic
-                        self.load_args_and_call_zig(backend, &bitcode::NUM_ADD_OR_PANIC_INT[width])
-                    }
+                    IntWidth::I128 | IntWidth::U128 => self.num128_add_or_sub_wrap(backend, false),
                     IntWidth::I64 | IntWidth::U64 => {
                         self.load_args(backend);
                         backend.code_builder.i64_add()
@@ -862,10 +1042,7 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.f64_add()
                     }
                 },
-                Layout::Builtin(Builtin::Decimal) => {
-                    // TODO: don't panic
-                    self.load_args_and_call_zig(backend, bitcode::DEC_ADD_OR_PANIC)
-                }
+                Layout::Builtin(Builtin::Decimal) => self.num128_add_or_sub_wrap(backend, false),
                 _ => panic_ret_type(),
             },
 
@@ -925,10 +1102,7 @@ impl<'a> LowLevelCall<'a> {
 
             NumSubWrap => match self.ret_layout_raw {
                 Layout::Builtin(Builtin::Int(width)) => match width {
-                    IntWidth::I128 | IntWidth::U128 => {
-                        // TODO: don't panic
-                        self.load_args_and_call_zig(backend, &bitcode::NUM_SUB_OR_PANIC_INT[width])
-                    }
+                    IntWidth::I128 | IntWidth::U128 => self.num128_add_or_sub_wrap(backend, true),
                     IntWidth::I64 | IntWidth::U64 => {
                         self.load_args(backend);
                         backend.code_builder.i64_sub()
@@ -953,10 +1127,7 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.f64_sub()
                     }
                 },
-                Layout::Builtin(Builtin::Decimal) => {
-                    // TODO: don't panic
-                    self.load_args_and_call_zig(backend, bitcode::DEC_SUB_OR_PANIC)
-                }
+                Layout::Builtin(Builtin::Decimal) => self.num128_add_or_sub_wrap(backend, true),
                 _ => panic_ret_type(),
             },
             NumSubChecked => {
@@ -1080,6 +1251,11 @@ impl<'a> LowLevelCall<'a> {
                 }
             }
             NumGt => {
+                if let I128 | Decimal = CodeGenNumType::for_symbol(backend, self.arguments[0]) {
+                    // x > y  <=>  y < x
+                    self.num128_lt(backend, self.arguments[1], self.arguments[0]);
+                    return;
+                }
                 self.load_args(backend);
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => {
@@ -1098,10 +1274,16 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_gt(),
                     F64 => backend.code_builder.f64_gt(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    I128 | Decimal => unreachable!("128-bit numbers are compared above"),
                 }
             }
             NumGte => {
+                if let I128 | Decimal = CodeGenNumType::for_symbol(backend, self.arguments[0]) {
+                    // x >= y  <=>  !(x < y)
+                    self.num128_lt(backend, self.arguments[0], self.arguments[1]);
+                    backend.code_builder.i32_eqz();
+                    return;
+                }
                 self.load_args(backend);
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => {
@@ -1120,10 +1302,14 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_ge(),
                     F64 => backend.code_builder.f64_ge(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    I128 | Decimal => unreachable!("128-bit numbers are compared above"),
                 }
             }
             NumLt => {
+                if let I128 | Decimal = CodeGenNumType::for_symbol(backend, self.arguments[0]) {
+                    self.num128_lt(backend, self.arguments[0], self.arguments[1]);
+                    return;
+                }
                 self.load_args(backend);
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => {
@@ -1142,10 +1328,16 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_lt(),
                     F64 => backend.code_builder.f64_lt(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    I128 | Decimal => unreachable!("128-bit numbers are compared above"),
                 }
             }
             NumLte => {
+                if let I128 | Decimal = CodeGenNumType::for_symbol(backend, self.arguments[0]) {
+                    // x <= y  <=>  !(y < x)
+                    self.num128_lt(backend, self.arguments[1], self.arguments[0]);
+                    backend.code_builder.i32_eqz();
+                    return;
+                }
                 self.load_args(backend);
                 let layout = backend.storage.symbol_layouts[&self.arguments[0]];
                 match CodeGenNumType::from(layout) {
@@ -1165,7 +1357,7 @@ impl<'a> LowLevelCall<'a> {
                     }
                     F32 => backend.code_builder.f32_le(),
                     F64 => backend.code_builder.f64_le(),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    I128 | Decimal => unreachable!("128-bit numbers are compared above"),
                 }
             }
             NumCompare => {
@@ -1215,18 +1407,31 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.f64_lt();
                         backend.code_builder.i32_add();
                     }
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    I128 | Decimal => {
+                        let [x, y] = [self.arguments[0], self.arguments[1]];
+                        for half in [LOW_HALF, HIGH_HALF] {
+                            load_num128_half(backend, x, half);
+                            load_num128_half(backend, y, half);
+                            backend.code_builder.i64_ne();
+                        }
+                        backend.code_builder.i32_or();
+                        self.num128_lt(backend, x, y);
+                        backend.code_builder.i32_add();
+                    }
                 }
             }
-            NumDivFrac => {
-                self.load_args(backend);
-                match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
-                    F32 => backend.code_builder.f32_div(),
-                    F64 => backend.code_builder.f64_div(),
-                    Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_DIV),
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+            NumDivFrac => match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
+                F32 => {
+                    self.load_args(backend);
+                    backend.code_builder.f32_div();
                 }
-            }
+                F64 => {
+                    self.load_args(backend);
+                    backend.code_builder.f64_div();
+                }
+                Decimal => self.load_args_and_call_zig(backend, bitcode::DEC_DIV),
+                I32 | I64 | I128 => panic_ret_type(),
+            },
             NumDivTruncUnchecked => {
                 self.load_args(backend);
                 let is_signed = symbol_is_signed_int(backend, self.arguments[0]);
@@ -1245,7 +1450,15 @@ impl<'a> LowLevelCall<'a> {
                             backend.code_builder.i64_div_u()
                         }
                     }
-                    x => todo!("{:?} for {:?}", self.lowlevel, x),
+                    // from compiler_rt
+                    I128 => {
+                        if is_signed {
+                            self.load_args_and_call_zig(backend, "__divti3")
+                        } else {
+                            self.load_args_and_call_zig(backend, "__udivti3")
+                        }
+                    }
+                    x => internal_error!("{:?} is not defined for {:?}", self.lowlevel, x),
                 }
             }
             NumDivCeilUnchecked => match self.ret_layout_raw {
@@ -1260,7 +1473,19 @@ impl<'a> LowLevelCall<'a> {
                 match CodeGenNumType::for_symbol(backend, self.arguments[0]) {
                     I32 => backend.code_builder.i32_rem_s(),
                     I64 => backend.code_builder.i64_rem_s(),
-                    _ => todo!("{:?} for {:?}", self.lowlevel, self.ret_layout),
+                    // from compiler_rt
+                    I128 => {
+                        if symbol_is_signed_int(backend, self.arguments[0]) {
+                            self.load_args_and_call_zig(backend, "__modti3")
+                        } else {
+                            self.load_args_and_call_zig(backend, "__umodti3")
+                        }
+                    }
+                    _ => internal_error!(
+                        "{:?} is not defined for {:?}",
+                        self.lowlevel,
+                        self.ret_layout
+                    ),
                 }
             }
             NumIsMultipleOf => {
@@ -1366,6 +1591,23 @@ impl<'a> LowLevelCall<'a> {
                 const PANIC_MSG: &str =
                     "integer absolute overflowed because its argument is the minimum value";
 
+                if let I128 | Decimal = CodeGenNumType::from(self.ret_layout) {
+                    if self.ret_layout == Layout::U128 {
+                        self.num128_copy(backend);
+                        return;
+                    }
+                    // x < 0 ? -x : x
+                    load_num128_half(backend, self.arguments[0], HIGH_HALF);
+                    backend.code_builder.i64_const(0);
+                    backend.code_builder.i64_lt_s();
+                    backend.code_builder.if_();
+                    self.num128_neg(backend, PANIC_MSG);
+                    backend.code_builder.else_();
+                    self.num128_copy(backend);
+                    backend.code_builder.end();
+                    return;
+                }
+
                 self.load_args(backend);
 
                 match CodeGenNumType::from(self.ret_layout) {
@@ -1430,6 +1672,11 @@ impl<'a> LowLevelCall<'a> {
                 const PANIC_MSG: &str =
                     "integer negation overflowed because its argument is the minimum value";
 
+                if let I128 | Decimal = CodeGenNumType::from(self.ret_layout) {
+                    self.num128_neg(backend, PANIC_MSG);
+                    return;
+                }
+
                 self.load_args(backend);
                 match CodeGenNumType::from(self.ret_layout) {
                     I32 => {
@@ -1490,9 +1737,45 @@ impl<'a> LowLevelCall<'a> {
                 _ => panic_ret_type(),
             },
             NumToFrac => {
-                self.load_args(backend);
                 let ret_type = CodeGenNumType::from(self.ret_layout);
                 let arg_type = CodeGenNumType::for_symbol(backend, self.arguments[0]);
+                if ret_type == Decimal {
+                    let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
+                    match backend.layout_interner.get(arg_layout) {
+                        Layout::Builtin(Builtin::Int(width)) => {
+                            self.load_args_and_call_zig(backend, &bitcode::DEC_FROM_INT[width]);
+                        }
+                        Layout::Builtin(Builtin::Float(FloatWidth::F64)) => {
+                            self.load_args_and_call_zig(backend, bitcode::DEC_FROM_F64);
+                        }
+                        Layout::Builtin(Builtin::Float(FloatWidth::F32)) => {
+                            // Zig only converts from F64, so promote the argument on its way in
+                            let (num_wasm_args, has_return_val, _) = self.load_args(backend);
+                            backend.code_builder.f64_promote_f32();
+                            backend.call_host_fn_after_loading_args(
+                                bitcode::DEC_FROM_F64,
+                                num_wasm_args,
+                                has_return_val,
+                            );
+                        }
+                        Layout::Builtin(Builtin::Decimal) => self.num128_copy(backend),
+                        x => internal_error!("NumToFrac is not defined for {:?}", x),
+                    }
+                    return;
+                }
+                if let I128 | Decimal = arg_type {
+                    if arg_type == I128 {
+                        self.num128_to_f64(backend);
+                    } else {
+                        self.load_args_and_call_zig(backend, bitcode::DEC_TO_F64);
+                    }
+                    if ret_type == F32 {
+                        backend.code_builder.f32_demote_f64();
+                    }
+                    return;
+                }
+
+                self.load_args(backend);
                 match (ret_type, arg_type) {
                     (F32, I32) => backend.code_builder.f32_convert_s_i32(),
                     (F32, I64) => backend.code_builder.f32_convert_s_i64(),
@@ -1555,7 +1838,13 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => {
+                        if layout_is_signed_int(self.ret_layout) {
+                            IntWidth::I128
+                        } else {
+                            IntWidth::U128
+                        }
+                    }
                     _ => internal_error!("Invalid return type for round: {:?}", ret_type),
                 };
 
@@ -1590,7 +1879,9 @@ impl<'a> LowLevelCall<'a> {
                     (I32, F64) => backend.code_builder.i32_trunc_s_f64(),
                     (I64, F32) => backend.code_builder.i64_trunc_s_f32(),
                     (I64, F64) => backend.code_builder.i64_trunc_s_f64(),
-                    (I128, _) => todo!("{:?} for I128", self.lowlevel),
+                    // from compiler_rt. The return address was loaded along with the argument.
+                    (I128, F32) => backend.call_host_fn_after_loading_args("__fixsfti", 2, false),
+                    (I128, F64) => backend.call_host_fn_after_loading_args("__fixdfti", 2, false),
                     _ => panic_ret_type(),
                 }
             }
@@ -1606,7 +1897,13 @@ impl<'a> LowLevelCall<'a> {
                 let width = match ret_type {
                     CodeGenNumType::I32 => IntWidth::I32,
                     CodeGenNumType::I64 => IntWidth::I64,
-                    CodeGenNumType::I128 => todo!("{:?} for I128", self.lowlevel),
+                    CodeGenNumType::I128 => {
+                        if layout_is_signed_int(self.ret_layout) {
+                            IntWidth::I128
+                        } else {
+                            IntWidth::U128
+                        }
+                    }
                     _ => internal_error!("Invalid return type for pow: {:?}", ret_type),
                 };
 
@@ -1639,46 +1936,60 @@ impl<'a> LowLevelCall<'a> {
             NumBytesToU32 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U32),
             NumBytesToU64 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U64),
             NumBytesToU128 => self.load_args_and_call_zig(backend, bitcode::NUM_BYTES_TO_U128),
-            NumBitwiseAnd => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_and(),
-                    I64 => backend.code_builder.i64_and(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+            NumBitwiseAnd => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_and()
                 }
-            }
-            NumBitwiseXor => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_xor(),
-                    I64 => backend.code_builder.i64_xor(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_and()
                 }
-            }
-            NumBitwiseOr => {
-                self.load_args(backend);
-                match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_or(),
-                    I64 => backend.code_builder.i64_or(),
-                    I128 => todo!("{:?} for I128", self.lowlevel),
-                    _ => panic_ret_type(),
+                I128 => self.num128_halfwise(backend, CodeBuilder::i64_and),
+                _ => panic_ret_type(),
+            },
+            NumBitwiseXor => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_xor()
                 }
-            }
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_xor()
+                }
+                I128 => self.num128_halfwise(backend, CodeBuilder::i64_xor),
+                _ => panic_ret_type(),
+            },
+            NumBitwiseOr => match CodeGenNumType::from(self.ret_layout) {
+                I32 => {
+                    self.load_args(backend);
+                    backend.code_builder.i32_or()
+                }
+                I64 => {
+                    self.load_args(backend);
+                    backend.code_builder.i64_or()
+                }
+                I128 => self.num128_halfwise(backend, CodeBuilder::i64_or),
+                _ => panic_ret_type(),
+            },
             NumShiftLeftBy => {
                 let num = self.arguments[0];
                 let bits = self.arguments[1];
-                backend
-                    .storage
-                    .load_symbols(&mut backend.code_builder, &[num, bits]);
                 match CodeGenNumType::from(self.ret_layout) {
-                    I32 => backend.code_builder.i32_shl(),
+                    I32 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[num, bits]);
+                        backend.code_builder.i32_shl()
+                    }
                     I64 => {
+                        backend
+                            .storage
+                            .load_symbols(&mut backend.code_builder, &[num, bits]);
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shl();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => self.load_args_and_call_zig(backend, "__ashlti3"), // from compiler_rt
                     _ => panic_ret_type(),
                 }
             }
@@ -1727,7 +2038,7 @@ impl<'a> LowLevelCall<'a> {
                         backend.code_builder.i64_extend_u_i32();
                         backend.code_builder.i64_shr_s();
                     }
-                    I128 => todo!("{:?} for I128", self.lowlevel),
+                    I128 => self.load_args_and_call_zig(backend, "__ashrti3"), // from compiler_rt
                     _ => panic_ret_type(),
                 }
             }
@@ -1800,8 +2111,9 @@ impl<'a> LowLevelCall<'a> {
                         self.wrap_small_int(backend, ret_width);
                     }
                     (I32, I128) => {
-                        self.load_args(backend);
-                        backend.code_builder.i32_load(Align::Bytes4, 0);
+                        load_num128_half(backend, self.arguments[0], LOW_HALF);
+                        backend.code_builder.i32_wrap_i64();
+                        self.wrap_small_int(backend, ret_width);
                     }
                     (I64, I32) => {
                         self.load_args(backend);
@@ -1815,37 +2127,48 @@ impl<'a> LowLevelCall<'a> {
                         self.load_args(backend);
                     }
                     (I64, I128) => {
-                        let (frame_ptr, offset) = match backend.storage.get(&self.arguments[0]) {
-                            StoredValue::StackMemory { location, .. } => {
-                                location.local_and_offset(backend.storage.stack_frame_pointer)
-                            }
-                            _ => internal_error!("I128 should be in stack memory"),
-                        };
-                        backend.code_builder.get_local(frame_ptr);
-                        backend.code_builder.i64_load(Align::Bytes8, offset);
+                        load_num128_half(backend, self.arguments[0], LOW_HALF);
                     }
-                    (I128, I64) => {
-                        // Symbols are loaded as if for a call, so the i128 "return address" and i64 value are on the value stack
-                        self.load_args(backend);
-                        backend.code_builder.i64_store(Align::Bytes8, 0);
-
-                        // Zero the most significant 64 bits
-                        let (frame_ptr, offset) = match &self.ret_storage {
-                            StoredValue::StackMemory { location, .. } => {
-                                location.local_and_offset(backend.storage.stack_frame_pointer)
+                    (I128, I32 | I64) => {
+                        let (ret_local, ret_offset) = num128_location(backend, &self.ret_storage);
+                        let load_arg_as_i64 = |backend: &mut WasmBackend<'a, '_>| {
+                            backend
+                                .storage
+                                .load_symbols(&mut backend.code_builder, self.arguments);
+                            if arg_type == I32 {
+                                if arg_width.is_signed() {
+                                    backend.code_builder.i64_extend_s_i32()
+                                } else {
+                                    backend.code_builder.i64_extend_u_i32()
+                                }
                             }
-                            _ => internal_error!("I128 should be in stack memory"),
                         };
-                        backend.code_builder.get_local(frame_ptr);
-                        backend.code_builder.i64_const(0);
-                        backend.code_builder.i64_store(Align::Bytes8, offset + 8);
+
+                        backend.code_builder.get_local(ret_local);
+                        load_arg_as_i64(backend);
+                        backend
+                            .code_builder
+                            .i64_store(Align::Bytes8, ret_offset + LOW_HALF);
+
+                        // Sign-extend or zero-extend into the high half
+                        backend.code_builder.get_local(ret_local);
+                        if arg_width.is_signed() {
+                            load_arg_as_i64(backend);
+                            backend.code_builder.i64_const(63);
+                            backend.code_builder.i64_shr_s();
+                        } else {
+                            backend.code_builder.i64_const(0);
+                        }
+                        backend
+                            .code_builder
+                            .i64_store(Align::Bytes8, ret_offset + HIGH_HALF);
                     }
+                    (I128, I128) => self.num128_copy(backend),
 
                     _ => todo!("{:?}: {:?} -> {:?}", self.lowlevel, arg_type, ret_type),
                 }
             }
             NumToFloatCast => {
-                self.load_args(backend);
                 let arg_layout = backend.storage.symbol_layouts[&self.arguments[0]];
                 let arg_signed = match backend.layout_interner.get(arg_layout) {
                     Layout::Builtin(Builtin::Int(w)) => w.is_signed(),
@@ -1856,6 +2179,20 @@ impl<'a> LowLevelCall<'a> {
                 let ret_type = CodeGenNumType::from(self.ret_layout);
                 let arg_type = CodeGenNumType::from(arg_layout);
 
+                if let I128 | Decimal = arg_type {
+                    if arg_type == I128 {
+                        self.num128_to_f64(backend);
+                    } else {
+                        self.load_args_and_call_zig(backend, bitcode::DEC_TO_F64);
+                    }
+                    if ret_type == F32 {
+                        backend.code_builder.f32_demote_f64();
+                    }
+                    return;
+                }
+
+                self.load_args(backend);
+
                 match (ret_type, arg_type) {
                     (F32, F32) => {}
                     (F32, F64) => backend.code_builder.f32_demote_f64(),
@@ -1965,6 +2302,26 @@ impl<'a> LowLevelCall<'a> {
 
             PtrWrite => todo!("{:?}", self.lowlevel),
 
+            PtrStore => {
+                let ptr = self.arguments[0];
+                let ptr_storage = backend.storage.get(&ptr).to_owned();
+                let ptr_local = match backend.storage.ensure_value_has_local(
+                    &mut backend.code_builder,
+                    ptr,
+                    ptr_storage,
+                ) {
+                    StoredValue::Local { local_id, .. } => local_id,
+                    other => internal_error!("{:?} should be a pointer, not {:?}", ptr, other),
+                };
+
+                backend.storage.copy_value_to_memory(
+                    &mut backend.code_builder,
+                    ptr_local,
+                    0,
+                    self.arguments[1],
+                );
+            }
+
             Hash => todo!("{:?}", self.lowlevel),
 
             Eq | NotEq => self.eq_or_neq(backend),
@@ -1980,6 +2337,7 @@ impl<'a> LowLevelCall<'a> {
                     ValueType::I64 => backend.code_builder.i64_const(0),
                     ValueType::F32 => backend.code_builder.f32_const(0.0),
                     ValueType::F64 => backend.code_builder.f64_const(0.0),
+                    ValueType::V128 => backend.code_builder.v128_const(0),
                 },
                 StoredValue::StackMemory { .. } => { /* do nothing */ }
             },
@@ -2011,7 +2369,14 @@ impl<'a> LowLevelCall<'a> {
             ) => self.eq_or_neq_number(backend),
 
             Layout::Builtin(Builtin::Str) => {
-                self.load_args_and_call_zig(backend, bitcode::STR_EQUAL);
+                self.eq_bytes(backend, true);
+                if invert_result {
+                    backend.code_builder.i32_eqz();
+                }
+            }
+
+            Layout::Builtin(Builtin::List(_)) if backend.is_byte_list(arg_layout) => {
+                self.eq_bytes(backend, false);
                 if invert_result {
                     backend.code_builder.i32_eqz();
                 }
@@ -2060,6 +2425,127 @@ impl<'a> LowLevelCall<'a> {
         }
     }
 
+    /// Equality for a `Str` or a list of bytes. Compare the lengths, then the contents,
+    /// 16 bytes at a time with SIMD and then one byte at a time for the remainder.
+    fn eq_bytes(&self, backend: &mut WasmBackend<'a, '_>, is_str: bool) {
+        let mut ptrs = [LocalId(0); 2];
+        let mut lens = [LocalId(0); 2];
+
+        for (i, arg) in self.arguments.iter().enumerate() {
+            let (local_id, offset) = match backend.storage.get(arg) {
+                StoredValue::StackMemory { location, .. } => {
+                    location.local_and_offset(backend.storage.stack_frame_pointer)
+                }
+                _ => internal_error!("invalid storage for {:?}", arg),
+            };
+            ptrs[i] = backend.storage.create_anonymous_local(PTR_TYPE);
+            lens[i] = backend.storage.create_anonymous_local(ValueType::I32);
+
+            if is_str {
+                // A small string keeps its bytes inline, and its length in the last byte.
+                // It has a negative capacity. A seamless slice has the high bit of the length set.
+                let is_small = backend.storage.create_anonymous_local(ValueType::I32);
+                backend.code_builder.get_local(local_id);
+                backend.code_builder.i32_load(Align::Bytes4, offset + 8);
+                backend.code_builder.i32_const(0);
+                backend.code_builder.i32_lt_s();
+                backend.code_builder.set_local(is_small);
+
+                backend.code_builder.get_local(local_id);
+                if offset != 0 {
+                    backend.code_builder.i32_const(offset as i32);
+                    backend.code_builder.i32_add();
+                }
+                backend.code_builder.get_local(local_id);
+                backend.code_builder.i32_load(Align::Bytes4, offset);
+                backend.code_builder.get_local(is_small);
+                backend.code_builder.select();
+                backend.code_builder.set_local(ptrs[i]);
+
+                backend.code_builder.get_local(local_id);
+                backend.code_builder.i32_load8_u(Align::Bytes1, offset + 11);
+                backend.code_builder.i32_const(0x80);
+                backend.code_builder.i32_xor();
+                backend.code_builder.get_local(local_id);
+                backend.code_builder.i32_load(Align::Bytes4, offset + 4);
+                backend.code_builder.i32_const(i32::MAX);
+                backend.code_builder.i32_and();
+                backend.code_builder.get_local(is_small);
+                backend.code_builder.select();
+                backend.code_builder.set_local(lens[i]);
+            } else {
+                backend.code_builder.get_local(local_id);
+                backend.code_builder.i32_load(Align::Bytes4, offset);
+                backend.code_builder.set_local(ptrs[i]);
+
+                backend.code_builder.get_local(local_id);
+                backend
+                    .code_builder
+                    .i32_load(Align::Bytes4, offset + 4 * Builtin::WRAPPER_LEN);
+                backend.code_builder.set_local(lens[i]);
+            }
+        }
+
+        let [ptr0, ptr1] = ptrs;
+        let len = lens[0];
+        let result = backend.storage.create_anonymous_local(ValueType::I32);
+        backend.code_builder.i32_const(0);
+        backend.code_builder.set_local(result);
+
+        // Outer block: branch out of it as soon as we know the values are not equal
+        backend.code_builder.block();
+        backend.code_builder.get_local(lens[0]);
+        backend.code_builder.get_local(lens[1]);
+        backend.code_builder.i32_ne();
+        backend.code_builder.br_if(0);
+
+        for chunk_size in [16, 1] {
+            // Loop over chunks, with an inner block to exit when there are too few bytes left
+            backend.code_builder.loop_();
+            backend.code_builder.block();
+            backend.code_builder.get_local(len);
+            backend.code_builder.i32_const(chunk_size);
+            backend.code_builder.i32_lt_u();
+            backend.code_builder.br_if(0);
+
+            backend.code_builder.get_local(ptr0);
+            if chunk_size == 16 {
+                backend.code_builder.v128_load(Align::Bytes1, 0);
+                backend.code_builder.get_local(ptr1);
+                backend.code_builder.v128_load(Align::Bytes1, 0);
+                backend.code_builder.i8x16_eq();
+                backend.code_builder.i8x16_all_true();
+                backend.code_builder.i32_eqz();
+            } else {
+                backend.code_builder.i32_load8_u(Align::Bytes1, 0);
+                backend.code_builder.get_local(ptr1);
+                backend.code_builder.i32_load8_u(Align::Bytes1, 0);
+                backend.code_builder.i32_ne();
+            }
+            backend.code_builder.br_if(2);
+
+            for local in [ptr0, ptr1] {
+                backend.code_builder.get_local(local);
+                backend.code_builder.i32_const(chunk_size);
+                backend.code_builder.i32_add();
+                backend.code_builder.set_local(local);
+            }
+            backend.code_builder.get_local(len);
+            backend.code_builder.i32_const(chunk_size);
+            backend.code_builder.i32_sub();
+            backend.code_builder.set_local(len);
+            backend.code_builder.br(1);
+            backend.code_builder.end();
+            backend.code_builder.end();
+        }
+
+        backend.code_builder.i32_const(1);
+        backend.code_builder.set_local(result);
+        backend.code_builder.end();
+
+        backend.code_builder.get_local(result);
+    }
+
     fn eq_or_neq_number(&self, backend: &mut WasmBackend<'a, '_>) {
         use StoredValue::*;
 
@@ -2072,12 +2558,20 @@ impl<'a> LowLevelCall<'a> {
                         ValueType::I64 => backend.code_builder.i64_eq(),
                         ValueType::F32 => backend.code_builder.f32_eq(),
                         ValueType::F64 => backend.code_builder.f64_eq(),
+                        ValueType::V128 => {
+                            backend.code_builder.i8x16_eq();
+                            backend.code_builder.i8x16_all_true();
+                        }
                     },
                     LowLevel::NotEq => match value_type {
                         ValueType::I32 => backend.code_builder.i32_ne(),
                         ValueType::I64 => backend.code_builder.i64_ne(),
                         ValueType::F32 => backend.code_builder.f32_ne(),
                         ValueType::F64 => backend.code_builder.f64_ne(),
+                        ValueType::V128 => {
+                            backend.code_builder.i8x16_ne();
+                            backend.code_builder.v128_any_true();
+                        }
                     },
                     _ => internal_error!("{:?} ended up in Equality code", self.lowlevel),
                 }
@@ -2215,6 +2709,7 @@ fn num_is_nan(backend: &mut WasmBackend<'_, '_>, argument: Symbol) {
                     backend.code_builder.i64_ne(); // Mantissa is non-zero
                     backend.code_builder.i32_and();
                 }
+                ValueType::V128 => internal_error!("Roc numbers are never stored as v128"),
             }
         }
         StackMemory { format, .. } => {
@@ -2258,6 +2753,7 @@ fn num_is_infinite(backend: &mut WasmBackend<'_, '_>, argument: Symbol) {
                     backend.code_builder.i64_const(0x7ff0_0000_0000_0000);
                     backend.code_builder.i64_eq();
                 }
+                ValueType::V128 => internal_error!("Roc numbers are never stored as v128"),
             }
         }
         StackMemory { format, .. } => {
@@ -2301,6 +2797,7 @@ fn num_is_finite(backend: &mut WasmBackend<'_, '_>, argument: Symbol) {
                     backend.code_builder.i64_const(0x7ff0_0000_0000_0000);
                     backend.code_builder.i64_ne();
                 }
+                ValueType::V128 => internal_error!("Roc numbers are never stored as v128"),
             }
         }
         StackMemory { format, .. } => {
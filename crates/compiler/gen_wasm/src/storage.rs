@@ -8,7 +8,7 @@ use roc_mono::layout::{InLayout, STLayoutInterner};
 
 use crate::code_builder::{CodeBuilder, VmSymbolState};
 use crate::layout::{CallConv, ReturnMethod, StackMemoryFormat, WasmLayout};
-use crate::{copy_memory, CopyMemoryConfig, PTR_TYPE};
+use crate::{copy_memory, store_multi_value, CopyMemoryConfig, PTR_TYPE};
 use roc_wasm_module::{round_up_to_alignment, Align, LocalId, ValueType};
 
 pub enum StoredVarKind {
@@ -69,6 +69,7 @@ impl StoredValue {
                     I64 => &[I64],
                     F32 => &[F32],
                     F64 => &[F64],
+                    V128 => &[V128],
                 }
             }
             // Stack memory values: 1 Roc argument => 0-2 Wasm arguments
@@ -88,7 +89,8 @@ pub enum AddressValue {
 /// including the VM stack, local variables, and linear memory
 #[derive(Debug)]
 pub struct Storage<'a> {
-    pub return_var: Option<LocalId>,
+    /// Locals holding the return value(s). More than one for `ReturnMethod::MultiValue`.
+    pub return_vars: Vec<'a, LocalId>,
     pub arg_types: Vec<'a, ValueType>,
     pub local_types: Vec<'a, ValueType>,
     pub symbol_layouts: MutMap<Symbol, InLayout<'a>>,
@@ -100,7 +102,7 @@ pub struct Storage<'a> {
 impl<'a> Storage<'a> {
     pub fn new(arena: &'a Bump) -> Self {
         Storage {
-            return_var: None,
+            return_vars: Vec::with_capacity_in(2, arena),
             arg_types: Vec::with_capacity_in(8, arena),
             local_types: Vec::with_capacity_in(32, arena),
             symbol_layouts: MutMap::default(),
@@ -111,7 +113,7 @@ impl<'a> Storage<'a> {
     }
 
     pub fn clear(&mut self) {
-        self.return_var = None;
+        self.return_vars.clear();
         self.arg_types.clear();
         self.local_types.clear();
         self.symbol_layouts.clear();
@@ -131,6 +133,16 @@ impl<'a> Storage<'a> {
         id
     }
 
+    pub fn local_type(&self, id: LocalId) -> ValueType {
+        let index = id.0 as usize;
+        let n_args = self.arg_types.len();
+        if index < n_args {
+            self.arg_types[index]
+        } else {
+            self.local_types[index - n_args]
+        }
+    }
+
     pub fn allocate_anonymous_stack_memory(
         &mut self,
         size: u32,
@@ -190,12 +202,15 @@ impl<'a> Storage<'a> {
                 format,
             } => {
                 let location = match kind {
-                    StoredVarKind::Variable => {
+                    StoredVarKind::ReturnValue if self.return_vars.is_empty() => {
+                        StackMemoryLocation::PointerArg(LocalId(0))
+                    }
+
+                    // A multi-value return is loaded from our own frame into return_vars
+                    StoredVarKind::Variable | StoredVarKind::ReturnValue => {
                         let offset = self.allocate_stack_memory(size, alignment_bytes);
                         StackMemoryLocation::FrameOffset(offset)
                     }
-
-                    StoredVarKind::ReturnValue => StackMemoryLocation::PointerArg(LocalId(0)),
                 };
 
                 StoredValue::StackMemory {
@@ -501,7 +516,7 @@ impl<'a> Storage<'a> {
 
         let return_method = return_layout.return_method(call_conv);
         let has_return_val = match return_method {
-            Primitive(..) => true,
+            Primitive(..) | MultiValue(..) => true,
             NoReturnValue => false,
             WriteToPointerArg => {
                 num_wasm_args += 1;
@@ -539,7 +554,7 @@ impl<'a> Storage<'a> {
 
             for arg in arguments {
                 match call_conv {
-                    CallConv::C => self.load_symbol_ccc(code_builder, *arg),
+                    CallConv::C | CallConv::Roc => self.load_symbol_ccc(code_builder, *arg),
                     CallConv::Zig => self.load_symbol_zig(code_builder, *arg),
                 }
             }
@@ -552,6 +567,39 @@ impl<'a> Storage<'a> {
         )
     }
 
+    /// After calling a function that uses `ReturnMethod::MultiValue`,
+    /// store the returned values from the VM stack into the return symbol's stack memory
+    pub fn store_multi_value_return(
+        &mut self,
+        code_builder: &mut CodeBuilder,
+        return_symbol: Symbol,
+        value_types: &[ValueType],
+    ) {
+        let mut tmp_locals = [LocalId(0); 2]; // ReturnMethod::MultiValue has at most 2 values
+        for (tmp, ty) in tmp_locals.iter_mut().zip(value_types) {
+            *tmp = self.create_anonymous_local(*ty);
+        }
+
+        match self.get(&return_symbol).to_owned() {
+            StoredValue::StackMemory {
+                location,
+                alignment_bytes,
+                ..
+            } => {
+                let (to_ptr, to_offset) = location.local_and_offset(self.stack_frame_pointer);
+                store_multi_value(
+                    code_builder,
+                    value_types,
+                    &tmp_locals[..value_types.len()],
+                    to_ptr,
+                    to_offset,
+                    alignment_bytes,
+                );
+            }
+            _ => internal_error!("Only stack memory values are returned as multiple values"),
+        }
+    }
+
     /// Generate code to copy a StoredValue to an arbitrary memory location
     /// (defined by a pointer and offset).
     pub fn copy_value_to_memory(
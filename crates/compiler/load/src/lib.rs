@@ -19,7 +19,8 @@ const SKIP_SUBS_CACHE: bool = {
 pub use roc_load_internal::docs;
 pub use roc_load_internal::file::{
     EntryPoint, ExecutionMode, ExpectMetadata, Expectations, ExposedToHost, LoadConfig, LoadResult,
-    LoadStart, LoadedModule, LoadingProblem, MonomorphizedModule, Phase, Threading,
+    LoadStart, LoadedModule, LoadingProblem, MonoDiagnostics, MonoOptimizations,
+    MonomorphizedModule, Phase, Threading,
 };
 
 #[allow(clippy::too_many_arguments)]
@@ -52,6 +53,8 @@ pub fn load_single_threaded<'a>(
     palette: Palette,
     roc_cache_dir: RocCacheDir<'_>,
     exec_mode: ExecutionMode,
+    mono_optimizations: MonoOptimizations,
+    mono_diagnostics: MonoDiagnostics,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let cached_subs = read_cached_types();
     let exposed_types = ExposedByModule::default();
@@ -65,6 +68,8 @@ pub fn load_single_threaded<'a>(
         render,
         palette,
         exec_mode,
+        mono_optimizations,
+        mono_diagnostics,
         roc_cache_dir,
     )
 }
@@ -186,6 +191,8 @@ pub fn load_and_typecheck_str<'a>(
         palette,
         roc_cache_dir,
         ExecutionMode::Check,
+        MonoOptimizations::Disabled,
+        MonoDiagnostics::default(),
     )? {
         Monomorphized(_) => unreachable!(""),
         TypeChecked(module) => Ok(module),
@@ -19,8 +19,9 @@ use roc_constrain::module::constrain_module;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_REFCOUNT,
-    ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_LOAD_LOG,
+    ROC_CHECK_MONO_IR, ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_INLINING,
+    ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION,
+    ROC_PRINT_LOAD_LOG,
 };
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::internal_error;
@@ -30,15 +31,18 @@ use roc_module::symbol::{
     IdentIds, IdentIdsByModule, Interns, ModuleId, ModuleIds, PQModuleName, PackageModuleIds,
     PackageQualified, Symbol,
 };
+use roc_mono::inline;
 use roc_mono::ir::{
     CapturedSymbols, ExternalSpecializations, GlueLayouts, LambdaSetId, PartialProc, Proc,
-    ProcLayout, Procs, ProcsBase, UpdateModeIds,
+    ProcLayout, Procs, ProcsBase, SymbolRegions, UpdateModeIds,
 };
 use roc_mono::layout::LayoutInterner;
 use roc_mono::layout::{
     GlobalLayoutInterner, LambdaName, Layout, LayoutCache, LayoutProblem, Niche, STLayoutInterner,
 };
+use roc_mono::missed_updates;
 use roc_mono::reset_reuse;
+use roc_mono::simplify;
 use roc_mono::{drop_specialization, inc_dec};
 use roc_packaging::cache::RocCacheDir;
 use roc_parse::ast::{
@@ -77,6 +81,7 @@ use {
 
 pub use crate::work::Phase;
 use crate::work::{DepCycle, Dependencies};
+pub use roc_mono::ir::{MonoDiagnostics, MonoOptimizations};
 
 #[cfg(target_family = "wasm")]
 use crate::wasm_instant::{Duration, Instant};
@@ -105,6 +110,8 @@ pub struct LoadConfig {
     pub palette: Palette,
     pub threading: Threading,
     pub exec_mode: ExecutionMode,
+    pub mono_optimizations: MonoOptimizations,
+    pub mono_diagnostics: MonoDiagnostics,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -447,6 +454,16 @@ fn start_phase<'a>(
                     expectations,
                 } = typechecked;
 
+                if state.mono_diagnostics.tracks_symbol_regions() {
+                    // top-level defs are not lowered by `make_specializations`, which records the
+                    // regions of everything else
+                    for loc_symbol in decls.symbols.iter() {
+                        state
+                            .symbol_regions
+                            .insert(loc_symbol.value, loc_symbol.region);
+                    }
+                }
+
                 let mut imported_module_thunks = bumpalo::collections::Vec::new_in(arena);
 
                 if let Some(imports) = state.module_cache.imports.get(&module_id) {
@@ -614,6 +631,7 @@ fn start_phase<'a>(
                     exposed_by_module: state.exposed_types.clone(),
                     derived_module,
                     expectations,
+                    track_symbol_regions: state.mono_diagnostics.tracks_symbol_regions(),
                 }
             }
         }
@@ -762,6 +780,10 @@ pub struct MonomorphizedModule<'a> {
     pub output_path: Box<Path>,
     pub can_problems: MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     pub type_problems: MutMap<ModuleId, Vec<TypeError>>,
+    /// Missed in-place updates. These are only reported on request.
+    pub mono_problems: MutMap<ModuleId, Vec<roc_problem::mono::Problem>>,
+    /// Empty unless [`MonoDiagnostics`] asked for them
+    pub symbol_regions: SymbolRegions,
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub toplevel_expects: ToplevelExpects,
     pub entry_point: EntryPoint<'a>,
@@ -892,6 +914,7 @@ enum Msg<'a> {
         external_specializations_requested: BumpMap<ModuleId, ExternalSpecializations<'a>>,
         procs_base: ProcsBase<'a>,
         procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+        symbol_regions: SymbolRegions,
         update_mode_ids: UpdateModeIds,
         module_timing: ModuleTiming,
         subs: Subs,
@@ -988,6 +1011,8 @@ struct State<'a> {
     pub procedures: MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
     pub toplevel_expects: ToplevelExpects,
     pub exposed_to_host: ExposedToHost,
+    pub symbol_regions: SymbolRegions,
+    pub mono_problems: MutMap<ModuleId, Vec<roc_problem::mono::Problem>>,
 
     /// This is the "final" list of IdentIds, after canonicalization and constraint gen
     /// have completed for a given module.
@@ -1016,6 +1041,8 @@ struct State<'a> {
     pub render: RenderTarget,
     pub palette: Palette,
     pub exec_mode: ExecutionMode,
+    pub mono_optimizations: MonoOptimizations,
+    pub mono_diagnostics: MonoDiagnostics,
 
     /// All abilities across all modules.
     pub world_abilities: WorldAbilities,
@@ -1047,6 +1074,8 @@ impl<'a> State<'a> {
         palette: Palette,
         number_of_workers: usize,
         exec_mode: ExecutionMode,
+        mono_optimizations: MonoOptimizations,
+        mono_diagnostics: MonoDiagnostics,
     ) -> Self {
         let arc_shorthands = Arc::new(Mutex::new(MutMap::default()));
         let cache_dir = roc_packaging::cache::roc_cache_dir();
@@ -1066,6 +1095,8 @@ impl<'a> State<'a> {
             procedures: MutMap::default(),
             toplevel_expects: ToplevelExpects::default(),
             exposed_to_host: ExposedToHost::default(),
+            symbol_regions: SymbolRegions::default(),
+            mono_problems: MutMap::default(),
             exposed_modules: &[],
             exposed_types,
             arc_modules,
@@ -1081,6 +1112,8 @@ impl<'a> State<'a> {
             render,
             palette,
             exec_mode,
+            mono_optimizations,
+            mono_diagnostics,
             make_specializations_pass: MakeSpecializationsPass::Pass(1),
             world_abilities: Default::default(),
             layout_interner: GlobalLayoutInterner::with_capacity(128, target_info),
@@ -1268,6 +1301,7 @@ enum BuildTask<'a> {
         world_abilities: WorldAbilities,
         derived_module: SharedDerivedModule,
         expectations: Option<Expectations>,
+        track_symbol_regions: bool,
     },
 }
 
@@ -1357,6 +1391,8 @@ pub fn load_and_typecheck_str<'a>(
         palette,
         threading,
         exec_mode: ExecutionMode::Check,
+        mono_optimizations: MonoOptimizations::Disabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
 
     match load(
@@ -1616,6 +1652,8 @@ pub fn load<'a>(
             load_config.render,
             load_config.palette,
             load_config.exec_mode,
+            load_config.mono_optimizations,
+            load_config.mono_diagnostics,
             roc_cache_dir,
         ),
         Threads::Many(threads) => load_multi_threaded(
@@ -1628,6 +1666,8 @@ pub fn load<'a>(
             load_config.palette,
             threads,
             load_config.exec_mode,
+            load_config.mono_optimizations,
+            load_config.mono_diagnostics,
             roc_cache_dir,
         ),
     }
@@ -1643,6 +1683,8 @@ pub fn load_single_threaded<'a>(
     render: RenderTarget,
     palette: Palette,
     exec_mode: ExecutionMode,
+    mono_optimizations: MonoOptimizations,
+    mono_diagnostics: MonoDiagnostics,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -1674,6 +1716,8 @@ pub fn load_single_threaded<'a>(
         palette,
         number_of_workers,
         exec_mode,
+        mono_optimizations,
+        mono_diagnostics,
     );
 
     // We'll add tasks to this, and then worker threads will take tasks from it.
@@ -1958,6 +2002,8 @@ fn load_multi_threaded<'a>(
     palette: Palette,
     available_threads: usize,
     exec_mode: ExecutionMode,
+    mono_optimizations: MonoOptimizations,
+    mono_diagnostics: MonoDiagnostics,
     roc_cache_dir: RocCacheDir<'_>,
 ) -> Result<LoadResult<'a>, LoadingProblem<'a>> {
     let LoadStart {
@@ -2004,6 +2050,8 @@ fn load_multi_threaded<'a>(
         palette,
         num_workers,
         exec_mode,
+        mono_optimizations,
+        mono_diagnostics,
     );
 
     // an arena for every worker, stored in an arena-allocated bumpalo vec to make the lifetimes work
@@ -2326,9 +2374,9 @@ macro_rules! debug_print_ir {
 }
 
 macro_rules! debug_check_ir {
-    ($state:expr, $arena:expr, $interner:expr, $flag:path) => {
+    ($state:expr, $arena:expr, $interner:expr, $flag:path, $check:ident) => {
         dbg_do!($flag, {
-            use roc_mono::debug::{check_procs, format_problems};
+            use roc_mono::debug::{format_problems, $check};
 
             let interns = Interns {
                 module_ids: $state.arc_modules.lock().clone().into_module_ids(),
@@ -2337,7 +2385,7 @@ macro_rules! debug_check_ir {
 
             let procedures = &$state.procedures;
 
-            let problems = check_procs($arena, &mut $interner, procedures);
+            let problems = $check($arena, &mut $interner, procedures);
             if !problems.is_empty() {
                 let formatted = format_problems(&interns, &$interner, problems);
                 eprintln!("IR PROBLEMS FOUND:\n{formatted}");
@@ -2968,6 +3016,7 @@ fn update<'a>(
             subs,
             procs_base,
             procedures,
+            symbol_regions,
             external_specializations_requested,
             module_timing,
             layout_cache,
@@ -2985,6 +3034,7 @@ fn update<'a>(
             let _ = layout_cache;
 
             state.procedures.extend(procedures);
+            state.symbol_regions.extend(symbol_regions);
             state.module_cache.late_specializations.insert(
                 module_id,
                 LateSpecializationsModule {
@@ -3098,10 +3148,40 @@ fn update<'a>(
                     log!("specializations complete from {:?}", module_id);
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_SPECIALIZATION);
-                    debug_check_ir!(state, arena, layout_interner, ROC_CHECK_MONO_IR);
+                    debug_check_ir!(
+                        state,
+                        arena,
+                        layout_interner,
+                        ROC_CHECK_MONO_IR,
+                        check_procs
+                    );
 
                     let ident_ids = state.constrained_ident_ids.get_mut(&module_id).unwrap();
 
+                    if let MonoOptimizations::Enabled = state.mono_optimizations {
+                        let entry_points: std::vec::Vec<Symbol> = state
+                            .exposed_to_host
+                            .top_level_values
+                            .keys()
+                            .chain(state.toplevel_expects.pure.keys())
+                            .chain(state.toplevel_expects.fx.keys())
+                            .copied()
+                            .collect();
+
+                        inline::inline_procs(
+                            arena,
+                            module_id,
+                            ident_ids,
+                            &mut update_mode_ids,
+                            &entry_points,
+                            &mut state.procedures,
+                        );
+
+                        debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_INLINING);
+
+                        simplify::simplify_procs(arena, &mut state.procedures);
+                    }
+
                     inc_dec::insert_inc_dec_operations(
                         arena,
                         &layout_interner,
@@ -3121,6 +3201,14 @@ fn update<'a>(
 
                     debug_print_ir!(state, &layout_interner, ROC_PRINT_IR_AFTER_RESET_REUSE);
 
+                    if state.mono_diagnostics.missed_updates {
+                        state.mono_problems = missed_updates::find_missed_updates(
+                            &layout_interner,
+                            &state.procedures,
+                            &state.symbol_regions,
+                        );
+                    }
+
                     drop_specialization::specialize_drops(
                         arena,
                         &mut layout_interner,
@@ -3135,6 +3223,13 @@ fn update<'a>(
                         &layout_interner,
                         ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION
                     );
+                    debug_check_ir!(
+                        state,
+                        arena,
+                        layout_interner,
+                        ROC_CHECK_MONO_IR,
+                        check_refcounts
+                    );
 
                     // This is not safe with the new non-recursive RC updates that we do for tag unions
                     //
@@ -3390,6 +3485,8 @@ fn finish_specialization<'a>(
     let State {
         toplevel_expects,
         procedures,
+        mono_problems,
+        symbol_regions,
         module_cache,
         output_path,
         platform_data,
@@ -3472,6 +3569,8 @@ fn finish_specialization<'a>(
     Ok(MonomorphizedModule {
         can_problems,
         type_problems,
+        mono_problems,
+        symbol_regions,
         output_path,
         expectations: module_expectations,
         exposed_to_host,
@@ -5765,9 +5864,11 @@ fn make_specializations<'a>(
     exposed_by_module: &ExposedByModule,
     derived_module: SharedDerivedModule,
     mut expectations: Option<Expectations>,
+    track_symbol_regions: bool,
 ) -> Msg<'a> {
     let make_specializations_start = Instant::now();
     let mut update_mode_ids = UpdateModeIds::new();
+    let mut symbol_regions = SymbolRegions::default();
     // do the thing
     let mut mono_env = roc_mono::ir::Env {
         arena,
@@ -5782,6 +5883,11 @@ fn make_specializations<'a>(
         abilities: AbilitiesView::World(&world_abilities),
         exposed_by_module,
         derived_module: &derived_module,
+        symbol_regions: if track_symbol_regions {
+            Some(&mut symbol_regions)
+        } else {
+            None
+        },
     };
 
     let mut procs = Procs::new_in(arena);
@@ -5810,7 +5916,8 @@ fn make_specializations<'a>(
     );
 
     let external_specializations_requested = procs.externals_we_need.clone();
-    let (procedures, restored_procs_base) = procs.get_specialized_procs_without_rc(&mut mono_env);
+    let (procedures, restored_procs_base) =
+        procs.get_specialized_procs_without_rc(&mut mono_env, &mut layout_cache);
 
     // Turn `Bytes.Decode.IdentId(238)` into `Bytes.Decode.238`, we rely on this in mono tests
     mono_env.home.register_debug_idents(mono_env.ident_ids);
@@ -5826,6 +5933,7 @@ fn make_specializations<'a>(
         layout_cache,
         procs_base: restored_procs_base,
         procedures,
+        symbol_regions,
         update_mode_ids,
         subs,
         expectations,
@@ -5882,6 +5990,8 @@ fn build_pending_specializations<'a>(
         abilities: AbilitiesView::Module(&abilities_store),
         exposed_by_module,
         derived_module: &derived_module,
+        // the bodies of procs are lowered when making specializations, not here
+        symbol_regions: None,
     };
 
     let layout_cache_snapshot = layout_cache.snapshot();
@@ -6363,6 +6473,7 @@ fn load_derived_partial_procs<'a>(
             abilities: AbilitiesView::World(world_abilities),
             exposed_by_module,
             derived_module,
+            symbol_regions: None,
         };
 
         let partial_proc = match derived_expr {
@@ -6543,6 +6654,7 @@ fn run_task<'a>(
             exposed_by_module,
             derived_module,
             expectations,
+            track_symbol_regions,
         } => Ok(make_specializations(
             arena,
             module_id,
@@ -6557,6 +6669,7 @@ fn run_task<'a>(
             &exposed_by_module,
             derived_module,
             expectations,
+            track_symbol_regions,
         )),
     }?;
 
@@ -1,131 +1,148 @@
 use crate::symbol::Symbol;
 
-/// Low-level operations that get translated directly into e.g. LLVM instructions.
-/// These are always wrapped when exposed to end users, and can only make it
-/// into an Expr when added directly by can::builtins
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum LowLevel {
-    StrConcat,
-    StrJoinWith,
-    StrIsEmpty,
-    StrStartsWith,
-    StrStartsWithScalar,
-    StrEndsWith,
-    StrSplit,
-    StrCountGraphemes,
-    StrCountUtf8Bytes,
-    StrFromInt,
-    StrFromUtf8Range,
-    StrToUtf8,
-    StrRepeat,
-    StrFromFloat,
-    StrTrim,
-    StrTrimLeft,
-    StrTrimRight,
-    StrToNum,
-    StrToScalars,
-    StrGetUnsafe,
-    StrSubstringUnsafe,
-    StrReserve,
-    StrAppendScalar,
-    StrGetScalarUnsafe,
-    StrGetCapacity,
-    StrWithCapacity,
-    StrGraphemes,
-    StrReleaseExcessCapacity,
-    ListLen,
-    ListWithCapacity,
-    ListReserve,
-    ListReleaseExcessCapacity,
-    ListAppendUnsafe,
-    ListGetUnsafe,
-    ListReplaceUnsafe,
-    ListConcat,
-    ListPrepend,
-    ListMap,
-    ListMap2,
-    ListMap3,
-    ListMap4,
-    ListSortWith,
-    ListSublist,
-    ListDropAt,
-    ListSwap,
-    ListIsUnique,
-    ListGetCapacity,
-    NumAdd,
-    NumAddWrap,
-    NumAddChecked,
-    NumAddSaturated,
-    NumSub,
-    NumSubWrap,
-    NumSubChecked,
-    NumSubSaturated,
-    NumMul,
-    NumMulWrap,
-    NumMulSaturated,
-    NumMulChecked,
-    NumGt,
-    NumGte,
-    NumLt,
-    NumLte,
-    NumCompare,
-    NumDivFrac,
-    NumDivTruncUnchecked,
-    NumDivCeilUnchecked,
-    NumRemUnchecked,
-    NumIsMultipleOf,
-    NumAbs,
-    NumNeg,
-    NumSin,
-    NumCos,
-    NumSqrtUnchecked,
-    NumLogUnchecked,
-    NumRound,
-    NumToFrac,
-    NumPow,
-    NumCeiling,
-    NumPowInt,
-    NumFloor,
-    NumIsNan,
-    NumIsInfinite,
-    NumIsFinite,
-    NumAtan,
-    NumAcos,
-    NumAsin,
-    NumBytesToU16,
-    NumBytesToU32,
-    NumBytesToU64,
-    NumBytesToU128,
-    NumBitwiseAnd,
-    NumBitwiseXor,
-    NumBitwiseOr,
-    NumShiftLeftBy,
-    NumShiftRightBy,
-    NumShiftRightZfBy,
-    NumIntCast,
-    NumToFloatCast,
-    NumToIntChecked,
-    NumToFloatChecked,
-    NumToStr,
-    NumCountLeadingZeroBits,
-    NumCountTrailingZeroBits,
-    NumCountOneBits,
-    Eq,
-    NotEq,
-    And,
-    Or,
-    Not,
-    Hash,
-    PtrCast,
-    PtrWrite,
-    RefCountIncRcPtr,
-    RefCountDecRcPtr,
-    RefCountIncDataPtr,
-    RefCountDecDataPtr,
-    RefCountIsUnique,
-    BoxExpr,
-    UnboxExpr,
-    Unreachable,
+macro_rules! define_lowlevels {
+    ($(#[$attr:meta])* pub enum LowLevel { $($variant:ident,)* }) => {
+        $(#[$attr])*
+        pub enum LowLevel {
+            $($variant,)*
+        }
+
+        impl LowLevel {
+            /// Every lowlevel, in declaration order
+            pub const ALL: &'static [LowLevel] = &[$(LowLevel::$variant,)*];
+        }
+    };
+}
+
+define_lowlevels! {
+    /// Low-level operations that get translated directly into e.g. LLVM instructions.
+    /// These are always wrapped when exposed to end users, and can only make it
+    /// into an Expr when added directly by can::builtins
+    #[derive(Debug, Copy, Clone, PartialEq, Eq)]
+    pub enum LowLevel {
+        StrConcat,
+        StrJoinWith,
+        StrIsEmpty,
+        StrStartsWith,
+        StrStartsWithScalar,
+        StrEndsWith,
+        StrSplit,
+        StrCountGraphemes,
+        StrCountUtf8Bytes,
+        StrFromInt,
+        StrFromUtf8Range,
+        StrToUtf8,
+        StrRepeat,
+        StrFromFloat,
+        StrTrim,
+        StrTrimLeft,
+        StrTrimRight,
+        StrToNum,
+        StrToScalars,
+        StrGetUnsafe,
+        StrSubstringUnsafe,
+        StrReserve,
+        StrAppendScalar,
+        StrGetScalarUnsafe,
+        StrGetCapacity,
+        StrWithCapacity,
+        StrGraphemes,
+        StrReleaseExcessCapacity,
+        ListLen,
+        ListWithCapacity,
+        ListReserve,
+        ListReleaseExcessCapacity,
+        ListAppendUnsafe,
+        ListGetUnsafe,
+        ListReplaceUnsafe,
+        ListConcat,
+        ListPrepend,
+        ListMap,
+        ListMap2,
+        ListMap3,
+        ListMap4,
+        ListSortWith,
+        ListSublist,
+        ListDropAt,
+        ListSwap,
+        ListIsUnique,
+        ListGetCapacity,
+        NumAdd,
+        NumAddWrap,
+        NumAddChecked,
+        NumAddSaturated,
+        NumSub,
+        NumSubWrap,
+        NumSubChecked,
+        NumSubSaturated,
+        NumMul,
+        NumMulWrap,
+        NumMulSaturated,
+        NumMulChecked,
+        NumGt,
+        NumGte,
+        NumLt,
+        NumLte,
+        NumCompare,
+        NumDivFrac,
+        NumDivTruncUnchecked,
+        NumDivCeilUnchecked,
+        NumRemUnchecked,
+        NumIsMultipleOf,
+        NumAbs,
+        NumNeg,
+        NumSin,
+        NumCos,
+        NumSqrtUnchecked,
+        NumLogUnchecked,
+        NumRound,
+        NumToFrac,
+        NumPow,
+        NumCeiling,
+        NumPowInt,
+        NumFloor,
+        NumIsNan,
+        NumIsInfinite,
+        NumIsFinite,
+        NumAtan,
+        NumAcos,
+        NumAsin,
+        NumBytesToU16,
+        NumBytesToU32,
+        NumBytesToU64,
+        NumBytesToU128,
+        NumBitwiseAnd,
+        NumBitwiseXor,
+        NumBitwiseOr,
+        NumShiftLeftBy,
+        NumShiftRightBy,
+        NumShiftRightZfBy,
+        NumIntCast,
+        NumToFloatCast,
+        NumToIntChecked,
+        NumToFloatChecked,
+        NumToStr,
+        NumCountLeadingZeroBits,
+        NumCountTrailingZeroBits,
+        NumCountOneBits,
+        Eq,
+        NotEq,
+        And,
+        Or,
+        Not,
+        Hash,
+        PtrCast,
+        PtrWrite,
+        PtrStore,
+        RefCountIncRcPtr,
+        RefCountDecRcPtr,
+        RefCountIncDataPtr,
+        RefCountDecDataPtr,
+        RefCountIsUnique,
+        BoxExpr,
+        UnboxExpr,
+        Unreachable,
+    }
 }
 
 macro_rules! higher_order {
@@ -227,6 +244,7 @@ macro_rules! map_symbol_to_lowlevel {
                 LowLevel::Hash => unimplemented!(),
                 LowLevel::PtrCast => unimplemented!(),
                 LowLevel::PtrWrite => unimplemented!(),
+                LowLevel::PtrStore => unimplemented!(),
                 LowLevel::RefCountIncRcPtr => unimplemented!(),
                 LowLevel::RefCountDecRcPtr=> unimplemented!(),
                 LowLevel::RefCountIncDataPtr => unimplemented!(),
@@ -741,7 +741,7 @@ impl<'a> BorrowInfState<'a> {
                 self.if_is_owned_then_own(z, *x);
             }
 
-            GetTagId { structure: x, .. } => {
+            GetTagId { structure: x, .. } | UnionFieldPtrAtIndex { structure: x, .. } => {
                 // if the structure (record/tag/array) is owned, the extracted value is
                 self.if_is_owned_then_own(*x, z);
 
@@ -1029,6 +1029,8 @@ pub fn lowlevel_borrow_signature(arena: &Bump, op: LowLevel) -> &[Ownership] {
 
         ListIsUnique => arena.alloc_slice_copy(&[borrowed]),
 
+        PtrStore => arena.alloc_slice_copy(&[irrelevant, owned]),
+
         BoxExpr | UnboxExpr => {
             unreachable!("These lowlevel operations are turned into mono Expr's")
         }
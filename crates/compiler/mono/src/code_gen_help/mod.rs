@@ -12,6 +12,7 @@ use crate::ir::{
 use crate::layout::{
     Builtin, InLayout, LambdaName, Layout, LayoutInterner, Niche, STLayoutInterner, UnionLayout,
 };
+use crate::low_level::HigherOrder;
 
 mod equality;
 mod refcount;
@@ -23,6 +24,16 @@ const ARG_1: Symbol = Symbol::ARG_1;
 const ARG_2: Symbol = Symbol::ARG_2;
 const ARG_3: Symbol = Symbol::ARG_3;
 
+/// Arguments of a `CallerProc`: the captures, up to four elements, and the output pointer
+const CALLER_PROC_ARGS: [Symbol; 6] = [
+    Symbol::ARG_1,
+    Symbol::ARG_2,
+    Symbol::ARG_3,
+    Symbol::ARG_4,
+    Symbol::ARG_5,
+    Symbol::ARG_6,
+];
+
 /// "Infinite" reference count, for static values
 /// Ref counts are encoded as negative numbers where isize::MIN represents 1
 pub const REFCOUNT_MAX: usize = 0;
@@ -417,6 +428,7 @@ impl<'a> CodeGenHelp<'a> {
             ret_layout,
             is_self_recursive: SelfRecursive::NotSelfRecursive,
             host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+            stack_allocations: &[],
         });
 
         proc_symbol
@@ -602,18 +614,24 @@ impl<'a> CallerProc<'a> {
         Self::create_symbol(home, ident_ids, &debug_name)
     }
 
+    /// Wrap the function passed to a higher-order builtin in a proc that Zig can call.
+    /// The captures and the elements are passed by pointer. Mappers write their result
+    /// through an extra pointer argument, while comparators return the `Ordering` directly.
     pub fn new(
         arena: &'a Bump,
         home: ModuleId,
         ident_ids: &mut IdentIds,
         layout_interner: &mut STLayoutInterner<'a>,
+        op: &HigherOrder,
         passed_function: &PassedFunction<'a>,
         capture_layout: Option<InLayout<'a>>,
     ) -> Self {
-        let mut ctx = Context {
-            new_linker_data: Vec::new_in(arena),
-            recursive_union: None,
-            op: HelperOp::Eq,
+        let (operation, returns_via_pointer) = match op {
+            HigherOrder::ListSortWith { .. } => ("compare", false),
+            HigherOrder::ListMap { .. }
+            | HigherOrder::ListMap2 { .. }
+            | HigherOrder::ListMap3 { .. }
+            | HigherOrder::ListMap4 { .. } => ("map", true),
         };
 
         let box_capture_layout = if let Some(capture_layout) = capture_layout {
@@ -622,36 +640,53 @@ impl<'a> CallerProc<'a> {
             layout_interner.insert(Layout::Boxed(Layout::UNIT))
         };
 
-        let box_argument_layout =
-            layout_interner.insert(Layout::Boxed(passed_function.argument_layouts[0]));
+        // the layout of the captures (if any) is the last of the argument layouts
+        let element_layouts = &passed_function.argument_layouts[..op.function_arity()];
 
         let box_return_layout =
             layout_interner.insert(Layout::Boxed(passed_function.return_layout));
 
+        let mut argument_layouts = Vec::with_capacity_in(element_layouts.len() + 2, arena);
+        argument_layouts.push(box_capture_layout);
+        for element_layout in element_layouts {
+            argument_layouts.push(layout_interner.insert(Layout::Boxed(*element_layout)));
+        }
+        if returns_via_pointer {
+            argument_layouts.push(box_return_layout);
+        }
+        let argument_layouts = argument_layouts.into_bump_slice();
+
         let proc_layout = ProcLayout {
-            arguments: arena.alloc([box_capture_layout, box_argument_layout, box_return_layout]),
-            result: Layout::UNIT,
+            arguments: argument_layouts,
+            result: if returns_via_pointer {
+                Layout::UNIT
+            } else {
+                passed_function.return_layout
+            },
             niche: Niche::NONE,
         };
 
-        let proc_symbol =
-            Self::create_caller_proc_symbol(home, ident_ids, "map", passed_function.name.name());
-
-        ctx.new_linker_data.push((proc_symbol, proc_layout));
-
-        let unbox_capture = Expr::ExprUnbox {
-            symbol: Symbol::ARG_1,
-        };
+        let proc_symbol = Self::create_caller_proc_symbol(
+            home,
+            ident_ids,
+            operation,
+            passed_function.name.name(),
+        );
 
-        let unbox_argument = Expr::ExprUnbox {
-            symbol: Symbol::ARG_2,
-        };
+        let arg_symbols = &CALLER_PROC_ARGS[..argument_layouts.len()];
 
         let unboxed_capture = Self::create_symbol(home, ident_ids, "unboxed_capture");
-        let unboxed_argument = Self::create_symbol(home, ident_ids, "unboxed_argument");
+        let unboxed_arguments = Vec::from_iter_in(
+            (0..element_layouts.len())
+                .map(|i| Self::create_symbol(home, ident_ids, &format!("unboxed_argument_{}", i))),
+            arena,
+        );
         let call_result = Self::create_symbol(home, ident_ids, "call_result");
-        let unit_symbol = Self::create_symbol(home, ident_ids, "unit_symbol");
-        let ignored = Self::create_symbol(home, ident_ids, "ignored");
+
+        let mut call_arguments = Vec::from_iter_in(unboxed_arguments.iter().copied(), arena);
+        if capture_layout.is_some() {
+            call_arguments.push(unboxed_capture);
+        }
 
         let call = Expr::Call(Call {
             call_type: CallType::ByName {
@@ -660,68 +695,83 @@ impl<'a> CallerProc<'a> {
                 arg_layouts: passed_function.argument_layouts,
                 specialization_id: passed_function.specialization_id,
             },
-            arguments: if capture_layout.is_some() {
-                arena.alloc([unboxed_argument, unboxed_capture])
-            } else {
-                arena.alloc([unboxed_argument])
-            },
+            arguments: call_arguments.into_bump_slice(),
         });
 
-        let ptr_write = Expr::Call(Call {
-            call_type: CallType::LowLevel {
-                op: LowLevel::PtrWrite,
-                update_mode: UpdateModeId::BACKEND_DUMMY,
-            },
-            arguments: arena.alloc([Symbol::ARG_3, call_result]),
-        });
+        let after_call = if returns_via_pointer {
+            let unit_symbol = Self::create_symbol(home, ident_ids, "unit_symbol");
+            let ignored = Self::create_symbol(home, ident_ids, "ignored");
 
-        let mut body = Stmt::Let(
-            unboxed_argument,
-            unbox_argument,
-            passed_function.argument_layouts[0],
-            arena.alloc(Stmt::Let(
-                call_result,
-                call,
-                passed_function.return_layout,
+            let ptr_write = Expr::Call(Call {
+                call_type: CallType::LowLevel {
+                    op: LowLevel::PtrWrite,
+                    update_mode: UpdateModeId::BACKEND_DUMMY,
+                },
+                arguments: arena.alloc([arg_symbols[arg_symbols.len() - 1], call_result]),
+            });
+
+            Stmt::Let(
+                ignored,
+                ptr_write,
+                box_return_layout,
                 arena.alloc(Stmt::Let(
-                    ignored,
-                    ptr_write,
-                    box_return_layout,
-                    arena.alloc(Stmt::Let(
-                        unit_symbol,
-                        Expr::Struct(&[]),
-                        Layout::UNIT,
-                        arena.alloc(Stmt::Ret(unit_symbol)),
-                    )),
+                    unit_symbol,
+                    Expr::Struct(&[]),
+                    Layout::UNIT,
+                    arena.alloc(Stmt::Ret(unit_symbol)),
                 )),
-            )),
+            )
+        } else {
+            Stmt::Ret(call_result)
+        };
+
+        let mut body = Stmt::Let(
+            call_result,
+            call,
+            passed_function.return_layout,
+            arena.alloc(after_call),
         );
 
+        for (i, element_layout) in element_layouts.iter().enumerate().rev() {
+            body = Stmt::Let(
+                unboxed_arguments[i],
+                Expr::ExprUnbox {
+                    symbol: arg_symbols[i + 1],
+                },
+                *element_layout,
+                arena.alloc(body),
+            );
+        }
+
         if let Some(capture_layout) = capture_layout {
             body = Stmt::Let(
                 unboxed_capture,
-                unbox_capture,
+                Expr::ExprUnbox {
+                    symbol: arg_symbols[0],
+                },
                 capture_layout,
                 arena.alloc(body),
             );
         }
 
-        let args: &'a [(InLayout<'a>, Symbol)] = {
-            arena.alloc([
-                (box_capture_layout, ARG_1),
-                (box_argument_layout, ARG_2),
-                (box_return_layout, ARG_3),
-            ])
-        };
+        let args = Vec::from_iter_in(
+            argument_layouts
+                .iter()
+                .copied()
+                .zip(arg_symbols.iter().copied()),
+            arena,
+        )
+        .into_bump_slice();
 
         let proc = Proc {
             name: LambdaName::no_niche(proc_symbol),
             args,
             body,
             closure_data_layout: None,
-            ret_layout: Layout::UNIT,
+            ret_layout: proc_layout.result,
             is_self_recursive: SelfRecursive::NotSelfRecursive,
             host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+            stack_allocations: &[],
         };
 
         if false {
@@ -746,6 +796,72 @@ impl<'a> CallerProc<'a> {
             proc,
         }
     }
+
+    /// Wrap the `Dec` refcount proc for `layout` in a proc that takes its argument by pointer,
+    /// so that Zig can use it to decrement elements in place (e.g. the tail of a list in `map2`).
+    /// Callers should only keep the first wrapper created for a given `proc_symbol`.
+    pub fn new_dec_wrapper(
+        arena: &'a Bump,
+        home: ModuleId,
+        ident_ids: &mut IdentIds,
+        layout_interner: &mut STLayoutInterner<'a>,
+        layout: InLayout<'a>,
+        dec_proc_symbol: Symbol,
+    ) -> Self {
+        let box_layout = layout_interner.insert(Layout::Boxed(layout));
+
+        let proc_layout = ProcLayout {
+            arguments: arena.alloc([box_layout]),
+            result: Layout::UNIT,
+            niche: Niche::NONE,
+        };
+
+        // There is one wrapper per `Dec` proc, so reuse the symbol if it was created before
+        let debug_name = format!("#help_{}_{}_{:?}", "caller", "dec", dec_proc_symbol);
+        let proc_symbol = Symbol::new(home, ident_ids.get_or_insert(&debug_name));
+
+        let unboxed = Self::create_symbol(home, ident_ids, "unboxed");
+        let call_result = Self::create_symbol(home, ident_ids, "call_result");
+
+        let call = Expr::Call(Call {
+            call_type: CallType::ByName {
+                name: LambdaName::no_niche(dec_proc_symbol),
+                ret_layout: Layout::UNIT,
+                arg_layouts: arena.alloc([layout]),
+                specialization_id: CallSpecId::BACKEND_DUMMY,
+            },
+            arguments: arena.alloc([unboxed]),
+        });
+
+        let body = Stmt::Let(
+            unboxed,
+            Expr::ExprUnbox { symbol: ARG_1 },
+            layout,
+            arena.alloc(Stmt::Let(
+                call_result,
+                call,
+                Layout::UNIT,
+                arena.alloc(Stmt::Ret(call_result)),
+            )),
+        );
+
+        let proc = Proc {
+            name: LambdaName::no_niche(proc_symbol),
+            args: arena.alloc([(box_layout, ARG_1)]),
+            body,
+            closure_data_layout: None,
+            ret_layout: Layout::UNIT,
+            is_self_recursive: SelfRecursive::NotSelfRecursive,
+            host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+            stack_allocations: &[],
+        };
+
+        Self {
+            proc_symbol,
+            proc_layout,
+            proc,
+        }
+    }
 }
 
 fn let_lowlevel<'a>(
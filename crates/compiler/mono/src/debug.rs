@@ -1,5 +1,7 @@
 mod checker;
+mod refcount;
 mod report;
 
-pub use checker::{check_procs, Problem, Problems};
+pub use checker::{check_procs, Problem, ProblemKind, Problems, RefcountExit};
+pub use refcount::check_refcounts;
 pub use report::format_problems;
@@ -28,6 +28,14 @@ pub enum UseKind {
     ExpectLookup,
 }
 
+/// How a control path ends, when it is checked for balanced reference counts
+pub enum RefcountExit {
+    Return,
+    /// A jump back to a join point whose body the path is in. The reference counts must be the
+    /// same as when the body was entered, or they would grow or shrink with every iteration.
+    LoopIteration(JoinPointId),
+}
+
 pub enum ProblemKind<'a> {
     RedefinedSymbol {
         symbol: Symbol,
@@ -113,6 +121,19 @@ pub enum ProblemKind<'a> {
         num_needed: usize,
         num_given: usize,
     },
+    RefcountImbalance {
+        symbol: Symbol,
+        /// The field of `symbol` that is out of balance, if any
+        fields: Vec<u64>,
+        def_line: usize,
+        /// The number of references that are left at the exit; negative if too many were consumed
+        imbalance: i64,
+        exit: RefcountExit,
+    },
+    UseAfterConsume {
+        symbol: Symbol,
+        def_line: usize,
+    },
 }
 
 pub struct Problem<'a> {
@@ -129,6 +150,10 @@ impl<'a> Problems<'a> {
     pub fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Problem<'a>> {
+        self.0.iter()
+    }
 }
 
 pub fn check_procs<'a>(
@@ -426,6 +451,17 @@ impl<'a, 'r> Ctx<'a, 'r> {
             } => self.with_sym_layout(structure, |ctx, _def_line, layout| {
                 ctx.check_union_at_index(structure, layout, union_layout, tag_id, index)
             }),
+            &Expr::UnionFieldPtrAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => {
+                self.with_sym_layout(structure, |ctx, _def_line, layout| {
+                    ctx.check_union_at_index(structure, layout, union_layout, tag_id, index)
+                });
+                Some(Layout::usize(self.interner.target_info()))
+            }
             Expr::Array { elem_layout, elems } => {
                 for elem in elems.iter() {
                     match elem {
@@ -653,12 +689,12 @@ impl<'a, 'r> Ctx<'a, 'r> {
     }
 }
 
-enum TagPayloads<'a> {
+pub(super) enum TagPayloads<'a> {
     IdNotInUnion,
     Payloads(&'a [InLayout<'a>]),
 }
 
-fn get_tag_id_payloads(union_layout: UnionLayout, tag_id: TagIdIntType) -> TagPayloads {
+pub(super) fn get_tag_id_payloads(union_layout: UnionLayout, tag_id: TagIdIntType) -> TagPayloads {
     macro_rules! check_tag_id_oob {
         ($len:expr) => {
             if tag_id as usize >= $len {
@@ -0,0 +1,774 @@
+//! Checks that the reference counting operations in the [ir][crate::ir::Proc] are balanced.
+//!
+//! Every control path through a proc is interpreted abstractly, counting the owned references
+//! to every refcounted value. When the proc returns, every value must have been consumed
+//! exactly once: by a `dec`, by passing it to something that takes ownership, or by returning
+//! it. The ownership rules are the ones that `inc_dec` uses to insert the operations.
+//!
+//! A few operations need more than a single counter per symbol:
+//!
+//! - Indexing into a value (`StructAtIndex`, `UnionAtIndex`, `ExprUnbox`) gives a borrowed
+//!   reference to the field, so the field is tracked as a place of its own, shared by every
+//!   symbol that indexes the same field.
+//! - Owning a struct (or a non-recursive union with a known tag) is the same as owning each of
+//!   its fields. Drop specialization relies on this by replacing `dec struct` with a `dec` of
+//!   each field.
+//! - A `decref` of a value that is known to be unique (on the `true` branch of a
+//!   `RefCountIsUnique`) frees the allocation, and hands its references to the fields to us.
+//!
+//! Besides the balance at the end of every path, a value must not be used after the last
+//! reference that the proc owned has been consumed, because it may have been freed by then.
+
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::{LowLevel, LowLevelWrapperType};
+use roc_module::symbol::Symbol;
+
+use crate::{
+    borrow::lowlevel_borrow_signature,
+    ir::{
+        BranchInfo, Call, CallType, Expr, JoinPointId, ListLiteralElement, ModifyRc, Param, Proc,
+        ProcLayout, Stmt,
+    },
+    layout::{InLayout, Layout, LayoutInterner, STLayoutInterner, TagIdIntType, UnionLayout},
+    low_level::HigherOrder,
+};
+
+use super::checker::{
+    get_tag_id_payloads, Problem, ProblemKind, Problems, RefcountExit, TagPayloads,
+};
+
+/// Join points are checked once for every distinct state they are jumped to with, which in
+/// pathological cases is exponential in the number of join points. Give up on a proc after
+/// this many join point bodies.
+const MAX_JOIN_POINT_VISITS: usize = 10_000;
+
+type Procs<'a> = MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>;
+
+/// Check that along every path through every proc, every owned reference is consumed exactly
+/// once. This only makes sense after reference counting operations have been inserted.
+pub fn check_refcounts<'a>(
+    arena: &'a Bump,
+    interner: &mut STLayoutInterner<'a>,
+    procs: &Procs<'a>,
+) -> Problems<'a> {
+    let mut problems = Default::default();
+
+    for ((symbol, proc_layout), proc) in procs.iter() {
+        // Refcounting is not performed in lowlevel wrappers, their callers do it instead
+        if !matches!(
+            LowLevelWrapperType::from_symbol(*symbol),
+            LowLevelWrapperType::NotALowLevelWrapper
+        ) {
+            continue;
+        }
+
+        let mut ctx = Ctx {
+            arena,
+            interner,
+            proc,
+            proc_layout: *proc_layout,
+            problems: &mut problems,
+            lines: Default::default(),
+            def_lines: Default::default(),
+            joinpoints: Default::default(),
+            active_joinpoints: Default::default(),
+            visited_joinpoints: Default::default(),
+            reported: Default::default(),
+            gave_up: false,
+        };
+        ctx.check_proc(proc);
+    }
+
+    Problems(problems)
+}
+
+/// A refcounted value: the value of a symbol, or a field (of a field...) of one
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Place {
+    root: Symbol,
+    fields: Vec<u64>,
+}
+
+impl Place {
+    fn symbol(symbol: Symbol) -> Self {
+        Place {
+            root: symbol,
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// The owned reference counts of all places with a non-zero count, in a canonical order
+type Counts = Vec<(Place, i64)>;
+
+#[derive(Clone, Default)]
+struct RcState<'a> {
+    /// Symbols that are a (borrowed) field of another place
+    aliases: MutMap<Symbol, Place>,
+    /// The number of references to a place that are owned by the proc
+    counts: MutMap<Place, i64>,
+    /// The layouts of all refcounted places; places without a layout are not tracked
+    layouts: MutMap<Place, InLayout<'a>>,
+    /// Fields of places that were constructed in this proc, which are the values they were
+    /// constructed from
+    fields: MutMap<(Place, u64), Place>,
+    tags: MutMap<Place, TagIdIntType>,
+    /// Symbols defined as `RefCountIsUnique place`
+    uniqueness_tests: MutMap<Symbol, Place>,
+    unique: MutSet<Place>,
+    /// Values whose references are borrowed from another value, which keeps them alive
+    borrowed: MutSet<Place>,
+    /// Values that were owned, but all of whose references have been consumed
+    consumed: MutSet<Place>,
+}
+
+impl<'a> RcState<'a> {
+    fn place(&self, symbol: Symbol) -> Place {
+        match self.aliases.get(&symbol) {
+            Some(place) => place.clone(),
+            None => Place::symbol(symbol),
+        }
+    }
+
+    fn field(&self, parent: &Place, index: u64) -> Place {
+        match self.fields.get(&(parent.clone(), index)) {
+            Some(place) => place.clone(),
+            None => {
+                let mut place = parent.clone();
+                place.fields.push(index);
+                place
+            }
+        }
+    }
+
+    fn add(&mut self, place: Place, amount: i64) {
+        if self.layouts.contains_key(&place) {
+            let count = self.counts.entry(place.clone()).or_default();
+            *count += amount;
+
+            // Fields are kept alive by their parent, and borrowed values by their owner
+            if *count > 0 {
+                self.consumed.remove(&place);
+            } else if amount < 0 && place.fields.is_empty() && !self.borrowed.contains(&place) {
+                self.consumed.insert(place);
+            }
+        }
+    }
+
+    fn consume(&mut self, symbol: Symbol) {
+        self.add(self.place(symbol), -1)
+    }
+}
+
+struct Ctx<'a, 'r> {
+    arena: &'a Bump,
+    interner: &'r STLayoutInterner<'a>,
+    problems: &'r mut Vec<Problem<'a>>,
+    proc: &'r Proc<'a>,
+    proc_layout: ProcLayout<'a>,
+    lines: MutMap<*const Stmt<'a>, usize>,
+    def_lines: MutMap<Symbol, usize>,
+    joinpoints: MutMap<JoinPointId, (&'a [Param<'a>], &'r Stmt<'a>)>,
+    /// Join points whose body is being checked, with the counts on entry
+    active_joinpoints: MutMap<JoinPointId, Counts>,
+    visited_joinpoints: MutSet<(JoinPointId, Counts)>,
+    /// Paths often share their ends, so the same problem can be found more than once
+    reported: MutSet<(usize, Place)>,
+    gave_up: bool,
+}
+
+impl<'a, 'r> Ctx<'a, 'r> {
+    fn line(&self, stmt: &Stmt<'a>) -> usize {
+        self.lines[&(stmt as *const _)]
+    }
+
+    /// Number the statements like the type checker does, so that problems can be reported the
+    /// same way.
+    fn number_lines(&mut self, stmt: &'r Stmt<'a>, line: &mut usize) {
+        *line += 1;
+        self.lines.insert(stmt as *const _, *line);
+
+        match stmt {
+            Stmt::Let(symbol, _, _, rest) => {
+                self.def_lines.insert(*symbol, *line);
+                self.number_lines(rest, line);
+            }
+            Stmt::Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                for (_, _, branch) in branches.iter() {
+                    self.number_lines(branch, line);
+                }
+                self.number_lines(default_branch.1, line);
+            }
+            Stmt::Refcounting(_, rest)
+            | Stmt::Expect {
+                remainder: rest, ..
+            }
+            | Stmt::ExpectFx {
+                remainder: rest, ..
+            }
+            | Stmt::Dbg {
+                remainder: rest, ..
+            } => self.number_lines(rest, line),
+            Stmt::Join {
+                parameters,
+                body,
+                remainder,
+                ..
+            } => {
+                for param in parameters.iter() {
+                    self.def_lines.insert(param.symbol, *line);
+                }
+                self.number_lines(body, line);
+                *line += 1; // `in` line
+                self.number_lines(remainder, line);
+            }
+            Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => {}
+        }
+    }
+
+    fn check_proc(&mut self, proc: &'r Proc<'a>) {
+        self.number_lines(&proc.body, &mut 0);
+
+        let mut state = RcState::default();
+
+        // `inc_dec` passes all arguments as owned
+        for (layout, symbol) in proc.args.iter() {
+            self.def_lines.insert(*symbol, 0);
+            self.define(&mut state, *symbol, *layout, 1);
+        }
+
+        self.check_stmt(&mut state, &proc.body);
+    }
+
+    fn is_refcounted(&self, layout: InLayout<'a>) -> bool {
+        self.interner.contains_refcounted(layout)
+    }
+
+    /// Define a new value, of which we own `count` references
+    fn define(&self, state: &mut RcState<'a>, symbol: Symbol, layout: InLayout<'a>, count: i64) {
+        if self.is_refcounted(layout) {
+            let place = Place::symbol(symbol);
+            state.layouts.insert(place.clone(), layout);
+            if count == 0 {
+                state.borrowed.insert(place.clone());
+            }
+            state.add(place, count);
+        }
+    }
+
+    /// Define a symbol as a borrowed reference to a field of `structure`
+    fn define_field(
+        &self,
+        state: &mut RcState<'a>,
+        symbol: Symbol,
+        layout: InLayout<'a>,
+        structure: Symbol,
+        index: u64,
+    ) {
+        if self.is_refcounted(layout) {
+            let place = state.field(&state.place(structure), index);
+            state.layouts.entry(place.clone()).or_insert(layout);
+            state.aliases.insert(symbol, place);
+        }
+    }
+
+    /// Record that the fields of a constructed value are the values it was constructed from
+    fn define_fields(&self, state: &mut RcState<'a>, symbol: Symbol, arguments: &[Symbol]) {
+        let place = Place::symbol(symbol);
+
+        for (index, argument) in arguments.iter().enumerate() {
+            let argument = state.place(*argument);
+            if state.layouts.contains_key(&argument) {
+                state.fields.insert((place.clone(), index as u64), argument);
+            }
+        }
+    }
+
+    fn check_stmt(&mut self, state: &mut RcState<'a>, stmt: &'r Stmt<'a>) {
+        if self.gave_up {
+            return;
+        }
+
+        match stmt {
+            Stmt::Let(symbol, expr, layout, rest) => {
+                for used in used_symbols(expr) {
+                    self.check_use(state, stmt, used);
+                }
+                self.check_let(state, *symbol, expr, *layout);
+                self.check_stmt(state, rest);
+            }
+            Stmt::Switch {
+                cond_symbol,
+                branches,
+                default_branch,
+                ..
+            } => {
+                for (value, info, branch) in branches.iter() {
+                    let mut branch_state = state.clone();
+                    learn_branch(&mut branch_state, *cond_symbol, Some(*value), info);
+                    self.check_stmt(&mut branch_state, branch);
+                }
+
+                let (info, branch) = default_branch;
+                let mut branch_state = state.clone();
+                learn_branch(&mut branch_state, *cond_symbol, None, info);
+                self.check_stmt(&mut branch_state, branch);
+            }
+            &Stmt::Ret(symbol) => {
+                self.check_use(state, stmt, symbol);
+                state.consume(symbol);
+
+                let counts = self.normalize(state.clone());
+                for (place, count) in counts {
+                    self.problem(stmt, place, count, RefcountExit::Return);
+                }
+            }
+            Stmt::Refcounting(modify_rc, rest) => {
+                match *modify_rc {
+                    ModifyRc::Inc(symbol, amount) => {
+                        self.check_use(state, stmt, symbol);
+                        state.add(state.place(symbol), amount as i64);
+                    }
+                    ModifyRc::Dec(symbol) => state.consume(symbol),
+                    ModifyRc::DecRef(symbol) => {
+                        let place = state.place(symbol);
+                        state.add(place.clone(), -1);
+
+                        if state.unique.contains(&place) {
+                            self.take_fields(state, &place);
+                        }
+                    }
+                }
+                self.check_stmt(state, rest);
+            }
+            Stmt::Expect {
+                lookups, remainder, ..
+            }
+            | Stmt::ExpectFx {
+                lookups, remainder, ..
+            } => {
+                for symbol in lookups.iter() {
+                    self.check_use(state, stmt, *symbol);
+                    state.consume(*symbol);
+                }
+                self.check_stmt(state, remainder);
+            }
+            Stmt::Dbg {
+                symbol, remainder, ..
+            } => {
+                self.check_use(state, stmt, *symbol);
+                state.consume(*symbol);
+                self.check_stmt(state, remainder);
+            }
+            Stmt::Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                // The body is checked from every jump to it
+                self.joinpoints.insert(*id, (*parameters, *body));
+                self.check_stmt(state, remainder);
+            }
+            Stmt::Jump(id, arguments) => {
+                for argument in arguments.iter() {
+                    self.check_use(state, stmt, *argument);
+                    state.consume(*argument);
+                }
+
+                self.check_jump(state, stmt, *id);
+            }
+            Stmt::Crash(_, _) => {
+                // Anything can leak when crashing
+            }
+        }
+    }
+
+    fn check_jump(&mut self, state: &RcState<'a>, stmt: &Stmt<'a>, id: JoinPointId) {
+        let counts = self.normalize(state.clone());
+
+        if let Some(entry_counts) = self.active_joinpoints.get(&id) {
+            let mut differences: MutMap<Place, i64> = MutMap::default();
+            for (place, count) in counts.iter() {
+                *differences.entry(place.clone()).or_default() += count;
+            }
+            for (place, count) in entry_counts.iter() {
+                *differences.entry(place.clone()).or_default() -= count;
+            }
+
+            let mut differences: Counts =
+                differences.into_iter().filter(|(_, d)| *d != 0).collect();
+            differences.sort();
+
+            for (place, difference) in differences {
+                self.problem(stmt, place, difference, RefcountExit::LoopIteration(id));
+            }
+
+            return;
+        }
+
+        if !self.visited_joinpoints.insert((id, counts.clone())) {
+            return;
+        }
+
+        if self.visited_joinpoints.len() > MAX_JOIN_POINT_VISITS {
+            self.gave_up = true;
+            return;
+        }
+
+        let (parameters, body) = self.joinpoints[&id];
+
+        // `inc_dec` makes all join point parameters owned
+        let mut body_state = state.clone();
+        for param in parameters.iter() {
+            self.define(&mut body_state, param.symbol, param.layout, 1);
+        }
+
+        self.active_joinpoints.insert(id, counts);
+        self.check_stmt(&mut body_state, body);
+        self.active_joinpoints.remove(&id);
+    }
+
+    fn check_let(
+        &mut self,
+        state: &mut RcState<'a>,
+        symbol: Symbol,
+        expr: &Expr<'a>,
+        layout: InLayout<'a>,
+    ) {
+        match expr {
+            Expr::Literal(_)
+            | Expr::NullPointer
+            | Expr::EmptyArray
+            | Expr::RuntimeErrorFunction(_) => {
+                self.define(state, symbol, layout, 1);
+            }
+            Expr::Tag {
+                tag_id, arguments, ..
+            }
+            | Expr::Reuse {
+                tag_id, arguments, ..
+            } => {
+                if let Expr::Reuse { symbol: token, .. } = expr {
+                    state.consume(*token);
+                }
+
+                for argument in arguments.iter() {
+                    state.consume(*argument);
+                }
+                self.define(state, symbol, layout, 1);
+                self.define_fields(state, symbol, arguments);
+                state.tags.insert(Place::symbol(symbol), *tag_id);
+            }
+            Expr::Struct(fields) => {
+                for field in fields.iter() {
+                    state.consume(*field);
+                }
+                self.define(state, symbol, layout, 1);
+                self.define_fields(state, symbol, fields);
+            }
+            Expr::Array { elems, .. } => {
+                for elem in elems.iter() {
+                    if let ListLiteralElement::Symbol(elem) = elem {
+                        state.consume(*elem);
+                    }
+                }
+                self.define(state, symbol, layout, 1);
+            }
+            &Expr::ExprBox { symbol: inner } => {
+                state.consume(inner);
+                self.define(state, symbol, layout, 1);
+                self.define_fields(state, symbol, &[inner]);
+            }
+            &Expr::StructAtIndex {
+                index, structure, ..
+            } => self.define_field(state, symbol, layout, structure, index),
+            &Expr::UnionAtIndex {
+                structure,
+                tag_id,
+                index,
+                ..
+            } => {
+                state.tags.insert(state.place(structure), tag_id);
+                self.define_field(state, symbol, layout, structure, index);
+            }
+            &Expr::ExprUnbox { symbol: boxed } => {
+                self.define_field(state, symbol, layout, boxed, 0);
+            }
+            Expr::GetTagId { .. } | Expr::UnionFieldPtrAtIndex { .. } => {}
+            &Expr::Reset { symbol: reset, .. } | &Expr::ResetRef { symbol: reset, .. } => {
+                // The reset value is consumed, we get a reuse token in return
+                state.consume(reset);
+                self.define(state, symbol, layout, 1);
+            }
+            Expr::Call(call) => self.check_call(state, symbol, call, layout),
+        }
+    }
+
+    fn check_call(
+        &mut self,
+        state: &mut RcState<'a>,
+        symbol: Symbol,
+        call: &Call<'a>,
+        layout: InLayout<'a>,
+    ) {
+        let Call {
+            call_type,
+            arguments,
+        } = call;
+
+        match call_type.clone().replace_lowlevel_wrapper() {
+            CallType::ByName { .. } => {
+                // Procs take all their arguments as owned
+                for argument in arguments.iter() {
+                    state.consume(*argument);
+                }
+            }
+            CallType::Foreign { .. } => {
+                // Foreign functions borrow their arguments
+            }
+            CallType::LowLevel {
+                op: LowLevel::ListGetUnsafe,
+                ..
+            } => {
+                // The element is borrowed from the list
+                self.define(state, symbol, layout, 0);
+                return;
+            }
+            CallType::LowLevel {
+                op: LowLevel::RefCountIsUnique,
+                ..
+            } => {
+                state
+                    .uniqueness_tests
+                    .insert(symbol, state.place(arguments[0]));
+            }
+            CallType::LowLevel { op, .. } => {
+                let borrow_signature = lowlevel_borrow_signature(self.arena, op);
+                for (argument, ownership) in arguments.iter().zip(borrow_signature.iter()) {
+                    if ownership.is_owned() {
+                        state.consume(*argument);
+                    }
+                }
+            }
+            CallType::HigherOrder(higher_order) => match higher_order.op {
+                // The mapped lists are borrowed, and `decref`d after the call
+                HigherOrder::ListMap { .. }
+                | HigherOrder::ListMap2 { .. }
+                | HigherOrder::ListMap3 { .. }
+                | HigherOrder::ListMap4 { .. } => {}
+                HigherOrder::ListSortWith { xs } => state.consume(xs),
+            },
+        }
+
+        self.define(state, symbol, layout, 1);
+    }
+
+    /// The allocation of a unique value was freed, which hands its references to its fields
+    /// to us.
+    fn take_fields(&self, state: &mut RcState<'a>, place: &Place) {
+        let layout = match state.layouts.get(place) {
+            Some(layout) => self.interner.chase_recursive_in(*layout),
+            None => return,
+        };
+
+        let field_layouts: &[InLayout<'a>] = match self.interner.get(layout) {
+            Layout::Union(union_layout) => {
+                let tag_id = match union_layout {
+                    UnionLayout::NonNullableUnwrapped(_) => Some(0),
+                    _ => state.tags.get(place).copied(),
+                };
+
+                match tag_id.map(|tag_id| get_tag_id_payloads(union_layout, tag_id)) {
+                    Some(TagPayloads::Payloads(payloads)) => payloads,
+                    Some(TagPayloads::IdNotInUnion) | None => return,
+                }
+            }
+            Layout::Boxed(inner) => self.arena.alloc([inner]),
+            _ => return,
+        };
+
+        self.add_to_fields(state, place, field_layouts, 1);
+    }
+
+    fn add_to_fields(
+        &self,
+        state: &mut RcState<'a>,
+        place: &Place,
+        field_layouts: &[InLayout<'a>],
+        amount: i64,
+    ) {
+        for (index, field_layout) in field_layouts.iter().enumerate() {
+            if self.is_refcounted(*field_layout) {
+                let field = state.field(place, index as u64);
+                let field_layout = self.interner.chase_recursive_in(*field_layout);
+                state.layouts.entry(field.clone()).or_insert(field_layout);
+                state.add(field, amount);
+            }
+        }
+    }
+
+    /// Values that live on the stack are not refcounted themselves, so owning one is the same
+    /// as owning all of its fields. Move the counts of such values to their fields, so that
+    /// consuming a value and consuming all of its fields compare equal.
+    fn normalize(&self, mut state: RcState<'a>) -> Counts {
+        loop {
+            let decomposable: Vec<(Place, i64)> = state
+                .counts
+                .iter()
+                .filter(|(_, count)| **count != 0)
+                .map(|(place, count)| (place.clone(), *count))
+                .collect();
+
+            let mut changed = false;
+
+            for (place, count) in decomposable {
+                let layout = state.layouts[&place];
+
+                match self.interner.runtime_representation(layout) {
+                    Layout::Struct { field_layouts, .. } => {
+                        self.add_to_fields(&mut state, &place, field_layouts, count);
+                    }
+                    Layout::Union(UnionLayout::NonRecursive(tags)) => {
+                        match state.tags.get(&place) {
+                            Some(tag_id) => {
+                                let field_layouts = tags[*tag_id as usize];
+                                self.add_to_fields(&mut state, &place, field_layouts, count);
+                            }
+                            None => continue,
+                        }
+                    }
+                    Layout::Union(_) if self.is_null(&state, &place) => {
+                        // Null values do not need to be consumed
+                    }
+                    _ => continue,
+                }
+
+                state.counts.insert(place, 0);
+                changed = true;
+            }
+
+            if !changed {
+                break;
+            }
+        }
+
+        let mut counts: Counts = state
+            .counts
+            .into_iter()
+            .filter(|(_, count)| *count != 0)
+            .collect();
+        counts.sort();
+        counts
+    }
+
+    fn is_null(&self, state: &RcState<'a>, place: &Place) -> bool {
+        let nullable_id = match self.interner.runtime_representation(state.layouts[place]) {
+            Layout::Union(UnionLayout::NullableWrapped { nullable_id, .. }) => nullable_id,
+            Layout::Union(UnionLayout::NullableUnwrapped { nullable_id, .. }) => {
+                nullable_id as TagIdIntType
+            }
+            _ => return false,
+        };
+
+        state.tags.get(place) == Some(&nullable_id)
+    }
+
+    /// Report a use of a value that may have been freed already
+    fn check_use(&mut self, state: &RcState<'a>, stmt: &Stmt<'a>, symbol: Symbol) {
+        let place = state.place(symbol);
+        if !state.consumed.contains(&place) {
+            return;
+        }
+
+        let line = self.line(stmt);
+        if !self.reported.insert((line, place)) {
+            return;
+        }
+
+        let def_line = self.def_lines.get(&symbol).copied().unwrap_or(0);
+
+        self.problems.push(Problem {
+            proc: self.arena.alloc(self.proc.clone()),
+            proc_layout: self.proc_layout,
+            line,
+            kind: ProblemKind::UseAfterConsume { symbol, def_line },
+        })
+    }
+
+    fn problem(&mut self, stmt: &Stmt<'a>, place: Place, imbalance: i64, exit: RefcountExit) {
+        let line = self.line(stmt);
+
+        if !self.reported.insert((line, place.clone())) {
+            return;
+        }
+
+        let def_line = self.def_lines.get(&place.root).copied().unwrap_or(0);
+
+        self.problems.push(Problem {
+            proc: self.arena.alloc(self.proc.clone()),
+            proc_layout: self.proc_layout,
+            line,
+            kind: ProblemKind::RefcountImbalance {
+                symbol: place.root,
+                fields: place.fields,
+                def_line,
+                imbalance,
+                exit,
+            },
+        })
+    }
+}
+
+/// Learn what taking a switch branch tells us about the values involved
+fn learn_branch(state: &mut RcState, cond_symbol: Symbol, value: Option<u64>, info: &BranchInfo) {
+    if let BranchInfo::Constructor {
+        scrutinee, tag_id, ..
+    } = info
+    {
+        state.tags.insert(state.place(*scrutinee), *tag_id);
+    }
+
+    if value == Some(1) {
+        if let Some(place) = state.uniqueness_tests.get(&cond_symbol) {
+            state.unique.insert(place.clone());
+        }
+    }
+}
+
+/// The symbols that an expression reads
+fn used_symbols<'a>(expr: &Expr<'a>) -> Vec<Symbol> {
+    match expr {
+        Expr::Literal(_) | Expr::NullPointer | Expr::EmptyArray | Expr::RuntimeErrorFunction(_) => {
+            vec![]
+        }
+        Expr::Call(Call { arguments, .. })
+        | Expr::Tag { arguments, .. }
+        | Expr::Struct(arguments) => arguments.to_vec(),
+        Expr::Reuse {
+            symbol, arguments, ..
+        } => std::iter::once(*symbol)
+            .chain(arguments.iter().copied())
+            .collect(),
+        Expr::Array { elems, .. } => elems
+            .iter()
+            .filter_map(|elem| match elem {
+                ListLiteralElement::Symbol(symbol) => Some(*symbol),
+                ListLiteralElement::Literal(_) => None,
+            })
+            .collect(),
+        Expr::ExprBox { symbol }
+        | Expr::ExprUnbox { symbol }
+        | Expr::Reset { symbol, .. }
+        | Expr::ResetRef { symbol, .. } => vec![*symbol],
+        Expr::StructAtIndex { structure, .. }
+        | Expr::GetTagId { structure, .. }
+        | Expr::UnionAtIndex { structure, .. }
+        | Expr::UnionFieldPtrAtIndex { structure, .. } => vec![*structure],
+    }
+}
@@ -9,7 +9,7 @@ use crate::{
 };
 
 use super::{
-    checker::{ProblemKind, UseKind},
+    checker::{ProblemKind, RefcountExit, UseKind},
     Problem, Problems,
 };
 
@@ -410,6 +410,66 @@ where
                 f.as_string(num_given),
             ])
         }
+        ProblemKind::RefcountImbalance {
+            symbol,
+            fields,
+            def_line,
+            imbalance,
+            exit,
+        } => {
+            let place = || format_place(f, interns, symbol, &fields);
+            docs_before = vec![(def_line, f.concat([place(), f.reflow(" is defined here")]))];
+            let exit = match exit {
+                RefcountExit::Return => f.reflow("before returning here"),
+                RefcountExit::LoopIteration(id) => f.concat([
+                    f.reflow("in an iteration of the join point "),
+                    format_symbol(f, interns, id.0),
+                    f.reflow(" ending here"),
+                ]),
+            };
+            if imbalance > 0 {
+                title = "REFCOUNT LEAK";
+                f.concat([
+                    f.reflow("but "),
+                    f.as_string(imbalance),
+                    f.reflow(if imbalance == 1 {
+                        " reference to "
+                    } else {
+                        " references to "
+                    }),
+                    place(),
+                    f.reflow(if imbalance == 1 { " is" } else { " are" }),
+                    f.reflow(" not consumed "),
+                    exit,
+                ])
+            } else {
+                title = "REFCOUNT OVER-CONSUMPTION";
+                f.concat([
+                    f.reflow("but "),
+                    place(),
+                    f.reflow(" is consumed "),
+                    f.as_string(-imbalance),
+                    f.reflow(if imbalance == -1 { " time" } else { " times" }),
+                    f.reflow(" too often "),
+                    exit,
+                ])
+            }
+        }
+        ProblemKind::UseAfterConsume { symbol, def_line } => {
+            title = "USE AFTER CONSUMPTION";
+            docs_before = vec![(
+                def_line,
+                f.concat([
+                    format_symbol(f, interns, symbol),
+                    f.reflow(" is defined here"),
+                ]),
+            )];
+            f.concat([
+                f.reflow("but every reference to "),
+                format_symbol(f, interns, symbol),
+                f.reflow(" was already consumed when it is used here"),
+            ])
+        }
     };
     (title, docs_before, doc)
 }
@@ -420,6 +480,16 @@ fn format_symbol<'d>(f: &'d Arena<'d>, interns: &'d Interns, symbol: Symbol) ->
         .append(f.text(symbol.as_str(interns)))
 }
 
+fn format_place<'d>(
+    f: &'d Arena<'d>,
+    interns: &'d Interns,
+    symbol: Symbol,
+    fields: &[u64],
+) -> Doc<'d> {
+    let fields = fields.iter().map(|index| f.text(format!(".{}", index)));
+    format_symbol(f, interns, symbol).append(f.concat(fields))
+}
+
 fn format_use_kind(use_kind: UseKind) -> &'static str {
     match use_kind {
         UseKind::Ret => "return value",
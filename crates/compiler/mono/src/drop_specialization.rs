@@ -188,6 +188,7 @@ fn specialize_drops_stmt<'a, 'i>(
                 | Expr::ExprBox { .. }
                 | Expr::NullPointer
                 | Expr::GetTagId { .. }
+                | Expr::UnionFieldPtrAtIndex { .. }
                 | Expr::EmptyArray
                 | Expr::Array { .. } => {
                     // Does nothing relevant to drop specialization. So we can just continue.
@@ -908,6 +908,7 @@ fn insert_refcount_operations_binding<'a>(
         Expr::GetTagId { structure, .. }
         | Expr::StructAtIndex { structure, .. }
         | Expr::UnionAtIndex { structure, .. }
+        | Expr::UnionFieldPtrAtIndex { structure, .. }
         | Expr::ExprUnbox { symbol: structure } => {
             // All structures are alive at this point and don't have to be copied in order to take an index out/get tag id/copy values to the stack.
             // But we do want to make sure to decrement this item if it is the last reference.
@@ -923,7 +924,7 @@ fn insert_refcount_operations_binding<'a>(
                     | Expr::UnionAtIndex { .. }
                     | Expr::ExprUnbox { .. } => insert_inc_stmt(arena, *binding, 1, new_stmt),
                     // No usage of an element of a reference counted symbol. No need to increment.
-                    Expr::GetTagId { .. } => new_stmt,
+                    Expr::GetTagId { .. } | Expr::UnionFieldPtrAtIndex { .. } => new_stmt,
                     _ => unreachable!("Unexpected expression type"),
                 }
             } else {
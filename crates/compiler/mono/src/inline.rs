@@ -0,0 +1,880 @@
+use crate::borrow::Ownership;
+use crate::ir::{
+    BranchInfo, Call, CallSpecId, CallType, Expr, HigherOrderLowLevel, HostExposedLayouts,
+    JoinPointId, ListLiteralElement, ModifyRc, Param, PassedFunction, Proc, ProcLayout, Stmt,
+    UpdateModeIds,
+};
+use crate::layout::InLayout;
+use crate::low_level::HigherOrder;
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_collections::{MutMap, ReferenceMatrix};
+use roc_module::symbol::{IdentIds, ModuleId, Symbol};
+
+/// Procs of at most this size are inlined at every call site
+const SMALL_PROC_SIZE: usize = 12;
+
+/// Procs that are called exactly once are inlined, and then removed, up to this size
+const SINGLE_USE_PROC_SIZE: usize = 256;
+
+type ProcKey<'a> = (Symbol, ProcLayout<'a>);
+
+/// Inline small procs, and procs that are only called once, into their callers.
+///
+/// The dev and wasm backends don't have an inliner, so without this pass every
+/// record accessor, lambda set wrapper and tiny lambda is a real call.
+///
+/// Recursive procs (including mutually recursive ones) are never inlined. Procs are processed
+/// callees-first, so a proc is inlined with everything that was inlined into it already.
+/// Every symbol and join point in an inlined body is given a fresh name, and calls in it get
+/// fresh specialization ids and update modes, so later passes and alias analysis see it as
+/// an ordinary part of its caller.
+///
+/// This must run before refcount insertion: it does not know about borrowed parameters.
+/// `entry_points` are the symbols that the host, the repl or the test runner look up, which
+/// must be kept around even if they are called only once.
+pub fn inline_procs<'a, 'i>(
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    update_mode_ids: &'i mut UpdateModeIds,
+    entry_points: &[Symbol],
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    let mut entries: std::vec::Vec<(ProcKey<'a>, Proc<'a>)> = procs.drain().collect();
+
+    let key_to_index: MutMap<ProcKey<'a>, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, (key, _))| (*key, index))
+        .collect();
+
+    let mut matrix = ReferenceMatrix::new(entries.len());
+    let mut call_counts = vec![0usize; entries.len()];
+    let mut is_passed = vec![false; entries.len()];
+    let mut max_call_spec_id = CallSpecId::BACKEND_DUMMY;
+
+    for (row, (_, proc)) in entries.iter().enumerate() {
+        for_each_expr(arena, &proc.body, |expr| {
+            if let Expr::Call(call) = expr {
+                match &call.call_type {
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts,
+                        specialization_id,
+                    } => {
+                        max_call_spec_id = max_call_spec_id.max(*specialization_id);
+
+                        let key = (
+                            name.name(),
+                            ProcLayout::new(arena, arg_layouts, name.niche(), *ret_layout),
+                        );
+
+                        if let Some(&col) = key_to_index.get(&key) {
+                            matrix.set_row_col(row, col, true);
+                            call_counts[col] += 1;
+                        }
+                    }
+                    CallType::HigherOrder(HigherOrderLowLevel {
+                        passed_function,
+                        update_mode,
+                        ..
+                    }) => {
+                        max_call_spec_id = max_call_spec_id.max(passed_function.specialization_id);
+                        update_mode_ids.skip_past(*update_mode);
+
+                        let key = (
+                            passed_function.name.name(),
+                            ProcLayout {
+                                arguments: passed_function.argument_layouts,
+                                result: passed_function.return_layout,
+                                niche: passed_function.name.niche(),
+                            },
+                        );
+
+                        if let Some(&col) = key_to_index.get(&key) {
+                            matrix.set_row_col(row, col, true);
+                            is_passed[col] = true;
+                        }
+                    }
+                    CallType::LowLevel { update_mode, .. } => {
+                        update_mode_ids.skip_past(*update_mode);
+                    }
+                    CallType::Foreign { .. } => {}
+                }
+            }
+
+            match expr {
+                Expr::Reuse { update_mode, .. }
+                | Expr::Reset { update_mode, .. }
+                | Expr::ResetRef { update_mode, .. } => {
+                    update_mode_ids.skip_past(*update_mode);
+                }
+                _ => {}
+            }
+        });
+    }
+
+    let single_use = entries
+        .iter()
+        .enumerate()
+        .map(|(index, ((symbol, _), proc))| {
+            call_counts[index] == 1
+                && !is_passed[index]
+                && !entry_points.contains(symbol)
+                && matches!(
+                    proc.host_exposed_layouts,
+                    HostExposedLayouts::NotHostExposed
+                )
+        })
+        .collect();
+
+    let mut inliner = Inliner {
+        arena,
+        home,
+        ident_ids,
+        update_mode_ids,
+        next_call_spec_id: max_call_spec_id.next(),
+        key_to_index,
+        inline_size: vec![None; entries.len()],
+        single_use,
+        inlined_single_use: vec![false; entries.len()],
+    };
+
+    // the groups come in topological order, so callees are always processed before their callers
+    let sccs = matrix.strongly_connected_components_all();
+
+    for (group, _) in sccs.groups() {
+        let is_recursive = match group.count_ones() {
+            1 => {
+                let index = group.first_one().unwrap();
+                matrix.get_row_col(index, index)
+            }
+            _ => true,
+        };
+
+        for index in group.iter_ones() {
+            let new_body = inliner.inline_stmt(&entries, index, &entries[index].1.body);
+            entries[index].1.body = new_body;
+
+            if !is_recursive {
+                inliner.inline_size[index] = inline_size(arena, &entries[index].1.body);
+            }
+        }
+    }
+
+    let removed = inliner.inlined_single_use;
+
+    procs.extend(
+        entries
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| !removed[*index])
+            .map(|(_, entry)| entry),
+    );
+}
+
+struct Inliner<'a, 'i> {
+    arena: &'a Bump,
+    home: ModuleId,
+    ident_ids: &'i mut IdentIds,
+    update_mode_ids: &'i mut UpdateModeIds,
+    next_call_spec_id: CallSpecId,
+    key_to_index: MutMap<ProcKey<'a>, usize>,
+    /// The size of a proc body after inlining into it, if the proc can be inlined at all
+    inline_size: std::vec::Vec<Option<usize>>,
+    single_use: std::vec::Vec<bool>,
+    inlined_single_use: std::vec::Vec<bool>,
+}
+
+/// What to do with a `Ret` in an inlined body
+enum Return<'a, 'r> {
+    /// The call was in tail position; return from the caller
+    Ret,
+    /// Jump to the join point that holds the rest of the caller
+    Jump(JoinPointId),
+    /// The body is straight-line code; continue with the rest of the caller
+    Continue(&'r Stmt<'a>),
+}
+
+#[derive(Default)]
+struct Renaming {
+    symbols: MutMap<Symbol, Symbol>,
+    join_points: MutMap<JoinPointId, JoinPointId>,
+}
+
+impl Renaming {
+    fn symbol(&self, symbol: Symbol) -> Symbol {
+        self.symbols.get(&symbol).copied().unwrap_or(symbol)
+    }
+
+    fn join_point(&self, id: JoinPointId) -> JoinPointId {
+        self.join_points.get(&id).copied().unwrap_or(id)
+    }
+}
+
+impl<'a, 'i> Inliner<'a, 'i> {
+    fn fresh_symbol(&mut self) -> Symbol {
+        Symbol::new(self.home, self.ident_ids.gen_unique())
+    }
+
+    fn fresh_call_spec_id(&mut self) -> CallSpecId {
+        let id = self.next_call_spec_id;
+        self.next_call_spec_id = id.next();
+        id
+    }
+
+    /// The index of the proc that this call should be replaced with, if any
+    fn inline_target(&mut self, current: usize, call: &Call<'a>) -> Option<usize> {
+        let (name, ret_layout, arg_layouts) = match call.call_type {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                ..
+            } => (name, ret_layout, arg_layouts),
+            _ => return None,
+        };
+
+        let key = (
+            name.name(),
+            ProcLayout::new(self.arena, arg_layouts, name.niche(), ret_layout),
+        );
+
+        let callee = *self.key_to_index.get(&key)?;
+
+        if callee == current {
+            return None;
+        }
+
+        // procs that are recursive, or have not been processed yet, have no size
+        let size = self.inline_size[callee]?;
+
+        if size <= SMALL_PROC_SIZE {
+            Some(callee)
+        } else if self.single_use[callee] && size <= SINGLE_USE_PROC_SIZE {
+            self.inlined_single_use[callee] = true;
+
+            Some(callee)
+        } else {
+            None
+        }
+    }
+
+    fn inline_stmt(
+        &mut self,
+        procs: &[(ProcKey<'a>, Proc<'a>)],
+        current: usize,
+        stmt: &Stmt<'a>,
+    ) -> Stmt<'a> {
+        use Stmt::*;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                if let Expr::Call(call) = expr {
+                    if let Some(callee) = self.inline_target(current, call) {
+                        return self.inline_call(
+                            procs,
+                            current,
+                            &procs[callee].1,
+                            call.arguments,
+                            *symbol,
+                            *layout,
+                            cont,
+                        );
+                    }
+                }
+
+                let cont = self.inline_stmt(procs, current, cont);
+
+                Let(*symbol, expr.clone(), *layout, self.arena.alloc(cont))
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut new_branches = Vec::with_capacity_in(branches.len(), self.arena);
+
+                for (tag, info, branch) in branches.iter() {
+                    let branch = self.inline_stmt(procs, current, branch);
+                    new_branches.push((*tag, info.clone(), branch));
+                }
+
+                let default = self.inline_stmt(procs, current, default_branch.1);
+
+                Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: (default_branch.0.clone(), self.arena.alloc(default)),
+                    ret_layout: *ret_layout,
+                }
+            }
+            Refcounting(modify, cont) => {
+                let cont = self.inline_stmt(procs, current, cont);
+
+                Refcounting(*modify, self.arena.alloc(cont))
+            }
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.inline_stmt(procs, current, remainder);
+
+                Expect {
+                    condition: *condition,
+                    region: *region,
+                    lookups: *lookups,
+                    variables: *variables,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.inline_stmt(procs, current, remainder);
+
+                ExpectFx {
+                    condition: *condition,
+                    region: *region,
+                    lookups: *lookups,
+                    variables: *variables,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                let remainder = self.inline_stmt(procs, current, remainder);
+
+                Dbg {
+                    symbol: *symbol,
+                    variable: *variable,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let body = self.inline_stmt(procs, current, body);
+                let remainder = self.inline_stmt(procs, current, remainder);
+
+                Join {
+                    id: *id,
+                    parameters: *parameters,
+                    body: self.arena.alloc(body),
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Ret(_) | Jump(_, _) | Crash(_, _) => stmt.clone(),
+        }
+    }
+
+    /// Replace `let symbol = callee arguments in cont` with the body of `callee`
+    fn inline_call(
+        &mut self,
+        procs: &[(ProcKey<'a>, Proc<'a>)],
+        current: usize,
+        callee: &Proc<'a>,
+        arguments: &[Symbol],
+        symbol: Symbol,
+        layout: InLayout<'a>,
+        cont: &Stmt<'a>,
+    ) -> Stmt<'a> {
+        let mut renaming = Renaming::default();
+
+        for ((_, param), argument) in callee.args.iter().zip(arguments) {
+            renaming.symbols.insert(*param, *argument);
+        }
+
+        let cont = self.inline_stmt(procs, current, cont);
+
+        if cont == Stmt::Ret(symbol) {
+            return self.copy_stmt(&callee.body, &mut renaming, &Return::Ret);
+        }
+
+        if let Some(result) = straight_line_result(&callee.body) {
+            // bind the result directly to the symbol that the caller expects
+            renaming.symbols.insert(result, symbol);
+
+            return self.copy_stmt(&callee.body, &mut renaming, &Return::Continue(&cont));
+        }
+
+        let id = JoinPointId(self.fresh_symbol());
+        let remainder = self.copy_stmt(&callee.body, &mut renaming, &Return::Jump(id));
+
+        let parameters = self.arena.alloc([Param {
+            symbol,
+            ownership: Ownership::Owned,
+            layout,
+        }]);
+
+        Stmt::Join {
+            id,
+            parameters,
+            body: self.arena.alloc(cont),
+            remainder: self.arena.alloc(remainder),
+        }
+    }
+
+    /// Copy an inlined body, giving everything it binds a fresh name
+    fn copy_stmt(
+        &mut self,
+        stmt: &Stmt<'a>,
+        renaming: &mut Renaming,
+        ret: &Return<'a, '_>,
+    ) -> Stmt<'a> {
+        use Stmt::*;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                let expr = self.copy_expr(expr, renaming);
+
+                let new_symbol = match renaming.symbols.get(symbol) {
+                    Some(new_symbol) => *new_symbol,
+                    None => {
+                        let new_symbol = self.fresh_symbol();
+                        renaming.symbols.insert(*symbol, new_symbol);
+                        new_symbol
+                    }
+                };
+
+                let cont = self.copy_stmt(cont, renaming, ret);
+
+                Let(new_symbol, expr, *layout, self.arena.alloc(cont))
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut new_branches = Vec::with_capacity_in(branches.len(), self.arena);
+
+                for (tag, info, branch) in branches.iter() {
+                    let info = rename_branch_info(info, renaming);
+                    let branch = self.copy_stmt(branch, renaming, ret);
+                    new_branches.push((*tag, info, branch));
+                }
+
+                let default_info = rename_branch_info(&default_branch.0, renaming);
+                let default = self.copy_stmt(default_branch.1, renaming, ret);
+
+                Switch {
+                    cond_symbol: renaming.symbol(*cond_symbol),
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: (default_info, self.arena.alloc(default)),
+                    ret_layout: *ret_layout,
+                }
+            }
+            Ret(symbol) => match ret {
+                Return::Ret => Ret(renaming.symbol(*symbol)),
+                Return::Jump(id) => Jump(*id, self.arena.alloc([renaming.symbol(*symbol)])),
+                Return::Continue(cont) => (*cont).clone(),
+            },
+            Refcounting(modify, cont) => {
+                let modify = match modify {
+                    ModifyRc::Inc(symbol, n) => ModifyRc::Inc(renaming.symbol(*symbol), *n),
+                    ModifyRc::Dec(symbol) => ModifyRc::Dec(renaming.symbol(*symbol)),
+                    ModifyRc::DecRef(symbol) => ModifyRc::DecRef(renaming.symbol(*symbol)),
+                };
+                let cont = self.copy_stmt(cont, renaming, ret);
+
+                Refcounting(modify, self.arena.alloc(cont))
+            }
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.copy_stmt(remainder, renaming, ret);
+
+                Expect {
+                    condition: renaming.symbol(*condition),
+                    region: *region,
+                    lookups: self.rename_symbols(lookups, renaming),
+                    variables: *variables,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.copy_stmt(remainder, renaming, ret);
+
+                ExpectFx {
+                    condition: renaming.symbol(*condition),
+                    region: *region,
+                    lookups: self.rename_symbols(lookups, renaming),
+                    variables: *variables,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                let remainder = self.copy_stmt(remainder, renaming, ret);
+
+                Dbg {
+                    symbol: renaming.symbol(*symbol),
+                    variable: *variable,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let new_id = JoinPointId(self.fresh_symbol());
+                renaming.join_points.insert(*id, new_id);
+
+                let mut new_parameters = Vec::with_capacity_in(parameters.len(), self.arena);
+
+                for param in parameters.iter() {
+                    let symbol = self.fresh_symbol();
+                    renaming.symbols.insert(param.symbol, symbol);
+                    new_parameters.push(Param { symbol, ..*param });
+                }
+
+                let body = self.copy_stmt(body, renaming, ret);
+                let remainder = self.copy_stmt(remainder, renaming, ret);
+
+                Join {
+                    id: new_id,
+                    parameters: new_parameters.into_bump_slice(),
+                    body: self.arena.alloc(body),
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Jump(id, arguments) => Jump(
+                renaming.join_point(*id),
+                self.rename_symbols(arguments, renaming),
+            ),
+            Crash(symbol, tag) => Crash(renaming.symbol(*symbol), *tag),
+        }
+    }
+
+    fn copy_expr(&mut self, expr: &Expr<'a>, renaming: &Renaming) -> Expr<'a> {
+        use Expr::*;
+
+        match expr {
+            Literal(_) | NullPointer | EmptyArray | RuntimeErrorFunction(_) => expr.clone(),
+            Call(call) => Call(crate::ir::Call {
+                call_type: self.copy_call_type(&call.call_type, renaming),
+                arguments: self.rename_symbols(call.arguments, renaming),
+            }),
+            Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+            } => Tag {
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: self.rename_symbols(arguments, renaming),
+            },
+            Struct(fields) => Struct(self.rename_symbols(fields, renaming)),
+            StructAtIndex {
+                index,
+                field_layouts,
+                structure,
+            } => StructAtIndex {
+                index: *index,
+                field_layouts: *field_layouts,
+                structure: renaming.symbol(*structure),
+            },
+            GetTagId {
+                structure,
+                union_layout,
+            } => GetTagId {
+                structure: renaming.symbol(*structure),
+                union_layout: *union_layout,
+            },
+            UnionAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => UnionAtIndex {
+                structure: renaming.symbol(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            UnionFieldPtrAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => UnionFieldPtrAtIndex {
+                structure: renaming.symbol(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            Array { elem_layout, elems } => {
+                let elems = Vec::from_iter_in(
+                    elems.iter().map(|elem| match elem {
+                        ListLiteralElement::Literal(_) => *elem,
+                        ListLiteralElement::Symbol(symbol) => {
+                            ListLiteralElement::Symbol(renaming.symbol(*symbol))
+                        }
+                    }),
+                    self.arena,
+                );
+
+                Array {
+                    elem_layout: *elem_layout,
+                    elems: elems.into_bump_slice(),
+                }
+            }
+            ExprBox { symbol } => ExprBox {
+                symbol: renaming.symbol(*symbol),
+            },
+            ExprUnbox { symbol } => ExprUnbox {
+                symbol: renaming.symbol(*symbol),
+            },
+            Reuse {
+                symbol,
+                update_tag_id,
+                tag_layout,
+                tag_id,
+                arguments,
+                ..
+            } => Reuse {
+                symbol: renaming.symbol(*symbol),
+                update_tag_id: *update_tag_id,
+                update_mode: self.update_mode_ids.next_id(),
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: self.rename_symbols(arguments, renaming),
+            },
+            Reset { symbol, .. } => Reset {
+                symbol: renaming.symbol(*symbol),
+                update_mode: self.update_mode_ids.next_id(),
+            },
+            ResetRef { symbol, .. } => ResetRef {
+                symbol: renaming.symbol(*symbol),
+                update_mode: self.update_mode_ids.next_id(),
+            },
+        }
+    }
+
+    fn copy_call_type(&mut self, call_type: &CallType<'a>, renaming: &Renaming) -> CallType<'a> {
+        match call_type {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                ..
+            } => CallType::ByName {
+                name: *name,
+                ret_layout: *ret_layout,
+                arg_layouts: *arg_layouts,
+                specialization_id: self.fresh_call_spec_id(),
+            },
+            CallType::Foreign { .. } => call_type.clone(),
+            CallType::LowLevel { op, .. } => CallType::LowLevel {
+                op: *op,
+                update_mode: self.update_mode_ids.next_id(),
+            },
+            CallType::HigherOrder(higher_order) => {
+                let passed_function = PassedFunction {
+                    specialization_id: self.fresh_call_spec_id(),
+                    captured_environment: renaming
+                        .symbol(higher_order.passed_function.captured_environment),
+                    ..higher_order.passed_function
+                };
+
+                CallType::HigherOrder(self.arena.alloc(HigherOrderLowLevel {
+                    op: rename_higher_order(&higher_order.op, renaming),
+                    closure_env_layout: higher_order.closure_env_layout,
+                    update_mode: self.update_mode_ids.next_id(),
+                    passed_function,
+                }))
+            }
+        }
+    }
+
+    fn rename_symbols(&self, symbols: &[Symbol], renaming: &Renaming) -> &'a [Symbol] {
+        Vec::from_iter_in(
+            symbols.iter().map(|symbol| renaming.symbol(*symbol)),
+            self.arena,
+        )
+        .into_bump_slice()
+    }
+}
+
+fn rename_branch_info<'a>(info: &BranchInfo<'a>, renaming: &Renaming) -> BranchInfo<'a> {
+    match info {
+        BranchInfo::None => BranchInfo::None,
+        BranchInfo::Constructor {
+            scrutinee,
+            layout,
+            tag_id,
+        } => BranchInfo::Constructor {
+            scrutinee: renaming.symbol(*scrutinee),
+            layout: *layout,
+            tag_id: *tag_id,
+        },
+        BranchInfo::List { scrutinee, len } => BranchInfo::List {
+            scrutinee: renaming.symbol(*scrutinee),
+            len: *len,
+        },
+    }
+}
+
+fn rename_higher_order(op: &HigherOrder, renaming: &Renaming) -> HigherOrder {
+    use HigherOrder::*;
+
+    match op {
+        ListMap { xs } => ListMap {
+            xs: renaming.symbol(*xs),
+        },
+        ListMap2 { xs, ys } => ListMap2 {
+            xs: renaming.symbol(*xs),
+            ys: renaming.symbol(*ys),
+        },
+        ListMap3 { xs, ys, zs } => ListMap3 {
+            xs: renaming.symbol(*xs),
+            ys: renaming.symbol(*ys),
+            zs: renaming.symbol(*zs),
+        },
+        ListMap4 { xs, ys, zs, ws } => ListMap4 {
+            xs: renaming.symbol(*xs),
+            ys: renaming.symbol(*ys),
+            zs: renaming.symbol(*zs),
+            ws: renaming.symbol(*ws),
+        },
+        ListSortWith { xs } => ListSortWith {
+            xs: renaming.symbol(*xs),
+        },
+    }
+}
+
+/// If the body is a chain of `let`s that ends by returning one of them, the returned symbol
+fn straight_line_result(body: &Stmt) -> Option<Symbol> {
+    let mut stmt = body;
+
+    while let Stmt::Let(_, _, _, cont) = stmt {
+        stmt = cont;
+    }
+
+    let result = match stmt {
+        Stmt::Ret(result) => *result,
+        _ => return None,
+    };
+
+    let mut stmt = body;
+
+    while let Stmt::Let(symbol, _, _, cont) = stmt {
+        if *symbol == result {
+            return Some(result);
+        }
+
+        stmt = cont;
+    }
+
+    None
+}
+
+/// The number of statements and expressions in a proc body, or `None` if the body
+/// contains `expect` or `dbg` statements. Those refer to the types of the module they were
+/// defined in, so they can't be moved into another proc.
+fn inline_size<'a>(arena: &'a Bump, body: &Stmt<'a>) -> Option<usize> {
+    use Stmt::*;
+
+    let mut size = 0;
+    let mut stack = bumpalo::vec![in arena; body];
+
+    while let Some(stmt) = stack.pop() {
+        size += 1;
+
+        match stmt {
+            Let(_, _, _, cont) => stack.push(cont),
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|b| &b.2));
+                stack.push(default_branch.1);
+            }
+            Refcounting(_, cont) => stack.push(cont),
+            Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Expect { .. } | ExpectFx { .. } | Dbg { .. } => return None,
+            Ret(_) | Jump(_, _) | Crash(_, _) => {}
+        }
+    }
+
+    Some(size)
+}
+
+fn for_each_expr<'a, F>(arena: &'a Bump, body: &Stmt<'a>, mut f: F)
+where
+    F: FnMut(&Expr<'a>),
+{
+    use Stmt::*;
+
+    let mut stack = bumpalo::vec![in arena; body];
+
+    while let Some(stmt) = stack.pop() {
+        match stmt {
+            Let(_, expr, _, cont) => {
+                f(expr);
+                stack.push(cont);
+            }
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                stack.extend(branches.iter().map(|b| &b.2));
+                stack.push(default_branch.1);
+            }
+            Refcounting(_, cont) => stack.push(cont),
+            Expect { remainder, .. } | ExpectFx { remainder, .. } | Dbg { remainder, .. } => {
+                stack.push(remainder)
+            }
+            Join {
+                body, remainder, ..
+            } => {
+                stack.push(body);
+                stack.push(remainder);
+            }
+            Ret(_) | Jump(_, _) | Crash(_, _) => {}
+        }
+    }
+}
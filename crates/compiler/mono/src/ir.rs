@@ -17,8 +17,9 @@ use roc_collections::VecMap;
 use roc_debug_flags::dbg_do;
 #[cfg(debug_assertions)]
 use roc_debug_flags::{
-    ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_REFCOUNT,
-    ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION, ROC_PRINT_RUNTIME_ERROR_GEN,
+    ROC_PRINT_IR_AFTER_DROP_SPECIALIZATION, ROC_PRINT_IR_AFTER_INLINING,
+    ROC_PRINT_IR_AFTER_REFCOUNT, ROC_PRINT_IR_AFTER_RESET_REUSE, ROC_PRINT_IR_AFTER_SPECIALIZATION,
+    ROC_PRINT_RUNTIME_ERROR_GEN,
 };
 use roc_derive::SharedDerivedModule;
 use roc_error_macros::{internal_error, todo_abilities};
@@ -45,12 +46,16 @@ pub use literal::{ListLiteralElement, Literal};
 mod decision_tree;
 mod literal;
 mod pattern;
+pub mod text;
 
 #[inline(always)]
 pub fn pretty_print_ir_symbols() -> bool {
     dbg_do!(ROC_PRINT_IR_AFTER_SPECIALIZATION, {
         return true;
     });
+    dbg_do!(ROC_PRINT_IR_AFTER_INLINING, {
+        return true;
+    });
     dbg_do!(ROC_PRINT_IR_AFTER_RESET_REUSE, {
         return true;
     });
@@ -127,6 +132,33 @@ pub enum OptLevel {
     Optimize,
 }
 
+/// Whether to run the backend-independent optimizations (inlining, constant folding and dead
+/// code elimination) on the mono IR after specialization.
+///
+/// LLVM does all of this (and much better) by itself, so this is mostly useful for the
+/// dev and wasm backends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonoOptimizations {
+    Disabled,
+    Enabled,
+}
+
+/// Extra information to gather about the mono IR, for diagnostics that are only shown on request.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MonoDiagnostics {
+    /// Remember where in the source the symbols are defined, see [`SymbolRegions`]
+    pub symbol_regions: bool,
+    /// Look for values that are copied where they could have been updated in place.
+    /// This implies `symbol_regions`.
+    pub missed_updates: bool,
+}
+
+impl MonoDiagnostics {
+    pub fn tracks_symbol_regions(&self) -> bool {
+        self.symbol_regions || self.missed_updates
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SingleEntryPoint<'a> {
     pub symbol: Symbol,
@@ -306,6 +338,9 @@ pub struct Proc<'a> {
     pub ret_layout: InLayout<'a>,
     pub is_self_recursive: SelfRecursive,
     pub host_exposed_layouts: HostExposedLayouts<'a>,
+    /// Boxes, lists and tag union nodes defined in this proc that never escape it. The backends
+    /// put them in the stack frame instead of on the heap; see [`crate::stack_allocation`].
+    pub stack_allocations: &'a [Symbol],
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -404,7 +439,11 @@ impl<'a> Proc<'a> {
         String::from_utf8(w).unwrap()
     }
 
-    fn make_tail_recursive(&mut self, env: &mut Env<'a, '_>) {
+    pub(crate) fn make_tail_recursive(
+        &mut self,
+        env: &mut Env<'a, '_>,
+        layout_cache: &mut LayoutCache<'a>,
+    ) {
         let mut args = Vec::with_capacity_in(self.args.len(), env.arena);
         let mut proc_args = Vec::with_capacity_in(self.args.len(), env.arena);
 
@@ -416,14 +455,27 @@ impl<'a> Proc<'a> {
 
         use self::SelfRecursive::*;
         if let SelfRecursive(id) = self.is_self_recursive {
-            let transformed = crate::tail_recursion::make_tail_recursive(
-                env.arena,
+            let args = args.into_bump_slice();
+
+            let transformed = crate::tail_recursion::make_tail_recursive_modulo_cons(
+                env,
+                layout_cache,
                 id,
                 self.name,
-                self.body.clone(),
-                args.into_bump_slice(),
+                &self.body,
+                args,
                 self.ret_layout,
-            );
+            )
+            .or_else(|| {
+                crate::tail_recursion::make_tail_recursive(
+                    env.arena,
+                    id,
+                    self.name,
+                    self.body.clone(),
+                    args,
+                    self.ret_layout,
+                )
+            });
 
             if let Some(with_tco) = transformed {
                 self.body = with_tco;
@@ -1019,17 +1071,24 @@ impl<'a> Procs<'a> {
     pub fn get_specialized_procs_without_rc(
         self,
         env: &mut Env<'a, '_>,
+        layout_cache: &mut LayoutCache<'a>,
     ) -> (MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>, ProcsBase<'a>) {
         let mut specialized_procs =
             MutMap::with_capacity_and_hasher(self.specialized.len(), default_hasher());
 
         for (symbol, layout, mut proc) in self.specialized.into_iter_assert_done() {
-            proc.make_tail_recursive(env);
+            proc.make_tail_recursive(env, layout_cache);
 
             let key = (symbol, layout);
             specialized_procs.insert(key, proc);
         }
 
+        crate::tail_recursion::make_mutually_tail_recursive(
+            env,
+            layout_cache,
+            &mut specialized_procs,
+        );
+
         let restored_procs_base = ProcsBase {
             partial_procs: self.partial_procs.drain().collect(),
             module_thunks: self.module_thunks,
@@ -1382,6 +1441,8 @@ pub struct Env<'a, 'i> {
     pub abilities: AbilitiesView<'i>,
     pub exposed_by_module: &'i ExposedByModule,
     pub derived_module: &'i SharedDerivedModule,
+    /// Source regions of the expressions that define symbols, if they are being tracked
+    pub symbol_regions: Option<&'i mut SymbolRegions>,
 }
 
 impl<'a, 'i> Env<'a, 'i> {
@@ -1509,6 +1570,10 @@ pub fn cond<'a>(
 
 pub type Stores<'a> = &'a [(Symbol, Layout<'a>, Expr<'a>)];
 
+/// The source region of the expression that defines a symbol, where it is known. Mono IR has no
+/// regions of its own, so diagnostics about the IR use this to point at the source.
+pub type SymbolRegions = MutMap<Symbol, Region>;
+
 /// The specialized type of a lookup. Represented as a type-variable.
 pub type LookupType = Variable;
 
@@ -1724,7 +1789,7 @@ impl<'a> Call<'a> {
     }
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CallSpecId {
     id: u32,
 }
@@ -1734,12 +1799,17 @@ impl CallSpecId {
         self.id.to_ne_bytes()
     }
 
+    /// The id directly after this one. Used by passes that duplicate calls after specialization.
+    pub(crate) fn next(self) -> Self {
+        Self { id: self.id + 1 }
+    }
+
     /// Dummy value for generating refcount helper procs in the backends
     /// This happens *after* specialization so it's safe
     pub const BACKEND_DUMMY: Self = Self { id: 0 };
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct UpdateModeId {
     id: u32,
 }
@@ -1769,6 +1839,11 @@ impl UpdateModeIds {
         self.next += 1;
         id
     }
+
+    /// Make sure that all ids handed out from now on are larger than `id`
+    pub(crate) fn skip_past(&mut self, id: UpdateModeId) {
+        self.next = self.next.max(id.id + 1);
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -1872,6 +1947,15 @@ pub enum Expr<'a> {
         index: u64,
     },
 
+    /// The address of a field of a heap-allocated tag, as a `usize`. Only used by tail recursion
+    /// modulo cons, to fill in the field later with `PtrStore`.
+    UnionFieldPtrAtIndex {
+        structure: Symbol,
+        tag_id: TagIdIntType,
+        union_layout: UnionLayout<'a>,
+        index: u64,
+    },
+
     Array {
         elem_layout: InLayout<'a>,
         elems: &'a [ListLiteralElement<'a>],
@@ -2090,6 +2174,18 @@ impl<'a> Expr<'a> {
             } => alloc
                 .text(format!("UnionAtIndex (Id {}) (Index {}) ", tag_id, index))
                 .append(symbol_to_doc(alloc, *structure, pretty)),
+
+            UnionFieldPtrAtIndex {
+                tag_id,
+                structure,
+                index,
+                ..
+            } => alloc
+                .text(format!(
+                    "UnionFieldPtrAtIndex (Id {}) (Index {}) ",
+                    tag_id, index
+                ))
+                .append(symbol_to_doc(alloc, *structure, pretty)),
         }
     }
 
@@ -2357,6 +2453,10 @@ fn from_can_let<'a>(
     }
 
     if let roc_can::pattern::Pattern::Identifier(symbol) = &def.loc_pattern.value {
+        if let Some(symbol_regions) = env.symbol_regions.as_deref_mut() {
+            symbol_regions.insert(*symbol, def.loc_expr.region);
+        }
+
         return match def.loc_expr.value {
             Closure(closure_data) => {
                 register_capturing_closure(env, procs, layout_cache, *symbol, closure_data);
@@ -3196,6 +3296,7 @@ fn generate_runtime_error_function<'a>(
         ret_layout,
         is_self_recursive: SelfRecursive::NotSelfRecursive,
         host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+        stack_allocations: &[],
     }
 }
 
@@ -3288,6 +3389,7 @@ fn generate_host_exposed_function<'a>(
                 ret_layout: result,
                 is_self_recursive: SelfRecursive::NotSelfRecursive,
                 host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+                stack_allocations: &[],
             };
 
             let top_level = ProcLayout::from_raw_named(env.arena, lambda_name, layout);
@@ -3352,6 +3454,7 @@ fn generate_host_exposed_lambda_set<'a>(
         ret_layout: return_layout,
         is_self_recursive: SelfRecursive::NotSelfRecursive,
         host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+        stack_allocations: &[],
     };
 
     let top_level = ProcLayout::new(
@@ -3448,6 +3551,7 @@ fn specialize_proc_help<'a>(
                 ret_layout,
                 is_self_recursive: recursivity,
                 host_exposed_layouts,
+                stack_allocations: &[],
             }
         }
         SpecializedLayout::FunctionBody {
@@ -3647,6 +3751,7 @@ fn specialize_proc_help<'a>(
                 ret_layout,
                 is_self_recursive: recursivity,
                 host_exposed_layouts,
+                stack_allocations: &[],
             }
         }
     };
@@ -5149,6 +5254,10 @@ pub fn with_hole<'a>(
         Call(boxed, loc_args, _) => {
             let (fn_var, loc_expr, _lambda_set_var, _ret_var) = *boxed;
 
+            if let Some(symbol_regions) = env.symbol_regions.as_deref_mut() {
+                symbol_regions.entry(assigned).or_insert(loc_expr.region);
+            }
+
             // even if a call looks like it's by name, it may in fact be by-pointer.
             // E.g. in `(\f, x -> f x)` the call is in fact by pointer.
             // So we check the function name against the list of partial procedures,
@@ -7599,6 +7708,21 @@ fn substitute_in_expr<'a>(
             }),
             None => None,
         },
+
+        UnionFieldPtrAtIndex {
+            structure,
+            tag_id,
+            index,
+            union_layout,
+        } => match substitute(subs, *structure) {
+            Some(structure) => Some(UnionFieldPtrAtIndex {
+                structure,
+                tag_id: *tag_id,
+                index: *index,
+                union_layout: *union_layout,
+            }),
+            None => None,
+        },
     }
 }
 
@@ -8015,15 +8139,21 @@ fn assign_to_symbol<'a>(
             )
         }
         Value(_symbol) => result,
-        NotASymbol => with_hole(
-            env,
-            loc_arg.value,
-            arg_var,
-            procs,
-            layout_cache,
-            symbol,
-            env.arena.alloc(result),
-        ),
+        NotASymbol => {
+            if let Some(symbol_regions) = env.symbol_regions.as_deref_mut() {
+                symbol_regions.entry(symbol).or_insert(loc_arg.region);
+            }
+
+            with_hole(
+                env,
+                loc_arg.value,
+                arg_var,
+                procs,
+                layout_cache,
+                symbol,
+                env.arena.alloc(result),
+            )
+        }
     }
 }
 
@@ -9881,6 +10011,7 @@ where
             ret_layout: *field,
             is_self_recursive: SelfRecursive::NotSelfRecursive,
             host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+            stack_allocations: &[],
         };
 
         answer.push(GlueProc {
@@ -9976,6 +10107,7 @@ where
             ret_layout: *field,
             is_self_recursive: SelfRecursive::NotSelfRecursive,
             host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+            stack_allocations: &[],
         };
 
         answer.push(GlueProc {
@@ -0,0 +1,719 @@
+//! A textual format for the mono IR, which can be both printed and parsed.
+//!
+//! The [pretty printer][Proc::to_pretty] leaves out most layouts, so its output cannot be read
+//! back in. This format spells out everything that later passes and the backends rely on, so
+//! that they can be tested on hand-written IR:
+//!
+//! ```text
+//! procedure Test.1 (Test.2: List I64) -> U64:
+//!     let Test.3 : U64 = lowlevel ListLen Test.2;
+//!     dec Test.2;
+//!     ret Test.3;
+//! ```
+//!
+//! Symbols are written as `Module.N`, where `N` is the index of the ident in its module. Join
+//! points use the same syntax. Indentation is not significant.
+//!
+//! # Layouts
+//!
+//! - `I8` .. `I128`, `U8` .. `U128`, `F32`, `F64`, `Bool`, `Dec`, `Str`
+//! - `List L`, `Box L`
+//! - `{L1, L2}`: a struct
+//! - `[C L1 L2, C]`: a non-recursive tag union, one `C` per tag
+//! - `[<r> C L *self, C]`, `[<rnnu> C L *self]`, `[<rnu> <null>, C L *self]` and
+//!   `[<rnw> C L *self, <null>, C]`: recursive tag unions, where `*self` points to the
+//!   innermost enclosing union and `<null>` marks the nullable tag
+//!
+//! Lambda sets are written as their runtime representation.
+//!
+//! # Procs
+//!
+//! ```text
+//! procedure f niche {L} (a: L1, b: L2) -> L closure L3 selfrecursive j stack [x, y]:
+//!     <stmt>
+//! ```
+//!
+//! Everything between the return layout and the colon is optional. `stack` lists the
+//! allocations that the backends put in the stack frame, see [`crate::stack_allocation`].
+//!
+//! # Statements
+//!
+//! ```text
+//! let x : L = <expr>;
+//! inc x;  inc 2 x;  dec x;  decref x;
+//! dbg x;  expect x [lookups];  expect-fx x [lookups];
+//! ret x;
+//! crash x;  crash x user;
+//! switch x : L -> L:
+//!     case 0 (y : L is TagId(0)):
+//!         <stmt>
+//!     case 1 (y has len 2):
+//!         <stmt>
+//!     default:
+//!         <stmt>
+//! joinpoint j (p1: L, borrowed p2: L):
+//!     <stmt>
+//! in
+//! <stmt>
+//! jump j a b;
+//! ```
+//!
+//! # Expressions
+//!
+//! ```text
+//! 1i64  1u128  1.5f64  1.5dec  true  1u8  "string"
+//! CallByName f (L1, L2) a b           CallByName f niche {L} (L1) a
+//! lowlevel NumAdd a b                 foreign "name" a b
+//! lowlevel ListMap xs f env with (L1) -> L2 [niche {L}] [env L] [borrowed]
+//! TagId(1) [C I64, C] a               NullPointer
+//! Struct {a, b}                       StructAtIndex 0 {I64, Str} x
+//! GetTagId [C I64, C] x               UnionAtIndex (Id 0) (Index 1) [C I64, C] x
+//! UnionFieldPtrAtIndex (Id 0) (Index 1) [C I64, C] x
+//! Array I64 [1i64, a]                 Array []
+//! Box x                               Unbox x
+//! Reuse x [update] TagId(1) L a b     Reset x        ResetRef x
+//! ErrorFunction "message"
+//! ```
+//!
+//! What the format cannot express is filled in with defaults by the parser: regions and type
+//! variables of `expect` and `dbg`, the field order hashes of structs, host-exposed layouts, and
+//! specialization and update mode ids, which are numbered afresh.
+use std::fmt::Write;
+
+use roc_module::symbol::{ModuleIds, Symbol};
+use roc_std::RocDec;
+
+use crate::borrow::Ownership;
+use crate::layout::{Builtin, InLayout, Layout, LayoutInterner, Niche, UnionLayout};
+
+use super::{
+    BranchInfo, Call, CallType, CrashTag, Expr, ListLiteralElement, Literal, ModifyRc, Proc,
+    SelfRecursive, Stmt,
+};
+
+mod parse;
+
+pub use parse::{parse_procs, ParseError};
+
+/// Print procs in the textual format
+pub fn procs_to_text<'a, 'p, I>(
+    interner: &I,
+    module_ids: &ModuleIds,
+    procs: impl IntoIterator<Item = &'p Proc<'a>>,
+) -> String
+where
+    'a: 'p,
+    I: LayoutInterner<'a>,
+{
+    let mut printer = Printer {
+        interner,
+        module_ids,
+        buf: String::new(),
+        indent: 0,
+    };
+
+    for (i, proc) in procs.into_iter().enumerate() {
+        if i > 0 {
+            printer.buf.push('\n');
+        }
+        printer.proc(proc);
+    }
+
+    printer.buf
+}
+
+struct Printer<'r, I> {
+    interner: &'r I,
+    module_ids: &'r ModuleIds,
+    buf: String,
+    indent: usize,
+}
+
+impl<'a, 'r, I: LayoutInterner<'a>> Printer<'r, I> {
+    fn line(&mut self) {
+        self.buf.push('\n');
+        for _ in 0..self.indent {
+            self.buf.push_str("    ");
+        }
+    }
+
+    fn symbol(&mut self, symbol: Symbol) {
+        let module_name = match self.module_ids.get_name(symbol.module_id()) {
+            Some(name) => name.as_str(),
+            None => roc_error_macros::internal_error!("no name for module of {:?}", symbol),
+        };
+        let _ = write!(self.buf, "{}.{}", module_name, symbol.ident_id().index());
+    }
+
+    fn symbols(&mut self, symbols: &[Symbol], separator: &str) {
+        for (i, symbol) in symbols.iter().enumerate() {
+            if i > 0 {
+                self.buf.push_str(separator);
+            }
+            self.symbol(*symbol);
+        }
+    }
+
+    /// Print symbols as arguments, each preceded by a space
+    fn arguments(&mut self, symbols: &[Symbol]) {
+        for symbol in symbols {
+            self.buf.push(' ');
+            self.symbol(*symbol);
+        }
+    }
+
+    fn proc(&mut self, proc: &Proc<'a>) {
+        self.buf.push_str("procedure ");
+        self.symbol(proc.name.name());
+        self.niche(proc.name.niche());
+        self.buf.push_str(" (");
+        for (i, (layout, symbol)) in proc.args.iter().enumerate() {
+            if i > 0 {
+                self.buf.push_str(", ");
+            }
+            self.symbol(*symbol);
+            self.buf.push_str(": ");
+            self.layout(*layout);
+        }
+        self.buf.push_str(") -> ");
+        self.layout(proc.ret_layout);
+        if let Some(closure_data_layout) = proc.closure_data_layout {
+            self.buf.push_str(" closure ");
+            self.layout_atom(closure_data_layout);
+        }
+        if let SelfRecursive::SelfRecursive(id) = proc.is_self_recursive {
+            self.buf.push_str(" selfrecursive ");
+            self.symbol(id.0);
+        }
+        if !proc.stack_allocations.is_empty() {
+            self.buf.push_str(" stack [");
+            for (i, symbol) in proc.stack_allocations.iter().enumerate() {
+                if i > 0 {
+                    self.buf.push_str(", ");
+                }
+                self.symbol(*symbol);
+            }
+            self.buf.push(']');
+        }
+        self.buf.push(':');
+
+        self.indent += 1;
+        self.line();
+        self.stmt(&proc.body);
+        self.indent -= 1;
+        self.buf.push('\n');
+    }
+
+    fn niche(&mut self, niche: Niche<'a>) {
+        let captures = niche.captures();
+        if !captures.is_empty() {
+            self.buf.push_str(" niche ");
+            self.layout_list("{", captures, "}");
+        }
+    }
+
+    fn stmt(&mut self, stmt: &Stmt<'a>) {
+        let mut stmt = stmt;
+
+        loop {
+            match stmt {
+                Stmt::Let(symbol, expr, layout, rest) => {
+                    self.buf.push_str("let ");
+                    self.symbol(*symbol);
+                    self.buf.push_str(" : ");
+                    self.layout(*layout);
+                    self.buf.push_str(" = ");
+                    self.expr(expr);
+                    self.buf.push(';');
+                    stmt = rest;
+                }
+                Stmt::Refcounting(modify_rc, rest) => {
+                    match modify_rc {
+                        ModifyRc::Inc(symbol, 1) => {
+                            self.buf.push_str("inc ");
+                            self.symbol(*symbol);
+                        }
+                        ModifyRc::Inc(symbol, n) => {
+                            let _ = write!(self.buf, "inc {} ", n);
+                            self.symbol(*symbol);
+                        }
+                        ModifyRc::Dec(symbol) => {
+                            self.buf.push_str("dec ");
+                            self.symbol(*symbol);
+                        }
+                        ModifyRc::DecRef(symbol) => {
+                            self.buf.push_str("decref ");
+                            self.symbol(*symbol);
+                        }
+                    }
+                    self.buf.push(';');
+                    stmt = rest;
+                }
+                Stmt::Dbg {
+                    symbol, remainder, ..
+                } => {
+                    self.buf.push_str("dbg ");
+                    self.symbol(*symbol);
+                    self.buf.push(';');
+                    stmt = remainder;
+                }
+                Stmt::Expect {
+                    condition,
+                    lookups,
+                    remainder,
+                    ..
+                }
+                | Stmt::ExpectFx {
+                    condition,
+                    lookups,
+                    remainder,
+                    ..
+                } => {
+                    let keyword = match stmt {
+                        Stmt::Expect { .. } => "expect ",
+                        _ => "expect-fx ",
+                    };
+                    self.buf.push_str(keyword);
+                    self.symbol(*condition);
+                    if !lookups.is_empty() {
+                        self.buf.push_str(" [");
+                        self.symbols(lookups, ", ");
+                        self.buf.push(']');
+                    }
+                    self.buf.push(';');
+                    stmt = remainder;
+                }
+                Stmt::Join {
+                    id,
+                    parameters,
+                    body,
+                    remainder,
+                } => {
+                    self.buf.push_str("joinpoint ");
+                    self.symbol(id.0);
+                    self.buf.push_str(" (");
+                    for (i, param) in parameters.iter().enumerate() {
+                        if i > 0 {
+                            self.buf.push_str(", ");
+                        }
+                        if param.ownership == Ownership::Borrowed {
+                            self.buf.push_str("borrowed ");
+                        }
+                        self.symbol(param.symbol);
+                        self.buf.push_str(": ");
+                        self.layout(param.layout);
+                    }
+                    self.buf.push_str("):");
+
+                    self.indent += 1;
+                    self.line();
+                    self.stmt(body);
+                    self.indent -= 1;
+
+                    self.line();
+                    self.buf.push_str("in");
+                    stmt = remainder;
+                }
+                Stmt::Switch {
+                    cond_symbol,
+                    cond_layout,
+                    branches,
+                    default_branch,
+                    ret_layout,
+                } => {
+                    self.buf.push_str("switch ");
+                    self.symbol(*cond_symbol);
+                    self.buf.push_str(" : ");
+                    self.layout(*cond_layout);
+                    self.buf.push_str(" -> ");
+                    self.layout(*ret_layout);
+                    self.buf.push(':');
+
+                    self.indent += 1;
+                    for (value, info, branch) in branches.iter() {
+                        self.line();
+                        let _ = write!(self.buf, "case {}", value);
+                        self.branch(info, branch);
+                    }
+                    self.line();
+                    self.buf.push_str("default");
+                    self.branch(&default_branch.0, default_branch.1);
+                    self.indent -= 1;
+
+                    return;
+                }
+                Stmt::Ret(symbol) => {
+                    self.buf.push_str("ret ");
+                    self.symbol(*symbol);
+                    self.buf.push(';');
+                    return;
+                }
+                Stmt::Jump(id, arguments) => {
+                    self.buf.push_str("jump ");
+                    self.symbol(id.0);
+                    self.arguments(arguments);
+                    self.buf.push(';');
+                    return;
+                }
+                Stmt::Crash(symbol, tag) => {
+                    self.buf.push_str("crash ");
+                    self.symbol(*symbol);
+                    if *tag == CrashTag::User {
+                        self.buf.push_str(" user");
+                    }
+                    self.buf.push(';');
+                    return;
+                }
+            }
+
+            self.line();
+        }
+    }
+
+    fn branch(&mut self, info: &BranchInfo<'a>, branch: &Stmt<'a>) {
+        match info {
+            BranchInfo::None => {}
+            BranchInfo::Constructor {
+                scrutinee,
+                layout,
+                tag_id,
+            } => {
+                self.buf.push_str(" (");
+                self.symbol(*scrutinee);
+                self.buf.push_str(" : ");
+                self.layout(*layout);
+                let _ = write!(self.buf, " is TagId({}))", tag_id);
+            }
+            BranchInfo::List { scrutinee, len } => {
+                self.buf.push_str(" (");
+                self.symbol(*scrutinee);
+                let _ = write!(self.buf, " has len {})", len);
+            }
+        }
+        self.buf.push(':');
+
+        self.indent += 1;
+        self.line();
+        self.stmt(branch);
+        self.indent -= 1;
+    }
+
+    fn expr(&mut self, expr: &Expr<'a>) {
+        match expr {
+            Expr::Literal(literal) => self.literal(literal),
+            Expr::Call(call) => self.call(call),
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+            } => {
+                let _ = write!(self.buf, "TagId({}) ", tag_id);
+                self.union_layout(*tag_layout);
+                self.arguments(arguments);
+            }
+            Expr::Struct(fields) => {
+                self.buf.push_str("Struct {");
+                self.symbols(fields, ", ");
+                self.buf.push('}');
+            }
+            Expr::NullPointer => self.buf.push_str("NullPointer"),
+            Expr::StructAtIndex {
+                index,
+                field_layouts,
+                structure,
+            } => {
+                let _ = write!(self.buf, "StructAtIndex {} ", index);
+                self.layout_list("{", field_layouts, "}");
+                self.buf.push(' ');
+                self.symbol(*structure);
+            }
+            Expr::GetTagId {
+                structure,
+                union_layout,
+            } => {
+                self.buf.push_str("GetTagId ");
+                self.union_layout(*union_layout);
+                self.buf.push(' ');
+                self.symbol(*structure);
+            }
+            Expr::UnionAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => {
+                let _ = write!(self.buf, "UnionAtIndex (Id {}) (Index {}) ", tag_id, index);
+                self.union_layout(*union_layout);
+                self.buf.push(' ');
+                self.symbol(*structure);
+            }
+            Expr::UnionFieldPtrAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => {
+                let _ = write!(
+                    self.buf,
+                    "UnionFieldPtrAtIndex (Id {}) (Index {}) ",
+                    tag_id, index
+                );
+                self.union_layout(*union_layout);
+                self.buf.push(' ');
+                self.symbol(*structure);
+            }
+            Expr::Array { elem_layout, elems } => {
+                self.buf.push_str("Array ");
+                self.layout_atom(*elem_layout);
+                self.buf.push_str(" [");
+                for (i, elem) in elems.iter().enumerate() {
+                    if i > 0 {
+                        self.buf.push_str(", ");
+                    }
+                    match elem {
+                        ListLiteralElement::Literal(literal) => self.literal(literal),
+                        ListLiteralElement::Symbol(symbol) => self.symbol(*symbol),
+                    }
+                }
+                self.buf.push(']');
+            }
+            Expr::EmptyArray => self.buf.push_str("Array []"),
+            Expr::ExprBox { symbol } => {
+                self.buf.push_str("Box ");
+                self.symbol(*symbol);
+            }
+            Expr::ExprUnbox { symbol } => {
+                self.buf.push_str("Unbox ");
+                self.symbol(*symbol);
+            }
+            Expr::Reuse {
+                symbol,
+                update_tag_id,
+                tag_layout,
+                tag_id,
+                arguments,
+                ..
+            } => {
+                self.buf.push_str("Reuse ");
+                self.symbol(*symbol);
+                if *update_tag_id {
+                    self.buf.push_str(" update");
+                }
+                let _ = write!(self.buf, " TagId({}) ", tag_id);
+                self.union_layout(*tag_layout);
+                self.arguments(arguments);
+            }
+            Expr::Reset { symbol, .. } => {
+                self.buf.push_str("Reset ");
+                self.symbol(*symbol);
+            }
+            Expr::ResetRef { symbol, .. } => {
+                self.buf.push_str("ResetRef ");
+                self.symbol(*symbol);
+            }
+            Expr::RuntimeErrorFunction(message) => {
+                let _ = write!(self.buf, "ErrorFunction {:?}", message);
+            }
+        }
+    }
+
+    fn literal(&mut self, literal: &Literal<'a>) {
+        let _ = match literal {
+            Literal::Int(bytes) => write!(self.buf, "{}i64", i128::from_ne_bytes(*bytes)),
+            Literal::U128(bytes) => write!(self.buf, "{}u128", u128::from_ne_bytes(*bytes)),
+            Literal::Float(float) => write!(self.buf, "{}f64", float),
+            Literal::Decimal(bytes) => write!(self.buf, "{}dec", RocDec::from_ne_bytes(*bytes)),
+            Literal::Bool(b) => write!(self.buf, "{}", b),
+            Literal::Byte(byte) => write!(self.buf, "{}u8", byte),
+            Literal::Str(string) => write!(self.buf, "{:?}", string),
+        };
+    }
+
+    fn call(&mut self, call: &Call<'a>) {
+        match &call.call_type {
+            CallType::ByName {
+                name, arg_layouts, ..
+            } => {
+                self.buf.push_str("CallByName ");
+                self.symbol(name.name());
+                self.niche(name.niche());
+                self.buf.push(' ');
+                self.layout_list("(", arg_layouts, ")");
+                self.arguments(call.arguments);
+            }
+            CallType::Foreign { foreign_symbol, .. } => {
+                let _ = write!(self.buf, "foreign {:?}", foreign_symbol.as_str());
+                self.arguments(call.arguments);
+            }
+            CallType::LowLevel { op, .. } => {
+                let _ = write!(self.buf, "lowlevel {:?}", op);
+                self.arguments(call.arguments);
+            }
+            CallType::HigherOrder(higher_order) => {
+                let passed_function = &higher_order.passed_function;
+
+                let _ = write!(self.buf, "lowlevel {:?}", higher_order.op.lowlevel());
+                self.arguments(call.arguments);
+                self.buf.push_str(" with ");
+                self.layout_list("(", passed_function.argument_layouts, ")");
+                self.buf.push_str(" -> ");
+                self.layout(passed_function.return_layout);
+                self.niche(passed_function.name.niche());
+                if let Some(env_layout) = higher_order.closure_env_layout {
+                    self.buf.push_str(" env ");
+                    self.layout_atom(env_layout);
+                }
+                if !passed_function.owns_captured_environment {
+                    self.buf.push_str(" borrowed");
+                }
+            }
+        }
+    }
+
+    fn layout_list(&mut self, open: &str, layouts: &[InLayout<'a>], close: &str) {
+        self.buf.push_str(open);
+        for (i, layout) in layouts.iter().enumerate() {
+            if i > 0 {
+                self.buf.push_str(", ");
+            }
+            self.layout(*layout);
+        }
+        self.buf.push_str(close);
+    }
+
+    /// A layout that does not need parentheses to be an argument, e.g. of `List`
+    fn layout_atom(&mut self, layout: InLayout<'a>) {
+        match self.interner.runtime_representation(layout) {
+            Layout::Builtin(Builtin::List(_)) | Layout::Boxed(_) => {
+                self.buf.push('(');
+                self.layout(layout);
+                self.buf.push(')');
+            }
+            _ => self.layout(layout),
+        }
+    }
+
+    fn layout(&mut self, layout: InLayout<'a>) {
+        self.layout_help(layout, false)
+    }
+
+    fn layout_help(&mut self, layout: InLayout<'a>, in_recursive_union: bool) {
+        use roc_builtins::bitcode::{FloatWidth, IntWidth};
+
+        match self.interner.get(layout) {
+            Layout::Builtin(builtin) => match builtin {
+                Builtin::Int(width) => self.buf.push_str(match width {
+                    IntWidth::U8 => "U8",
+                    IntWidth::U16 => "U16",
+                    IntWidth::U32 => "U32",
+                    IntWidth::U64 => "U64",
+                    IntWidth::U128 => "U128",
+                    IntWidth::I8 => "I8",
+                    IntWidth::I16 => "I16",
+                    IntWidth::I32 => "I32",
+                    IntWidth::I64 => "I64",
+                    IntWidth::I128 => "I128",
+                }),
+                Builtin::Float(FloatWidth::F32) => self.buf.push_str("F32"),
+                Builtin::Float(FloatWidth::F64) => self.buf.push_str("F64"),
+                Builtin::Bool => self.buf.push_str("Bool"),
+                Builtin::Decimal => self.buf.push_str("Dec"),
+                Builtin::Str => self.buf.push_str("Str"),
+                Builtin::List(element) => {
+                    self.buf.push_str("List ");
+                    self.layout_atom_help(element, in_recursive_union);
+                }
+            },
+            Layout::Struct { field_layouts, .. } => {
+                self.buf.push('{');
+                for (i, field) in field_layouts.iter().enumerate() {
+                    if i > 0 {
+                        self.buf.push_str(", ");
+                    }
+                    self.layout_help(*field, in_recursive_union);
+                }
+                self.buf.push('}');
+            }
+            Layout::Boxed(inner) => {
+                self.buf.push_str("Box ");
+                self.layout_atom_help(inner, in_recursive_union);
+            }
+            Layout::Union(union_layout) => self.union_layout_help(union_layout, in_recursive_union),
+            Layout::LambdaSet(lambda_set) => {
+                self.layout_help(lambda_set.runtime_representation(), in_recursive_union)
+            }
+            Layout::RecursivePointer(union) => {
+                if in_recursive_union {
+                    self.buf.push_str("*self");
+                } else {
+                    // Outside of its union, a recursive pointer is just the union
+                    self.layout_help(union, false);
+                }
+            }
+        }
+    }
+
+    fn layout_atom_help(&mut self, layout: InLayout<'a>, in_recursive_union: bool) {
+        match self.interner.runtime_representation(layout) {
+            Layout::Builtin(Builtin::List(_)) | Layout::Boxed(_) => {
+                self.buf.push('(');
+                self.layout_help(layout, in_recursive_union);
+                self.buf.push(')');
+            }
+            _ => self.layout_help(layout, in_recursive_union),
+        }
+    }
+
+    fn union_layout(&mut self, union_layout: UnionLayout<'a>) {
+        self.union_layout_help(union_layout, false)
+    }
+
+    fn union_layout_help(&mut self, union_layout: UnionLayout<'a>, in_recursive_union: bool) {
+        let (kind, tags, null_position): (&str, &[&[InLayout<'a>]], Option<usize>) =
+            match union_layout {
+                UnionLayout::NonRecursive(tags) => ("", tags, None),
+                UnionLayout::Recursive(tags) => ("<r> ", tags, None),
+                UnionLayout::NonNullableUnwrapped(fields) => {
+                    ("<rnnu> ", std::slice::from_ref(&fields), None)
+                }
+                UnionLayout::NullableUnwrapped {
+                    nullable_id,
+                    other_fields,
+                } => (
+                    "<rnu> ",
+                    std::slice::from_ref(&other_fields),
+                    Some(nullable_id as usize),
+                ),
+                UnionLayout::NullableWrapped {
+                    nullable_id,
+                    other_tags,
+                } => ("<rnw> ", other_tags, Some(nullable_id as usize)),
+            };
+        let in_recursive_union =
+            in_recursive_union || !matches!(union_layout, UnionLayout::NonRecursive(_));
+
+        self.buf.push('[');
+        self.buf.push_str(kind);
+
+        let mut tags = tags.iter();
+        let tag_count = tags.len() + null_position.is_some() as usize;
+        for tag_id in 0..tag_count {
+            if tag_id > 0 {
+                self.buf.push_str(", ");
+            }
+
+            if null_position == Some(tag_id) {
+                self.buf.push_str("<null>");
+                continue;
+            }
+
+            self.buf.push('C');
+            for field in tags.next().unwrap().iter() {
+                self.buf.push(' ');
+                self.layout_atom_help(*field, in_recursive_union);
+            }
+        }
+
+        self.buf.push(']');
+    }
+}
@@ -0,0 +1,1149 @@
+//! Parser for the [textual mono IR][super].
+use std::fmt;
+
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_module::ident::{ForeignSymbol, ModuleName};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{IdentId, ModuleIds, Symbol};
+use roc_region::all::Region;
+use roc_types::subs::Variable;
+
+use crate::borrow::Ownership;
+use crate::layout::{
+    Builtin, InLayout, LambdaName, Layout, LayoutInterner, Niche, TagIdIntType, UnionLayout,
+};
+use crate::low_level::HigherOrder;
+
+use crate::ir::{
+    BranchInfo, Call, CallSpecId, CallType, CrashTag, Expr, HigherOrderLowLevel,
+    HostExposedLayouts, JoinPointId, ListLiteralElement, Literal, ModifyRc, Param, PassedFunction,
+    Proc, SelfRecursive, Stmt, UpdateModeId,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// 1-based
+    pub line: usize,
+    /// 1-based
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Parse procs in the textual format.
+///
+/// Modules that are not in `module_ids` yet are added to it.
+pub fn parse_procs<'a, I>(
+    arena: &'a Bump,
+    interner: &mut I,
+    module_ids: &mut ModuleIds,
+    src: &str,
+) -> Result<std::vec::Vec<Proc<'a>>, ParseError>
+where
+    I: LayoutInterner<'a>,
+{
+    let tokens = lex(src)?;
+    let mut parser = Parser {
+        arena,
+        interner,
+        module_ids,
+        tokens,
+        position: 0,
+        next_id: 0,
+        in_recursive_union: false,
+        saw_recursion_pointer: false,
+    };
+
+    let mut procs = std::vec::Vec::new();
+    while !parser.at_end() {
+        procs.push(parser.proc()?);
+    }
+
+    Ok(procs)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token<'s> {
+    /// A keyword, symbol, number or layout name
+    Word(&'s str),
+    Str(String),
+    Punct(char),
+}
+
+impl<'s> fmt::Display for Token<'s> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "`{}`", word),
+            Token::Str(string) => write!(f, "{:?}", string),
+            Token::Punct(c) => write!(f, "`{}`", c),
+        }
+    }
+}
+
+const PUNCTUATION: &[char] = &['(', ')', '[', ']', '{', '}', ',', ';', ':', '='];
+
+struct Located<'s> {
+    token: Token<'s>,
+    line: usize,
+    column: usize,
+}
+
+fn lex(src: &str) -> Result<std::vec::Vec<Located<'_>>, ParseError> {
+    let mut tokens = std::vec::Vec::new();
+
+    for (line_index, line) in src.lines().enumerate() {
+        let mut chars = line.char_indices().peekable();
+
+        while let Some(&(start, c)) = chars.peek() {
+            let error = |message: &str| ParseError {
+                line: line_index + 1,
+                column: start + 1,
+                message: message.to_string(),
+            };
+
+            let token = if c.is_whitespace() {
+                chars.next();
+                continue;
+            } else if line[start..].starts_with("//") {
+                break;
+            } else if PUNCTUATION.contains(&c) {
+                chars.next();
+                Token::Punct(c)
+            } else if c == '"' {
+                chars.next();
+                let mut string = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => string.push('\n'),
+                            Some((_, 't')) => string.push('\t'),
+                            Some((_, 'r')) => string.push('\r'),
+                            Some((_, '0')) => string.push('\0'),
+                            Some((_, 'u')) => {
+                                let mut hex = String::new();
+                                if !matches!(chars.next(), Some((_, '{'))) {
+                                    return Err(error("expected `{` in a unicode escape"));
+                                }
+                                for (_, c) in chars.by_ref() {
+                                    if c == '}' {
+                                        break;
+                                    }
+                                    hex.push(c);
+                                }
+                                match u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                                    Some(c) => string.push(c),
+                                    None => return Err(error("invalid unicode escape")),
+                                }
+                            }
+                            Some((_, c)) => string.push(c),
+                            None => return Err(error("unterminated string")),
+                        },
+                        Some((_, c)) => string.push(c),
+                        None => return Err(error("unterminated string")),
+                    }
+                }
+                Token::Str(string)
+            } else {
+                let mut end = line.len();
+                while let Some(&(index, c)) = chars.peek() {
+                    if c.is_whitespace() || PUNCTUATION.contains(&c) || c == '"' {
+                        end = index;
+                        break;
+                    }
+                    chars.next();
+                }
+                Token::Word(&line[start..end])
+            };
+
+            tokens.push(Located {
+                token,
+                line: line_index + 1,
+                column: start + 1,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a, 'r, 's, I> {
+    arena: &'a Bump,
+    interner: &'r mut I,
+    module_ids: &'r mut ModuleIds,
+    tokens: std::vec::Vec<Located<'s>>,
+    position: usize,
+    /// For fresh specialization and update mode ids
+    next_id: u32,
+    /// Whether `*self` can be used
+    in_recursive_union: bool,
+    saw_recursion_pointer: bool,
+}
+
+type ParseResult<T> = Result<T, ParseError>;
+
+impl<'a, 'r, 's, I: LayoutInterner<'a>> Parser<'a, 'r, 's, I> {
+    // Tokens
+
+    fn at_end(&self) -> bool {
+        self.position >= self.tokens.len()
+    }
+
+    fn peek(&self) -> Option<&Token<'s>> {
+        self.peek_nth(0)
+    }
+
+    fn peek_nth(&self, n: usize) -> Option<&Token<'s>> {
+        self.tokens.get(self.position + n).map(|t| &t.token)
+    }
+
+    fn next(&mut self) -> ParseResult<Token<'s>> {
+        match self.tokens.get(self.position) {
+            Some(located) => {
+                self.position += 1;
+                Ok(located.token.clone())
+            }
+            None => Err(self.error("unexpected end of input")),
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        let (line, column) = match self.tokens.get(self.position) {
+            Some(located) => (located.line, located.column),
+            None => match self.tokens.last() {
+                Some(located) => (located.line, located.column),
+                None => (1, 1),
+            },
+        };
+
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> ParseResult<T> {
+        match self.peek() {
+            Some(token) => Err(self.error(format!("expected {}, found {}", expected, token))),
+            None => Err(self.error(format!("expected {}, found the end of input", expected))),
+        }
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn is_word(&self, word: &str) -> bool {
+        self.peek() == Some(&Token::Word(word))
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        let found = self.is_punct(c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = self.is_word(word);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_punct(&mut self, c: char) -> ParseResult<()> {
+        if self.eat_punct(c) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", c))
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> ParseResult<()> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", word))
+        }
+    }
+
+    fn word(&mut self, expected: &str) -> ParseResult<&'s str> {
+        match self.peek() {
+            Some(&Token::Word(word)) => {
+                self.position += 1;
+                Ok(word)
+            }
+            _ => self.unexpected(expected),
+        }
+    }
+
+    fn string(&mut self) -> ParseResult<String> {
+        match self.peek() {
+            Some(Token::Str(string)) => {
+                let string = string.clone();
+                self.position += 1;
+                Ok(string)
+            }
+            _ => self.unexpected("a string"),
+        }
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> ParseResult<T> {
+        let position = self.position;
+        let word = self.word("a number")?;
+        match word.parse() {
+            Ok(n) => Ok(n),
+            Err(_) => {
+                self.position = position;
+                self.unexpected("a number")
+            }
+        }
+    }
+
+    fn fresh_ids(&mut self) -> (CallSpecId, UpdateModeId) {
+        let id = self.next_id;
+        self.next_id += 1;
+        (CallSpecId { id }, UpdateModeId { id })
+    }
+
+    // Symbols
+
+    fn is_symbol(&self) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if split_symbol(word).is_some())
+    }
+
+    fn symbol(&mut self) -> ParseResult<Symbol> {
+        let (module_name, ident_index) = match self.peek() {
+            Some(Token::Word(word)) => match split_symbol(word) {
+                Some(parts) => parts,
+                None => return self.unexpected("a symbol"),
+            },
+            _ => return self.unexpected("a symbol"),
+        };
+        self.position += 1;
+
+        let module_id = self
+            .module_ids
+            .get_or_insert(&ModuleName::from(module_name));
+        // Safety: the ident only needs a name for debug printing
+        let ident_id = unsafe { IdentId::from_index(ident_index) };
+
+        Ok(Symbol::new(module_id, ident_id))
+    }
+
+    /// Symbols up to the next token that is not a symbol
+    fn symbols(&mut self) -> ParseResult<&'a [Symbol]> {
+        let mut symbols = Vec::new_in(self.arena);
+        while self.is_symbol() {
+            symbols.push(self.symbol()?);
+        }
+        Ok(symbols.into_bump_slice())
+    }
+
+    /// Comma-separated symbols between `open` and `close`
+    fn symbol_list(&mut self, open: char, close: char) -> ParseResult<&'a [Symbol]> {
+        let mut symbols = Vec::new_in(self.arena);
+        self.expect_punct(open)?;
+        while !self.eat_punct(close) {
+            if !symbols.is_empty() {
+                self.expect_punct(',')?;
+            }
+            symbols.push(self.symbol()?);
+        }
+        Ok(symbols.into_bump_slice())
+    }
+
+    // Procs
+
+    fn proc(&mut self) -> ParseResult<Proc<'a>> {
+        self.expect_word("procedure")?;
+        let name = self.symbol()?;
+        let niche = self.niche()?;
+
+        let mut args = Vec::new_in(self.arena);
+        self.expect_punct('(')?;
+        while !self.eat_punct(')') {
+            if !args.is_empty() {
+                self.expect_punct(',')?;
+            }
+            let symbol = self.symbol()?;
+            self.expect_punct(':')?;
+            args.push((self.layout()?, symbol));
+        }
+
+        self.expect_word("->")?;
+        let ret_layout = self.layout()?;
+
+        let closure_data_layout = if self.eat_word("closure") {
+            Some(self.layout_atom()?)
+        } else {
+            None
+        };
+
+        let is_self_recursive = if self.eat_word("selfrecursive") {
+            SelfRecursive::SelfRecursive(JoinPointId(self.symbol()?))
+        } else {
+            SelfRecursive::NotSelfRecursive
+        };
+
+        let stack_allocations = if self.eat_word("stack") {
+            self.symbol_list('[', ']')?
+        } else {
+            &[]
+        };
+
+        self.expect_punct(':')?;
+        let body = self.stmt()?;
+
+        Ok(Proc {
+            name: LambdaName::with_niche(name, niche),
+            args: args.into_bump_slice(),
+            body,
+            closure_data_layout,
+            ret_layout,
+            is_self_recursive,
+            host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+            stack_allocations,
+        })
+    }
+
+    fn niche(&mut self) -> ParseResult<Niche<'a>> {
+        if self.eat_word("niche") {
+            let captures = self.layout_list('{', '}')?;
+            Ok(Niche::from_captures(captures))
+        } else {
+            Ok(Niche::NONE)
+        }
+    }
+
+    // Statements
+
+    fn stmt(&mut self) -> ParseResult<Stmt<'a>> {
+        let keyword = self.word("a statement")?;
+
+        let stmt = match keyword {
+            "let" => {
+                let symbol = self.symbol()?;
+                self.expect_punct(':')?;
+                let layout = self.layout()?;
+                self.expect_punct('=')?;
+                let mut expr = self.expr()?;
+                self.expect_punct(';')?;
+
+                if let Expr::Call(Call {
+                    call_type:
+                        CallType::ByName { ret_layout, .. } | CallType::Foreign { ret_layout, .. },
+                    ..
+                }) = &mut expr
+                {
+                    *ret_layout = layout;
+                }
+
+                Stmt::Let(symbol, expr, layout, self.rest()?)
+            }
+            "inc" => {
+                let count = if self.is_symbol() { 1 } else { self.number()? };
+                let symbol = self.symbol()?;
+                self.expect_punct(';')?;
+                Stmt::Refcounting(ModifyRc::Inc(symbol, count), self.rest()?)
+            }
+            "dec" | "decref" => {
+                let symbol = self.symbol()?;
+                self.expect_punct(';')?;
+                let modify_rc = match keyword {
+                    "dec" => ModifyRc::Dec(symbol),
+                    _ => ModifyRc::DecRef(symbol),
+                };
+                Stmt::Refcounting(modify_rc, self.rest()?)
+            }
+            "dbg" => {
+                let symbol = self.symbol()?;
+                self.expect_punct(';')?;
+                Stmt::Dbg {
+                    symbol,
+                    variable: Variable::NULL,
+                    remainder: self.rest()?,
+                }
+            }
+            "expect" | "expect-fx" => {
+                let condition = self.symbol()?;
+                let lookups = if self.is_punct('[') {
+                    self.symbol_list('[', ']')?
+                } else {
+                    &[]
+                };
+                let variables = self
+                    .arena
+                    .alloc_slice_fill_copy(lookups.len(), Variable::NULL);
+                self.expect_punct(';')?;
+                let remainder = self.rest()?;
+
+                match keyword {
+                    "expect" => Stmt::Expect {
+                        condition,
+                        region: Region::zero(),
+                        lookups,
+                        variables,
+                        remainder,
+                    },
+                    _ => Stmt::ExpectFx {
+                        condition,
+                        region: Region::zero(),
+                        lookups,
+                        variables,
+                        remainder,
+                    },
+                }
+            }
+            "ret" => {
+                let symbol = self.symbol()?;
+                self.expect_punct(';')?;
+                Stmt::Ret(symbol)
+            }
+            "jump" => {
+                let id = JoinPointId(self.symbol()?);
+                let arguments = self.symbols()?;
+                self.expect_punct(';')?;
+                Stmt::Jump(id, arguments)
+            }
+            "crash" => {
+                let symbol = self.symbol()?;
+                let tag = if self.eat_word("user") {
+                    CrashTag::User
+                } else {
+                    CrashTag::Roc
+                };
+                self.expect_punct(';')?;
+                Stmt::Crash(symbol, tag)
+            }
+            "joinpoint" => {
+                let id = JoinPointId(self.symbol()?);
+
+                let mut parameters = Vec::new_in(self.arena);
+                self.expect_punct('(')?;
+                while !self.eat_punct(')') {
+                    if !parameters.is_empty() {
+                        self.expect_punct(',')?;
+                    }
+                    let ownership = if self.eat_word("borrowed") {
+                        Ownership::Borrowed
+                    } else {
+                        Ownership::Owned
+                    };
+                    let symbol = self.symbol()?;
+                    self.expect_punct(':')?;
+                    let layout = self.layout()?;
+                    parameters.push(Param {
+                        symbol,
+                        ownership,
+                        layout,
+                    });
+                }
+                self.expect_punct(':')?;
+
+                let body = self.rest()?;
+                self.expect_word("in")?;
+                let remainder = self.rest()?;
+
+                Stmt::Join {
+                    id,
+                    parameters: parameters.into_bump_slice(),
+                    body,
+                    remainder,
+                }
+            }
+            "switch" => {
+                let cond_symbol = self.symbol()?;
+                self.expect_punct(':')?;
+                let cond_layout = self.layout()?;
+                self.expect_word("->")?;
+                let ret_layout = self.layout()?;
+                self.expect_punct(':')?;
+
+                let mut branches = Vec::new_in(self.arena);
+                while self.eat_word("case") {
+                    let value = self.number()?;
+                    let info = self.branch_info()?;
+                    self.expect_punct(':')?;
+                    branches.push((value, info, self.stmt()?));
+                }
+
+                self.expect_word("default")?;
+                let default_info = self.branch_info()?;
+                self.expect_punct(':')?;
+                let default_branch = (default_info, self.rest()?);
+
+                Stmt::Switch {
+                    cond_symbol,
+                    cond_layout,
+                    branches: branches.into_bump_slice(),
+                    default_branch,
+                    ret_layout,
+                }
+            }
+            _ => {
+                self.position -= 1;
+                return self.unexpected("a statement");
+            }
+        };
+
+        Ok(stmt)
+    }
+
+    fn rest(&mut self) -> ParseResult<&'a Stmt<'a>> {
+        let stmt = self.stmt()?;
+        Ok(self.arena.alloc(stmt))
+    }
+
+    fn branch_info(&mut self) -> ParseResult<BranchInfo<'a>> {
+        if !self.eat_punct('(') {
+            return Ok(BranchInfo::None);
+        }
+
+        let scrutinee = self.symbol()?;
+        let info = if self.eat_word("has") {
+            self.expect_word("len")?;
+            BranchInfo::List {
+                scrutinee,
+                len: self.number()?,
+            }
+        } else {
+            self.expect_punct(':')?;
+            let layout = self.layout()?;
+            self.expect_word("is")?;
+            BranchInfo::Constructor {
+                scrutinee,
+                layout,
+                tag_id: self.tag_id()?,
+            }
+        };
+        self.expect_punct(')')?;
+
+        Ok(info)
+    }
+
+    // Expressions
+
+    fn expr(&mut self) -> ParseResult<Expr<'a>> {
+        match self.peek() {
+            Some(Token::Str(_)) => return Ok(Expr::Literal(self.literal()?)),
+            Some(Token::Word(_)) => {}
+            _ => return self.unexpected("an expression"),
+        }
+
+        let position = self.position;
+        let keyword = self.word("an expression")?;
+
+        let expr = match keyword {
+            "CallByName" => {
+                let name = self.symbol()?;
+                let niche = self.niche()?;
+                let arg_layouts = self.layout_list('(', ')')?;
+                let arguments = self.symbols()?;
+
+                // The result layout is filled in from the `let`
+                let (specialization_id, _) = self.fresh_ids();
+                Expr::Call(Call {
+                    call_type: CallType::ByName {
+                        name: LambdaName::with_niche(name, niche),
+                        ret_layout: Layout::VOID,
+                        arg_layouts,
+                        specialization_id,
+                    },
+                    arguments,
+                })
+            }
+            "foreign" => {
+                let name = self.string()?;
+                let arguments = self.symbols()?;
+                Expr::Call(Call {
+                    call_type: CallType::Foreign {
+                        foreign_symbol: ForeignSymbol::from(name),
+                        ret_layout: Layout::VOID,
+                    },
+                    arguments,
+                })
+            }
+            "lowlevel" => self.lowlevel()?,
+            "TagId" => {
+                self.position -= 1;
+                let tag_id = self.tag_id()?;
+                let tag_layout = self.expr_union_layout()?;
+                Expr::Tag {
+                    tag_layout,
+                    tag_id,
+                    arguments: self.symbols()?,
+                }
+            }
+            "NullPointer" => Expr::NullPointer,
+            "Struct" => Expr::Struct(self.symbol_list('{', '}')?),
+            "StructAtIndex" => Expr::StructAtIndex {
+                index: self.number()?,
+                field_layouts: self.layout_list('{', '}')?,
+                structure: self.symbol()?,
+            },
+            "GetTagId" => Expr::GetTagId {
+                union_layout: self.expr_union_layout()?,
+                structure: self.symbol()?,
+            },
+            "UnionAtIndex" => {
+                let (tag_id, index) = self.tag_id_and_index()?;
+                Expr::UnionAtIndex {
+                    tag_id,
+                    index,
+                    union_layout: self.expr_union_layout()?,
+                    structure: self.symbol()?,
+                }
+            }
+            "UnionFieldPtrAtIndex" => {
+                let (tag_id, index) = self.tag_id_and_index()?;
+                Expr::UnionFieldPtrAtIndex {
+                    tag_id,
+                    index,
+                    union_layout: self.expr_union_layout()?,
+                    structure: self.symbol()?,
+                }
+            }
+            "Array" => {
+                let is_empty = self.is_punct('[')
+                    && self.peek_nth(1) == Some(&Token::Punct(']'))
+                    && self.peek_nth(2) != Some(&Token::Punct('['));
+                if is_empty {
+                    self.position += 2;
+                    Expr::EmptyArray
+                } else {
+                    let elem_layout = self.layout_atom()?;
+                    let mut elems = Vec::new_in(self.arena);
+                    self.expect_punct('[')?;
+                    while !self.eat_punct(']') {
+                        if !elems.is_empty() {
+                            self.expect_punct(',')?;
+                        }
+                        if self.is_symbol() {
+                            elems.push(ListLiteralElement::Symbol(self.symbol()?));
+                        } else {
+                            elems.push(ListLiteralElement::Literal(self.literal()?));
+                        }
+                    }
+                    Expr::Array {
+                        elem_layout,
+                        elems: elems.into_bump_slice(),
+                    }
+                }
+            }
+            "Box" => Expr::ExprBox {
+                symbol: self.symbol()?,
+            },
+            "Unbox" => Expr::ExprUnbox {
+                symbol: self.symbol()?,
+            },
+            "Reuse" => {
+                let symbol = self.symbol()?;
+                let update_tag_id = self.eat_word("update");
+                let tag_id = self.tag_id()?;
+                let tag_layout = self.expr_union_layout()?;
+                let (_, update_mode) = self.fresh_ids();
+                Expr::Reuse {
+                    symbol,
+                    update_tag_id,
+                    update_mode,
+                    tag_layout,
+                    tag_id,
+                    arguments: self.symbols()?,
+                }
+            }
+            "Reset" | "ResetRef" => {
+                let symbol = self.symbol()?;
+                let (_, update_mode) = self.fresh_ids();
+                match keyword {
+                    "Reset" => Expr::Reset {
+                        symbol,
+                        update_mode,
+                    },
+                    _ => Expr::ResetRef {
+                        symbol,
+                        update_mode,
+                    },
+                }
+            }
+            "ErrorFunction" => {
+                let message = self.string()?;
+                Expr::RuntimeErrorFunction(self.arena.alloc_str(&message))
+            }
+            _ => {
+                self.position = position;
+                Expr::Literal(self.literal()?)
+            }
+        };
+
+        Ok(expr)
+    }
+
+    fn tag_id(&mut self) -> ParseResult<TagIdIntType> {
+        let position = self.position;
+        match self.word("`TagId`")? {
+            "TagId" => {}
+            _ => {
+                self.position = position;
+                return self.unexpected("`TagId`");
+            }
+        }
+        self.expect_punct('(')?;
+        let tag_id = self.number()?;
+        self.expect_punct(')')?;
+        Ok(tag_id)
+    }
+
+    fn lowlevel(&mut self) -> ParseResult<Expr<'a>> {
+        let position = self.position;
+        let name = self.word("a lowlevel")?;
+        let op = match LowLevel::ALL.iter().find(|op| format!("{:?}", op) == name) {
+            Some(op) => *op,
+            None => {
+                self.position = position;
+                return self.unexpected("a lowlevel");
+            }
+        };
+        let arguments = self.symbols()?;
+
+        if !op.is_higher_order() {
+            let (_, update_mode) = self.fresh_ids();
+            return Ok(Expr::Call(Call {
+                call_type: CallType::LowLevel { op, update_mode },
+                arguments,
+            }));
+        }
+
+        // The lists, then the function and its captured environment
+        let list = |i: usize| arguments[i];
+        let lists = arguments.len().saturating_sub(2);
+        let higher_order_op = match (op, lists) {
+            (LowLevel::ListMap, 1) => HigherOrder::ListMap { xs: list(0) },
+            (LowLevel::ListMap2, 2) => HigherOrder::ListMap2 {
+                xs: list(0),
+                ys: list(1),
+            },
+            (LowLevel::ListMap3, 3) => HigherOrder::ListMap3 {
+                xs: list(0),
+                ys: list(1),
+                zs: list(2),
+            },
+            (LowLevel::ListMap4, 4) => HigherOrder::ListMap4 {
+                xs: list(0),
+                ys: list(1),
+                zs: list(2),
+                ws: list(3),
+            },
+            (LowLevel::ListSortWith, 1) => HigherOrder::ListSortWith { xs: list(0) },
+            _ => {
+                return Err(self.error(format!(
+                    "{:?} does not take {} arguments",
+                    op,
+                    arguments.len()
+                )))
+            }
+        };
+
+        self.expect_word("with")?;
+        let argument_layouts = self.layout_list('(', ')')?;
+        self.expect_word("->")?;
+        let return_layout = self.layout()?;
+        let niche = self.niche()?;
+        let closure_env_layout = if self.eat_word("env") {
+            Some(self.layout_atom()?)
+        } else {
+            None
+        };
+        let owns_captured_environment = !self.eat_word("borrowed");
+
+        let (specialization_id, update_mode) = self.fresh_ids();
+        let higher_order = HigherOrderLowLevel {
+            op: higher_order_op,
+            closure_env_layout,
+            update_mode,
+            passed_function: PassedFunction {
+                name: LambdaName::with_niche(arguments[higher_order_op.function_index()], niche),
+                argument_layouts,
+                return_layout,
+                specialization_id,
+                captured_environment: arguments[higher_order_op.closure_data_index()],
+                owns_captured_environment,
+            },
+        };
+
+        Ok(Expr::Call(Call {
+            call_type: CallType::HigherOrder(self.arena.alloc(higher_order)),
+            arguments,
+        }))
+    }
+
+    fn literal(&mut self) -> ParseResult<Literal<'a>> {
+        if let Some(Token::Str(_)) = self.peek() {
+            let string = self.string()?;
+            return Ok(Literal::Str(self.arena.alloc_str(&string)));
+        }
+
+        let position = self.position;
+        let word = self.word("a literal")?;
+
+        let literal = if word == "true" || word == "false" {
+            Some(Literal::Bool(word == "true"))
+        } else if let Some(n) = word.strip_suffix("i64") {
+            n.parse::<i128>()
+                .ok()
+                .map(|n| Literal::Int(n.to_ne_bytes()))
+        } else if let Some(n) = word.strip_suffix("u128") {
+            n.parse::<u128>()
+                .ok()
+                .map(|n| Literal::U128(n.to_ne_bytes()))
+        } else if let Some(n) = word.strip_suffix("f64") {
+            n.parse::<f64>().ok().map(Literal::Float)
+        } else if let Some(n) = word.strip_suffix("dec") {
+            parse_dec(n).map(|n| Literal::Decimal(n.to_ne_bytes()))
+        } else if let Some(n) = word.strip_suffix("u8") {
+            n.parse::<u8>().ok().map(Literal::Byte)
+        } else {
+            None
+        };
+
+        match literal {
+            Some(literal) => Ok(literal),
+            None => {
+                self.position = position;
+                self.unexpected("a literal")
+            }
+        }
+    }
+
+    // Layouts
+
+    fn layout_list(&mut self, open: char, close: char) -> ParseResult<&'a [InLayout<'a>]> {
+        let mut layouts = Vec::new_in(self.arena);
+        self.expect_punct(open)?;
+        while !self.eat_punct(close) {
+            if !layouts.is_empty() {
+                self.expect_punct(',')?;
+            }
+            layouts.push(self.layout()?);
+        }
+        Ok(layouts.into_bump_slice())
+    }
+
+    fn layout(&mut self) -> ParseResult<InLayout<'a>> {
+        if self.eat_word("List") {
+            let element = self.layout_atom()?;
+            Ok(self
+                .interner
+                .insert(Layout::Builtin(Builtin::List(element))))
+        } else if self.eat_word("Box") {
+            let inner = self.layout_atom()?;
+            Ok(self.interner.insert(Layout::Boxed(inner)))
+        } else {
+            self.layout_atom()
+        }
+    }
+
+    fn layout_atom(&mut self) -> ParseResult<InLayout<'a>> {
+        if self.eat_punct('(') {
+            let layout = self.layout()?;
+            self.expect_punct(')')?;
+            return Ok(layout);
+        }
+
+        if self.is_punct('{') {
+            let fields = self.layout_list('{', '}')?;
+            return Ok(self.interner.insert(Layout::struct_no_name_order(fields)));
+        }
+
+        if self.is_punct('[') {
+            let (union_layout, refers_to_self) = self.union_layout()?;
+            return Ok(self.insert_union(union_layout, refers_to_self));
+        }
+
+        let position = self.position;
+        let layout = match self.word("a layout")? {
+            "I8" => Layout::I8,
+            "I16" => Layout::I16,
+            "I32" => Layout::I32,
+            "I64" => Layout::I64,
+            "I128" => Layout::I128,
+            "U8" => Layout::U8,
+            "U16" => Layout::U16,
+            "U32" => Layout::U32,
+            "U64" => Layout::U64,
+            "U128" => Layout::U128,
+            "F32" => Layout::F32,
+            "F64" => Layout::F64,
+            "Bool" => Layout::BOOL,
+            "Dec" => Layout::DEC,
+            "Str" => Layout::STR,
+            "*self" if self.in_recursive_union => {
+                self.saw_recursion_pointer = true;
+                Layout::NAKED_RECURSIVE_PTR
+            }
+            "*self" => {
+                self.position = position;
+                return Err(self.error("`*self` can only be used in a recursive union"));
+            }
+            _ => {
+                self.position = position;
+                return self.unexpected("a layout");
+            }
+        };
+
+        Ok(layout)
+    }
+
+    /// Intern a union layout, tying the `*self` pointers of a recursive union back to it
+    fn insert_union(
+        &mut self,
+        union_layout: UnionLayout<'a>,
+        refers_to_self: bool,
+    ) -> InLayout<'a> {
+        let layout = Layout::Union(union_layout);
+        if refers_to_self {
+            self.interner.insert_recursive(self.arena, layout)
+        } else {
+            self.interner.insert(layout)
+        }
+    }
+
+    /// `(Id 0) (Index 1)`
+    fn tag_id_and_index(&mut self) -> ParseResult<(TagIdIntType, u64)> {
+        self.expect_punct('(')?;
+        self.expect_word("Id")?;
+        let tag_id = self.number()?;
+        self.expect_punct(')')?;
+        self.expect_punct('(')?;
+        self.expect_word("Index")?;
+        let index = self.number()?;
+        self.expect_punct(')')?;
+
+        Ok((tag_id, index))
+    }
+
+    /// A union layout as it appears in an expression, with its `*self` pointers resolved
+    fn expr_union_layout(&mut self) -> ParseResult<UnionLayout<'a>> {
+        let (union_layout, refers_to_self) = self.union_layout()?;
+        let layout = self.insert_union(union_layout, refers_to_self);
+        match self.interner.get(layout) {
+            Layout::Union(union_layout) => Ok(union_layout),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Parse a union layout, and whether it is recursive and uses `*self`
+    fn union_layout(&mut self) -> ParseResult<(UnionLayout<'a>, bool)> {
+        self.expect_punct('[')?;
+
+        let kind = match self.peek() {
+            Some(&Token::Word(kind @ ("<r>" | "<rnnu>" | "<rnu>" | "<rnw>"))) => {
+                self.position += 1;
+                Some(kind)
+            }
+            _ => None,
+        };
+
+        // In a non-recursive union, `*self` still refers to the enclosing recursive union
+        let outer_in_recursive_union = self.in_recursive_union;
+        let outer_saw_recursion_pointer = self.saw_recursion_pointer;
+        if kind.is_some() {
+            self.in_recursive_union = true;
+            self.saw_recursion_pointer = false;
+        }
+
+        let mut tags = Vec::new_in(self.arena);
+        let mut null_position = None;
+        while !self.eat_punct(']') {
+            if !tags.is_empty() || null_position.is_some() {
+                self.expect_punct(',')?;
+            }
+
+            if self.eat_word("<null>") {
+                if null_position.is_some() {
+                    return Err(self.error("a union can only have one null tag"));
+                }
+                null_position = Some(tags.len());
+                continue;
+            }
+
+            self.expect_word("C")?;
+            let mut fields = Vec::new_in(self.arena);
+            while !self.is_punct(',') && !self.is_punct(']') {
+                fields.push(self.layout_atom()?);
+            }
+            tags.push(fields.into_bump_slice() as &[_]);
+        }
+
+        let mut refers_to_self = false;
+        if kind.is_some() {
+            refers_to_self = self.saw_recursion_pointer;
+            self.in_recursive_union = outer_in_recursive_union;
+            self.saw_recursion_pointer = outer_saw_recursion_pointer;
+        }
+
+        let tags = tags.into_bump_slice();
+        let union_layout = match (kind, null_position, tags) {
+            (None, None, tags) => UnionLayout::NonRecursive(tags),
+            (Some("<r>"), None, tags) => UnionLayout::Recursive(tags),
+            (Some("<rnnu>"), None, &[fields]) => UnionLayout::NonNullableUnwrapped(fields),
+            (Some("<rnu>"), Some(null_position), &[fields]) if null_position <= 1 => {
+                UnionLayout::NullableUnwrapped {
+                    nullable_id: null_position == 1,
+                    other_fields: fields,
+                }
+            }
+            (Some("<rnw>"), Some(null_position), tags) => UnionLayout::NullableWrapped {
+                nullable_id: null_position as TagIdIntType,
+                other_tags: tags,
+            },
+            _ => return Err(self.error("the tags do not fit the kind of union")),
+        };
+
+        Ok((union_layout, refers_to_self))
+    }
+}
+
+/// Split `Module.N` into the module name and ident index
+fn split_symbol(word: &str) -> Option<(&str, u32)> {
+    let (module_name, ident_index) = word.rsplit_once('.')?;
+    if module_name.is_empty() || !ident_index.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((module_name, ident_index.parse().ok()?))
+}
+
+/// Parse a decimal number into the representation of a `Dec`
+fn parse_dec(string: &str) -> Option<i128> {
+    const DECIMAL_PLACES: usize = 18;
+
+    let (negative, digits) = match string.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, string),
+    };
+    let (whole, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if fraction.len() > DECIMAL_PLACES {
+        return None;
+    }
+
+    let whole: i128 = whole.parse().ok()?;
+    let fraction: i128 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse::<i128>().ok()? * 10i128.pow((DECIMAL_PLACES - fraction.len()) as u32)
+    };
+
+    let n = whole
+        .checked_mul(10i128.pow(DECIMAL_PLACES as u32))?
+        .checked_add(fraction)?;
+    Some(if negative { -n } else { n })
+}
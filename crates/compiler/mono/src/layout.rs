@@ -940,8 +940,10 @@ impl<'a> UnionLayout<'a> {
         self.discriminant().layout()
     }
 
-    fn stores_tag_id_in_pointer_bits(tags: &[&[InLayout<'a>]], target_info: TargetInfo) -> bool {
-        tags.len() < target_info.ptr_width() as usize
+    /// Whether a recursive union with this many (non-null) tags stores its tag id
+    /// in the unused low bits of its pointer.
+    pub fn stores_tag_id_in_pointer_bits(num_tags: usize, target_info: TargetInfo) -> bool {
+        num_tags < target_info.ptr_width() as usize
     }
 
     pub const POINTER_MASK_32BIT: usize = 0b0000_0111;
@@ -961,7 +963,7 @@ impl<'a> UnionLayout<'a> {
             UnionLayout::Recursive(tags)
             | UnionLayout::NullableWrapped {
                 other_tags: tags, ..
-            } => !Self::stores_tag_id_in_pointer_bits(tags, target_info),
+            } => !Self::stores_tag_id_in_pointer_bits(tags.len(), target_info),
             UnionLayout::NonNullableUnwrapped(_) | UnionLayout::NullableUnwrapped { .. } => false,
         }
     }
@@ -972,7 +974,7 @@ impl<'a> UnionLayout<'a> {
             UnionLayout::Recursive(tags)
             | UnionLayout::NullableWrapped {
                 other_tags: tags, ..
-            } => Self::stores_tag_id_in_pointer_bits(tags, target_info),
+            } => Self::stores_tag_id_in_pointer_bits(tags.len(), target_info),
             UnionLayout::NonNullableUnwrapped(_) | UnionLayout::NullableUnwrapped { .. } => false,
         }
     }
@@ -1299,6 +1301,16 @@ pub struct Niche<'a>(NichePriv<'a>);
 impl<'a> Niche<'a> {
     pub const NONE: Niche<'a> = Niche(NichePriv::Captures(&[]));
 
+    /// The niche of a lambda that captures values of these layouts
+    pub(crate) fn from_captures(captures: &'a [InLayout<'a>]) -> Self {
+        Niche(NichePriv::Captures(captures))
+    }
+
+    pub(crate) fn captures(&self) -> &'a [InLayout<'a>] {
+        let NichePriv::Captures(captures) = self.0;
+        captures
+    }
+
     pub fn to_doc<'b, D, A, I>(
         self,
         alloc: &'b D,
@@ -1370,6 +1382,11 @@ impl<'a> LambdaName<'a> {
     pub(crate) fn replace_name(&self, name: Symbol) -> Self {
         Self { name, ..*self }
     }
+
+    #[inline(always)]
+    pub(crate) fn with_niche(name: Symbol, niche: Niche<'a>) -> Self {
+        Self { name, niche }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
@@ -64,7 +64,7 @@ cache_interned_layouts! {
     15, DEC,  pub, Layout::Builtin(Builtin::Decimal)
     16, STR,  pub, Layout::Builtin(Builtin::Str)
     17, OPAQUE_PTR,  pub, Layout::Boxed(Layout::VOID)
-    18, NAKED_RECURSIVE_PTR,  pub(super), Layout::RecursivePointer(Layout::VOID)
+    18, NAKED_RECURSIVE_PTR,  pub(crate), Layout::RecursivePointer(Layout::VOID)
 
     ; 19
 }
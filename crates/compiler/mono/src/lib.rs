@@ -13,11 +13,15 @@ pub mod borrow;
 pub mod code_gen_help;
 pub mod drop_specialization;
 pub mod inc_dec;
+pub mod inline;
 pub mod ir;
 pub mod layout;
 pub mod layout_soa;
 pub mod low_level;
+pub mod missed_updates;
 pub mod reset_reuse;
+pub mod simplify;
+pub mod stack_allocation;
 pub mod tail_recursion;
 
 pub mod debug;
@@ -1,3 +1,4 @@
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -53,6 +54,16 @@ impl HigherOrder {
     pub const fn function_index(&self) -> usize {
         self.closure_data_index() - 1
     }
+
+    pub const fn lowlevel(&self) -> LowLevel {
+        match self {
+            HigherOrder::ListMap { .. } => LowLevel::ListMap,
+            HigherOrder::ListMap2 { .. } => LowLevel::ListMap2,
+            HigherOrder::ListMap3 { .. } => LowLevel::ListMap3,
+            HigherOrder::ListMap4 { .. } => LowLevel::ListMap4,
+            HigherOrder::ListSortWith { .. } => LowLevel::ListSortWith,
+        }
+    }
 }
 
 #[allow(dead_code)]
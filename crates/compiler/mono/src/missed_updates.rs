@@ -0,0 +1,327 @@
+//! Find places where the program copies or allocates memory that it could have updated in place.
+//!
+//! Builtins like `List.set` and `Dict.insert` update their argument in place when it is unique,
+//! and otherwise copy it. After refcount insertion, an argument that is used again after the
+//! call is incremented right before it, so it is always shared during the call and always
+//! copied. Similarly, reset/reuse can only reuse the memory of a value that is released before
+//! a new value of the same layout is allocated; when the old value is still alive at the
+//! allocation, the new value gets fresh memory.
+//!
+//! Mono IR has no source regions, so problems are reported at the regions recorded in
+//! [`SymbolRegions`] while lowering, and are skipped when no region is known.
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::{ModuleId, Symbol};
+use roc_problem::mono::Problem;
+
+use crate::ir::{
+    Call, CallType, Expr, ListLiteralElement, ModifyRc, Proc, ProcLayout, Stmt, SymbolRegions,
+};
+use crate::layout::{
+    InLayout, Layout, LayoutInterner, STLayoutInterner, TagIdIntType, UnionLayout,
+};
+
+/// Builtins that update their first argument in place when it is unique
+const IN_PLACE_BUILTINS: &[Symbol] = &[
+    Symbol::LIST_SET,
+    Symbol::LIST_REPLACE,
+    Symbol::LIST_APPEND,
+    Symbol::LIST_PREPEND,
+    Symbol::LIST_CONCAT,
+    Symbol::LIST_SWAP,
+    Symbol::LIST_DROP_AT,
+    Symbol::LIST_SORT_WITH,
+    Symbol::LIST_RESERVE,
+    Symbol::DICT_INSERT,
+    Symbol::DICT_REMOVE,
+    Symbol::DICT_UPDATE,
+    Symbol::SET_INSERT,
+    Symbol::SET_REMOVE,
+    Symbol::STR_CONCAT,
+];
+
+/// Lowlevels that update their first argument in place when it is unique, for when one of the
+/// builtins above was inlined
+const IN_PLACE_LOWLEVELS: &[LowLevel] = &[
+    LowLevel::ListReplaceUnsafe,
+    LowLevel::ListAppendUnsafe,
+    LowLevel::ListPrepend,
+    LowLevel::ListConcat,
+    LowLevel::ListSwap,
+    LowLevel::ListDropAt,
+    LowLevel::ListSortWith,
+    LowLevel::ListReserve,
+    LowLevel::StrConcat,
+];
+
+/// Find the missed in-place updates of all procs, by the module they should be reported in.
+///
+/// This must run after reset/reuse operations have been inserted.
+pub fn find_missed_updates<'a>(
+    layout_interner: &STLayoutInterner<'a>,
+    procs: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+    symbol_regions: &SymbolRegions,
+) -> MutMap<ModuleId, std::vec::Vec<Problem>> {
+    let mut env = Env {
+        interner: layout_interner,
+        symbol_regions,
+        layouts: MutMap::default(),
+        unpacked: MutSet::default(),
+        problems: MutMap::default(),
+    };
+
+    for proc in procs.values() {
+        env.layouts.clear();
+        env.unpacked.clear();
+
+        for (layout, symbol) in proc.args.iter() {
+            env.layouts.insert(*symbol, *layout);
+        }
+
+        env.check_stmt(&proc.body);
+    }
+
+    env.problems
+}
+
+struct Env<'a, 'r> {
+    interner: &'r STLayoutInterner<'a>,
+    symbol_regions: &'r SymbolRegions,
+    layouts: MutMap<Symbol, InLayout<'a>>,
+    /// Values of a nullable union that are known not to be null, because a field was loaded
+    unpacked: MutSet<Symbol>,
+    /// The same source expression may be specialized several times; each problem is only kept
+    /// once
+    problems: MutMap<ModuleId, std::vec::Vec<Problem>>,
+}
+
+impl<'a, 'r> Env<'a, 'r> {
+    fn check_stmt(&mut self, stmt: &Stmt<'a>) {
+        // the values incremented right before the current statement
+        let mut incremented = std::vec::Vec::new();
+        let mut stmt = stmt;
+
+        loop {
+            match stmt {
+                Stmt::Refcounting(ModifyRc::Inc(symbol, _), cont) => {
+                    incremented.push(*symbol);
+                    stmt = cont;
+                    continue;
+                }
+                Stmt::Let(binding, expr, layout, cont) => {
+                    if let Some(value) = self.in_place_update(expr) {
+                        if incremented.contains(&value) {
+                            self.shared_update(*binding, expr, value, cont);
+                        }
+                    }
+
+                    if let Expr::UnionAtIndex { structure, .. } = expr {
+                        self.unpacked.insert(*structure);
+                    }
+
+                    if let Expr::Tag {
+                        tag_layout, tag_id, ..
+                    } = expr
+                    {
+                        if is_heap_allocated(tag_layout, *tag_id) {
+                            self.allocation(*binding, *layout, cont);
+                        }
+                    }
+
+                    self.layouts.insert(*binding, *layout);
+                    stmt = cont;
+                }
+                Stmt::Refcounting(_, cont) => stmt = cont,
+                Stmt::Expect { remainder, .. }
+                | Stmt::ExpectFx { remainder, .. }
+                | Stmt::Dbg { remainder, .. } => stmt = remainder,
+                Stmt::Switch {
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    for (_, _, branch) in branches.iter() {
+                        self.check_stmt(branch);
+                    }
+                    self.check_stmt(default_branch.1);
+
+                    return;
+                }
+                Stmt::Join {
+                    parameters,
+                    body,
+                    remainder,
+                    ..
+                } => {
+                    for param in parameters.iter() {
+                        self.layouts.insert(param.symbol, param.layout);
+                    }
+
+                    self.check_stmt(body);
+                    self.check_stmt(remainder);
+
+                    return;
+                }
+                Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => return,
+            }
+
+            incremented.clear();
+        }
+    }
+
+    /// The value that `expr` updates in place, if it is unique
+    fn in_place_update(&self, expr: &Expr<'a>) -> Option<Symbol> {
+        let Expr::Call(Call { call_type, arguments }) = expr else {
+            return None;
+        };
+
+        let updates = match call_type {
+            CallType::ByName { name, .. } => IN_PLACE_BUILTINS.contains(&name.name()),
+            CallType::LowLevel { op, .. } => IN_PLACE_LOWLEVELS.contains(op),
+            _ => false,
+        };
+
+        if updates {
+            arguments.first().copied()
+        } else {
+            None
+        }
+    }
+
+    fn shared_update(&mut self, binding: Symbol, expr: &Expr<'a>, value: Symbol, cont: &Stmt<'a>) {
+        let region = match self.symbol_regions.get(&binding) {
+            Some(region) => *region,
+            None => return,
+        };
+
+        let function = match expr {
+            Expr::Call(Call {
+                call_type: CallType::ByName { name, .. },
+                ..
+            }) => Some(name.name()),
+            _ => None,
+        };
+
+        let later_use = first_use(cont, value).and_then(|user| self.symbol_regions.get(&user));
+
+        self.push(
+            binding,
+            Problem::SharedUpdate {
+                region,
+                function,
+                value,
+                later_use: later_use.copied(),
+            },
+        );
+    }
+
+    /// A fresh allocation could have reused the memory of a value of the same layout, if that
+    /// value had not still been alive. It then is released later in the same block.
+    fn allocation(&mut self, binding: Symbol, layout: InLayout<'a>, cont: &Stmt<'a>) {
+        let region = match self.symbol_regions.get(&binding) {
+            Some(region) => *region,
+            None => return,
+        };
+
+        let mut stmt = cont;
+        let freed = loop {
+            match stmt {
+                Stmt::Refcounting(ModifyRc::Dec(symbol), _)
+                    if self.layouts.get(symbol) == Some(&layout) && self.is_allocated(*symbol) =>
+                {
+                    break *symbol;
+                }
+                Stmt::Let(_, _, _, cont) | Stmt::Refcounting(_, cont) => stmt = cont,
+                Stmt::Expect { remainder, .. }
+                | Stmt::ExpectFx { remainder, .. }
+                | Stmt::Dbg { remainder, .. } => stmt = remainder,
+                _ => return,
+            }
+        };
+
+        self.push(
+            binding,
+            Problem::MissedReuse {
+                region,
+                freed,
+                freed_region: self.symbol_regions.get(&freed).copied(),
+            },
+        );
+    }
+
+    /// Whether `symbol` is known to point to heap memory
+    fn is_allocated(&self, symbol: Symbol) -> bool {
+        match self.interner.get(self.layouts[&symbol]) {
+            Layout::Union(UnionLayout::Recursive(_) | UnionLayout::NonNullableUnwrapped(_)) => true,
+            Layout::Union(
+                UnionLayout::NullableWrapped { .. } | UnionLayout::NullableUnwrapped { .. },
+            ) => self.unpacked.contains(&symbol),
+            _ => false,
+        }
+    }
+
+    fn push(&mut self, binding: Symbol, problem: Problem) {
+        let problems = self.problems.entry(binding.module_id()).or_default();
+
+        if !problems.contains(&problem) {
+            problems.push(problem);
+        }
+    }
+}
+
+fn is_heap_allocated(union_layout: &UnionLayout, tag_id: TagIdIntType) -> bool {
+    !matches!(union_layout, UnionLayout::NonRecursive(_)) && !union_layout.tag_is_null(tag_id)
+}
+
+/// The first binding in `stmt` whose expression uses `symbol`
+fn first_use(stmt: &Stmt, symbol: Symbol) -> Option<Symbol> {
+    match stmt {
+        Stmt::Let(binding, expr, _, cont) => {
+            if expr_uses(expr, symbol) {
+                Some(*binding)
+            } else {
+                first_use(cont, symbol)
+            }
+        }
+        Stmt::Refcounting(_, cont) => first_use(cont, symbol),
+        Stmt::Expect { remainder, .. }
+        | Stmt::ExpectFx { remainder, .. }
+        | Stmt::Dbg { remainder, .. } => first_use(remainder, symbol),
+        Stmt::Switch {
+            branches,
+            default_branch,
+            ..
+        } => branches
+            .iter()
+            .map(|(_, _, branch)| branch)
+            .chain([&default_branch.1])
+            .find_map(|branch| first_use(branch, symbol)),
+        Stmt::Join {
+            body, remainder, ..
+        } => first_use(remainder, symbol).or_else(|| first_use(body, symbol)),
+        Stmt::Ret(_) | Stmt::Jump(_, _) | Stmt::Crash(_, _) => None,
+    }
+}
+
+fn expr_uses(expr: &Expr, symbol: Symbol) -> bool {
+    match expr {
+        Expr::Call(call) => call.arguments.contains(&symbol),
+        Expr::Tag { arguments, .. } | Expr::Struct(arguments) | Expr::Reuse { arguments, .. } => {
+            arguments.contains(&symbol)
+        }
+        Expr::StructAtIndex { structure, .. }
+        | Expr::GetTagId { structure, .. }
+        | Expr::UnionAtIndex { structure, .. }
+        | Expr::UnionFieldPtrAtIndex { structure, .. } => *structure == symbol,
+        Expr::Array { elems, .. } => elems
+            .iter()
+            .any(|elem| matches!(elem, ListLiteralElement::Symbol(s) if *s == symbol)),
+        Expr::ExprBox { symbol: s }
+        | Expr::ExprUnbox { symbol: s }
+        | Expr::Reset { symbol: s, .. }
+        | Expr::ResetRef { symbol: s, .. } => *s == symbol,
+        Expr::Literal(_) | Expr::NullPointer | Expr::EmptyArray | Expr::RuntimeErrorFunction(_) => {
+            false
+        }
+    }
+}
@@ -0,0 +1,857 @@
+use crate::ir::{
+    BranchInfo, Call, CallType, Expr, HigherOrderLowLevel, JoinPointId, ListLiteralElement,
+    Literal, ModifyRc, PassedFunction, Proc, ProcLayout, Stmt,
+};
+use crate::layout::{InLayout, Layout, TagIdIntType};
+use crate::low_level::HigherOrder;
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_builtins::bitcode::IntWidth;
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+/// Constant folding and dead code elimination.
+///
+/// - `LowLevel` integer and boolean operations on literals are evaluated. Operations that
+///   would crash at runtime (e.g. an overflowing `Num.add`) are left alone.
+/// - A `Switch` on a known value is replaced by the branch that would be taken.
+/// - Accessing a field of a struct or tag that was built in the same proc is replaced by the
+///   field itself, and later uses of the accessed symbol are renamed.
+/// - `let`s of pure expressions whose symbol is never used are removed, as are join points
+///   that are never jumped to.
+///
+/// This is most useful after inlining, when literal arguments end up in the body of the
+/// callee. It must run before refcount insertion.
+pub fn simplify_procs<'a>(arena: &'a Bump, procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>) {
+    for proc in procs.values_mut() {
+        let mut env = Env {
+            arena,
+            known: MutMap::default(),
+            renaming: MutMap::default(),
+        };
+
+        let body = env.simplify_stmt(&proc.body);
+
+        let mut used = Used::default();
+        proc.body = remove_dead_code(arena, &body, &mut used);
+    }
+}
+
+/// What we know about the value of a symbol
+#[derive(Clone, Copy)]
+enum Known<'a> {
+    Int(i128, IntWidth),
+    Bool(bool),
+    Byte(u8),
+    Tag {
+        tag_id: TagIdIntType,
+        arguments: &'a [Symbol],
+    },
+    Struct(&'a [Symbol]),
+}
+
+impl<'a> Known<'a> {
+    /// The value as compared by a `Switch`
+    fn switch_value(self) -> Option<u64> {
+        match self {
+            Known::Int(value, _) => Some(value as u64),
+            Known::Bool(value) => Some(value as u64),
+            Known::Byte(value) => Some(value as u64),
+            Known::Tag { .. } | Known::Struct(_) => None,
+        }
+    }
+}
+
+/// The result of simplifying an expression
+enum Simplified<'a> {
+    Expr(Expr<'a>),
+    /// The expression is equal to an existing symbol
+    Copy(Symbol),
+}
+
+struct Env<'a> {
+    arena: &'a Bump,
+    known: MutMap<Symbol, Known<'a>>,
+    /// Symbols that were found to be copies of other symbols
+    renaming: MutMap<Symbol, Symbol>,
+}
+
+impl<'a> Env<'a> {
+    fn rename(&self, symbol: Symbol) -> Symbol {
+        self.renaming.get(&symbol).copied().unwrap_or(symbol)
+    }
+
+    fn rename_symbols(&self, symbols: &[Symbol]) -> &'a [Symbol] {
+        Vec::from_iter_in(symbols.iter().map(|s| self.rename(*s)), self.arena).into_bump_slice()
+    }
+
+    fn simplify_stmt(&mut self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        use Stmt::*;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                let expr = self.rename_expr(expr);
+
+                match self.simplify_expr(expr, *layout) {
+                    Simplified::Copy(existing) => {
+                        self.renaming.insert(*symbol, existing);
+
+                        self.simplify_stmt(cont)
+                    }
+                    Simplified::Expr(expr) => {
+                        if let Some(known) = known_value(&expr, *layout) {
+                            self.known.insert(*symbol, known);
+                        }
+
+                        let cont = self.simplify_stmt(cont);
+
+                        Let(*symbol, expr, *layout, self.arena.alloc(cont))
+                    }
+                }
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let cond_symbol = self.rename(*cond_symbol);
+
+                if let Some(value) = self.known.get(&cond_symbol).and_then(|k| k.switch_value()) {
+                    let taken = branches
+                        .iter()
+                        .find(|(tag, _, _)| *tag == value)
+                        .map(|(_, _, branch)| branch)
+                        .unwrap_or(default_branch.1);
+
+                    return self.simplify_stmt(taken);
+                }
+
+                let mut new_branches = Vec::with_capacity_in(branches.len(), self.arena);
+
+                for (tag, info, branch) in branches.iter() {
+                    let info = self.rename_branch_info(info);
+                    let branch = self.simplify_stmt(branch);
+                    new_branches.push((*tag, info, branch));
+                }
+
+                let default_info = self.rename_branch_info(&default_branch.0);
+                let default = self.simplify_stmt(default_branch.1);
+
+                Switch {
+                    cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch: (default_info, self.arena.alloc(default)),
+                    ret_layout: *ret_layout,
+                }
+            }
+            Ret(symbol) => Ret(self.rename(*symbol)),
+            Refcounting(modify, cont) => {
+                let modify = match modify {
+                    ModifyRc::Inc(symbol, n) => ModifyRc::Inc(self.rename(*symbol), *n),
+                    ModifyRc::Dec(symbol) => ModifyRc::Dec(self.rename(*symbol)),
+                    ModifyRc::DecRef(symbol) => ModifyRc::DecRef(self.rename(*symbol)),
+                };
+                let cont = self.simplify_stmt(cont);
+
+                Refcounting(modify, self.arena.alloc(cont))
+            }
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.simplify_stmt(remainder);
+
+                Expect {
+                    condition: self.rename(*condition),
+                    region: *region,
+                    lookups: self.rename_symbols(lookups),
+                    variables: *variables,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => {
+                let remainder = self.simplify_stmt(remainder);
+
+                ExpectFx {
+                    condition: self.rename(*condition),
+                    region: *region,
+                    lookups: self.rename_symbols(lookups),
+                    variables: *variables,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => {
+                let remainder = self.simplify_stmt(remainder);
+
+                Dbg {
+                    symbol: self.rename(*symbol),
+                    variable: *variable,
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let body = self.simplify_stmt(body);
+                let remainder = self.simplify_stmt(remainder);
+
+                Join {
+                    id: *id,
+                    parameters: *parameters,
+                    body: self.arena.alloc(body),
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Jump(id, arguments) => Jump(*id, self.rename_symbols(arguments)),
+            Crash(symbol, tag) => Crash(self.rename(*symbol), *tag),
+        }
+    }
+
+    fn simplify_expr(&self, expr: Expr<'a>, layout: InLayout<'a>) -> Simplified<'a> {
+        match &expr {
+            Expr::StructAtIndex {
+                index, structure, ..
+            } => {
+                if let Some(Known::Struct(fields)) = self.known.get(structure) {
+                    return Simplified::Copy(fields[*index as usize]);
+                }
+            }
+            Expr::UnionAtIndex {
+                structure,
+                tag_id,
+                index,
+                ..
+            } => {
+                if let Some(Known::Tag {
+                    tag_id: known_id,
+                    arguments,
+                }) = self.known.get(structure)
+                {
+                    if known_id == tag_id {
+                        return Simplified::Copy(arguments[*index as usize]);
+                    }
+                }
+            }
+            Expr::GetTagId { structure, .. } => {
+                if let (Some(Known::Tag { tag_id, .. }), Some(_)) =
+                    (self.known.get(structure), int_width(layout))
+                {
+                    return Simplified::Expr(int_literal(*tag_id as i128));
+                }
+            }
+            Expr::Call(Call {
+                call_type: CallType::LowLevel { op, .. },
+                arguments,
+            }) => {
+                let known: std::vec::Vec<_> = arguments
+                    .iter()
+                    .map_while(|arg| self.known.get(arg).copied())
+                    .collect();
+
+                if known.len() == arguments.len() {
+                    if let Some(folded) = fold_lowlevel(*op, &known, layout) {
+                        return Simplified::Expr(folded);
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Simplified::Expr(expr)
+    }
+
+    fn rename_branch_info(&self, info: &BranchInfo<'a>) -> BranchInfo<'a> {
+        match info {
+            BranchInfo::None => BranchInfo::None,
+            BranchInfo::Constructor {
+                scrutinee,
+                layout,
+                tag_id,
+            } => BranchInfo::Constructor {
+                scrutinee: self.rename(*scrutinee),
+                layout: *layout,
+                tag_id: *tag_id,
+            },
+            BranchInfo::List { scrutinee, len } => BranchInfo::List {
+                scrutinee: self.rename(*scrutinee),
+                len: *len,
+            },
+        }
+    }
+
+    fn rename_expr(&self, expr: &Expr<'a>) -> Expr<'a> {
+        use Expr::*;
+
+        if self.renaming.is_empty() {
+            return expr.clone();
+        }
+
+        match expr {
+            Literal(_) | NullPointer | EmptyArray | RuntimeErrorFunction(_) => expr.clone(),
+            Call(call) => {
+                let call_type = match &call.call_type {
+                    CallType::HigherOrder(higher_order) => {
+                        let passed_function = PassedFunction {
+                            captured_environment: self
+                                .rename(higher_order.passed_function.captured_environment),
+                            ..higher_order.passed_function
+                        };
+
+                        CallType::HigherOrder(self.arena.alloc(HigherOrderLowLevel {
+                            op: self.rename_higher_order(&higher_order.op),
+                            closure_env_layout: higher_order.closure_env_layout,
+                            update_mode: higher_order.update_mode,
+                            passed_function,
+                        }))
+                    }
+                    other => other.clone(),
+                };
+
+                Call(crate::ir::Call {
+                    call_type,
+                    arguments: self.rename_symbols(call.arguments),
+                })
+            }
+            Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+            } => Tag {
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: self.rename_symbols(arguments),
+            },
+            Struct(fields) => Struct(self.rename_symbols(fields)),
+            StructAtIndex {
+                index,
+                field_layouts,
+                structure,
+            } => StructAtIndex {
+                index: *index,
+                field_layouts: *field_layouts,
+                structure: self.rename(*structure),
+            },
+            GetTagId {
+                structure,
+                union_layout,
+            } => GetTagId {
+                structure: self.rename(*structure),
+                union_layout: *union_layout,
+            },
+            UnionAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => UnionAtIndex {
+                structure: self.rename(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            UnionFieldPtrAtIndex {
+                structure,
+                tag_id,
+                union_layout,
+                index,
+            } => UnionFieldPtrAtIndex {
+                structure: self.rename(*structure),
+                tag_id: *tag_id,
+                union_layout: *union_layout,
+                index: *index,
+            },
+            Array { elem_layout, elems } => {
+                let elems = Vec::from_iter_in(
+                    elems.iter().map(|elem| match elem {
+                        ListLiteralElement::Literal(_) => *elem,
+                        ListLiteralElement::Symbol(symbol) => {
+                            ListLiteralElement::Symbol(self.rename(*symbol))
+                        }
+                    }),
+                    self.arena,
+                );
+
+                Array {
+                    elem_layout: *elem_layout,
+                    elems: elems.into_bump_slice(),
+                }
+            }
+            ExprBox { symbol } => ExprBox {
+                symbol: self.rename(*symbol),
+            },
+            ExprUnbox { symbol } => ExprUnbox {
+                symbol: self.rename(*symbol),
+            },
+            Reuse {
+                symbol,
+                update_tag_id,
+                update_mode,
+                tag_layout,
+                tag_id,
+                arguments,
+            } => Reuse {
+                symbol: self.rename(*symbol),
+                update_tag_id: *update_tag_id,
+                update_mode: *update_mode,
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                arguments: self.rename_symbols(arguments),
+            },
+            Reset {
+                symbol,
+                update_mode,
+            } => Reset {
+                symbol: self.rename(*symbol),
+                update_mode: *update_mode,
+            },
+            ResetRef {
+                symbol,
+                update_mode,
+            } => ResetRef {
+                symbol: self.rename(*symbol),
+                update_mode: *update_mode,
+            },
+        }
+    }
+
+    fn rename_higher_order(&self, op: &HigherOrder) -> HigherOrder {
+        use HigherOrder::*;
+
+        match op {
+            ListMap { xs } => ListMap {
+                xs: self.rename(*xs),
+            },
+            ListMap2 { xs, ys } => ListMap2 {
+                xs: self.rename(*xs),
+                ys: self.rename(*ys),
+            },
+            ListMap3 { xs, ys, zs } => ListMap3 {
+                xs: self.rename(*xs),
+                ys: self.rename(*ys),
+                zs: self.rename(*zs),
+            },
+            ListMap4 { xs, ys, zs, ws } => ListMap4 {
+                xs: self.rename(*xs),
+                ys: self.rename(*ys),
+                zs: self.rename(*zs),
+                ws: self.rename(*ws),
+            },
+            ListSortWith { xs } => ListSortWith {
+                xs: self.rename(*xs),
+            },
+        }
+    }
+}
+
+fn int_width(layout: InLayout) -> Option<IntWidth> {
+    use IntWidth::*;
+
+    // 128-bit integers are not folded
+    [U8, U16, U32, U64, I8, I16, I32, I64]
+        .into_iter()
+        .find(|width| Layout::int_width(*width) == layout)
+}
+
+fn int_literal<'a>(value: i128) -> Expr<'a> {
+    Expr::Literal(Literal::Int(value.to_ne_bytes()))
+}
+
+fn known_value<'a>(expr: &Expr<'a>, layout: InLayout<'a>) -> Option<Known<'a>> {
+    match expr {
+        Expr::Literal(Literal::Int(bytes)) => {
+            int_width(layout).map(|width| Known::Int(i128::from_ne_bytes(*bytes), width))
+        }
+        Expr::Literal(Literal::Bool(value)) => Some(Known::Bool(*value)),
+        Expr::Literal(Literal::Byte(value)) => Some(Known::Byte(*value)),
+        Expr::Tag {
+            tag_id, arguments, ..
+        } => Some(Known::Tag {
+            tag_id: *tag_id,
+            arguments: *arguments,
+        }),
+        Expr::Struct(fields) => Some(Known::Struct(*fields)),
+        _ => None,
+    }
+}
+
+fn int_bounds(width: IntWidth) -> (i128, i128) {
+    use IntWidth::*;
+
+    match width {
+        U8 => (0, u8::MAX as i128),
+        U16 => (0, u16::MAX as i128),
+        U32 => (0, u32::MAX as i128),
+        U64 => (0, u64::MAX as i128),
+        I8 => (i8::MIN as i128, i8::MAX as i128),
+        I16 => (i16::MIN as i128, i16::MAX as i128),
+        I32 => (i32::MIN as i128, i32::MAX as i128),
+        I64 => (i64::MIN as i128, i64::MAX as i128),
+        U128 | I128 => unreachable!("128-bit integers are not folded"),
+    }
+}
+
+/// Wrap around like the machine arithmetic of the given width would
+fn wrap(value: i128, width: IntWidth) -> i128 {
+    let bits = width.stack_size() * 8;
+    let truncated = value & ((1i128 << bits) - 1);
+
+    if width.is_signed() && truncated >= 1i128 << (bits - 1) {
+        truncated - (1i128 << bits)
+    } else {
+        truncated
+    }
+}
+
+/// Only the result of a checked operation that does not overflow can be folded
+fn checked<'a>(value: i128, width: IntWidth) -> Option<Expr<'a>> {
+    let (min, max) = int_bounds(width);
+
+    if (min..=max).contains(&value) {
+        Some(int_literal(value))
+    } else {
+        None
+    }
+}
+
+fn fold_lowlevel<'a>(op: LowLevel, args: &[Known<'a>], layout: InLayout<'a>) -> Option<Expr<'a>> {
+    use LowLevel::*;
+
+    let bool_literal = |value| Some(Expr::Literal(Literal::Bool(value)));
+
+    match args {
+        [Known::Int(a, width), Known::Int(b, other_width)] if width == other_width => {
+            let (a, b, width) = (*a, *b, *width);
+
+            match op {
+                // the result of comparisons is a Bool, so only the arithmetic needs the width
+                NumGt => bool_literal(a > b),
+                NumGte => bool_literal(a >= b),
+                NumLt => bool_literal(a < b),
+                NumLte => bool_literal(a <= b),
+                Eq => bool_literal(a == b),
+                NotEq => bool_literal(a != b),
+                _ if int_width(layout) != Some(width) => None,
+                NumAdd => checked(a + b, width),
+                NumSub => checked(a - b, width),
+                NumMul => checked(a.checked_mul(b)?, width),
+                NumAddWrap => Some(int_literal(wrap(a + b, width))),
+                NumSubWrap => Some(int_literal(wrap(a - b, width))),
+                NumMulWrap => Some(int_literal(wrap(a.wrapping_mul(b), width))),
+                NumBitwiseAnd => Some(int_literal(a & b)),
+                NumBitwiseOr => Some(int_literal(a | b)),
+                NumBitwiseXor => Some(int_literal(wrap(a ^ b, width))),
+                _ => None,
+            }
+        }
+        [Known::Bool(a), Known::Bool(b)] => match op {
+            And => bool_literal(*a && *b),
+            Or => bool_literal(*a || *b),
+            Eq => bool_literal(a == b),
+            NotEq => bool_literal(a != b),
+            _ => None,
+        },
+        [Known::Byte(a), Known::Byte(b)] => match op {
+            Eq => bool_literal(a == b),
+            NotEq => bool_literal(a != b),
+            _ => None,
+        },
+        [Known::Bool(a)] => match op {
+            Not => bool_literal(!*a),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Lowlevels that can be removed when their result is not used: they have no effects and
+/// cannot crash
+fn is_pure_lowlevel(op: LowLevel) -> bool {
+    use LowLevel::*;
+
+    matches!(
+        op,
+        NumAddWrap
+            | NumSubWrap
+            | NumMulWrap
+            | NumGt
+            | NumGte
+            | NumLt
+            | NumLte
+            | NumCompare
+            | NumBitwiseAnd
+            | NumBitwiseOr
+            | NumBitwiseXor
+            | Eq
+            | NotEq
+            | And
+            | Or
+            | Not
+            | ListLen
+    )
+}
+
+fn is_pure(expr: &Expr) -> bool {
+    match expr {
+        Expr::Literal(_)
+        | Expr::Tag { .. }
+        | Expr::Struct(_)
+        | Expr::NullPointer
+        | Expr::StructAtIndex { .. }
+        | Expr::GetTagId { .. }
+        | Expr::UnionAtIndex { .. }
+        | Expr::UnionFieldPtrAtIndex { .. }
+        | Expr::Array { .. }
+        | Expr::EmptyArray
+        | Expr::ExprBox { .. }
+        | Expr::ExprUnbox { .. } => true,
+        Expr::Call(Call {
+            call_type: CallType::LowLevel { op, .. },
+            ..
+        }) => is_pure_lowlevel(*op),
+        Expr::Call(_)
+        | Expr::Reuse { .. }
+        | Expr::Reset { .. }
+        | Expr::ResetRef { .. }
+        | Expr::RuntimeErrorFunction(_) => false,
+    }
+}
+
+#[derive(Default)]
+struct Used {
+    symbols: MutSet<Symbol>,
+    join_points: MutSet<JoinPointId>,
+}
+
+impl Used {
+    fn extend(&mut self, symbols: &[Symbol]) {
+        self.symbols.extend(symbols.iter().copied());
+    }
+}
+
+/// Remove pure `let`s whose symbol is not used, and join points that are never jumped to.
+///
+/// Statements are processed back to front, so `used` contains all uses that come after the
+/// current statement. Symbols are unique within a proc, so uses in one branch of a `Switch`
+/// never refer to a symbol that is bound in another branch.
+fn remove_dead_code<'a>(arena: &'a Bump, stmt: &Stmt<'a>, used: &mut Used) -> Stmt<'a> {
+    use Stmt::*;
+
+    match stmt {
+        Let(symbol, expr, layout, cont) => {
+            let cont = remove_dead_code(arena, cont, used);
+
+            if !used.symbols.contains(symbol) && is_pure(expr) {
+                return cont;
+            }
+
+            add_expr_uses(expr, used);
+
+            Let(*symbol, expr.clone(), *layout, arena.alloc(cont))
+        }
+        Switch {
+            cond_symbol,
+            cond_layout,
+            branches,
+            default_branch,
+            ret_layout,
+        } => {
+            let mut new_branches = Vec::with_capacity_in(branches.len(), arena);
+
+            for (tag, info, branch) in branches.iter() {
+                let branch = remove_dead_code(arena, branch, used);
+                new_branches.push((*tag, info.clone(), branch));
+            }
+
+            let default = remove_dead_code(arena, default_branch.1, used);
+
+            used.symbols.insert(*cond_symbol);
+
+            for info in branches
+                .iter()
+                .map(|b| &b.1)
+                .chain(std::iter::once(&default_branch.0))
+            {
+                match info {
+                    BranchInfo::Constructor { scrutinee, .. }
+                    | BranchInfo::List { scrutinee, .. } => {
+                        used.symbols.insert(*scrutinee);
+                    }
+                    BranchInfo::None => {}
+                }
+            }
+
+            Switch {
+                cond_symbol: *cond_symbol,
+                cond_layout: *cond_layout,
+                branches: new_branches.into_bump_slice(),
+                default_branch: (default_branch.0.clone(), arena.alloc(default)),
+                ret_layout: *ret_layout,
+            }
+        }
+        Ret(symbol) => {
+            used.symbols.insert(*symbol);
+
+            stmt.clone()
+        }
+        Refcounting(modify, cont) => {
+            let cont = remove_dead_code(arena, cont, used);
+            used.symbols.insert(modify.get_symbol());
+
+            Refcounting(*modify, arena.alloc(cont))
+        }
+        Expect {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => {
+            let remainder = remove_dead_code(arena, remainder, used);
+            used.symbols.insert(*condition);
+            used.extend(lookups);
+
+            Expect {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: arena.alloc(remainder),
+            }
+        }
+        ExpectFx {
+            condition,
+            region,
+            lookups,
+            variables,
+            remainder,
+        } => {
+            let remainder = remove_dead_code(arena, remainder, used);
+            used.symbols.insert(*condition);
+            used.extend(lookups);
+
+            ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: arena.alloc(remainder),
+            }
+        }
+        Dbg {
+            symbol,
+            variable,
+            remainder,
+        } => {
+            let remainder = remove_dead_code(arena, remainder, used);
+            used.symbols.insert(*symbol);
+
+            Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: arena.alloc(remainder),
+            }
+        }
+        Join {
+            id,
+            parameters,
+            body,
+            remainder,
+        } => {
+            let remainder = remove_dead_code(arena, remainder, used);
+
+            if !used.join_points.contains(id) {
+                // the body is unreachable
+                return remainder;
+            }
+
+            let body = remove_dead_code(arena, body, used);
+
+            Join {
+                id: *id,
+                parameters: *parameters,
+                body: arena.alloc(body),
+                remainder: arena.alloc(remainder),
+            }
+        }
+        Jump(id, arguments) => {
+            used.join_points.insert(*id);
+            used.extend(arguments);
+
+            stmt.clone()
+        }
+        Crash(symbol, _) => {
+            used.symbols.insert(*symbol);
+
+            stmt.clone()
+        }
+    }
+}
+
+fn add_expr_uses(expr: &Expr, used: &mut Used) {
+    use Expr::*;
+
+    match expr {
+        Literal(_) | NullPointer | EmptyArray | RuntimeErrorFunction(_) => {}
+        Call(call) => {
+            used.extend(call.arguments);
+
+            if let CallType::HigherOrder(higher_order) = &call.call_type {
+                let passed_function = &higher_order.passed_function;
+                used.symbols.insert(passed_function.captured_environment);
+
+                match higher_order.op {
+                    HigherOrder::ListMap { xs } | HigherOrder::ListSortWith { xs } => {
+                        used.extend(&[xs])
+                    }
+                    HigherOrder::ListMap2 { xs, ys } => used.extend(&[xs, ys]),
+                    HigherOrder::ListMap3 { xs, ys, zs } => used.extend(&[xs, ys, zs]),
+                    HigherOrder::ListMap4 { xs, ys, zs, ws } => used.extend(&[xs, ys, zs, ws]),
+                }
+            }
+        }
+        Tag { arguments, .. } | Reuse { arguments, .. } => {
+            used.extend(arguments);
+
+            if let Reuse { symbol, .. } = expr {
+                used.symbols.insert(*symbol);
+            }
+        }
+        Struct(fields) => used.extend(fields),
+        StructAtIndex { structure, .. }
+        | GetTagId { structure, .. }
+        | UnionAtIndex { structure, .. }
+        | UnionFieldPtrAtIndex { structure, .. } => {
+            used.symbols.insert(*structure);
+        }
+        Array { elems, .. } => {
+            used.symbols
+                .extend(elems.iter().filter_map(|elem| elem.to_symbol()));
+        }
+        ExprBox { symbol }
+        | ExprUnbox { symbol }
+        | Reset { symbol, .. }
+        | ResetRef { symbol, .. } => {
+            used.symbols.insert(*symbol);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+//! Escape analysis, to put allocations that never outlive their proc in its stack frame.
+//!
+//! Boxes, list literals and recursive tag union nodes are normally allocated on the heap, behind
+//! a refcount. When such an allocation is not returned, not jumped with, not passed to a proc or
+//! to an owned argument of a lowlevel, and not stored in anything that escapes, it can live in
+//! the stack frame of its proc instead. The backends give it a refcount that marks it as
+//! read-only, so that refcount operations on values that contain it are no-ops.
+//!
+//! When the contents of such an allocation are themselves refcounted, it still has to release
+//! them. The allocation then behaves like a struct of its children: `inc x` and `dec x` become
+//! an `inc` or `dec` of every child, `decref x` is removed and `x` is always unique. That is only
+//! sound when `x` is used directly, so these allocations are kept on the heap as soon as any
+//! other value may point to them.
+use bumpalo::collections::Vec;
+use bumpalo::Bump;
+use roc_collections::{MutMap, MutSet};
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+
+use crate::borrow::{lowlevel_borrow_signature, Ownership};
+use crate::ir::{
+    Call, CallType, Expr, ListLiteralElement, Literal, ModifyRc, Proc, ProcLayout, Stmt,
+};
+use crate::layout::{InLayout, Layout, LayoutInterner, STLayoutInterner, UnionLayout};
+
+/// Larger allocations stay on the heap, so that deep recursion does not overflow the stack.
+const MAX_STACK_ALLOCATION_BYTES: u32 = 1024;
+
+/// Find the allocations of every proc that can be put on the stack, remove their refcount
+/// operations and record them in [`Proc::stack_allocations`].
+///
+/// This must run after all refcount operations have been inserted, right before code
+/// generation.
+pub fn stack_allocate_procs<'a>(
+    arena: &'a Bump,
+    layout_interner: &STLayoutInterner<'a>,
+    procs: &mut MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    for proc in procs.values_mut() {
+        let mut env = Env {
+            arena,
+            interner: layout_interner,
+            layouts: MutMap::default(),
+            candidates: MutMap::default(),
+            aliases: MutMap::default(),
+            stack: MutSet::default(),
+        };
+
+        for (layout, symbol) in proc.args.iter() {
+            env.layouts.insert(*symbol, *layout);
+        }
+
+        env.analyze_stmt(&proc.body);
+
+        let mut stack_allocations = Vec::from_iter_in(
+            env.candidates
+                .iter()
+                .filter(|(_, candidate)| !candidate.escapes)
+                .map(|(symbol, _)| *symbol),
+            arena,
+        );
+
+        if stack_allocations.is_empty() {
+            continue;
+        }
+
+        stack_allocations.sort();
+        env.stack.extend(stack_allocations.iter().copied());
+
+        proc.body = env.rewrite_stmt(&proc.body);
+        proc.stack_allocations = stack_allocations.into_bump_slice();
+    }
+}
+
+/// An allocation that may be put on the stack
+struct Candidate<'a> {
+    /// The refcounted values stored in the allocation
+    children: &'a [Symbol],
+    escapes: bool,
+}
+
+struct Env<'a, 'i> {
+    arena: &'a Bump,
+    interner: &'i STLayoutInterner<'a>,
+    layouts: MutMap<Symbol, InLayout<'a>>,
+    candidates: MutMap<Symbol, Candidate<'a>>,
+    /// The candidates that a symbol may point to, directly or through the values it contains
+    aliases: MutMap<Symbol, MutSet<Symbol>>,
+    /// The candidates that do not escape; filled in after the analysis
+    stack: MutSet<Symbol>,
+}
+
+impl<'a, 'i> Env<'a, 'i> {
+    fn aliases_of(&self, symbol: Symbol) -> MutSet<Symbol> {
+        self.aliases.get(&symbol).cloned().unwrap_or_default()
+    }
+
+    /// The candidates that the values stored in `symbol` may point to
+    fn contents_of(&self, symbol: Symbol) -> MutSet<Symbol> {
+        match self.candidates.get(&symbol) {
+            Some(candidate) => candidate
+                .children
+                .iter()
+                .flat_map(|child| self.aliases_of(*child))
+                .collect(),
+            None => self.aliases_of(symbol),
+        }
+    }
+
+    fn escape(&mut self, symbol: Symbol) {
+        for alias in self.aliases_of(symbol) {
+            if let Some(candidate) = self.candidates.get_mut(&alias) {
+                candidate.escapes = true;
+            }
+        }
+    }
+
+    /// Record that `symbol` may point to the given candidates
+    fn add_aliases(&mut self, symbol: Symbol, aliases: MutSet<Symbol>) {
+        for alias in aliases.iter() {
+            if let Some(candidate) = self.candidates.get_mut(alias) {
+                // an allocation with refcounted contents must be used directly, see the module docs
+                if !candidate.children.is_empty() {
+                    candidate.escapes = true;
+                }
+            }
+        }
+
+        if !aliases.is_empty() {
+            self.aliases.entry(symbol).or_default().extend(aliases);
+        }
+    }
+
+    /// `symbol` holds the values of `stored`
+    fn store(&mut self, symbol: Symbol, stored: impl IntoIterator<Item = Symbol>) {
+        let aliases = stored
+            .into_iter()
+            .flat_map(|s| self.aliases_of(s))
+            .collect();
+        self.add_aliases(symbol, aliases);
+    }
+
+    /// `symbol` is read out of `structure`
+    fn read(&mut self, symbol: Symbol, structure: Symbol, layout: InLayout<'a>) {
+        if self.interner.contains_refcounted(layout) {
+            let aliases = self.contents_of(structure);
+            self.add_aliases(symbol, aliases);
+        }
+    }
+
+    /// `symbol` is a new allocation of `size` bytes that holds the values of `stored`
+    fn allocation(&mut self, symbol: Symbol, stored: &[Symbol], size: u32, alignment: u32) {
+        let ptr_width = self.interner.target_info().ptr_width() as u32;
+
+        if size == 0 || size > MAX_STACK_ALLOCATION_BYTES || alignment > ptr_width {
+            self.store(symbol, stored.iter().copied());
+            return;
+        }
+
+        let children = Vec::from_iter_in(
+            stored
+                .iter()
+                .copied()
+                .filter(|s| match self.layouts.get(s) {
+                    Some(layout) => self.interner.contains_refcounted(*layout),
+                    None => true,
+                }),
+            self.arena,
+        )
+        .into_bump_slice();
+
+        let mut aliases: MutSet<Symbol> = children
+            .iter()
+            .flat_map(|child| self.aliases_of(*child))
+            .collect();
+        aliases.insert(symbol);
+
+        self.aliases.insert(symbol, aliases);
+        self.candidates.insert(
+            symbol,
+            Candidate {
+                children,
+                escapes: false,
+            },
+        );
+    }
+
+    fn analyze_stmt(&mut self, stmt: &Stmt<'a>) {
+        use Stmt::*;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                self.layouts.insert(*symbol, *layout);
+                self.analyze_expr(*symbol, expr, *layout);
+                self.analyze_stmt(cont);
+            }
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                for (_, _, branch) in branches.iter() {
+                    self.analyze_stmt(branch);
+                }
+                self.analyze_stmt(default_branch.1);
+            }
+            Ret(symbol) | Crash(symbol, _) => self.escape(*symbol),
+            Jump(_, arguments) => {
+                for argument in arguments.iter() {
+                    self.escape(*argument);
+                }
+            }
+            Join {
+                parameters,
+                body,
+                remainder,
+                ..
+            } => {
+                for param in parameters.iter() {
+                    self.layouts.insert(param.symbol, param.layout);
+                }
+                self.analyze_stmt(body);
+                self.analyze_stmt(remainder);
+            }
+            Refcounting(_, cont) => self.analyze_stmt(cont),
+            Expect { remainder, .. } | ExpectFx { remainder, .. } | Dbg { remainder, .. } => {
+                self.analyze_stmt(remainder)
+            }
+        }
+    }
+
+    fn analyze_expr(&mut self, symbol: Symbol, expr: &Expr<'a>, layout: InLayout<'a>) {
+        use Expr::*;
+
+        match expr {
+            Literal(_) | NullPointer | EmptyArray | GetTagId { .. } | RuntimeErrorFunction(_) => {}
+            Call(call) => self.analyze_call(symbol, call, layout),
+            Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+            } => match tag_layout {
+                UnionLayout::NonRecursive(_) => self.store(symbol, arguments.iter().copied()),
+                _ if tag_layout.tag_is_null(*tag_id) => {}
+                _ => {
+                    let (size, alignment) = tag_layout
+                        .data_size_and_alignment(self.interner, self.interner.target_info());
+                    self.allocation(symbol, arguments, size, alignment);
+                }
+            },
+            Struct(fields) => self.store(symbol, fields.iter().copied()),
+            Array { elem_layout, elems } => {
+                let stored = Vec::from_iter_in(
+                    elems.iter().filter_map(|elem| match elem {
+                        ListLiteralElement::Symbol(s) => Some(*s),
+                        ListLiteralElement::Literal(_) => None,
+                    }),
+                    self.arena,
+                );
+                let (elem_size, alignment) = self.interner.stack_size_and_alignment(*elem_layout);
+                self.allocation(symbol, &stored, elem_size * elems.len() as u32, alignment);
+            }
+            ExprBox { symbol: boxed } => match self.interner.get(layout) {
+                Layout::Boxed(inner) => {
+                    let (size, alignment) = self.interner.stack_size_and_alignment(inner);
+                    self.allocation(symbol, &[*boxed], size, alignment);
+                }
+                _ => self.store(symbol, [*boxed]),
+            },
+            StructAtIndex { structure, .. }
+            | UnionAtIndex { structure, .. }
+            | ExprUnbox { symbol: structure } => self.read(symbol, *structure, layout),
+            Reuse { arguments, .. } => self.store(symbol, arguments.iter().copied()),
+            Reset {
+                symbol: structure, ..
+            }
+            | ResetRef {
+                symbol: structure, ..
+            }
+            | UnionFieldPtrAtIndex { structure, .. } => self.escape(*structure),
+        }
+    }
+
+    fn analyze_call(&mut self, symbol: Symbol, call: &Call<'a>, layout: InLayout<'a>) {
+        use LowLevel::*;
+
+        match call.call_type.clone().replace_lowlevel_wrapper() {
+            CallType::LowLevel {
+                op: ListGetUnsafe, ..
+            } => self.read(symbol, call.arguments[0], layout),
+            CallType::LowLevel {
+                op: RefCountIsUnique,
+                ..
+            } => {}
+            CallType::LowLevel {
+                op:
+                    PtrCast | PtrWrite | PtrStore | RefCountIncRcPtr | RefCountDecRcPtr
+                    | RefCountIncDataPtr | RefCountDecDataPtr | BoxExpr | UnboxExpr,
+                ..
+            } => {
+                for argument in call.arguments.iter() {
+                    self.escape(*argument);
+                }
+            }
+            CallType::LowLevel { op, .. } => {
+                let signature = lowlevel_borrow_signature(self.arena, op);
+                let mut borrowed = MutSet::default();
+
+                for (argument, ownership) in call.arguments.iter().zip(signature.iter()) {
+                    match ownership {
+                        Ownership::Owned => self.escape(*argument),
+                        Ownership::Borrowed => borrowed.extend(self.aliases_of(*argument)),
+                    }
+                }
+
+                // the result may point into a borrowed argument
+                if self.interner.contains_refcounted(layout) {
+                    self.add_aliases(symbol, borrowed);
+                }
+            }
+            CallType::ByName { .. } | CallType::Foreign { .. } | CallType::HigherOrder(_) => {
+                for argument in call.arguments.iter() {
+                    self.escape(*argument);
+                }
+            }
+        }
+    }
+
+    fn rewrite_stmt(&self, stmt: &Stmt<'a>) -> Stmt<'a> {
+        use Stmt::*;
+
+        match stmt {
+            Let(symbol, expr, layout, cont) => {
+                let expr = match expr {
+                    Expr::Call(Call {
+                        call_type:
+                            CallType::LowLevel {
+                                op: LowLevel::RefCountIsUnique,
+                                ..
+                            },
+                        arguments,
+                    }) if self.stack.contains(&arguments[0]) => Expr::Literal(Literal::Bool(true)),
+                    _ => expr.clone(),
+                };
+                let cont = self.rewrite_stmt(cont);
+
+                Let(*symbol, expr, *layout, self.arena.alloc(cont))
+            }
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let branches = Vec::from_iter_in(
+                    branches
+                        .iter()
+                        .map(|(tag, info, branch)| (*tag, info.clone(), self.rewrite_stmt(branch))),
+                    self.arena,
+                );
+                let default = self.rewrite_stmt(default_branch.1);
+
+                Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: branches.into_bump_slice(),
+                    default_branch: (default_branch.0.clone(), self.arena.alloc(default)),
+                    ret_layout: *ret_layout,
+                }
+            }
+            Refcounting(modify, cont) => {
+                let cont = self.rewrite_stmt(cont);
+
+                match *modify {
+                    ModifyRc::Inc(symbol, n) if self.stack.contains(&symbol) => {
+                        self.modify_children(symbol, &|child| ModifyRc::Inc(child, n), cont)
+                    }
+                    ModifyRc::Dec(symbol) if self.stack.contains(&symbol) => {
+                        self.modify_children(symbol, &ModifyRc::Dec, cont)
+                    }
+                    ModifyRc::DecRef(symbol) if self.stack.contains(&symbol) => cont,
+                    _ => Refcounting(*modify, self.arena.alloc(cont)),
+                }
+            }
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => {
+                let body = self.rewrite_stmt(body);
+                let remainder = self.rewrite_stmt(remainder);
+
+                Join {
+                    id: *id,
+                    parameters: *parameters,
+                    body: self.arena.alloc(body),
+                    remainder: self.arena.alloc(remainder),
+                }
+            }
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => Expect {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: self.arena.alloc(self.rewrite_stmt(remainder)),
+            },
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups: *lookups,
+                variables: *variables,
+                remainder: self.arena.alloc(self.rewrite_stmt(remainder)),
+            },
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: self.arena.alloc(self.rewrite_stmt(remainder)),
+            },
+            Ret(_) | Jump(_, _) | Crash(_, _) => stmt.clone(),
+        }
+    }
+
+    /// Apply `modify` to the children of the stack allocation `symbol` rather than to itself
+    fn modify_children(
+        &self,
+        symbol: Symbol,
+        modify: &dyn Fn(Symbol) -> ModifyRc,
+        mut cont: Stmt<'a>,
+    ) -> Stmt<'a> {
+        for child in self.candidates[&symbol].children.iter().rev() {
+            cont = if self.stack.contains(child) {
+                self.modify_children(*child, modify, cont)
+            } else {
+                Stmt::Refcounting(modify(*child), self.arena.alloc(cont))
+            };
+        }
+
+        cont
+    }
+}
@@ -1,12 +1,19 @@
 #![allow(clippy::manual_map)]
 
 use crate::borrow::Ownership;
-use crate::ir::{CallType, Expr, JoinPointId, Param, Stmt};
-use crate::layout::{InLayout, LambdaName};
+use crate::ir::{
+    BranchInfo, Call, CallType, Env, Expr, HostExposedLayouts, JoinPointId, Param, Proc,
+    ProcLayout, SelfRecursive, Stmt, UpdateModeId,
+};
+use crate::layout::{InLayout, LambdaName, Layout, LayoutCache, Niche, TagIdIntType, UnionLayout};
 use bumpalo::collections::Vec;
 use bumpalo::Bump;
+use roc_collections::{MutMap, ReferenceMatrix};
+use roc_module::low_level::LowLevel;
 use roc_module::symbol::Symbol;
 
+type ProcKey<'a> = (Symbol, ProcLayout<'a>);
+
 /// Make tail calls into loops (using join points)
 ///
 /// e.g.
@@ -39,7 +46,21 @@ pub fn make_tail_recursive<'a>(
 ) -> Option<Stmt<'a>> {
     let allocated = arena.alloc(stmt);
 
-    let new_stmt = insert_jumps(arena, allocated, id, needle, args, ret_layout)?;
+    // to insert a tail-call, it must not just be a call to the function itself, but it must also
+    // have the same layout. In particular when lambda sets get involved, a self-recursive call may
+    // have a different type and should not be converted to a jump!
+    let mut replace = |function_name: LambdaName, arguments: &[_], result, call_args| {
+        let it = args.iter().map(|t| &t.0);
+
+        if needle == function_name && it.eq(arguments.iter()) && ret_layout == result {
+            // replace the call and return with a jump
+            Some(&*arena.alloc(Stmt::Jump(id, call_args)))
+        } else {
+            None
+        }
+    };
+
+    let new_stmt = insert_jumps(arena, allocated, &mut replace)?;
 
     // if we did not early-return, jumps were inserted, we must now add a join point
 
@@ -68,28 +89,719 @@ pub fn make_tail_recursive<'a>(
     Some(join)
 }
 
-fn insert_jumps<'a>(
-    arena: &'a Bump,
-    stmt: &'a Stmt<'a>,
-    goal_id: JoinPointId,
-    needle: LambdaName,
-    needle_arguments: &'a [(InLayout<'a>, Symbol, Symbol)],
-    needle_result: InLayout<'a>,
-) -> Option<&'a Stmt<'a>> {
-    use Stmt::*;
+/// Make recursive calls whose result is immediately wrapped in a tag into loops
+///
+/// e.g.
+///
+/// > map = \list, f -> when list is
+/// >     Nil -> Nil
+/// >     Cons x xs -> Cons (f x) (map xs f)
+///
+/// is not tail recursive: the tag is allocated after the recursive call returns. Instead, we
+/// allocate the tag first, with a placeholder for the result of the recursive call, and pass the
+/// address of the placeholder (the "hole") along to the next iteration, which writes its result
+/// into it. The first hole is in a box, which holds the final result:
+///
+/// ```elm
+/// map list1 f1 =
+///     let slot = Box NullPointer
+///     let joinpoint j list f hole =
+///             when list is
+///                 Nil ->
+///                     PtrStore hole Nil
+///                     Unbox slot
+///                 Cons x xs ->
+///                     let cons = Cons (f x) NullPointer
+///                     let next = UnionFieldPtrAtIndex 1 cons
+///                     PtrStore hole cons
+///                     jump j xs f next
+///     in
+///         jump j list1 f1 (PtrCast slot)
+/// ```
+///
+/// Every other return writes its value into the hole and returns the slot's contents, and plain
+/// tail calls become jumps that keep the current hole. Returns `None` when there is no recursive
+/// call that is wrapped in a tag, so the regular [`make_tail_recursive`] can be used.
+#[allow(clippy::too_many_arguments)]
+pub fn make_tail_recursive_modulo_cons<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    id: JoinPointId,
+    needle: LambdaName<'a>,
+    stmt: &Stmt<'a>,
+    args: &'a [(InLayout<'a>, Symbol, Symbol)],
+    ret_layout: InLayout<'a>,
+) -> Option<Stmt<'a>> {
+    let arena = env.arena;
 
-    // to insert a tail-call, it must not just be a call to the function itself, but it must also
-    // have the same layout. In particular when lambda sets get involved, a self-recursive call may
-    // have a different type and should not be converted to a jump!
-    let is_equal_function = |function_name: LambdaName, arguments: &[_], result| {
-        let it = needle_arguments.iter().map(|t| &t.0);
-        needle == function_name && it.eq(arguments.iter()) && needle_result == result
+    let trmc = Trmc {
+        id,
+        needle,
+        arg_layouts: Vec::from_iter_in(args.iter().map(|t| t.0), arena).into_bump_slice(),
+        ret_layout,
+        slot: env.unique_symbol(),
+        hole: env.unique_symbol(),
+    };
+
+    if !trmc.has_cons_site(stmt) {
+        return None;
+    }
+
+    let body = trmc.rewrite(env, stmt);
+
+    let usize_layout = Layout::usize(env.target_info);
+    let box_layout = layout_cache.put_in(Layout::Boxed(ret_layout));
+
+    let mut params = Vec::from_iter_in(
+        args.iter().map(|(layout, symbol, _)| Param {
+            symbol: *symbol,
+            layout: *layout,
+            ownership: Ownership::Borrowed,
+        }),
+        arena,
+    );
+    params.push(Param {
+        symbol: trmc.hole,
+        layout: usize_layout,
+        ownership: Ownership::Borrowed,
+    });
+
+    let first_hole = env.unique_symbol();
+    let mut jump_args = Vec::from_iter_in(args.iter().map(|t| t.2), arena);
+    jump_args.push(first_hole);
+
+    let null = env.unique_symbol();
+    let update_mode = env.next_update_mode_id();
+    let jump = arena.alloc(Stmt::Jump(id, jump_args.into_bump_slice()));
+    let first_hole_stmt = arena.alloc(Stmt::Let(
+        first_hole,
+        lowlevel(LowLevel::PtrCast, update_mode, arena.alloc([trmc.slot])),
+        usize_layout,
+        jump,
+    ));
+    let slot_stmt = arena.alloc(Stmt::Let(
+        trmc.slot,
+        Expr::ExprBox { symbol: null },
+        box_layout,
+        first_hole_stmt,
+    ));
+
+    let join = Stmt::Join {
+        id,
+        parameters: params.into_bump_slice(),
+        body: arena.alloc(body),
+        remainder: arena.alloc(Stmt::Let(null, Expr::NullPointer, ret_layout, slot_stmt)),
+    };
+
+    Some(join)
+}
+
+struct Trmc<'a> {
+    id: JoinPointId,
+    needle: LambdaName<'a>,
+    arg_layouts: &'a [InLayout<'a>],
+    ret_layout: InLayout<'a>,
+    /// The box that holds the final result
+    slot: Symbol,
+    /// The address that the result of the current iteration is written to
+    hole: Symbol,
+}
+
+/// A recursive call whose result is put in a tag that is then returned
+struct ConsSite<'a> {
+    call_arguments: &'a [Symbol],
+    tag: Symbol,
+    tag_layout: UnionLayout<'a>,
+    tag_id: TagIdIntType,
+    tag_arguments: &'a [Symbol],
+    /// The index of the result of the call in the tag arguments
+    index: usize,
+}
+
+impl<'a> Trmc<'a> {
+    /// The arguments of `expr` if it is a recursive call that can be turned into a jump
+    fn recursive_call(&self, expr: &Expr<'a>) -> Option<&'a [Symbol]> {
+        match expr {
+            Expr::Call(Call {
+                call_type:
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts,
+                        ..
+                    },
+                arguments,
+            }) if *name == self.needle
+                && *ret_layout == self.ret_layout
+                && *arg_layouts == self.arg_layouts =>
+            {
+                Some(*arguments)
+            }
+            _ => None,
+        }
+    }
+
+    fn cons_site(&self, stmt: &Stmt<'a>) -> Option<ConsSite<'a>> {
+        let Stmt::Let(result, call, _, cont) = stmt else {
+            return None;
+        };
+        let call_arguments = self.recursive_call(call)?;
+
+        let Stmt::Let(
+            tag,
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments: tag_arguments,
+            },
+            layout,
+            Stmt::Ret(returned),
+        ) = cont
+        else {
+            return None;
+        };
+
+        let is_heap_allocated =
+            !matches!(tag_layout, UnionLayout::NonRecursive(_)) && !tag_layout.tag_is_null(*tag_id);
+
+        if tag != returned || *layout != self.ret_layout || !is_heap_allocated {
+            return None;
+        }
+
+        let mut uses = tag_arguments
+            .iter()
+            .enumerate()
+            .filter(|(_, argument)| *argument == result)
+            .map(|(index, _)| index);
+
+        match (uses.next(), uses.next()) {
+            (Some(index), None) if !call_arguments.contains(result) => Some(ConsSite {
+                call_arguments,
+                tag: *tag,
+                tag_layout: *tag_layout,
+                tag_id: *tag_id,
+                tag_arguments,
+                index,
+            }),
+            _ => None,
+        }
+    }
+
+    fn has_cons_site(&self, stmt: &Stmt<'a>) -> bool {
+        use Stmt::*;
+
+        if self.cons_site(stmt).is_some() {
+            return true;
+        }
+
+        match stmt {
+            Let(_, _, _, cont) | Refcounting(_, cont) => self.has_cons_site(cont),
+            Dbg { remainder, .. } | Expect { remainder, .. } | ExpectFx { remainder, .. } => {
+                self.has_cons_site(remainder)
+            }
+            Join {
+                body, remainder, ..
+            } => self.has_cons_site(body) || self.has_cons_site(remainder),
+            Switch {
+                branches,
+                default_branch,
+                ..
+            } => {
+                branches
+                    .iter()
+                    .any(|(_, _, branch)| self.has_cons_site(branch))
+                    || self.has_cons_site(default_branch.1)
+            }
+            Ret(_) | Jump(_, _) | Crash(..) => false,
+        }
+    }
+
+    fn rewrite(&self, env: &mut Env<'a, '_>, stmt: &Stmt<'a>) -> Stmt<'a> {
+        use Stmt::*;
+
+        let arena = env.arena;
+
+        if let Some(site) = self.cons_site(stmt) {
+            return self.rewrite_cons_site(env, site);
+        }
+
+        match stmt {
+            Let(symbol, expr, _, Ret(returned))
+                if symbol == returned && self.recursive_call(expr).is_some() =>
+            {
+                // the result of a plain tail call goes into the current hole
+                let arguments = self.recursive_call(expr).unwrap();
+                let mut jump_args = Vec::from_iter_in(arguments.iter().copied(), arena);
+                jump_args.push(self.hole);
+
+                Jump(self.id, jump_args.into_bump_slice())
+            }
+            Let(symbol, expr, layout, cont) => {
+                let cont = self.rewrite(env, cont);
+                Let(*symbol, expr.clone(), *layout, arena.alloc(cont))
+            }
+            Refcounting(modify, cont) => {
+                let cont = self.rewrite(env, cont);
+                Refcounting(*modify, arena.alloc(cont))
+            }
+            Dbg {
+                symbol,
+                variable,
+                remainder,
+            } => Dbg {
+                symbol: *symbol,
+                variable: *variable,
+                remainder: arena.alloc(self.rewrite(env, remainder)),
+            },
+            Expect {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => Expect {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: arena.alloc(self.rewrite(env, remainder)),
+            },
+            ExpectFx {
+                condition,
+                region,
+                lookups,
+                variables,
+                remainder,
+            } => ExpectFx {
+                condition: *condition,
+                region: *region,
+                lookups,
+                variables,
+                remainder: arena.alloc(self.rewrite(env, remainder)),
+            },
+            Join {
+                id,
+                parameters,
+                body,
+                remainder,
+            } => Join {
+                id: *id,
+                parameters,
+                body: arena.alloc(self.rewrite(env, body)),
+                remainder: arena.alloc(self.rewrite(env, remainder)),
+            },
+            Switch {
+                cond_symbol,
+                cond_layout,
+                branches,
+                default_branch,
+                ret_layout,
+            } => {
+                let mut new_branches = Vec::with_capacity_in(branches.len(), arena);
+                for (label, info, branch) in branches.iter() {
+                    new_branches.push((*label, info.clone(), self.rewrite(env, branch)));
+                }
+
+                let default_branch = (
+                    default_branch.0.clone(),
+                    &*arena.alloc(self.rewrite(env, default_branch.1)),
+                );
+
+                Switch {
+                    cond_symbol: *cond_symbol,
+                    cond_layout: *cond_layout,
+                    branches: new_branches.into_bump_slice(),
+                    default_branch,
+                    ret_layout: *ret_layout,
+                }
+            }
+            Ret(symbol) => self.fill_hole(env, *symbol),
+            Jump(_, _) | Crash(..) => stmt.clone(),
+        }
+    }
+
+    /// Allocate the tag with a placeholder for the result of the call, and continue with the
+    /// placeholder as the new hole
+    fn rewrite_cons_site(&self, env: &mut Env<'a, '_>, site: ConsSite<'a>) -> Stmt<'a> {
+        let arena = env.arena;
+
+        let placeholder = env.unique_symbol();
+        let next_hole = env.unique_symbol();
+        let unit = env.unique_symbol();
+        let update_mode = env.next_update_mode_id();
+
+        let mut tag_arguments = Vec::from_iter_in(site.tag_arguments.iter().copied(), arena);
+        tag_arguments[site.index] = placeholder;
+
+        let mut jump_args = Vec::from_iter_in(site.call_arguments.iter().copied(), arena);
+        jump_args.push(next_hole);
+
+        let jump = arena.alloc(Stmt::Jump(self.id, jump_args.into_bump_slice()));
+        let store = arena.alloc(Stmt::Let(
+            unit,
+            lowlevel(
+                LowLevel::PtrStore,
+                update_mode,
+                arena.alloc([self.hole, site.tag]),
+            ),
+            Layout::UNIT,
+            jump,
+        ));
+        let field_ptr = arena.alloc(Stmt::Let(
+            next_hole,
+            Expr::UnionFieldPtrAtIndex {
+                structure: site.tag,
+                tag_id: site.tag_id,
+                union_layout: site.tag_layout,
+                index: site.index as u64,
+            },
+            Layout::usize(env.target_info),
+            store,
+        ));
+        let tag = arena.alloc(Stmt::Let(
+            site.tag,
+            Expr::Tag {
+                tag_layout: site.tag_layout,
+                tag_id: site.tag_id,
+                arguments: tag_arguments.into_bump_slice(),
+            },
+            self.ret_layout,
+            field_ptr,
+        ));
+
+        Stmt::Let(placeholder, Expr::NullPointer, self.ret_layout, tag)
+    }
+
+    /// Write `symbol` into the hole, and return the final result
+    fn fill_hole(&self, env: &mut Env<'a, '_>, symbol: Symbol) -> Stmt<'a> {
+        let arena = env.arena;
+
+        let unit = env.unique_symbol();
+        let result = env.unique_symbol();
+        let update_mode = env.next_update_mode_id();
+
+        let ret = arena.alloc(Stmt::Ret(result));
+        let unbox = arena.alloc(Stmt::Let(
+            result,
+            Expr::ExprUnbox { symbol: self.slot },
+            self.ret_layout,
+            ret,
+        ));
+
+        Stmt::Let(
+            unit,
+            lowlevel(
+                LowLevel::PtrStore,
+                update_mode,
+                arena.alloc([self.hole, symbol]),
+            ),
+            Layout::UNIT,
+            unbox,
+        )
+    }
+}
+
+fn lowlevel<'a>(op: LowLevel, update_mode: UpdateModeId, arguments: &'a [Symbol]) -> Expr<'a> {
+    Expr::Call(Call {
+        call_type: CallType::LowLevel { op, update_mode },
+        arguments,
+    })
+}
+
+/// Make tail calls between mutually recursive procs into jumps
+///
+/// A group of procs that tail-call each other is merged into a single new proc, which takes
+/// the arguments of one of the members as a tag union and dispatches on the tag:
+///
+/// > isEven = \n -> if n == 0 then Bool.true else isOdd (n - 1)
+/// > isOdd = \n -> if n == 0 then Bool.false else isEven (n - 1)
+///
+/// becomes
+///
+/// ```elm
+/// merged state =
+///     when state is
+///         IsEven n -> if n == 0 then Bool.true else merged (IsOdd (n - 1))
+///         IsOdd n -> if n == 0 then Bool.false else merged (IsEven (n - 1))
+///
+/// isEven = \n -> merged (IsEven n)
+/// isOdd = \n -> merged (IsOdd n)
+/// ```
+///
+/// The merged proc is self-recursive, so [`make_tail_recursive`] then turns its tail calls into
+/// jumps. The members keep their names and layouts, so calls from outside the group don't change.
+///
+/// This must run before refcount insertion, on procs that were already made tail recursive.
+pub fn make_mutually_tail_recursive<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    procs: &mut MutMap<ProcKey<'a>, Proc<'a>>,
+) {
+    let mut entries: std::vec::Vec<(ProcKey<'a>, Proc<'a>)> = procs.drain().collect();
+
+    let key_to_index: MutMap<ProcKey<'a>, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(index, (key, _))| (*key, index))
+        .collect();
+
+    let mut matrix = ReferenceMatrix::new(entries.len());
+
+    for (row, (_, proc)) in entries.iter().enumerate() {
+        let mut calls = std::vec::Vec::new();
+        tail_calls(&proc.body, &mut calls);
+
+        for key in calls {
+            match key_to_index.get(&key) {
+                Some(&col) if col != row => matrix.set_row_col(row, col, true),
+                _ => {}
+            }
+        }
+    }
+
+    let groups: std::vec::Vec<std::vec::Vec<usize>> = matrix
+        .strongly_connected_components_all()
+        .groups()
+        .map(|(group, _)| group.iter_ones().collect())
+        .filter(|group: &std::vec::Vec<usize>| can_merge(&entries, group))
+        .collect();
+
+    let mut merged_procs = std::vec::Vec::with_capacity(groups.len());
+
+    for group in groups {
+        merged_procs.push(merge_group(env, layout_cache, &mut entries, &group));
+    }
+
+    procs.extend(entries);
+    procs.extend(merged_procs);
+}
+
+/// Whether the procs of a strongly connected component of the tail call graph can be merged
+fn can_merge<'a>(entries: &[(ProcKey<'a>, Proc<'a>)], group: &[usize]) -> bool {
+    if group.len() < 2 {
+        return false;
+    }
+
+    let first = &entries[group[0]].1;
+
+    group.iter().enumerate().all(|(i, index)| {
+        let proc = &entries[*index].1;
+
+        // the bodies end up in the same proc, so they must not bind the same symbols, like two
+        // specializations of the same function would
+        proc.ret_layout == first.ret_layout
+            && group[..i]
+                .iter()
+                .all(|other| entries[*other].1.name.name() != proc.name.name())
+    })
+}
+
+fn merge_group<'a>(
+    env: &mut Env<'a, '_>,
+    layout_cache: &mut LayoutCache<'a>,
+    entries: &mut [(ProcKey<'a>, Proc<'a>)],
+    group: &[usize],
+) -> (ProcKey<'a>, Proc<'a>) {
+    let arena = env.arena;
+
+    let merged_name = LambdaName::no_niche(env.unique_symbol());
+    let ret_layout = entries[group[0]].1.ret_layout;
+
+    let tags = Vec::from_iter_in(
+        group.iter().map(|index| entries[*index].0 .1.arguments),
+        arena,
+    )
+    .into_bump_slice();
+    let union_layout = UnionLayout::NonRecursive(tags);
+    let state_layout = layout_cache.put_in(Layout::Union(union_layout));
+    let arg_layouts: &'a [InLayout<'a>] = arena.alloc([state_layout]);
+
+    let merged_key = (
+        merged_name.name(),
+        ProcLayout {
+            arguments: arg_layouts,
+            result: ret_layout,
+            niche: Niche::NONE,
+        },
+    );
+
+    let members: std::vec::Vec<ProcKey<'a>> = group.iter().map(|index| entries[*index].0).collect();
+
+    // a tail call to a member becomes a tail call to the merged proc, with the arguments wrapped
+    // in the tag of the member
+    let call_merged = |env: &mut Env<'a, '_>, tag_id: usize, arguments: &'a [Symbol]| {
+        let state = env.unique_symbol();
+        let result = env.unique_symbol();
+
+        let call = Expr::Call(Call {
+            call_type: CallType::ByName {
+                name: merged_name,
+                ret_layout,
+                arg_layouts,
+                specialization_id: env.next_call_specialization_id(),
+            },
+            arguments: arena.alloc([state]),
+        });
+
+        let ret = arena.alloc(Stmt::Ret(result));
+        let call = arena.alloc(Stmt::Let(result, call, ret_layout, ret));
+        let tag = Expr::Tag {
+            tag_layout: union_layout,
+            tag_id: tag_id as _,
+            arguments,
+        };
+
+        Stmt::Let(state, tag, state_layout, call)
     };
 
+    let state = env.unique_symbol();
+    let tag_id = env.unique_symbol();
+
+    let mut branches = Vec::with_capacity_in(group.len(), arena);
+
+    for (tag_index, index) in group.iter().enumerate() {
+        let proc = &mut entries[*index].1;
+
+        let args =
+            Vec::from_iter_in(proc.args.iter().map(|(_, symbol)| *symbol), arena).into_bump_slice();
+        let wrapper = call_merged(env, tag_index, args);
+        let body = std::mem::replace(&mut proc.body, wrapper);
+        proc.is_self_recursive = SelfRecursive::NotSelfRecursive;
+
+        let body = &*arena.alloc(body);
+        let mut replace = |function_name: LambdaName<'a>,
+                           arguments: &'a [InLayout<'a>],
+                           result: InLayout<'a>,
+                           call_args: &'a [Symbol]| {
+            let key = (
+                function_name.name(),
+                ProcLayout {
+                    arguments,
+                    result,
+                    niche: function_name.niche(),
+                },
+            );
+
+            let tag_id = members.iter().position(|member| *member == key)?;
+
+            Some(&*arena.alloc(call_merged(env, tag_id, call_args)))
+        };
+        let mut body = insert_jumps(arena, body, &mut replace).unwrap_or(body);
+
+        // unpack the arguments of the member from the state
+        for (field_index, (layout, symbol)) in proc.args.iter().enumerate().rev() {
+            let field = Expr::UnionAtIndex {
+                structure: state,
+                tag_id: tag_index as _,
+                union_layout,
+                index: field_index as u64,
+            };
+
+            body = arena.alloc(Stmt::Let(*symbol, field, *layout, body));
+        }
+
+        branches.push((tag_index as u64, BranchInfo::None, body.clone()));
+    }
+
+    let (_, _, default_branch) = branches.pop().unwrap();
+    let tag_id_layout = union_layout.tag_id_layout();
+
+    let switch = arena.alloc(Stmt::Switch {
+        cond_symbol: tag_id,
+        cond_layout: tag_id_layout,
+        branches: branches.into_bump_slice(),
+        default_branch: (BranchInfo::None, arena.alloc(default_branch)),
+        ret_layout,
+    });
+
+    let get_tag_id = Expr::GetTagId {
+        structure: state,
+        union_layout,
+    };
+
+    let mut merged = Proc {
+        name: merged_name,
+        args: arena.alloc([(state_layout, state)]),
+        body: Stmt::Let(tag_id, get_tag_id, tag_id_layout, switch),
+        closure_data_layout: None,
+        ret_layout,
+        is_self_recursive: SelfRecursive::SelfRecursive(JoinPointId(env.unique_symbol())),
+        host_exposed_layouts: HostExposedLayouts::NotHostExposed,
+        stack_allocations: &[],
+    };
+
+    merged.make_tail_recursive(env, layout_cache);
+
+    (merged_key, merged)
+}
+
+/// The procs that `stmt` calls in tail position
+fn tail_calls<'a>(stmt: &Stmt<'a>, calls: &mut std::vec::Vec<ProcKey<'a>>) {
+    use Stmt::*;
+
     match stmt {
         Let(
             symbol,
-            Expr::Call(crate::ir::Call {
+            Expr::Call(Call {
+                call_type:
+                    CallType::ByName {
+                        name,
+                        ret_layout,
+                        arg_layouts,
+                        ..
+                    },
+                ..
+            }),
+            _,
+            Ret(rsym),
+        ) if symbol == rsym => calls.push((
+            name.name(),
+            ProcLayout {
+                arguments: *arg_layouts,
+                result: *ret_layout,
+                niche: name.niche(),
+            },
+        )),
+        Let(_, _, _, cont) | Refcounting(_, cont) => tail_calls(cont, calls),
+        Dbg { remainder, .. } | Expect { remainder, .. } | ExpectFx { remainder, .. } => {
+            tail_calls(remainder, calls)
+        }
+        Join {
+            body, remainder, ..
+        } => {
+            tail_calls(body, calls);
+            tail_calls(remainder, calls);
+        }
+        Switch {
+            branches,
+            default_branch,
+            ..
+        } => {
+            for (_, _, branch) in branches.iter() {
+                tail_calls(branch, calls);
+            }
+            tail_calls(default_branch.1, calls);
+        }
+        Ret(_) | Jump(_, _) | Crash(..) => {}
+    }
+}
+
+/// Replace the tail calls in `stmt` for which `replace` gives a replacement
+fn insert_jumps<'a, F>(arena: &'a Bump, stmt: &'a Stmt<'a>, replace: &mut F) -> Option<&'a Stmt<'a>>
+where
+    F: FnMut(
+        LambdaName<'a>,
+        &'a [InLayout<'a>],
+        InLayout<'a>,
+        &'a [Symbol],
+    ) -> Option<&'a Stmt<'a>>,
+{
+    use Stmt::*;
+
+    match stmt {
+        Let(
+            symbol,
+            Expr::Call(Call {
                 call_type:
                     CallType::ByName {
                         name: fsym,
@@ -101,23 +813,10 @@ fn insert_jumps<'a>(
             }),
             _,
             Stmt::Ret(rsym),
-        ) if symbol == rsym && is_equal_function(*fsym, arg_layouts, *ret_layout) => {
-            // replace the call and return with a jump
-
-            let jump = Stmt::Jump(goal_id, arguments);
-
-            Some(arena.alloc(jump))
-        }
+        ) if symbol == rsym => replace(*fsym, *arg_layouts, *ret_layout, *arguments),
 
         Let(symbol, expr, layout, cont) => {
-            let opt_cont = insert_jumps(
-                arena,
-                cont,
-                goal_id,
-                needle,
-                needle_arguments,
-                needle_result,
-            );
+            let opt_cont = insert_jumps(arena, cont, replace);
 
             if opt_cont.is_some() {
                 let cont = opt_cont.unwrap_or(cont);
@@ -134,22 +833,8 @@ fn insert_jumps<'a>(
             remainder,
             body: continuation,
         } => {
-            let opt_remainder = insert_jumps(
-                arena,
-                remainder,
-                goal_id,
-                needle,
-                needle_arguments,
-                needle_result,
-            );
-            let opt_continuation = insert_jumps(
-                arena,
-                continuation,
-                goal_id,
-                needle,
-                needle_arguments,
-                needle_result,
-            );
+            let opt_remainder = insert_jumps(arena, remainder, replace);
+            let opt_continuation = insert_jumps(arena, continuation, replace);
 
             if opt_remainder.is_some() || opt_continuation.is_some() {
                 let remainder = opt_remainder.unwrap_or(remainder);
@@ -172,27 +857,13 @@ fn insert_jumps<'a>(
             default_branch,
             ret_layout,
         } => {
-            let opt_default = insert_jumps(
-                arena,
-                default_branch.1,
-                goal_id,
-                needle,
-                needle_arguments,
-                needle_result,
-            );
+            let opt_default = insert_jumps(arena, default_branch.1, replace);
 
             let mut did_change = false;
 
             let opt_branches = Vec::from_iter_in(
                 branches.iter().map(|(label, info, branch)| {
-                    match insert_jumps(
-                        arena,
-                        branch,
-                        goal_id,
-                        needle,
-                        needle_arguments,
-                        needle_result,
-                    ) {
+                    match insert_jumps(arena, branch, replace) {
                         None => None,
                         Some(branch) => {
                             did_change = true;
@@ -236,32 +907,16 @@ fn insert_jumps<'a>(
                 None
             }
         }
-        Refcounting(modify, cont) => {
-            match insert_jumps(
-                arena,
-                cont,
-                goal_id,
-                needle,
-                needle_arguments,
-                needle_result,
-            ) {
-                Some(cont) => Some(arena.alloc(Refcounting(*modify, cont))),
-                None => None,
-            }
-        }
+        Refcounting(modify, cont) => match insert_jumps(arena, cont, replace) {
+            Some(cont) => Some(arena.alloc(Refcounting(*modify, cont))),
+            None => None,
+        },
 
         Dbg {
             symbol,
             variable,
             remainder,
-        } => match insert_jumps(
-            arena,
-            remainder,
-            goal_id,
-            needle,
-            needle_arguments,
-            needle_result,
-        ) {
+        } => match insert_jumps(arena, remainder, replace) {
             Some(cont) => Some(arena.alloc(Dbg {
                 symbol: *symbol,
                 variable: *variable,
@@ -276,14 +931,7 @@ fn insert_jumps<'a>(
             lookups,
             variables,
             remainder,
-        } => match insert_jumps(
-            arena,
-            remainder,
-            goal_id,
-            needle,
-            needle_arguments,
-            needle_result,
-        ) {
+        } => match insert_jumps(arena, remainder, replace) {
             Some(cont) => Some(arena.alloc(Expect {
                 condition: *condition,
                 region: *region,
@@ -300,14 +948,7 @@ fn insert_jumps<'a>(
             lookups,
             variables,
             remainder,
-        } => match insert_jumps(
-            arena,
-            remainder,
-            goal_id,
-            needle,
-            needle_arguments,
-            needle_result,
-        ) {
+        } => match insert_jumps(arena, remainder, replace) {
             Some(cont) => Some(arena.alloc(ExpectFx {
                 condition: *condition,
                 region: *region,
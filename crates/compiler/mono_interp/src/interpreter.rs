@@ -0,0 +1,466 @@
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_collections::MutMap;
+use roc_module::symbol::Symbol;
+use roc_mono::ir::{
+    Call, CallType, Expr, JoinPointId, ListLiteralElement, Literal, LookupType, ModifyRc, Param,
+    PassedFunction, Proc, ProcLayout, Stmt,
+};
+use roc_mono::layout::{
+    Builtin, InLayout, Layout, LayoutInterner, STLayoutInterner, TagIdIntType, UnionLayout,
+};
+use roc_mono::low_level::HigherOrder;
+use roc_region::all::Region;
+
+use crate::value::{Heap, HeapData, Value};
+use crate::Error;
+
+/// Calls nest on the Rust stack, so they need a limit well below where that would overflow.
+/// Tail calls are join points, which do not count.
+const MAX_CALL_DEPTH: usize = 10_000;
+
+type Procs<'a> = MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>;
+
+/// An `expect` whose condition was false
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailedExpect<'a> {
+    pub region: Region,
+    /// The values of the symbols the condition looked up. They are kept alive, so whoever takes
+    /// them out of [Interpreter::failed_expects] has to `dec` them.
+    pub lookups: Vec<Value>,
+    pub variables: &'a [LookupType],
+}
+
+/// The values and join points in scope in a proc
+#[derive(Default)]
+struct Frame<'a, 'r> {
+    values: MutMap<Symbol, Value>,
+    joinpoints: MutMap<JoinPointId, (&'a [Param<'a>], &'r Stmt<'a>)>,
+}
+
+impl<'a, 'r> Frame<'a, 'r> {
+    fn get(&self, symbol: Symbol) -> Result<Value, Error> {
+        match self.values.get(&symbol) {
+            Some(value) => Ok(value.clone()),
+            None => Err(Error::NoSymbolInScope(symbol)),
+        }
+    }
+
+    fn get_all(&self, symbols: &[Symbol]) -> Result<Vec<Value>, Error> {
+        symbols.iter().map(|symbol| self.get(*symbol)).collect()
+    }
+}
+
+pub struct Interpreter<'a, 'r> {
+    pub(crate) interner: &'r STLayoutInterner<'a>,
+    procs: &'r Procs<'a>,
+    pub heap: Heap,
+    /// The `expect`s that failed, in order
+    pub failed_expects: Vec<FailedExpect<'a>>,
+    /// The values passed to `dbg`, in order
+    pub dbg_output: Vec<String>,
+    depth: usize,
+}
+
+impl<'a, 'r> Interpreter<'a, 'r> {
+    pub fn new(interner: &'r STLayoutInterner<'a>, procs: &'r Procs<'a>) -> Self {
+        Interpreter {
+            interner,
+            procs,
+            heap: Heap::default(),
+            failed_expects: Vec::new(),
+            dbg_output: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Call a proc. Like every proc, it takes ownership of its arguments, and the caller owns
+    /// the returned value.
+    pub fn call(
+        &mut self,
+        name: Symbol,
+        layout: ProcLayout<'a>,
+        arguments: Vec<Value>,
+    ) -> Result<Value, Error> {
+        let procs = self.procs;
+        let proc = match procs.get(&(name, layout)) {
+            Some(proc) => proc,
+            None => return Err(Error::UndefinedProc(name)),
+        };
+
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(Error::StackOverflow);
+        }
+
+        let mut frame = Frame::default();
+        for ((_, symbol), value) in proc.args.iter().zip(arguments) {
+            frame.values.insert(*symbol, value);
+        }
+
+        self.depth += 1;
+        let result = self.eval_stmt(&mut frame, &proc.body);
+        self.depth -= 1;
+
+        result
+    }
+
+    fn eval_stmt(&mut self, frame: &mut Frame<'a, 'r>, stmt: &'r Stmt<'a>) -> Result<Value, Error> {
+        let mut stmt = stmt;
+
+        loop {
+            match stmt {
+                Stmt::Let(symbol, expr, layout, rest) => {
+                    let value = self.eval_expr(frame, expr, *layout)?;
+                    frame.values.insert(*symbol, value);
+                    stmt = rest;
+                }
+                Stmt::Switch {
+                    cond_symbol,
+                    branches,
+                    default_branch,
+                    ..
+                } => {
+                    let cond = self.switch_value(&frame.get(*cond_symbol)?)?;
+
+                    stmt = match branches.iter().find(|(value, _, _)| *value == cond) {
+                        Some((_, _, branch)) => branch,
+                        None => default_branch.1,
+                    };
+                }
+                Stmt::Ret(symbol) => return frame.get(*symbol),
+                Stmt::Refcounting(modify_rc, rest) => {
+                    match *modify_rc {
+                        ModifyRc::Inc(symbol, amount) => {
+                            self.heap.inc(&frame.get(symbol)?, amount as usize)?
+                        }
+                        ModifyRc::Dec(symbol) => self.heap.dec(&frame.get(symbol)?)?,
+                        ModifyRc::DecRef(symbol) => self.heap.decref(&frame.get(symbol)?)?,
+                    }
+                    stmt = rest;
+                }
+                Stmt::Expect {
+                    condition,
+                    region,
+                    lookups,
+                    variables,
+                    remainder,
+                }
+                | Stmt::ExpectFx {
+                    condition,
+                    region,
+                    lookups,
+                    variables,
+                    remainder,
+                } => {
+                    // The expect owns the values it looks up. When it fails, they are handed
+                    // over to the failure, so they can still be shown.
+                    let values = frame.get_all(lookups)?;
+
+                    if frame.get(*condition)? != Value::Bool(true) {
+                        self.failed_expects.push(FailedExpect {
+                            region: *region,
+                            lookups: values,
+                            variables: *variables,
+                        });
+                    } else {
+                        for value in values {
+                            self.heap.dec(&value)?;
+                        }
+                    }
+
+                    stmt = remainder;
+                }
+                Stmt::Dbg {
+                    symbol, remainder, ..
+                } => {
+                    let value = frame.get(*symbol)?;
+                    self.dbg_output.push(self.heap.display(&value));
+                    self.heap.dec(&value)?;
+                    stmt = remainder;
+                }
+                Stmt::Join {
+                    id,
+                    parameters,
+                    body,
+                    remainder,
+                } => {
+                    frame.joinpoints.insert(*id, (*parameters, *body));
+                    stmt = remainder;
+                }
+                Stmt::Jump(id, arguments) => {
+                    let (parameters, body) = match frame.joinpoints.get(id) {
+                        Some(joinpoint) => *joinpoint,
+                        None => return Err(Error::NoJoinPoint(*id)),
+                    };
+
+                    let arguments = frame.get_all(arguments)?;
+                    for (param, value) in parameters.iter().zip(arguments) {
+                        frame.values.insert(param.symbol, value);
+                    }
+
+                    stmt = body;
+                }
+                Stmt::Crash(symbol, tag) => {
+                    let message = self.heap.str(&frame.get(*symbol)?)?.to_string();
+                    return Err(Error::Crash { message, tag: *tag });
+                }
+            }
+        }
+    }
+
+    fn switch_value(&self, value: &Value) -> Result<u64, Error> {
+        match value {
+            Value::Int(n, _) => Ok(*n as u64),
+            Value::Bool(b) => Ok(*b as u64),
+            other => Err(Error::type_mismatch("a switch condition", other)),
+        }
+    }
+
+    fn eval_expr(
+        &mut self,
+        frame: &mut Frame<'a, 'r>,
+        expr: &'r Expr<'a>,
+        layout: InLayout<'a>,
+    ) -> Result<Value, Error> {
+        match expr {
+            Expr::Literal(literal) => self.eval_literal(literal, layout),
+            Expr::Call(call) => self.eval_call(frame, call, layout),
+            Expr::Tag {
+                tag_layout,
+                tag_id,
+                arguments,
+            } => {
+                let fields = frame.get_all(arguments)?;
+                Ok(self.make_tag(*tag_layout, *tag_id, fields))
+            }
+            Expr::Struct(fields) => Ok(Value::Struct(frame.get_all(fields)?)),
+            Expr::NullPointer => Ok(Value::Null),
+            Expr::StructAtIndex {
+                index, structure, ..
+            } => match frame.get(*structure)? {
+                Value::Struct(mut fields) if (*index as usize) < fields.len() => {
+                    Ok(fields.swap_remove(*index as usize))
+                }
+                other => Err(Error::type_mismatch("a struct", &other)),
+            },
+            Expr::GetTagId {
+                structure,
+                union_layout,
+            } => {
+                let structure = frame.get(*structure)?;
+                let tag_id = match self.heap.tag(&structure)? {
+                    Some((tag_id, _)) => tag_id,
+                    None => null_tag_id(*union_layout),
+                };
+
+                Ok(Value::Int(tag_id as i128, self.int_width(layout)?))
+            }
+            Expr::UnionAtIndex {
+                structure, index, ..
+            } => {
+                let structure = frame.get(*structure)?;
+                match self.heap.tag(&structure)? {
+                    Some((_, fields)) if (*index as usize) < fields.len() => {
+                        Ok(fields[*index as usize].clone())
+                    }
+                    _ => Err(Error::type_mismatch("a tag with fields", &structure)),
+                }
+            }
+            Expr::UnionFieldPtrAtIndex {
+                structure, index, ..
+            } => match frame.get(*structure)? {
+                Value::Ptr(id) => Ok(Value::FieldPtr(id, *index as usize)),
+                other => Err(Error::type_mismatch("a heap-allocated tag", &other)),
+            },
+            Expr::Array { elem_layout, elems } => {
+                let mut elements = Vec::with_capacity(elems.len());
+                for elem in elems.iter() {
+                    elements.push(match elem {
+                        ListLiteralElement::Literal(literal) => {
+                            self.eval_literal(literal, *elem_layout)?
+                        }
+                        ListLiteralElement::Symbol(symbol) => frame.get(*symbol)?,
+                    });
+                }
+
+                Ok(self.heap.alloc_list(elements))
+            }
+            Expr::EmptyArray => Ok(Value::List(None)),
+            Expr::ExprBox { symbol } => {
+                let inner = frame.get(*symbol)?;
+                Ok(Value::Ptr(self.heap.alloc(HeapData::Box(inner))))
+            }
+            Expr::ExprUnbox { symbol } => match frame.get(*symbol)? {
+                Value::Ptr(id) => match self.heap.get(id)? {
+                    HeapData::Box(inner) => Ok(inner.clone()),
+                    other => Err(Error::type_mismatch("a box", other)),
+                },
+                other => Err(Error::type_mismatch("a box", &other)),
+            },
+            Expr::Reuse {
+                symbol,
+                tag_layout,
+                tag_id,
+                arguments,
+                ..
+            } => {
+                let fields = frame.get_all(arguments)?;
+                match frame.get(*symbol)? {
+                    Value::Ptr(id) if !tag_layout.tag_is_null(*tag_id) => {
+                        self.heap.set(
+                            id,
+                            HeapData::Tag {
+                                tag_id: *tag_id,
+                                fields,
+                            },
+                        )?;
+                        Ok(Value::Ptr(id))
+                    }
+                    token => {
+                        self.heap.decref(&token)?;
+                        Ok(self.make_tag(*tag_layout, *tag_id, fields))
+                    }
+                }
+            }
+            Expr::Reset { symbol, .. } => match frame.get(*symbol)? {
+                Value::Ptr(id) if self.heap.is_unique(id)? => {
+                    self.heap.reset(id)?;
+                    Ok(Value::Ptr(id))
+                }
+                value => {
+                    self.heap.dec(&value)?;
+                    Ok(Value::Null)
+                }
+            },
+            Expr::ResetRef { symbol, .. } => match frame.get(*symbol)? {
+                Value::Ptr(id) if self.heap.is_unique(id)? => Ok(Value::Ptr(id)),
+                value => {
+                    self.heap.dec(&value)?;
+                    Ok(Value::Null)
+                }
+            },
+            Expr::RuntimeErrorFunction(message) => Err(Error::roc_crash(message)),
+        }
+    }
+
+    pub(crate) fn make_tag(
+        &mut self,
+        union_layout: UnionLayout<'a>,
+        tag_id: TagIdIntType,
+        fields: Vec<Value>,
+    ) -> Value {
+        match union_layout {
+            UnionLayout::NonRecursive(_) => Value::Tag { tag_id, fields },
+            _ if union_layout.tag_is_null(tag_id) => Value::Null,
+            _ => Value::Ptr(self.heap.alloc(HeapData::Tag { tag_id, fields })),
+        }
+    }
+
+    fn eval_literal(
+        &mut self,
+        literal: &Literal<'a>,
+        layout: InLayout<'a>,
+    ) -> Result<Value, Error> {
+        Ok(match literal {
+            Literal::Int(bytes) => Value::Int(i128::from_ne_bytes(*bytes), self.int_width(layout)?),
+            Literal::U128(bytes) => {
+                Value::Int(u128::from_ne_bytes(*bytes) as i128, self.int_width(layout)?)
+            }
+            Literal::Float(n) => match self.interner.get(layout) {
+                Layout::Builtin(Builtin::Float(FloatWidth::F32)) => {
+                    Value::Float(*n as f32 as f64, FloatWidth::F32)
+                }
+                _ => Value::Float(*n, FloatWidth::F64),
+            },
+            Literal::Decimal(bytes) => Value::Dec(i128::from_ne_bytes(*bytes)),
+            Literal::Str(string) => self.heap.alloc_str(string.to_string()),
+            Literal::Bool(b) => Value::Bool(*b),
+            Literal::Byte(b) => Value::Int(*b as i128, IntWidth::U8),
+        })
+    }
+
+    pub(crate) fn int_width(&self, layout: InLayout<'a>) -> Result<IntWidth, Error> {
+        match self.interner.runtime_representation(layout) {
+            Layout::Builtin(Builtin::Int(width)) => Ok(width),
+            other => Err(Error::type_mismatch("an integer layout", &other)),
+        }
+    }
+
+    fn eval_call(
+        &mut self,
+        frame: &mut Frame<'a, 'r>,
+        call: &'r Call<'a>,
+        layout: InLayout<'a>,
+    ) -> Result<Value, Error> {
+        match call.call_type.clone().replace_lowlevel_wrapper() {
+            CallType::ByName {
+                name,
+                ret_layout,
+                arg_layouts,
+                ..
+            } => {
+                let proc_layout = ProcLayout {
+                    arguments: arg_layouts,
+                    result: ret_layout,
+                    niche: name.niche(),
+                };
+
+                self.call(name.name(), proc_layout, frame.get_all(call.arguments)?)
+            }
+            CallType::Foreign { foreign_symbol, .. } => {
+                Err(Error::ForeignCall(foreign_symbol.as_str().to_string()))
+            }
+            CallType::LowLevel { op, .. } => {
+                self.eval_lowlevel(op, frame.get_all(call.arguments)?, layout)
+            }
+            CallType::HigherOrder(higher_order) => {
+                // The arguments are the lists, the function and its closure data. The function
+                // is not a value in scope; it is described by the `passed_function`.
+                let lists = match higher_order.op {
+                    HigherOrder::ListMap { xs } | HigherOrder::ListSortWith { xs } => vec![xs],
+                    HigherOrder::ListMap2 { xs, ys } => vec![xs, ys],
+                    HigherOrder::ListMap3 { xs, ys, zs } => vec![xs, ys, zs],
+                    HigherOrder::ListMap4 { xs, ys, zs, ws } => vec![xs, ys, zs, ws],
+                };
+                let closure_data = match call.arguments.last() {
+                    Some(symbol) => frame.get(*symbol)?,
+                    None => return Err(Error::type_mismatch("closure data", &call.arguments)),
+                };
+
+                self.eval_higher_order(higher_order, frame.get_all(&lists)?, closure_data)
+            }
+        }
+    }
+
+    /// Call the function passed to a higher-order builtin
+    pub(crate) fn call_passed_function(
+        &mut self,
+        passed_function: &PassedFunction<'a>,
+        mut arguments: Vec<Value>,
+        captured_environment: &Value,
+    ) -> Result<Value, Error> {
+        let layout = ProcLayout {
+            arguments: passed_function.argument_layouts,
+            result: passed_function.return_layout,
+            niche: passed_function.name.niche(),
+        };
+
+        // Functions that capture nothing do not take the closure data as an argument
+        if layout.arguments.len() > arguments.len() {
+            // The function owns its arguments, so every call needs its own reference
+            if passed_function.owns_captured_environment {
+                self.heap.inc(captured_environment, 1)?;
+            }
+            arguments.push(captured_environment.clone());
+        }
+
+        self.call(passed_function.name.name(), layout, arguments)
+    }
+}
+
+fn null_tag_id(union_layout: UnionLayout) -> TagIdIntType {
+    match union_layout {
+        UnionLayout::NullableWrapped { nullable_id, .. } => nullable_id,
+        UnionLayout::NullableUnwrapped { nullable_id, .. } => nullable_id as TagIdIntType,
+        _ => 0,
+    }
+}
@@ -0,0 +1,85 @@
+//! An interpreter for the [mono IR][roc_mono::ir::Proc].
+//!
+//! It runs the procs that the backends would otherwise compile, tracking the refcount of every
+//! allocation exactly. That makes it a reference to compare the backends against, and it
+//! reports leaks and uses after free precisely instead of as a crash (or not at all).
+//!
+//! Builtins are reimplemented in Rust, following the semantics of the Zig bitcode, including how
+//! they consume their arguments. Builtins that are not implemented yet report
+//! [Error::UnsupportedLowLevel].
+mod interpreter;
+mod low_level;
+mod value;
+
+use std::fmt;
+
+use roc_module::low_level::LowLevel;
+use roc_module::symbol::Symbol;
+use roc_mono::ir::{CrashTag, JoinPointId};
+
+pub use interpreter::{FailedExpect, Interpreter};
+pub use value::{wrap_int, CellId, Heap, HeapData, Value};
+
+/// Everything that can stop the interpreter
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+    /// The program crashed, either with `crash` or in a builtin, e.g. on integer overflow
+    Crash {
+        message: String,
+        tag: CrashTag,
+    },
+    /// An allocation was used after its refcount dropped to zero
+    UseAfterFree(CellId),
+    /// A value did not have the shape its layout promised
+    TypeMismatch {
+        expected: &'static str,
+        found: String,
+    },
+    NoSymbolInScope(Symbol),
+    NoJoinPoint(JoinPointId),
+    UndefinedProc(Symbol),
+    UnsupportedLowLevel(LowLevel),
+    /// Calls to the host are not supported
+    ForeignCall(String),
+    StackOverflow,
+}
+
+impl Error {
+    pub(crate) fn type_mismatch(expected: &'static str, found: &impl fmt::Debug) -> Self {
+        Error::TypeMismatch {
+            expected,
+            found: format!("{:?}", found),
+        }
+    }
+
+    pub(crate) fn roc_crash(message: &str) -> Self {
+        Error::Crash {
+            message: message.to_string(),
+            tag: CrashTag::Roc,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Crash { message, .. } => write!(f, "Roc failed with message: {:?}", message),
+            Error::UseAfterFree(id) => write!(f, "allocation #{} was used after it was freed", id),
+            Error::TypeMismatch { expected, found } => {
+                write!(f, "expected {}, but found {}", expected, found)
+            }
+            Error::NoSymbolInScope(symbol) => write!(f, "{:?} is not in scope", symbol),
+            Error::NoJoinPoint(id) => write!(f, "join point {:?} is not in scope", id),
+            Error::UndefinedProc(symbol) => {
+                write!(f, "{:?} has no specialization with this layout", symbol)
+            }
+            Error::UnsupportedLowLevel(op) => {
+                write!(f, "the interpreter does not implement {:?} yet", op)
+            }
+            Error::ForeignCall(name) => {
+                write!(f, "the interpreter cannot call the host function {}", name)
+            }
+            Error::StackOverflow => write!(f, "the stack overflowed"),
+        }
+    }
+}
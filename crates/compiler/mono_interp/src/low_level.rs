@@ -0,0 +1,944 @@
+//! Rust reimplementations of the builtins.
+//!
+//! These follow the Zig bitcode (and the LLVM backend, for the builtins it implements inline):
+//! arguments that the borrow signature marks as owned are consumed, reusing their allocation
+//! when it is unique, and borrowed arguments are left alone.
+use std::cmp::Ordering;
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_module::low_level::LowLevel;
+use roc_mono::ir::HigherOrderLowLevel;
+use roc_mono::layout::{Builtin, InLayout, Layout, LayoutInterner};
+use roc_mono::low_level::HigherOrder;
+
+use crate::interpreter::Interpreter;
+use crate::value::{wrap_int, Value};
+use crate::Error;
+
+/// One, as a `Dec`
+const DEC_ONE: i128 = 1_000_000_000_000_000_000;
+
+#[derive(Clone, Copy)]
+enum Arith {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl Arith {
+    fn from_lowlevel(op: LowLevel) -> Option<Self> {
+        use LowLevel::*;
+
+        match op {
+            NumAdd | NumAddWrap | NumAddChecked | NumAddSaturated => Some(Arith::Add),
+            NumSub | NumSubWrap | NumSubChecked | NumSubSaturated => Some(Arith::Sub),
+            NumMul | NumMulWrap | NumMulChecked | NumMulSaturated => Some(Arith::Mul),
+            _ => None,
+        }
+    }
+
+    fn overflow_message(self) -> &'static str {
+        match self {
+            Arith::Add => "integer addition overflowed!",
+            Arith::Sub => "integer subtraction overflowed!",
+            Arith::Mul => "integer multiplication overflowed!",
+        }
+    }
+
+    fn dec_overflow_message(self) -> &'static str {
+        match self {
+            Arith::Add => "decimal addition overflowed",
+            Arith::Sub => "decimal subtraction overflowed",
+            Arith::Mul => "decimal multiplication overflowed",
+        }
+    }
+}
+
+impl<'a, 'r> Interpreter<'a, 'r> {
+    pub(crate) fn eval_lowlevel(
+        &mut self,
+        op: LowLevel,
+        args: Vec<Value>,
+        layout: InLayout<'a>,
+    ) -> Result<Value, Error> {
+        use LowLevel::*;
+
+        let arg = |index: usize| match args.get(index) {
+            Some(value) => Ok(value),
+            None => Err(Error::type_mismatch("another argument", &args)),
+        };
+
+        match op {
+            // Str
+            StrConcat => {
+                let mut string = self.heap.take_str(arg(0)?)?;
+                string.push_str(self.heap.str(arg(1)?)?);
+                Ok(self.heap.alloc_str(string))
+            }
+            StrJoinWith => {
+                let mut strings = Vec::new();
+                for element in self.heap.list(arg(0)?)? {
+                    strings.push(self.heap.str(element)?);
+                }
+                let string = strings.join(self.heap.str(arg(1)?)?);
+                Ok(self.heap.alloc_str(string))
+            }
+            StrIsEmpty => Ok(Value::Bool(self.heap.str(arg(0)?)?.is_empty())),
+            StrStartsWith => {
+                let (string, prefix) = (self.heap.str(arg(0)?)?, self.heap.str(arg(1)?)?);
+                Ok(Value::Bool(string.starts_with(prefix)))
+            }
+            StrStartsWithScalar => {
+                let string = self.heap.str(arg(0)?)?;
+                let scalar = char::from_u32(int(arg(1)?)? as u32);
+                Ok(Value::Bool(string.chars().next() == scalar))
+            }
+            StrEndsWith => {
+                let (string, suffix) = (self.heap.str(arg(0)?)?, self.heap.str(arg(1)?)?);
+                Ok(Value::Bool(string.ends_with(suffix)))
+            }
+            StrSplit => {
+                let (string, delimiter) = (self.heap.str(arg(0)?)?, self.heap.str(arg(1)?)?);
+                let parts: Vec<String> = if delimiter.is_empty() {
+                    vec![string.to_string()]
+                } else {
+                    string.split(delimiter).map(str::to_string).collect()
+                };
+                let elements = parts
+                    .into_iter()
+                    .map(|part| self.heap.alloc_str(part))
+                    .collect();
+                Ok(self.heap.alloc_list(elements))
+            }
+            StrCountUtf8Bytes | StrGetCapacity => {
+                let len = self.heap.str(arg(0)?)?.len();
+                Ok(Value::int(len as i128, self.int_width(layout)?))
+            }
+            StrFromInt => {
+                let string = self.heap.display(arg(0)?);
+                Ok(self.heap.alloc_str(string))
+            }
+            StrFromFloat => {
+                let string = format_float(arg(0)?)?;
+                Ok(self.heap.alloc_str(string))
+            }
+            NumToStr => {
+                let string = match arg(0)? {
+                    value @ Value::Float(..) => format_float(value)?,
+                    value => self.heap.display(value),
+                };
+                Ok(self.heap.alloc_str(string))
+            }
+            StrFromUtf8Range => {
+                let (start, count) = (usize_arg(arg(1)?)?, usize_arg(arg(2)?)?);
+                let bytes = self
+                    .heap
+                    .list(arg(0)?)?
+                    .iter()
+                    .skip(start)
+                    .take(count)
+                    .map(|byte| int(byte).map(|byte| byte as u8))
+                    .collect::<Result<Vec<u8>, Error>>()?;
+                self.heap.dec(arg(0)?)?;
+
+                let nat = match self.interner.get(layout) {
+                    Layout::Struct { field_layouts, .. } => self.int_width(field_layouts[0])?,
+                    other => return Err(Error::type_mismatch("a struct layout", &other)),
+                };
+
+                // { aByteIndex : Nat, bString : Str, cIsOk : Bool, dProblemCode : Utf8ByteProblem }
+                match String::from_utf8(bytes) {
+                    Ok(string) => Ok(Value::Struct(vec![
+                        Value::Int(0, nat),
+                        self.heap.alloc_str(string),
+                        Value::Bool(true),
+                        Value::Int(0, IntWidth::U8),
+                    ])),
+                    Err(error) => {
+                        let error = error.utf8_error();
+                        let problem = match error.error_len() {
+                            // The Zig builtin distinguishes more kinds of invalid sequences
+                            Some(_) => 0,
+                            None => 1,
+                        };
+                        Ok(Value::Struct(vec![
+                            Value::Int(error.valid_up_to() as i128, nat),
+                            Value::Str(None),
+                            Value::Bool(false),
+                            Value::Int(problem, IntWidth::U8),
+                        ]))
+                    }
+                }
+            }
+            StrToUtf8 => {
+                let string = self.heap.take_str(arg(0)?)?;
+                let bytes = string
+                    .bytes()
+                    .map(|byte| Value::Int(byte as i128, IntWidth::U8))
+                    .collect();
+                Ok(self.heap.alloc_list(bytes))
+            }
+            StrRepeat => {
+                let string = self.heap.str(arg(0)?)?.repeat(usize_arg(arg(1)?)?);
+                Ok(self.heap.alloc_str(string))
+            }
+            StrTrim | StrTrimLeft | StrTrimRight => {
+                let string = self.heap.take_str(arg(0)?)?;
+                let trimmed = match op {
+                    StrTrim => string.trim(),
+                    StrTrimLeft => string.trim_start(),
+                    _ => string.trim_end(),
+                };
+                Ok(self.heap.alloc_str(trimmed.to_string()))
+            }
+            StrToScalars => {
+                let scalars = self
+                    .heap
+                    .str(arg(0)?)?
+                    .chars()
+                    .map(|c| Value::Int(c as i128, IntWidth::U32))
+                    .collect();
+                Ok(self.heap.alloc_list(scalars))
+            }
+            StrGetUnsafe => {
+                let string = self.heap.str(arg(0)?)?;
+                match string.as_bytes().get(usize_arg(arg(1)?)?) {
+                    Some(byte) => Ok(Value::Int(*byte as i128, IntWidth::U8)),
+                    None => Err(Error::roc_crash("string index out of bounds")),
+                }
+            }
+            StrSubstringUnsafe => {
+                let string = self.heap.str(arg(0)?)?;
+                let (start, len) = (usize_arg(arg(1)?)?, usize_arg(arg(2)?)?);
+                match string.get(start..start + len) {
+                    Some(substring) => {
+                        let substring = substring.to_string();
+                        Ok(self.heap.alloc_str(substring))
+                    }
+                    None => Err(Error::roc_crash("invalid substring")),
+                }
+            }
+            StrReserve | StrReleaseExcessCapacity => {
+                // Capacity is not modelled
+                Ok(arg(0)?.clone())
+            }
+            StrWithCapacity => Ok(Value::Str(None)),
+            StrAppendScalar => {
+                let mut string = self.heap.take_str(arg(0)?)?;
+                match char::from_u32(int(arg(1)?)? as u32) {
+                    Some(c) => string.push(c),
+                    None => return Err(Error::roc_crash("invalid unicode scalar value")),
+                }
+                Ok(self.heap.alloc_str(string))
+            }
+            StrGetScalarUnsafe => {
+                let string = self.heap.str(arg(0)?)?;
+                let index = usize_arg(arg(1)?)?;
+                let c = match string.get(index..).and_then(|rest| rest.chars().next()) {
+                    Some(c) => c,
+                    None => return Err(Error::roc_crash("string index out of bounds")),
+                };
+                let nat = self.int_width(struct_field(self, layout, 0)?)?;
+
+                // { bytesParsed : Nat, scalar : U32 }
+                Ok(Value::Struct(vec![
+                    Value::Int(c.len_utf8() as i128, nat),
+                    Value::Int(c as i128, IntWidth::U32),
+                ]))
+            }
+
+            // List
+            ListLen | ListGetCapacity => {
+                let len = self.heap.list(arg(0)?)?.len();
+                Ok(Value::int(len as i128, self.int_width(layout)?))
+            }
+            ListWithCapacity => Ok(Value::List(None)),
+            ListReserve | ListReleaseExcessCapacity => Ok(arg(0)?.clone()),
+            ListAppendUnsafe => {
+                let mut elements = self.heap.take_list(arg(0)?)?;
+                elements.push(arg(1)?.clone());
+                Ok(self.heap.alloc_list(elements))
+            }
+            ListPrepend => {
+                let mut elements = self.heap.take_list(arg(0)?)?;
+                elements.insert(0, arg(1)?.clone());
+                Ok(self.heap.alloc_list(elements))
+            }
+            ListConcat => {
+                let mut elements = self.heap.take_list(arg(0)?)?;
+                elements.extend(self.heap.take_list(arg(1)?)?);
+                Ok(self.heap.alloc_list(elements))
+            }
+            ListGetUnsafe => {
+                // The element is borrowed from the list
+                let index = usize_arg(arg(1)?)?;
+                match self.heap.list(arg(0)?)?.get(index) {
+                    Some(element) => Ok(element.clone()),
+                    None => Err(Error::roc_crash("list index out of bounds")),
+                }
+            }
+            ListReplaceUnsafe => {
+                let mut elements = self.heap.take_list(arg(0)?)?;
+                let index = usize_arg(arg(1)?)?;
+                let old = match elements.get_mut(index) {
+                    Some(element) => std::mem::replace(element, arg(2)?.clone()),
+                    None => return Err(Error::roc_crash("list index out of bounds")),
+                };
+                let list = self.heap.alloc_list(elements);
+
+                // { list : List a, value : a }, sorted by alignment, then by name
+                let value_first = match self.interner.get(layout) {
+                    Layout::Struct { field_layouts, .. } => {
+                        self.interner.get(field_layouts[1])
+                            == Layout::Builtin(Builtin::List(field_layouts[0]))
+                    }
+                    other => return Err(Error::type_mismatch("a struct layout", &other)),
+                };
+
+                if value_first {
+                    Ok(Value::Struct(vec![old, list]))
+                } else {
+                    Ok(Value::Struct(vec![list, old]))
+                }
+            }
+            ListSublist => {
+                let elements = self.heap.take_list(arg(0)?)?;
+                let (start, len) = (usize_arg(arg(1)?)?, usize_arg(arg(2)?)?);
+                let (mut kept, mut dropped) = (Vec::new(), Vec::new());
+                for (index, element) in elements.into_iter().enumerate() {
+                    if index >= start && index - start < len {
+                        kept.push(element);
+                    } else {
+                        dropped.push(element);
+                    }
+                }
+                for element in dropped.iter() {
+                    self.heap.dec(element)?;
+                }
+                Ok(self.heap.alloc_list(kept))
+            }
+            ListDropAt => {
+                let mut elements = self.heap.take_list(arg(0)?)?;
+                let index = usize_arg(arg(1)?)?;
+                if index < elements.len() {
+                    let dropped = elements.remove(index);
+                    self.heap.dec(&dropped)?;
+                }
+                Ok(self.heap.alloc_list(elements))
+            }
+            ListSwap => {
+                let mut elements = self.heap.take_list(arg(0)?)?;
+                let (i, j) = (usize_arg(arg(1)?)?, usize_arg(arg(2)?)?);
+                if i < elements.len() && j < elements.len() {
+                    elements.swap(i, j);
+                }
+                Ok(self.heap.alloc_list(elements))
+            }
+            PtrCast => match arg(0)? {
+                Value::Ptr(id) => Ok(Value::FieldPtr(*id, 0)),
+                other => Ok(other.clone()),
+            },
+            PtrStore => {
+                self.heap.store(arg(0)?, arg(1)?.clone())?;
+                Ok(Value::unit())
+            }
+            ListIsUnique | RefCountIsUnique => match arg(0)? {
+                Value::Ptr(id) | Value::List(Some(id)) | Value::Str(Some(id)) => {
+                    Ok(Value::Bool(self.heap.is_unique(*id)?))
+                }
+                _ => Ok(Value::Bool(true)),
+            },
+
+            // Num
+            NumAdd | NumAddWrap | NumAddChecked | NumAddSaturated | NumSub | NumSubWrap
+            | NumSubChecked | NumSubSaturated | NumMul | NumMulWrap | NumMulChecked
+            | NumMulSaturated => {
+                let arith = Arith::from_lowlevel(op).unwrap();
+                arithmetic(op, arith, arg(0)?, arg(1)?)
+            }
+            NumGt | NumGte | NumLt | NumLte => {
+                let ordering = compare(arg(0)?, arg(1)?)?;
+                Ok(Value::Bool(match (op, ordering) {
+                    (_, None) => false,
+                    (NumGt, Some(ordering)) => ordering == Ordering::Greater,
+                    (NumGte, Some(ordering)) => ordering != Ordering::Less,
+                    (NumLt, Some(ordering)) => ordering == Ordering::Less,
+                    (_, Some(ordering)) => ordering != Ordering::Greater,
+                }))
+            }
+            NumCompare => {
+                // [EQ, GT, LT]
+                let tag_id = match compare(arg(0)?, arg(1)?)? {
+                    Some(Ordering::Equal) | None => 0,
+                    Some(Ordering::Greater) => 1,
+                    Some(Ordering::Less) => 2,
+                };
+                Ok(Value::Int(tag_id, self.int_width(layout)?))
+            }
+            NumDivFrac => match (arg(0)?, arg(1)?) {
+                (Value::Float(a, width), Value::Float(b, _)) => Ok(float(a / b, *width)),
+                (Value::Dec(a), Value::Dec(b)) => match dec_div(*a, *b) {
+                    Some(n) => Ok(Value::Dec(n)),
+                    None => Err(Error::roc_crash("decimal division overflowed")),
+                },
+                (a, _) => Err(Error::type_mismatch("a fraction", a)),
+            },
+            NumDivTruncUnchecked | NumDivCeilUnchecked | NumRemUnchecked | NumIsMultipleOf => {
+                let (a, width) = int_and_width(arg(0)?)?;
+                let b = int(arg(1)?)?;
+                int_division(op, a, b, width)
+            }
+            NumAbs | NumNeg => match arg(0)? {
+                Value::Int(n, width) if !width.is_signed() => {
+                    // Unsigned integers are their own absolute value, and negation wraps
+                    let result = if op == NumAbs { *n } else { n.wrapping_neg() };
+                    Ok(Value::int(result, *width))
+                }
+                Value::Int(n, width) if *n == int_min(*width) => {
+                    let message = if op == NumAbs {
+                        "integer absolute overflowed because its argument is the minimum value"
+                    } else {
+                        "integer negation overflowed because its argument is the minimum value"
+                    };
+                    Err(Error::roc_crash(message))
+                }
+                Value::Int(n, width) => {
+                    Ok(Value::Int(if op == NumAbs { n.abs() } else { -n }, *width))
+                }
+                Value::Float(n, width) => {
+                    Ok(float(if op == NumAbs { n.abs() } else { -n }, *width))
+                }
+                Value::Dec(n) => Ok(Value::Dec(if op == NumAbs { n.abs() } else { -n })),
+                other => Err(Error::type_mismatch("a number", other)),
+            },
+            NumSin | NumCos | NumSqrtUnchecked | NumLogUnchecked | NumAtan | NumAcos | NumAsin => {
+                let (n, width) = float_and_width(arg(0)?)?;
+                let result = match op {
+                    NumSin => n.sin(),
+                    NumCos => n.cos(),
+                    NumSqrtUnchecked => n.sqrt(),
+                    NumLogUnchecked => n.ln(),
+                    NumAtan => n.atan(),
+                    NumAcos => n.acos(),
+                    _ => n.asin(),
+                };
+                Ok(float(result, width))
+            }
+            NumPow => {
+                let (a, width) = float_and_width(arg(0)?)?;
+                let (b, _) = float_and_width(arg(1)?)?;
+                Ok(float(a.powf(b), width))
+            }
+            NumPowInt => {
+                let (a, width) = int_and_width(arg(0)?)?;
+                let exponent = u32::try_from(int(arg(1)?)?).unwrap_or(u32::MAX);
+                let result = if width == IntWidth::U128 {
+                    (a as u128).wrapping_pow(exponent) as i128
+                } else {
+                    a.wrapping_pow(exponent)
+                };
+                Ok(Value::int(result, width))
+            }
+            NumRound | NumFloor | NumCeiling => {
+                let (n, _) = float_and_width(arg(0)?)?;
+                let result = match op {
+                    NumRound => n.round(),
+                    NumFloor => n.floor(),
+                    _ => n.ceil(),
+                };
+                Ok(Value::int(result as i128, self.int_width(layout)?))
+            }
+            NumIsNan | NumIsInfinite | NumIsFinite => {
+                let (n, _) = float_and_width(arg(0)?)?;
+                Ok(Value::Bool(match op {
+                    NumIsNan => n.is_nan(),
+                    NumIsInfinite => n.is_infinite(),
+                    _ => n.is_finite(),
+                }))
+            }
+            NumToFrac | NumToFloatCast => {
+                let n = match arg(0)? {
+                    Value::Int(n, IntWidth::U128) => *n as u128 as f64,
+                    Value::Int(n, _) => *n as f64,
+                    Value::Float(n, _) => *n,
+                    Value::Dec(n) => *n as f64 / DEC_ONE as f64,
+                    other => return Err(Error::type_mismatch("a number", other)),
+                };
+                match self.interner.get(layout) {
+                    Layout::Builtin(Builtin::Float(width)) => Ok(float(n, width)),
+                    Layout::Builtin(Builtin::Decimal) => match arg(0)? {
+                        Value::Dec(n) => Ok(Value::Dec(*n)),
+                        Value::Int(n, _) => match n.checked_mul(DEC_ONE) {
+                            Some(n) => Ok(Value::Dec(n)),
+                            None => Err(Error::roc_crash("decimal multiplication overflowed")),
+                        },
+                        _ => Ok(Value::Dec((n * DEC_ONE as f64) as i128)),
+                    },
+                    other => Err(Error::type_mismatch("a fraction layout", &other)),
+                }
+            }
+            NumIntCast => {
+                let n = int(arg(0)?)?;
+                Ok(Value::int(n, self.int_width(layout)?))
+            }
+            NumBytesToU16 | NumBytesToU32 | NumBytesToU64 | NumBytesToU128 => {
+                let bytes = self.heap.list(arg(0)?)?;
+                let index = usize_arg(arg(1)?)?;
+                let width = self.int_width(layout)?;
+                let size = width.stack_size() as usize;
+
+                let mut result = 0u128;
+                for i in (0..size).rev() {
+                    match bytes.get(index + i) {
+                        Some(byte) => result = (result << 8) | int(byte)? as u8 as u128,
+                        None => return Err(Error::roc_crash("list index out of bounds")),
+                    }
+                }
+                Ok(Value::int(result as i128, width))
+            }
+            NumBitwiseAnd | NumBitwiseXor | NumBitwiseOr => {
+                let (a, width) = int_and_width(arg(0)?)?;
+                let b = int(arg(1)?)?;
+                let result = match op {
+                    NumBitwiseAnd => a & b,
+                    NumBitwiseXor => a ^ b,
+                    _ => a | b,
+                };
+                Ok(Value::int(result, width))
+            }
+            NumShiftLeftBy | NumShiftRightBy | NumShiftRightZfBy => {
+                let (a, width) = int_and_width(arg(0)?)?;
+                let by = int(arg(1)?)? as u32;
+                Ok(Value::int(shift(op, a, by, width), width))
+            }
+            NumCountLeadingZeroBits | NumCountTrailingZeroBits | NumCountOneBits => {
+                let (a, width) = int_and_width(arg(0)?)?;
+                let bits = width.stack_size() * 8;
+                let unsigned = (a as u128) & mask(bits);
+                let count = match op {
+                    NumCountLeadingZeroBits => unsigned.leading_zeros() - (128 - bits),
+                    NumCountTrailingZeroBits => unsigned.trailing_zeros().min(bits),
+                    _ => unsigned.count_ones(),
+                };
+                Ok(Value::Int(count as i128, self.int_width(layout)?))
+            }
+
+            // Bool
+            Eq | NotEq => {
+                let equal = self.heap.equal(arg(0)?, arg(1)?)?;
+                Ok(Value::Bool(equal == (op == Eq)))
+            }
+            And | Or => {
+                let (a, b) = (bool_arg(arg(0)?)?, bool_arg(arg(1)?)?);
+                Ok(Value::Bool(if op == And { a && b } else { a || b }))
+            }
+            Not => Ok(Value::Bool(!bool_arg(arg(0)?)?)),
+
+            Unreachable => Err(Error::roc_crash("reached unreachable code")),
+
+            StrCountGraphemes | StrGraphemes | StrToNum | NumToIntChecked | NumToFloatChecked
+            | Hash | PtrWrite | RefCountIncRcPtr | RefCountDecRcPtr | RefCountIncDataPtr
+            | RefCountDecDataPtr | BoxExpr | UnboxExpr | ListMap | ListMap2 | ListMap3
+            | ListMap4 | ListSortWith => Err(Error::UnsupportedLowLevel(op)),
+        }
+    }
+
+    pub(crate) fn eval_higher_order(
+        &mut self,
+        higher_order: &HigherOrderLowLevel<'a>,
+        lists: Vec<Value>,
+        closure_data: Value,
+    ) -> Result<Value, Error> {
+        let passed_function = &higher_order.passed_function;
+
+        match higher_order.op {
+            HigherOrder::ListMap { .. }
+            | HigherOrder::ListMap2 { .. }
+            | HigherOrder::ListMap3 { .. }
+            | HigherOrder::ListMap4 { .. } => {
+                let mut lists_elements = Vec::with_capacity(lists.len());
+                for list in lists.iter() {
+                    lists_elements.push(self.heap.list(list)?.to_vec());
+                }
+                let len = lists_elements.iter().map(Vec::len).min().unwrap_or(0);
+
+                // The elements are moved into the function; the lists are `decref`d afterwards.
+                // If the lists have different lengths, the remaining elements are dropped.
+                for elements in lists_elements.iter() {
+                    for element in &elements[len..] {
+                        self.heap.dec(element)?;
+                    }
+                }
+
+                let mut results = Vec::with_capacity(len);
+                for i in 0..len {
+                    let arguments = lists_elements.iter().map(|xs| xs[i].clone()).collect();
+                    results.push(self.call_passed_function(
+                        passed_function,
+                        arguments,
+                        &closure_data,
+                    )?);
+                }
+
+                Ok(self.heap.alloc_list(results))
+            }
+            HigherOrder::ListSortWith { .. } => {
+                let elements = self.heap.take_list(&lists[0])?;
+                let sorted = self.merge_sort(higher_order, elements, &closure_data)?;
+                Ok(self.heap.alloc_list(sorted))
+            }
+        }
+    }
+
+    /// A stable sort that calls the comparison function passed to `List.sortWith`
+    fn merge_sort(
+        &mut self,
+        higher_order: &HigherOrderLowLevel<'a>,
+        mut elements: Vec<Value>,
+        closure_data: &Value,
+    ) -> Result<Vec<Value>, Error> {
+        if elements.len() <= 1 {
+            return Ok(elements);
+        }
+
+        let right = elements.split_off(elements.len() / 2);
+        let left = self.merge_sort(higher_order, elements, closure_data)?;
+        let right = self.merge_sort(higher_order, right, closure_data)?;
+
+        let mut merged = Vec::with_capacity(left.len() + right.len());
+        let mut left = left.into_iter().peekable();
+        let mut right = right.into_iter().peekable();
+
+        while let (Some(a), Some(b)) = (left.peek(), right.peek()) {
+            // The function owns its arguments, and the list keeps the elements
+            self.heap.inc(a, 1)?;
+            self.heap.inc(b, 1)?;
+
+            let arguments = vec![a.clone(), b.clone()];
+            let ordering =
+                self.call_passed_function(&higher_order.passed_function, arguments, closure_data)?;
+
+            // [EQ, GT, LT]
+            if int(&ordering)? == 1 {
+                merged.extend(right.next());
+            } else {
+                merged.extend(left.next());
+            }
+        }
+
+        merged.extend(left);
+        merged.extend(right);
+
+        Ok(merged)
+    }
+}
+
+fn struct_field<'a>(
+    interp: &Interpreter<'a, '_>,
+    layout: InLayout<'a>,
+    index: usize,
+) -> Result<InLayout<'a>, Error> {
+    match interp.interner.get(layout) {
+        Layout::Struct { field_layouts, .. } if index < field_layouts.len() => {
+            Ok(field_layouts[index])
+        }
+        other => Err(Error::type_mismatch("a struct layout", &other)),
+    }
+}
+
+fn int(value: &Value) -> Result<i128, Error> {
+    int_and_width(value).map(|(n, _)| n)
+}
+
+fn int_and_width(value: &Value) -> Result<(i128, IntWidth), Error> {
+    match value {
+        Value::Int(n, width) => Ok((*n, *width)),
+        other => Err(Error::type_mismatch("an integer", other)),
+    }
+}
+
+fn usize_arg(value: &Value) -> Result<usize, Error> {
+    match usize::try_from(int(value)?) {
+        Ok(n) => Ok(n),
+        Err(_) => Err(Error::type_mismatch("a Nat", value)),
+    }
+}
+
+fn float_and_width(value: &Value) -> Result<(f64, FloatWidth), Error> {
+    match value {
+        Value::Float(n, width) => Ok((*n, *width)),
+        other => Err(Error::type_mismatch("a float", other)),
+    }
+}
+
+fn bool_arg(value: &Value) -> Result<bool, Error> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        other => Err(Error::type_mismatch("a Bool", other)),
+    }
+}
+
+/// A float of the given width, rounded to it
+fn float(n: f64, width: FloatWidth) -> Value {
+    match width {
+        FloatWidth::F32 => Value::Float(n as f32 as f64, width),
+        FloatWidth::F64 => Value::Float(n, width),
+    }
+}
+
+fn format_float(value: &Value) -> Result<String, Error> {
+    match value {
+        Value::Float(n, FloatWidth::F32) => Ok((*n as f32).to_string()),
+        Value::Float(n, FloatWidth::F64) => Ok(n.to_string()),
+        other => Err(Error::type_mismatch("a float", other)),
+    }
+}
+
+fn mask(bits: u32) -> u128 {
+    if bits >= 128 {
+        u128::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+fn int_min(width: IntWidth) -> i128 {
+    match width {
+        IntWidth::I128 => i128::MIN,
+        _ if width.is_signed() => -(1 << (width.stack_size() * 8 - 1)),
+        _ => 0,
+    }
+}
+
+/// The maximum value of every width except `U128`, which does not fit an `i128`
+fn int_max(width: IntWidth) -> i128 {
+    match width {
+        IntWidth::I128 | IntWidth::U128 => i128::MAX,
+        _ if width.is_signed() => (1 << (width.stack_size() * 8 - 1)) - 1,
+        _ => (1 << (width.stack_size() * 8)) - 1,
+    }
+}
+
+fn int_checked(arith: Arith, a: i128, b: i128, width: IntWidth) -> Option<i128> {
+    if width == IntWidth::U128 {
+        let (a, b) = (a as u128, b as u128);
+        let result = match arith {
+            Arith::Add => a.checked_add(b),
+            Arith::Sub => a.checked_sub(b),
+            Arith::Mul => a.checked_mul(b),
+        };
+        result.map(|n| n as i128)
+    } else {
+        let result = match arith {
+            Arith::Add => a.checked_add(b),
+            Arith::Sub => a.checked_sub(b),
+            Arith::Mul => a.checked_mul(b),
+        }?;
+        (wrap_int(result, width) == result).then_some(result)
+    }
+}
+
+fn int_wrapping(arith: Arith, a: i128, b: i128, width: IntWidth) -> i128 {
+    let result = match arith {
+        Arith::Add => a.wrapping_add(b),
+        Arith::Sub => a.wrapping_sub(b),
+        Arith::Mul => a.wrapping_mul(b),
+    };
+    wrap_int(result, width)
+}
+
+fn int_saturating(arith: Arith, a: i128, b: i128, width: IntWidth) -> i128 {
+    if let Some(result) = int_checked(arith, a, b, width) {
+        return result;
+    }
+
+    let positive_overflow = match arith {
+        Arith::Add => b > 0 || width == IntWidth::U128,
+        Arith::Sub => b < 0 && width.is_signed(),
+        Arith::Mul => (a < 0) == (b < 0) || width == IntWidth::U128,
+    };
+
+    match (positive_overflow, width) {
+        (true, IntWidth::U128) => u128::MAX as i128,
+        (true, _) => int_max(width),
+        (false, _) => int_min(width),
+    }
+}
+
+fn arithmetic(op: LowLevel, arith: Arith, a: &Value, b: &Value) -> Result<Value, Error> {
+    use LowLevel::*;
+
+    match (a, b) {
+        (Value::Int(a, width), Value::Int(b, _)) => {
+            let (a, b, width) = (*a, *b, *width);
+            match op {
+                NumAdd | NumSub | NumMul => match int_checked(arith, a, b, width) {
+                    Some(n) => Ok(Value::Int(n, width)),
+                    None => Err(Error::roc_crash(arith.overflow_message())),
+                },
+                NumAddWrap | NumSubWrap | NumMulWrap => {
+                    Ok(Value::Int(int_wrapping(arith, a, b, width), width))
+                }
+                NumAddSaturated | NumSubSaturated | NumMulSaturated => {
+                    Ok(Value::Int(int_saturating(arith, a, b, width), width))
+                }
+                _ => {
+                    // { value, has_overflowed }
+                    let result = int_checked(arith, a, b, width);
+                    Ok(Value::Struct(vec![
+                        Value::Int(int_wrapping(arith, a, b, width), width),
+                        Value::Bool(result.is_none()),
+                    ]))
+                }
+            }
+        }
+        (Value::Float(a, width), Value::Float(b, _)) => {
+            let result = match arith {
+                Arith::Add => a + b,
+                Arith::Sub => a - b,
+                Arith::Mul => a * b,
+            };
+            let result = float(result, *width);
+            match op {
+                NumAddChecked | NumSubChecked | NumMulChecked => {
+                    let has_overflowed = matches!(result, Value::Float(n, _) if !n.is_finite());
+                    Ok(Value::Struct(vec![result, Value::Bool(has_overflowed)]))
+                }
+                _ => Ok(result),
+            }
+        }
+        (Value::Dec(a), Value::Dec(b)) => {
+            let result = match arith {
+                Arith::Add => a.checked_add(*b),
+                Arith::Sub => a.checked_sub(*b),
+                Arith::Mul => dec_mul(*a, *b),
+            };
+            match op {
+                NumAddChecked | NumSubChecked | NumMulChecked => Ok(Value::Struct(vec![
+                    Value::Dec(result.unwrap_or_default()),
+                    Value::Bool(result.is_none()),
+                ])),
+                NumAddSaturated | NumSubSaturated | NumMulSaturated => {
+                    let positive = match arith {
+                        Arith::Add => *b > 0,
+                        Arith::Sub => *b < 0,
+                        Arith::Mul => (*a < 0) == (*b < 0),
+                    };
+                    let saturated = if positive { i128::MAX } else { i128::MIN };
+                    Ok(Value::Dec(result.unwrap_or(saturated)))
+                }
+                _ => match result {
+                    Some(n) => Ok(Value::Dec(n)),
+                    None => Err(Error::roc_crash(arith.dec_overflow_message())),
+                },
+            }
+        }
+        (a, _) => Err(Error::type_mismatch("two numbers of the same type", a)),
+    }
+}
+
+fn int_division(op: LowLevel, a: i128, b: i128, width: IntWidth) -> Result<Value, Error> {
+    use LowLevel::*;
+
+    if op == NumIsMultipleOf {
+        let is_multiple = match b {
+            0 => a == 0,
+            -1 => true,
+            _ if width == IntWidth::U128 => (a as u128) % (b as u128) == 0,
+            _ => a % b == 0,
+        };
+        return Ok(Value::Bool(is_multiple));
+    }
+
+    if b == 0 {
+        return Err(Error::roc_crash("integer division by zero"));
+    }
+
+    let result = if width == IntWidth::U128 {
+        let (a, b) = (a as u128, b as u128);
+        let result = match op {
+            NumDivTruncUnchecked => a / b,
+            NumDivCeilUnchecked => a / b + (a % b != 0) as u128,
+            _ => a % b,
+        };
+        result as i128
+    } else {
+        match op {
+            NumDivTruncUnchecked => a.wrapping_div(b),
+            NumDivCeilUnchecked => {
+                let quotient = a.wrapping_div(b);
+                let rounds_up = a.wrapping_rem(b) != 0 && (a < 0) == (b < 0);
+                quotient + rounds_up as i128
+            }
+            _ => a.wrapping_rem(b),
+        }
+    };
+
+    Ok(Value::int(result, width))
+}
+
+fn shift(op: LowLevel, a: i128, by: u32, width: IntWidth) -> i128 {
+    let bits = width.stack_size() * 8;
+    let unsigned = (a as u128) & mask(bits);
+
+    match op {
+        LowLevel::NumShiftLeftBy if by >= bits => 0,
+        LowLevel::NumShiftLeftBy => (unsigned << by) as i128,
+        LowLevel::NumShiftRightZfBy if by >= bits => 0,
+        LowLevel::NumShiftRightZfBy => (unsigned >> by) as i128,
+        _ if !width.is_signed() => {
+            if by >= bits {
+                0
+            } else {
+                (unsigned >> by) as i128
+            }
+        }
+        _ => a >> by.min(127),
+    }
+}
+
+/// Compare two numbers; `None` if either is NaN
+fn compare(a: &Value, b: &Value) -> Result<Option<Ordering>, Error> {
+    match (a, b) {
+        (Value::Int(a, IntWidth::U128), Value::Int(b, _)) => {
+            Ok(Some((*a as u128).cmp(&(*b as u128))))
+        }
+        (Value::Int(a, _), Value::Int(b, _)) => Ok(Some(a.cmp(b))),
+        (Value::Float(a, _), Value::Float(b, _)) => Ok(a.partial_cmp(b)),
+        (Value::Dec(a), Value::Dec(b)) => Ok(Some(a.cmp(b))),
+        (a, _) => Err(Error::type_mismatch("two numbers of the same type", a)),
+    }
+}
+
+/// Multiply two `Dec`s without overflowing the intermediate product, unless the result overflows
+fn dec_mul(a: i128, b: i128) -> Option<i128> {
+    // a * b / ONE = a_whole * b + a_frac * b_whole + a_frac * b_frac / ONE
+    let (a_whole, a_frac) = (a / DEC_ONE, a % DEC_ONE);
+    let (b_whole, b_frac) = (b / DEC_ONE, b % DEC_ONE);
+
+    a_whole
+        .checked_mul(b)?
+        .checked_add(a_frac.checked_mul(b_whole)?)?
+        .checked_add(a_frac * b_frac / DEC_ONE)
+}
+
+/// Divide two `Dec`s by long division, one decimal digit at a time
+fn dec_div(a: i128, b: i128) -> Option<i128> {
+    if b == 0 {
+        return None;
+    }
+
+    let negative = (a < 0) != (b < 0);
+    let (a, b) = (a.unsigned_abs(), b.unsigned_abs());
+
+    let mut quotient = a / b;
+    let mut remainder = a % b;
+    for _ in 0..18 {
+        remainder = remainder.checked_mul(10)?;
+        quotient = quotient.checked_mul(10)?.checked_add(remainder / b)?;
+        remainder %= b;
+    }
+
+    let quotient = i128::try_from(quotient).ok()?;
+    Some(if negative { -quotient } else { quotient })
+}
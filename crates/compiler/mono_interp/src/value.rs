@@ -0,0 +1,502 @@
+use std::fmt::Write;
+
+use roc_builtins::bitcode::{FloatWidth, IntWidth};
+use roc_mono::layout::TagIdIntType;
+use roc_std::RocDec;
+
+use crate::Error;
+
+/// Index of an allocation in the [Heap]
+pub type CellId = usize;
+
+/// A runtime value.
+///
+/// Values that live on the stack (numbers, structs, non-recursive tag unions) are stored inline.
+/// Everything that is refcounted at runtime (lists, strings, boxes, recursive tag unions) lives in
+/// a [Heap] cell, and the value only refers to it. Copying a value therefore does not change
+/// any refcounts; like in the generated code, that is up to the `inc` and `dec` statements.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Integers of all widths. `U128` values are stored as their bit pattern.
+    Int(i128, IntWidth),
+    Float(f64, FloatWidth),
+    /// A `Dec`, as a fixed-point number with 18 decimal places
+    Dec(i128),
+    Bool(bool),
+    /// Records, tuples and closure data
+    Struct(Vec<Value>),
+    /// A value of a non-recursive tag union
+    Tag {
+        tag_id: TagIdIntType,
+        fields: Vec<Value>,
+    },
+    /// A box, a value of a recursive tag union, or a reuse token
+    Ptr(CellId),
+    /// The null tag of a nullable tag union, or a reuse token that cannot be reused
+    Null,
+    /// The address of a field of a heap-allocated tag, from `UnionFieldPtrAtIndex`
+    FieldPtr(CellId, usize),
+    /// A list; empty lists are not allocated
+    List(Option<CellId>),
+    /// A string; empty strings are not allocated
+    Str(Option<CellId>),
+}
+
+impl Value {
+    pub fn int(value: i128, width: IntWidth) -> Self {
+        Value::Int(wrap_int(value, width), width)
+    }
+
+    pub fn unit() -> Self {
+        Value::Struct(Vec::new())
+    }
+}
+
+/// Truncate an integer to the given width, and sign- or zero-extend it back.
+pub fn wrap_int(value: i128, width: IntWidth) -> i128 {
+    use IntWidth::*;
+
+    match width {
+        U8 => value as u8 as i128,
+        U16 => value as u16 as i128,
+        U32 => value as u32 as i128,
+        U64 => value as u64 as i128,
+        I8 => value as i8 as i128,
+        I16 => value as i16 as i128,
+        I32 => value as i32 as i128,
+        I64 => value as i64 as i128,
+        U128 | I128 => value,
+    }
+}
+
+/// The data of a heap allocation
+#[derive(Clone, Debug, PartialEq)]
+pub enum HeapData {
+    List(Vec<Value>),
+    Str(String),
+    Tag {
+        tag_id: TagIdIntType,
+        fields: Vec<Value>,
+    },
+    Box(Value),
+    /// The cell was reset to be reused; its fields have been released already
+    Reset,
+}
+
+#[derive(Debug)]
+struct Cell {
+    refcount: usize,
+    data: HeapData,
+}
+
+/// All allocations made by the program, with their reference counts.
+///
+/// Cells are never reused, so that a use after free is always detected.
+#[derive(Debug, Default)]
+pub struct Heap {
+    cells: Vec<Option<Cell>>,
+    live: usize,
+}
+
+impl Heap {
+    pub fn alloc(&mut self, data: HeapData) -> CellId {
+        self.cells.push(Some(Cell { refcount: 1, data }));
+        self.live += 1;
+        self.cells.len() - 1
+    }
+
+    pub fn alloc_list(&mut self, elements: Vec<Value>) -> Value {
+        if elements.is_empty() {
+            Value::List(None)
+        } else {
+            Value::List(Some(self.alloc(HeapData::List(elements))))
+        }
+    }
+
+    pub fn alloc_str(&mut self, string: String) -> Value {
+        if string.is_empty() {
+            Value::Str(None)
+        } else {
+            Value::Str(Some(self.alloc(HeapData::Str(string))))
+        }
+    }
+
+    fn cell(&self, id: CellId) -> Result<&Cell, Error> {
+        match self.cells.get(id) {
+            Some(Some(cell)) => Ok(cell),
+            _ => Err(Error::UseAfterFree(id)),
+        }
+    }
+
+    fn cell_mut(&mut self, id: CellId) -> Result<&mut Cell, Error> {
+        match self.cells.get_mut(id) {
+            Some(Some(cell)) => Ok(cell),
+            _ => Err(Error::UseAfterFree(id)),
+        }
+    }
+
+    pub fn get(&self, id: CellId) -> Result<&HeapData, Error> {
+        self.cell(id).map(|cell| &cell.data)
+    }
+
+    pub fn set(&mut self, id: CellId, data: HeapData) -> Result<(), Error> {
+        self.cell_mut(id)?.data = data;
+        Ok(())
+    }
+
+    pub fn refcount(&self, id: CellId) -> Result<usize, Error> {
+        self.cell(id).map(|cell| cell.refcount)
+    }
+
+    pub fn is_unique(&self, id: CellId) -> Result<bool, Error> {
+        Ok(self.refcount(id)? == 1)
+    }
+
+    /// The number of allocations that have not been freed
+    pub fn live_cells(&self) -> usize {
+        self.live
+    }
+
+    /// The ids of allocations that have not been freed
+    pub fn live_cell_ids(&self) -> impl Iterator<Item = CellId> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(id, cell)| cell.as_ref().map(|_| id))
+    }
+
+    fn free(&mut self, id: CellId) -> Result<HeapData, Error> {
+        match self.cells.get_mut(id).and_then(Option::take) {
+            Some(cell) => {
+                self.live -= 1;
+                Ok(cell.data)
+            }
+            None => Err(Error::UseAfterFree(id)),
+        }
+    }
+
+    /// Increment the refcounts of everything the value owns, like `inc` does
+    pub fn inc(&mut self, value: &Value, amount: usize) -> Result<(), Error> {
+        match value {
+            Value::Ptr(id) | Value::List(Some(id)) | Value::Str(Some(id)) => {
+                self.cell_mut(*id)?.refcount += amount;
+            }
+            Value::Struct(fields) | Value::Tag { fields, .. } => {
+                for field in fields {
+                    self.inc(field, amount)?;
+                }
+            }
+            Value::Int(..)
+            | Value::Float(..)
+            | Value::Dec(_)
+            | Value::Bool(_)
+            | Value::Null
+            | Value::FieldPtr(..)
+            | Value::List(None)
+            | Value::Str(None) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Decrement the refcounts of everything the value owns, like `dec` does. Allocations
+    /// whose refcount drops to zero are freed, and their contents decremented in turn.
+    pub fn dec(&mut self, value: &Value) -> Result<(), Error> {
+        // Long linked lists would overflow the stack if this recursed
+        let mut stack = vec![value.clone()];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::Ptr(id) | Value::List(Some(id)) | Value::Str(Some(id)) => {
+                    if let Some(data) = self.release(id)? {
+                        push_children(&mut stack, data);
+                    }
+                }
+                Value::Struct(fields) | Value::Tag { fields, .. } => stack.extend(fields),
+                Value::Int(..)
+                | Value::Float(..)
+                | Value::Dec(_)
+                | Value::Bool(_)
+                | Value::Null
+                | Value::FieldPtr(..)
+                | Value::List(None)
+                | Value::Str(None) => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Decrement the refcount of an allocation without touching its contents, like `decref` does
+    pub fn decref(&mut self, value: &Value) -> Result<(), Error> {
+        match value {
+            Value::Ptr(id) | Value::List(Some(id)) | Value::Str(Some(id)) => {
+                self.release(*id)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Decrement a refcount, returning the data if the allocation was freed
+    fn release(&mut self, id: CellId) -> Result<Option<HeapData>, Error> {
+        let cell = self.cell_mut(id)?;
+        cell.refcount -= 1;
+
+        if cell.refcount == 0 {
+            self.free(id).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Release the fields of a unique allocation so it can be reused, like `reset` does
+    pub fn reset(&mut self, id: CellId) -> Result<(), Error> {
+        let data = std::mem::replace(&mut self.cell_mut(id)?.data, HeapData::Reset);
+        let mut children = Vec::new();
+        push_children(&mut children, data);
+
+        for child in children {
+            self.dec(&child)?;
+        }
+
+        Ok(())
+    }
+
+    /// Take the elements of an owned list. If the list is unique they are moved out of it and it
+    /// is freed; otherwise they are copied, so their refcounts are incremented.
+    pub fn take_list(&mut self, value: &Value) -> Result<Vec<Value>, Error> {
+        match value {
+            Value::List(None) => Ok(Vec::new()),
+            &Value::List(Some(id)) => {
+                if self.is_unique(id)? {
+                    match self.free(id)? {
+                        HeapData::List(elements) => Ok(elements),
+                        other => Err(Error::type_mismatch("a list", &other)),
+                    }
+                } else {
+                    let elements = self.list(value)?.to_vec();
+                    for element in elements.iter() {
+                        self.inc(element, 1)?;
+                    }
+                    self.release(id)?;
+                    Ok(elements)
+                }
+            }
+            other => Err(Error::type_mismatch("a list", other)),
+        }
+    }
+
+    /// Take the contents of an owned string, freeing it if it was unique
+    pub fn take_str(&mut self, value: &Value) -> Result<String, Error> {
+        let string = self.str(value)?.to_string();
+        self.decref(value)?;
+        Ok(string)
+    }
+
+    pub fn list(&self, value: &Value) -> Result<&[Value], Error> {
+        match value {
+            Value::List(None) => Ok(&[]),
+            Value::List(Some(id)) => match self.get(*id)? {
+                HeapData::List(elements) => Ok(elements),
+                other => Err(Error::type_mismatch("a list", other)),
+            },
+            other => Err(Error::type_mismatch("a list", other)),
+        }
+    }
+
+    pub fn str(&self, value: &Value) -> Result<&str, Error> {
+        match value {
+            Value::Str(None) => Ok(""),
+            Value::Str(Some(id)) => match self.get(*id)? {
+                HeapData::Str(string) => Ok(string),
+                other => Err(Error::type_mismatch("a string", other)),
+            },
+            other => Err(Error::type_mismatch("a string", other)),
+        }
+    }
+
+    /// Write `value` into the field that `ptr` points to, like `PtrStore` does
+    pub fn store(&mut self, ptr: &Value, value: Value) -> Result<(), Error> {
+        match ptr {
+            Value::FieldPtr(id, index) => match &mut self.cell_mut(*id)?.data {
+                HeapData::Tag { fields, .. } if *index < fields.len() => {
+                    fields[*index] = value;
+                    Ok(())
+                }
+                HeapData::Box(inner) if *index == 0 => {
+                    *inner = value;
+                    Ok(())
+                }
+                other => Err(Error::type_mismatch("a tag with fields or a box", other)),
+            },
+            other => Err(Error::type_mismatch("a field pointer", other)),
+        }
+    }
+
+    /// The tag id and fields of a tag union value, whether it lives on the stack or the heap.
+    /// The null tag has no fields, and no tag id: that is only known from the layout.
+    pub fn tag<'v>(
+        &'v self,
+        value: &'v Value,
+    ) -> Result<Option<(TagIdIntType, &'v [Value])>, Error> {
+        match value {
+            Value::Tag { tag_id, fields } => Ok(Some((*tag_id, fields))),
+            Value::Ptr(id) => match self.get(*id)? {
+                HeapData::Tag { tag_id, fields } => Ok(Some((*tag_id, fields))),
+                other => Err(Error::type_mismatch("a tag", other)),
+            },
+            Value::Null => Ok(None),
+            other => Err(Error::type_mismatch("a tag", other)),
+        }
+    }
+
+    /// Structural equality, like `Eq` on the values
+    pub fn equal(&self, a: &Value, b: &Value) -> Result<bool, Error> {
+        use Value::*;
+
+        Ok(match (a, b) {
+            (Ptr(a), Ptr(b)) if a == b => true,
+            (Ptr(_) | Null, Ptr(_) | Null) => match (self.get_ptr(a)?, self.get_ptr(b)?) {
+                (None, None) => true,
+                (Some(a), Some(b)) => self.data_equal(a, b)?,
+                _ => false,
+            },
+            (List(_), List(_)) => {
+                let (a, b) = (self.list(a)?, self.list(b)?);
+                a.len() == b.len() && self.all_equal(a, b)?
+            }
+            (Str(_), Str(_)) => self.str(a)? == self.str(b)?,
+            (Struct(a), Struct(b)) => self.all_equal(a, b)?,
+            (
+                Tag {
+                    tag_id: id_a,
+                    fields: a,
+                },
+                Tag {
+                    tag_id: id_b,
+                    fields: b,
+                },
+            ) => id_a == id_b && self.all_equal(a, b)?,
+            (Int(a, _), Int(b, _)) => a == b,
+            (Float(a, _), Float(b, _)) => a == b,
+            (Dec(a), Dec(b)) => a == b,
+            (Bool(a), Bool(b)) => a == b,
+            _ => return Err(Error::type_mismatch("values of the same type", b)),
+        })
+    }
+
+    fn get_ptr(&self, value: &Value) -> Result<Option<&HeapData>, Error> {
+        match value {
+            Value::Ptr(id) => self.get(*id).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    fn data_equal(&self, a: &HeapData, b: &HeapData) -> Result<bool, Error> {
+        Ok(match (a, b) {
+            (
+                HeapData::Tag {
+                    tag_id: id_a,
+                    fields: a,
+                },
+                HeapData::Tag {
+                    tag_id: id_b,
+                    fields: b,
+                },
+            ) => id_a == id_b && self.all_equal(a, b)?,
+            (HeapData::Box(a), HeapData::Box(b)) => self.equal(a, b)?,
+            _ => return Err(Error::type_mismatch("values of the same type", b)),
+        })
+    }
+
+    fn all_equal(&self, a: &[Value], b: &[Value]) -> Result<bool, Error> {
+        for (a, b) in a.iter().zip(b) {
+            if !self.equal(a, b)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Render a value for `dbg` and error messages
+    pub fn display(&self, value: &Value) -> String {
+        let mut buf = String::new();
+        self.display_help(&mut buf, value);
+        buf
+    }
+
+    fn display_help(&self, buf: &mut String, value: &Value) {
+        let _ = match value {
+            Value::Int(n, width) if *width == IntWidth::U128 => write!(buf, "{}", *n as u128),
+            Value::Int(n, _) => write!(buf, "{}", n),
+            Value::Float(n, _) => write!(buf, "{}", n),
+            Value::Dec(n) => write!(buf, "{}", RocDec::new(*n)),
+            Value::Bool(true) => write!(buf, "Bool.true"),
+            Value::Bool(false) => write!(buf, "Bool.false"),
+            Value::Struct(fields) => {
+                self.display_fields(buf, "{ ", fields, " }");
+                Ok(())
+            }
+            Value::Tag { tag_id, fields } => {
+                self.display_tag(buf, *tag_id, fields);
+                Ok(())
+            }
+            Value::Null => write!(buf, "<null>"),
+            Value::FieldPtr(id, index) => write!(buf, "<field {} of #{}>", index, id),
+            Value::Ptr(id) => match self.get(*id) {
+                Ok(HeapData::Tag { tag_id, fields }) => {
+                    self.display_tag(buf, *tag_id, fields);
+                    Ok(())
+                }
+                Ok(HeapData::Box(inner)) => {
+                    buf.push_str("Box(");
+                    self.display_help(buf, inner);
+                    buf.push(')');
+                    Ok(())
+                }
+                Ok(data) => write!(buf, "<{:?}>", data),
+                Err(_) => write!(buf, "<freed #{}>", id),
+            },
+            Value::List(_) => match self.list(value) {
+                Ok(elements) => {
+                    self.display_fields(buf, "[", elements, "]");
+                    Ok(())
+                }
+                Err(_) => write!(buf, "<freed list>"),
+            },
+            Value::Str(_) => match self.str(value) {
+                Ok(string) => write!(buf, "{:?}", string),
+                Err(_) => write!(buf, "<freed string>"),
+            },
+        };
+    }
+
+    fn display_tag(&self, buf: &mut String, tag_id: TagIdIntType, fields: &[Value]) {
+        let _ = write!(buf, "#{}", tag_id);
+        if !fields.is_empty() {
+            self.display_fields(buf, " (", fields, ")");
+        }
+    }
+
+    fn display_fields(&self, buf: &mut String, open: &str, fields: &[Value], close: &str) {
+        buf.push_str(open);
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                buf.push_str(", ");
+            }
+            self.display_help(buf, field);
+        }
+        buf.push_str(close);
+    }
+}
+
+fn push_children(stack: &mut Vec<Value>, data: HeapData) {
+    match data {
+        HeapData::List(elements) => stack.extend(elements),
+        HeapData::Tag { fields, .. } => stack.extend(fields),
+        HeapData::Box(inner) => stack.push(inner),
+        HeapData::Str(_) | HeapData::Reset => {}
+    }
+}
@@ -0,0 +1,40 @@
+use roc_module::symbol::Symbol;
+use roc_region::all::Region;
+
+use crate::Severity;
+
+/// Problems found in the monomorphized program. These are not mistakes, but places where the
+/// program is slower than it looks, so they are only reported on request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Problem {
+    /// A builtin that updates its argument in place when it is unique is given a value that is
+    /// always shared at that point, because it is used again afterwards. The builtin has to
+    /// copy the value instead.
+    SharedUpdate {
+        region: Region,
+        /// The builtin, unless it was inlined
+        function: Option<Symbol>,
+        value: Symbol,
+        /// Where `value` is used after the update, if that is known
+        later_use: Option<Region>,
+    },
+    /// The memory of `freed` is released, and a value of the same shape is then allocated fresh
+    /// instead of reusing it.
+    MissedReuse {
+        region: Region,
+        freed: Symbol,
+        freed_region: Option<Region>,
+    },
+}
+
+impl Problem {
+    pub fn severity(&self) -> Severity {
+        Severity::Warning
+    }
+
+    pub fn region(&self) -> Region {
+        match self {
+            Problem::SharedUpdate { region, .. } | Problem::MissedReuse { region, .. } => *region,
+        }
+    }
+}
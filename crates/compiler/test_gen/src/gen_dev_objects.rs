@@ -0,0 +1,203 @@
+//! Checks the object files that the dev backend produces, including for targets we can not run
+//! tests on.
+
+use crate::helpers::dev::helper_object;
+use object::{Object, ObjectSection, ObjectSymbol, RelocationKind, RelocationTarget, SectionKind};
+use std::str::FromStr;
+use target_lexicon::Triple;
+
+fn object_for_target(target: &str, src: &str) -> (String, Vec<u8>) {
+    let arena = bumpalo::Bump::new();
+    let target = Triple::from_str(target).unwrap();
+    let (main_fn_name, _errors, bytes) = helper_object(&arena, src, false, &target);
+
+    (main_fn_name, bytes)
+}
+
+fn windows_x86_64_object(src: &str) -> (String, Vec<u8>) {
+    object_for_target("x86_64-pc-windows-msvc", src)
+}
+
+const SRC: &str = indoc::indoc!(
+    r#"
+    app "test" provides [main] to "./platform"
+
+    double = \x -> 2 * x
+
+    main =
+        List.map [1, 2, 3] double
+        |> List.sum
+    "#
+);
+
+#[test]
+fn windows_x86_64_coff() {
+    let (main_fn_name, bytes) = windows_x86_64_object(SRC);
+    let file = object::File::parse(bytes.as_slice()).unwrap();
+
+    assert_eq!(file.format(), object::BinaryFormat::Coff);
+    assert_eq!(file.architecture(), object::Architecture::X86_64);
+
+    // The PE surgical linker only picks up code from `.text` and data from `.rdata`.
+    for section in file.sections() {
+        match section.kind() {
+            SectionKind::Text => assert_eq!(section.name(), Ok(".text")),
+            SectionKind::Data => assert_eq!(section.size(), 0),
+            _ => {}
+        }
+    }
+
+    assert!(file
+        .symbols()
+        .any(|symbol| symbol.name() == Ok(main_fn_name.as_str()) && symbol.is_definition()));
+}
+
+#[test]
+fn windows_x86_64_unwind_info() {
+    let (_, bytes) = windows_x86_64_object(SRC);
+    let file = object::File::parse(bytes.as_slice()).unwrap();
+
+    let pdata = file.section_by_name(".pdata").expect("no .pdata section");
+    let pdata_bytes = pdata.data().unwrap();
+    let xdata = file.section_by_name(".xdata").expect("no .xdata section");
+    let xdata_bytes = xdata.data().unwrap();
+
+    // Every RUNTIME_FUNCTION entry has 3 fields that are relative to the image base.
+    let entries = pdata.size() / 12;
+    assert!(entries > 1);
+    let relocations: Vec<_> = pdata.relocations().collect();
+    assert_eq!(relocations.len() as u64, 3 * entries);
+
+    for (offset, relocation) in relocations {
+        assert_eq!(relocation.kind(), RelocationKind::ImageOffset);
+
+        let symbol = match relocation.target() {
+            RelocationTarget::Symbol(index) => file.symbol_by_index(index).unwrap(),
+            target => panic!("unexpected relocation target {:?}", target),
+        };
+        let field = offset as usize;
+        let addend = u32::from_le_bytes(pdata_bytes[field..field + 4].try_into().unwrap());
+
+        if offset % 12 == 8 {
+            assert_eq!(symbol.section_index(), Some(xdata.index()));
+
+            // Version 1 without flags, and no frame register.
+            let unwind_info = &xdata_bytes[addend as usize..];
+            assert_eq!(unwind_info[0], 1);
+            assert_eq!(unwind_info[3], 0);
+
+            // The last unwind code is the `push rbp` at the start of the prologue.
+            let slot_count = unwind_info[2] as usize;
+            assert!(slot_count > 0);
+            let last_slot = 4 + 2 * (slot_count - 1);
+            assert_eq!(unwind_info[last_slot..last_slot + 2], [1, 0x50]);
+        } else {
+            let section = file
+                .section_by_index(symbol.section_index().unwrap())
+                .unwrap();
+            assert_eq!(section.name(), Ok(".text"));
+        }
+    }
+}
+
+fn check_eh_frame(target: &str, return_address_register: u8) {
+    let (_, bytes) = object_for_target(target, SRC);
+    let file = object::File::parse(bytes.as_slice()).unwrap();
+
+    let is_macho = file.format() == object::BinaryFormat::MachO;
+    let section_name = if is_macho { "__eh_frame" } else { ".eh_frame" };
+    let eh_frame = file
+        .section_by_name(section_name)
+        .expect("no eh_frame section");
+    let eh_frame_bytes = eh_frame.data().unwrap();
+    let relocations: Vec<_> = eh_frame.relocations().collect();
+
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes(eh_frame_bytes[offset..offset + 4].try_into().unwrap()) as usize
+    };
+
+    // A single CIE: version 1, "zR" augmentation, code alignment 1, data alignment -8.
+    let cie_length = read_u32(0);
+    assert_eq!(read_u32(4), 0);
+    assert_eq!(
+        eh_frame_bytes[8..15],
+        [1, b'z', b'R', 0, 1, 0x78, return_address_register]
+    );
+
+    // Followed by an FDE per proc, each pointing back at the CIE and pc-relative to its proc.
+    let mut offset = 4 + cie_length;
+    let mut fdes = 0;
+    loop {
+        let length = read_u32(offset);
+        if length == 0 {
+            break;
+        }
+        assert_eq!(read_u32(offset + 4), offset + 4);
+
+        let field = offset as u64 + 8;
+        let field_relocations: Vec<_> = relocations
+            .iter()
+            .filter(|(reloc_offset, _)| *reloc_offset == field)
+            .map(|(_, relocation)| relocation)
+            .collect();
+
+        if is_macho {
+            // Mach-O subtracts a symbol at the start of the section, and the field holds
+            // the rest of the difference.
+            let subtractor = match file.architecture() {
+                object::Architecture::X86_64 => object::macho::X86_64_RELOC_SUBTRACTOR,
+                _ => object::macho::ARM64_RELOC_SUBTRACTOR,
+            };
+            let kinds: Vec<_> = field_relocations.iter().map(|r| r.kind()).collect();
+            assert_eq!(
+                kinds,
+                [
+                    RelocationKind::MachO {
+                        value: subtractor,
+                        relative: false
+                    },
+                    RelocationKind::Absolute
+                ]
+            );
+
+            let start_symbol = match field_relocations[0].target() {
+                RelocationTarget::Symbol(index) => file.symbol_by_index(index).unwrap(),
+                target => panic!("unexpected relocation target {:?}", target),
+            };
+            assert_eq!(start_symbol.section_index(), Some(eh_frame.index()));
+            assert_eq!(start_symbol.address(), eh_frame.address());
+            assert_eq!(read_u32(offset + 8) as u32, (field as u32).wrapping_neg());
+        } else {
+            let kinds: Vec<_> = field_relocations.iter().map(|r| r.kind()).collect();
+            assert_eq!(kinds, [RelocationKind::Relative]);
+        }
+        assert!(read_u32(offset + 12) > 0);
+
+        fdes += 1;
+        offset += 4 + length;
+    }
+
+    assert!(fdes > 1);
+    assert_eq!(fdes * if is_macho { 2 } else { 1 }, relocations.len());
+    assert_eq!(offset + 4, eh_frame_bytes.len());
+}
+
+#[test]
+fn linux_x86_64_eh_frame() {
+    check_eh_frame("x86_64-unknown-linux-gnu", 16);
+}
+
+#[test]
+fn linux_aarch64_eh_frame() {
+    check_eh_frame("aarch64-unknown-linux-gnu", 30);
+}
+
+#[test]
+fn macos_x86_64_eh_frame() {
+    check_eh_frame("x86_64-apple-darwin", 16);
+}
+
+#[test]
+fn macos_aarch64_eh_frame() {
+    check_eh_frame("aarch64-apple-darwin", 30);
+}
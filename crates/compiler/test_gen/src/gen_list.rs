@@ -7,6 +7,9 @@ use crate::helpers::dev::assert_evals_to;
 #[cfg(feature = "gen-wasm")]
 use crate::helpers::wasm::assert_evals_to;
 
+#[cfg(feature = "gen-interp")]
+use crate::helpers::interp::assert_evals_to;
+
 #[allow(unused_imports)]
 use crate::helpers::with_larger_debug_stack;
 //use crate::assert_wasm_evals_to as assert_evals_to;
@@ -23,25 +26,45 @@ fn roc_list_construction() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn empty_list_literal() {
     assert_evals_to!("[]", RocList::<i64>::from_slice(&[]), RocList<i64>);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_literal_empty_record() {
     assert_evals_to!("[{}]", RocList::from_slice(&[()]), RocList<()>);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_singleton_list_literal() {
     assert_evals_to!("[1, 2]", RocList::from_slice(&[1, 2]), RocList<i64>);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_list_literal() {
     assert_evals_to!("[12, 9]", RocList::from_slice(&[12, 9]), RocList<i64>);
     assert_evals_to!(
@@ -52,7 +75,12 @@ fn int_list_literal() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn bool_list_literal() {
     assert_evals_to!(
         indoc!(
@@ -87,7 +115,12 @@ fn bool_list_literal() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn bool_list_concat() {
     assert_evals_to!(
         indoc!(
@@ -164,7 +197,12 @@ fn bool_list_literal_repeat() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn variously_sized_list_literals() {
     assert_evals_to!("[]", RocList::<i64>::from_slice(&[]), RocList<i64>);
     assert_evals_to!("[1]", RocList::from_slice(&[1]), RocList<i64>);
@@ -183,7 +221,12 @@ fn variously_sized_list_literals() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_append_basic() {
     assert_evals_to!(
         "List.append [1] 2",
@@ -198,7 +241,7 @@ fn list_append_basic() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_take_first() {
     assert_evals_to!(
         "List.takeFirst [1, 2, 3] 2",
@@ -223,7 +266,7 @@ fn list_take_first() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_take_last() {
     assert_evals_to!(
         "List.takeLast [1, 2, 3] 2",
@@ -248,7 +291,7 @@ fn list_take_last() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_sublist() {
     assert_evals_to!(
         "List.sublist [1, 2, 3] { start: 0 , len: 2 } ",
@@ -288,7 +331,7 @@ fn list_sublist() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_map_try_ok() {
     assert_evals_to!(
         // No transformation
@@ -345,7 +388,7 @@ fn list_map_try_err() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_split() {
     assert_evals_to!(
         r#"
@@ -462,7 +505,7 @@ fn list_split_last() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop() {
     assert_evals_to!(
         "List.drop [1,2,3] 2",
@@ -482,7 +525,7 @@ fn list_drop() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_at() {
     assert_evals_to!(
         "List.dropAt [1, 2, 3] 0",
@@ -507,7 +550,7 @@ fn list_drop_at() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_intersperse() {
     assert_evals_to!(
         indoc!(
@@ -530,7 +573,7 @@ fn list_intersperse() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_at_shared() {
     assert_evals_to!(
         indoc!(
@@ -552,7 +595,7 @@ fn list_drop_at_shared() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_if_empty_list_of_int() {
     assert_evals_to!(
         indoc!(
@@ -569,7 +612,7 @@ fn list_drop_if_empty_list_of_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_if_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -586,7 +629,7 @@ fn list_drop_if_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_if_always_false_for_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -600,7 +643,7 @@ fn list_drop_if_always_false_for_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_if_always_true_for_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -614,7 +657,7 @@ fn list_drop_if_always_true_for_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_if_geq3() {
     assert_evals_to!(
         indoc!(
@@ -628,7 +671,7 @@ fn list_drop_if_geq3() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_if_string_eq() {
     assert_evals_to!(
         indoc!(
@@ -642,7 +685,7 @@ fn list_drop_if_string_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_last() {
     assert_evals_to!(
         "List.dropLast [1, 2, 3]",
@@ -662,7 +705,7 @@ fn list_drop_last() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_last_mutable() {
     assert_evals_to!(
         indoc!(
@@ -684,7 +727,7 @@ fn list_drop_last_mutable() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_drop_first() {
     assert_evals_to!(
         "List.dropFirst [1, 2, 3]",
@@ -704,7 +747,7 @@ fn list_drop_first() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_swap() {
     assert_evals_to!(
         "List.swap [] 0 1",
@@ -740,13 +783,23 @@ fn list_swap() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_append_to_empty_list() {
     assert_evals_to!("List.append [] 3", RocList::from_slice(&[3]), RocList<i64>);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_append_to_empty_list_of_int() {
     assert_evals_to!(
         indoc!(
@@ -764,7 +817,12 @@ fn list_append_to_empty_list_of_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_append_bools() {
     assert_evals_to!(
         "List.append [Bool.true, Bool.false] Bool.true",
@@ -774,7 +832,12 @@ fn list_append_bools() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_append_longer_list() {
     assert_evals_to!(
         "List.append [11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22] 23",
@@ -784,7 +847,12 @@ fn list_append_longer_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_prepend() {
     assert_evals_to!("List.prepend [] 1", RocList::from_slice(&[1]), RocList<i64>);
     assert_evals_to!(
@@ -809,7 +877,7 @@ fn list_prepend() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_prepend_str() {
     assert_evals_to!(
         indoc!(
@@ -827,7 +895,12 @@ fn list_prepend_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_prepend_bools() {
     assert_evals_to!(
         "List.prepend [Bool.true, Bool.false] Bool.true",
@@ -837,7 +910,12 @@ fn list_prepend_bools() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_prepend_big_list() {
     assert_evals_to!(
         "List.prepend [10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 100, 100, 100, 100] 9",
@@ -849,7 +927,7 @@ fn list_prepend_big_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_backwards_empty_all_inline() {
     assert_evals_to!(
         indoc!(
@@ -877,7 +955,7 @@ fn list_walk_backwards_empty_all_inline() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_backwards_with_str() {
     assert_evals_to!(
         r#"List.walkBackwards ["x", "y", "z"] "<" Str.concat"#,
@@ -893,7 +971,7 @@ fn list_walk_backwards_with_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_backwards_with_record() {
     assert_evals_to!(
         indoc!(
@@ -921,7 +999,7 @@ fn list_walk_backwards_with_record() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_with_str() {
     assert_evals_to!(
         r#"List.walk ["x", "y", "z"] "<" Str.concat"#,
@@ -937,13 +1015,13 @@ fn list_walk_with_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_subtraction() {
     assert_evals_to!(r#"List.walk [1, 2] 1 Num.sub"#, (1 - 1) - 2, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_until_sum() {
     assert_evals_to!(
         r#"List.walkUntil [1, 2] 0 \a,b -> Continue (a + b)"#,
@@ -953,7 +1031,7 @@ fn list_walk_until_sum() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_implements_position() {
     assert_evals_to!(
         r#"
@@ -981,7 +1059,7 @@ fn list_walk_implements_position() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_until_even_prefix_sum() {
     assert_evals_to!(
         r#"
@@ -999,13 +1077,13 @@ fn list_walk_until_even_prefix_sum() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_from_sum() {
     assert_evals_to!(r#"List.walkFrom [1, 2, 3] 1 0 Num.add"#, 5, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_if_empty_list_of_int() {
     assert_evals_to!(
         indoc!(
@@ -1023,7 +1101,7 @@ fn list_keep_if_empty_list_of_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_if_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1042,7 +1120,7 @@ fn list_keep_if_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_if_always_true_for_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1064,7 +1142,7 @@ fn list_keep_if_always_true_for_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_if_always_false_for_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1082,7 +1160,7 @@ fn list_keep_if_always_false_for_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_if_one() {
     assert_evals_to!(
         indoc!(
@@ -1100,7 +1178,7 @@ fn list_keep_if_one() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_if_str_is_hello() {
     assert_evals_to!(
         indoc!(
@@ -1114,7 +1192,7 @@ fn list_keep_if_str_is_hello() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_count_if_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1128,7 +1206,7 @@ fn list_count_if_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_count_if_always_true_for_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1150,7 +1228,7 @@ fn list_count_if_always_true_for_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_count_if_always_false_for_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1168,7 +1246,7 @@ fn list_count_if_always_false_for_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_count_if_condition() {
     assert_evals_to!(
         indoc!(
@@ -1186,7 +1264,7 @@ fn list_count_if_condition() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_count_if_str() {
     assert_evals_to!(
         indoc!(
@@ -1200,7 +1278,12 @@ fn list_count_if_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_on_empty_list_with_int_layout() {
     assert_evals_to!(
         indoc!(
@@ -1218,7 +1301,12 @@ fn list_map_on_empty_list_with_int_layout() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_on_non_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1236,7 +1324,12 @@ fn list_map_on_non_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_changes_input() {
     assert_evals_to!(
         indoc!(
@@ -1254,7 +1347,12 @@ fn list_map_changes_input() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_on_big_list() {
     assert_evals_to!(
         indoc!(
@@ -1274,7 +1372,12 @@ fn list_map_on_big_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_with_type_change() {
     assert_evals_to!(
         indoc!(
@@ -1293,7 +1396,12 @@ fn list_map_with_type_change() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_using_defined_function() {
     assert_evals_to!(
         indoc!(
@@ -1315,7 +1423,12 @@ fn list_map_using_defined_function() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_all_inline() {
     assert_evals_to!(
         indoc!(
@@ -1329,7 +1442,12 @@ fn list_map_all_inline() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map_closure_int() {
     assert_evals_to!(
         indoc!(
@@ -1350,7 +1468,7 @@ fn list_map_closure_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_map_closure_float() {
     assert_evals_to!(
         indoc!(
@@ -1371,7 +1489,7 @@ fn list_map_closure_float() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn list_map4_group() {
     assert_evals_to!(
         indoc!(
@@ -1385,7 +1503,12 @@ fn list_map4_group() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map4_different_length() {
     assert_evals_to!(
         indoc!(
@@ -1404,7 +1527,12 @@ fn list_map4_different_length() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map3_group() {
     assert_evals_to!(
         indoc!(
@@ -1418,7 +1546,12 @@ fn list_map3_group() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map3_different_length() {
     assert_evals_to!(
         indoc!(
@@ -1436,7 +1569,12 @@ fn list_map3_different_length() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map2_pair() {
     assert_evals_to!(
         indoc!(
@@ -1451,7 +1589,12 @@ fn list_map2_pair() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_map2_different_lengths() {
     assert_evals_to!(
         indoc!(
@@ -1468,7 +1611,7 @@ fn list_map2_different_lengths() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_empty_list() {
     assert_evals_to!(
         "List.join []",
@@ -1478,7 +1621,7 @@ fn list_join_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_one_list() {
     assert_evals_to!(
         "List.join [[1, 2, 3]]",
@@ -1488,7 +1631,7 @@ fn list_join_one_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_two_non_empty_lists() {
     assert_evals_to!(
         "List.join [[1, 2, 3] , [4 ,5, 6]]",
@@ -1498,7 +1641,7 @@ fn list_join_two_non_empty_lists() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_two_non_empty_lists_of_float() {
     assert_evals_to!(
         "List.join [[1.2, 1.1], [2.1, 2.2]]",
@@ -1508,7 +1651,7 @@ fn list_join_two_non_empty_lists_of_float() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_to_big_list() {
     assert_evals_to!(
         indoc!(
@@ -1534,7 +1677,7 @@ fn list_join_to_big_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_defined_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -1552,7 +1695,7 @@ fn list_join_defined_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_all_empty_lists() {
     assert_evals_to!(
         "List.join [[], [], []]",
@@ -1562,7 +1705,7 @@ fn list_join_all_empty_lists() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_one_empty_list() {
     assert_evals_to!(
         "List.join [[1.2, 1.1], []]",
@@ -1572,14 +1715,14 @@ fn list_join_one_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_single() {
     assert_evals_to!("List.single 1", RocList::from_slice(&[1]), RocList<i64>);
     assert_evals_to!("List.single 5.6", RocList::from_slice(&[5.6]), RocList<f64>);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_repeat() {
     assert_evals_to!(
         "List.repeat 1 5",
@@ -1620,7 +1763,62 @@ fn list_repeat() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(feature = "gen-wasm")]
+fn list_repeat_bytes_memory_fill() {
+    use crate::helpers::wasm::{main_fn_instructions, Instruction::Misc};
+    use roc_wasm_module::opcodes::MiscOp::MEMORYFILL;
+
+    let src = "List.repeat 7u8 21";
+    let instructions = main_fn_instructions(src, std::marker::PhantomData::<RocList<u8>>);
+    assert!(instructions.contains(&Misc(MEMORYFILL)));
+    assert_evals_to!(src, RocList::from_slice(&[7; 21]), RocList<u8>);
+
+    assert_evals_to!(
+        "List.repeat Bool.true 3",
+        RocList::from_slice(&[true; 3]),
+        RocList<bool>
+    );
+    assert_evals_to!("List.repeat 7u8 0", RocList::<u8>::default(), RocList<u8>);
+
+    let src = "List.len ([] : List U8)";
+    let instructions = main_fn_instructions(src, std::marker::PhantomData::<usize>);
+    assert!(instructions.contains(&Misc(MEMORYFILL)));
+    assert_evals_to!(src, 0, usize);
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn list_eq_bytes_simd() {
+    use crate::helpers::wasm::{main_fn_instructions, Instruction::Simd};
+    use roc_wasm_module::opcodes::SimdOp::{I8X16ALLTRUE, I8X16EQ};
+
+    let src = "List.repeat 7u8 21 == List.concat (List.repeat 7u8 20) [7]";
+    let instructions = main_fn_instructions(src, std::marker::PhantomData::<bool>);
+    assert!(instructions
+        .windows(2)
+        .any(|w| w == [Simd(I8X16EQ), Simd(I8X16ALLTRUE)]));
+    assert_evals_to!(src, true, bool);
+
+    assert_evals_to!(
+        "List.repeat 7u8 21 == List.concat (List.repeat 7u8 20) [8]",
+        false,
+        bool
+    );
+    assert_evals_to!(
+        "List.repeat 7u8 21 == List.concat [8] (List.repeat 7u8 20)",
+        false,
+        bool
+    );
+    assert_evals_to!("List.repeat 7u8 21 != List.repeat 7u8 20", true, bool);
+    assert_evals_to!(
+        "[Bool.true, Bool.false] == [Bool.true, Bool.false]",
+        true,
+        bool
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_reverse() {
     assert_evals_to!(
         "List.reverse [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]",
@@ -1636,7 +1834,7 @@ fn list_reverse() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_reverse_empty_list_of_int() {
     assert_evals_to!(
         indoc!(
@@ -1654,7 +1852,7 @@ fn list_reverse_empty_list_of_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_reverse_empty_list() {
     assert_evals_to!(
         "List.reverse []",
@@ -1664,7 +1862,12 @@ fn list_reverse_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_concat_two_empty_lists() {
     assert_evals_to!(
         "List.concat [] []",
@@ -1674,7 +1877,12 @@ fn list_concat_two_empty_lists() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_concat_two_empty_lists_of_int() {
     assert_evals_to!(
         indoc!(
@@ -1696,7 +1904,12 @@ fn list_concat_two_empty_lists_of_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_concat_second_list_is_empty() {
     assert_evals_to!(
         "List.concat [12, 13] []",
@@ -1706,7 +1919,12 @@ fn list_concat_second_list_is_empty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_concat_first_list_is_empty() {
     assert_evals_to!(
         "List.concat [] [23, 24]",
@@ -1716,7 +1934,12 @@ fn list_concat_first_list_is_empty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_concat_two_non_empty_lists() {
     assert_evals_to!(
         "List.concat [1, 2] [3, 4]",
@@ -1732,7 +1955,7 @@ fn list_concat_two_non_empty_lists() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_concat_two_bigger_non_empty_lists() {
     assert_evals_to!(
         "List.concat [1.1, 2.2] [3.3, 4.4, 5.5]",
@@ -1817,19 +2040,34 @@ fn list_concat_large() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn empty_list_len() {
     assert_evals_to!("List.len []", 0, usize);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn basic_int_list_len() {
     assert_evals_to!("List.len [12, 9, 6, 3]", 4, usize);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn loaded_int_list_len() {
     assert_evals_to!(
         indoc!(
@@ -1845,7 +2083,12 @@ fn loaded_int_list_len() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn fn_int_list_len() {
     assert_evals_to!(
         indoc!(
@@ -1863,13 +2106,13 @@ fn fn_int_list_len() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn int_list_is_empty() {
     assert_evals_to!("List.isEmpty [12, 9, 6, 3]", false, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn empty_list_is_empty() {
     assert_evals_to!("List.isEmpty []", true, bool);
 }
@@ -2056,7 +2299,12 @@ fn get_int_list_oob() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn replace_unique_int_list() {
     assert_evals_to!(
         indoc!(
@@ -2071,7 +2319,12 @@ fn replace_unique_int_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn replace_unique_int_list_out_of_bounds() {
     assert_evals_to!(
         indoc!(
@@ -2086,7 +2339,12 @@ fn replace_unique_int_list_out_of_bounds() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn replace_unique_int_list_get_old_value() {
     assert_evals_to!(
         indoc!(
@@ -2118,7 +2376,7 @@ fn replace_unique_get_large_value() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn replace_shared_int_list() {
     assert_evals_to!(
         indoc!(
@@ -2175,7 +2433,7 @@ fn get_set_unique_int_list_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn set_unique_int_list() {
     assert_evals_to!(
         "List.set [12, 9, 7, 1, 5] 2 33",
@@ -2185,7 +2443,12 @@ fn set_unique_int_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn set_unique_list_oob() {
     assert_evals_to!(
         "List.set [3, 17, 4.1] 1337 9.25",
@@ -2195,7 +2458,7 @@ fn set_unique_list_oob() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn set_shared_int_list() {
     assert_evals_to!(
         indoc!(
@@ -2223,7 +2486,7 @@ fn set_shared_int_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn set_shared_list_oob() {
     assert_evals_to!(
         indoc!(
@@ -2266,7 +2529,12 @@ fn get_unique_int_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_wrap_len() {
     assert_evals_to!(
         indoc!(
@@ -2283,7 +2551,12 @@ fn gen_wrap_len() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_wrap_first() {
     assert_evals_to!(
         indoc!(
@@ -2300,7 +2573,12 @@ fn gen_wrap_first() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_duplicate() {
     assert_evals_to!(
         indoc!(
@@ -2323,7 +2601,7 @@ fn gen_duplicate() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_swap() {
     assert_evals_to!(
         indoc!(
@@ -2579,7 +2857,12 @@ fn quicksort_singleton() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn empty_list_increment_decrement() {
     assert_evals_to!(
         indoc!(
@@ -2596,7 +2879,12 @@ fn empty_list_increment_decrement() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_literal_increment_decrement() {
     assert_evals_to!(
         indoc!(
@@ -2613,7 +2901,12 @@ fn list_literal_increment_decrement() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_pass_to_function() {
     assert_evals_to!(
         indoc!(
@@ -2633,7 +2926,12 @@ fn list_pass_to_function() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_pass_to_set() {
     assert_evals_to!(
         indoc!(
@@ -2653,7 +2951,7 @@ fn list_pass_to_set() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_wrap_in_tag() {
     assert_evals_to!(
         indoc!(
@@ -2671,7 +2969,7 @@ fn list_wrap_in_tag() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_contains_int() {
     assert_evals_to!(indoc!("List.contains [1,2,3] 1"), true, bool);
 
@@ -2681,7 +2979,7 @@ fn list_contains_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_contains_str() {
     assert_evals_to!(indoc!(r#"List.contains ["foo", "bar"] "bar""#), true, bool);
 
@@ -2695,7 +2993,12 @@ fn list_contains_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_manual_range() {
     assert_evals_to!(
         indoc!(
@@ -2766,7 +3069,7 @@ fn list_max() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_sum() {
     assert_evals_to!("List.sum []", 0, i64);
     assert_evals_to!("List.sum [1, 2, 3]", 6, i64);
@@ -2774,7 +3077,7 @@ fn list_sum() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_product() {
     assert_evals_to!("List.product []", 1, i64);
     assert_evals_to!("List.product [1, 2, 3]", 6, i64);
@@ -2782,7 +3085,7 @@ fn list_product() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_void() {
     assert_evals_to!(
         "List.keepOks [] (\\x -> x)",
@@ -2798,7 +3101,7 @@ fn list_keep_void() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_oks() {
     assert_evals_to!(
         "List.keepOks [Ok {}, Ok {}] (\\x -> x)",
@@ -2823,7 +3126,7 @@ fn list_keep_oks() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_keep_errs() {
     assert_evals_to!(
         "List.keepErrs [Err {}, Err {}] (\\x -> x)",
@@ -2853,7 +3156,7 @@ fn list_keep_errs() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_map_with_index() {
     assert_evals_to!(
         "List.mapWithIndex [0,0,0] (\\x, index -> Num.intCast index + x)",
@@ -2883,7 +3186,12 @@ fn cleanup_because_exception() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_sort_with() {
     assert_evals_to!(
         "List.sortWith [] Num.compare",
@@ -2903,7 +3211,12 @@ fn list_sort_with() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_sort_asc() {
     assert_evals_to!(
         "List.sortAsc []",
@@ -2918,7 +3231,12 @@ fn list_sort_asc() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_sort_desc() {
     assert_evals_to!(
         "List.sortDesc []",
@@ -2933,7 +3251,7 @@ fn list_sort_desc() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_any() {
     assert_evals_to!("List.any [] (\\e -> e > 3)", false, bool);
     assert_evals_to!("List.any [1, 2, 3] (\\e -> e > 3)", false, bool);
@@ -2947,7 +3265,12 @@ fn list_any_empty_with_unknown_element_type() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn list_all() {
     assert_evals_to!("List.all [] (\\e -> e > 3)", true, bool);
     assert_evals_to!("List.all [1, 2, 3] (\\e -> e > 3)", false, bool);
@@ -2988,7 +3311,7 @@ fn lists_with_incompatible_type_param_in_if() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn map_with_index_multi_record() {
     // see https://github.com/roc-lang/roc/issues/1700
     assert_evals_to!(
@@ -3003,7 +3326,7 @@ fn map_with_index_multi_record() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn empty_list_of_function_type() {
     // see https://github.com/roc-lang/roc/issues/1732
     assert_evals_to!(
@@ -3032,7 +3355,7 @@ fn empty_list_of_function_type() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_map() {
     assert_evals_to!(
         indoc!(
@@ -3052,7 +3375,7 @@ fn list_join_map() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_join_map_empty() {
     assert_evals_to!(
         indoc!(
@@ -3262,7 +3585,7 @@ fn list_find_index_empty_typed_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_ends_with_empty() {
     assert_evals_to!(
         indoc!(
@@ -3296,7 +3619,7 @@ fn list_ends_with_empty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_ends_with_nonempty() {
     assert_evals_to!(
         indoc!(
@@ -3340,7 +3663,7 @@ fn list_ends_with_nonempty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_starts_with_empty() {
     assert_evals_to!(
         indoc!(
@@ -3374,7 +3697,7 @@ fn list_starts_with_empty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_starts_with_nonempty() {
     assert_evals_to!(
         indoc!(
@@ -3418,7 +3741,7 @@ fn list_starts_with_nonempty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn monomorphized_lists() {
     assert_evals_to!(
         indoc!(
@@ -3437,7 +3760,12 @@ fn monomorphized_lists() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn with_capacity() {
     assert_evals_to!(
         indoc!(
@@ -3456,7 +3784,12 @@ fn with_capacity() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn with_capacity_append() {
     // see https://github.com/roc-lang/roc/issues/1732
     assert_evals_to!(
@@ -3476,7 +3809,12 @@ fn with_capacity_append() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn reserve() {
     assert_evals_to!(
         indoc!(
@@ -3491,7 +3829,12 @@ fn reserve() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn reserve_unchanged() {
     assert_evals_to!(
         indoc!(
@@ -3515,7 +3858,7 @@ fn reserve_unchanged() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn release_excess_capacity() {
     assert_evals_to!(
         indoc!(
@@ -3531,7 +3874,7 @@ fn release_excess_capacity() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn release_excess_capacity_with_len() {
     assert_evals_to!(
         indoc!(
@@ -3547,7 +3890,7 @@ fn release_excess_capacity_with_len() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn release_excess_capacity_empty() {
     assert_evals_to!(
         indoc!(
@@ -3562,7 +3905,7 @@ fn release_excess_capacity_empty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn call_function_in_empty_list() {
     assert_evals_to!(
         indoc!(
@@ -3578,7 +3921,7 @@ fn call_function_in_empty_list() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn call_function_in_empty_list_unbound() {
     assert_evals_to!(
         indoc!(
@@ -3593,7 +3936,7 @@ fn call_function_in_empty_list_unbound() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn issue_3571_lowlevel_call_function_with_bool_lambda_set() {
     assert_evals_to!(
         indoc!(
@@ -3625,7 +3968,7 @@ fn issue_3571_lowlevel_call_function_with_bool_lambda_set() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn issue_3530_uninitialized_capacity_in_list_literal() {
     assert_evals_to!(
         indoc!(
@@ -3640,7 +3983,7 @@ fn issue_3530_uninitialized_capacity_in_list_literal() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_infer_usage() {
     assert_evals_to!(
         indoc!(
@@ -3660,7 +4003,7 @@ fn list_infer_usage() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_backwards_implements_position() {
     assert_evals_to!(
         r#"
@@ -3688,7 +4031,7 @@ fn list_walk_backwards_implements_position() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_backwards_until_sum() {
     assert_evals_to!(
         r#"List.walkBackwardsUntil [1, 2] 0 \a,b -> Continue (a + b)"#,
@@ -3698,7 +4041,7 @@ fn list_walk_backwards_until_sum() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_backwards_until_even_prefix_sum() {
     assert_evals_to!(
         r#"
@@ -3716,7 +4059,7 @@ fn list_walk_backwards_until_even_prefix_sum() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_from_until_sum() {
     assert_evals_to!(
         r#"List.walkFromUntil [1, 2, 3, 4] 2 0 \a,b -> Continue (a + b)"#,
@@ -3726,7 +4069,7 @@ fn list_walk_from_until_sum() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn concat_unique_to_nonunique_overlapping_issue_4697() {
     assert_evals_to!(
         r#"
@@ -3747,7 +4090,7 @@ fn concat_unique_to_nonunique_overlapping_issue_4697() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn list_walk_from_even_prefix_sum() {
     assert_evals_to!(
         r#"
@@ -7,6 +7,9 @@ use crate::helpers::dev::assert_evals_to;
 #[cfg(feature = "gen-wasm")]
 use crate::helpers::wasm::assert_evals_to;
 
+#[cfg(feature = "gen-interp")]
+use crate::helpers::interp::assert_evals_to;
+
 // use crate::assert_wasm_evals_to as assert_evals_to;
 #[allow(unused_imports)]
 use indoc::indoc;
@@ -14,7 +17,12 @@ use indoc::indoc;
 use roc_std::{RocDec, RocOrder, RocResult};
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn nat_alias() {
     assert_evals_to!(
         indoc!(
@@ -31,7 +39,12 @@ fn nat_alias() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn i128_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -47,7 +60,12 @@ fn i128_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i64_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -66,7 +84,12 @@ fn i64_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i32_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -82,7 +105,12 @@ fn i32_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i16_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -98,7 +126,12 @@ fn i16_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i8_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -115,7 +148,12 @@ fn i8_signed_int_alias() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i128_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -131,7 +169,12 @@ fn i128_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i64_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -147,7 +190,12 @@ fn i64_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i32_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -163,7 +211,12 @@ fn i32_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i16_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -179,7 +232,12 @@ fn i16_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn i8_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -196,7 +254,12 @@ fn i8_hex_int_alias() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u128_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -212,7 +275,12 @@ fn u128_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u64_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -228,7 +296,12 @@ fn u64_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u32_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -244,7 +317,12 @@ fn u32_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u16_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -260,7 +338,12 @@ fn u16_signed_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u8_signed_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -277,7 +360,12 @@ fn u8_signed_int_alias() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u128_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -293,7 +381,12 @@ fn u128_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u64_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -309,7 +402,12 @@ fn u64_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u32_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -325,7 +423,12 @@ fn u32_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u16_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -341,7 +444,12 @@ fn u16_hex_int_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn u8_hex_int_alias() {
     assert_evals_to!(
         indoc!(
@@ -418,7 +526,12 @@ fn character_literal_new_line() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn dec_float_alias() {
     assert_evals_to!(
         indoc!(
@@ -435,7 +548,12 @@ fn dec_float_alias() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f64_float_alias() {
     assert_evals_to!(
         indoc!(
@@ -451,7 +569,12 @@ fn f64_float_alias() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f32_float_alias() {
     assert_evals_to!(
         indoc!(
@@ -468,13 +591,23 @@ fn f32_float_alias() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f64_sqrt_100() {
     assert_evals_to!("Num.sqrt 100", 10.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f64_sqrt_checked_0() {
     assert_evals_to!("Num.sqrt 0", 0.0, f64);
 }
@@ -492,7 +625,12 @@ fn f64_sqrt_checked_negative() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f64_log() {
     assert_evals_to!("Num.log 7.38905609893", 1.999999999999912, f64);
 }
@@ -510,13 +648,18 @@ fn f64_log_checked_zero() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn f64_log_negative() {
     assert_evals_to!("Num.log -1", true, f64, |f: f64| f.is_nan());
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f64_round() {
     assert_evals_to!("Num.round 3.6", 4, i64);
     assert_evals_to!("Num.round 3.4", 3, i64);
@@ -526,7 +669,12 @@ fn f64_round() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn f64_abs() {
     assert_evals_to!("Num.abs -4.7", 4.7, f64);
     assert_evals_to!("Num.abs 5.8", 5.8, f64);
@@ -539,7 +687,12 @@ fn f64_abs() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn i64_abs() {
     assert_evals_to!("Num.abs -6", 6, i64);
     assert_evals_to!("Num.abs 7", 7, i64);
@@ -554,22 +707,46 @@ fn i64_abs() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn various_sized_abs() {
     assert_evals_to!("Num.abs -6i8", 6, i8);
     assert_evals_to!("Num.abs -6i16", 6, i16);
     assert_evals_to!("Num.abs -6i32", 6, i32);
     assert_evals_to!("Num.abs -6i64", 6, i64);
-    if !cfg!(feature = "gen-wasm") {
-        assert_evals_to!("Num.abs -6i128", 6, i128);
-    }
+    assert_evals_to!("Num.abs -6i128", 6, i128);
     assert_evals_to!("Num.abs 6u8", 6, u8);
     assert_evals_to!("Num.abs 6u16", 6, u16);
     assert_evals_to!("Num.abs 6u32", 6, u32);
     assert_evals_to!("Num.abs 6u64", 6, u64);
-    if !cfg!(feature = "gen-wasm") {
-        assert_evals_to!("Num.abs 6u128", 6, u128);
-    }
+    assert_evals_to!("Num.abs 6u128", 6, u128);
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn frac_abs() {
+    assert_evals_to!("Num.abs -1.5f32", 1.5, f32);
+    assert_evals_to!("Num.abs -1.5f64", 1.5, f64);
+    assert_evals_to!("Num.abs 1.5f64", 1.5, f64);
+    assert_evals_to!(
+        "Num.abs -1.5dec",
+        RocDec::from_str_to_i128_unsafe("1.5"),
+        i128
+    );
+    assert_evals_to!(
+        "Num.abs -10_000_000_000_000_000_000i128",
+        10_000_000_000_000_000_000,
+        i128
+    );
 }
 
 #[test]
@@ -590,7 +767,12 @@ fn abs_min_int_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_if_fn() {
     assert_evals_to!(
         indoc!(
@@ -614,7 +796,12 @@ fn gen_if_fn() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_float_eq() {
     assert_evals_to!(
         indoc!(
@@ -628,7 +815,12 @@ fn gen_float_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_add_dec() {
     assert_evals_to!(
         indoc!(
@@ -650,7 +842,12 @@ fn gen_add_dec() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_f32() {
     assert_evals_to!(
         indoc!(
@@ -663,7 +860,12 @@ fn gen_add_f32() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_f64() {
     assert_evals_to!(
         indoc!(
@@ -677,7 +879,12 @@ fn gen_add_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_wrap_add_nums() {
     assert_evals_to!(
         indoc!(
@@ -693,19 +900,29 @@ fn gen_wrap_add_nums() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_div_f64() {
     assert_evals_to!("48 / 2", 24.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_div_f32() {
     assert_evals_to!("48f32 / 2", 24.0, f32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_div_checked_f64() {
     assert_evals_to!(
         indoc!(
@@ -721,7 +938,7 @@ fn gen_div_checked_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_div_checked_by_zero_f64() {
     assert_evals_to!(
         indoc!(
@@ -737,7 +954,12 @@ fn gen_div_checked_by_zero_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_div_dec() {
     assert_evals_to!(
         indoc!(
@@ -757,7 +979,7 @@ fn gen_div_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_div_checked_dec() {
     assert_evals_to!(
         indoc!(
@@ -778,7 +1000,7 @@ fn gen_div_checked_dec() {
     );
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_div_checked_by_zero_dec() {
     assert_evals_to!(
         indoc!(
@@ -800,7 +1022,12 @@ fn gen_div_checked_by_zero_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_int_eq() {
     assert_evals_to!(
         indoc!(
@@ -814,7 +1041,12 @@ fn gen_int_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_int_neq() {
     assert_evals_to!(
         indoc!(
@@ -828,25 +1060,45 @@ fn gen_int_neq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_less_than() {
     assert_evals_to!("4 < 5", true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn float_less_than() {
     assert_evals_to!("4.0 < 5.0", true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn float_greater_than() {
     assert_evals_to!("5.0 > 4.0", true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_dec_eq() {
     assert_evals_to!(
         indoc!(
@@ -866,7 +1118,12 @@ fn gen_dec_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_dec_neq() {
     assert_evals_to!(
         indoc!(
@@ -886,7 +1143,40 @@ fn gen_dec_neq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn gen_float_neq() {
+    assert_evals_to!("1.0 != 2.0", true, bool);
+    assert_evals_to!("1.0f32 != 1.0f32", false, bool);
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn gen_i128_eq() {
+    assert_evals_to!(
+        "10_000_000_000_000_000_000i128 == 10_000_000_000_000_000_000",
+        true,
+        bool
+    );
+    assert_evals_to!(
+        "10_000_000_000_000_000_000i128 != 10_000_000_000_000_000_001",
+        true,
+        bool
+    );
+    assert_evals_to!("1u128 != 1", false, bool);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_wrap_int_neq() {
     assert_evals_to!(
         indoc!(
@@ -904,7 +1194,12 @@ fn gen_wrap_int_neq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_i8() {
     assert_evals_to!(
         indoc!(
@@ -918,7 +1213,12 @@ fn gen_add_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_u8() {
     assert_evals_to!(
         indoc!(
@@ -932,7 +1232,12 @@ fn gen_add_u8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_i16() {
     assert_evals_to!(
         indoc!(
@@ -946,7 +1251,12 @@ fn gen_add_i16() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_u16() {
     assert_evals_to!(
         indoc!(
@@ -960,7 +1270,12 @@ fn gen_add_u16() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_i32() {
     assert_evals_to!(
         indoc!(
@@ -974,7 +1289,12 @@ fn gen_add_i32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_u32() {
     assert_evals_to!(
         indoc!(
@@ -988,7 +1308,12 @@ fn gen_add_u32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_add_i64() {
     assert_evals_to!(
         indoc!(
@@ -1002,7 +1327,12 @@ fn gen_add_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_sub_dec() {
     assert_evals_to!(
         indoc!(
@@ -1025,7 +1355,12 @@ fn gen_sub_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_mul_dec() {
     assert_evals_to!(
         indoc!(
@@ -1048,61 +1383,111 @@ fn gen_mul_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_sub_f64() {
     assert_evals_to!("1.5f64 - 2.4 - 3", -3.9, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_sub_f32() {
     assert_evals_to!("1.5f32 - 2.4 - 3", -3.9, f32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_i8() {
     assert_evals_to!("1i8 - 2i8 - 3i8", -4, i8);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_u8() {
     assert_evals_to!("8u8 - 2u8 - 3u8", 3, u8);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_i16() {
     assert_evals_to!("1i16 - 2i16 - 3i16", -4, i16);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_u16() {
     assert_evals_to!("8u16 - 2u16 - 3u16", 3, u16);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_i32() {
     assert_evals_to!("1i32 - 2i32 - 3i32", -4, i32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_u32() {
     assert_evals_to!("8u32 - 2u32 - 3u32", 3, u32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_sub_i64() {
     assert_evals_to!("1 - 2 - 3", -4, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_signed_mul_quadword_and_lower() {
     assert_evals_to!("2i64 * 4 * 6", 48, i64);
     assert_evals_to!("2i32 * 4 * 6", 48, i32);
@@ -1111,7 +1496,12 @@ fn gen_signed_mul_quadword_and_lower() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_unsigned_mul_quadword_and_lower() {
     assert_evals_to!("2u64 * 4 * 6", 48, u64);
     assert_evals_to!("2u32 * 4 * 6", 48, u32);
@@ -1120,31 +1510,51 @@ fn gen_unsigned_mul_quadword_and_lower() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_mul_f64() {
     assert_evals_to!("2f64 * 4 * 6", 48.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_mul_f32() {
     assert_evals_to!("2f32 * 4 * 6", 48.0, f32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_div_i64() {
     assert_evals_to!("1000i64 // 10", 100, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_div_u64() {
     assert_evals_to!("1000u64 // 10", 100, u64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_div_checked_i64() {
     assert_evals_to!(
         indoc!(
@@ -1160,7 +1570,7 @@ fn gen_div_checked_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_div_checked_by_zero_i64() {
     assert_evals_to!(
         indoc!(
@@ -1176,13 +1586,23 @@ fn gen_div_checked_by_zero_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_rem_i64() {
     assert_evals_to!("Num.rem 8 3", 2, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_rem_checked_div_by_zero_i64() {
     assert_evals_to!(
         indoc!(
@@ -1198,7 +1618,7 @@ fn gen_rem_checked_div_by_zero_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_positive_i64() {
     assert_evals_to!("Num.isPositive 0", false, bool);
     assert_evals_to!("Num.isPositive 1", true, bool);
@@ -1206,7 +1626,7 @@ fn gen_is_positive_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_negative_i64() {
     assert_evals_to!("Num.isNegative 0", false, bool);
     assert_evals_to!("Num.isNegative 3", false, bool);
@@ -1214,7 +1634,7 @@ fn gen_is_negative_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_positive_f64() {
     assert_evals_to!("Num.isPositive 0.0", false, bool);
     assert_evals_to!("Num.isPositive 4.7", true, bool);
@@ -1222,7 +1642,7 @@ fn gen_is_positive_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_negative_f64() {
     assert_evals_to!("Num.isNegative 0.0", false, bool);
     assert_evals_to!("Num.isNegative 9.9", false, bool);
@@ -1230,7 +1650,12 @@ fn gen_is_negative_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gen_is_zero_i64() {
     assert_evals_to!("Num.isZero 0", true, bool);
     assert_evals_to!("Num.isZero 0_0", true, bool);
@@ -1238,48 +1663,53 @@ fn gen_is_zero_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_zero_f64() {
     assert_evals_to!("Num.isZero 0.0", true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_odd() {
     assert_evals_to!("Num.isOdd 4", false, bool);
     assert_evals_to!("Num.isOdd 5", true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_is_even() {
     assert_evals_to!("Num.isEven 6", true, bool);
     assert_evals_to!("Num.isEven 7", false, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn sin() {
     assert_evals_to!("Num.sin 0", 0.0, f64);
     assert_evals_to!("Num.sin 1.41421356237", 0.9877659459922529, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn cos() {
     assert_evals_to!("Num.cos 0", 1.0, f64);
     assert_evals_to!("Num.cos 3.14159265359", -1.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn tan() {
     assert_evals_to!("Num.tan 0", 0.0, f64);
     assert_evals_to!("Num.tan 1", 1.557407724654902, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn bitwise_and() {
     assert_evals_to!("Num.bitwiseAnd 20 20", 20, i64);
     assert_evals_to!("Num.bitwiseAnd 25 10", 8, i64);
@@ -1287,7 +1717,12 @@ fn bitwise_and() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn bitwise_xor() {
     assert_evals_to!("Num.bitwiseXor 20 20", 0, i64);
     assert_evals_to!("Num.bitwiseXor 15 14", 1, i64);
@@ -1296,14 +1731,19 @@ fn bitwise_xor() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn bitwise_or() {
     assert_evals_to!("Num.bitwiseOr 1 1", 1, i64);
     assert_evals_to!("Num.bitwiseOr 1 2", 3, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn lt_u8() {
     assert_evals_to!("1u8 < 2u8", true, bool);
     assert_evals_to!("1u8 < 1u8", false, bool);
@@ -1313,7 +1753,7 @@ fn lt_u8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn lte_u8() {
     assert_evals_to!("1u8 <= 1u8", true, bool);
     assert_evals_to!("2u8 <= 1u8", false, bool);
@@ -1323,7 +1763,7 @@ fn lte_u8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gt_u8() {
     assert_evals_to!("2u8 > 1u8", true, bool);
     assert_evals_to!("2u8 > 2u8", false, bool);
@@ -1333,7 +1773,7 @@ fn gt_u8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gte_u8() {
     assert_evals_to!("1u8 >= 1u8", true, bool);
     assert_evals_to!("1u8 >= 2u8", false, bool);
@@ -1343,7 +1783,12 @@ fn gte_u8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn lt_u64() {
     assert_evals_to!("1u64 < 2u64", true, bool);
     assert_evals_to!("1u64 < 1u64", false, bool);
@@ -1353,7 +1798,7 @@ fn lt_u64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn lte_u64() {
     assert_evals_to!("1u64 <= 1u64", true, bool);
     assert_evals_to!("2u64 <= 1u64", false, bool);
@@ -1363,7 +1808,12 @@ fn lte_u64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gt_u64() {
     assert_evals_to!("2u64 > 1u64", true, bool);
     assert_evals_to!("2u64 > 2u64", false, bool);
@@ -1373,7 +1823,7 @@ fn gt_u64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gte_u64() {
     assert_evals_to!("1u64 >= 1u64", true, bool);
     assert_evals_to!("1u64 >= 2u64", false, bool);
@@ -1383,7 +1833,12 @@ fn gte_u64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn lt_i64() {
     assert_evals_to!("1 < 2", true, bool);
     assert_evals_to!("1 < 1", false, bool);
@@ -1392,7 +1847,7 @@ fn lt_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn lte_i64() {
     assert_evals_to!("1 <= 1", true, bool);
     assert_evals_to!("2 <= 1", false, bool);
@@ -1401,7 +1856,12 @@ fn lte_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn gt_i64() {
     assert_evals_to!("2 > 1", true, bool);
     assert_evals_to!("2 > 2", false, bool);
@@ -1410,7 +1870,7 @@ fn gt_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gte_i64() {
     assert_evals_to!("1 >= 1", true, bool);
     assert_evals_to!("1 >= 2", false, bool);
@@ -1419,7 +1879,7 @@ fn gte_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn lt_f64() {
     assert_evals_to!("1.1 < 1.2", true, bool);
     assert_evals_to!("1.1 < 1.1", false, bool);
@@ -1428,7 +1888,7 @@ fn lt_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn lte_f64() {
     assert_evals_to!("1.1 <= 1.1", true, bool);
     assert_evals_to!("1.2 <= 1.1", false, bool);
@@ -1437,7 +1897,7 @@ fn lte_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gt_f64() {
     assert_evals_to!("2.2 > 1.1", true, bool);
     assert_evals_to!("2.2 > 2.2", false, bool);
@@ -1446,7 +1906,7 @@ fn gt_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gte_f64() {
     assert_evals_to!("1.1 >= 1.1", true, bool);
     assert_evals_to!("1.1 >= 1.2", false, bool);
@@ -1455,7 +1915,12 @@ fn gte_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_order_of_arithmetic_ops() {
     assert_evals_to!(
         indoc!(
@@ -1469,7 +1934,7 @@ fn gen_order_of_arithmetic_ops() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_order_of_arithmetic_ops_complex_float() {
     assert_evals_to!(
         indoc!(
@@ -1483,7 +1948,12 @@ fn gen_order_of_arithmetic_ops_complex_float() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn if_guard_bind_variable_false() {
     assert_evals_to!(
         indoc!(
@@ -1502,7 +1972,12 @@ fn if_guard_bind_variable_false() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn if_guard_bind_variable_true() {
     assert_evals_to!(
         indoc!(
@@ -1521,7 +1996,12 @@ fn if_guard_bind_variable_true() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn tail_call_elimination() {
     assert_evals_to!(
         indoc!(
@@ -1551,13 +2031,43 @@ fn int_negate_dev() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_negate() {
     assert_evals_to!("Num.neg 123", -123, i64);
     assert_evals_to!("Num.neg Num.maxI64", -i64::MAX, i64);
     assert_evals_to!("Num.neg (Num.minI64 + 1)", i64::MAX, i64);
 }
 
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn various_sized_negate() {
+    assert_evals_to!("Num.neg 6i8", -6, i8);
+    assert_evals_to!("Num.neg 6i32", -6, i32);
+    assert_evals_to!("Num.neg 6i128", -6, i128);
+    assert_evals_to!(
+        "Num.neg -10_000_000_000_000_000_000i128",
+        10_000_000_000_000_000_000,
+        i128
+    );
+    assert_evals_to!("Num.neg 1.5f32", -1.5, f32);
+    assert_evals_to!("Num.neg 1.5f64", -1.5, f64);
+    assert_evals_to!(
+        "Num.neg 1.5dec",
+        RocDec::from_str_to_i128_unsafe("-1.5"),
+        i128
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 #[should_panic(
@@ -1576,7 +2086,7 @@ fn neg_min_int_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn gen_wrap_int_neg() {
     assert_evals_to!(
         indoc!(
@@ -1592,7 +2102,12 @@ fn gen_wrap_int_neg() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn gen_basic_fn() {
     assert_evals_to!(
         indoc!(
@@ -1609,19 +2124,29 @@ fn gen_basic_fn() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_to_float() {
     assert_evals_to!("Num.toFrac 0x9", 9.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn num_to_frac() {
     assert_evals_to!("Num.toFrac 9", 9.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn num_to_frac_f64_to_f32() {
     assert_evals_to!(
         indoc!(
@@ -1640,7 +2165,7 @@ fn num_to_frac_f64_to_f32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn num_to_frac_f32_to_f32() {
     assert_evals_to!(
         indoc!(
@@ -1660,7 +2185,12 @@ fn num_to_frac_f32_to_f32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn num_to_frac_f64_to_f64() {
     assert_evals_to!(
         indoc!(
@@ -1680,7 +2210,12 @@ fn num_to_frac_f64_to_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn num_to_frac_f32_to_f64() {
     assert_evals_to!(
         indoc!(
@@ -1700,13 +2235,23 @@ fn num_to_frac_f32_to_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn float_to_float() {
     assert_evals_to!("Num.toFrac 0.5", 0.5, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn frac_is_nan() {
     assert_evals_to!("Num.isNaN (0 / 0)", true, bool);
     assert_evals_to!("Num.isNaN (1 / 0)", false, bool);
@@ -1714,7 +2259,12 @@ fn frac_is_nan() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn frac_is_infinite() {
     assert_evals_to!("Num.isInfinite (1 / 0)", true, bool);
     assert_evals_to!("Num.isInfinite (-1 / 0)", true, bool);
@@ -1723,7 +2273,12 @@ fn frac_is_infinite() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn frac_is_finite() {
     assert_evals_to!("Num.isFinite 42", true, bool);
     assert_evals_to!("Num.isFinite (1 / 0)", false, bool);
@@ -1747,31 +2302,41 @@ fn float_compare() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn pow() {
     assert_evals_to!("Num.pow 2.0 2.0", 4.0, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn ceiling() {
     assert_evals_to!("Num.ceiling 1.1", 2, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn floor() {
     assert_evals_to!("Num.floor 1.9", 1, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn pow_int() {
     assert_evals_to!("Num.powInt 2 3", 8, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn atan() {
     assert_evals_to!("Num.atan 10", 1.4711276743037347, f64);
 }
@@ -1812,7 +2377,32 @@ fn int_add_checked_err() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn narrow_int_add_checked() {
+    assert_evals_to!(
+        "Num.addChecked 200u8 100",
+        RocResult::err(()),
+        RocResult<u8, ()>
+    );
+    assert_evals_to!(
+        "Num.addChecked 100u8 100",
+        RocResult::ok(200),
+        RocResult<u8, ()>
+    );
+    assert_evals_to!(
+        "Num.addChecked 100i8 100",
+        RocResult::err(()),
+        RocResult<i8, ()>
+    );
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_add_wrap() {
     assert_evals_to!(
         "Num.addWrap 9_223_372_036_854_775_807 1",
@@ -1822,7 +2412,7 @@ fn int_add_wrap() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn float_add_checked_pass() {
     assert_evals_to!(
         "Num.addChecked 1.0 0.0",
@@ -1832,7 +2422,7 @@ fn float_add_checked_pass() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
 fn float_add_checked_fail() {
     assert_evals_to!(
         "Num.addChecked 1.7976931348623157e308 1.7976931348623157e308",
@@ -1842,7 +2432,12 @@ fn float_add_checked_fail() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen_dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn float_add_overflow() {
     assert_evals_to!(
         "1.7976931348623157e308 + 1.7976931348623157e308",
@@ -1859,7 +2454,12 @@ fn int_sub_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn int_sub_wrap() {
     assert_evals_to!(
         "Num.subWrap -9_223_372_036_854_775_808 1",
@@ -1871,7 +2471,12 @@ fn int_sub_wrap() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn float_sub_overflow() {
     assert_evals_to!(
         "-1.7976931348623157e308 - 1.7976931348623157e308",
@@ -1881,7 +2486,7 @@ fn float_sub_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn int_sub_checked() {
     assert_evals_to!(
         indoc!(
@@ -1909,7 +2514,7 @@ fn int_sub_checked() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn float_sub_checked() {
     assert_evals_to!(
         indoc!(
@@ -1967,7 +2572,7 @@ fn int_negative_mul_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn float_positive_mul_overflow() {
     assert_evals_to!(
         indoc!(
@@ -1981,7 +2586,7 @@ fn float_positive_mul_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn float_negative_mul_overflow() {
     assert_evals_to!(
         indoc!(
@@ -1995,19 +2600,164 @@ fn float_negative_mul_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_mul_wrap_i64() {
     assert_evals_to!("Num.mulWrap Num.maxI64 2", -2, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn int_mul_wrap_i128() {
     assert_evals_to!("Num.mulWrap Num.maxI128 2", -2, i128);
 }
 
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn int_add_sub_i128() {
+    assert_evals_to!(
+        "18_446_744_073_709_551_615i128 + 1",
+        18_446_744_073_709_551_616,
+        i128
+    );
+    assert_evals_to!(
+        "18_446_744_073_709_551_616u128 - 1",
+        18_446_744_073_709_551_615,
+        u128
+    );
+    assert_evals_to!("5i128 - 7", -2, i128);
+    assert_evals_to!("Num.addWrap Num.maxI128 1", i128::MIN, i128);
+    assert_evals_to!("Num.subWrap 0u128 1", u128::MAX, u128);
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn int_compare_i128() {
+    assert_evals_to!("-1i128 < 1", true, bool);
+    assert_evals_to!(
+        "18_446_744_073_709_551_616i128 > 18_446_744_073_709_551_615",
+        true,
+        bool
+    );
+    assert_evals_to!("1u128 <= 1", true, bool);
+    assert_evals_to!("Num.maxU128 >= 1", true, bool);
+    assert_evals_to!("1.5dec < 2.5dec", true, bool);
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn int_div_rem_i128() {
+    assert_evals_to!(
+        "Num.divTrunc 10_000_000_000_000_000_000i128 -3",
+        -3_333_333_333_333_333_333,
+        i128
+    );
+    assert_evals_to!("Num.rem -10i128 3", -1, i128);
+    assert_evals_to!("Num.divTrunc 10u128 3", 3, u128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn bitwise_i128() {
+    assert_evals_to!(
+        "Num.bitwiseAnd Num.maxU128 (Num.shiftLeftBy 3u128 64)",
+        3 << 64,
+        u128
+    );
+    assert_evals_to!("Num.bitwiseOr -1i128 7", -1, i128);
+    assert_evals_to!("Num.bitwiseXor -1i128 1", -2, i128);
+    assert_evals_to!("Num.shiftLeftBy 1i128 100", 1 << 100, i128);
+    assert_evals_to!("Num.shiftRightBy (Num.shiftLeftBy -1i128 100) 99", -2, i128);
+    assert_evals_to!("Num.shiftRightZfBy Num.maxU128 127", 1, u128);
+    assert_evals_to!(
+        "Num.bitwiseXor (Num.shiftLeftBy 1u128 100) 1",
+        (1 << 100) | 1,
+        u128
+    );
+    assert_evals_to!(
+        "Num.shiftRightBy (Num.shiftLeftBy 1u128 127) 127",
+        u128::MAX,
+        u128
+    );
+    assert_evals_to!("Num.shiftRightZfBy (Num.shiftLeftBy 5i128 70) 70", 5, i128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
+fn int_cast_i128() {
+    assert_evals_to!("Num.toI128 -5i64", -5, i128);
+    assert_evals_to!("Num.toI128 -5i8", -5, i128);
+    assert_evals_to!("Num.toU128 Num.maxU64", u64::MAX as u128, u128);
+    assert_evals_to!("Num.toU128 Num.maxU32", u32::MAX as u128, u128);
+    assert_evals_to!("Num.toI64 (Num.shiftLeftBy 1i128 64 + 7)", 7, i64);
+    assert_evals_to!("Num.toU8 (Num.shiftLeftBy 1u128 64 + 300)", 44, u8);
+    assert_evals_to!("Num.toI128 Num.maxU128", -1, i128);
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
+fn i128_to_float() {
+    assert_evals_to!("Num.toF64 -3i128", -3.0, f64);
+    assert_evals_to!("Num.toF64 (Num.shiftLeftBy 1u128 100)", 2f64.powi(100), f64);
+    assert_evals_to!("Num.toF32 1u128", 1.0, f32);
+    assert_evals_to!(
+        indoc!(
+            r#"
+            y : F64
+            y = 150_000_000_000_000_000_000.0
+
+            x : I128
+            x = Num.floor y
+
+            Num.toF64 x
+            "#
+        ),
+        1.5e20,
+        f64
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn dec_compare() {
+    assert_evals_to!("-1.5dec < 1.5dec", true, bool);
+    assert_evals_to!("2.5dec > 2.25dec", true, bool);
+    assert_evals_to!("-2.5dec >= -2.5dec", true, bool);
+    assert_evals_to!("Num.compare -2.5dec 1.0dec", RocOrder::Lt, RocOrder);
+    assert_evals_to!("Num.compare 1.0dec 1.0dec", RocOrder::Eq, RocOrder);
+    assert_evals_to!(
+        "Num.abs -0.000000000000000001dec",
+        RocDec::from_str_to_i128_unsafe("0.000000000000000001"),
+        i128
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn int_mul_checked() {
     assert_evals_to!(
         indoc!(
@@ -2035,7 +2785,7 @@ fn int_mul_checked() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn float_mul_checked() {
     assert_evals_to!(
         indoc!(
@@ -2063,7 +2813,12 @@ fn float_mul_checked() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn shift_left_by() {
     assert_evals_to!("Num.shiftLeftBy 0b0000_0001 0", 0b0000_0001, i64);
     assert_evals_to!("Num.shiftLeftBy 0b0000_0001 1", 0b0000_0010, i64);
@@ -2072,7 +2827,12 @@ fn shift_left_by() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn shift_right_by() {
     // Sign Extended Right Shift
 
@@ -2112,7 +2872,12 @@ fn shift_right_by() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn shift_right_zf_by() {
     // Logical Right Shift
     assert_evals_to!("Num.shiftRightZfBy 0b1100_0000u8 2", 0b0011_0000u8, u8);
@@ -2122,7 +2887,12 @@ fn shift_right_zf_by() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn shift_right_cast_i8() {
     // FIXME (Brian) Something funny happening with 8-bit binary literals in tests
 
@@ -2156,133 +2926,143 @@ fn shift_right_cast_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn min_i128() {
     assert_evals_to!("Num.minI128", i128::MIN, i128);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn max_i128() {
     assert_evals_to!("Num.maxI128", i128::MAX, i128);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_i64() {
     assert_evals_to!("Num.minI64", i64::MIN, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_i64() {
     assert_evals_to!("Num.maxI64", i64::MAX, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_u64() {
     assert_evals_to!("Num.minU64", u64::MIN, u64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_u64() {
     assert_evals_to!("Num.maxU64", u64::MAX, u64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_i32() {
     assert_evals_to!("Num.minI32", i32::MIN, i32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_i32() {
     assert_evals_to!("Num.maxI32", i32::MAX, i32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_u32() {
     assert_evals_to!("Num.minU32", u32::MIN, u32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_u32() {
     assert_evals_to!("Num.maxU32", u32::MAX, u32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_i16() {
     assert_evals_to!("Num.minI16", i16::MIN, i16);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_i16() {
     assert_evals_to!("Num.maxI16", i16::MAX, i16);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_u16() {
     assert_evals_to!("Num.minU16", u16::MIN, u16);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_u16() {
     assert_evals_to!("Num.maxU16", u16::MAX, u16);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_i8() {
     assert_evals_to!("Num.minI8", i8::MIN, i8);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_i8() {
     assert_evals_to!("Num.maxI8", i8::MAX, i8);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_u8() {
     assert_evals_to!("Num.minU8", u8::MIN, u8);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_u8() {
     assert_evals_to!("Num.maxU8", u8::MAX, u8);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_f64() {
     assert_evals_to!("Num.maxF64", f64::MAX, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_f64() {
     assert_evals_to!("Num.minF64", f64::MIN, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn max_f32() {
     assert_evals_to!("Num.maxF32", f32::MAX, f32);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn min_f32() {
     assert_evals_to!("Num.minF32", f32::MIN, f32);
 }
@@ -2372,34 +3152,34 @@ num_conversion_tests! {
         to_nat_truncate, "115i128", 115
     )
     "Num.toF32", f32, (
-        to_f32_from_i8, "15i8", 15.0
-        to_f32_from_i16, "15i16", 15.0
-        to_f32_from_i32, "15i32", 15.0
-        to_f32_from_i64, "15i64", 15.0
-        to_f32_from_i128, "15i128", 15.0
-        to_f32_from_u8, "15u8", 15.0
-        to_f32_from_u16, "15u16", 15.0
-        to_f32_from_u32, "15u32", 15.0
-        to_f32_from_u64, "15u64", 15.0
-        to_f32_from_u128, "15u128", 15.0
-        to_f32_from_nat, "15nat", 15.0
-        to_f32_from_f32, "1.5f32", 1.5
-        to_f32_from_f64, "1.5f64", 1.5
+        to_f32_from_i8, "15i8", 15.0, ["gen-dev"]
+        to_f32_from_i16, "15i16", 15.0, ["gen-dev"]
+        to_f32_from_i32, "15i32", 15.0, ["gen-dev"]
+        to_f32_from_i64, "15i64", 15.0, ["gen-dev"]
+        to_f32_from_i128, "15i128", 15.0, ["gen-dev"]
+        to_f32_from_u8, "15u8", 15.0, ["gen-dev"]
+        to_f32_from_u16, "15u16", 15.0, ["gen-dev"]
+        to_f32_from_u32, "15u32", 15.0, ["gen-dev"]
+        to_f32_from_u64, "15u64", 15.0, ["gen-dev"]
+        to_f32_from_u128, "15u128", 15.0, ["gen-dev"]
+        to_f32_from_nat, "15nat", 15.0, ["gen-dev"]
+        to_f32_from_f32, "1.5f32", 1.5, ["gen-dev"]
+        to_f32_from_f64, "1.5f64", 1.5, ["gen-dev"]
     )
     "Num.toF64", f64, (
-        to_f64_from_i8, "15i8", 15.0
-        to_f64_from_i16, "15i16", 15.0
-        to_f64_from_i32, "15i32", 15.0
-        to_f64_from_i64, "15i64", 15.0
-        to_f64_from_i128, "15i128", 15.0
-        to_f64_from_u8, "15u8", 15.0
-        to_f64_from_u16, "15u16", 15.0
-        to_f64_from_u32, "15u32", 15.0
-        to_f64_from_u64, "15u64", 15.0
-        to_f64_from_u128, "15u128", 15.0
-        to_f64_from_nat, "15nat", 15.0
-        to_f64_from_f32, "1.5f32", 1.5
-        to_f64_from_f64, "1.5f64", 1.5
+        to_f64_from_i8, "15i8", 15.0, ["gen-dev"]
+        to_f64_from_i16, "15i16", 15.0, ["gen-dev"]
+        to_f64_from_i32, "15i32", 15.0, ["gen-dev"]
+        to_f64_from_i64, "15i64", 15.0, ["gen-dev"]
+        to_f64_from_i128, "15i128", 15.0, ["gen-dev"]
+        to_f64_from_u8, "15u8", 15.0, ["gen-dev"]
+        to_f64_from_u16, "15u16", 15.0, ["gen-dev"]
+        to_f64_from_u32, "15u32", 15.0, ["gen-dev"]
+        to_f64_from_u64, "15u64", 15.0, ["gen-dev"]
+        to_f64_from_u128, "15u128", 15.0, ["gen-dev"]
+        to_f64_from_nat, "15nat", 15.0, ["gen-dev"]
+        to_f64_from_f32, "1.5f32", 1.5, ["gen-dev"]
+        to_f64_from_f64, "1.5f64", 1.5, ["gen-dev"]
     )
 }
 
@@ -2549,7 +3329,12 @@ to_int_checked_tests! {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn is_multiple_of_signed() {
     // true
     assert_evals_to!("Num.isMultipleOf 5 1", true, bool);
@@ -2566,7 +3351,7 @@ fn is_multiple_of_signed() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn is_multiple_of_unsigned() {
     // true
     assert_evals_to!("Num.isMultipleOf 5u8 1", true, bool);
@@ -2720,7 +3505,7 @@ fn bytes_to_u128_subtly_out_of_bounds() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u16_max_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2736,7 +3521,7 @@ fn bytes_to_u16_max_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u16_min_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2752,7 +3537,7 @@ fn bytes_to_u16_min_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u16_random_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2768,7 +3553,7 @@ fn bytes_to_u16_random_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u32_min_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2784,7 +3569,7 @@ fn bytes_to_u32_min_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u32_max_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2800,7 +3585,7 @@ fn bytes_to_u32_max_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u32_random_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2816,7 +3601,7 @@ fn bytes_to_u32_random_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u64_min_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2832,7 +3617,7 @@ fn bytes_to_u64_min_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u64_max_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2848,7 +3633,7 @@ fn bytes_to_u64_max_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u64_random_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2864,7 +3649,7 @@ fn bytes_to_u64_random_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u128_min_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2880,7 +3665,7 @@ fn bytes_to_u128_min_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u128_max_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2896,7 +3681,7 @@ fn bytes_to_u128_max_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn bytes_to_u128_random_u8s() {
     assert_evals_to!(
         indoc!(
@@ -2912,7 +3697,7 @@ fn bytes_to_u128_random_u8s() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn when_on_i32() {
     assert_evals_to!(
         indoc!(
@@ -2935,7 +3720,7 @@ fn when_on_i32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn when_on_i16() {
     assert_evals_to!(
         indoc!(
@@ -2958,7 +3743,12 @@ fn when_on_i16() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn num_to_str() {
     use roc_std::RocStr;
 
@@ -2982,7 +3772,7 @@ fn num_to_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_u8() {
     use roc_std::RocStr;
 
@@ -2995,7 +3785,7 @@ fn num_to_str_u8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_u16() {
     use roc_std::RocStr;
 
@@ -3012,7 +3802,7 @@ fn num_to_str_u16() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_u32() {
     use roc_std::RocStr;
 
@@ -3029,7 +3819,7 @@ fn num_to_str_u32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_u64() {
     use roc_std::RocStr;
 
@@ -3046,7 +3836,7 @@ fn num_to_str_u64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_i8() {
     use roc_std::RocStr;
 
@@ -3064,7 +3854,7 @@ fn num_to_str_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_i16() {
     use roc_std::RocStr;
 
@@ -3090,7 +3880,7 @@ fn num_to_str_i16() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_i32() {
     use roc_std::RocStr;
 
@@ -3116,7 +3906,7 @@ fn num_to_str_i32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_i64() {
     use roc_std::RocStr;
 
@@ -3142,7 +3932,7 @@ fn num_to_str_i64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_f32() {
     use roc_std::RocStr;
 
@@ -3166,7 +3956,7 @@ fn num_to_str_f32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_f64() {
     use roc_std::RocStr;
 
@@ -3190,7 +3980,7 @@ fn num_to_str_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_to_str_dec() {
     use roc_std::RocStr;
 
@@ -3214,7 +4004,7 @@ fn num_to_str_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn u8_addition_greater_than_i8() {
     assert_evals_to!(
         indoc!(
@@ -3232,7 +4022,7 @@ fn u8_addition_greater_than_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn u8_sub_greater_than_i8() {
     assert_evals_to!(
         indoc!(
@@ -3250,7 +4040,7 @@ fn u8_sub_greater_than_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn u8_mul_greater_than_i8() {
     assert_evals_to!(
         indoc!(
@@ -3268,7 +4058,12 @@ fn u8_mul_greater_than_i8() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn add_saturated() {
     assert_evals_to!(
         indoc!(
@@ -3314,7 +4109,12 @@ fn add_saturated() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn sub_saturated() {
     assert_evals_to!(
         indoc!(
@@ -3358,7 +4158,12 @@ fn sub_saturated() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn mul_saturated() {
     assert_evals_to!(
         indoc!(
@@ -3428,7 +4233,7 @@ fn mul_saturated() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn monomorphized_ints() {
     assert_evals_to!(
         indoc!(
@@ -3447,7 +4252,7 @@ fn monomorphized_ints() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn monomorphized_floats() {
     assert_evals_to!(
         indoc!(
@@ -3466,7 +4271,7 @@ fn monomorphized_floats() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn monomorphized_ints_names_dont_conflict() {
     assert_evals_to!(
         indoc!(
@@ -3490,7 +4295,7 @@ fn monomorphized_ints_names_dont_conflict() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn monomorphized_ints_aliased() {
     assert_evals_to!(
         indoc!(
@@ -3517,7 +4322,12 @@ fn monomorphized_ints_aliased() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn to_float_f32() {
     assert_evals_to!(
         indoc!(
@@ -3536,7 +4346,12 @@ fn to_float_f32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn to_float_f64() {
     assert_evals_to!(
         indoc!(
@@ -3555,7 +4370,71 @@ fn to_float_f64() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn dec_to_float() {
+    assert_evals_to!("Num.toF64 1.5dec", 1.5, f64);
+    assert_evals_to!("Num.toF32 -1.5dec", -1.5, f32);
+}
+
+#[test]
+#[cfg(feature = "gen-wasm")]
+fn to_frac_dec() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            n : I128
+            n = -12
+
+            f : Dec
+            f = Num.toFrac n
+            f
+            "#
+        ),
+        RocDec::from_str_to_i128_unsafe("-12"),
+        i128
+    );
+    assert_evals_to!(
+        indoc!(
+            r#"
+            n : U8
+            n = 200
+
+            f : Dec
+            f = Num.toFrac n
+            f
+            "#
+        ),
+        RocDec::from_str_to_i128_unsafe("200"),
+        i128
+    );
+    assert_evals_to!(
+        indoc!(
+            r#"
+            n : F32
+            n = 2.5
+
+            f : Dec
+            f = Num.toFrac n
+            f / 2
+            "#
+        ),
+        RocDec::from_str_to_i128_unsafe("1.25"),
+        i128
+    );
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 // https://github.com/roc-lang/roc/issues/2696
 fn upcast_of_int_is_zext() {
     assert_evals_to!(
@@ -3587,7 +4466,7 @@ fn upcast_of_int_checked_is_zext() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn modulo_of_unsigned() {
     assert_evals_to!(
         indoc!(
@@ -3601,7 +4480,7 @@ fn modulo_of_unsigned() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn div_of_unsigned() {
     assert_evals_to!(
         indoc!(
@@ -3615,7 +4494,12 @@ fn div_of_unsigned() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn dec_float_suffix() {
     assert_evals_to!(
         indoc!(
@@ -3629,7 +4513,12 @@ fn dec_float_suffix() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn dec_no_decimal() {
     assert_evals_to!(
         indoc!(
@@ -3643,7 +4532,7 @@ fn dec_no_decimal() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn ceiling_to_u32() {
     assert_evals_to!(
         indoc!(
@@ -3659,7 +4548,7 @@ fn ceiling_to_u32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn floor_to_u32() {
     assert_evals_to!(
         indoc!(
@@ -3675,7 +4564,7 @@ fn floor_to_u32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn round_to_u32() {
     assert_evals_to!(
         indoc!(
@@ -3691,7 +4580,7 @@ fn round_to_u32() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn promote_u64_number_layout() {
     assert_evals_to!(
         indoc!(
@@ -3705,7 +4594,7 @@ fn promote_u64_number_layout() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn promote_i128_number_layout() {
     assert_evals_to!(
         indoc!(
@@ -3722,7 +4611,7 @@ fn promote_i128_number_layout() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn promote_u128_number_layout() {
     assert_evals_to!(
         indoc!(
@@ -3736,7 +4625,7 @@ fn promote_u128_number_layout() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn when_on_decimals() {
     assert_evals_to!(
         indoc!(
@@ -3767,7 +4656,12 @@ fn when_on_decimals() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn when_on_i128() {
     assert_evals_to!(
         indoc!(
@@ -3785,7 +4679,7 @@ fn when_on_i128() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn when_on_u128() {
     assert_evals_to!(
         indoc!(
@@ -3803,7 +4697,7 @@ fn when_on_u128() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn condition_polymorphic_num_becomes_float() {
     assert_evals_to!(
         indoc!(
@@ -3818,7 +4712,7 @@ fn condition_polymorphic_num_becomes_float() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_count_leading_zero_bits() {
     assert_evals_to!(r#"Num.countLeadingZeroBits 0b0010_1000u8"#, 2, usize);
     assert_evals_to!(r#"Num.countLeadingZeroBits 0b0010_1000u16"#, 10, usize);
@@ -3827,7 +4721,7 @@ fn num_count_leading_zero_bits() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_count_trailing_zero_bits() {
     assert_evals_to!(r#"Num.countTrailingZeroBits 0b0010_1000u8"#, 3, usize);
     assert_evals_to!(r#"Num.countTrailingZeroBits 0b0010_0000u16"#, 5, usize);
@@ -3836,7 +4730,7 @@ fn num_count_trailing_zero_bits() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_count_one_bits() {
     assert_evals_to!(r#"Num.countOneBits 0b0010_1000u8"#, 2, usize);
     assert_evals_to!(r#"Num.countOneBits 0b0010_0000u16"#, 1, usize);
@@ -3845,7 +4739,7 @@ fn num_count_one_bits() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_abs_diff_int() {
     assert_evals_to!(r#"Num.absDiff 0u8 0u8"#, 0, u8);
     assert_evals_to!(r#"Num.absDiff 1u8 2u8"#, 1, u8);
@@ -3856,7 +4750,7 @@ fn num_abs_diff_int() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_abs_diff_large_bits() {
     assert_evals_to!(r#"Num.absDiff 0u128 0u128"#, 0, u128);
     assert_evals_to!(r#"Num.absDiff 1u128 2u128"#, 1, u128);
@@ -3865,7 +4759,7 @@ fn num_abs_diff_large_bits() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_abs_diff_float() {
     assert_evals_to!(r#"Num.absDiff 0.0 0.0"#, 0.0, f64);
     assert_evals_to!(r#"Num.absDiff 1.0 2.0"#, 1.0, f64);
@@ -3889,20 +4783,25 @@ fn num_abs_int_min_overflow() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 #[should_panic(expected = r#"Roc failed with message: "integer subtraction overflowed!"#)]
 fn num_abs_large_bits_min_overflow() {
     assert_evals_to!(r#"Num.absDiff Num.minI128 0"#, 0, i128);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn num_abs_float_overflow() {
     assert_evals_to!("Num.absDiff Num.maxF64 Num.minF64", f64::INFINITY, f64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn bool_in_switch() {
     assert_evals_to!(
         indoc!(
@@ -7,18 +7,31 @@ use crate::helpers::dev::assert_evals_to;
 #[cfg(feature = "gen-wasm")]
 use crate::helpers::wasm::assert_evals_to;
 
+#[cfg(feature = "gen-interp")]
+use crate::helpers::interp::assert_evals_to;
+
 use indoc::indoc;
 #[allow(unused_imports)]
 use roc_std::{RocBox, RocList, RocStr};
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn basic_int() {
     assert_evals_to!("123", 123, i64);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-dev",
+    feature = "gen-wasm",
+    feature = "gen-interp"
+))]
 fn basic_float() {
     assert_evals_to!("1234.0", 1234.0, f64);
 }
@@ -3556,6 +3569,73 @@ fn mutual_recursion_top_level_defs() {
     )
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn mutual_tail_recursion_does_not_grow_the_stack() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [ main ] to "./platform"
+
+            isEven : U64, U64 -> U64
+            isEven = \n, steps ->
+                if n == 0 then
+                    steps
+                else
+                    isOdd (n - 1) (steps + 1)
+
+            isOdd : U64, U64 -> U64
+            isOdd = \n, steps ->
+                if n == 0 then
+                    steps
+                else
+                    isEven (n - 1) (steps + 1)
+
+            main = isEven 10_000_000 0
+            "#
+        ),
+        10_000_000,
+        u64
+    )
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn recursion_under_a_constructor_does_not_grow_the_stack() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [ main ] to "./platform"
+
+            LinkedList : [Nil, Cons U64 LinkedList]
+
+            range : U64, U64 -> LinkedList
+            range = \from, to ->
+                if from >= to then
+                    Nil
+                else
+                    Cons from (range (from + 1) to)
+
+            double : LinkedList -> LinkedList
+            double = \list ->
+                when list is
+                    Nil -> Nil
+                    Cons x xs -> Cons (2 * x) (double xs)
+
+            sum : LinkedList, U64 -> U64
+            sum = \list, acc ->
+                when list is
+                    Nil -> acc
+                    Cons x xs -> sum xs (acc + x)
+
+            main = sum (double (range 0 1_000_000)) 0
+            "#
+        ),
+        999_999_000_000,
+        u64
+    )
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn polymorphic_lambda_captures_polymorphic_value() {
@@ -4498,3 +4578,326 @@ fn pass_lambda_set_to_function() {
         i64
     );
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn inline_small_accessors() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            getX = \r -> r.x
+            getY = \r -> r.y
+
+            add = \r -> getX r + getY r
+
+            main =
+                a = { x: 1i64, y: 2 }
+                b = { x: 30, y: 40 }
+
+                add a + add b + getX b
+            "#
+        ),
+        103,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn inline_identity_not_in_tail_position() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            id = \x -> x
+
+            main =
+                a = id 5i64
+                b = id 7i64
+
+                a * b
+            "#
+        ),
+        35,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn inline_branching_proc_into_join_point() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            clamp = \x ->
+                if x < 0 then
+                    0
+                else if x > 10 then
+                    10
+                else
+                    x
+
+            main =
+                a = clamp (-5i64)
+                b = clamp 50
+                c = clamp 3
+
+                a + b * 100 + c * 1000
+            "#
+        ),
+        4000,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn inline_single_use_proc_with_local_join_points() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            classify = \n ->
+                size =
+                    when n is
+                        0 -> 1
+                        1 -> 2
+                        _ -> 3
+
+                big = if n > 5 then 20 else 10
+
+                size + big
+
+            main =
+                x = classify 7i64
+
+                x + 100
+            "#
+        ),
+        123,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+fn inline_does_not_unroll_recursion() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            isEven = \n -> if n == 0 then Bool.true else isOdd (n - 1)
+            isOdd = \n -> if n == 0 then Bool.false else isEven (n - 1)
+
+            double = \n -> n * 2
+
+            main =
+                if isEven (double 21i64) then 1u8 else 0u8
+            "#
+        ),
+        1,
+        u8
+    );
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn fold_constant_arithmetic_and_comparisons() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            scale = \x -> x * 3 + 1
+
+            main =
+                if scale 4i64 > 10 then scale 4 else 0
+            "#
+        ),
+        13,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
+#[should_panic(expected = r#"Roc failed with message: "integer addition overflowed!"#)]
+fn fold_keeps_overflowing_addition() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            inc = \x -> x + 1
+
+            main = inc 255u8
+            "#
+        ),
+        0,
+        u8
+    );
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn fold_switch_on_known_tag() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            withDefault = \result, default ->
+                when result is
+                    Ok value -> value
+                    Err _ -> default
+
+            main =
+                withDefault (Ok 41i64) 0 + withDefault (Err {}) 1
+            "#
+        ),
+        42,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
+fn fold_known_record_field_access() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            pair = \a, b -> { first: a, second: b }
+
+            main =
+                p = pair 3i64 4i64
+
+                p.first * 10 + p.second
+            "#
+        ),
+        34,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn loop_that_swaps_its_params() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            swap : I64, I64, I64 -> I64
+            swap = \n, a, b ->
+                if n == 0 then
+                    a - b
+                else
+                    swap (n - 1) b a
+
+            main = swap 3 10 1
+            "#
+        ),
+        -9,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn more_live_values_than_registers() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            count : I64, I64 -> I64
+            count = \n, acc -> if n == 0 then acc else count (n - 1) (acc + 1)
+
+            main =
+                x = count 1 0
+
+                a = x + 1
+                b = x + 2
+                c = x + 3
+                d = x + 4
+                e = x + 5
+                f = x + 6
+                g = x + 7
+                h = x + 8
+                i = x + 9
+                j = x + 10
+                k = x + 11
+                l = x + 12
+                m = x + 13
+                n = x + 14
+                o = x + 15
+                p = x + 16
+                q = x + 17
+                r = x + 18
+                s = x + 19
+                t = x + 20
+
+                a * 1 + b * 2 + c * 3 + d * 4 + e * 5 + f * 6 + g * 7 + h * 8 + i * 9 + j * 10 + k * 11 + l * 12 + m * 13 + n * 14 + o * 15 + p * 16 + q * 17 + r * 18 + s * 19 + t * 20
+            "#
+        ),
+        3080,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-wasm"))]
+fn branches_merge_into_a_join_point() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            count : I64, I64 -> I64
+            count = \n, acc -> if n == 0 then acc else count (n - 1) (acc + 1)
+
+            merge : I64, I64 -> I64
+            merge = \x, y ->
+                z =
+                    if x > 5 then
+                        a = x * 2
+                        b = y + a
+
+                        b * 3
+                    else
+                        y - x
+
+                z * 100 + x * 10 + y
+
+            main =
+                merge (count 7 0) (count 2 0) + merge (count 3 0) (count 4 0) * 1000
+            "#
+        ),
+        138872,
+        i64
+    );
+}
@@ -711,6 +711,23 @@ fn return_record_7() {
     );
 }
 
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn pass_and_return_big_record() {
+    // Big enough for the Wasm backend to copy it with `memory.copy`
+    assert_evals_to!(
+        indoc!(
+            r#"
+                swap = \r -> { r & a: r.j, j: r.a }
+
+                swap { a: 1, b: 2, c: 3, d: 4, e: 5, f: 6, g: 7, h: 8, i: 9, j: 10 }
+                "#
+        ),
+        [10, 2, 3, 4, 5, 6, 7, 8, 9, 1],
+        [i64; 10]
+    );
+}
+
 #[test]
 #[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
 fn return_record_float_int() {
@@ -1130,3 +1147,72 @@ fn pass_record_of_u8s() {
         bool
     )
 }
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn return_small_structs_from_functions() {
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            swap : { x : I64, y : I64 } -> { x : I64, y : I64 }
+            swap = \{ x, y } -> { x: y, y: x }
+
+            rotate : { a : U32, b : U32, c : U32 } -> { a : U32, b : U32, c : U32 }
+            rotate = \{ a, b, c } -> { a: b, b: c, c: a }
+
+            safeDiv : I64, I64 -> Result I64 [DivByZero]
+            safeDiv = \a, b -> if b == 0 then Err DivByZero else Ok (a // b)
+
+            main =
+                s = swap { x: 1, y: 2 }
+                r = rotate { a: 3, b: 4, c: 5 }
+                d =
+                    when safeDiv 60 2 is
+                        Ok n -> n
+                        Err DivByZero -> 0
+
+                s.x * 100000 + s.y * 10000 + Num.toI64 (r.a * 100 + r.b * 10 + r.c) + d
+            "#
+        ),
+        210483,
+        i64
+    );
+}
+
+#[test]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+fn return_16_byte_struct_and_dec() {
+    // `main` is exposed to the host, so it writes its result to memory,
+    // while `pair` and `triple` return theirs as several values
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            pair : I64 -> { x : I64, y : I64 }
+            pair = \n -> { x: n * 2, y: n - 10 }
+
+            main = pair 7
+            "#
+        ),
+        (14, -3),
+        (i64, i64)
+    );
+
+    assert_evals_to!(
+        indoc!(
+            r#"
+            app "test" provides [main] to "./platform"
+
+            triple : Dec -> Dec
+            triple = \x -> x * 3
+
+            main = triple 1.25
+            "#
+        ),
+        roc_std::RocDec::from_str_to_i128_unsafe("3.75"),
+        i128
+    );
+}
@@ -10,13 +10,21 @@ use crate::helpers::dev::assert_evals_to;
 #[cfg(feature = "gen-dev")]
 use crate::helpers::dev::assert_evals_to as assert_llvm_evals_to;
 
+#[cfg(feature = "gen-interp")]
+use crate::helpers::interp::assert_evals_to;
+
 #[allow(unused_imports)]
 use indoc::indoc;
 #[allow(unused_imports)]
 use roc_std::{RocList, RocResult, RocStr};
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn string_eq() {
     // context: the dev backend did not correctly mask the boolean that zig returns here
     assert_evals_to!(
@@ -33,7 +41,12 @@ fn string_eq() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn string_neq() {
     // context: the dev backend did not correctly mask the boolean that zig returns here
     assert_evals_to!(
@@ -110,7 +123,7 @@ fn str_split_bigger_delimiter_small_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_str_concat_repeated() {
     assert_evals_to!(
         indoc!(
@@ -134,7 +147,7 @@ fn str_split_str_concat_repeated() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_small_str_bigger_delimiter() {
     assert_evals_to!(
         indoc!(r#"Str.split "JJJ" "0123456789abcdefghi""#),
@@ -144,7 +157,7 @@ fn str_split_small_str_bigger_delimiter() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_big_str_small_delimiter() {
     assert_evals_to!(
         indoc!(
@@ -174,7 +187,7 @@ fn str_split_big_str_small_delimiter() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_small_str_small_delimiter() {
     assert_evals_to!(
         indoc!(
@@ -188,7 +201,7 @@ fn str_split_small_str_small_delimiter() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_bigger_delimiter_big_strs() {
     assert_evals_to!(
         indoc!(
@@ -204,7 +217,7 @@ fn str_split_bigger_delimiter_big_strs() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_empty_strs() {
     assert_evals_to!(
         indoc!(
@@ -218,7 +231,7 @@ fn str_split_empty_strs() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_minimal_example() {
     assert_evals_to!(
         indoc!(
@@ -232,7 +245,7 @@ fn str_split_minimal_example() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_small_str_big_delimiter() {
     assert_evals_to!(
         indoc!(
@@ -261,7 +274,7 @@ fn str_split_small_str_big_delimiter() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_small_str_20_char_delimiter() {
     assert_evals_to!(
         indoc!(
@@ -277,7 +290,7 @@ fn str_split_small_str_20_char_delimiter() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_concat_big_to_big() {
     assert_evals_to!(
             indoc!(
@@ -436,7 +449,7 @@ fn small_str_concat_empty_second_arg() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn small_str_concat_small_to_big() {
     assert_evals_to!(
         r#"Str.concat "abc" " this is longer than 15 chars""#,
@@ -481,7 +494,7 @@ fn small_str_concat_small_to_small_staying_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn small_str_concat_small_to_small_overflow_to_big() {
     assert_evals_to!(
         r#"Str.concat "abcdefghijklm" "nopqrstuvwxyz""#,
@@ -491,19 +504,19 @@ fn small_str_concat_small_to_small_overflow_to_big() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_concat_empty() {
     assert_evals_to!(r#"Str.concat "" """#, RocStr::default(), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn small_str_is_empty() {
     assert_evals_to!(r#"Str.isEmpty "abc""#, false, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn big_str_is_empty() {
     assert_evals_to!(
         r#"Str.isEmpty "this is more than 23 chars long""#,
@@ -513,13 +526,13 @@ fn big_str_is_empty() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn empty_str_is_empty() {
     assert_evals_to!(r#"Str.isEmpty """#, true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with() {
     assert_evals_to!(r#"Str.startsWith "hello world" "hell""#, true, bool);
     assert_evals_to!(r#"Str.startsWith "hello world" """#, true, bool);
@@ -529,7 +542,7 @@ fn str_starts_with() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with_scalar() {
     assert_evals_to!(
         &format!(r#"Str.startsWithScalar "foobar" {}"#, 'f' as u32),
@@ -544,7 +557,7 @@ fn str_starts_with_scalar() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_ends_with() {
     assert_evals_to!(r#"Str.endsWith "hello world" "world""#, true, bool);
     assert_evals_to!(r#"Str.endsWith "nope" "hello world""#, false, bool);
@@ -574,7 +587,7 @@ fn str_count_graphemes_big_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with_same_big_str() {
     assert_evals_to!(
         r#"Str.startsWith "123456789123456789" "123456789123456789""#,
@@ -584,7 +597,7 @@ fn str_starts_with_same_big_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with_different_big_str() {
     assert_evals_to!(
         r#"Str.startsWith "12345678912345678910" "123456789123456789""#,
@@ -594,24 +607,24 @@ fn str_starts_with_different_big_str() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with_same_small_str() {
     assert_evals_to!(r#"Str.startsWith "1234" "1234""#, true, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with_different_small_str() {
     assert_evals_to!(r#"Str.startsWith "1234" "12""#, true, bool);
 }
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_starts_with_false_small_str() {
     assert_evals_to!(r#"Str.startsWith "1234" "23""#, false, bool);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_single_ascii() {
     assert_evals_to!(
         indoc!(
@@ -627,7 +640,7 @@ fn str_from_utf8_pass_single_ascii() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_many_ascii() {
     assert_evals_to!(
         indoc!(
@@ -643,7 +656,7 @@ fn str_from_utf8_pass_many_ascii() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_single_unicode() {
     assert_evals_to!(
         indoc!(
@@ -659,7 +672,7 @@ fn str_from_utf8_pass_single_unicode() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_many_unicode() {
     assert_evals_to!(
         indoc!(
@@ -675,7 +688,7 @@ fn str_from_utf8_pass_many_unicode() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_single_grapheme() {
     assert_evals_to!(
         indoc!(
@@ -691,7 +704,7 @@ fn str_from_utf8_pass_single_grapheme() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_many_grapheme() {
     assert_evals_to!(
         indoc!(
@@ -707,7 +720,7 @@ fn str_from_utf8_pass_many_grapheme() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_pass_all() {
     assert_evals_to!(
         indoc!(
@@ -723,7 +736,7 @@ fn str_from_utf8_pass_all() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_fail_invalid_start_byte() {
     assert_evals_to!(
         indoc!(
@@ -743,7 +756,7 @@ fn str_from_utf8_fail_invalid_start_byte() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_fail_unexpected_end_of_sequence() {
     assert_evals_to!(
         indoc!(
@@ -763,7 +776,7 @@ fn str_from_utf8_fail_unexpected_end_of_sequence() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_fail_expected_continuation() {
     assert_evals_to!(
         indoc!(
@@ -783,7 +796,7 @@ fn str_from_utf8_fail_expected_continuation() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_fail_overlong_encoding() {
     assert_evals_to!(
         indoc!(
@@ -803,7 +816,7 @@ fn str_from_utf8_fail_overlong_encoding() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_fail_codepoint_too_large() {
     assert_evals_to!(
         indoc!(
@@ -823,7 +836,7 @@ fn str_from_utf8_fail_codepoint_too_large() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_from_utf8_fail_surrogate_half() {
     assert_evals_to!(
         indoc!(
@@ -843,7 +856,7 @@ fn str_from_utf8_fail_surrogate_half() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_equality() {
     assert_evals_to!(r#""a" == "a""#, true, bool);
     assert_evals_to!(
@@ -899,7 +912,7 @@ fn nested_recursive_literal() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_join_comma_small() {
     assert_evals_to!(
         r#"Str.joinWith ["1", "2"] ", " "#,
@@ -909,7 +922,7 @@ fn str_join_comma_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_join_comma_big() {
     assert_evals_to!(
         r#"Str.joinWith ["10000000", "2000000", "30000000"] ", " "#,
@@ -919,7 +932,7 @@ fn str_join_comma_big() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_join_comma_single() {
     assert_evals_to!(r#"Str.joinWith ["1"] ", " "#, RocStr::from("1"), RocStr);
 }
@@ -1065,7 +1078,7 @@ fn str_from_utf8_range_count_too_high_for_start() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_repeat_small_stays_small() {
     assert_evals_to!(
         indoc!(r#"Str.repeat "Roc" 3"#),
@@ -1075,7 +1088,7 @@ fn str_repeat_small_stays_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_repeat_small_becomes_big() {
     assert_evals_to!(
         indoc!(r#"Str.repeat "less than 23 characters" 2"#),
@@ -1085,7 +1098,7 @@ fn str_repeat_small_becomes_big() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_repeat_big() {
     assert_evals_to!(
         indoc!(r#"Str.repeat "more than 23 characters now" 2"#),
@@ -1095,26 +1108,26 @@ fn str_repeat_big() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_repeat_empty_string() {
     let a = indoc!(r#"Str.repeat "" 3"#);
     assert_evals_to!(a, RocStr::from(""), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_repeat_zero_times() {
     assert_evals_to!(indoc!(r#"Str.repeat "Roc" 0"#), RocStr::from(""), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_empty_string() {
     assert_evals_to!(indoc!(r#"Str.trim """#), RocStr::from(""), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_null_byte() {
     assert_evals_to!(
         indoc!(r#"Str.trim (Str.reserve "\u(0000)" 40)"#),
@@ -1124,13 +1137,13 @@ fn str_trim_null_byte() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_small_blank_string() {
     assert_evals_to!(indoc!(r#"Str.trim " ""#), RocStr::from(""), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_small_to_small() {
     assert_evals_to!(
         indoc!(r#"Str.trim "  hello world  ""#),
@@ -1140,7 +1153,7 @@ fn str_trim_small_to_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_large_to_large_unique() {
     assert_evals_to!(
         indoc!(r#"Str.trim (Str.concat "  " "hello world from a large string ")"#),
@@ -1150,7 +1163,7 @@ fn str_trim_large_to_large_unique() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_large_to_small_unique() {
     assert_evals_to!(
         indoc!(r#"Str.trim (Str.concat "  " "hello world        ")"#),
@@ -1217,13 +1230,13 @@ fn str_trim_small_to_small_shared() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_left_small_blank_string() {
     assert_evals_to!(indoc!(r#"Str.trimLeft " ""#), RocStr::from(""), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_left_small_to_small() {
     assert_evals_to!(
         indoc!(r#"Str.trimLeft "  hello world  ""#),
@@ -1233,7 +1246,7 @@ fn str_trim_left_small_to_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_left_large_to_large_unique() {
     assert_evals_to!(
         indoc!(r#"Str.trimLeft (Str.concat "    " "hello world from a large string ")"#),
@@ -1243,7 +1256,7 @@ fn str_trim_left_large_to_large_unique() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_left_large_to_small_unique() {
     assert_evals_to!(
         indoc!(r#"Str.trimLeft (Str.concat "  " "hello world        ")"#),
@@ -1310,13 +1323,13 @@ fn str_trim_left_small_to_small_shared() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_right_small_blank_string() {
     assert_evals_to!(indoc!(r#"Str.trimRight " ""#), RocStr::from(""), RocStr);
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_right_small_to_small() {
     assert_evals_to!(
         indoc!(r#"Str.trimRight "  hello world  ""#),
@@ -1326,7 +1339,7 @@ fn str_trim_right_small_to_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_right_large_to_large_unique() {
     assert_evals_to!(
         indoc!(r#"Str.trimRight (Str.concat " hello world from a large string" "    ")"#),
@@ -1336,7 +1349,7 @@ fn str_trim_right_large_to_large_unique() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_trim_right_large_to_small_unique() {
     assert_evals_to!(
         indoc!(r#"Str.trimRight (Str.concat "        hello world" "  ")"#),
@@ -1610,7 +1623,7 @@ fn str_to_dec() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn issue_2811() {
     assert_evals_to!(
         indoc!(
@@ -1626,7 +1639,7 @@ fn issue_2811() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn to_scalar_1_byte() {
     assert_evals_to!(
         indoc!(
@@ -1650,7 +1663,7 @@ fn to_scalar_1_byte() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn to_scalar_2_byte() {
     assert_evals_to!(
         indoc!(
@@ -1674,7 +1687,7 @@ fn to_scalar_2_byte() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn to_scalar_3_byte() {
     assert_evals_to!(
         indoc!(
@@ -1698,7 +1711,7 @@ fn to_scalar_3_byte() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn to_scalar_4_byte() {
     // from https://design215.com/toolbox/utf8-4byte-characters.php
     assert_evals_to!(
@@ -1837,7 +1850,7 @@ fn str_split_last_not_found() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_overlapping_substring_1() {
     assert_evals_to!(
         r#"Str.split "aaa" "aa""#,
@@ -1847,7 +1860,7 @@ fn str_split_overlapping_substring_1() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-dev"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-dev", feature = "gen-interp"))]
 fn str_split_overlapping_substring_2() {
     assert_evals_to!(
         r#"Str.split "aaaa" "aa""#,
@@ -1970,7 +1983,7 @@ fn llvm_wasm_str_layout_small() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn when_on_strings() {
     assert_evals_to!(
         indoc!(
@@ -2003,7 +2016,12 @@ fn when_on_strings() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn with_capacity() {
     assert_evals_to!(
         indoc!(
@@ -2017,7 +2035,12 @@ fn with_capacity() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn with_capacity_concat() {
     assert_evals_to!(
         indoc!(
@@ -2031,7 +2054,12 @@ fn with_capacity_concat() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn str_with_prefix() {
     assert_evals_to!(
         indoc!(
@@ -2055,7 +2083,12 @@ fn str_with_prefix() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn destructure_pattern_assigned_from_thunk_opaque() {
     assert_evals_to!(
         indoc!(
@@ -2077,7 +2110,12 @@ fn destructure_pattern_assigned_from_thunk_opaque() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-dev"))]
+#[cfg(any(
+    feature = "gen-llvm",
+    feature = "gen-wasm",
+    feature = "gen-dev",
+    feature = "gen-interp"
+))]
 fn destructure_pattern_assigned_from_thunk_tag() {
     assert_evals_to!(
         indoc!(
@@ -2098,7 +2136,7 @@ fn destructure_pattern_assigned_from_thunk_tag() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn release_excess_capacity() {
     assert_evals_to!(
         indoc!(
@@ -2114,7 +2152,7 @@ fn release_excess_capacity() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn release_excess_capacity_with_len() {
     assert_evals_to!(
         indoc!(
@@ -2131,7 +2169,7 @@ fn release_excess_capacity_with_len() {
 }
 
 #[test]
-#[cfg(any(feature = "gen-llvm", feature = "gen-wasm"))]
+#[cfg(any(feature = "gen-llvm", feature = "gen-wasm", feature = "gen-interp"))]
 fn release_excess_capacity_empty() {
     assert_evals_to!(
         indoc!(
@@ -1,7 +1,9 @@
 use libloading::Library;
 use roc_build::link::{link, LinkType};
 use roc_builtins::bitcode;
-use roc_load::{EntryPoint, ExecutionMode, LoadConfig, Threading};
+use roc_load::{
+    EntryPoint, ExecutionMode, LoadConfig, MonoDiagnostics, MonoOptimizations, Threading,
+};
 use roc_mono::ir::SingleEntryPoint;
 use roc_packaging::cache::RocCacheDir;
 use roc_region::all::LineInfo;
@@ -34,12 +36,63 @@ pub fn helper(
     _leak: bool,
     lazy_literals: bool,
 ) -> (String, Vec<roc_problem::can::Problem>, Library) {
+    let dir = tempdir().unwrap();
+    let app_o_file = dir.path().join("app.o");
+
+    let target = target_lexicon::Triple::host();
+    let (main_fn_name, delayed_errors, module_out) =
+        helper_object(arena, src, lazy_literals, &target);
+    std::fs::write(&app_o_file, module_out).expect("failed to write object to file");
+
+    let builtins_host_tempfile =
+        roc_bitcode::host_tempfile().expect("failed to write host builtins object to tempfile");
+
+    if false {
+        std::fs::copy(&app_o_file, "/tmp/app.o").unwrap();
+    }
+
+    let (mut child, dylib_path) = link(
+        &target,
+        app_o_file.clone(),
+        // Long term we probably want a smarter way to link in zig builtins.
+        // With the current method all methods are kept and it adds about 100k to all outputs.
+        &[
+            app_o_file.to_str().unwrap(),
+            builtins_host_tempfile.path().to_str().unwrap(),
+        ],
+        LinkType::Dylib,
+    )
+    .expect("failed to link dynamic library");
+
+    child.wait().unwrap();
+
+    // Extend the lifetime of the tempfile so it doesn't get dropped
+    // (and thus deleted) before the linking process is done using it!
+    let _ = builtins_host_tempfile;
+
+    // Load the dylib
+    let path = dylib_path.as_path().to_str().unwrap();
+
+    // std::fs::copy(&path, "/tmp/libapp.so").unwrap();
+
+    let lib = unsafe { Library::new(path) }.expect("failed to load shared library");
+
+    (main_fn_name, delayed_errors, lib)
+}
+
+/// Compiles `src` with the dev backend to an object file for `target`.
+/// Returns the name of the main function, the problems whose reporting we delay, and the object bytes.
+#[allow(dead_code)]
+pub fn helper_object(
+    arena: &bumpalo::Bump,
+    src: &str,
+    lazy_literals: bool,
+    target: &target_lexicon::Triple,
+) -> (String, Vec<roc_problem::can::Problem>, Vec<u8>) {
     use std::path::PathBuf;
 
-    let dir = tempdir().unwrap();
     let filename = PathBuf::from("Test.roc");
     let src_dir = PathBuf::from("fake/test/path");
-    let app_o_file = dir.path().join("app.o");
 
     let module_src;
     let temp;
@@ -53,11 +106,13 @@ pub fn helper(
     }
 
     let load_config = LoadConfig {
-        target_info: roc_target::TargetInfo::default_x86_64(),
+        target_info: roc_target::TargetInfo::from(target),
         render: roc_reporting::report::RenderTarget::ColorTerminal,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        mono_optimizations: MonoOptimizations::Enabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -193,54 +248,14 @@ pub fn helper(
         mode: roc_gen_dev::AssemblyBackendMode::Test,
     };
 
-    let target = target_lexicon::Triple::host();
-    let module_object = roc_gen_dev::build_module(
-        &env,
-        &mut interns,
-        &mut layout_interner,
-        &target,
-        procedures,
-    );
+    let module_object =
+        roc_gen_dev::build_module(&env, &mut interns, &mut layout_interner, target, procedures);
 
     let module_out = module_object
         .write()
         .expect("failed to build output object");
-    std::fs::write(&app_o_file, module_out).expect("failed to write object to file");
-
-    let builtins_host_tempfile =
-        roc_bitcode::host_tempfile().expect("failed to write host builtins object to tempfile");
-
-    if false {
-        std::fs::copy(&app_o_file, "/tmp/app.o").unwrap();
-    }
 
-    let (mut child, dylib_path) = link(
-        &target,
-        app_o_file.clone(),
-        // Long term we probably want a smarter way to link in zig builtins.
-        // With the current method all methods are kept and it adds about 100k to all outputs.
-        &[
-            app_o_file.to_str().unwrap(),
-            builtins_host_tempfile.path().to_str().unwrap(),
-        ],
-        LinkType::Dylib,
-    )
-    .expect("failed to link dynamic library");
-
-    child.wait().unwrap();
-
-    // Extend the lifetime of the tempfile so it doesn't get dropped
-    // (and thus deleted) before the linking process is done using it!
-    let _ = builtins_host_tempfile;
-
-    // Load the dylib
-    let path = dylib_path.as_path().to_str().unwrap();
-
-    // std::fs::copy(&path, "/tmp/libapp.so").unwrap();
-
-    let lib = unsafe { Library::new(path) }.expect("failed to load shared library");
-
-    (main_fn_name, delayed_errors, lib)
+    (main_fn_name, delayed_errors, module_out)
 }
 
 #[allow(unused_macros)]
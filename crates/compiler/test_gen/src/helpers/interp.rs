@@ -0,0 +1,234 @@
+use roc_load::{
+    EntryPoint, ExecutionMode, LoadConfig, MonoDiagnostics, MonoOptimizations, Threading,
+};
+use roc_mono_interp::{Heap, Interpreter, Value};
+use roc_packaging::cache::RocCacheDir;
+use roc_std::{RocDec, RocList, RocStr};
+
+#[allow(dead_code)]
+fn promote_expr_to_module(src: &str) -> String {
+    let mut buffer = String::from("app \"test\" provides [main] to \"./platform\"\n\nmain =\n");
+
+    for line in src.lines() {
+        // indent the body!
+        buffer.push_str("    ");
+        buffer.push_str(line);
+        buffer.push('\n');
+    }
+
+    buffer
+}
+
+/// Run `main` in the mono IR interpreter, and convert its result.
+///
+/// With `leak` set, every allocation must have been freed once the result is dropped; unlike the
+/// native backends, the interpreter can check that exactly.
+#[allow(dead_code)]
+pub fn helper<T: FromInterpValue>(src: &str, leak: bool) -> Result<T, roc_mono_interp::Error> {
+    use std::path::PathBuf;
+
+    let arena = bumpalo::Bump::new();
+    let filename = PathBuf::from("Test.roc");
+    let src_dir = PathBuf::from("fake/test/path");
+
+    let module_src;
+    let temp;
+    if src.starts_with("app") {
+        // this is already a module
+        module_src = src;
+    } else {
+        // this is an expression, promote it to a module
+        temp = promote_expr_to_module(src);
+        module_src = &temp;
+    }
+
+    let load_config = LoadConfig {
+        target_info: roc_target::TargetInfo::default_x86_64(),
+        render: roc_reporting::report::RenderTarget::ColorTerminal,
+        palette: roc_reporting::report::DEFAULT_PALETTE,
+        threading: Threading::Single,
+        exec_mode: ExecutionMode::Executable,
+        mono_optimizations: MonoOptimizations::Enabled,
+        mono_diagnostics: MonoDiagnostics::default(),
+    };
+    let loaded = roc_load::load_and_monomorphize_from_str(
+        &arena,
+        filename,
+        module_src,
+        src_dir,
+        RocCacheDir::Disallowed,
+        load_config,
+    );
+
+    let loaded = loaded.expect("failed to load module");
+
+    let errors = loaded
+        .can_problems
+        .values()
+        .flatten()
+        .filter(|problem| {
+            use roc_problem::can::Problem::*;
+            !matches!(
+                problem,
+                UnusedDef(..) | UnusedArgument(..) | UnusedModuleImport(..)
+            )
+        })
+        .count()
+        + loaded.type_problems.values().map(Vec::len).sum::<usize>();
+    assert_eq!(errors, 0, "Mistakes were made");
+
+    let (symbol, layout) = match loaded.entry_point {
+        EntryPoint::Executable {
+            exposed_to_host, ..
+        } => {
+            debug_assert_eq!(exposed_to_host.len(), 1);
+            exposed_to_host[0]
+        }
+        EntryPoint::Test => unreachable!(),
+    };
+
+    let mut interpreter = Interpreter::new(&loaded.layout_interner, &loaded.procedures);
+    let value = interpreter.call(symbol, layout, Vec::new())?;
+
+    let result = T::from_interp_value(&interpreter.heap, &value);
+
+    interpreter.heap.dec(&value)?;
+    if leak {
+        let leaked: Vec<_> = interpreter.heap.live_cell_ids().collect();
+        assert!(
+            leaked.is_empty(),
+            "these allocations were leaked: {:?}",
+            leaked
+        );
+    }
+
+    Ok(result)
+}
+
+/// Conversion of an interpreter value to the Rust type the tests compare against
+pub trait FromInterpValue {
+    fn from_interp_value(heap: &Heap, value: &Value) -> Self;
+}
+
+macro_rules! from_interp_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromInterpValue for $ty {
+                fn from_interp_value(_heap: &Heap, value: &Value) -> Self {
+                    match value {
+                        Value::Int(n, _) => *n as $ty,
+                        other => panic!("expected an integer, but got {:?}", other),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_interp_int!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl FromInterpValue for f32 {
+    fn from_interp_value(_heap: &Heap, value: &Value) -> Self {
+        match value {
+            Value::Float(n, _) => *n as f32,
+            other => panic!("expected a float, but got {:?}", other),
+        }
+    }
+}
+
+impl FromInterpValue for f64 {
+    fn from_interp_value(_heap: &Heap, value: &Value) -> Self {
+        match value {
+            Value::Float(n, _) => *n,
+            other => panic!("expected a float, but got {:?}", other),
+        }
+    }
+}
+
+impl FromInterpValue for bool {
+    fn from_interp_value(_heap: &Heap, value: &Value) -> Self {
+        match value {
+            Value::Bool(b) => *b,
+            other => panic!("expected a Bool, but got {:?}", other),
+        }
+    }
+}
+
+impl FromInterpValue for RocDec {
+    fn from_interp_value(_heap: &Heap, value: &Value) -> Self {
+        match value {
+            Value::Dec(n) => RocDec::new(*n),
+            other => panic!("expected a Dec, but got {:?}", other),
+        }
+    }
+}
+
+impl FromInterpValue for RocStr {
+    fn from_interp_value(heap: &Heap, value: &Value) -> Self {
+        RocStr::from(heap.str(value).unwrap())
+    }
+}
+
+impl<T: FromInterpValue + Clone> FromInterpValue for RocList<T> {
+    fn from_interp_value(heap: &Heap, value: &Value) -> Self {
+        let elements: Vec<T> = heap
+            .list(value)
+            .unwrap()
+            .iter()
+            .map(|element| T::from_interp_value(heap, element))
+            .collect();
+
+        RocList::from_slice(&elements)
+    }
+}
+
+impl FromInterpValue for () {
+    fn from_interp_value(_heap: &Heap, _value: &Value) -> Self {}
+}
+
+macro_rules! from_interp_tuple {
+    ($($name:ident),*) => {
+        impl<$($name: FromInterpValue),*> FromInterpValue for ($($name,)*) {
+            fn from_interp_value(heap: &Heap, value: &Value) -> Self {
+                match value {
+                    Value::Struct(fields) => {
+                        let mut fields = fields.iter();
+                        ($($name::from_interp_value(heap, fields.next().unwrap()),)*)
+                    }
+                    other => panic!("expected a struct, but got {:?}", other),
+                }
+            }
+        }
+    };
+}
+
+from_interp_tuple!(A, B);
+from_interp_tuple!(A, B, C);
+from_interp_tuple!(A, B, C, D);
+
+#[allow(unused_macros)]
+macro_rules! assert_evals_to {
+    ($src:expr, $expected:expr, $ty:ty) => {{
+        assert_evals_to!($src, $expected, $ty, (|val| val));
+    }};
+    ($src:expr, $expected:expr, $ty:ty, $transform:expr) => {
+        // Same as above, except with an additional transformation argument.
+        {
+            assert_evals_to!($src, $expected, $ty, $transform, true);
+        }
+    };
+    ($src:expr, $expected:expr, $ty:ty, $transform:expr, $leak:expr) => {{
+        match $crate::helpers::interp::helper::<$ty>($src, $leak) {
+            Ok(success) => {
+                let expected = $expected;
+                #[allow(clippy::redundant_closure_call)]
+                let given = $transform(success);
+                assert_eq!(&given, &expected);
+            }
+            Err(error) => panic!("{}", error),
+        }
+    }};
+}
+
+#[allow(unused_imports)]
+pub(crate) use assert_evals_to;
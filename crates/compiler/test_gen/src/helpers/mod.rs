@@ -5,6 +5,8 @@ pub mod platform_functions;
 #[cfg(feature = "gen-dev")]
 pub mod dev;
 pub mod from_wasm32_memory;
+#[cfg(feature = "gen-interp")]
+pub mod interp;
 #[cfg(feature = "gen-llvm")]
 pub mod llvm;
 #[cfg(any(feature = "gen-wasm", feature = "gen-llvm-wasm"))]
@@ -4,11 +4,13 @@ use bumpalo::Bump;
 use roc_collections::all::MutSet;
 use roc_gen_wasm::wasm32_result::Wasm32Result;
 use roc_gen_wasm::DEBUG_SETTINGS;
-use roc_load::{ExecutionMode, LoadConfig, Threading};
+use roc_load::{ExecutionMode, LoadConfig, MonoDiagnostics, MonoOptimizations, Threading};
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::DEFAULT_PALETTE_HTML;
 use roc_std::RocStr;
 use roc_wasm_interp::{wasi, ImportDispatcher, Instance, WasiDispatcher};
+use roc_wasm_module::opcodes::{MiscOp, OpCode, SimdOp};
+use roc_wasm_module::parse::{Parse, SkipBytes};
 use roc_wasm_module::{Export, ExportType, Value, WasmModule};
 use std::marker::PhantomData;
 use std::path::PathBuf;
@@ -55,7 +57,7 @@ pub fn compile_to_wasm_bytes<'a, T: Wasm32Result>(
     let platform_bytes = include_bytes!(host_bytes_path!());
     println!("Loading test host {}", host_bytes_path!());
 
-    let compiled_bytes =
+    let (compiled_bytes, _) =
         compile_roc_to_wasm_bytes(arena, platform_bytes, src, test_wrapper_type_info);
 
     if write_final_wasm() {
@@ -71,7 +73,7 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
     host_bytes: &[u8],
     src: &str,
     _test_wrapper_type_info: PhantomData<T>,
-) -> Vec<u8> {
+) -> (Vec<u8>, u32) {
     let filename = PathBuf::from("Test.roc");
     let src_dir = PathBuf::from("fake/test/path");
 
@@ -92,6 +94,8 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
         palette: DEFAULT_PALETTE_HTML,
         threading: Threading::Single,
         exec_mode: ExecutionMode::Executable,
+        mono_optimizations: MonoOptimizations::Enabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -169,7 +173,59 @@ fn compile_roc_to_wasm_bytes<'a, T: Wasm32Result>(
     let mut app_module_bytes = std::vec::Vec::with_capacity(module.size());
     module.serialize(&mut app_module_bytes);
 
-    app_module_bytes
+    (app_module_bytes, main_fn_index)
+}
+
+/// A Wasm instruction, without its immediate arguments
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Op(OpCode),
+    Misc(MiscOp),
+    Simd(SimdOp),
+}
+
+/// Compile an expression and list the instructions in the body of `main`,
+/// so that tests can check which instructions the Wasm backend chose.
+#[allow(dead_code)]
+pub fn main_fn_instructions<T: Wasm32Result>(
+    src: &str,
+    phantom: PhantomData<T>,
+) -> std::vec::Vec<Instruction> {
+    let arena = Bump::new();
+    let platform_bytes = include_bytes!(host_bytes_path!());
+    let (wasm_bytes, main_fn_index) =
+        compile_roc_to_wasm_bytes(&arena, platform_bytes, src, phantom);
+
+    let require_relocatable = false;
+    let module = WasmModule::preload(&arena, &wasm_bytes, require_relocatable).unwrap();
+    let code_index = main_fn_index as usize - module.import.function_count();
+    let bytes = &module.code.bytes;
+    let mut cursor = module.code.function_offsets[code_index] as usize;
+
+    let fn_length = u32::parse((), bytes, &mut cursor).unwrap() as usize;
+    let fn_end = cursor + fn_length;
+    let local_groups = u32::parse((), bytes, &mut cursor).unwrap();
+    for _ in 0..local_groups {
+        u32::skip_bytes(bytes, &mut cursor).unwrap();
+        cursor += 1; // value type
+    }
+
+    let mut instructions = std::vec::Vec::new();
+    while cursor < fn_end {
+        let mut sub_opcode_cursor = cursor + 1;
+        let instruction = match OpCode::from(bytes[cursor]) {
+            OpCode::MISCPREFIX => {
+                Instruction::Misc(MiscOp::parse((), bytes, &mut sub_opcode_cursor).unwrap())
+            }
+            OpCode::SIMDPREFIX => {
+                Instruction::Simd(SimdOp::parse((), bytes, &mut sub_opcode_cursor).unwrap())
+            }
+            op => Instruction::Op(op),
+        };
+        instructions.push(instruction);
+        OpCode::skip_bytes(bytes, &mut cursor).unwrap();
+    }
+    instructions
 }
 
 #[allow(dead_code)]
@@ -6,6 +6,8 @@
 
 pub mod gen_abilities;
 pub mod gen_compare;
+#[cfg(feature = "gen-dev")]
+pub mod gen_dev_objects;
 pub mod gen_dict;
 pub mod gen_list;
 pub mod gen_num;
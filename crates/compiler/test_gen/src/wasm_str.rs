@@ -717,6 +717,39 @@ fn str_equality() {
     assert_evals_to!(r#""a" == "b""#, false, bool);
 }
 
+#[test]
+fn str_equality_simd() {
+    use crate::helpers::wasm::{main_fn_instructions, Instruction::Simd};
+    use roc_wasm_module::opcodes::SimdOp::{I8X16ALLTRUE, I8X16EQ};
+
+    let src = r#"Str.concat "loremipsum" "dolarsitamet" == "loremipsumdolarsitamet""#;
+    let instructions = main_fn_instructions(src, std::marker::PhantomData::<bool>);
+    assert!(instructions
+        .windows(2)
+        .any(|w| w == [Simd(I8X16EQ), Simd(I8X16ALLTRUE)]));
+    assert_evals_to!(src, true, bool);
+
+    // Same length, different in the last 16-byte chunk or in the bytes after it
+    assert_evals_to!(
+        r#""loremipsumdolarsitamet" == "loremipsumdolarsitamex""#,
+        false,
+        bool
+    );
+    assert_evals_to!(
+        r#""loremipsumdolarsitamet" == "loremipsumdolarxitamet""#,
+        false,
+        bool
+    );
+    assert_evals_to!(
+        r#""loremipsumdolarsitamet" != "loremipsumdolarsitametx""#,
+        true,
+        bool
+    );
+
+    // A big string and a small one with the same contents
+    assert_evals_to!(r#"Str.reserve "abc" 40 == "abc""#, true, bool);
+}
+
 #[test]
 fn str_join_comma_small() {
     assert_evals_to!(
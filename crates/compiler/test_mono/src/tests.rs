@@ -18,6 +18,8 @@ use roc_collections::all::MutMap;
 use roc_load::ExecutionMode;
 use roc_load::LoadConfig;
 use roc_load::LoadMonomorphizedError;
+use roc_load::MonoDiagnostics;
+use roc_load::MonoOptimizations;
 use roc_load::Threading;
 use roc_module::symbol::Interns;
 use roc_module::symbol::Symbol;
@@ -108,6 +110,8 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        mono_optimizations: MonoOptimizations::Disabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -154,6 +158,8 @@ fn compiles_to_ir(test_name: &str, src: &str, mode: &str, allow_type_errors: boo
         check_procedures(arena, &interns, &mut layout_interner, &procedures);
     }
 
+    check_text_round_trip(arena, &interns, &mut layout_interner, &procedures);
+
     verify_procedures(test_name, layout_interner, procedures, main_fn_symbol);
 }
 
@@ -163,13 +169,40 @@ fn check_procedures<'a>(
     interner: &mut STLayoutInterner<'a>,
     procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
 ) {
-    use roc_mono::debug::{check_procs, format_problems};
+    use roc_mono::debug::{check_procs, check_refcounts, format_problems};
     let problems = check_procs(arena, interner, procedures);
-    if problems.is_empty() {
-        return;
+    if !problems.is_empty() {
+        let formatted = format_problems(interns, interner, problems);
+        panic!("IR problems found:\n{formatted}");
+    }
+
+    let problems = check_refcounts(arena, interner, procedures);
+    if !problems.is_empty() {
+        let formatted = format_problems(interns, interner, problems);
+        panic!("Refcount problems found:\n{formatted}");
     }
-    let formatted = format_problems(interns, interner, problems);
-    panic!("IR problems found:\n{formatted}");
+}
+
+/// Printing the procedures in the textual format, parsing them back and printing them again must
+/// give the same text.
+fn check_text_round_trip<'a>(
+    arena: &'a Bump,
+    interns: &Interns,
+    interner: &mut STLayoutInterner<'a>,
+    procedures: &MutMap<(Symbol, ProcLayout<'a>), Proc<'a>>,
+) {
+    use roc_mono::ir::text::{parse_procs, procs_to_text};
+
+    let text = procs_to_text(&*interner, &interns.module_ids, procedures.values());
+
+    let mut module_ids = interns.module_ids.clone();
+    let parsed = match parse_procs(arena, interner, &mut module_ids, &text) {
+        Ok(parsed) => parsed,
+        Err(error) => panic!("could not parse the printed IR at {error}:\n{text}"),
+    };
+
+    let reprinted = procs_to_text(&*interner, &module_ids, &parsed);
+    assert_eq!(text, reprinted, "the IR did not survive a round trip");
 }
 
 fn verify_procedures<'a>(
@@ -2923,3 +2956,317 @@ fn error_on_erroneous_condition() {
         "#
     )
 }
+
+fn procs_by_name<'a>(
+    arena: &'a Bump,
+    procs: Vec<Proc<'a>>,
+) -> MutMap<(Symbol, ProcLayout<'a>), Proc<'a>> {
+    procs
+        .into_iter()
+        .map(|proc| {
+            let proc_layout = ProcLayout {
+                arguments: arena.alloc_slice_fill_iter(proc.args.iter().map(|(layout, _)| *layout)),
+                result: proc.ret_layout,
+                niche: proc.name.niche(),
+            };
+            ((proc.name.name(), proc_layout), proc)
+        })
+        .collect()
+}
+
+#[test]
+fn ir_text_hand_written() {
+    use roc_mono::debug::{check_procs, format_problems};
+    use roc_mono::ir::text::{parse_procs, procs_to_text};
+
+    let text = indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64, Test.3: I64) -> I64:
+            let Test.4 : U64 = lowlevel ListLen Test.2;
+            let Test.5 : U64 = 0i64;
+            let Test.6 : Bool = lowlevel Eq Test.4 Test.5;
+            dec Test.2;
+            joinpoint Test.7 (Test.8: I64):
+                ret Test.8;
+            in
+            switch Test.6 : Bool -> I64:
+                case 1:
+                    let Test.9 : I64 = 1i64;
+                    jump Test.7 Test.9;
+                default:
+                    jump Test.7 Test.3;
+        "#
+    );
+
+    let arena = &Bump::new();
+    let mut interner = STLayoutInterner::with_capacity(4, TARGET_INFO);
+    let mut interns = Interns::default();
+
+    let procs = parse_procs(arena, &mut interner, &mut interns.module_ids, text).unwrap();
+    assert_eq!(procs_to_text(&interner, &interns.module_ids, &procs), text);
+
+    let procedures = procs_by_name(arena, procs);
+
+    let problems = check_procs(arena, &mut interner, &procedures);
+    if !problems.is_empty() {
+        let formatted = format_problems(&interns, &interner, problems);
+        panic!("IR problems found:\n{formatted}");
+    }
+}
+
+/// Run the refcount checker on hand-written procs. The parsed symbols have no names, so the
+/// problems are described by ident index rather than formatted.
+fn refcount_problems(text: &str) -> Vec<String> {
+    use roc_mono::debug::{check_refcounts, ProblemKind, RefcountExit};
+    use roc_mono::ir::text::parse_procs;
+
+    let arena = &Bump::new();
+    let mut interner = STLayoutInterner::with_capacity(4, TARGET_INFO);
+    let mut interns = Interns::default();
+
+    let procs = parse_procs(arena, &mut interner, &mut interns.module_ids, text).unwrap();
+    let procedures = procs_by_name(arena, procs);
+
+    let problems = check_refcounts(arena, &mut interner, &procedures);
+    let name = |symbol: Symbol| format!("Test.{}", symbol.ident_id().index());
+
+    problems
+        .iter()
+        .map(|problem| match &problem.kind {
+            ProblemKind::RefcountImbalance {
+                symbol,
+                imbalance,
+                exit,
+                ..
+            } => {
+                let exit = match exit {
+                    RefcountExit::Return => "on return".to_string(),
+                    RefcountExit::LoopIteration(id) => format!("looping to {}", name(id.0)),
+                };
+                format!(
+                    "line {}: {} is off by {} {}",
+                    problem.line,
+                    name(*symbol),
+                    imbalance,
+                    exit
+                )
+            }
+            ProblemKind::UseAfterConsume { symbol, .. } => {
+                format!(
+                    "line {}: {} is used after it was consumed",
+                    problem.line,
+                    name(*symbol)
+                )
+            }
+            _ => format!("line {}: not a refcount problem", problem.line),
+        })
+        .collect()
+}
+
+#[test]
+fn refcount_checker_accepts_balanced_ir() {
+    let problems = refcount_problems(indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64, Test.3: U64) -> U64:
+            joinpoint Test.4 (Test.5: List I64, Test.6: U64):
+                let Test.7 : U64 = lowlevel ListLen Test.5;
+                let Test.8 : Bool = lowlevel NumGt Test.7 Test.6;
+                switch Test.8 : Bool -> U64:
+                    case 1:
+                        let Test.9 : U64 = 1i64;
+                        let Test.10 : U64 = lowlevel NumAdd Test.6 Test.9;
+                        jump Test.4 Test.5 Test.10;
+                    default:
+                        dec Test.5;
+                        ret Test.6;
+            in
+            inc Test.2;
+            let Test.11 : U64 = lowlevel ListLen Test.2;
+            dec Test.2;
+            jump Test.4 Test.2 Test.3;
+        "#
+    ));
+
+    assert_eq!(problems, Vec::<String>::new());
+}
+
+#[test]
+fn refcount_checker_reports_missing_dec() {
+    let problems = refcount_problems(indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64) -> U64:
+            let Test.3 : U64 = lowlevel ListLen Test.2;
+            ret Test.3;
+        "#
+    ));
+
+    assert_eq!(problems, ["line 2: Test.2 is off by 1 on return"]);
+}
+
+#[test]
+fn refcount_checker_reports_missing_dec_in_loop() {
+    let problems = refcount_problems(indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64, Test.3: U64) -> U64:
+            joinpoint Test.4 (Test.5: U64):
+                let Test.6 : Bool = lowlevel NumGt Test.5 Test.3;
+                switch Test.6 : Bool -> U64:
+                    case 1:
+                        dec Test.2;
+                        ret Test.5;
+                    default:
+                        inc Test.2;
+                        let Test.7 : U64 = lowlevel ListLen Test.2;
+                        let Test.8 : U64 = lowlevel NumAdd Test.5 Test.7;
+                        jump Test.4 Test.8;
+            in
+            let Test.9 : U64 = 0i64;
+            jump Test.4 Test.9;
+        "#
+    ));
+
+    assert_eq!(problems, ["line 9: Test.2 is off by 1 looping to Test.4"]);
+}
+
+#[test]
+fn refcount_checker_reports_double_dec() {
+    let problems = refcount_problems(indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64) -> U64:
+            let Test.3 : U64 = lowlevel ListLen Test.2;
+            dec Test.2;
+            dec Test.2;
+            ret Test.3;
+        "#
+    ));
+
+    assert_eq!(problems, ["line 4: Test.2 is off by -1 on return"]);
+}
+
+#[test]
+fn refcount_checker_reports_use_after_dec() {
+    let problems = refcount_problems(indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64) -> U64:
+            dec Test.2;
+            let Test.3 : U64 = lowlevel ListLen Test.2;
+            ret Test.3;
+        "#
+    ));
+
+    assert_eq!(problems, ["line 2: Test.2 is used after it was consumed"]);
+}
+
+#[test]
+fn stack_allocate_non_escaping() {
+    use roc_mono::ir::text::{parse_procs, procs_to_text};
+    use roc_mono::stack_allocation::stack_allocate_procs;
+
+    let text = indoc!(
+        r#"
+        procedure Test.1 (Test.2: I64) -> Box I64:
+            let Test.3 : Box I64 = Box Test.2;
+            let Test.4 : I64 = Unbox Test.3;
+            dec Test.3;
+            let Test.5 : Box I64 = Box Test.4;
+            ret Test.5;
+
+        procedure Test.6 (Test.7: Str) -> U64:
+            inc Test.7;
+            let Test.8 : List Str = Array Str [Test.7, Test.7];
+            let Test.9 : U64 = lowlevel ListLen Test.8;
+            dec Test.8;
+            ret Test.9;
+        "#
+    );
+
+    let arena = &Bump::new();
+    let mut interner = STLayoutInterner::with_capacity(4, TARGET_INFO);
+    let mut interns = Interns::default();
+
+    let procs = parse_procs(arena, &mut interner, &mut interns.module_ids, text).unwrap();
+    let mut procedures = procs_by_name(arena, procs);
+
+    stack_allocate_procs(arena, &interner, &mut procedures);
+
+    let mut procs: Vec<_> = procedures.values().collect();
+    procs.sort_by_key(|proc| proc.name.name());
+
+    // the first box is returned, and the list releases its strings when it is dropped
+    assert_eq!(
+        procs_to_text(&interner, &interns.module_ids, procs),
+        indoc!(
+            r#"
+            procedure Test.1 (Test.2: I64) -> Box I64 stack [Test.3]:
+                let Test.3 : Box I64 = Box Test.2;
+                let Test.4 : I64 = Unbox Test.3;
+                let Test.5 : Box I64 = Box Test.4;
+                ret Test.5;
+
+            procedure Test.6 (Test.7: Str) -> U64 stack [Test.8]:
+                inc Test.7;
+                let Test.8 : List Str = Array Str [Test.7, Test.7];
+                let Test.9 : U64 = lowlevel ListLen Test.8;
+                dec Test.7;
+                dec Test.7;
+                ret Test.9;
+            "#
+        )
+    );
+}
+
+#[test]
+fn missed_update_of_shared_list() {
+    use roc_mono::ir::text::parse_procs;
+    use roc_mono::ir::{ModifyRc, Stmt, SymbolRegions};
+    use roc_mono::missed_updates::find_missed_updates;
+    use roc_problem::mono::Problem;
+    use roc_region::all::{Position, Region};
+
+    let text = indoc!(
+        r#"
+        procedure Test.1 (Test.2: List I64, Test.3: I64) -> U64:
+            inc Test.2;
+            let Test.4 : List I64 = lowlevel ListAppendUnsafe Test.2 Test.3;
+            dec Test.4;
+            let Test.5 : U64 = lowlevel ListLen Test.2;
+            dec Test.2;
+            ret Test.5;
+        "#
+    );
+
+    let arena = &Bump::new();
+    let mut interner = STLayoutInterner::with_capacity(4, TARGET_INFO);
+    let mut interns = Interns::default();
+
+    let procs = parse_procs(arena, &mut interner, &mut interns.module_ids, text).unwrap();
+
+    let (list, appended, len) = match &procs[0].body {
+        Stmt::Refcounting(
+            ModifyRc::Inc(list, _),
+            Stmt::Let(appended, _, _, Stmt::Refcounting(_, Stmt::Let(len, ..))),
+        ) => (*list, *appended, *len),
+        other => panic!("unexpected body {other:?}"),
+    };
+
+    let append_region = Region::new(Position::new(10), Position::new(30));
+    let len_region = Region::new(Position::new(40), Position::new(55));
+
+    let mut symbol_regions = SymbolRegions::default();
+    symbol_regions.insert(appended, append_region);
+    symbol_regions.insert(len, len_region);
+
+    let procedures = procs_by_name(arena, procs);
+    let problems = find_missed_updates(&interner, &procedures, &symbol_regions);
+
+    // the list is incremented before the append because its length is taken afterwards
+    assert_eq!(
+        problems.get(&appended.module_id()),
+        Some(&vec![Problem::SharedUpdate {
+            region: append_region,
+            function: None,
+            value: list,
+            later_use: Some(len_region),
+        }])
+    );
+}
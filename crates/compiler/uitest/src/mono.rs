@@ -2,7 +2,10 @@ use std::io;
 
 use bumpalo::Bump;
 use roc_collections::MutMap;
-use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
+use roc_load::{
+    ExecutionMode, LoadConfig, LoadMonomorphizedError, MonoDiagnostics, MonoOptimizations,
+    Threading,
+};
 use roc_module::symbol::{Interns, Symbol};
 use roc_mono::{
     ir::{Proc, ProcLayout},
@@ -45,6 +48,8 @@ pub fn write_compiled_ir<'a>(
         render: roc_reporting::report::RenderTarget::Generic,
         palette: roc_reporting::report::DEFAULT_PALETTE,
         exec_mode,
+        mono_optimizations: MonoOptimizations::Disabled,
+        mono_diagnostics: MonoDiagnostics::default(),
     };
     let loaded = roc_load::load_and_monomorphize_from_str(
         arena,
@@ -255,6 +255,42 @@ generateStruct = \buf, types, id, name, structFields, visibility ->
     |> Str.concat "#[repr(\(repr))]\n\(pub)struct \(escapedName) {\n"
     |> generateStructFields types Public structFields
     |> Str.concat "}\n\n"
+    |> generateStructLayoutChecks types id escapedName structFields
+
+# Have rustc check that the struct has the layout Roc expects, so that glue which
+# has drifted out of date with its platform fails to build instead of crashing.
+generateStructLayoutChecks = \buf, types, id, escapedName, structFields ->
+    when structFields is
+        HasNoClosure fields ->
+            sizeOfSelf = Num.toStr (Types.size types id)
+            alignOfSelf = Num.toStr (Types.alignment types id)
+
+            # Fields are already sorted the way Roc lays them out, so each one goes
+            # at the next offset that satisfies its alignment.
+            offsetChecks =
+                List.walk fields { offset: 0, checks: "" } \state, { name: fieldName, id: fieldId } ->
+                    fieldAlignment = Types.alignment types fieldId
+                    fieldOffset =
+                        if fieldAlignment == 0 then
+                            state.offset
+                        else
+                            nextMultipleOf state.offset fieldAlignment
+                    offsetStr = Num.toStr fieldOffset
+                    escapedFieldName = escapeKW fieldName
+
+                    {
+                        offset: fieldOffset + Types.size types fieldId,
+                        checks: Str.concat state.checks "\(indent)assert!(glue_offset_of!(\(escapedName), \(escapedFieldName)) == \(offsetStr));\n",
+                    }
+                |> .checks
+
+            buf
+            |> Str.concat (layoutCheck escapedName sizeOfSelf alignOfSelf offsetChecks)
+            |> Str.concat "\n\n"
+
+        HasClosure _ ->
+            # Closures are opaque to the host, so their layout is only known to the application.
+            buf
 
 generateStructFields = \buf, types, visibility, structFields ->
     when structFields is
@@ -292,6 +328,7 @@ generateEnumeration = \buf, types, enumType, name, tags, tagBytes ->
     escapedName = escapeKW name
 
     reprBits = tagBytes * 8 |> Num.toStr
+    tagBytesStr = Num.toStr tagBytes
 
     buf
     |> generateDeriveStr types enumType ExcludeDebug
@@ -310,6 +347,8 @@ generateEnumeration = \buf, types, enumType, name, tags, tagBytes ->
         """
     |> \b -> List.walk tags b (generateEnumTagsDebug name)
     |> Str.concat "\(indent)\(indent)}\n\(indent)}\n}\n\n"
+    |> Str.concat (layoutCheck escapedName tagBytesStr tagBytesStr "")
+    |> Str.concat "\n\n"
 
 generateEnumTags = \accum, index, name ->
     indexStr = Num.toStr index
@@ -601,8 +640,11 @@ generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, di
         |> nextMultipleOf alignOfUnion
         |> Num.toStr
 
+    unionLayoutCheck = layoutCheck unionName sizeOfUnionStr alignOfUnionStr ""
+
     sizeOfSelf = Num.toStr (Types.size types id)
     alignOfSelf = Num.toStr (Types.alignment types id)
+    selfLayoutCheck = layoutCheck escapedName sizeOfSelf alignOfSelf ""
 
     # TODO: this value can be different than the alignment of `id`
     align =
@@ -620,11 +662,9 @@ generateNonRecursiveTagUnion = \buf, types, id, name, tags, discriminantSize, di
         """
         }
 
-        const _SIZE_CHECK_\(unionName): () = assert!(core::mem::size_of::<\(unionName)>() == \(sizeOfUnionStr));
-        const _ALIGN_CHECK_\(unionName): () = assert!(core::mem::align_of::<\(unionName)>() == \(alignOfUnionStr));
+        \(unionLayoutCheck)
 
-        const _SIZE_CHECK_\(escapedName): () = assert!(core::mem::size_of::<\(escapedName)>() == \(sizeOfSelf));
-        const _ALIGN_CHECK_\(escapedName): () = assert!(core::mem::align_of::<\(escapedName)>() == \(alignOfSelf));
+        \(selfLayoutCheck)
 
         impl \(escapedName) {
             \(discriminantDocComment)
@@ -983,6 +1023,7 @@ generateRecursiveTagUnion = \buf, types, id, tagUnionName, tags, discriminantSiz
 
     sizeOfSelf = Num.toStr (Types.size types id)
     alignOfSelf = Num.toStr (Types.alignment types id)
+    selfLayoutCheck = layoutCheck escapedName sizeOfSelf alignOfSelf ""
 
     buf
     |> generateDiscriminant types discriminantName tagNames discriminantSize
@@ -991,8 +1032,7 @@ generateRecursiveTagUnion = \buf, types, id, tagUnionName, tags, discriminantSiz
         #[repr(transparent)]
         pub struct \(escapedName)(*mut \(unionName));
 
-        const _SIZE_CHECK_\(escapedName): () = assert!(core::mem::size_of::<\(escapedName)>() == \(sizeOfSelf));
-        const _ALIGN_CHECK_\(escapedName): () = assert!(core::mem::align_of::<\(escapedName)>() == \(alignOfSelf));
+        \(selfLayoutCheck)
 
         impl \(escapedName) {
             pub fn discriminant(&self) -> discriminant_\(escapedName) {
@@ -1277,6 +1317,7 @@ generateNullableUnwrapped = \buf, types, tagUnionid, name, nullTag, nonNullTag,
 
     sizeOfSelf = Num.toStr (Types.size types tagUnionid)
     alignOfSelf = Num.toStr (Types.alignment types tagUnionid)
+    selfLayoutCheck = layoutCheck name sizeOfSelf alignOfSelf ""
 
     """
     \(buf)
@@ -1287,8 +1328,7 @@ generateNullableUnwrapped = \buf, types, tagUnionid, name, nullTag, nonNullTag,
 
     \(discriminant)
 
-    const _SIZE_CHECK_\(name): () = assert!(core::mem::size_of::<\(name)>() == \(sizeOfSelf));
-    const _ALIGN_CHECK_\(name): () = assert!(core::mem::align_of::<\(name)>() == \(alignOfSelf));
+    \(selfLayoutCheck)
 
     impl \(name) {
         pub fn \(nullTag)() -> Self {
@@ -1922,6 +1962,19 @@ fileHeader =
     #![allow(clippy::needless_borrow)]
     #![allow(clippy::clone_on_copy)]
 
+    /// The byte offset of a field within a struct, usable in the layout checks below.
+    macro_rules! glue_offset_of {
+        ($ty:ty, $field:tt) => {{
+            let uninit = core::mem::MaybeUninit::<$ty>::uninit();
+            let base = uninit.as_ptr();
+
+            unsafe {
+                (core::ptr::addr_of!((*base).$field) as *const u8).offset_from(base as *const u8)
+                    as usize
+            }
+        }};
+    }
+
 
 
     """
@@ -1992,6 +2045,22 @@ escapeKW = \input ->
     else
         input
 
+# The same `_LAYOUT_CHECK_` block that rust_glue.rs emits. Raw identifiers can't be
+# spliced into another identifier, so the `r#` is dropped from the const's name.
+layoutCheck = \name, size, align, offsetChecks ->
+    checkName =
+        if Str.startsWith name "r#" then
+            Str.replaceFirst name "r#" "" |> Result.withDefault name
+        else
+            name
+
+    """
+    const _LAYOUT_CHECK_\(checkName): () = {
+    \(indent)assert!(core::mem::size_of::<\(name)>() == \(size));
+    \(indent)assert!(core::mem::align_of::<\(name)>() == \(align));
+    \(offsetChecks)};
+    """
+
 nextMultipleOf = \lhs, rhs ->
     when lhs % rhs is
         0 -> lhs
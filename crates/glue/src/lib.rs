@@ -1,6 +1,6 @@
 //! Generates code needed for platform hosts to communicate with Roc apps.
 //! This tool is not necessary for writing a platform in another language,
-//! however, it's a great convenience! Currently supports Rust platforms, and
+//! however, it's a great convenience! Currently supports Rust and Zig platforms, and
 //! the plan is to support any language via a plugin model.
 pub mod enums;
 pub mod load;
@@ -8,11 +8,14 @@ pub mod roc_type;
 pub mod rust_glue;
 pub mod structs;
 pub mod types;
+pub mod verify;
+pub mod zig_glue;
 
 #[rustfmt::skip]
 pub mod glue;
 
 pub use load::generate;
+pub use verify::verify;
 
 // required because we use roc_std here
 mod roc_externs {
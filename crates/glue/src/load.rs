@@ -1,5 +1,6 @@
 use crate::roc_type;
 use crate::types::Types;
+use crate::verify::{layout_manifest, MANIFEST_FILE_NAME};
 use bumpalo::Bump;
 use libloading::Library;
 use roc_build::{
@@ -10,7 +11,11 @@ use roc_build::{
     },
 };
 use roc_collections::MutMap;
-use roc_load::{ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, Threading};
+use roc_gen_llvm::llvm::sanitize::Sanitizers;
+use roc_load::{
+    ExecutionMode, LoadConfig, LoadedModule, LoadingProblem, MonoDiagnostics, MonoOptimizations,
+    Threading,
+};
 use roc_mono::ir::{generate_glue_procs, GlueProc, OptLevel};
 use roc_mono::layout::{GlobalLayoutInterner, LayoutCache, LayoutInterner};
 use roc_packaging::cache::{self, RocCacheDir};
@@ -30,7 +35,7 @@ pub struct IgnoreErrors {
 }
 
 impl IgnoreErrors {
-    const NONE: Self = IgnoreErrors { can: false };
+    pub const NONE: Self = IgnoreErrors { can: false };
 }
 
 pub fn generate(
@@ -54,21 +59,22 @@ pub fn generate(
                 backend,
                 opt_level: OptLevel::Development,
                 emit_debug_info: false,
+                profile_alloc: false,
+                report_missed_updates: false,
+                sanitizers: Sanitizers::default(),
             };
 
             let load_config = standard_load_config(
                 &triple,
                 BuildOrdering::BuildIfChecks,
                 Threading::AllAvailable,
+                &code_gen_options,
             );
 
             let arena = ManuallyDrop::new(Bump::new());
             let link_type = LinkType::Dylib;
-            let linking_strategy = if roc_linker::supported(link_type, &triple) {
-                LinkingStrategy::Surgical
-            } else {
-                LinkingStrategy::Legacy
-            };
+            // A glue spec has no host to link into: the app itself is the shared library we load.
+            let linking_strategy = LinkingStrategy::Legacy;
 
             let res_binary_path = build_file(
                 &arena,
@@ -184,6 +190,19 @@ pub fn generate(
                         });
                     }
 
+                    // Record the layouts this glue was generated against,
+                    // so that `roc glue --verify` can tell when it goes stale.
+                    let manifest_path = output_path.join(MANIFEST_FILE_NAME);
+                    std::fs::write(&manifest_path, layout_manifest(&types)).unwrap_or_else(|err| {
+                        eprintln!(
+                            "Unable to write layout manifest {} - {:?}",
+                            manifest_path.display(),
+                            err
+                        );
+
+                        process::exit(1);
+                    });
+
                     println!(
                         "🎉 Generated type declarations in:\n\n\t{}",
                         output_path.display()
@@ -354,6 +373,8 @@ pub fn load_types(
             palette: DEFAULT_PALETTE,
             threading,
             exec_mode: ExecutionMode::Check,
+            mono_optimizations: MonoOptimizations::Disabled,
+            mono_diagnostics: MonoDiagnostics::default(),
         },
     )
     .unwrap_or_else(|problem| match problem {
@@ -391,14 +412,15 @@ pub fn load_types(
     let architectures = Architecture::iter();
     let mut arch_types = Vec::with_capacity(architectures.len());
 
-    let layout_interner = GlobalLayoutInterner::with_capacity(128, target_info);
-
     for architecture in architectures {
         let mut interns = interns.clone(); // TODO there may be a way to avoid this.
         let target_info = TargetInfo {
             architecture,
             operating_system,
         };
+        // Layouts depend on the target (e.g. its pointer width), so each architecture
+        // needs its own interner rather than sharing the host's.
+        let layout_interner = GlobalLayoutInterner::with_capacity(128, target_info);
         let mut layout_cache = LayoutCache::new(layout_interner.fork(), target_info);
         let mut glue_procs_by_layout = MutMap::default();
 
@@ -2,6 +2,7 @@ use crate::types::{
     Accessors, File, RocFn, RocNum, RocSingleTagPayload, RocStructFields, RocTagUnion, RocType,
     TypeId, Types,
 };
+use crate::verify::expected_layout;
 use indexmap::IndexMap;
 use roc_target::{Architecture, TargetInfo};
 use std::fmt::{Display, Write};
@@ -67,6 +68,7 @@ pub fn emit(types: &[Types]) -> Vec<File> {
     for types in types {
         for id in types.sorted_ids() {
             add_type(types.target(), id, types, &mut impls);
+            add_layout_check(types.target(), id, types, &mut impls);
         }
     }
 
@@ -144,6 +146,45 @@ pub fn emit(types: &[Types]) -> Vec<File> {
     }]
 }
 
+/// Have rustc check that the generated type has the layout Roc expects, so that glue
+/// which has drifted out of date with its platform fails to build instead of crashing.
+fn add_layout_check(target_info: TargetInfo, id: TypeId, types: &Types, impls: &mut Impls) {
+    let layout = match expected_layout(id, types) {
+        Some(layout) => layout,
+        None => return,
+    };
+    let name = type_name(id, types);
+    let check_name = name.trim_start_matches("r#");
+    let mut buf = format!("const _LAYOUT_CHECK_{check_name}: () = {{\n");
+
+    writeln!(
+        buf,
+        "{INDENT}assert!(core::mem::size_of::<{name}>() == {});",
+        layout.size
+    )
+    .unwrap();
+    writeln!(
+        buf,
+        "{INDENT}assert!(core::mem::align_of::<{name}>() == {});",
+        layout.align
+    )
+    .unwrap();
+
+    for (label, offset) in layout.field_offsets {
+        let label = escape_kw(label);
+
+        writeln!(
+            buf,
+            "{INDENT}assert!(glue_offset_of!({name}, {label}) == {offset});"
+        )
+        .unwrap();
+    }
+
+    buf.push_str("};");
+
+    add_decl(impls, None, target_info, buf);
+}
+
 fn add_type(target_info: TargetInfo, id: TypeId, types: &Types, impls: &mut Impls) {
     match types.get_type(id) {
         RocType::Struct { name, fields } => {
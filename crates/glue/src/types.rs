@@ -2291,9 +2291,29 @@ fn tag_to_type<'a, D: Display>(
             // create a RocType for the payload and save it
             let struct_name = format!("{}_{}", &name, tag_name); // e.g. "MyUnion_MyVariant"
             let fields = payload_vars.iter().copied().enumerate();
-            let struct_id = add_struct(env, struct_name, fields, types, layout, |name, fields| {
-                RocType::TagUnionPayload { name, fields }
-            });
+
+            // The payload is laid out like a struct of its fields, so that's the layout
+            // its size and alignment must come from (rather than the whole tag union's).
+            let field_layouts = bumpalo::collections::Vec::from_iter_in(
+                payload_vars.iter().map(|var| {
+                    env.layout_cache
+                        .from_var(env.arena, *var, env.subs)
+                        .expect("Something weird ended up in the content")
+                }),
+                env.arena,
+            );
+            let payload_layout = env.layout_cache.put_in(Layout::struct_no_name_order(
+                field_layouts.into_bump_slice(),
+            ));
+
+            let struct_id = add_struct(
+                env,
+                struct_name,
+                fields,
+                types,
+                payload_layout,
+                |name, fields| RocType::TagUnionPayload { name, fields },
+            );
 
             (tag_name, Some(struct_id))
         }
@@ -0,0 +1,270 @@
+//! Detects glue that has drifted out of date with the platform it was generated from.
+//!
+//! Whenever `roc glue` writes glue code, it also writes a manifest of the size, alignment,
+//! and field offsets Roc expects each generated type to have on every target.
+//! `roc glue --verify` re-derives those layouts from the platform as it is now,
+//! and reports any differences from the manifest.
+use crate::load::{load_types, IgnoreErrors};
+use crate::types::{RocSingleTagPayload, RocStructFields, RocTagUnion, RocType, TypeId, Types};
+use roc_collections::VecMap;
+use roc_load::Threading;
+use std::fmt::Write;
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::process;
+
+/// The name of the manifest `roc glue` writes next to the generated glue.
+pub const MANIFEST_FILE_NAME: &str = "roc-glue-layouts.txt";
+
+/// The layout Roc expects a generated type to have on one target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedLayout {
+    pub size: u32,
+    pub align: u32,
+    /// Field names are the ones the generated code uses (e.g. tag payload fields are `f0`, `f1`, ...),
+    /// in the order Roc lays them out.
+    pub field_offsets: Vec<(String, u32)>,
+}
+
+/// The layout of the given type, if glue generates a declaration for it.
+pub fn expected_layout(id: TypeId, types: &Types) -> Option<ExpectedLayout> {
+    let field_offsets = match types.get_type(id) {
+        RocType::Struct {
+            fields: RocStructFields::HasNoClosure { fields },
+            ..
+        } => field_offsets(
+            fields
+                .iter()
+                .map(|(label, type_id)| (label.clone(), *type_id)),
+            types,
+        ),
+        RocType::TagUnionPayload {
+            fields: RocStructFields::HasNoClosure { fields },
+            ..
+        } => field_offsets(
+            fields
+                .iter()
+                .map(|(label, type_id)| (format!("f{label}"), *type_id)),
+            types,
+        ),
+        RocType::TagUnion(RocTagUnion::SingleTagStruct {
+            payload: RocSingleTagPayload::HasNoClosure { payload_fields },
+            ..
+        }) => field_offsets(
+            payload_fields
+                .iter()
+                .enumerate()
+                .map(|(index, type_id)| (format!("f{index}"), *type_id)),
+            types,
+        ),
+        RocType::TagUnion(RocTagUnion::NonRecursive { tags, .. })
+        | RocType::TagUnion(RocTagUnion::Recursive { tags, .. })
+            if !tags.is_empty() =>
+        {
+            Vec::new()
+        }
+        RocType::TagUnion(RocTagUnion::Enumeration { .. })
+        | RocType::TagUnion(RocTagUnion::NullableWrapped { .. })
+        | RocType::TagUnion(RocTagUnion::NullableUnwrapped { .. })
+        | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { .. }) => Vec::new(),
+        // Closures are opaque to the host, so their layout is only known to the application.
+        RocType::Struct { .. }
+        | RocType::TagUnionPayload { .. }
+        | RocType::TagUnion(RocTagUnion::SingleTagStruct { .. })
+        | RocType::Function(_) => return None,
+        // These either get no declaration, or come from the glue's header.
+        RocType::TagUnion(RocTagUnion::NonRecursive { .. })
+        | RocType::TagUnion(RocTagUnion::Recursive { .. })
+        | RocType::Unit
+        | RocType::EmptyTagUnion
+        | RocType::Num(_)
+        | RocType::Bool
+        | RocType::RocResult(_, _)
+        | RocType::RocStr
+        | RocType::RocDict(_, _)
+        | RocType::RocSet(_)
+        | RocType::RocList(_)
+        | RocType::RocBox(_)
+        | RocType::Unsized
+        | RocType::RecursivePointer(_) => return None,
+    };
+
+    Some(ExpectedLayout {
+        size: types.size_rounded_to_alignment(id),
+        align: types.align(id),
+        field_offsets,
+    })
+}
+
+/// Fields are already sorted the way Roc lays them out, so each one goes at the next
+/// offset that satisfies its alignment.
+fn field_offsets(
+    fields: impl Iterator<Item = (String, TypeId)>,
+    types: &Types,
+) -> Vec<(String, u32)> {
+    let mut offset = 0;
+
+    fields
+        .map(|(label, type_id)| {
+            let align = types.align(type_id);
+            let field_offset = round_up_to_alignment(offset, align);
+
+            offset = field_offset + types.size_rounded_to_alignment(type_id);
+
+            (label, field_offset)
+        })
+        .collect()
+}
+
+fn round_up_to_alignment(offset: u32, align: u32) -> u32 {
+    match align {
+        0 | 1 => offset,
+        _ => (offset + align - 1) / align * align,
+    }
+}
+
+fn type_name(id: TypeId, types: &Types) -> Option<&str> {
+    match types.get_type(id) {
+        RocType::Struct { name, .. }
+        | RocType::TagUnionPayload { name, .. }
+        | RocType::TagUnion(RocTagUnion::NonRecursive { name, .. })
+        | RocType::TagUnion(RocTagUnion::Recursive { name, .. })
+        | RocType::TagUnion(RocTagUnion::Enumeration { name, .. })
+        | RocType::TagUnion(RocTagUnion::NullableWrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::NullableUnwrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::SingleTagStruct { name, .. }) => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+/// One line per generated type per target, e.g.
+///
+/// X86_64 MyRcd size=24 align=8 b@0 a@16
+pub fn layout_manifest(types_by_target: &[Types]) -> String {
+    let mut buf = String::new();
+
+    for types in types_by_target {
+        let architecture = types.target().architecture;
+
+        for id in types.sorted_ids() {
+            if let (Some(name), Some(layout)) = (type_name(id, types), expected_layout(id, types)) {
+                write!(
+                    buf,
+                    "{architecture:?} {name} size={} align={}",
+                    layout.size, layout.align
+                )
+                .unwrap();
+
+                for (label, offset) in layout.field_offsets {
+                    write!(buf, " {label}@{offset}").unwrap();
+                }
+
+                buf.push('\n');
+            }
+        }
+    }
+
+    buf
+}
+
+/// Describe every way the `actual` manifest differs from the `expected` one.
+pub fn diff_manifests(expected: &str, actual: &str) -> Vec<String> {
+    fn parse(manifest: &str) -> VecMap<&str, &str> {
+        manifest
+            .lines()
+            .filter_map(|line| {
+                // The key is the target and the type name; the rest is its layout.
+                let mut spaces = line.match_indices(' ').map(|(index, _)| index);
+                let _target_end = spaces.next()?;
+
+                match spaces.next() {
+                    Some(key_end) => Some((&line[..key_end], &line[key_end + 1..])),
+                    None => Some((line, "")),
+                }
+            })
+            .collect()
+    }
+
+    let expected = parse(expected);
+    let actual = parse(actual);
+    let mut problems = Vec::new();
+
+    for (key, expected_layout) in expected.iter() {
+        match actual.get(key) {
+            Some(actual_layout) if actual_layout == expected_layout => {}
+            Some(actual_layout) => problems.push(format!(
+                "{key} changed: the glue has `{expected_layout}`, but the platform now has `{actual_layout}`"
+            )),
+            None => problems.push(format!(
+                "{key} is in the glue, but no longer in the platform"
+            )),
+        }
+    }
+
+    for (key, _) in actual.iter() {
+        if !expected.contains_key(key) {
+            problems.push(format!(
+                "{key} is in the platform, but missing from the glue"
+            ));
+        }
+    }
+
+    problems
+}
+
+/// Check the glue previously generated into `output_path` against the platform at `input_path`.
+pub fn verify(input_path: &Path, output_path: &Path) -> io::Result<i32> {
+    let manifest_path = output_path.join(MANIFEST_FILE_NAME);
+    let expected = match std::fs::read_to_string(&manifest_path) {
+        Ok(manifest) => manifest,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            eprintln!(
+                "No layout manifest found at {}. Regenerate the glue with `roc glue` to create one.",
+                manifest_path.display()
+            );
+
+            return Ok(1);
+        }
+        Err(err) => return Err(err),
+    };
+
+    let types = match load_types(
+        input_path.to_path_buf(),
+        Threading::AllAvailable,
+        IgnoreErrors::NONE,
+    ) {
+        Ok(types) => types,
+        Err(err) if err.kind() == ErrorKind::NotFound => {
+            eprintln!("Platform module file not found: {}", input_path.display());
+            process::exit(1);
+        }
+        Err(err) => return Err(err),
+    };
+
+    let problems = diff_manifests(&expected, &layout_manifest(&types));
+
+    if problems.is_empty() {
+        println!(
+            "The glue in {} is up to date with {}.",
+            output_path.display(),
+            input_path.display()
+        );
+
+        Ok(0)
+    } else {
+        eprintln!(
+            "The glue in {} is out of date with {}:\n",
+            output_path.display(),
+            input_path.display()
+        );
+
+        for problem in problems {
+            eprintln!("    {problem}");
+        }
+
+        eprintln!("\nRegenerate it with `roc glue`.");
+
+        Ok(1)
+    }
+}
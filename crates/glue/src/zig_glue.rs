@@ -0,0 +1,1125 @@
+use crate::types::{
+    File, RocFn, RocNum, RocSingleTagPayload, RocStructFields, RocTagUnion, RocType, TypeId, Types,
+};
+use crate::verify::expected_layout;
+use indexmap::IndexMap;
+use roc_mono::layout::UnionLayout;
+use roc_target::{Architecture, PtrWidth, TargetInfo};
+use std::fmt::Write;
+
+pub static HEADER: &[u8] = include_bytes!("../templates/header.zig");
+const INDENT: &str = "    ";
+const DISCRIMINANT_DOC_COMMENT: &str =
+    "/// Returns which variant this tag union holds. Note that this never includes a payload!";
+
+/// Every top-level declaration, by name, along with each distinct body it has
+/// and the targets which use that body.
+type Decls = IndexMap<String, IndexMap<String, Vec<TargetInfo>>>;
+
+/// Add the given declaration body, along with the architecture, to the Decls.
+fn add_decl(decls: &mut Decls, name: String, target_info: TargetInfo, body: String) {
+    let bodies = decls.entry(name).or_default();
+    let targets = bodies.entry(body).or_default();
+
+    targets.push(target_info);
+}
+
+pub fn emit(types: &[Types]) -> Vec<File> {
+    let mut buf = std::str::from_utf8(HEADER).unwrap().to_string();
+    let mut decls: Decls = IndexMap::default();
+    let mut layout_checks: Decls = IndexMap::default();
+
+    for types in types {
+        for id in types.sorted_ids() {
+            add_type(types.target(), id, types, &mut decls);
+            add_layout_check(types.target(), id, types, &mut layout_checks);
+        }
+    }
+
+    for (name, bodies) in decls {
+        buf.push('\n');
+
+        let opt_checks = layout_checks.get(&name).cloned();
+
+        match bodies.get_index(0) {
+            Some((body, targets)) if bodies.len() == 1 && targets.len() == types.len() => {
+                // Every target agrees on this declaration, so there's no need to
+                // switch on the architecture.
+                writeln!(buf, "pub const {name} = {body};").unwrap();
+            }
+            _ => {
+                writeln!(buf, "pub const {name} = switch (builtin.cpu.arch) {{").unwrap();
+
+                for (body, targets) in bodies {
+                    let arches = targets
+                        .iter()
+                        .map(|target_info| format!(".{}", arch_to_str(target_info.architecture)))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+
+                    writeln!(buf, "{INDENT}{arches} => {},", indent_body(&body)).unwrap();
+                }
+
+                writeln!(
+                    buf,
+                    "{INDENT}else => @compileError(\"{name} is not supported on this architecture\"),"
+                )
+                .unwrap();
+                buf.push_str("};\n");
+            }
+        }
+
+        if let Some(checks) = opt_checks {
+            emit_layout_checks(&mut buf, checks, types.len());
+        }
+    }
+
+    vec![File {
+        name: "glue.zig".to_string(),
+        content: buf,
+    }]
+}
+
+/// Have the Zig compiler check that the generated type has the layout Roc expects, so that
+/// glue which has drifted out of date with its platform fails to build instead of crashing.
+fn add_layout_check(target_info: TargetInfo, id: TypeId, types: &Types, layout_checks: &mut Decls) {
+    let layout = match expected_layout(id, types) {
+        Some(layout) => layout,
+        None => return,
+    };
+    let name = type_name(id, types);
+    let mut buf = format!("assertLayout({name}, {}, {});", layout.size, layout.align);
+
+    for (label, offset) in layout.field_offsets {
+        write!(buf, "\nassertOffset({name}, \"{label}\", {offset});").unwrap();
+    }
+
+    add_decl(layout_checks, name, target_info, buf);
+}
+
+fn emit_layout_checks(
+    buf: &mut String,
+    checks: IndexMap<String, Vec<TargetInfo>>,
+    num_targets: usize,
+) {
+    let indent_lines = |lines: &str, depth: usize| {
+        lines
+            .lines()
+            .map(|line| format!("{}{line}\n", INDENT.repeat(depth)))
+            .collect::<String>()
+    };
+
+    buf.push_str("\ncomptime {\n");
+
+    match checks.get_index(0) {
+        Some((lines, targets)) if checks.len() == 1 && targets.len() == num_targets => {
+            buf.push_str(&indent_lines(lines, 1));
+        }
+        _ => {
+            writeln!(buf, "{INDENT}switch (builtin.cpu.arch) {{").unwrap();
+
+            for (lines, targets) in checks {
+                let arches = targets
+                    .iter()
+                    .map(|target_info| format!(".{}", arch_to_str(target_info.architecture)))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                writeln!(buf, "{INDENT}{INDENT}{arches} => {{").unwrap();
+                buf.push_str(&indent_lines(&lines, 3));
+                writeln!(buf, "{INDENT}{INDENT}}},").unwrap();
+            }
+
+            writeln!(buf, "{INDENT}{INDENT}else => {{}},").unwrap();
+            writeln!(buf, "{INDENT}}}").unwrap();
+        }
+    }
+
+    buf.push_str("}\n");
+}
+
+fn add_type(target_info: TargetInfo, id: TypeId, types: &Types, decls: &mut Decls) {
+    match types.get_type(id) {
+        RocType::Struct { name, fields } => {
+            add_struct(name, target_info, fields, types, decls, false)
+        }
+        RocType::TagUnionPayload { name, fields } => {
+            add_struct(name, target_info, fields, types, decls, true)
+        }
+        RocType::TagUnion(tag_union) => {
+            match tag_union {
+                RocTagUnion::Enumeration { tags, name, size } => {
+                    add_enumeration(name, target_info, tags, *size, decls)
+                }
+                RocTagUnion::NonRecursive {
+                    tags,
+                    name,
+                    discriminant_size,
+                    discriminant_offset,
+                } => {
+                    // Empty tag unions can never come up at runtime,
+                    // and so don't need declared types.
+                    if !tags.is_empty() {
+                        add_tag_union(
+                            name,
+                            target_info,
+                            id,
+                            tags,
+                            *discriminant_size,
+                            *discriminant_offset,
+                            types,
+                            decls,
+                        );
+                    }
+                }
+                RocTagUnion::Recursive {
+                    tags,
+                    name,
+                    discriminant_size,
+                    discriminant_offset,
+                } => {
+                    if !tags.is_empty() {
+                        let tag_id_in_pointer =
+                            UnionLayout::stores_tag_id_in_pointer_bits(tags.len(), target_info);
+
+                        add_recursive_tag_union(
+                            name,
+                            target_info,
+                            tags,
+                            None,
+                            *discriminant_size,
+                            *discriminant_offset,
+                            tag_id_in_pointer,
+                            types,
+                            decls,
+                        );
+                    }
+                }
+                RocTagUnion::NullableWrapped {
+                    name,
+                    index_of_null_tag,
+                    tags,
+                    discriminant_size,
+                    discriminant_offset,
+                } => {
+                    // The null tag is represented by the null pointer, so it doesn't
+                    // count towards the tags which need a tag id.
+                    let tag_id_in_pointer =
+                        UnionLayout::stores_tag_id_in_pointer_bits(tags.len() - 1, target_info);
+
+                    add_recursive_tag_union(
+                        name,
+                        target_info,
+                        tags,
+                        Some(*index_of_null_tag as usize),
+                        *discriminant_size,
+                        *discriminant_offset,
+                        tag_id_in_pointer,
+                        types,
+                        decls,
+                    );
+                }
+                RocTagUnion::NullableUnwrapped {
+                    name,
+                    null_tag,
+                    non_null_tag,
+                    non_null_payload,
+                    null_represents_first_tag: _,
+                } => add_nullable_unwrapped(
+                    name,
+                    target_info,
+                    null_tag,
+                    non_null_tag,
+                    *non_null_payload,
+                    types,
+                    decls,
+                ),
+                RocTagUnion::SingleTagStruct {
+                    name,
+                    tag_name,
+                    payload,
+                } => add_single_tag_struct(name, target_info, tag_name, payload, types, decls),
+                RocTagUnion::NonNullableUnwrapped {
+                    name,
+                    tag_name,
+                    payload,
+                } => {
+                    add_recursive_tag_union(
+                        name,
+                        target_info,
+                        &[(tag_name.clone(), Some(*payload))],
+                        None,
+                        0,
+                        0,
+                        false,
+                        types,
+                        decls,
+                    );
+                }
+            }
+        }
+        // These types don't need to be declared in Zig; the header covers them.
+        RocType::Unit
+        | RocType::EmptyTagUnion
+        | RocType::Num(_)
+        | RocType::Bool
+        | RocType::RocResult(_, _)
+        | RocType::RocStr
+        | RocType::RocDict(_, _)
+        | RocType::RocSet(_)
+        | RocType::RocList(_)
+        | RocType::RocBox(_)
+        | RocType::Unsized => {}
+        RocType::RecursivePointer { .. } => {
+            // This is recursively pointing to a type that should already have been added,
+            // so no extra work needs to happen.
+        }
+        RocType::Function(roc_fn) => add_function(target_info, roc_fn, types, decls),
+    }
+}
+
+fn add_struct(
+    name: &str,
+    target_info: TargetInfo,
+    fields: &RocStructFields,
+    types: &Types,
+    decls: &mut Decls,
+    is_tag_union_payload: bool,
+) {
+    // Tag union payloads have numbered fields, so we prefix them with an "f"
+    // (just like the Rust glue does) rather than writing @"0" everywhere.
+    let field_label = |label: &str| {
+        if is_tag_union_payload {
+            format!("f{label}")
+        } else {
+            escape_kw(label.to_string())
+        }
+    };
+
+    let body = match fields {
+        RocStructFields::HasNoClosure { fields } => {
+            let fields = fields
+                .iter()
+                .map(|(label, type_id)| (field_label(label), *type_id));
+
+            extern_struct(fields, types)
+        }
+        RocStructFields::HasClosure { fields } => {
+            let getters = fields.iter().map(|(label, type_id, accessors)| {
+                (field_label(label), *type_id, accessors.getter.as_str())
+            });
+
+            opaque_with_getters(getters, types)
+        }
+    };
+
+    add_decl(decls, escape_kw(name.to_string()), target_info, body);
+}
+
+fn extern_struct(fields: impl ExactSizeIterator<Item = (String, TypeId)>, types: &Types) -> String {
+    if fields.len() == 0 {
+        return "extern struct {}".to_string();
+    }
+
+    let mut buf = "extern struct {\n".to_string();
+
+    for (label, type_id) in fields {
+        writeln!(buf, "{INDENT}{label}: {},", type_name(type_id, types)).unwrap();
+    }
+
+    buf.push('}');
+
+    buf
+}
+
+/// If a struct contains a closure, its size and field order depend on the application,
+/// so the host can't know them at build time. Instead, expose it as an opaque type
+/// whose fields are read through the getters the application provides.
+fn opaque_with_getters<'a>(
+    getters: impl Iterator<Item = (String, TypeId, &'a str)>,
+    types: &Types,
+) -> String {
+    let mut buf = format!("opaque {{\n{INDENT}const Self = @This();\n");
+
+    for (label, type_id, getter_name) in getters {
+        let ret = type_name(type_id, types);
+        let fn_name = format!("get{}", upper_first(label.trim_start_matches('@')));
+
+        buf.push('\n');
+
+        if let RocType::Function(_) = types.get_type(type_id) {
+            write!(
+                buf,
+                r#"    extern fn {getter_name}_size() usize;
+    extern fn {getter_name}_generic([*]u8, *const Self) void;
+
+    pub fn {fn_name}(self: *const Self) {ret} {{
+        // Closures are variably-sized, so ask the application how much room this one needs.
+        const size = {getter_name}_size();
+        const bytes = allocateWithRefcount(size, @alignOf(usize));
+
+        {getter_name}_generic(bytes, self);
+
+        return {ret}{{ .closure_data = RocList(u8){{ .elements = bytes, .length = size, .capacity_or_ref_ptr = size }} }};
+    }}
+"#
+            )
+            .unwrap();
+        } else {
+            write!(
+                buf,
+                r#"    extern fn {getter_name}_generic(*{ret}, *const Self) void;
+
+    pub fn {fn_name}(self: *const Self) {ret} {{
+        var answer: {ret} = undefined;
+
+        {getter_name}_generic(&answer, self);
+
+        return answer;
+    }}
+"#
+            )
+            .unwrap();
+        }
+    }
+
+    buf.push('}');
+
+    buf
+}
+
+fn add_enumeration<S: AsRef<str>>(
+    name: &str,
+    target_info: TargetInfo,
+    tags: &[S],
+    tag_bytes: u32,
+    decls: &mut Decls,
+) {
+    let repr_bits = tag_bytes * 8;
+
+    // e.g. "enum(u8) {\n"
+    let mut buf = format!("enum(u{repr_bits}) {{\n");
+
+    for (index, tag_name) in tags.iter().enumerate() {
+        writeln!(
+            buf,
+            "{INDENT}{} = {index},",
+            escape_kw(tag_name.as_ref().to_string())
+        )
+        .unwrap();
+    }
+
+    buf.push('}');
+
+    add_decl(decls, escape_kw(name.to_string()), target_info, buf);
+}
+
+fn add_discriminant(
+    name: &str,
+    target_info: TargetInfo,
+    tags: &[(String, Option<TypeId>)],
+    size: u32,
+    decls: &mut Decls,
+) -> String {
+    // The tag union's discriminant, e.g.
+    //
+    // pub const discriminant_MyTagUnion = enum(u8) {
+    //     Bar = 0,
+    //     Foo = 1,
+    // };
+    let discriminant_name = format!("discriminant_{name}");
+    let tag_names: Vec<&str> = tags.iter().map(|(tag_name, _)| tag_name.as_str()).collect();
+
+    add_enumeration(&discriminant_name, target_info, &tag_names, size, decls);
+
+    discriminant_name
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_tag_union(
+    name: &str,
+    target_info: TargetInfo,
+    type_id: TypeId,
+    tags: &[(String, Option<TypeId>)],
+    discriminant_size: u32,
+    discriminant_offset: u32,
+    types: &Types,
+    decls: &mut Decls,
+) {
+    let name = escape_kw(name.to_string());
+    let discriminant_name = add_discriminant(&name, target_info, tags, discriminant_size, decls);
+    let size_rounded_to_alignment = types.size_rounded_to_alignment(type_id);
+
+    // Roc places the discriminant right after the largest payload, which may well be
+    // inside that payload's trailing alignment padding. So rather than a struct of
+    // a union and a discriminant (which could come out larger than Roc's layout),
+    // the whole thing is a union, and the discriminant is written at its offset.
+    let mut buf = "extern union {\n".to_string();
+
+    for (tag_name, opt_payload_id) in tags {
+        if let Some(payload_id) = opt_payload_id {
+            writeln!(
+                buf,
+                "{INDENT}{tag_name}: {},",
+                type_name(*payload_id, types)
+            )
+            .unwrap();
+        }
+    }
+
+    write!(
+        buf,
+        r#"    _sizer: [{size_rounded_to_alignment}]u8,
+
+    const Self = @This();
+
+    {DISCRIMINANT_DOC_COMMENT}
+    pub fn discriminant(self: Self) {discriminant_name} {{
+        const bytes = @ptrCast([*]const u8, &self);
+
+        return @ptrCast(*align(1) const {discriminant_name}, bytes + {discriminant_offset}).*;
+    }}
+
+    /// Internal helper
+    fn setDiscriminant(self: *Self, tag: {discriminant_name}) void {{
+        const bytes = @ptrCast([*]u8, self);
+
+        @ptrCast(*align(1) {discriminant_name}, bytes + {discriminant_offset}).* = tag;
+    }}
+"#
+    )
+    .unwrap();
+
+    for (tag_name, opt_payload_id) in tags {
+        match opt_payload_id {
+            Some(payload_id) => {
+                let payload_type = type_name(*payload_id, types);
+
+                write!(
+                    buf,
+                    r#"
+    /// Construct a tag named `{tag_name}`, with the appropriate payload
+    pub fn init{tag_name}(payload: {payload_type}) Self {{
+        var answer = Self{{ .{tag_name} = payload }};
+
+        answer.setDiscriminant(.{tag_name});
+
+        return answer;
+    }}
+
+    /// Unsafely assume this `{name}` has a `.discriminant()` of `{tag_name}` and return its payload.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    pub fn get{tag_name}(self: Self) {payload_type} {{
+        std.debug.assert(self.discriminant() == .{tag_name});
+
+        return self.{tag_name};
+    }}
+"#
+                )
+                .unwrap();
+            }
+            None => {
+                write!(
+                    buf,
+                    r#"
+    /// Construct a tag named `{tag_name}`, which has no payload.
+    pub fn init{tag_name}() Self {{
+        var answer = Self{{ ._sizer = [_]u8{{0}} ** {size_rounded_to_alignment} }};
+
+        answer.setDiscriminant(.{tag_name});
+
+        return answer;
+    }}
+"#
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    buf.push('}');
+
+    add_decl(decls, name, target_info, buf);
+}
+
+/// Recursive tag unions are a pointer to a refcounted heap cell holding the payload.
+/// Depending on the number of tags, the discriminant is either stored in the unused
+/// low bits of that pointer (see `UnionLayout::stores_tag_id_in_pointer`) or right
+/// after the payload inside the heap cell.
+#[allow(clippy::too_many_arguments)]
+fn add_recursive_tag_union(
+    name: &str,
+    target_info: TargetInfo,
+    tags: &[(String, Option<TypeId>)],
+    null_tag_index: Option<usize>, // used only in the nullable-wrapped case
+    discriminant_size: u32,
+    discriminant_offset: u32,
+    tag_id_in_pointer: bool,
+    types: &Types,
+    decls: &mut Decls,
+) {
+    let name = escape_kw(name.to_string());
+    let union_name = format!("union_{name}");
+    let opt_discriminant_name = if discriminant_size > 0 {
+        Some(add_discriminant(
+            &name,
+            target_info,
+            tags,
+            discriminant_size,
+            decls,
+        ))
+    } else {
+        None
+    };
+
+    // The heap cell the pointer points to
+    {
+        let mut buf = "extern union {\n".to_string();
+
+        for (tag_name, opt_payload_id) in tags {
+            if let Some(payload_id) = opt_payload_id {
+                writeln!(
+                    buf,
+                    "{INDENT}{tag_name}: {},",
+                    type_name(*payload_id, types)
+                )
+                .unwrap();
+            }
+        }
+
+        if opt_discriminant_name.is_some() && !tag_id_in_pointer {
+            // Make room for the discriminant after the largest payload.
+            let cell_size = discriminant_offset + discriminant_size;
+
+            writeln!(buf, "{INDENT}_sizer: [{cell_size}]u8,").unwrap();
+        }
+
+        buf.push('}');
+
+        add_decl(decls, union_name.clone(), target_info, buf);
+    }
+
+    let mut buf = format!(
+        "extern struct {{\n{INDENT}pointer: ?*anyopaque,\n\n{INDENT}const Self = @This();\n"
+    );
+
+    if tag_id_in_pointer {
+        writeln!(
+            buf,
+            "{INDENT}const tag_mask: usize = {:#b};",
+            tagged_pointer_bitmask(target_info)
+        )
+        .unwrap();
+    }
+
+    let untagged_pointer = if tag_id_in_pointer {
+        "@ptrToInt(self.pointer) & ~tag_mask"
+    } else {
+        "@ptrToInt(self.pointer)"
+    };
+
+    write!(
+        buf,
+        r#"
+    /// Internal helper
+    fn unionPointer(self: Self) *{union_name} {{
+        return @intToPtr(*{union_name}, {untagged_pointer});
+    }}
+"#
+    )
+    .unwrap();
+
+    if let Some(discriminant_name) = &opt_discriminant_name {
+        let repr_bits = discriminant_size * 8;
+        let null_check = match null_tag_index {
+            Some(index) => {
+                let null_tag = &tags[index].0;
+
+                format!(
+                    r#"if (self.pointer == null) {{
+            return .{null_tag};
+        }}
+
+        "#
+                )
+            }
+            None => String::new(),
+        };
+
+        if tag_id_in_pointer {
+            write!(
+                buf,
+                r#"
+    {DISCRIMINANT_DOC_COMMENT}
+    pub fn discriminant(self: Self) {discriminant_name} {{
+        {null_check}// The discriminant is stored in the unused bits at the end of the pointer
+        return @intToEnum({discriminant_name}, @truncate(u{repr_bits}, @ptrToInt(self.pointer) & tag_mask));
+    }}
+
+    /// Internal helper
+    fn fromCell(cell: *{union_name}, tag: {discriminant_name}) Self {{
+        return Self{{ .pointer = @intToPtr(*anyopaque, @ptrToInt(cell) | @enumToInt(tag)) }};
+    }}
+"#
+            )
+            .unwrap();
+        } else {
+            write!(
+                buf,
+                r#"
+    {DISCRIMINANT_DOC_COMMENT}
+    pub fn discriminant(self: Self) {discriminant_name} {{
+        {null_check}// There are too many tags to fit in the pointer, so the discriminant is in the heap cell
+        const bytes = @ptrCast([*]const u8, self.unionPointer());
+
+        return @ptrCast(*align(1) const {discriminant_name}, bytes + {discriminant_offset}).*;
+    }}
+
+    /// Internal helper
+    fn fromCell(cell: *{union_name}, tag: {discriminant_name}) Self {{
+        const bytes = @ptrCast([*]u8, cell);
+
+        @ptrCast(*align(1) {discriminant_name}, bytes + {discriminant_offset}).* = tag;
+
+        return Self{{ .pointer = cell }};
+    }}
+"#
+            )
+            .unwrap();
+        }
+    }
+
+    for (tag_index, (tag_name, opt_payload_id)) in tags.iter().enumerate() {
+        let from_cell = if opt_discriminant_name.is_some() {
+            format!("Self.fromCell(cell, .{tag_name})")
+        } else {
+            "Self{ .pointer = cell }".to_string()
+        };
+
+        match opt_payload_id {
+            Some(payload_id) => {
+                let payload_type = type_name(*payload_id, types);
+
+                let getter_doc = if opt_discriminant_name.is_some() {
+                    format!(
+                        r#"/// Unsafely assume this `{name}` has a `.discriminant()` of `{tag_name}` and return its payload.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    pub fn get{tag_name}(self: Self) {payload_type} {{
+        std.debug.assert(self.discriminant() == .{tag_name});
+"#
+                    )
+                } else {
+                    format!(
+                        r#"/// Since `{name}` only has one tag (namely, `{tag_name}`),
+    /// return `{tag_name}`'s payload.
+    pub fn get{tag_name}(self: Self) {payload_type} {{"#
+                    )
+                };
+
+                write!(
+                    buf,
+                    r#"
+    /// Construct a tag named `{tag_name}`, with the appropriate payload
+    pub fn init{tag_name}(payload: {payload_type}) Self {{
+        const cell = allocateRefcounted({union_name});
+
+        cell.* = {union_name}{{ .{tag_name} = payload }};
+
+        return {from_cell};
+    }}
+
+    {getter_doc}
+        return self.unionPointer().{tag_name};
+    }}
+"#
+                )
+                .unwrap();
+            }
+            None if Some(tag_index) == null_tag_index => {
+                // The null tag index only occurs for nullable-wrapped tag unions,
+                // and it always has no payload.
+                write!(
+                    buf,
+                    r#"
+    /// Construct a tag named `{tag_name}`, which has no payload.
+    pub fn init{tag_name}() Self {{
+        return Self{{ .pointer = null }};
+    }}
+"#
+                )
+                .unwrap();
+            }
+            None => {
+                write!(
+                    buf,
+                    r#"
+    /// Construct a tag named `{tag_name}`, which has no payload.
+    pub fn init{tag_name}() Self {{
+        const cell = allocateRefcounted({union_name});
+
+        return {from_cell};
+    }}
+"#
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    buf.push('}');
+
+    add_decl(decls, name, target_info, buf);
+}
+
+fn add_nullable_unwrapped(
+    name: &str,
+    target_info: TargetInfo,
+    null_tag: &str,
+    non_null_tag: &str,
+    non_null_payload: TypeId,
+    types: &Types,
+    decls: &mut Decls,
+) {
+    let name = escape_kw(name.to_string());
+    let mut tags = vec![
+        (null_tag.to_string(), None),
+        (non_null_tag.to_string(), None),
+    ];
+
+    tags.sort();
+
+    let discriminant_name = add_discriminant(&name, target_info, &tags, 1, decls);
+    let payload_type = type_name(non_null_payload, types);
+
+    let body = format!(
+        r#"extern struct {{
+    pointer: ?*{payload_type},
+
+    const Self = @This();
+
+    {DISCRIMINANT_DOC_COMMENT}
+    pub fn discriminant(self: Self) {discriminant_name} {{
+        if (self.pointer == null) {{
+            return .{null_tag};
+        }} else {{
+            return .{non_null_tag};
+        }}
+    }}
+
+    /// Construct a tag named `{null_tag}`, which has no payload.
+    pub fn init{null_tag}() Self {{
+        return Self{{ .pointer = null }};
+    }}
+
+    /// Construct a tag named `{non_null_tag}`, with the appropriate payload
+    pub fn init{non_null_tag}(payload: {payload_type}) Self {{
+        const cell = allocateRefcounted({payload_type});
+
+        cell.* = payload;
+
+        return Self{{ .pointer = cell }};
+    }}
+
+    /// Unsafely assume this `{name}` has a `.discriminant()` of `{non_null_tag}` and return its payload.
+    /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+    pub fn get{non_null_tag}(self: Self) {payload_type} {{
+        std.debug.assert(self.discriminant() == .{non_null_tag});
+
+        return self.pointer.?.*;
+    }}
+}}"#
+    );
+
+    add_decl(decls, name, target_info, body);
+}
+
+fn add_single_tag_struct(
+    name: &str,
+    target_info: TargetInfo,
+    tag_name: &str,
+    payload: &RocSingleTagPayload,
+    types: &Types,
+    decls: &mut Decls,
+) {
+    // Store single-tag unions as structs rather than unions,
+    // because they have only one alternative.
+    let body = match payload {
+        RocSingleTagPayload::HasNoClosure { payload_fields } => {
+            let mut buf = "extern struct {\n".to_string();
+            let mut args = Vec::with_capacity(payload_fields.len());
+            let mut inits = Vec::with_capacity(payload_fields.len());
+
+            for (index, field_id) in payload_fields.iter().enumerate() {
+                let field_type = type_name(*field_id, types);
+
+                writeln!(buf, "{INDENT}f{index}: {field_type},").unwrap();
+                args.push(format!("f{index}: {field_type}"));
+                inits.push(format!(".f{index} = f{index}"));
+            }
+
+            if !payload_fields.is_empty() {
+                buf.push('\n');
+            }
+
+            let (doc, init) = if inits.is_empty() {
+                ("which has no payload", "Self{}".to_string())
+            } else {
+                (
+                    "with the given payload",
+                    format!("Self{{ {} }}", inits.join(", ")),
+                )
+            };
+            let args = args.join(", ");
+
+            write!(
+                buf,
+                r#"    const Self = @This();
+
+    /// A tag named `{tag_name}`, {doc}.
+    pub fn init{tag_name}({args}) Self {{
+        return {init};
+    }}
+}}"#
+            )
+            .unwrap();
+
+            buf
+        }
+        RocSingleTagPayload::HasClosure { payload_getters } => {
+            let getters = payload_getters
+                .iter()
+                .enumerate()
+                .map(|(index, (type_id, getter))| (index.to_string(), *type_id, getter.as_str()));
+
+            opaque_with_getters(getters, types)
+        }
+    };
+
+    add_decl(decls, escape_kw(name.to_string()), target_info, body);
+}
+
+fn add_function(target_info: TargetInfo, roc_fn: &RocFn, types: &Types, decls: &mut Decls) {
+    let name = escape_kw(roc_fn.function_name.to_string());
+    let extern_name = &roc_fn.extern_name;
+    let lambda_set_type = type_name(roc_fn.lambda_set, types);
+    let return_type = type_name(roc_fn.ret, types);
+
+    let mut extern_args = String::new();
+    let mut args = String::new();
+    let mut call_args = String::new();
+
+    for (i, argument_type) in roc_fn.args.iter().enumerate() {
+        let argument_type = type_name(*argument_type, types);
+
+        write!(extern_args, "*const {argument_type}, ").unwrap();
+        write!(args, ", arg_{i}: {argument_type}").unwrap();
+        write!(call_args, "&arg_{i}, ").unwrap();
+    }
+
+    // fn extern_name(arg1: *const arg1_type, ..., closure_data: ?[*]u8, output: *return_type);
+    let body = format!(
+        r#"extern struct {{
+    closure_data: {lambda_set_type},
+
+    extern fn {extern_name}({extern_args}?[*]u8, *{return_type}) void;
+
+    pub fn forceThunk(self: @This(){args}) {return_type} {{
+        var output: {return_type} = undefined;
+
+        {extern_name}({call_args}self.closure_data.elements, &output);
+
+        return output;
+    }}
+}}"#
+    );
+
+    add_decl(decls, name, target_info, body);
+}
+
+fn type_name(id: TypeId, types: &Types) -> String {
+    match types.get_type(id) {
+        RocType::Unit => "RocUnit".to_string(),
+        RocType::EmptyTagUnion => "RocEmptyTagUnion".to_string(),
+        RocType::RocStr => "RocStr".to_string(),
+        RocType::Bool => "bool".to_string(),
+        RocType::Num(RocNum::U8) => "u8".to_string(),
+        RocType::Num(RocNum::U16) => "u16".to_string(),
+        RocType::Num(RocNum::U32) => "u32".to_string(),
+        RocType::Num(RocNum::U64) => "u64".to_string(),
+        RocType::Num(RocNum::U128) => "u128".to_string(),
+        RocType::Num(RocNum::I8) => "i8".to_string(),
+        RocType::Num(RocNum::I16) => "i16".to_string(),
+        RocType::Num(RocNum::I32) => "i32".to_string(),
+        RocType::Num(RocNum::I64) => "i64".to_string(),
+        RocType::Num(RocNum::I128) => "i128".to_string(),
+        RocType::Num(RocNum::F32) => "f32".to_string(),
+        RocType::Num(RocNum::F64) => "f64".to_string(),
+        RocType::Num(RocNum::Dec) => "RocDec".to_string(),
+        RocType::RocDict(key_id, val_id) => format!(
+            "RocDict({}, {})",
+            type_name(*key_id, types),
+            type_name(*val_id, types)
+        ),
+        RocType::RocSet(elem_id) => format!("RocSet({})", type_name(*elem_id, types)),
+        RocType::RocList(elem_id) => format!("RocList({})", type_name(*elem_id, types)),
+        RocType::RocBox(elem_id) => format!("RocBox({})", type_name(*elem_id, types)),
+        RocType::Unsized => "RocList(u8)".to_string(),
+        RocType::RocResult(ok_id, err_id) => {
+            format!(
+                "RocResult({}, {})",
+                type_name(*ok_id, types),
+                type_name(*err_id, types)
+            )
+        }
+        RocType::Struct { name, .. }
+        | RocType::TagUnionPayload { name, .. }
+        | RocType::TagUnion(RocTagUnion::NonRecursive { name, .. })
+        | RocType::TagUnion(RocTagUnion::Recursive { name, .. })
+        | RocType::TagUnion(RocTagUnion::Enumeration { name, .. })
+        | RocType::TagUnion(RocTagUnion::NullableWrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::NullableUnwrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::NonNullableUnwrapped { name, .. })
+        | RocType::TagUnion(RocTagUnion::SingleTagStruct { name, .. }) => escape_kw(name.clone()),
+        RocType::RecursivePointer(content) => type_name(*content, types),
+        RocType::Function(RocFn { function_name, .. }) => escape_kw(function_name.clone()),
+    }
+}
+
+/// The names `builtin.cpu.arch` uses for each architecture (as of Zig 0.9)
+fn arch_to_str(architecture: Architecture) -> &'static str {
+    match architecture {
+        Architecture::X86_64 => "x86_64",
+        Architecture::X86_32 => "i386",
+        Architecture::Aarch64 => "aarch64",
+        Architecture::Aarch32 => "arm",
+        Architecture::Wasm32 => "wasm32",
+    }
+}
+
+fn tagged_pointer_bitmask(target_info: TargetInfo) -> u8 {
+    match target_info.ptr_width() {
+        // On a 64-bit system, pointers have 3 bits that are unused
+        PtrWidth::Bytes8 => 0b0000_0111,
+        // On a 32-bit system, pointers have 2 bits that are unused
+        PtrWidth::Bytes4 => 0b0000_0011,
+    }
+}
+
+/// Indent every line after the first, so a multi-line body can be nested in a `switch` arm.
+fn indent_body(body: &str) -> String {
+    let mut lines = body.lines();
+    let mut buf = lines.next().unwrap_or_default().to_string();
+
+    for line in lines {
+        buf.push('\n');
+
+        if !line.is_empty() {
+            buf.push_str(INDENT);
+            buf.push_str(line);
+        }
+    }
+
+    buf
+}
+
+fn upper_first(input: &str) -> String {
+    let mut chars = input.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// Based on https://ziglang.org/documentation/0.9.1/#Keyword-Reference
+// plus the primitive types, which can't be shadowed either.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "addrspace",
+    "align",
+    "allowzero",
+    "and",
+    "anyframe",
+    "anytype",
+    "asm",
+    "async",
+    "await",
+    "break",
+    "callconv",
+    "catch",
+    "comptime",
+    "const",
+    "continue",
+    "defer",
+    "else",
+    "enum",
+    "errdefer",
+    "error",
+    "export",
+    "extern",
+    "fn",
+    "for",
+    "if",
+    "inline",
+    "linksection",
+    "noalias",
+    "noinline",
+    "nosuspend",
+    "opaque",
+    "or",
+    "orelse",
+    "packed",
+    "pub",
+    "resume",
+    "return",
+    "struct",
+    "suspend",
+    "switch",
+    "test",
+    "threadlocal",
+    "try",
+    "union",
+    "unreachable",
+    "usingnamespace",
+    "var",
+    "volatile",
+    "while",
+    "anyerror",
+    "anyopaque",
+    "bool",
+    "f16",
+    "f32",
+    "f64",
+    "f80",
+    "f128",
+    "i8",
+    "i16",
+    "i32",
+    "i64",
+    "i128",
+    "isize",
+    "noreturn",
+    "type",
+    "u8",
+    "u16",
+    "u32",
+    "u64",
+    "u128",
+    "usize",
+    "void",
+    "null",
+    "true",
+    "false",
+    "undefined",
+];
+
+/// Escape a Zig reserved keyword, if necessary.
+fn escape_kw(input: String) -> String {
+    let is_reserved_keyword = RESERVED_KEYWORDS.contains(&input.as_str());
+
+    if is_reserved_keyword {
+        // https://ziglang.org/documentation/0.9.1/#Identifiers
+        format!("@\"{input}\"")
+    } else {
+        input
+    }
+}
@@ -17,6 +17,19 @@
 #![allow(clippy::needless_borrow)]
 #![allow(clippy::clone_on_copy)]
 
+/// The byte offset of a field within a struct, usable in the layout checks below.
+macro_rules! glue_offset_of {
+    ($ty:ty, $field:tt) => {{
+        let uninit = core::mem::MaybeUninit::<$ty>::uninit();
+        let base = uninit.as_ptr();
+
+        unsafe {
+            (core::ptr::addr_of!((*base).$field) as *const u8).offset_from(base as *const u8)
+                as usize
+        }
+    }};
+}
+
 type Op_StderrWrite = roc_std::RocStr;
 type Op_StdoutWrite = roc_std::RocStr;
 type TODO_roc_function_69 = roc_std::RocStr;
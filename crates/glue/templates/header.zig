@@ -0,0 +1,236 @@
+// ⚠️ GENERATED CODE ⚠️ - this entire file was generated by the `roc glue` CLI command
+
+const std = @import("std");
+const builtin = @import("builtin");
+
+// This is provided by the host, just like it is for the Roc application itself.
+extern fn roc_alloc(size: usize, alignment: u32) callconv(.C) ?*anyopaque;
+
+/// The refcount of a uniquely-owned allocation.
+pub const REFCOUNT_ONE: isize = std.math.minInt(isize);
+
+const SEAMLESS_SLICE_BIT: usize = @bitCast(usize, @as(isize, std.math.minInt(isize)));
+
+/// Allocate room for `data_bytes` bytes preceded by a refcount of one,
+/// following the same memory layout as the Roc builtins.
+pub fn allocateWithRefcount(data_bytes: usize, element_alignment: u32) [*]u8 {
+    const ptr_width = @sizeOf(usize);
+    const alignment = std.math.max(ptr_width, element_alignment);
+
+    const new_bytes = roc_alloc(alignment + data_bytes, alignment) orelse @panic("roc_alloc returned null");
+    const data_ptr = @ptrCast([*]u8, new_bytes) + alignment;
+    const refcount_ptr = @ptrCast([*]isize, @alignCast(ptr_width, data_ptr - ptr_width));
+    refcount_ptr[0] = REFCOUNT_ONE;
+
+    return data_ptr;
+}
+
+/// Allocate a refcounted value of type `T`, such as the heap cell of a recursive tag union.
+pub fn allocateRefcounted(comptime T: type) *T {
+    const data_ptr = allocateWithRefcount(@sizeOf(T), @alignOf(T));
+
+    return @ptrCast(*T, @alignCast(@alignOf(T), data_ptr));
+}
+
+/// Fails the build if `T` doesn't have the size and alignment Roc expects,
+/// which means this glue is out of date with its platform.
+pub fn assertLayout(comptime T: type, comptime size: usize, comptime alignment: usize) void {
+    if (@sizeOf(T) != size or @alignOf(T) != alignment) {
+        @compileError(std.fmt.comptimePrint(
+            "{s} has size {} and alignment {}, but Roc expects size {} and alignment {}. Regenerate this glue with `roc glue`.",
+            .{ @typeName(T), @sizeOf(T), @alignOf(T), size, alignment },
+        ));
+    }
+}
+
+/// Fails the build if `field` isn't at the offset within `T` that Roc expects,
+/// which means this glue is out of date with its platform.
+pub fn assertOffset(comptime T: type, comptime field: []const u8, comptime offset: usize) void {
+    if (@offsetOf(T, field) != offset) {
+        @compileError(std.fmt.comptimePrint(
+            "{s}.{s} is at offset {}, but Roc expects offset {}. Regenerate this glue with `roc glue`.",
+            .{ @typeName(T), field, @offsetOf(T, field), offset },
+        ));
+    }
+}
+
+/// A zero-sized type, such as an empty record or a single-tag union with no payload.
+pub const RocUnit = extern struct {};
+
+/// The empty tag union; values of this type can never exist at runtime.
+pub const RocEmptyTagUnion = extern struct {};
+
+/// A Roc `Dec`: a fixed-point decimal stored as an `i128` scaled by 10^18.
+pub const RocDec = extern struct {
+    num: i128,
+
+    pub const one_point_zero: i128 = 1_000_000_000_000_000_000;
+};
+
+pub const RocStr = extern struct {
+    bytes: ?[*]u8,
+    length: usize,
+    capacity_or_ref_ptr: usize,
+
+    const small_string_size = @sizeOf(RocStr);
+
+    pub fn empty() RocStr {
+        return RocStr{ .bytes = null, .length = 0, .capacity_or_ref_ptr = SEAMLESS_SLICE_BIT };
+    }
+
+    pub fn fromSlice(slice: []const u8) RocStr {
+        if (slice.len < small_string_size) {
+            var string = RocStr.empty();
+            const string_bytes = @ptrCast([*]u8, &string);
+
+            std.mem.copy(u8, string_bytes[0..slice.len], slice);
+            string_bytes[small_string_size - 1] = @intCast(u8, slice.len) | 0b1000_0000;
+
+            return string;
+        } else {
+            const data_ptr = allocateWithRefcount(slice.len, @alignOf(usize));
+
+            std.mem.copy(u8, data_ptr[0..slice.len], slice);
+
+            return RocStr{ .bytes = data_ptr, .length = slice.len, .capacity_or_ref_ptr = slice.len };
+        }
+    }
+
+    pub fn isSmallStr(self: RocStr) bool {
+        return @bitCast(isize, self.capacity_or_ref_ptr) < 0;
+    }
+
+    pub fn len(self: RocStr) usize {
+        if (self.isSmallStr()) {
+            const string_bytes = @ptrCast([*]const u8, &self);
+
+            return string_bytes[small_string_size - 1] ^ 0b1000_0000;
+        } else {
+            return self.length & ~SEAMLESS_SLICE_BIT;
+        }
+    }
+
+    /// The returned slice borrows from `self`, so `self` must outlive it.
+    pub fn asSlice(self: *const RocStr) []const u8 {
+        if (self.isSmallStr()) {
+            return @ptrCast([*]const u8, self)[0..self.len()];
+        } else if (self.bytes) |bytes| {
+            return bytes[0..self.len()];
+        } else {
+            return &[_]u8{};
+        }
+    }
+};
+
+pub fn RocList(comptime T: type) type {
+    return extern struct {
+        elements: ?[*]T,
+        length: usize,
+        // This technically points to directly after the refcount.
+        capacity_or_ref_ptr: usize,
+
+        const Self = @This();
+
+        pub fn empty() Self {
+            return Self{ .elements = null, .length = 0, .capacity_or_ref_ptr = 0 };
+        }
+
+        pub fn fromSlice(slice: []const T) Self {
+            if (slice.len == 0) {
+                return Self.empty();
+            }
+
+            const data_ptr = allocateWithRefcount(slice.len * @sizeOf(T), @alignOf(T));
+            const elements = @ptrCast([*]T, @alignCast(@alignOf(T), data_ptr));
+
+            std.mem.copy(T, elements[0..slice.len], slice);
+
+            return Self{ .elements = elements, .length = slice.len, .capacity_or_ref_ptr = slice.len };
+        }
+
+        pub fn len(self: Self) usize {
+            return self.length & ~SEAMLESS_SLICE_BIT;
+        }
+
+        pub fn isEmpty(self: Self) bool {
+            return self.len() == 0;
+        }
+
+        /// The returned slice borrows from the list, so the list must outlive it.
+        pub fn asSlice(self: Self) []const T {
+            if (self.elements) |elements| {
+                return elements[0..self.len()];
+            } else {
+                return &[_]T{};
+            }
+        }
+    };
+}
+
+/// Dictionary entries are stored contiguously, with whichever of the key and value has
+/// the larger alignment first.
+pub fn RocDictItem(comptime K: type, comptime V: type) type {
+    if (@alignOf(K) >= @alignOf(V)) {
+        return extern struct { key: K, value: V };
+    } else {
+        return extern struct { value: V, key: K };
+    }
+}
+
+pub fn RocDict(comptime K: type, comptime V: type) type {
+    return extern struct {
+        items: RocList(RocDictItem(K, V)),
+    };
+}
+
+/// A set is stored as a dictionary with zero-sized values, which is laid out
+/// exactly like a list of its elements.
+pub fn RocSet(comptime T: type) type {
+    return extern struct {
+        items: RocList(T),
+    };
+}
+
+pub fn RocBox(comptime T: type) type {
+    return extern struct {
+        contents: *T,
+
+        const Self = @This();
+
+        pub fn init(contents: T) Self {
+            const ptr = allocateRefcounted(T);
+            ptr.* = contents;
+
+            return Self{ .contents = ptr };
+        }
+    };
+}
+
+pub const RocResultTag = enum(u8) {
+    RocErr = 0,
+    RocOk = 1,
+};
+
+pub fn RocResult(comptime T: type, comptime E: type) type {
+    return extern struct {
+        payload: extern union {
+            ok: T,
+            err: E,
+        },
+        tag: RocResultTag,
+
+        const Self = @This();
+
+        pub fn ok(payload: T) Self {
+            return Self{ .payload = .{ .ok = payload }, .tag = .RocOk };
+        }
+
+        pub fn err(payload: E) Self {
+            return Self{ .payload = .{ .err = payload }, .tag = .RocErr };
+        }
+
+        pub fn isOk(self: Self) bool {
+            return self.tag == .RocOk;
+        }
+    };
+}
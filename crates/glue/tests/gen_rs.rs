@@ -44,6 +44,20 @@ mod test_gen_rs {
                         pub b: roc_std::I128,
                         pub a: u64,
                     }
+
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "aarch64",
+                        target_arch = "wasm32",
+                        target_arch = "x86",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_MyRcd: () = {
+                        assert!(core::mem::size_of::<MyRcd>() == 32);
+                        assert!(core::mem::align_of::<MyRcd>() == 16);
+                        assert!(glue_offset_of!(MyRcd, b) == 0);
+                        assert!(glue_offset_of!(MyRcd, a) == 16);
+                    };
                     "#
                     )
             }]
@@ -84,6 +98,19 @@ mod test_gen_rs {
                         pub z: roc_std::RocList<u8>,
                     }
 
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "wasm32",
+                        target_arch = "x86"
+                    ))]
+                    const _LAYOUT_CHECK_Outer: () = {
+                        assert!(core::mem::size_of::<Outer>() == 32);
+                        assert!(core::mem::align_of::<Outer>() == 4);
+                        assert!(glue_offset_of!(Outer, x) == 0);
+                        assert!(glue_offset_of!(Outer, y) == 8);
+                        assert!(glue_offset_of!(Outer, z) == 20);
+                    };
+
                     #[cfg(any(
                         target_arch = "arm",
                         target_arch = "aarch64",
@@ -98,6 +125,20 @@ mod test_gen_rs {
                         pub a: u16,
                     }
 
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "aarch64",
+                        target_arch = "wasm32",
+                        target_arch = "x86",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_Inner: () = {
+                        assert!(core::mem::size_of::<Inner>() == 8);
+                        assert!(core::mem::align_of::<Inner>() == 4);
+                        assert!(glue_offset_of!(Inner, b) == 0);
+                        assert!(glue_offset_of!(Inner, a) == 4);
+                    };
+
                     #[cfg(any(
                         target_arch = "aarch64",
                         target_arch = "x86_64"
@@ -109,6 +150,18 @@ mod test_gen_rs {
                         pub z: roc_std::RocList<u8>,
                         pub x: Inner,
                     }
+
+                    #[cfg(any(
+                        target_arch = "aarch64",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_Outer: () = {
+                        assert!(core::mem::size_of::<Outer>() == 56);
+                        assert!(core::mem::align_of::<Outer>() == 8);
+                        assert!(glue_offset_of!(Outer, y) == 0);
+                        assert!(glue_offset_of!(Outer, z) == 24);
+                        assert!(glue_offset_of!(Outer, x) == 48);
+                    };
                     "#
                     )
             }]
@@ -140,6 +193,20 @@ mod test_gen_rs {
                         pub b: roc_std::U128,
                         pub a: u64,
                     }
+
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "aarch64",
+                        target_arch = "wasm32",
+                        target_arch = "x86",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_R1: () = {
+                        assert!(core::mem::size_of::<R1>() == 32);
+                        assert!(core::mem::align_of::<R1>() == 16);
+                        assert!(glue_offset_of!(R1, b) == 0);
+                        assert!(glue_offset_of!(R1, a) == 16);
+                    };
                     "#
                     )
             }]
@@ -171,6 +238,19 @@ mod test_gen_rs {
                         pub z: roc_std::RocList<u8>,
                     }
 
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "wasm32",
+                        target_arch = "x86"
+                    ))]
+                    const _LAYOUT_CHECK_R1: () = {
+                        assert!(core::mem::size_of::<R1>() == 32);
+                        assert!(core::mem::align_of::<R1>() == 4);
+                        assert!(glue_offset_of!(R1, x) == 0);
+                        assert!(glue_offset_of!(R1, y) == 8);
+                        assert!(glue_offset_of!(R1, z) == 20);
+                    };
+
                     #[cfg(any(
                         target_arch = "arm",
                         target_arch = "aarch64",
@@ -185,6 +265,20 @@ mod test_gen_rs {
                         pub a: u16,
                     }
 
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "aarch64",
+                        target_arch = "wasm32",
+                        target_arch = "x86",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_R2: () = {
+                        assert!(core::mem::size_of::<R2>() == 8);
+                        assert!(core::mem::align_of::<R2>() == 4);
+                        assert!(glue_offset_of!(R2, b) == 0);
+                        assert!(glue_offset_of!(R2, a) == 4);
+                    };
+
                     #[cfg(any(
                         target_arch = "aarch64",
                         target_arch = "x86_64"
@@ -196,6 +290,18 @@ mod test_gen_rs {
                         pub z: roc_std::RocList<u8>,
                         pub x: R2,
                     }
+
+                    #[cfg(any(
+                        target_arch = "aarch64",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_R1: () = {
+                        assert!(core::mem::size_of::<R1>() == 56);
+                        assert!(core::mem::align_of::<R1>() == 8);
+                        assert!(glue_offset_of!(R1, y) == 0);
+                        assert!(glue_offset_of!(R1, z) == 24);
+                        assert!(glue_offset_of!(R1, x) == 48);
+                    };
                     "#
                     )
             }]
@@ -245,6 +351,18 @@ mod test_gen_rs {
                             }
                         }
                     }
+
+                    #[cfg(any(
+                        target_arch = "arm",
+                        target_arch = "aarch64",
+                        target_arch = "wasm32",
+                        target_arch = "x86",
+                        target_arch = "x86_64"
+                    ))]
+                    const _LAYOUT_CHECK_Enumeration: () = {
+                        assert!(core::mem::size_of::<Enumeration>() == 1);
+                        assert!(core::mem::align_of::<Enumeration>() == 1);
+                    };
                     "#
                     )
             }]
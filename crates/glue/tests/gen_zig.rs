@@ -0,0 +1,248 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[macro_use]
+extern crate indoc;
+
+mod helpers;
+
+#[cfg(test)]
+mod test_gen_zig {
+    use crate::helpers::generate_zig_bindings;
+    use roc_glue::types::File;
+    use roc_glue::zig_glue::HEADER;
+
+    #[test]
+    fn basic_record_aliased() {
+        let module = indoc!(
+            r#"
+            MyRcd : { a : U64, b : I128 }
+
+            main : MyRcd
+            main = { a: 1u64, b: 2i128 }
+            "#
+        );
+
+        let full_header = std::str::from_utf8(HEADER).unwrap().to_string() + "\n";
+        assert_eq!(
+            generate_zig_bindings(module),
+            vec![File {
+                name: "glue.zig".to_string(),
+                content: full_header
+                    + indoc!(
+                        r#"
+                    pub const MyRcd = extern struct {
+                        b: i128,
+                        a: u64,
+                    };
+
+                    comptime {
+                        assertLayout(MyRcd, 32, 16);
+                        assertOffset(MyRcd, "b", 0);
+                        assertOffset(MyRcd, "a", 16);
+                    }
+                    "#
+                    )
+            }]
+        );
+    }
+
+    #[test]
+    fn tag_union_enumeration() {
+        let module = indoc!(
+            r#"
+            MyEnum : [Foo, Bar, Baz]
+
+            main : MyEnum
+            main = Foo
+            "#
+        );
+
+        let full_header = std::str::from_utf8(HEADER).unwrap().to_string() + "\n";
+        assert_eq!(
+            generate_zig_bindings(module),
+            vec![File {
+                name: "glue.zig".to_string(),
+                content: full_header
+                    + indoc!(
+                        r#"
+                    pub const MyEnum = enum(u8) {
+                        Bar = 0,
+                        Baz = 1,
+                        Foo = 2,
+                    };
+
+                    comptime {
+                        assertLayout(MyEnum, 1, 1);
+                    }
+                    "#
+                    )
+            }]
+        );
+    }
+
+    #[test]
+    fn cons_list_of_strings() {
+        let module = indoc!(
+            r#"
+            StrConsList : [Nil, Cons Str StrConsList]
+
+            main : StrConsList
+            main = Cons "Hello, " (Cons "World!" Nil)
+            "#
+        );
+
+        let full_header = std::str::from_utf8(HEADER).unwrap().to_string() + "\n";
+        assert_eq!(
+            generate_zig_bindings(module),
+            vec![File {
+                name: "glue.zig".to_string(),
+                content: full_header
+                    + indoc!(
+                        r#"
+                    pub const discriminant_StrConsList = enum(u8) {
+                        Cons = 0,
+                        Nil = 1,
+                    };
+
+                    pub const StrConsList = extern struct {
+                        pointer: ?*StrConsList_Cons,
+
+                        const Self = @This();
+
+                        /// Returns which variant this tag union holds. Note that this never includes a payload!
+                        pub fn discriminant(self: Self) discriminant_StrConsList {
+                            if (self.pointer == null) {
+                                return .Nil;
+                            } else {
+                                return .Cons;
+                            }
+                        }
+
+                        /// Construct a tag named `Nil`, which has no payload.
+                        pub fn initNil() Self {
+                            return Self{ .pointer = null };
+                        }
+
+                        /// Construct a tag named `Cons`, with the appropriate payload
+                        pub fn initCons(payload: StrConsList_Cons) Self {
+                            const cell = allocateRefcounted(StrConsList_Cons);
+
+                            cell.* = payload;
+
+                            return Self{ .pointer = cell };
+                        }
+
+                        /// Unsafely assume this `StrConsList` has a `.discriminant()` of `Cons` and return its payload.
+                        /// (Always examine `.discriminant()` first to make sure this is the correct variant!)
+                        pub fn getCons(self: Self) StrConsList_Cons {
+                            std.debug.assert(self.discriminant() == .Cons);
+
+                            return self.pointer.?.*;
+                        }
+                    };
+
+                    comptime {
+                        switch (builtin.cpu.arch) {
+                            .arm, .wasm32, .i386 => {
+                                assertLayout(StrConsList, 4, 4);
+                            },
+                            .aarch64, .x86_64 => {
+                                assertLayout(StrConsList, 8, 8);
+                            },
+                            else => {},
+                        }
+                    }
+
+                    pub const StrConsList_Cons = extern struct {
+                        f0: RocStr,
+                        f1: StrConsList,
+                    };
+
+                    comptime {
+                        switch (builtin.cpu.arch) {
+                            .arm, .wasm32, .i386 => {
+                                assertLayout(StrConsList_Cons, 16, 4);
+                                assertOffset(StrConsList_Cons, "f0", 0);
+                                assertOffset(StrConsList_Cons, "f1", 12);
+                            },
+                            .aarch64, .x86_64 => {
+                                assertLayout(StrConsList_Cons, 32, 8);
+                                assertOffset(StrConsList_Cons, "f0", 0);
+                                assertOffset(StrConsList_Cons, "f1", 24);
+                            },
+                            else => {},
+                        }
+                    }
+                    "#
+                    )
+            }]
+        );
+    }
+
+    #[test]
+    fn single_tag_union() {
+        let module = indoc!(
+            r#"
+            UserId : [Id U32 Str]
+
+            main : UserId
+            main = Id 42 "blah"
+            "#
+        );
+
+        let full_header = std::str::from_utf8(HEADER).unwrap().to_string() + "\n";
+        assert_eq!(
+            generate_zig_bindings(module),
+            vec![File {
+                name: "glue.zig".to_string(),
+                content: full_header
+                    + indoc!(
+                        r#"
+                    pub const UserId = switch (builtin.cpu.arch) {
+                        .arm, .wasm32, .i386 => extern struct {
+                            f0: u32,
+                            f1: RocStr,
+
+                            const Self = @This();
+
+                            /// A tag named `Id`, with the given payload.
+                            pub fn initId(f0: u32, f1: RocStr) Self {
+                                return Self{ .f0 = f0, .f1 = f1 };
+                            }
+                        },
+                        .aarch64, .x86_64 => extern struct {
+                            f0: RocStr,
+                            f1: u32,
+
+                            const Self = @This();
+
+                            /// A tag named `Id`, with the given payload.
+                            pub fn initId(f0: RocStr, f1: u32) Self {
+                                return Self{ .f0 = f0, .f1 = f1 };
+                            }
+                        },
+                        else => @compileError("UserId is not supported on this architecture"),
+                    };
+
+                    comptime {
+                        switch (builtin.cpu.arch) {
+                            .arm, .wasm32, .i386 => {
+                                assertLayout(UserId, 16, 4);
+                                assertOffset(UserId, "f0", 0);
+                                assertOffset(UserId, "f1", 4);
+                            },
+                            .aarch64, .x86_64 => {
+                                assertLayout(UserId, 32, 8);
+                                assertOffset(UserId, "f0", 0);
+                                assertOffset(UserId, "f1", 24);
+                            },
+                            else => {},
+                        }
+                    }
+                    "#
+                    )
+            }]
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use roc_glue::load::{load_types, IgnoreErrors};
-use roc_glue::rust_glue;
+use roc_glue::types::Types;
+use roc_glue::{rust_glue, zig_glue};
 use roc_load::Threading;
 use std::env;
 use std::fs::File;
@@ -8,6 +9,16 @@ use std::path::PathBuf;
 
 #[allow(dead_code)]
 pub fn generate_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
+    rust_glue::emit(&load_platform_types(decl_src))
+}
+
+#[allow(dead_code)]
+pub fn generate_zig_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
+    zig_glue::emit(&load_platform_types(decl_src))
+}
+
+#[allow(dead_code)]
+pub fn load_platform_types(decl_src: &str) -> Vec<Types> {
     use tempfile::tempdir;
 
     let mut src = indoc!(
@@ -25,27 +36,23 @@ pub fn generate_bindings(decl_src: &str) -> Vec<roc_glue::types::File> {
 
     src.push_str(decl_src);
 
-    let types = {
-        let dir = tempdir().expect("Unable to create tempdir");
-        let filename = PathBuf::from("platform.roc");
-        let file_path = dir.path().join(filename);
-        let full_file_path = file_path.clone();
-        let mut file = File::create(file_path).unwrap();
-        writeln!(file, "{}", &src).unwrap();
-
-        let result = load_types(
-            full_file_path,
-            Threading::Single,
-            // required `nothing` is unused; that error is okay
-            IgnoreErrors { can: true },
-        );
+    let dir = tempdir().expect("Unable to create tempdir");
+    let filename = PathBuf::from("platform.roc");
+    let file_path = dir.path().join(filename);
+    let full_file_path = file_path.clone();
+    let mut file = File::create(file_path).unwrap();
+    writeln!(file, "{}", &src).unwrap();
 
-        dir.close().expect("Unable to close tempdir");
+    let result = load_types(
+        full_file_path,
+        Threading::Single,
+        // required `nothing` is unused; that error is okay
+        IgnoreErrors { can: true },
+    );
 
-        result.expect("had problems loading")
-    };
+    dir.close().expect("Unable to close tempdir");
 
-    rust_glue::emit(&types)
+    result.expect("had problems loading")
 }
 
 #[allow(dead_code)]
@@ -0,0 +1,79 @@
+#[macro_use]
+extern crate pretty_assertions;
+
+#[macro_use]
+extern crate indoc;
+
+mod helpers;
+
+#[cfg(test)]
+mod test_verify {
+    use crate::helpers::load_platform_types;
+    use roc_glue::verify::{diff_manifests, layout_manifest};
+
+    const MODULE: &str = indoc!(
+        r#"
+        Outer : { x : Inner, y : Str, z : List U8 }
+
+        Inner : { a : U16, b : F32 }
+
+        main : Outer
+        main = { x: { a: 5, b: 24 }, y: "foo", z: [1, 2] }
+        "#
+    );
+
+    #[test]
+    fn manifest_has_every_target() {
+        assert_eq!(
+            layout_manifest(&load_platform_types(MODULE)),
+            indoc!(
+                r#"
+                Aarch32 Outer size=32 align=4 x@0 y@8 z@20
+                Aarch32 Inner size=8 align=4 b@0 a@4
+                Aarch64 Outer size=56 align=8 y@0 z@24 x@48
+                Aarch64 Inner size=8 align=4 b@0 a@4
+                Wasm32 Outer size=32 align=4 x@0 y@8 z@20
+                Wasm32 Inner size=8 align=4 b@0 a@4
+                X86_32 Outer size=32 align=4 x@0 y@8 z@20
+                X86_32 Inner size=8 align=4 b@0 a@4
+                X86_64 Outer size=56 align=8 y@0 z@24 x@48
+                X86_64 Inner size=8 align=4 b@0 a@4
+                "#
+            )
+        );
+    }
+
+    #[test]
+    fn up_to_date_manifest_has_no_problems() {
+        let manifest = layout_manifest(&load_platform_types(MODULE));
+
+        assert_eq!(diff_manifests(&manifest, &manifest), Vec::<String>::new());
+    }
+
+    #[test]
+    fn stale_manifest_reports_problems() {
+        let stale = indoc!(
+            r#"
+            X86_64 Outer size=48 align=8 y@0 z@24 x@40
+            X86_64 Inner size=8 align=4 b@0 a@4
+            X86_64 Removed size=8 align=8
+            "#
+        );
+        let current = indoc!(
+            r#"
+            X86_64 Outer size=56 align=8 y@0 z@24 x@48
+            X86_64 Inner size=8 align=4 b@0 a@4
+            X86_64 Added size=1 align=1
+            "#
+        );
+
+        assert_eq!(
+            diff_manifests(stale, current),
+            vec![
+                "X86_64 Outer changed: the glue has `size=48 align=8 y@0 z@24 x@40`, but the platform now has `size=56 align=8 y@0 z@24 x@48`".to_string(),
+                "X86_64 Removed is in the glue, but no longer in the platform".to_string(),
+                "X86_64 Added is in the platform, but missing from the glue".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,245 @@
+//! Reading and rewriting the aarch64 instructions that refer to other code or data.
+//! The ELF and Mach-O surgeries both need these, they only differ in how relocations are described.
+use object::{elf, macho, Relocation, RelocationEncoding, RelocationKind};
+use roc_error_macros::internal_error;
+
+// aarch64 code addresses data with `adrp`, which is relative to the 4KiB page the instruction is on.
+// Shifting the host by anything other than whole pages would break those references.
+pub(crate) const AARCH64_PAGE_SIZE: u64 = 0x1000;
+
+pub(crate) const AARCH64_B: u32 = 0x1400_0000;
+pub(crate) const AARCH64_NOP: u32 = 0xd503_201f;
+
+/// The immediates aarch64 instructions use to refer to addresses.
+/// Unlike on x86_64, an address never gets its own bytes; it is split across bit fields of the instruction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Aarch64Immediate {
+    /// `b` and `bl`: a signed word offset from the instruction.
+    Branch26,
+    /// `adrp`: a signed offset from the instruction's 4KiB page to the target's page.
+    Page21,
+    /// `add` and loads/stores: the target's offset into its page, divided by the access size.
+    PageOffset12 { scale: u32 },
+}
+
+impl Aarch64Immediate {
+    pub(crate) fn from_elf_relocation(reloc: &Relocation) -> Option<Self> {
+        match (reloc.kind(), reloc.encoding()) {
+            (RelocationKind::PltRelative, RelocationEncoding::AArch64Call) => {
+                Some(Aarch64Immediate::Branch26)
+            }
+            (RelocationKind::Elf(r_type), _) => match r_type {
+                elf::R_AARCH64_CALL26 | elf::R_AARCH64_JUMP26 => Some(Aarch64Immediate::Branch26),
+                elf::R_AARCH64_ADR_PREL_PG_HI21 => Some(Aarch64Immediate::Page21),
+                elf::R_AARCH64_ADD_ABS_LO12_NC | elf::R_AARCH64_LDST8_ABS_LO12_NC => {
+                    Some(Aarch64Immediate::PageOffset12 { scale: 0 })
+                }
+                elf::R_AARCH64_LDST16_ABS_LO12_NC => {
+                    Some(Aarch64Immediate::PageOffset12 { scale: 1 })
+                }
+                elf::R_AARCH64_LDST32_ABS_LO12_NC => {
+                    Some(Aarch64Immediate::PageOffset12 { scale: 2 })
+                }
+                elf::R_AARCH64_LDST64_ABS_LO12_NC => {
+                    Some(Aarch64Immediate::PageOffset12 { scale: 3 })
+                }
+                elf::R_AARCH64_LDST128_ABS_LO12_NC => {
+                    Some(Aarch64Immediate::PageOffset12 { scale: 4 })
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Mach-O has a single relocation for every `add` and load/store, so the access size has to be
+    /// read from the instruction itself.
+    pub(crate) fn from_macho_relocation(r_type: u8, inst: u32) -> Option<Self> {
+        match r_type {
+            macho::ARM64_RELOC_BRANCH26 => Some(Aarch64Immediate::Branch26),
+            macho::ARM64_RELOC_PAGE21 | macho::ARM64_RELOC_GOT_LOAD_PAGE21 => {
+                Some(Aarch64Immediate::Page21)
+            }
+            macho::ARM64_RELOC_PAGEOFF12 | macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12 => {
+                Some(Aarch64Immediate::page_offset_12(inst))
+            }
+            _ => None,
+        }
+    }
+
+    fn page_offset_12(inst: u32) -> Self {
+        // Loads and stores with an unsigned immediate offset.
+        if inst & 0x3b00_0000 == 0x3900_0000 {
+            let size = inst >> 30;
+            let simd = inst & (1 << 26) != 0;
+            // A 128 bit q register access has size 0, but the top bit of opc set.
+            let scale = if simd && size == 0 && inst & (1 << 23) != 0 {
+                4
+            } else {
+                size
+            };
+
+            Aarch64Immediate::PageOffset12 { scale }
+        } else {
+            Aarch64Immediate::PageOffset12 { scale: 0 }
+        }
+    }
+
+    /// Rewrite the instruction at `place` so that it refers to `target`.
+    pub(crate) fn encode(self, inst: u32, place: u64, target: u64) -> u32 {
+        match self {
+            Aarch64Immediate::Branch26 => {
+                let offset = target as i64 - place as i64;
+                if offset % 4 != 0 || !(-(1 << 27)..(1 << 27)).contains(&offset) {
+                    internal_error!(
+                        "Branch from {:+x} to {:+x} is out of range for aarch64",
+                        place,
+                        target
+                    );
+                }
+
+                (inst & 0xfc00_0000) | ((offset >> 2) as u32 & 0x03ff_ffff)
+            }
+            Aarch64Immediate::Page21 => {
+                let page_mask = !(AARCH64_PAGE_SIZE - 1);
+                let pages = ((target & page_mask) as i64 - (place & page_mask) as i64) >> 12;
+                if !(-(1 << 20)..(1 << 20)).contains(&pages) {
+                    internal_error!(
+                        "Page of {:+x} is out of range of the adrp at {:+x}",
+                        target,
+                        place
+                    );
+                }
+
+                let pages = pages as u32;
+                let immlo = (pages & 0b11) << 29;
+                let immhi = ((pages >> 2) & 0x7_ffff) << 5;
+
+                (inst & !((0b11 << 29) | (0x7_ffff << 5))) | immlo | immhi
+            }
+            Aarch64Immediate::PageOffset12 { scale } => {
+                let page_offset = (target & (AARCH64_PAGE_SIZE - 1)) as u32;
+                if page_offset & ((1 << scale) - 1) != 0 {
+                    internal_error!(
+                        "Address {:+x} is not aligned for a {} byte access",
+                        target,
+                        1 << scale
+                    );
+                }
+
+                (inst & !(0xfff << 10)) | ((page_offset >> scale) << 10)
+            }
+        }
+    }
+}
+
+/// If this is a `b` or `bl` instruction at `address`, where does it go?
+pub(crate) fn aarch64_branch_target(inst: u32, address: u64) -> Option<u64> {
+    // `b` is 0b000101 and `bl` is 0b100101 in the top 6 bits.
+    if inst & 0x7c00_0000 != AARCH64_B {
+        return None;
+    }
+
+    // Sign extend the 26 bit word offset.
+    let offset = (((inst << 6) as i32) >> 6) as i64 * 4;
+
+    Some(address.wrapping_add(offset as u64))
+}
+
+/// Turn `ldr xN, [xM, #got_entry]` into `add xN, xM, #target`, so that a load from the GOT becomes
+/// the address it would have loaded. The immediate still needs to be encoded after this.
+pub(crate) fn aarch64_relax_got_load(inst: u32) -> u32 {
+    // ldr (64 bit, unsigned offset)
+    if inst & 0xffc0_0000 != 0xf940_0000 {
+        internal_error!("Expected a GOT load but found the instruction {:+x}", inst);
+    }
+
+    // add (64 bit, immediate), keeping the registers.
+    0x9100_0000 | (inst & 0x3ff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aarch64_branch() {
+        // bl #0x40
+        let bl = 0x9400_0010;
+        assert_eq!(aarch64_branch_target(bl, 0x1000), Some(0x1040));
+        // b #-0x8
+        let b = 0x17ff_fffe;
+        assert_eq!(aarch64_branch_target(b, 0x1000), Some(0xff8));
+        // ret
+        assert_eq!(aarch64_branch_target(0xd65f_03c0, 0x1000), None);
+
+        let encoded = Aarch64Immediate::Branch26.encode(bl, 0x1000, 0x20_0000);
+        assert_eq!(encoded & 0xfc00_0000, 0x9400_0000);
+        assert_eq!(aarch64_branch_target(encoded, 0x1000), Some(0x20_0000));
+
+        let encoded = Aarch64Immediate::Branch26.encode(AARCH64_B, 0x20_0000, 0x1000);
+        assert_eq!(aarch64_branch_target(encoded, 0x20_0000), Some(0x1000));
+    }
+
+    #[test]
+    fn aarch64_adrp_and_page_offset() {
+        // adrp x0, #0
+        let adrp = 0x9000_0000;
+        let encoded = Aarch64Immediate::Page21.encode(adrp, 0x1_0ffc, 0x23_4567);
+        // adrp x0, #0x224000
+        assert_eq!(encoded, 0x9000_1120);
+
+        let encoded = Aarch64Immediate::Page21.encode(adrp, 0x23_4567, 0x1_0ffc);
+        // adrp x0, #-0x224000
+        assert_eq!(encoded, 0x90ff_eee0);
+
+        // add x0, x0, #0
+        let add = 0x9100_0000;
+        let encoded = Aarch64Immediate::PageOffset12 { scale: 0 }.encode(add, 0, 0x23_4567);
+        // add x0, x0, #0x567
+        assert_eq!(encoded, 0x9115_9c00);
+
+        // ldr x1, [x0]
+        let ldr = 0xf940_0001;
+        let encoded = Aarch64Immediate::PageOffset12 { scale: 3 }.encode(ldr, 0, 0x23_4568);
+        // ldr x1, [x0, #0x568]
+        assert_eq!(encoded, 0xf942_b401);
+    }
+
+    #[test]
+    fn aarch64_macho_page_offset_scale() {
+        // add x0, x0, #0
+        assert_eq!(
+            Aarch64Immediate::from_macho_relocation(macho::ARM64_RELOC_PAGEOFF12, 0x9100_0000),
+            Some(Aarch64Immediate::PageOffset12 { scale: 0 })
+        );
+        // ldr x1, [x0]
+        assert_eq!(
+            Aarch64Immediate::from_macho_relocation(macho::ARM64_RELOC_PAGEOFF12, 0xf940_0001),
+            Some(Aarch64Immediate::PageOffset12 { scale: 3 })
+        );
+        // ldr w1, [x0]
+        assert_eq!(
+            Aarch64Immediate::from_macho_relocation(macho::ARM64_RELOC_PAGEOFF12, 0xb940_0001),
+            Some(Aarch64Immediate::PageOffset12 { scale: 2 })
+        );
+        // ldr q0, [x0]
+        assert_eq!(
+            Aarch64Immediate::from_macho_relocation(macho::ARM64_RELOC_PAGEOFF12, 0x3dc0_0000),
+            Some(Aarch64Immediate::PageOffset12 { scale: 4 })
+        );
+    }
+
+    #[test]
+    fn aarch64_got_load_relaxation() {
+        // ldr x8, [x8]
+        let ldr = 0xf940_0108;
+        let add = aarch64_relax_got_load(ldr);
+        // add x8, x8, #0
+        assert_eq!(add, 0x9100_0108);
+
+        let encoded = Aarch64Immediate::PageOffset12 { scale: 0 }.encode(add, 0, 0x4_0010);
+        // add x8, x8, #0x10
+        assert_eq!(encoded, 0x9100_4108);
+    }
+}
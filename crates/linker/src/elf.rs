@@ -18,6 +18,9 @@ use std::{
     time::{Duration, Instant},
 };
 
+use crate::aarch64::{
+    aarch64_branch_target, Aarch64Immediate, AARCH64_B, AARCH64_NOP, AARCH64_PAGE_SIZE,
+};
 use crate::{
     align_by_constraint, align_to_offset_by_constraint, load_struct_inplace,
     load_struct_inplace_mut, load_structs_inplace_mut, open_mmap, open_mmap_mut,
@@ -25,8 +28,85 @@ use crate::{
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
-// TODO: Analyze if this offset is always correct.
-const PLT_ADDRESS_OFFSET: u64 = 0x10;
+/// The instruction sets of the ELF hosts we know how to perform surgery on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ElfMachine {
+    X86_64,
+    Aarch64,
+}
+
+impl ElfMachine {
+    fn from_architecture(architecture: object::Architecture) -> Self {
+        match architecture {
+            object::Architecture::X86_64 => ElfMachine::X86_64,
+            object::Architecture::Aarch64 => ElfMachine::Aarch64,
+            other => {
+                user_error!("The surgical linker does not support {:?} ELF hosts", other);
+            }
+        }
+    }
+
+    fn from_e_machine(e_machine: u16) -> Self {
+        match e_machine {
+            elf::EM_X86_64 => ElfMachine::X86_64,
+            elf::EM_AARCH64 => ElfMachine::Aarch64,
+            other => {
+                internal_error!("The surgical linker does not support ELF machine {}", other);
+            }
+        }
+    }
+
+    /// The PLT starts with a header that is not associated with any symbol.
+    fn plt_header_size(self) -> u64 {
+        match self {
+            // TODO: Analyze if this offset is always correct.
+            ElfMachine::X86_64 => 0x10,
+            ElfMachine::Aarch64 => 0x20,
+        }
+    }
+
+    fn plt_entry_size(self) -> u64 {
+        match self {
+            ElfMachine::X86_64 | ElfMachine::Aarch64 => 0x10,
+        }
+    }
+
+    /// How much to align the bytes inserted after the program headers by.
+    fn shift_alignment(self) -> u64 {
+        match self {
+            ElfMachine::X86_64 => MIN_SECTION_ALIGNMENT as u64,
+            ElfMachine::Aarch64 => AARCH64_PAGE_SIZE,
+        }
+    }
+
+    fn r_none(self) -> u32 {
+        match self {
+            ElfMachine::X86_64 => elf::R_X86_64_NONE,
+            ElfMachine::Aarch64 => elf::R_AARCH64_NONE,
+        }
+    }
+
+    fn r_relative(self) -> u32 {
+        match self {
+            ElfMachine::X86_64 => elf::R_X86_64_RELATIVE,
+            ElfMachine::Aarch64 => elf::R_AARCH64_RELATIVE,
+        }
+    }
+
+    fn r_glob_dat(self) -> u32 {
+        match self {
+            ElfMachine::X86_64 => elf::R_X86_64_GLOB_DAT,
+            ElfMachine::Aarch64 => elf::R_AARCH64_GLOB_DAT,
+        }
+    }
+
+    fn r_jump_slot(self) -> u32 {
+        match self {
+            ElfMachine::X86_64 => elf::R_X86_64_JUMP_SLOT,
+            ElfMachine::Aarch64 => elf::R_AARCH64_JUMP_SLOT,
+        }
+    }
+}
 
 struct ElfDynamicDeps {
     got_app_syms: Vec<(String, usize)>,
@@ -69,6 +149,7 @@ struct Metadata {
     last_vaddr: u64,
     dynamic_section_offset: u64,
     dynamic_symbol_table_section_offset: u64,
+    dynamic_symbol_table_size: u64,
     symbol_table_section_offset: u64,
     symbol_table_size: u64,
     _macho_cmd_loc: u64,
@@ -163,13 +244,18 @@ fn collect_roc_definitions<'a>(object: &object::File<'a, &'a [u8]>) -> MutMap<St
 }
 
 struct Surgeries<'a> {
+    machine: ElfMachine,
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
     app_func_addresses: MutMap<u64, &'a str>,
     indirect_warning_given: bool,
 }
 
 impl<'a> Surgeries<'a> {
-    fn new(application_symbols: &[Symbol], app_func_addresses: MutMap<u64, &'a str>) -> Self {
+    fn new(
+        machine: ElfMachine,
+        application_symbols: &[Symbol],
+        app_func_addresses: MutMap<u64, &'a str>,
+    ) -> Self {
         let mut surgeries = MutMap::default();
 
         // for each symbol that the host expects from the application
@@ -180,6 +266,7 @@ impl<'a> Surgeries<'a> {
         }
 
         Self {
+            machine,
             surgeries,
             app_func_addresses,
             indirect_warning_given: false,
@@ -240,7 +327,37 @@ impl<'a> Surgeries<'a> {
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
-        let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
+
+        match self.machine {
+            ElfMachine::X86_64 => self.append_x86_64_branches(
+                object_bytes,
+                sec,
+                &data,
+                file_offset,
+                compressed,
+                verbose,
+            ),
+            ElfMachine::Aarch64 => self.append_aarch64_branches(
+                object_bytes,
+                sec,
+                &data,
+                file_offset,
+                compressed,
+                verbose,
+            ),
+        }
+    }
+
+    fn append_x86_64_branches(
+        &mut self,
+        object_bytes: &[u8],
+        sec: &Section,
+        data: &[u8],
+        file_offset: u64,
+        compressed: bool,
+        verbose: bool,
+    ) {
+        let mut decoder = Decoder::with_ip(64, data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
         while decoder.can_decode() {
@@ -331,6 +448,60 @@ impl<'a> Surgeries<'a> {
             }
         }
     }
+
+    fn append_aarch64_branches(
+        &mut self,
+        object_bytes: &[u8],
+        sec: &Section,
+        data: &[u8],
+        file_offset: u64,
+        compressed: bool,
+        verbose: bool,
+    ) {
+        // Every aarch64 instruction is 4 bytes, so there is nothing to decode but the branches.
+        // As with x86_64, calls through registers keep going through the plt.
+        for (i, bytes) in data.chunks_exact(4).enumerate() {
+            let inst = u32::from_le_bytes(bytes.try_into().unwrap());
+            let address = sec.address() + i as u64 * 4;
+
+            let func_name = match aarch64_branch_target(inst, address)
+                .and_then(|target| self.app_func_addresses.get(&target))
+            {
+                Some(func_name) => *func_name,
+                None => continue,
+            };
+
+            if compressed {
+                internal_error!(
+                    "Surgical linking does not work with compressed text sections: {:+x?}",
+                    sec
+                );
+            }
+
+            let offset = address - sec.address() + file_offset;
+            if verbose {
+                println!("Found branch from {:+x} to {}", address, func_name);
+                println!(
+                    "\tNeed to surgically replace the instruction at file offset {:+x}",
+                    offset,
+                );
+                println!(
+                    "\tIts current value is {:+x?}",
+                    &object_bytes[offset as usize..offset as usize + 4]
+                )
+            }
+
+            self.surgeries
+                .get_mut(func_name)
+                .unwrap()
+                .push(SurgeryEntry {
+                    file_offset: offset,
+                    // aarch64 branches are relative to the branch itself, not the next instruction.
+                    virtual_offset: VirtualOffset::Relative(address),
+                    size: 4,
+                });
+        }
+    }
 }
 
 /// Constructs a `Metadata` from a host executable binary, and writes it to disk
@@ -353,6 +524,8 @@ pub(crate) fn preprocess_elf(
         }
     };
 
+    let machine = ElfMachine::from_architecture(exec_obj.architecture());
+
     let mut md = Metadata {
         roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
         ..Default::default()
@@ -429,7 +602,7 @@ pub(crate) fn preprocess_elf(
                 }
             })
             .filter_map(|(_, reloc)| {
-                if let RelocationKind::Elf(7) = reloc.kind() {
+                if reloc.kind() == RelocationKind::Elf(machine.r_jump_slot()) {
                     Some(reloc)
                 } else {
                     None
@@ -438,8 +611,10 @@ pub(crate) fn preprocess_elf(
     for (i, reloc) in plt_relocs.enumerate() {
         for symbol in app_syms.iter() {
             if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
-                let func_address = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_address;
-                let func_offset = (i as u64 + 1) * PLT_ADDRESS_OFFSET + plt_offset;
+                let plt_entry_offset =
+                    machine.plt_header_size() + i as u64 * machine.plt_entry_size();
+                let func_address = plt_entry_offset + plt_address;
+                let func_offset = plt_entry_offset + plt_offset;
                 app_func_addresses.insert(func_address, symbol.name().unwrap());
                 md.plt_addresses.insert(
                     symbol.name().unwrap().to_string(),
@@ -475,7 +650,7 @@ pub(crate) fn preprocess_elf(
     // look at the text (i.e. code) sections and see collect work needs to be done
     let text_disassembly_start = Instant::now();
 
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
+    let mut surgeries = Surgeries::new(machine, &app_syms, app_func_addresses);
     surgeries.append_text_sections(exec_data, &exec_obj, verbose);
     md.surgeries = surgeries.surgeries;
 
@@ -495,7 +670,7 @@ pub(crate) fn preprocess_elf(
                 dynamic_lib_count,
                 shared_lib_index,
             } = scan_elf_dynamic_deps(
-                &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
+                &exec_obj, machine, &mut md, &app_syms, shared_lib, exec_data, verbose,
             );
 
             scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
@@ -505,6 +680,7 @@ pub(crate) fn preprocess_elf(
             // TODO little endian
             gen_elf_le(
                 exec_data,
+                machine,
                 &mut md,
                 preprocessed_path,
                 &got_app_syms,
@@ -575,6 +751,7 @@ pub(crate) fn preprocess_elf(
 #[allow(clippy::too_many_arguments)]
 fn gen_elf_le(
     exec_data: &[u8],
+    machine: ElfMachine,
     md: &mut Metadata,
     preprocessed_path: &Path,
     got_app_syms: &[(String, usize)],
@@ -606,7 +783,7 @@ fn gen_elf_le(
     let added_header_count = 2;
     md.added_byte_count = ph_ent_size as u64 * added_header_count;
     md.added_byte_count = md.added_byte_count
-        + (MIN_SECTION_ALIGNMENT as u64 - md.added_byte_count % MIN_SECTION_ALIGNMENT as u64);
+        + (machine.shift_alignment() - md.added_byte_count % machine.shift_alignment());
     let ph_end = ph_offset as usize + ph_num as usize * ph_ent_size as usize;
     let physical_shift_start = ph_end as u64;
 
@@ -747,18 +924,18 @@ fn gen_elf_le(
                 rel.r_offset.set(LE, r_offset + md.added_byte_count);
                 // Deal with potential adjusts to absolute jumps.
                 // TODO: Verify other relocation types.
-                if rel.r_type(LE, false) == elf::R_X86_64_RELATIVE {
+                if rel.r_type(LE, false) == machine.r_relative() {
                     let r_addend = rel.r_addend.get(LE);
                     rel.r_addend.set(LE, r_addend + md.added_byte_count as i64);
                 }
             }
             // If the relocation goes to a roc function, we need to surgically link it and change it to relative.
             let r_type = rel.r_type(LE, false);
-            if r_type == elf::R_X86_64_GLOB_DAT {
+            if r_type == machine.r_glob_dat() {
                 let r_sym = rel.r_sym(LE, false);
                 for (name, index) in got_app_syms.iter() {
                     if *index as u32 == r_sym {
-                        rel.set_r_info(LE, false, 0, elf::R_X86_64_RELATIVE);
+                        rel.set_r_info(LE, false, 0, machine.r_relative());
                         let addend_addr = sec_offset as usize
                             + i * mem::size_of::<elf::Rela64<LE>>()
                             // This 16 skips the first 2 fields and gets to the addend field.
@@ -783,8 +960,7 @@ fn gen_elf_le(
             .filter_map(|(i, rel)| {
                 let r_type = rel.r_type(LE, false);
                 let r_sym = rel.r_sym(LE, false);
-                if r_type == elf::R_X86_64_JUMP_SLOT && app_sym_indices.contains(&(r_sym as usize))
-                {
+                if r_type == machine.r_jump_slot() && app_sym_indices.contains(&(r_sym as usize)) {
                     Some(i)
                 } else {
                     None
@@ -800,7 +976,7 @@ fn gen_elf_le(
         for i in to_remove.iter() {
             relocations.swap(*i, j);
             let r_sym = relocations[j].r_sym(LE, false);
-            relocations[j].set_r_info(LE, false, r_sym, elf::R_X86_64_NONE);
+            relocations[j].set_r_info(LE, false, r_sym, machine.r_none());
             j -= 1;
         }
 
@@ -920,6 +1096,23 @@ fn gen_elf_le(
         }
     }
 
+    // The dynamic symbol table too. For a shared library host, these are the host's exports.
+    let dynsym_offset = md.dynamic_symbol_table_section_offset + md.added_byte_count;
+    let dynsym_size = md.dynamic_symbol_table_size as usize;
+
+    let dynamic_symbols = load_structs_inplace_mut::<elf::Sym64<LE>>(
+        &mut out_mmap,
+        dynsym_offset as usize,
+        dynsym_size / mem::size_of::<elf::Sym64<LE>>(),
+    );
+
+    for sym in dynamic_symbols {
+        let addr = sym.st_value.get(LE);
+        if sym.st_shndx.get(LE) != elf::SHN_ABS && virtual_shift_start <= addr {
+            sym.st_value.set(LE, addr + md.added_byte_count);
+        }
+    }
+
     // Update all data in the global offset table.
     for (offset, size) in got_sections {
         let global_offsets = load_structs_inplace_mut::<endian::U64<LE>>(
@@ -965,6 +1158,7 @@ fn gen_elf_le(
 
 fn scan_elf_dynamic_deps(
     exec_obj: &object::File,
+    machine: ElfMachine,
     md: &mut Metadata,
     app_syms: &[Symbol],
     shared_lib: &Path,
@@ -1084,6 +1278,7 @@ fn scan_elf_dynamic_deps(
         }
     };
     md.dynamic_symbol_table_section_offset = dynsym_offset as u64;
+    md.dynamic_symbol_table_size = dynsym_sec.size();
 
     let mut got_sections: Vec<(usize, usize)> = vec![];
     for sec in exec_obj
@@ -1111,7 +1306,7 @@ fn scan_elf_dynamic_deps(
         }
     })
     .filter_map(|(_, reloc)| {
-        if let RelocationKind::Elf(elf::R_X86_64_GLOB_DAT) = reloc.kind() {
+        if reloc.kind() == RelocationKind::Elf(machine.r_glob_dat()) {
             for symbol in app_syms.iter() {
                 if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                     return Some((symbol.name().unwrap().to_string(), symbol.index().0));
@@ -1130,7 +1325,7 @@ fn scan_elf_dynamic_deps(
         }
     })
     .filter_map(|(_, reloc)| {
-        if let RelocationKind::Elf(elf::R_X86_64_JUMP_SLOT) = reloc.kind() {
+        if reloc.kind() == RelocationKind::Elf(machine.r_jump_slot()) {
             for symbol in app_syms.iter() {
                 if reloc.target() == RelocationTarget::Symbol(symbol.index()) {
                     return Some(symbol.index().0);
@@ -1252,6 +1447,7 @@ fn surgery_elf_help(
     }
     let exec_header = load_struct_inplace::<elf::FileHeader64<LE>>(exec_mmap, 0);
 
+    let machine = ElfMachine::from_e_machine(exec_header.e_machine.get(LE));
     let ph_offset = exec_header.e_phoff.get(LE);
     let ph_ent_size = exec_header.e_phentsize.get(LE);
     let ph_num = exec_header.e_phnum.get(LE);
@@ -1263,6 +1459,7 @@ fn surgery_elf_help(
         println!();
         println!("Is Elf64: {}", elf64);
         println!("Is Little Endian: {}", litte_endian);
+        println!("Machine: {:?}", machine);
         println!("PH Offset: {:+x}", ph_offset);
         println!("PH Entry Size: {}", ph_ent_size);
         println!("PH Entry Count: {}", ph_num);
@@ -1443,6 +1640,22 @@ fn surgery_elf_help(
                     if let Some(target_offset) = target_offset {
                         let virt_base = section_virtual_offset + rel.0 as usize;
                         let base = section_offset + rel.0 as usize;
+
+                        if let Some(immediate) = Aarch64Immediate::from_elf_relocation(&rel.1) {
+                            let target = target_offset + rel.1.addend();
+                            if verbose {
+                                println!(
+                                    "\t\tRelocation base location: {base:+x} (virt: {virt_base:+x})",
+                                );
+                                println!("\t\tFinal relocation target: {target:+x}");
+                            }
+                            let inst =
+                                u32::from_le_bytes(exec_mmap[base..][..4].try_into().unwrap());
+                            let inst = immediate.encode(inst, virt_base as u64, target as u64);
+                            exec_mmap[base..][..4].copy_from_slice(&inst.to_le_bytes());
+                            continue;
+                        }
+
                         let target: i64 = match rel.1.kind() {
                             RelocationKind::Relative | RelocationKind::PltRelative => {
                                 target_offset - virt_base as i64 + rel.1.addend()
@@ -1629,6 +1842,20 @@ fn surgery_elf_help(
                 VirtualOffset::Absolute => 0,
             };
             match s.size {
+                4 if machine == ElfMachine::Aarch64 => {
+                    let file_offset = (s.file_offset + md.added_byte_count) as usize;
+                    let inst =
+                        u32::from_le_bytes(exec_mmap[file_offset..][..4].try_into().unwrap());
+                    let inst = Aarch64Immediate::Branch26.encode(
+                        inst,
+                        surgery_virt_offset as u64,
+                        func_virt_offset,
+                    );
+                    if verbose {
+                        println!("\tBranch Instruction: {:+x}", inst);
+                    }
+                    exec_mmap[file_offset..][..4].copy_from_slice(&inst.to_le_bytes());
+                }
                 4 => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
                     if verbose {
@@ -1658,18 +1885,34 @@ fn surgery_elf_help(
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
             let plt_off = (*plt_off + md.added_byte_count) as usize;
             let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+            let plt_entry = &mut exec_mmap[plt_off..][..machine.plt_entry_size() as usize];
             if verbose {
                 println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
-                println!("\tTarget Jump: {:+x}", target);
             }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+            match machine {
+                ElfMachine::X86_64 => {
+                    let jmp_inst_len = 5;
+                    let target =
+                        (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                    if verbose {
+                        println!("\tTarget Jump: {:+x}", target);
+                    }
+                    let data = target.to_le_bytes();
+                    plt_entry[0] = 0xE9;
+                    plt_entry[1..jmp_inst_len].copy_from_slice(&data);
+                    plt_entry[jmp_inst_len..].fill(0x90);
+                }
+                ElfMachine::Aarch64 => {
+                    let inst =
+                        Aarch64Immediate::Branch26.encode(AARCH64_B, plt_vaddr, func_virt_offset);
+                    if verbose {
+                        println!("\tBranch Instruction: {:+x}", inst);
+                    }
+                    plt_entry[..4].copy_from_slice(&inst.to_le_bytes());
+                    for nop in plt_entry[4..].chunks_exact_mut(4) {
+                        nop.copy_from_slice(&AARCH64_NOP.to_le_bytes());
+                    }
+                }
             }
         }
 
@@ -1715,7 +1958,7 @@ fn surgery_elf_help(
 mod tests {
     use super::*;
 
-    use crate::preprocessed_host_filename;
+    use crate::{preprocessed_host_filename, LinkType};
     use indoc::indoc;
     use target_lexicon::Triple;
 
@@ -1775,19 +2018,30 @@ mod tests {
     }
 
     #[allow(dead_code)]
-    fn zig_host_app_help(dir: &Path, target: &Triple) {
-        let host_zig = indoc!(
-            r#"
-            const std = @import("std");
+    fn zig_host_app_help(dir: &Path, target: &Triple, link_type: LinkType) {
+        let host_zig = match link_type {
+            LinkType::Dylib => indoc!(
+                r#"
+                extern fn roc_magic1(usize) callconv(.C) [*]const u8;
 
-            extern fn roc_magic1(usize) callconv(.C) [*]const u8;
+                export fn host_magic() [*]const u8 {
+                    return roc_magic1(0);
+                }
+                "#
+            ),
+            _ => indoc!(
+                r#"
+                const std = @import("std");
 
-            pub fn main() !void {
-                const stdout = std.io.getStdOut().writer();
-                try stdout.print("Hello {s}\n", .{roc_magic1(0)[0..3]});
-            }
-            "#
-        );
+                extern fn roc_magic1(usize) callconv(.C) [*]const u8;
+
+                pub fn main() !void {
+                    const stdout = std.io.getStdOut().writer();
+                    try stdout.print("Hello {s}\n", .{roc_magic1(0)[0..3]});
+                }
+                "#
+            ),
+        };
 
         let app_zig = indoc!(
             r#"
@@ -1800,6 +2054,10 @@ mod tests {
         );
 
         let zig = std::env::var("ROC_ZIG").unwrap_or_else(|_| "zig".into());
+        let zig_target = match target.architecture {
+            target_lexicon::Architecture::Aarch64(_) => "aarch64-linux-gnu",
+            _ => "x86_64-linux-gnu",
+        };
 
         std::fs::write(dir.join("host.zig"), host_zig.as_bytes()).unwrap();
         std::fs::write(dir.join("app.zig"), app_zig.as_bytes()).unwrap();
@@ -1812,7 +2070,7 @@ mod tests {
                 "app.zig",
                 "-fPIC",
                 "-target",
-                "x86_64-linux-gnu",
+                zig_target,
                 "-OReleaseFast",
             ])
             .output()
@@ -1841,20 +2099,22 @@ mod tests {
                 .collect()
         };
 
-        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names).unwrap();
+        let dylib_bytes = crate::generate_dylib::create_dylib_elf64(&names, target).unwrap();
         std::fs::write(dir.join("libapp.so"), dylib_bytes).unwrap();
 
         // now we can compile the host (it uses libapp.so, hence the order here)
+        let (host_args, host_path) = match link_type {
+            LinkType::Dylib => (["build-lib", "-dynamic", "-fPIC"], dir.join("libhost.so")),
+            _ => (["build-exe", "-fPIE", "-lc"], dir.join("host")),
+        };
         let output = std::process::Command::new(&zig)
             .current_dir(dir)
+            .args(host_args)
             .args([
-                "build-exe",
                 "libapp.so",
                 "host.zig",
-                "-fPIE",
-                "-lc",
                 "-target",
-                "x86_64-linux-gnu",
+                zig_target,
                 "-OReleaseFast",
             ])
             .output()
@@ -1866,14 +2126,14 @@ mod tests {
             std::io::stdout().write_all(&output.stdout).unwrap();
             std::io::stderr().write_all(&output.stderr).unwrap();
 
-            panic!("zig build-exe failed");
+            panic!("zig {} failed", host_args[0]);
         }
 
         let preprocessed_host_filename = dir.join(preprocessed_host_filename(target).unwrap());
 
         preprocess_elf(
             target_lexicon::Endianness::Little,
-            &dir.join("host"),
+            &host_path,
             &dir.join("metadata"),
             &preprocessed_host_filename,
             &dir.join("libapp.so"),
@@ -1900,7 +2160,11 @@ mod tests {
         let dir = tempfile::tempdir().unwrap();
         let dir = dir.path();
 
-        zig_host_app_help(dir, &Triple::from_str("x86_64-unknown-linux-musl").unwrap());
+        zig_host_app_help(
+            dir,
+            &Triple::from_str("x86_64-unknown-linux-musl").unwrap(),
+            LinkType::Executable,
+        );
 
         let output = std::process::Command::new(dir.join("final"))
             .current_dir(dir)
@@ -1920,4 +2184,97 @@ mod tests {
 
         assert_eq!("Hello foo\n", output);
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_aarch64() {
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        zig_host_app_help(
+            dir,
+            &Triple::from_str("aarch64-unknown-linux-gnu").unwrap(),
+            LinkType::Executable,
+        );
+
+        // We can't run the result without arm hardware, so check its structure instead.
+        let data = std::fs::read(dir.join("final")).unwrap();
+        let object = object::File::parse(data.as_slice()).unwrap();
+        assert_eq!(object.architecture(), object::Architecture::Aarch64);
+
+        let roc_magic1 = object
+            .dynamic_symbols()
+            .find(|sym| sym.name() == Ok("roc_magic1"))
+            .unwrap();
+        let app_text = object
+            .sections()
+            .find(|sec| {
+                sec.address() <= roc_magic1.address()
+                    && roc_magic1.address() < sec.address() + sec.size()
+            })
+            .unwrap();
+        assert_eq!(app_text.kind(), SectionKind::Text);
+
+        // The host's call to the app now goes directly to the app's code.
+        let host_text = object.section_by_name(".text").unwrap();
+        let branches = host_text
+            .data()
+            .unwrap()
+            .chunks_exact(4)
+            .enumerate()
+            .filter_map(|(i, bytes)| {
+                let inst = u32::from_le_bytes(bytes.try_into().unwrap());
+                aarch64_branch_target(inst, host_text.address() + i as u64 * 4)
+            })
+            .collect::<Vec<_>>();
+        assert!(branches.contains(&roc_magic1.address()));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_dylib() {
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        zig_host_app_help(
+            dir,
+            &Triple::from_str("x86_64-unknown-linux-gnu").unwrap(),
+            LinkType::Dylib,
+        );
+
+        // The host's own exports still point at its code, even though preprocessing moved it.
+        let data = std::fs::read(dir.join("final")).unwrap();
+        let object = object::File::parse(data.as_slice()).unwrap();
+        let host_magic = object
+            .dynamic_symbols()
+            .find(|sym| sym.name() == Ok("host_magic"))
+            .unwrap();
+        let host_text = object.section_by_name(".text").unwrap();
+        assert!(
+            host_text.address() <= host_magic.address()
+                && host_magic.address() < host_text.address() + host_text.size()
+        );
+
+        // And it can be loaded, without the stub libapp.so, and called into.
+        let path = std::ffi::CString::new(dir.join("final").to_str().unwrap()).unwrap();
+        let name = std::ffi::CString::new("host_magic").unwrap();
+
+        unsafe {
+            let handle = libc::dlopen(path.as_ptr(), libc::RTLD_NOW);
+            assert!(!handle.is_null(), "dlopen failed");
+
+            let host_magic = libc::dlsym(handle, name.as_ptr());
+            assert!(!host_magic.is_null(), "host_magic is not exported");
+
+            let host_magic: extern "C" fn() -> *const u8 = std::mem::transmute(host_magic);
+            let result = std::slice::from_raw_parts(host_magic(), 3);
+            assert_eq!(result, b"foo");
+
+            libc::dlclose(handle);
+        }
+    }
 }
@@ -1,8 +1,12 @@
 use object::{elf, Endianness};
+use target_lexicon::Triple;
 
 use crate::pe::next_multiple_of;
 
-pub fn create_dylib_elf64(custom_names: &[String]) -> object::read::Result<Vec<u8>> {
+pub fn create_dylib_elf64(
+    custom_names: &[String],
+    target: &Triple,
+) -> object::read::Result<Vec<u8>> {
     let endian = Endianness::Little;
 
     let mut out_data = Vec::new();
@@ -145,7 +149,10 @@ pub fn create_dylib_elf64(custom_names: &[String]) -> object::read::Result<Vec<u
             os_abi: 0,
             abi_version: 0,
             e_type: 3,
-            e_machine: 62,
+            e_machine: match target.architecture {
+                target_lexicon::Architecture::Aarch64(_) => elf::EM_AARCH64,
+                _ => elf::EM_X86_64,
+            },
             e_entry: 0x1000,
             e_flags: 0,
         })
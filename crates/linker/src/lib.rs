@@ -6,7 +6,10 @@
 use memmap2::{Mmap, MmapMut};
 use object::Object;
 use roc_error_macros::internal_error;
-use roc_load::{EntryPoint, ExecutionMode, ExposedToHost, LoadConfig, Threading};
+use roc_load::{
+    EntryPoint, ExecutionMode, ExposedToHost, LoadConfig, MonoDiagnostics, MonoOptimizations,
+    Threading,
+};
 use roc_module::symbol::Interns;
 use roc_packaging::cache::RocCacheDir;
 use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
@@ -16,6 +19,7 @@ use std::mem;
 use std::path::{Path, PathBuf};
 use target_lexicon::Triple;
 
+mod aarch64;
 mod elf;
 mod macho;
 mod pe;
@@ -31,21 +35,23 @@ pub enum LinkType {
 }
 
 pub fn supported(link_type: LinkType, target: &Triple) -> bool {
-    if let LinkType::Executable = link_type {
-        match target {
+    match link_type {
+        LinkType::Executable => match target {
             Triple {
-                architecture: target_lexicon::Architecture::X86_64,
+                architecture:
+                    target_lexicon::Architecture::X86_64 | target_lexicon::Architecture::Aarch64(_),
                 operating_system: target_lexicon::OperatingSystem::Linux,
                 binary_format: target_lexicon::BinaryFormat::Elf,
                 ..
             } => true,
 
-            // macho support is incomplete
             Triple {
+                architecture:
+                    target_lexicon::Architecture::X86_64 | target_lexicon::Architecture::Aarch64(_),
                 operating_system: target_lexicon::OperatingSystem::Darwin,
                 binary_format: target_lexicon::BinaryFormat::Macho,
                 ..
-            } => false,
+            } => true,
 
             Triple {
                 architecture: target_lexicon::Architecture::X86_64,
@@ -55,9 +61,21 @@ pub fn supported(link_type: LinkType, target: &Triple) -> bool {
             } => true,
 
             _ => false,
-        }
-    } else {
-        false
+        },
+
+        // A shared object is preprocessed just like a PIE executable, so ELF is all we need here.
+        LinkType::Dylib => matches!(
+            target,
+            Triple {
+                architecture: target_lexicon::Architecture::X86_64
+                    | target_lexicon::Architecture::Aarch64(_),
+                operating_system: target_lexicon::OperatingSystem::Linux,
+                binary_format: target_lexicon::BinaryFormat::Elf,
+                ..
+            }
+        ),
+
+        LinkType::None => false,
     }
 }
 
@@ -67,19 +85,52 @@ pub fn preprocessed_host_filename(target: &Triple) -> Option<String> {
     roc_target::get_target_triple_str(target).map(|x| format!("{}.{}", x, PRECOMPILED_HOST_EXT))
 }
 
-fn metadata_file_name(target: &Triple) -> String {
-    let target_triple_str = get_target_triple_str(target);
+/// Like [preprocessed_host_filename], but for a host that is a shared library (`roc build --lib`).
+/// A platform can have both, so they need different names.
+pub fn preprocessed_dylib_host_filename(target: &Triple) -> Option<String> {
+    roc_target::get_target_triple_str(target)
+        .map(|x| format!("{}-dylib.{}", x, PRECOMPILED_HOST_EXT))
+}
 
-    format!("metadata_{}.rm", target_triple_str.unwrap_or("unknown"))
+/// The extension of a shared library for `target`'s binary format.
+pub fn dylib_file_ext(target: &Triple) -> &'static str {
+    match target.binary_format {
+        target_lexicon::BinaryFormat::Macho => "dylib",
+        target_lexicon::BinaryFormat::Coff => "dll",
+        _ => "so",
+    }
+}
+
+/// The file name of the host before preprocessing, linked against the stub `libapp`.
+pub fn dynhost_filename(link_type: LinkType, target: &Triple) -> &'static str {
+    match (link_type, target.operating_system) {
+        (LinkType::Dylib, _) => match target.binary_format {
+            target_lexicon::BinaryFormat::Macho => "libdynhost.dylib",
+            target_lexicon::BinaryFormat::Coff => "dynhost.dll",
+            _ => "libdynhost.so",
+        },
+        (_, target_lexicon::OperatingSystem::Windows) => "dynhost.exe",
+        _ => "dynhost",
+    }
+}
+
+fn metadata_file_name(link_type: LinkType, target: &Triple) -> String {
+    let target_triple_str = get_target_triple_str(target).unwrap_or("unknown");
+
+    match link_type {
+        LinkType::Dylib => format!("metadata_{}-dylib.rm", target_triple_str),
+        _ => format!("metadata_{}.rm", target_triple_str),
+    }
 }
 
 pub fn link_preprocessed_host(
     target: &Triple,
+    link_type: LinkType,
     platform_path: &Path,
     roc_app_bytes: &[u8],
     binary_path: &Path,
 ) {
-    let metadata = platform_path.with_file_name(metadata_file_name(target));
+    let metadata = platform_path.with_file_name(metadata_file_name(link_type, target));
     surgery(roc_app_bytes, &metadata, binary_path, false, false, target)
 }
 
@@ -105,6 +156,8 @@ pub fn generate_stub_lib(
             palette: DEFAULT_PALETTE,
             threading: Threading::AllAvailable,
             exec_mode: ExecutionMode::Executable,
+            mono_optimizations: MonoOptimizations::Disabled,
+            mono_diagnostics: MonoDiagnostics::default(),
         },
     )
     .unwrap_or_else(|problem| todo!("{:?}", problem));
@@ -345,6 +398,7 @@ fn object_matches_target<'a>(target: &Triple, object: &object::File<'a, &'a [u8]
             let target_format = match target.operating_system {
                 TLO::Linux => object::BinaryFormat::Elf,
                 TLO::Windows => object::BinaryFormat::Pe,
+                TLO::Darwin | TLO::MacOSX { .. } => object::BinaryFormat::MachO,
                 _ => todo!("surgical linker does not support target {:?}", target),
             };
 
@@ -384,17 +438,14 @@ fn stub_lib_is_up_to_date(target: &Triple, stub_lib_path: &Path, custom_names: &
 
 pub fn preprocess_host(
     target: &Triple,
+    link_type: LinkType,
     platform_main_roc: &Path,
     preprocessed_path: &Path,
     shared_lib: &Path,
     stub_dll_symbols: &[String],
 ) {
-    let metadata_path = platform_main_roc.with_file_name(metadata_file_name(target));
-    let host_exe_path = if let target_lexicon::OperatingSystem::Windows = target.operating_system {
-        platform_main_roc.with_file_name("dynhost.exe")
-    } else {
-        platform_main_roc.with_file_name("dynhost")
-    };
+    let metadata_path = platform_main_roc.with_file_name(metadata_file_name(link_type, target));
+    let host_exe_path = platform_main_roc.with_file_name(dynhost_filename(link_type, target));
 
     preprocess(
         target,
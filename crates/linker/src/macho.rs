@@ -2,16 +2,17 @@ use bincode::{deserialize_from, serialize_into};
 use iced_x86::{Decoder, DecoderOptions, Instruction, OpCodeOperandKind, OpKind};
 use memmap2::MmapMut;
 use object::macho;
+use object::read::macho::{MachHeader, Section as _, Segment as _};
 use object::{
     CompressedFileRange, CompressionFormat, LittleEndian as LE, Object, ObjectSection,
-    ObjectSymbol, RelocationKind, RelocationTarget, Section, SectionIndex, SectionKind, Symbol,
-    SymbolIndex, SymbolSection,
+    ObjectSymbol, Section, SectionIndex, SectionKind, Symbol, SymbolIndex, SymbolSection,
 };
-use roc_collections::all::MutMap;
-use roc_error_macros::internal_error;
+use roc_collections::all::{MutMap, MutSet};
+use roc_error_macros::{internal_error, user_error};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
-    ffi::CStr,
+    ffi::{c_char, CStr},
     io::{BufReader, BufWriter},
     mem,
     path::Path,
@@ -19,24 +20,99 @@ use std::{
 };
 use target_lexicon::Triple;
 
+use crate::aarch64::{
+    aarch64_branch_target, aarch64_relax_got_load, Aarch64Immediate, AARCH64_B, AARCH64_NOP,
+};
 use crate::{
-    align_by_constraint, align_to_offset_by_constraint, load_struct_inplace,
-    load_struct_inplace_mut, load_structs_inplace, load_structs_inplace_mut, open_mmap,
-    open_mmap_mut,
+    align_by_constraint, load_struct_inplace, load_struct_inplace_mut, load_structs_inplace,
+    load_structs_inplace_mut, open_mmap, open_mmap_mut,
 };
 
 const MIN_SECTION_ALIGNMENT: usize = 0x40;
 
-// TODO: Analyze if this offset is always correct.
-const PLT_ADDRESS_OFFSET: u64 = 0x10;
-const STUB_ADDRESS_OFFSET: u64 = 0x06;
+/// The segment that preprocessing makes room for in the load commands. Surgery puts the app in it.
+const ROC_SEGMENT_NAME: [u8; 16] = *b"__ROC\0\0\0\0\0\0\0\0\0\0\0";
+
+/// The app's read-only data, code and zero-initialized data, in that order.
+/// dyld insists that a segment command is exactly as big as its sections, so there are always 3.
+const ROC_SECTION_NAMES: [[u8; 16]; 3] = [
+    *b"__const\0\0\0\0\0\0\0\0\0",
+    *b"__text\0\0\0\0\0\0\0\0\0\0",
+    *b"__bss\0\0\0\0\0\0\0\0\0\0\0",
+];
+
+// From mach-o/fixup-chains.h
+const DYLD_CHAINED_PTR_64: u16 = 2;
+const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+const DYLD_CHAINED_PTR_START_NONE: u16 = 0xffff;
+const DYLD_CHAINED_IMPORT: u32 = 1;
+const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+
+// From the code signing blob definitions in xnu (osfmk/kern/cs_blobs.h)
+const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade_0cc0;
+const CSMAGIC_CODEDIRECTORY: u32 = 0xfade_0c02;
+const CSSLOT_CODEDIRECTORY: u32 = 0;
+const CS_ADHOC: u32 = 0x0000_0002;
+const CS_LINKER_SIGNED: u32 = 0x0002_0000;
+const CS_HASHTYPE_SHA256: u8 = 2;
+const CS_SHA256_LEN: usize = 32;
+const CS_EXECSEG_MAIN_BINARY: u64 = 0x1;
+const CS_SUPPORTSEXECSEG: u32 = 0x20400;
+const CODE_SIGNATURE_PAGE_SIZE: usize = 0x1000;
+const CODE_DIRECTORY_HEADER_SIZE: usize = 88;
+// magic, length, count and the index of the one blob in it: the code directory.
+const SUPER_BLOB_HEADER_SIZE: usize = 20;
+
+/// The instruction sets of the Mach-O hosts we know how to perform surgery on.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum MachoCpu {
+    X86_64,
+    Aarch64,
+}
+
+impl MachoCpu {
+    fn from_architecture(architecture: object::Architecture) -> Self {
+        match architecture {
+            object::Architecture::X86_64 => MachoCpu::X86_64,
+            object::Architecture::Aarch64 => MachoCpu::Aarch64,
+            other => {
+                user_error!(
+                    "The surgical linker does not support {:?} Mach-O hosts",
+                    other
+                );
+            }
+        }
+    }
+
+    fn from_cputype(cputype: u32) -> Self {
+        match cputype {
+            macho::CPU_TYPE_X86_64 => MachoCpu::X86_64,
+            macho::CPU_TYPE_ARM64 => MachoCpu::Aarch64,
+            other => {
+                internal_error!(
+                    "The surgical linker does not support Mach-O cpu type {:#x}",
+                    other
+                );
+            }
+        }
+    }
+
+    /// Segments have to start on a page. Apple silicon uses 16KiB pages.
+    fn page_size(self) -> u64 {
+        match self {
+            MachoCpu::X86_64 => 0x1000,
+            MachoCpu::Aarch64 => 0x4000,
+        }
+    }
 
-// struct MachoDynamicDeps {
-//     got_app_syms: Vec<(String, usize)>,
-//     got_sections: Vec<(usize, usize)>,
-//     dynamic_lib_count: usize,
-//     shared_lib_index: usize,
-// }
+    fn reloc_subtractor(self) -> u8 {
+        match self {
+            MachoCpu::X86_64 => macho::X86_64_RELOC_SUBTRACTOR,
+            MachoCpu::Aarch64 => macho::ARM64_RELOC_SUBTRACTOR,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
 enum VirtualOffset {
@@ -51,10 +127,18 @@ struct SurgeryEntry {
     size: u8,
 }
 
-// TODO: Reanalyze each piece of data in this struct.
-// I think a number of them can be combined to reduce string duplication.
-// Also I think a few of them aren't need.
-// For example, I think preprocessing can deal with all shifting and remove the need for added_byte_count.
+/// How the pointers that dyld fixes up are stored in the host.
+#[derive(Default, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Debug)]
+enum PointerFormat {
+    /// Plain addresses, with rebases and binds described by `LC_DYLD_INFO` opcodes.
+    #[default]
+    Raw,
+    /// `DYLD_CHAINED_PTR_64`: a rebase holds the target's address.
+    Chained64,
+    /// `DYLD_CHAINED_PTR_64_OFFSET`: a rebase holds the target's offset from the start of the image.
+    Chained64Offset,
+}
+
 // TODO: we probably should be storing numbers in an endian neutral way.
 #[derive(Default, Serialize, Deserialize, PartialEq, Eq, Debug)]
 struct Metadata {
@@ -62,18 +146,17 @@ struct Metadata {
     // offset followed by address.
     plt_addresses: MutMap<String, (u64, u64)>,
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
-    dynamic_symbol_indices: MutMap<String, u64>,
-    _static_symbol_indices: MutMap<String, u64>,
+    // file offsets of the pointers that dyld used to bind to app functions.
+    app_pointers: MutMap<String, Vec<u64>>,
+    pointer_format: PointerFormat,
     roc_symbol_vaddresses: MutMap<String, u64>,
     exec_len: u64,
     load_align_constraint: u64,
-    added_byte_count: u64,
-    last_vaddr: u64,
-    _dynamic_section_offset: u64,
-    _dynamic_symbol_table_section_offset: u64,
-    _symbol_table_section_offset: u64,
-    _symbol_table_size: u64,
-    macho_cmd_loc: u64,
+    stub_size: u64,
+    image_base: u64,
+    roc_segment_cmd_offset: u64,
+    linkedit_cmd_offset: u64,
+    code_signature_cmd_offset: u64,
 }
 
 impl Metadata {
@@ -154,13 +237,18 @@ fn collect_roc_definitions<'a>(object: &object::File<'a, &'a [u8]>) -> MutMap<St
 }
 
 struct Surgeries<'a> {
+    cpu: MachoCpu,
     surgeries: MutMap<String, Vec<SurgeryEntry>>,
     app_func_addresses: MutMap<u64, &'a str>,
     indirect_warning_given: bool,
 }
 
 impl<'a> Surgeries<'a> {
-    fn new(application_symbols: &[Symbol], app_func_addresses: MutMap<u64, &'a str>) -> Self {
+    fn new(
+        cpu: MachoCpu,
+        application_symbols: &[Symbol],
+        app_func_addresses: MutMap<u64, &'a str>,
+    ) -> Self {
         let mut surgeries = MutMap::default();
 
         // for each symbol that the host expects from the application
@@ -171,6 +259,7 @@ impl<'a> Surgeries<'a> {
         }
 
         Self {
+            cpu,
             surgeries,
             app_func_addresses,
             indirect_warning_given: false,
@@ -231,7 +320,37 @@ impl<'a> Surgeries<'a> {
                 internal_error!("Failed to load text section, {:+x?}: {}", sec, err);
             }
         };
-        let mut decoder = Decoder::with_ip(64, &data, sec.address(), DecoderOptions::NONE);
+
+        match self.cpu {
+            MachoCpu::X86_64 => self.append_x86_64_branches(
+                object_bytes,
+                sec,
+                &data,
+                file_offset,
+                compressed,
+                verbose,
+            ),
+            MachoCpu::Aarch64 => self.append_aarch64_branches(
+                object_bytes,
+                sec,
+                &data,
+                file_offset,
+                compressed,
+                verbose,
+            ),
+        }
+    }
+
+    fn append_x86_64_branches(
+        &mut self,
+        object_bytes: &[u8],
+        sec: &Section,
+        data: &[u8],
+        file_offset: u64,
+        compressed: bool,
+        verbose: bool,
+    ) {
+        let mut decoder = Decoder::with_ip(64, data, sec.address(), DecoderOptions::NONE);
         let mut inst = Instruction::default();
 
         while decoder.can_decode() {
@@ -240,7 +359,7 @@ impl<'a> Surgeries<'a> {
             // Note: This gets really complex fast if we want to support more than basic calls/jumps.
             // A lot of them have to load addresses into registers/memory so we would have to discover that value.
             // Would probably require some static code analysis and would be impossible in some cases.
-            // As an alternative we can leave in the calls to the plt, but change the plt to jmp to the static function.
+            // As an alternative we can leave in the calls to the stubs, but change the stubs to jmp to the static function.
             // That way any indirect call will just have the overhead of an extra jump.
             match inst.try_op_kind(0) {
                 // Relative Offsets.
@@ -322,302 +441,1038 @@ impl<'a> Surgeries<'a> {
             }
         }
     }
-}
 
-/// Constructs a `Metadata` from a host executable binary, and writes it to disk
-pub(crate) fn preprocess_macho(
-    target: &Triple,
-    host_exe_path: &Path,
-    metadata_path: &Path,
-    preprocessed_path: &Path,
-    shared_lib: &Path,
-    verbose: bool,
-    time: bool,
-) {
-    let total_start = Instant::now();
-    let exec_parsing_start = total_start;
-    let exec_data = &*open_mmap(host_exe_path);
-    let exec_obj = match object::File::parse(exec_data) {
-        Ok(obj) => obj,
-        Err(err) => {
-            internal_error!("Failed to parse executable file: {}", err);
+    fn append_aarch64_branches(
+        &mut self,
+        object_bytes: &[u8],
+        sec: &Section,
+        data: &[u8],
+        file_offset: u64,
+        compressed: bool,
+        verbose: bool,
+    ) {
+        // Every aarch64 instruction is 4 bytes, so there is nothing to decode but the branches.
+        // As with x86_64, calls through registers keep going through the stubs.
+        for (i, bytes) in data.chunks_exact(4).enumerate() {
+            let inst = u32::from_le_bytes(bytes.try_into().unwrap());
+            let address = sec.address() + i as u64 * 4;
+
+            let func_name = match aarch64_branch_target(inst, address)
+                .and_then(|target| self.app_func_addresses.get(&target))
+            {
+                Some(func_name) => *func_name,
+                None => continue,
+            };
+
+            if compressed {
+                internal_error!(
+                    "Surgical linking does not work with compressed text sections: {:+x?}",
+                    sec
+                );
+            }
+
+            let offset = address - sec.address() + file_offset;
+            if verbose {
+                println!("Found branch from {:+x} to {}", address, func_name);
+                println!(
+                    "\tNeed to surgically replace the instruction at file offset {:+x}",
+                    offset,
+                );
+                println!(
+                    "\tIts current value is {:+x?}",
+                    &object_bytes[offset as usize..offset as usize + 4]
+                )
+            }
+
+            self.surgeries
+                .get_mut(func_name)
+                .unwrap()
+                .push(SurgeryEntry {
+                    file_offset: offset,
+                    // aarch64 branches are relative to the branch itself, not the next instruction.
+                    virtual_offset: VirtualOffset::Relative(address),
+                    size: 4,
+                });
         }
-    };
+    }
+}
 
-    let mut md = Metadata {
-        roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
-        ..Default::default()
-    };
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..][..2].try_into().unwrap())
+}
 
-    if verbose {
-        println!(
-            "Found roc symbol definitions: {:+x?}",
-            md.roc_symbol_vaddresses
-        );
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..][..4].try_into().unwrap())
+}
+
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(bytes[offset..][..8].try_into().unwrap())
+}
+
+fn write_u32(bytes: &mut [u8], offset: usize, value: u32) {
+    bytes[offset..][..4].copy_from_slice(&value.to_le_bytes());
+}
+
+fn write_u64(bytes: &mut [u8], offset: usize, value: u64) {
+    bytes[offset..][..8].copy_from_slice(&value.to_le_bytes());
+}
+
+fn read_uleb128(bytes: &[u8], pos: &mut usize) -> u64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            return value;
+        }
     }
+}
 
-    let exec_parsing_duration = exec_parsing_start.elapsed();
+fn read_sleb128(bytes: &[u8], pos: &mut usize) -> i64 {
+    let mut value = 0;
+    let mut shift = 0;
+    loop {
+        let byte = bytes[*pos];
+        *pos += 1;
+        value |= ((byte & 0x7f) as i64) << shift;
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                value |= -1 << shift;
+            }
+            return value;
+        }
+    }
+}
 
-    // PLT stands for Procedure Linkage Table which is, put simply, used to call external
-    // procedures/functions whose address isn't known in the time of linking, and is left
-    // to be resolved by the dynamic linker at run time.
-    let symbol_and_plt_processing_start = Instant::now();
-    let plt_section_name = "__stubs";
-
-    let (plt_address, plt_offset) = match exec_obj.section_by_name(plt_section_name) {
-        Some(section) => {
-            let file_offset = match section.compressed_file_range() {
-                Ok(
-                    range @ CompressedFileRange {
-                        format: CompressionFormat::None,
-                        ..
-                    },
-                ) => range.offset,
-                _ => {
-                    internal_error!("Surgical linking does not work with compressed plt section");
-                }
-            };
-            (section.address(), file_offset)
+fn write_uleb128(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
         }
-        None => {
-            internal_error!("Failed to find PLT section. Probably an malformed executable.");
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_sleb128(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        let done = (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0);
+        if done {
+            out.push(byte);
+            return;
         }
+        out.push(byte | 0x80);
+    }
+}
+
+/// Overwrite the ULEB128 at `pos` without changing its length, so nothing after it moves.
+/// A ULEB128 can always be padded out with continuation bytes.
+fn overwrite_uleb128(bytes: &mut [u8], pos: usize, value: u64) {
+    let mut end = pos;
+    read_uleb128(bytes, &mut end);
+
+    let mut encoded = Vec::new();
+    write_uleb128(&mut encoded, value);
+    if encoded.len() > end - pos {
+        internal_error!("{} does not fit in the ULEB128 at {:+x}", value, pos);
+    }
+
+    let last = end - pos - 1;
+    encoded.resize(end - pos, 0);
+    for byte in encoded[..last].iter_mut() {
+        *byte |= 0x80;
+    }
+    bytes[pos..end].copy_from_slice(&encoded);
+}
+
+/// A single symbol bound by the `LC_DYLD_INFO` bind opcodes.
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Bind<'a> {
+    segment: u8,
+    offset: u64,
+    ordinal: i64,
+    /// Where the opcode that set `ordinal` is in the opcode stream.
+    ordinal_at: usize,
+    symbol: &'a [u8],
+    flags: u8,
+    kind: u8,
+    addend: i64,
+}
+
+/// Run the bind opcodes to see which symbols end up where.
+/// The lazy bind opcodes are a separate program for every symbol, each ending with a `DONE`.
+fn decode_binds(stream: &[u8], lazy: bool) -> Vec<Bind> {
+    let mut binds = Vec::new();
+    let mut bind = Bind {
+        segment: 0,
+        offset: 0,
+        ordinal: 0,
+        ordinal_at: 0,
+        symbol: &[],
+        flags: 0,
+        kind: macho::BIND_TYPE_POINTER,
+        addend: 0,
     };
-    if verbose {
-        println!("PLT Address: {:+x}", plt_address);
-        println!("PLT File Offset: {:+x}", plt_offset);
+    let pointer_size = mem::size_of::<u64>() as u64;
+
+    let mut pos = 0;
+    while pos < stream.len() {
+        let opcode_at = pos;
+        let opcode = stream[pos] & macho::BIND_OPCODE_MASK;
+        let immediate = stream[pos] & macho::BIND_IMMEDIATE_MASK;
+        pos += 1;
+
+        match opcode {
+            macho::BIND_OPCODE_DONE => {
+                if !lazy {
+                    break;
+                }
+            }
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => {
+                bind.ordinal = immediate as i64;
+                bind.ordinal_at = opcode_at;
+            }
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                bind.ordinal = read_uleb128(stream, &mut pos) as i64;
+                bind.ordinal_at = opcode_at;
+            }
+            macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                // The special ordinals are small negative numbers.
+                bind.ordinal = if immediate == 0 {
+                    0
+                } else {
+                    (macho::BIND_OPCODE_MASK | immediate) as i8 as i64
+                };
+                bind.ordinal_at = opcode_at;
+            }
+            macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                let len = stream[pos..]
+                    .iter()
+                    .position(|byte| *byte == 0)
+                    .unwrap_or_else(|| internal_error!("Unterminated symbol in bind opcodes"));
+                bind.symbol = &stream[pos..pos + len];
+                bind.flags = immediate;
+                pos += len + 1;
+            }
+            macho::BIND_OPCODE_SET_TYPE_IMM => {
+                bind.kind = immediate;
+            }
+            macho::BIND_OPCODE_SET_ADDEND_SLEB => {
+                bind.addend = read_sleb128(stream, &mut pos);
+            }
+            macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                bind.segment = immediate;
+                bind.offset = read_uleb128(stream, &mut pos);
+            }
+            macho::BIND_OPCODE_ADD_ADDR_ULEB => {
+                bind.offset = bind.offset.wrapping_add(read_uleb128(stream, &mut pos));
+            }
+            macho::BIND_OPCODE_DO_BIND => {
+                binds.push(bind.clone());
+                bind.offset += pointer_size;
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
+                binds.push(bind.clone());
+                let skip = read_uleb128(stream, &mut pos);
+                bind.offset = bind.offset.wrapping_add(pointer_size).wrapping_add(skip);
+            }
+            macho::BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => {
+                binds.push(bind.clone());
+                bind.offset += pointer_size + immediate as u64 * pointer_size;
+            }
+            macho::BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = read_uleb128(stream, &mut pos);
+                let skip = read_uleb128(stream, &mut pos);
+                for _ in 0..count {
+                    binds.push(bind.clone());
+                    bind.offset = bind.offset.wrapping_add(pointer_size).wrapping_add(skip);
+                }
+            }
+            other => {
+                internal_error!("Unsupported bind opcode {:#x} at {:+x}", other, opcode_at);
+            }
+        }
     }
 
-    let app_syms: Vec<_> = exec_obj.symbols().filter(is_roc_undefined).collect();
+    binds
+}
 
-    let mut app_func_addresses: MutMap<u64, &str> = MutMap::default();
-    let mut macho_load_so_offset = None;
+/// The simplest opcodes that bind `binds`. Not as compact as what `ld` makes, but it doesn't need to be.
+fn encode_binds(binds: &[Bind]) -> Vec<u8> {
+    let mut out = Vec::new();
+
+    for bind in binds {
+        match bind.ordinal {
+            ordinal if ordinal <= 0 => out.push(
+                macho::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM
+                    | (ordinal as u8 & macho::BIND_IMMEDIATE_MASK),
+            ),
+            ordinal if ordinal <= macho::BIND_IMMEDIATE_MASK as i64 => {
+                out.push(macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM | ordinal as u8)
+            }
+            ordinal => {
+                out.push(macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB);
+                write_uleb128(&mut out, ordinal as u64);
+            }
+        }
 
-    {
-        use macho::{DyldInfoCommand, DylibCommand, Section64, SegmentCommand64};
+        out.push(macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM | bind.flags);
+        out.extend_from_slice(bind.symbol);
+        out.push(0);
 
-        let exec_header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
-        let num_load_cmds = exec_header.ncmds.get(LE);
+        out.push(macho::BIND_OPCODE_SET_TYPE_IMM | bind.kind);
 
-        let mut offset = mem::size_of_val(exec_header);
+        out.push(macho::BIND_OPCODE_SET_ADDEND_SLEB);
+        write_sleb128(&mut out, bind.addend);
 
-        let mut stubs_symbol_index = None;
-        let mut stubs_symbol_count = None;
+        out.push(macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | bind.segment);
+        write_uleb128(&mut out, bind.offset);
 
-        'cmds: for _ in 0..num_load_cmds {
-            let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, offset);
-            let cmd = info.cmd.get(LE);
-            let cmdsize = info.cmdsize.get(LE);
+        out.push(macho::BIND_OPCODE_DO_BIND);
+    }
 
-            if cmd == macho::LC_SEGMENT_64 {
-                let info = load_struct_inplace::<SegmentCommand64<LE>>(exec_data, offset);
+    out.push(macho::BIND_OPCODE_DONE);
 
-                if &info.segname[0..6] == b"__TEXT" {
-                    let sections = info.nsects.get(LE);
+    out
+}
 
-                    let sections_info = load_structs_inplace::<Section64<LE>>(
-                        exec_data,
-                        offset + mem::size_of_val(info),
-                        sections as usize,
-                    );
+/// Lower the dylib ordinal that the opcode at `at` sets by one, without changing the opcode's length.
+fn decrement_bind_ordinal(stream: &mut [u8], at: usize) {
+    match stream[at] & macho::BIND_OPCODE_MASK {
+        macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => stream[at] -= 1,
+        macho::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+            let mut pos = at + 1;
+            let ordinal = read_uleb128(stream, &mut pos);
+            overwrite_uleb128(stream, at + 1, ordinal - 1);
+        }
+        other => internal_error!("Expected a dylib ordinal opcode, found {:#x}", other),
+    }
+}
 
-                    for section_info in sections_info {
-                        if &section_info.sectname[0..7] == b"__stubs" {
-                            stubs_symbol_index = Some(section_info.reserved1.get(LE));
-                            stubs_symbol_count =
-                                Some(section_info.size.get(LE) / STUB_ADDRESS_OFFSET);
+/// Extend the rebase opcodes with rebases of the pointers at `slots` (segment index and offset).
+fn append_rebases(stream: &[u8], slots: &[(u8, u64)]) -> Vec<u8> {
+    // Find the `DONE` that ends the existing opcodes; anything after it is padding.
+    let mut pos = 0;
+    while pos < stream.len() {
+        let opcode = stream[pos] & macho::REBASE_OPCODE_MASK;
+        if opcode == macho::REBASE_OPCODE_DONE {
+            break;
+        }
+        pos += 1;
+
+        match opcode {
+            macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB
+            | macho::REBASE_OPCODE_ADD_ADDR_ULEB
+            | macho::REBASE_OPCODE_DO_REBASE_ULEB_TIMES
+            | macho::REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB => {
+                read_uleb128(stream, &mut pos);
+            }
+            macho::REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB => {
+                read_uleb128(stream, &mut pos);
+                read_uleb128(stream, &mut pos);
+            }
+            _ => {}
+        }
+    }
 
-                            break 'cmds;
-                        }
-                    }
+    let mut out = stream[..pos].to_vec();
+    out.push(macho::REBASE_OPCODE_SET_TYPE_IMM | macho::REBASE_TYPE_POINTER);
+    for (segment, offset) in slots {
+        out.push(macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | segment);
+        write_uleb128(&mut out, *offset);
+        out.push(macho::REBASE_OPCODE_DO_REBASE_IMM_TIMES | 1);
+    }
+    out.push(macho::REBASE_OPCODE_DONE);
+
+    out
+}
+
+/// The ordinal and name of every symbol that the chained fixups import.
+fn chained_imports(fixups: &[u8]) -> Vec<(u32, &[u8])> {
+    let imports_offset = read_u32(fixups, 8) as usize;
+    let symbols_offset = read_u32(fixups, 12) as usize;
+    let imports_count = read_u32(fixups, 16) as usize;
+    let imports_format = read_u32(fixups, 20);
+
+    (0..imports_count)
+        .map(|i| {
+            let (ordinal, name_offset) = match imports_format {
+                DYLD_CHAINED_IMPORT => {
+                    let import = read_u32(fixups, imports_offset + i * 4);
+                    (import & 0xff, import >> 9)
                 }
-            }
+                DYLD_CHAINED_IMPORT_ADDEND => {
+                    let import = read_u32(fixups, imports_offset + i * 8);
+                    (import & 0xff, import >> 9)
+                }
+                DYLD_CHAINED_IMPORT_ADDEND64 => {
+                    let import = read_u64(fixups, imports_offset + i * 16);
+                    ((import & 0xffff) as u32, (import >> 32) as u32)
+                }
+                other => internal_error!("Unknown chained fixups import format {}", other),
+            };
+
+            let name = &fixups[symbols_offset + name_offset as usize..];
+            let len = name
+                .iter()
+                .position(|byte| *byte == 0)
+                .unwrap_or(name.len());
 
-            offset += cmdsize as usize;
+            (ordinal, &name[..len])
+        })
+        .collect()
+}
+
+/// Account for a removed dylib in the imports of the chained fixups.
+///
+/// dyld looks up every import when it loads the executable, even ones that nothing binds to anymore.
+/// Imports from the removed dylib are turned into weak flat lookups, which dyld is fine not finding.
+fn fix_chained_import_ordinals(fixups: &mut [u8], removed_ordinal: u32) {
+    let imports_offset = read_u32(fixups, 8) as usize;
+    let imports_count = read_u32(fixups, 16) as usize;
+    let imports_format = read_u32(fixups, 20);
+
+    for i in 0..imports_count {
+        match imports_format {
+            DYLD_CHAINED_IMPORT | DYLD_CHAINED_IMPORT_ADDEND => {
+                let stride = if imports_format == DYLD_CHAINED_IMPORT {
+                    4
+                } else {
+                    8
+                };
+                let at = imports_offset + i * stride;
+                let import = read_u32(fixups, at);
+                let ordinal = import & 0xff;
+                // 8 bit ordinals from 0xf0 up are the negative special ordinals.
+                let import = if ordinal == removed_ordinal {
+                    let flat_lookup = macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as u8 as u32;
+                    (import & !0x1ff) | 0x100 | flat_lookup
+                } else if ordinal > removed_ordinal && ordinal < 0xf0 {
+                    import - 1
+                } else {
+                    import
+                };
+                write_u32(fixups, at, import);
+            }
+            DYLD_CHAINED_IMPORT_ADDEND64 => {
+                let at = imports_offset + i * 16;
+                let import = read_u64(fixups, at);
+                let ordinal = (import & 0xffff) as u32;
+                let import = if ordinal == removed_ordinal {
+                    let flat_lookup = macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as u16 as u64;
+                    (import & !0x1_ffff) | 0x1_0000 | flat_lookup
+                } else if ordinal > removed_ordinal && ordinal < 0xfff0 {
+                    import - 1
+                } else {
+                    import
+                };
+                write_u64(fixups, at, import);
+            }
+            other => internal_error!("Unknown chained fixups import format {}", other),
         }
+    }
+}
 
-        let stubs_symbol_index = stubs_symbol_index.unwrap_or_else(|| {
-            panic!("Could not find stubs symbol index.");
-        });
-        let stubs_symbol_count = stubs_symbol_count.unwrap_or_else(|| {
-            panic!("Could not find stubs symbol count.");
-        });
+/// Copy the chained fixups with an extra (empty) entry for a new segment at `segment_index`.
+/// dyld checks that there are exactly as many entries as there are segments.
+fn add_chained_fixups_segment(fixups: &[u8], segment_index: usize) -> Vec<u8> {
+    let starts_offset = read_u32(fixups, 4) as usize;
+    let seg_count = read_u32(fixups, starts_offset) as usize;
+    let seg_info_offsets = starts_offset + 4;
+    let seg_info_offsets_end = seg_info_offsets + seg_count * 4;
+
+    // The new entry only needs 4 bytes, but the segment starts after it contain u64s, so keep them aligned.
+    let growth = 8;
+
+    let mut out = Vec::with_capacity(fixups.len() + growth);
+    out.extend_from_slice(&fixups[..starts_offset]);
+    out.extend_from_slice(&(seg_count as u32 + 1).to_le_bytes());
+    for i in 0..=seg_count {
+        if i == segment_index {
+            out.extend_from_slice(&0u32.to_le_bytes());
+        }
+        if i < seg_count {
+            let seg_info_offset = read_u32(fixups, seg_info_offsets + i * 4);
+            let seg_info_offset = if seg_info_offset == 0 {
+                0
+            } else {
+                seg_info_offset + growth as u32
+            };
+            out.extend_from_slice(&seg_info_offset.to_le_bytes());
+        }
+    }
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&fixups[seg_info_offsets_end..]);
+
+    // The imports and their names come after the segment starts.
+    for field in [8, 12] {
+        let offset = read_u32(&out, field);
+        if offset as usize >= seg_info_offsets_end {
+            write_u32(&mut out, field, offset + growth as u32);
+        }
+    }
 
-        // Reset offset before looping through load commands again
-        offset = mem::size_of_val(exec_header);
+    out
+}
 
-        let shared_lib_filename = shared_lib.file_name();
+/// What preprocessing needs to know about a segment of the host.
+struct SegmentInfo {
+    cmd_offset: usize,
+    name: [u8; 16],
+    vmaddr: u64,
+    fileoff: u64,
+    filesize: u64,
+}
 
-        for _ in 0..num_load_cmds {
-            let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, offset);
-            let cmd = info.cmd.get(LE);
-            let cmdsize = info.cmdsize.get(LE);
+/// The `__stubs` section, which is where calls to dylib functions go. It is the Mach-O PLT.
+struct StubsInfo {
+    address: u64,
+    offset: u64,
+    size: u64,
+    first_indirect_symbol: u32,
+    stub_size: u32,
+}
 
-            if cmd == macho::LC_DYLD_INFO_ONLY {
-                let info = load_struct_inplace::<DyldInfoCommand<LE>>(exec_data, offset);
+/// The load commands of the host that preprocessing cares about.
+#[derive(Default)]
+struct LoadCommands {
+    segments: Vec<SegmentInfo>,
+    /// The offset and size of every command that loads a dylib, in ordinal order.
+    dylibs: Vec<(usize, usize)>,
+    stubs: Option<StubsInfo>,
+    symtab: Option<usize>,
+    dysymtab: Option<usize>,
+    dyld_info: Option<usize>,
+    chained_fixups: Option<usize>,
+    code_signature: Option<usize>,
+    /// Where the first section's data starts; the load commands can grow up to here.
+    first_section_offset: u64,
+}
 
-                let lazy_bind_offset = info.lazy_bind_off.get(LE) as usize;
+impl LoadCommands {
+    fn parse(exec_data: &[u8]) -> Self {
+        let mut commands = LoadCommands {
+            first_section_offset: exec_data.len() as u64,
+            ..Default::default()
+        };
 
-                let lazy_bind_symbols = mach_object::LazyBind::parse(
-                    &exec_data[lazy_bind_offset..],
-                    mem::size_of::<usize>(),
-                );
+        for (offset, cmd, cmd_size) in load_commands(exec_data) {
+            match cmd {
+                macho::LC_SEGMENT_64 => {
+                    let segment =
+                        load_struct_inplace::<macho::SegmentCommand64<LE>>(exec_data, offset);
+                    let sections = load_structs_inplace::<macho::Section64<LE>>(
+                        exec_data,
+                        offset + mem::size_of_val(segment),
+                        segment.nsects.get(LE) as usize,
+                    );
 
-                // Find all the lazily-bound roc symbols
-                // (e.g. "_roc__mainForHost_1_exposed")
-                // For Macho, we may need to deal with some GOT stuff here as well.
-                for (i, symbol) in lazy_bind_symbols
-                    .skip(stubs_symbol_index as usize)
-                    .take(stubs_symbol_count as usize)
-                    .enumerate()
-                {
-                    if let Some(sym) = app_syms
-                        .iter()
-                        .find(|app_sym| app_sym.name() == Ok(&symbol.name))
-                    {
-                        let func_address = (i as u64 + 1) * STUB_ADDRESS_OFFSET + plt_address;
-                        let func_offset = (i as u64 + 1) * STUB_ADDRESS_OFFSET + plt_offset;
-                        app_func_addresses.insert(func_address, sym.name().unwrap());
-                        md.plt_addresses
-                            .insert(sym.name().unwrap().to_string(), (func_offset, func_address));
-                    }
-                }
-            } else if cmd == macho::LC_LOAD_DYLIB {
-                let info = load_struct_inplace::<DylibCommand<LE>>(exec_data, offset);
-                let name_offset = info.dylib.name.offset.get(LE) as usize;
-                let str_start_index = offset + name_offset;
-                let str_end_index = offset + cmdsize as usize;
-                let str_bytes = &exec_data[str_start_index..str_end_index];
-                let path = {
-                    if str_bytes[str_bytes.len() - 1] == 0 {
-                        // If it's nul-terminated, it's a C String.
-                        // Use the unchecked version because these are
-                        // padded with 0s at the end, so since we don't
-                        // know the exact length, using the checked version
-                        // of this can fail due to the interior nul bytes.
-                        //
-                        // Also, we have to use from_ptr instead of
-                        // from_bytes_with_nul_unchecked because currently
-                        // std::ffi::CStr is actually not a char* under
-                        // the hood (!) but rather an array, so to strip
-                        // the trailing null bytes we have to use from_ptr.
-                        let c_str = unsafe { CStr::from_ptr(str_bytes.as_ptr() as *const i8) };
-
-                        Path::new(c_str.to_str().unwrap())
-                    } else {
-                        // It wasn't nul-terminated, so treat all the bytes
-                        // as the string
+                    for section in sections {
+                        // Zero-fill sections have no data, and an offset of zero.
+                        if section.offset.get(LE) > 0 {
+                            commands.first_section_offset = commands
+                                .first_section_offset
+                                .min(section.offset.get(LE) as u64);
+                        }
 
-                        Path::new(std::str::from_utf8(str_bytes).unwrap())
+                        if section.flags.get(LE) & macho::SECTION_TYPE == macho::S_SYMBOL_STUBS {
+                            commands.stubs = Some(StubsInfo {
+                                address: section.addr.get(LE),
+                                offset: section.offset.get(LE) as u64,
+                                size: section.size.get(LE),
+                                first_indirect_symbol: section.reserved1.get(LE),
+                                stub_size: section.reserved2.get(LE),
+                            });
+                        }
                     }
-                };
 
-                if path.file_name() == shared_lib_filename {
-                    macho_load_so_offset = Some(offset);
+                    commands.segments.push(SegmentInfo {
+                        cmd_offset: offset,
+                        name: segment.segname,
+                        vmaddr: segment.vmaddr.get(LE),
+                        fileoff: segment.fileoff.get(LE),
+                        filesize: segment.filesize.get(LE),
+                    });
                 }
+                macho::LC_LOAD_DYLIB
+                | macho::LC_LOAD_WEAK_DYLIB
+                | macho::LC_REEXPORT_DYLIB
+                | macho::LC_LAZY_LOAD_DYLIB
+                | macho::LC_LOAD_UPWARD_DYLIB => commands.dylibs.push((offset, cmd_size)),
+                macho::LC_SYMTAB => commands.symtab = Some(offset),
+                macho::LC_DYSYMTAB => commands.dysymtab = Some(offset),
+                macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => commands.dyld_info = Some(offset),
+                macho::LC_DYLD_CHAINED_FIXUPS => commands.chained_fixups = Some(offset),
+                macho::LC_CODE_SIGNATURE => commands.code_signature = Some(offset),
+                _ => {}
             }
-
-            offset += cmdsize as usize;
         }
-    }
 
-    for sym in app_syms.iter() {
-        let name = sym.name().unwrap().to_string();
-        md.app_functions.push(name.clone());
-        md.dynamic_symbol_indices.insert(name, sym.index().0 as u64);
+        commands
     }
-    if verbose {
-        println!();
-        println!("PLT Symbols for App Functions");
-        for symbol in app_syms.iter() {
-            println!("{}: {:+x?}", symbol.index().0, symbol);
-        }
 
-        println!();
-        println!("App Function Address Map: {:+x?}", app_func_addresses);
+    fn segment(&self, name: &[u8]) -> Option<(usize, &SegmentInfo)> {
+        self.segments
+            .iter()
+            .enumerate()
+            .find(|(_, segment)| segment_name(&segment.name) == name)
     }
-    let symbol_and_plt_processing_duration = symbol_and_plt_processing_start.elapsed();
 
-    // look at the text (i.e. code) sections and see collect work needs to be done
-    let text_disassembly_start = Instant::now();
-
-    let mut surgeries = Surgeries::new(&app_syms, app_func_addresses);
-    surgeries.append_text_sections(exec_data, &exec_obj, verbose);
-    md.surgeries = surgeries.surgeries;
+    fn file_offset(&self, segment: usize, offset: u64) -> u64 {
+        match self.segments.get(segment) {
+            Some(segment) => segment.fileoff + offset,
+            None => internal_error!("Fixup refers to segment {}, which does not exist", segment),
+        }
+    }
+}
 
-    let text_disassembly_duration = text_disassembly_start.elapsed();
+fn segment_name(name: &[u8; 16]) -> &[u8] {
+    let len = name
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(name.len());
+    &name[..len]
+}
 
-    let scanning_dynamic_deps_duration;
-    let platform_gen_start;
+/// The offset, kind and size of every load command.
+fn load_commands(exec_data: &[u8]) -> Vec<(usize, u32, usize)> {
+    let header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
+    let mut offset = mem::size_of_val(header);
 
-    let out_mmap = {
-        match target
-            .endianness()
-            .unwrap_or(target_lexicon::Endianness::Little)
-        {
-            target_lexicon::Endianness::Little => {
-                let scanning_dynamic_deps_start = Instant::now();
+    (0..header.ncmds.get(LE))
+        .map(|_| {
+            let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, offset);
+            let cmd_size = info.cmdsize.get(LE) as usize;
+            let command = (offset, info.cmd.get(LE), cmd_size);
+            offset += cmd_size;
+            command
+        })
+        .collect()
+}
 
-                // let ElfDynamicDeps {
-                //     got_app_syms,
-                //     got_sections,
-                //     dynamic_lib_count,
-                //     shared_lib_index,
-                // } = scan_elf_dynamic_deps(
-                //     &exec_obj, &mut md, &app_syms, shared_lib, exec_data, verbose,
-                // );
+fn dylib_path(exec_data: &[u8], offset: usize, cmd_size: usize) -> &Path {
+    let info = load_struct_inplace::<macho::DylibCommand<LE>>(exec_data, offset);
+    let name_offset = info.dylib.name.offset.get(LE) as usize;
+    let str_bytes = &exec_data[offset + name_offset..offset + cmd_size];
+
+    if str_bytes[str_bytes.len() - 1] == 0 {
+        // If it's nul-terminated, it's a C String.
+        // Use the unchecked version because these are
+        // padded with 0s at the end, so since we don't
+        // know the exact length, using the checked version
+        // of this can fail due to the interior nul bytes.
+        //
+        // Also, we have to use from_ptr instead of
+        // from_bytes_with_nul_unchecked because currently
+        // std::ffi::CStr is actually not a char* under
+        // the hood (!) but rather an array, so to strip
+        // the trailing null bytes we have to use from_ptr.
+        let c_str = unsafe { CStr::from_ptr(str_bytes.as_ptr() as *const c_char) };
+
+        Path::new(c_str.to_str().unwrap())
+    } else {
+        // It wasn't nul-terminated, so treat all the bytes
+        // as the string
 
-                scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
+        Path::new(std::str::from_utf8(str_bytes).unwrap())
+    }
+}
 
-                platform_gen_start = Instant::now();
+/// A piece of `__LINKEDIT` that preprocessing rewrote. It won't fit where the original was,
+/// so it goes at the end of `__LINKEDIT`.
+struct LinkeditReplacement {
+    /// Where the file offset of this data is in the load commands. Its size comes right after it.
+    field_offset: usize,
+    data: Vec<u8>,
+}
 
-                // TODO little endian
-                let macho_load_so_offset = match macho_load_so_offset {
-                    Some(offset) => offset,
-                    None => {
-                        internal_error!("Host does not link library `{}`!", shared_lib.display());
-                    }
-                };
+struct MachoDynamicDeps {
+    /// The command that loads the shared library standing in for the app, which we remove.
+    shared_lib_cmd: (usize, usize),
+    linkedit_replacements: Vec<LinkeditReplacement>,
+    /// Opcodes in the lazy binding info whose ordinal has to go down by one.
+    lazy_ordinal_fixes: Vec<usize>,
+}
 
-                // TODO this is correct on modern Macs (they align to the page size)
-                // but maybe someone can override the alignment somehow? Maybe in the
-                // future this could change? Is there some way to make this more future-proof?
-                md.load_align_constraint = 4096;
-
-                gen_macho_le(
-                    exec_data,
-                    &mut md,
-                    preprocessed_path,
-                    macho_load_so_offset,
-                    target,
-                    verbose,
-                )
-            }
-            target_lexicon::Endianness::Big => {
-                // TODO Is big-endian macOS even a thing that exists anymore?
-                // Just ancient PowerPC machines maybe?
-                todo!("Roc does not yet support big-endian macOS hosts!");
+/// Find everywhere dyld would bind a pointer to the app, and rewrite the binding info so that it
+/// no longer refers to the shared library standing in for the app.
+fn scan_macho_dynamic_deps(
+    exec_data: &[u8],
+    commands: &LoadCommands,
+    md: &mut Metadata,
+    shared_lib: &Path,
+    verbose: bool,
+) -> MachoDynamicDeps {
+    let shared_lib_filename = shared_lib.file_name();
+
+    let (removed_ordinal, shared_lib_cmd) =
+        match commands
+            .dylibs
+            .iter()
+            .enumerate()
+            .find(|(_, (offset, size))| {
+                dylib_path(exec_data, *offset, *size).file_name() == shared_lib_filename
+            }) {
+            Some((i, cmd)) => (i as u32 + 1, *cmd),
+            None => {
+                internal_error!("Host does not link library `{}`!", shared_lib.display());
             }
-        }
-    };
-
-    let platform_gen_duration = platform_gen_start.elapsed();
+        };
 
     if verbose {
         println!();
-        println!("{:+x?}", md);
+        println!(
+            "Removing dylib `{}` with ordinal {}",
+            shared_lib.display(),
+            removed_ordinal
+        );
     }
 
-    let saving_metadata_start = Instant::now();
-    md.write_to_file(metadata_path);
-    let saving_metadata_duration = saving_metadata_start.elapsed();
+    let mut linkedit_replacements = Vec::new();
+    let mut lazy_ordinal_fixes = Vec::new();
 
-    let flushing_data_start = Instant::now();
-    out_mmap
-        .flush()
-        .unwrap_or_else(|e| internal_error!("{}", e));
-    // Also drop files to to ensure data is fully written here.
-    drop(out_mmap);
+    if let Some(cmd_offset) = commands.chained_fixups {
+        let cmd = load_struct_inplace::<macho::LinkeditDataCommand<LE>>(exec_data, cmd_offset);
+        let fixups = &exec_data[cmd.dataoff.get(LE) as usize..][..cmd.datasize.get(LE) as usize];
+
+        let app_imports: MutMap<u32, &str> = chained_imports(fixups)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, (ordinal, _))| *ordinal == removed_ordinal)
+            .map(|(i, (_, name))| (i as u32, std::str::from_utf8(name).unwrap()))
+            .collect();
+
+        let mut pointer_format = None;
+
+        // Walk every chain, looking for binds to the app.
+        let starts_offset = read_u32(fixups, 4) as usize;
+        let seg_count = read_u32(fixups, starts_offset) as usize;
+        for segment in 0..seg_count {
+            let seg_info_offset = read_u32(fixups, starts_offset + 4 + segment * 4) as usize;
+            if seg_info_offset == 0 {
+                continue;
+            }
+
+            let starts = starts_offset + seg_info_offset;
+            let page_size = read_u16(fixups, starts + 4) as u64;
+            let format = read_u16(fixups, starts + 6);
+            let page_count = read_u16(fixups, starts + 20) as usize;
+
+            match pointer_format {
+                None => pointer_format = Some(format),
+                Some(other) if other != format => {
+                    internal_error!(
+                        "Mixed chained fixup pointer formats {} and {}",
+                        other,
+                        format
+                    )
+                }
+                Some(_) => {}
+            }
+
+            for page in 0..page_count {
+                let page_start = read_u16(fixups, starts + 22 + page * 2);
+                if page_start == DYLD_CHAINED_PTR_START_NONE {
+                    continue;
+                }
+
+                let mut offset = commands
+                    .file_offset(segment, page as u64 * page_size + page_start as u64)
+                    as usize;
+
+                loop {
+                    let pointer = read_u64(exec_data, offset);
+                    let is_bind = pointer >> 63 == 1;
+                    let next = (pointer >> 51) & 0xfff;
+
+                    if is_bind {
+                        let import = (pointer & 0xff_ffff) as u32;
+                        if let Some(name) = app_imports.get(&import) {
+                            md.app_pointers
+                                .entry(name.to_string())
+                                .or_default()
+                                .push(offset as u64);
+                        }
+                    }
+
+                    if next == 0 {
+                        break;
+                    }
+                    offset += next as usize * 4;
+                }
+            }
+        }
+
+        md.pointer_format = match pointer_format {
+            None | Some(DYLD_CHAINED_PTR_64) => PointerFormat::Chained64,
+            Some(DYLD_CHAINED_PTR_64_OFFSET) => PointerFormat::Chained64Offset,
+            Some(other) => {
+                internal_error!("Unsupported chained fixup pointer format {}", other);
+            }
+        };
+
+        // The app gets a segment of its own, right before __LINKEDIT.
+        let (linkedit_index, _) = commands
+            .segment(b"__LINKEDIT")
+            .unwrap_or_else(|| internal_error!("The host has no __LINKEDIT segment"));
+        let mut fixups = add_chained_fixups_segment(fixups, linkedit_index);
+        fix_chained_import_ordinals(&mut fixups, removed_ordinal);
+
+        linkedit_replacements.push(LinkeditReplacement {
+            field_offset: cmd_offset + 8,
+            data: fixups,
+        });
+    } else if let Some(cmd_offset) = commands.dyld_info {
+        md.pointer_format = PointerFormat::Raw;
+
+        let cmd = load_struct_inplace::<macho::DyldInfoCommand<LE>>(exec_data, cmd_offset);
+
+        // Binds to the app become rebases, because the pointers will point into the executable itself.
+        let bind_stream =
+            &exec_data[cmd.bind_off.get(LE) as usize..][..cmd.bind_size.get(LE) as usize];
+        let mut binds = Vec::new();
+        let mut app_slots = Vec::new();
+        for mut bind in decode_binds(bind_stream, false) {
+            if bind.ordinal == removed_ordinal as i64 {
+                let name = std::str::from_utf8(bind.symbol).unwrap().to_string();
+                let offset = commands.file_offset(bind.segment as usize, bind.offset);
+                md.app_pointers.entry(name).or_default().push(offset);
+                app_slots.push((bind.segment, bind.offset));
+            } else {
+                if bind.ordinal > removed_ordinal as i64 {
+                    bind.ordinal -= 1;
+                }
+                binds.push(bind);
+            }
+        }
+
+        linkedit_replacements.push(LinkeditReplacement {
+            field_offset: cmd_offset + 16,
+            data: encode_binds(&binds),
+        });
+
+        if !app_slots.is_empty() {
+            let rebase_stream =
+                &exec_data[cmd.rebase_off.get(LE) as usize..][..cmd.rebase_size.get(LE) as usize];
+
+            linkedit_replacements.push(LinkeditReplacement {
+                field_offset: cmd_offset + 8,
+                data: append_rebases(rebase_stream, &app_slots),
+            });
+        }
+
+        // Stubs jump through lazy pointers, which are rebased already. The stubs to the app will be
+        // replaced by direct jumps, but point the lazy pointers at the app as well, in case something
+        // reads them. Lazy binds are referred to by their offset from the stub helper, so they can
+        // only be patched in place.
+        let lazy_bind_off = cmd.lazy_bind_off.get(LE) as usize;
+        let lazy_bind_stream = &exec_data[lazy_bind_off..][..cmd.lazy_bind_size.get(LE) as usize];
+        let mut fixed_ordinals = MutSet::default();
+        for bind in decode_binds(lazy_bind_stream, true) {
+            if bind.ordinal == removed_ordinal as i64 {
+                let name = std::str::from_utf8(bind.symbol).unwrap().to_string();
+                let offset = commands.file_offset(bind.segment as usize, bind.offset);
+                md.app_pointers.entry(name).or_default().push(offset);
+            } else if bind.ordinal > removed_ordinal as i64
+                && fixed_ordinals.insert(bind.ordinal_at)
+            {
+                lazy_ordinal_fixes.push(lazy_bind_off + bind.ordinal_at);
+            }
+        }
+    } else {
+        internal_error!("The host has neither LC_DYLD_CHAINED_FIXUPS nor LC_DYLD_INFO");
+    }
+
+    if verbose {
+        println!("App pointers: {:+x?}", md.app_pointers);
+    }
+
+    MachoDynamicDeps {
+        shared_lib_cmd,
+        linkedit_replacements,
+        lazy_ordinal_fixes,
+    }
+}
+
+/// Constructs a `Metadata` from a host executable binary, and writes it to disk
+pub(crate) fn preprocess_macho(
+    target: &Triple,
+    host_exe_path: &Path,
+    metadata_path: &Path,
+    preprocessed_path: &Path,
+    shared_lib: &Path,
+    verbose: bool,
+    time: bool,
+) {
+    let total_start = Instant::now();
+    let exec_parsing_start = total_start;
+    let exec_data = &*open_mmap(host_exe_path);
+    let exec_obj = match object::File::parse(exec_data) {
+        Ok(obj) => obj,
+        Err(err) => {
+            internal_error!("Failed to parse executable file: {}", err);
+        }
+    };
+
+    if !exec_obj.is_64() || !exec_obj.is_little_endian() {
+        internal_error!("Only 64bit little endian Mach-O currently supported for surgery");
+    }
+
+    let cpu = MachoCpu::from_architecture(exec_obj.architecture());
+    let commands = LoadCommands::parse(exec_data);
+
+    let mut md = Metadata {
+        roc_symbol_vaddresses: collect_roc_definitions(&exec_obj),
+        load_align_constraint: cpu.page_size(),
+        ..Default::default()
+    };
+
+    if verbose {
+        println!(
+            "Found roc symbol definitions: {:+x?}",
+            md.roc_symbol_vaddresses
+        );
+    }
+
+    let exec_parsing_duration = exec_parsing_start.elapsed();
+
+    // The stubs are Mach-O's PLT: small functions that jump to wherever dyld resolved a symbol to.
+    // Which symbol each stub is for is in the indirect symbol table.
+    let symbol_and_plt_processing_start = Instant::now();
+
+    let stubs = match &commands.stubs {
+        Some(stubs) => stubs,
+        None => {
+            internal_error!(
+                "Failed to find the __stubs section. Probably an malformed executable."
+            );
+        }
+    };
+    md.stub_size = stubs.stub_size as u64;
+
+    if verbose {
+        println!("Stubs Address: {:+x}", stubs.address);
+        println!("Stubs File Offset: {:+x}", stubs.offset);
+        println!("Stub Size: {}", stubs.stub_size);
+    }
+
+    let indirect_symbols = match commands.dysymtab {
+        Some(offset) => {
+            let cmd = load_struct_inplace::<macho::DysymtabCommand<LE>>(exec_data, offset);
+            load_structs_inplace::<u32>(
+                exec_data,
+                cmd.indirectsymoff.get(LE) as usize,
+                cmd.nindirectsyms.get(LE) as usize,
+            )
+        }
+        None => internal_error!("The host has no LC_DYSYMTAB"),
+    };
+
+    let app_syms: Vec<_> = exec_obj.symbols().filter(is_roc_undefined).collect();
+
+    let mut app_func_addresses: MutMap<u64, &str> = MutMap::default();
+
+    let stub_count = stubs.size / stubs.stub_size as u64;
+    for i in 0..stub_count {
+        let symbol_index =
+            u32::from_le(indirect_symbols[stubs.first_indirect_symbol as usize + i as usize]);
+        if symbol_index & (macho::INDIRECT_SYMBOL_LOCAL | macho::INDIRECT_SYMBOL_ABS) != 0 {
+            continue;
+        }
+
+        if let Ok(sym) = exec_obj.symbol_by_index(SymbolIndex(symbol_index as usize)) {
+            if is_roc_undefined(&sym) {
+                let name = sym.name().unwrap();
+                let func_address = stubs.address + i * stubs.stub_size as u64;
+                let func_offset = stubs.offset + i * stubs.stub_size as u64;
+                app_func_addresses.insert(func_address, name);
+                md.plt_addresses
+                    .insert(name.to_string(), (func_offset, func_address));
+            }
+        }
+    }
+
+    for sym in app_syms.iter() {
+        md.app_functions.push(sym.name().unwrap().to_string());
+    }
+    if verbose {
+        println!();
+        println!("Stub Symbols for App Functions");
+        for symbol in app_syms.iter() {
+            println!("{}: {:+x?}", symbol.index().0, symbol);
+        }
+
+        println!();
+        println!("App Function Address Map: {:+x?}", app_func_addresses);
+    }
+    let symbol_and_plt_processing_duration = symbol_and_plt_processing_start.elapsed();
+
+    // look at the text (i.e. code) sections and see collect work needs to be done
+    let text_disassembly_start = Instant::now();
+
+    let mut surgeries = Surgeries::new(cpu, &app_syms, app_func_addresses);
+    surgeries.append_text_sections(exec_data, &exec_obj, verbose);
+    md.surgeries = surgeries.surgeries;
+
+    let text_disassembly_duration = text_disassembly_start.elapsed();
+
+    let scanning_dynamic_deps_duration;
+    let platform_gen_start;
+
+    let out_mmap = match target
+        .endianness()
+        .unwrap_or(target_lexicon::Endianness::Little)
+    {
+        target_lexicon::Endianness::Little => {
+            let scanning_dynamic_deps_start = Instant::now();
+
+            let deps = scan_macho_dynamic_deps(exec_data, &commands, &mut md, shared_lib, verbose);
+
+            scanning_dynamic_deps_duration = scanning_dynamic_deps_start.elapsed();
+
+            platform_gen_start = Instant::now();
+
+            gen_macho_le(
+                exec_data,
+                &mut md,
+                preprocessed_path,
+                &commands,
+                &deps,
+                verbose,
+            )
+        }
+        target_lexicon::Endianness::Big => {
+            // TODO Is big-endian macOS even a thing that exists anymore?
+            // Just ancient PowerPC machines maybe?
+            todo!("Roc does not yet support big-endian macOS hosts!");
+        }
+    };
+
+    let platform_gen_duration = platform_gen_start.elapsed();
+
+    if verbose {
+        println!();
+        println!("{:+x?}", md);
+    }
+
+    let saving_metadata_start = Instant::now();
+    md.write_to_file(metadata_path);
+    let saving_metadata_duration = saving_metadata_start.elapsed();
+
+    let flushing_data_start = Instant::now();
+    out_mmap
+        .flush()
+        .unwrap_or_else(|e| internal_error!("{}", e));
+    // Also drop files to to ensure data is fully written here.
+    drop(out_mmap);
     let flushing_data_duration = flushing_data_start.elapsed();
 
     let total_duration = total_start.elapsed();
@@ -654,9 +1509,9 @@ fn gen_macho_le(
     exec_data: &[u8],
     md: &mut Metadata,
     out_filename: &Path,
-    macho_load_so_offset: usize,
-    _target: &Triple,
-    _verbose: bool,
+    commands: &LoadCommands,
+    deps: &MachoDynamicDeps,
+    verbose: bool,
 ) -> MmapMut {
     // Just adding some extra context/useful info here.
     // I was talking to Jakub from the Zig team about macho linking and here are some useful comments:
@@ -668,470 +1523,587 @@ fn gen_macho_le(
     // 3) Jakub wants to make apple tooling absolute is working on zignature for code signing and zig-deploy for ios apps
     // https://github.com/kubkon/zignature
     // https://github.com/kubkon/zig-deploy
+    //
+    // Nothing in the host moves. Linkers leave room after the load commands, which is where the
+    // command for the app's segment goes. The app itself goes between the last segment and
+    // __LINKEDIT, which surgery moves to make room. Only __LINKEDIT is referred to by file offset,
+    // so moving it is a matter of updating the load commands.
+
+    let (_, linkedit) = commands
+        .segment(b"__LINKEDIT")
+        .unwrap_or_else(|| internal_error!("The host has no __LINKEDIT segment"));
+    let (_, text) = commands
+        .segment(b"__TEXT")
+        .unwrap_or_else(|| internal_error!("The host has no __TEXT segment"));
+    md.image_base = text.vmaddr;
+
+    // The code signature has to be at the very end, and we'll make a new one anyway.
+    let mut linkedit_end = (linkedit.fileoff + linkedit.filesize) as usize;
+    if let Some(offset) = commands.code_signature {
+        let cmd = load_struct_inplace::<macho::LinkeditDataCommand<LE>>(exec_data, offset);
+        if cmd.datasize.get(LE) > 0 {
+            linkedit_end = linkedit_end.min(cmd.dataoff.get(LE) as usize);
+        }
+    }
 
-    use macho::{Section64, SegmentCommand64};
-
-    let exec_header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_data, 0);
-    let num_load_cmds = exec_header.ncmds.get(LE);
-    let size_of_cmds = exec_header.sizeofcmds.get(LE) as usize;
-
-    // Add a new text segment and data segment
-    let segment_cmd_size = mem::size_of::<SegmentCommand64<LE>>();
-    let section_size = mem::size_of::<Section64<LE>>();
-
-    // We need the full command size, including the dynamic-length string at the end.
-    // To get that, we need to load the command.
-    let info = load_struct_inplace::<macho::LoadCommand<LE>>(exec_data, macho_load_so_offset);
-    let total_cmd_size = info.cmdsize.get(LE) as usize;
-
-    // ======================== Important TODO ==========================
-    // TODO: we accidentally instroduced a big change here.
-    // We use a mix of added_bytes and md.added_byte_count.
-    // Theses should proabably be the same variable.
-    // Also, I just realized that I have been shifting a lot of virtual offsets.
-    // This should not be necessary. If we add a fully 4k of buffer to the file, all of the virtual offsets will still stay aligned.
-    // So a lot of the following work can probably be commented out if we fix that.
-    // Of coruse, it will cost about 4k bytes to a final executable.
-    // This is what the elf version currently does.
-    // Theoretically it is not needed (if we update all virtual addresses in the binary),
-    // but I definitely ran into problems with elf when not adding this extra buffering.
-
-    // Copy header and shift everything to enable more program sections.
-    let added_byte_count = ((2 * segment_cmd_size) + (2 * section_size) - total_cmd_size) as u64;
-    md.added_byte_count = added_byte_count
-        // add some alignment padding
-        + (MIN_SECTION_ALIGNMENT as u64 - added_byte_count % MIN_SECTION_ALIGNMENT as u64);
-
-    md.exec_len = exec_data.len() as u64 + md.added_byte_count;
-
-    let mut out_mmap = open_mmap_mut(out_filename, md.exec_len as usize);
-    let end_of_cmds = size_of_cmds + mem::size_of_val(exec_header);
-
-    // "Delete" the dylib load command - by copying all the bytes before it
-    // and all the bytes after it, while skipping over its bytes.
-    // It has a dynamic-length string at the end that we also need to delete,
-    // in addition to the header.
-    out_mmap[..macho_load_so_offset].copy_from_slice(&exec_data[..macho_load_so_offset]);
-
-    out_mmap[macho_load_so_offset..end_of_cmds - total_cmd_size]
-        .copy_from_slice(&exec_data[macho_load_so_offset + total_cmd_size..end_of_cmds]);
-
-    // Copy the rest of the file, leaving a gap for the surgical linking to add our 2 commands
-    // (which happens after preprocessing), and some zero padding at the end for alignemnt.
-    // (It seems to cause bugs if that padding isn't there!)
-    let rest_of_data = &exec_data[end_of_cmds..];
-    let start_index = end_of_cmds + md.added_byte_count as usize;
-
-    out_mmap[start_index..start_index + rest_of_data.len()].copy_from_slice(rest_of_data);
-
-    let out_header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(&mut out_mmap, 0);
-
-    // TODO: this needs to change to adding the 2 new commands when we are ready.
-    // -1 because we're deleting 1 load command and then NOT adding 2 new ones.
+    let mut replacement_offsets = Vec::with_capacity(deps.linkedit_replacements.len());
+    let mut exec_len = linkedit_end;
+    for replacement in deps.linkedit_replacements.iter() {
+        exec_len = align_by_constraint(exec_len, mem::size_of::<u64>());
+        replacement_offsets.push(exec_len);
+        exec_len += replacement.data.len();
+    }
+    md.exec_len = exec_len as u64;
+
+    let mut out_mmap = open_mmap_mut(out_filename, exec_len);
+    out_mmap[..linkedit_end].copy_from_slice(&exec_data[..linkedit_end]);
+
+    for (replacement, offset) in deps
+        .linkedit_replacements
+        .iter()
+        .zip(replacement_offsets.into_iter())
     {
-        let added_bytes = -(total_cmd_size as isize); // TODO: Change when add the new sections.
-        out_header.ncmds.set(LE, num_load_cmds - 1);
-        out_header
-            .sizeofcmds
-            .set(LE, (size_of_cmds as isize + added_bytes) as u32);
-    }
-
-    // Go through every command and shift it by added_bytes if it's absolute, unless it's inside the command header
-    let mut offset = mem::size_of_val(exec_header);
-
-    // TODO: Add this back in the future when needed.
-    // let cpu_type = match target.architecture {
-    //     target_lexicon::Architecture::X86_64 => macho::CPU_TYPE_X86_64,
-    //     target_lexicon::Architecture::Aarch64(_) => macho::CPU_TYPE_ARM64,
-    //     _ => {
-    //         // We should have verified this via supported() before calling this function
-    //         unreachable!()
-    //     }
-    // };
-
-    // minus one because we "deleted" a load command
-    for _ in 0..(num_load_cmds - 1) {
-        let info = load_struct_inplace::<macho::LoadCommand<LE>>(&out_mmap, offset);
-        let cmd_size = info.cmdsize.get(LE) as usize;
-
-        match info.cmd.get(LE) {
-            macho::LC_SEGMENT_64 => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(&mut out_mmap, offset);
+        if verbose {
+            println!(
+                "Moving __LINKEDIT data referred to at {:+x} to {:+x}",
+                replacement.field_offset, offset
+            );
+        }
+        out_mmap[offset..][..replacement.data.len()].copy_from_slice(&replacement.data);
+        write_u32(&mut out_mmap, replacement.field_offset, offset as u32);
+        write_u32(
+            &mut out_mmap,
+            replacement.field_offset + 4,
+            replacement.data.len() as u32,
+        );
+    }
 
-                // Ignore page zero, it never moves.
-                if cmd.segname == "__PAGEZERO\0\0\0\0\0\0".as_bytes() || cmd.vmaddr.get(LE) == 0 {
-                    offset += cmd_size;
-                    continue;
-                }
+    for at in deps.lazy_ordinal_fixes.iter() {
+        decrement_bind_ordinal(&mut out_mmap, *at);
+    }
 
-                let old_file_offest = cmd.fileoff.get(LE);
-                // The segment with file offset zero also includes the header.
-                // As such, its file offset does not change.
-                // Instead, its file size should be increased.
-                if old_file_offest > 0 {
-                    cmd.fileoff.set(LE, old_file_offest + md.added_byte_count);
-                    cmd.vmaddr.set(LE, cmd.vmaddr.get(LE) + md.added_byte_count);
-                } else {
-                    cmd.filesize
-                        .set(LE, cmd.filesize.get(LE) + md.added_byte_count);
-                    cmd.vmsize.set(LE, cmd.vmsize.get(LE) + md.added_byte_count);
-                }
+    if let Some(offset) = commands.symtab {
+        fix_symbol_library_ordinals(&mut out_mmap, offset, deps.shared_lib_cmd, commands);
+    }
 
-                // let num_sections = cmd.nsects.get(LE);
-                // let sections = load_structs_inplace_mut::<macho::Section64<LE >>(
-                //     &mut out_mmap,
-                //     offset + mem::size_of::<macho::SegmentCommand64<LE >>(),
-                //     num_sections as usize,
-                // );
-                // struct Relocation {
-                //     offset: u32,
-                //     num_relocations: u32,
-                // }
-
-                // let mut relocation_offsets = Vec::with_capacity(sections.len());
-
-                // for section in sections {
-                //     section.addr.set(
-                //         LE ,
-                //         section.addr.get(LE) + md.added_byte_count as u64,
-                //     );
-
-                //     // If offset is zero, don't update it.
-                //     // Zero is used for things like BSS that don't exist in the file.
-                //     let old_offset = section.offset.get(LE);
-                //     if old_offset > 0 {
-                //         section
-                //             .offset
-                //             .set(LE , old_offset + md.added_byte_count as u32);
-                //     }
-
-                //     // dbg!(&section.reloff.get(LE));
-                //     // dbg!(section.reloff.get(LE) as i32);
-                //     // dbg!(&section);
-                //     // dbg!(&md.added_byte_count);
-                //     // dbg!(String::from_utf8_lossy(&section.sectname));
-                //     if section.nreloc.get(LE) > 0 {
-                //         section.reloff.set(
-                //             LE ,
-                //             section.reloff.get(LE) + md.added_byte_count as u32,
-                //         );
-                //     }
-
-                //     relocation_offsets.push(Relocation {
-                //         offset: section.reloff.get(LE),
-                //         num_relocations: section.nreloc.get(LE),
-                //     });
-                // }
-
-                // TODO FIXME this is necessary for ARM, but seems to be broken. Skipping for now since we're just targeting x86
-                // for Relocation {
-                //     offset,
-                //     num_relocations,
-                // } in relocation_offsets
-                // {
-                //     let relos = load_structs_inplace_mut::<macho::Relocation<LE >>(
-                //         &mut out_mmap,
-                //         offset as usize,
-                //         num_relocations as usize,
-                //     );
-
-                //     // TODO this has never been tested, because scattered relocations only come up on ARM!
-                //     for relo in relos.iter_mut() {
-                //         if relo.r_scattered(LE , cpu_type) {
-                //             let mut scattered_info = relo.scattered_info(LE);
-
-                //             if !scattered_info.r_pcrel {
-                //                 scattered_info.r_value += md.added_byte_count as u32;
-
-                //                 let new_info = scattered_info.relocation(LE );
-
-                //                 relo.r_word0 = new_info.r_word0;
-                //                 relo.r_word1 = new_info.r_word1;
-                //             }
-                //         }
-                //     }
-                // }
-
-                // TODO this seems to be wrong and unnecessary, and should probably be deleted.
-                // offset += num_sections as usize * mem::size_of::<macho::Section64<LE >>();
-            }
-            macho::LC_SYMTAB => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::SymtabCommand<LE>>(&mut out_mmap, offset);
+    rewrite_load_commands(&mut out_mmap, md, commands, deps.shared_lib_cmd, verbose);
 
-                let sym_offset = cmd.symoff.get(LE);
-                let num_syms = cmd.nsyms.get(LE);
+    out_mmap
+}
 
-                if num_syms > 0 {
-                    cmd.symoff.set(LE, sym_offset + md.added_byte_count as u32);
-                }
+/// Undefined symbols record which dylib they come from. dyld doesn't use it, but other tools do.
+fn fix_symbol_library_ordinals(
+    out_mmap: &mut [u8],
+    symtab_offset: usize,
+    shared_lib_cmd: (usize, usize),
+    commands: &LoadCommands,
+) {
+    let removed_ordinal = commands
+        .dylibs
+        .iter()
+        .position(|cmd| *cmd == shared_lib_cmd)
+        .unwrap() as u16
+        + 1;
 
-                if cmd.strsize.get(LE) > 0 {
-                    cmd.stroff
-                        .set(LE, cmd.stroff.get(LE) + md.added_byte_count as u32);
-                }
+    let (symoff, nsyms) = {
+        let cmd = load_struct_inplace::<macho::SymtabCommand<LE>>(out_mmap, symtab_offset);
+        (cmd.symoff.get(LE) as usize, cmd.nsyms.get(LE) as usize)
+    };
 
-                let table = load_structs_inplace_mut::<macho::Nlist64<LE>>(
-                    &mut out_mmap,
-                    sym_offset as usize + md.added_byte_count as usize,
-                    num_syms as usize,
-                );
+    let symbols = load_structs_inplace_mut::<macho::Nlist64<LE>>(out_mmap, symoff, nsyms);
+    for symbol in symbols {
+        if symbol.n_type & macho::N_TYPE != macho::N_UNDF || symbol.n_type & macho::N_EXT == 0 {
+            continue;
+        }
 
-                for entry in table {
-                    let entry_type = entry.n_type & macho::N_TYPE;
-                    if entry_type == macho::N_ABS || entry_type == macho::N_SECT {
-                        entry
-                            .n_value
-                            .set(LE, entry.n_value.get(LE) + md.added_byte_count);
-                    }
+        let n_desc = symbol.n_desc.get(LE);
+        let ordinal = n_desc >> 8;
+        let ordinal = if ordinal == removed_ordinal {
+            macho::DYNAMIC_LOOKUP_ORDINAL as u16
+        } else if ordinal > removed_ordinal && ordinal < macho::DYNAMIC_LOOKUP_ORDINAL as u16 {
+            ordinal - 1
+        } else {
+            ordinal
+        };
+        symbol.n_desc.set(LE, (ordinal << 8) | (n_desc & 0xff));
+    }
+}
+
+fn roc_segment_cmd_size() -> usize {
+    mem::size_of::<macho::SegmentCommand64<LE>>()
+        + ROC_SECTION_NAMES.len() * mem::size_of::<macho::Section64<LE>>()
+}
+
+/// Remove the command that loads the shared library, and add commands for the app's segment and
+/// (if the host doesn't have one yet) its code signature.
+fn rewrite_load_commands(
+    out_mmap: &mut [u8],
+    md: &mut Metadata,
+    commands: &LoadCommands,
+    shared_lib_cmd: (usize, usize),
+    verbose: bool,
+) {
+    let header_size = mem::size_of::<macho::MachHeader64<LE>>();
+    let (_, linkedit) = commands.segment(b"__LINKEDIT").unwrap();
+
+    let old_commands = load_commands(out_mmap);
+    let old_size_of_cmds = {
+        let header = load_struct_inplace::<macho::MachHeader64<LE>>(out_mmap, 0);
+        header.sizeofcmds.get(LE) as usize
+    };
+
+    let mut new_cmds = Vec::with_capacity(old_size_of_cmds + roc_segment_cmd_size());
+    let mut code_signature_cmd_offset = None;
+    for (offset, cmd, cmd_size) in old_commands.iter().copied() {
+        let bytes = &out_mmap[offset..offset + cmd_size];
+
+        if offset == shared_lib_cmd.0 {
+            continue;
+        }
+
+        if offset == linkedit.cmd_offset {
+            md.roc_segment_cmd_offset = (header_size + new_cmds.len()) as u64;
+            new_cmds.resize(new_cmds.len() + roc_segment_cmd_size(), 0);
+            md.linkedit_cmd_offset = (header_size + new_cmds.len()) as u64;
+        } else if cmd == macho::LC_CODE_SIGNATURE {
+            code_signature_cmd_offset = Some(header_size + new_cmds.len());
+        }
+
+        new_cmds.extend_from_slice(bytes);
+    }
+
+    md.code_signature_cmd_offset = match code_signature_cmd_offset {
+        Some(offset) => offset as u64,
+        None => {
+            // arm64 macOS won't run unsigned code, so make room for a signature.
+            let offset = header_size + new_cmds.len();
+            new_cmds.resize(
+                new_cmds.len() + mem::size_of::<macho::LinkeditDataCommand<LE>>(),
+                0,
+            );
+            offset as u64
+        }
+    };
+
+    let new_cmds_end = header_size + new_cmds.len();
+    if new_cmds_end as u64 > commands.first_section_offset {
+        user_error!(
+            concat!(
+                "The host executable does not have enough room after its load commands for the Roc app. ",
+                "It needs {} bytes but only has {}. Relinking the host with `-headerpad 0x1000` should fix this."
+            ),
+            new_cmds_end,
+            commands.first_section_offset,
+        );
+    }
+
+    if verbose {
+        println!();
+        println!(
+            "Load commands grow from {:+x} to {:+x} bytes",
+            old_size_of_cmds,
+            new_cmds.len()
+        );
+    }
+
+    let cmds_end = header_size + old_size_of_cmds.max(new_cmds.len());
+    out_mmap[header_size..header_size + new_cmds.len()].copy_from_slice(&new_cmds);
+    out_mmap[header_size + new_cmds.len()..cmds_end].fill(0);
+
+    // One dylib less, and a segment more.
+    let mut ncmds = old_commands.len() as u32;
+    if code_signature_cmd_offset.is_none() {
+        ncmds += 1;
+    }
+
+    let header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(out_mmap, 0);
+    header.ncmds.set(LE, ncmds);
+    header.sizeofcmds.set(LE, new_cmds.len() as u32);
+
+    // The app's segment is filled in by the surgery, once we know how big the app is.
+    {
+        let segment = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(
+            out_mmap,
+            md.roc_segment_cmd_offset as usize,
+        );
+        segment.cmd.set(LE, macho::LC_SEGMENT_64);
+        segment.cmdsize.set(LE, roc_segment_cmd_size() as u32);
+        segment.segname = ROC_SEGMENT_NAME;
+        segment.nsects.set(LE, ROC_SECTION_NAMES.len() as u32);
+    }
+
+    let sections = load_structs_inplace_mut::<macho::Section64<LE>>(
+        out_mmap,
+        md.roc_segment_cmd_offset as usize + mem::size_of::<macho::SegmentCommand64<LE>>(),
+        ROC_SECTION_NAMES.len(),
+    );
+    for (section, name) in sections.iter_mut().zip(ROC_SECTION_NAMES) {
+        section.sectname = name;
+        section.segname = ROC_SEGMENT_NAME;
+    }
+
+    {
+        let segment = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(
+            out_mmap,
+            md.linkedit_cmd_offset as usize,
+        );
+        let filesize = md.exec_len - segment.fileoff.get(LE);
+        segment.filesize.set(LE, filesize);
+        segment.vmsize.set(
+            LE,
+            align_by_constraint(filesize as usize, md.load_align_constraint as usize) as u64,
+        );
+    }
+
+    // The signature is made from scratch once the app is in.
+    let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
+        out_mmap,
+        md.code_signature_cmd_offset as usize,
+    );
+    cmd.cmd.set(LE, macho::LC_CODE_SIGNATURE);
+    cmd.cmdsize
+        .set(LE, mem::size_of::<macho::LinkeditDataCommand<LE>>() as u32);
+    cmd.dataoff.set(LE, 0);
+    cmd.datasize.set(LE, 0);
+}
+
+/// Everything in __LINKEDIT is referred to by its file offset. Move all of it by `delta` bytes.
+fn shift_linkedit_offsets(exec_mmap: &mut [u8], delta: u32) {
+    for (offset, cmd, _) in load_commands(exec_mmap) {
+        match cmd {
+            macho::LC_SYMTAB => {
+                let cmd = load_struct_inplace_mut::<macho::SymtabCommand<LE>>(exec_mmap, offset);
+
+                if cmd.nsyms.get(LE) > 0 {
+                    cmd.symoff.set(LE, cmd.symoff.get(LE) + delta);
+                }
+
+                if cmd.strsize.get(LE) > 0 {
+                    cmd.stroff.set(LE, cmd.stroff.get(LE) + delta);
                 }
             }
             macho::LC_DYSYMTAB => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::DysymtabCommand<LE>>(&mut out_mmap, offset);
+                let cmd = load_struct_inplace_mut::<macho::DysymtabCommand<LE>>(exec_mmap, offset);
 
                 if cmd.ntoc.get(LE) > 0 {
-                    cmd.tocoff
-                        .set(LE, cmd.tocoff.get(LE) + md.added_byte_count as u32);
+                    cmd.tocoff.set(LE, cmd.tocoff.get(LE) + delta);
                 }
 
                 if cmd.nmodtab.get(LE) > 0 {
-                    cmd.modtaboff
-                        .set(LE, cmd.modtaboff.get(LE) + md.added_byte_count as u32);
+                    cmd.modtaboff.set(LE, cmd.modtaboff.get(LE) + delta);
                 }
 
                 if cmd.nextrefsyms.get(LE) > 0 {
-                    cmd.extrefsymoff
-                        .set(LE, cmd.extrefsymoff.get(LE) + md.added_byte_count as u32);
+                    cmd.extrefsymoff.set(LE, cmd.extrefsymoff.get(LE) + delta);
                 }
 
                 if cmd.nindirectsyms.get(LE) > 0 {
                     cmd.indirectsymoff
-                        .set(LE, cmd.indirectsymoff.get(LE) + md.added_byte_count as u32);
+                        .set(LE, cmd.indirectsymoff.get(LE) + delta);
                 }
 
                 if cmd.nextrel.get(LE) > 0 {
-                    cmd.extreloff
-                        .set(LE, cmd.extreloff.get(LE) + md.added_byte_count as u32);
+                    cmd.extreloff.set(LE, cmd.extreloff.get(LE) + delta);
                 }
 
                 if cmd.nlocrel.get(LE) > 0 {
-                    cmd.locreloff
-                        .set(LE, cmd.locreloff.get(LE) + md.added_byte_count as u32);
+                    cmd.locreloff.set(LE, cmd.locreloff.get(LE) + delta);
                 }
-
-                // TODO maybe we need to update something else too - relocations maybe?
-                // I think this also has symbols that need to get moved around.
-                // Look at otool -I at least for the indirect symbols.
             }
-            macho::LC_TWOLEVEL_HINTS => {
-                let cmd = load_struct_inplace_mut::<macho::TwolevelHintsCommand<LE>>(
-                    &mut out_mmap,
-                    offset,
-                );
+            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
+                let cmd = load_struct_inplace_mut::<macho::DyldInfoCommand<LE>>(exec_mmap, offset);
 
-                if cmd.nhints.get(LE) > 0 {
-                    cmd.offset
-                        .set(LE, cmd.offset.get(LE) + md.added_byte_count as u32);
+                if cmd.rebase_size.get(LE) > 0 {
+                    cmd.rebase_off.set(LE, cmd.rebase_off.get(LE) + delta);
                 }
-            }
-            macho::LC_FUNCTION_STARTS => {
-                let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
-                    &mut out_mmap,
-                    offset,
-                );
 
-                if cmd.datasize.get(LE) > 0 {
-                    cmd.dataoff
-                        .set(LE, cmd.dataoff.get(LE) + md.added_byte_count as u32);
-                    // TODO: This lists the start of every function. Which, of course, have moved.
-                    // That being said, to my understanding this section is optional and may just be debug information.
-                    // As such, updating it should not be required.
-                    // It will be more work to update due to being in "DWARF-style ULEB128" values.
+                if cmd.bind_size.get(LE) > 0 {
+                    cmd.bind_off.set(LE, cmd.bind_off.get(LE) + delta);
                 }
-            }
-            macho::LC_DATA_IN_CODE => {
-                let (offset, size) = {
-                    let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
-                        &mut out_mmap,
-                        offset,
-                    );
 
-                    if cmd.datasize.get(LE) > 0 {
-                        cmd.dataoff
-                            .set(LE, cmd.dataoff.get(LE) + md.added_byte_count as u32);
-                    }
-                    (cmd.dataoff.get(LE), cmd.datasize.get(LE))
-                };
+                if cmd.weak_bind_size.get(LE) > 0 {
+                    cmd.weak_bind_off.set(LE, cmd.weak_bind_off.get(LE) + delta);
+                }
 
-                // Update every data in code entry.
-                if size > 0 {
-                    let entry_size = mem::size_of::<macho::DataInCodeEntry<LE>>();
-                    let entries = load_structs_inplace_mut::<macho::DataInCodeEntry<LE>>(
-                        &mut out_mmap,
-                        offset as usize,
-                        size as usize / entry_size,
-                    );
-                    for entry in entries.iter_mut() {
-                        entry
-                            .offset
-                            .set(LE, entry.offset.get(LE) + md.added_byte_count as u32)
-                    }
+                if cmd.lazy_bind_size.get(LE) > 0 {
+                    cmd.lazy_bind_off.set(LE, cmd.lazy_bind_off.get(LE) + delta);
+                }
+
+                if cmd.export_size.get(LE) > 0 {
+                    cmd.export_off.set(LE, cmd.export_off.get(LE) + delta);
                 }
             }
-            macho::LC_CODE_SIGNATURE
-            | macho::LC_SEGMENT_SPLIT_INFO
+            macho::LC_SEGMENT_SPLIT_INFO
+            | macho::LC_FUNCTION_STARTS
+            | macho::LC_DATA_IN_CODE
             | macho::LC_DYLIB_CODE_SIGN_DRS
             | macho::LC_LINKER_OPTIMIZATION_HINT
             | macho::LC_DYLD_EXPORTS_TRIE
             | macho::LC_DYLD_CHAINED_FIXUPS => {
-                let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
-                    &mut out_mmap,
-                    offset,
-                );
+                let cmd =
+                    load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(exec_mmap, offset);
 
                 if cmd.datasize.get(LE) > 0 {
-                    cmd.dataoff
-                        .set(LE, cmd.dataoff.get(LE) + md.added_byte_count as u32);
+                    cmd.dataoff.set(LE, cmd.dataoff.get(LE) + delta);
                 }
             }
-            macho::LC_ENCRYPTION_INFO_64 => {
-                let cmd = load_struct_inplace_mut::<macho::EncryptionInfoCommand64<LE>>(
-                    &mut out_mmap,
-                    offset,
-                );
+            macho::LC_TWOLEVEL_HINTS => {
+                let cmd =
+                    load_struct_inplace_mut::<macho::TwolevelHintsCommand<LE>>(exec_mmap, offset);
 
-                if cmd.cryptsize.get(LE) > 0 {
-                    cmd.cryptoff
-                        .set(LE, cmd.cryptoff.get(LE) + md.added_byte_count as u32);
+                if cmd.nhints.get(LE) > 0 {
+                    cmd.offset.set(LE, cmd.offset.get(LE) + delta);
                 }
             }
-            macho::LC_DYLD_INFO | macho::LC_DYLD_INFO_ONLY => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::DyldInfoCommand<LE>>(&mut out_mmap, offset);
+            _ => {
+                // Nothing else refers to __LINKEDIT. The code signature is written last, from scratch.
+            }
+        }
+    }
+}
 
-                if cmd.rebase_size.get(LE) > 0 {
-                    cmd.rebase_off
-                        .set(LE, cmd.rebase_off.get(LE) + md.added_byte_count as u32);
-                }
+/// The size of an ad-hoc signature for the first `code_limit` bytes of a file.
+fn code_signature_size(code_limit: usize, identifier: &str) -> usize {
+    let page_count = (code_limit + CODE_SIGNATURE_PAGE_SIZE - 1) / CODE_SIGNATURE_PAGE_SIZE;
 
-                if cmd.bind_size.get(LE) > 0 {
-                    cmd.bind_off
-                        .set(LE, cmd.bind_off.get(LE) + md.added_byte_count as u32);
-                }
+    SUPER_BLOB_HEADER_SIZE
+        + CODE_DIRECTORY_HEADER_SIZE
+        + identifier.len()
+        + 1
+        + page_count * CS_SHA256_LEN
+}
 
-                if cmd.weak_bind_size.get(LE) > 0 {
-                    cmd.weak_bind_off
-                        .set(LE, cmd.weak_bind_off.get(LE) + md.added_byte_count as u32);
-                }
+/// Sign the executable the way `ld` does when it isn't given an identity: an ad-hoc signature
+/// that is just the hash of every page before it. arm64 macOS won't run code without one.
+/// Code signature blobs are big endian.
+fn write_code_signature(
+    exec_mmap: &mut [u8],
+    signature_offset: usize,
+    identifier: &str,
+    text_segment: (u64, u64),
+) {
+    let size = code_signature_size(signature_offset, identifier);
+    let page_count = (signature_offset + CODE_SIGNATURE_PAGE_SIZE - 1) / CODE_SIGNATURE_PAGE_SIZE;
+    let identifier_offset = CODE_DIRECTORY_HEADER_SIZE;
+    let hash_offset = identifier_offset + identifier.len() + 1;
+
+    let mut blob = Vec::with_capacity(size);
+
+    blob.extend_from_slice(&CSMAGIC_EMBEDDED_SIGNATURE.to_be_bytes());
+    blob.extend_from_slice(&(size as u32).to_be_bytes());
+    blob.extend_from_slice(&1u32.to_be_bytes());
+    blob.extend_from_slice(&CSSLOT_CODEDIRECTORY.to_be_bytes());
+    blob.extend_from_slice(&(SUPER_BLOB_HEADER_SIZE as u32).to_be_bytes());
+
+    blob.extend_from_slice(&CSMAGIC_CODEDIRECTORY.to_be_bytes());
+    blob.extend_from_slice(&((size - SUPER_BLOB_HEADER_SIZE) as u32).to_be_bytes());
+    blob.extend_from_slice(&CS_SUPPORTSEXECSEG.to_be_bytes());
+    blob.extend_from_slice(&(CS_ADHOC | CS_LINKER_SIGNED).to_be_bytes());
+    blob.extend_from_slice(&(hash_offset as u32).to_be_bytes());
+    blob.extend_from_slice(&(identifier_offset as u32).to_be_bytes());
+    // special slots: there is nothing like entitlements to hash
+    blob.extend_from_slice(&0u32.to_be_bytes());
+    blob.extend_from_slice(&(page_count as u32).to_be_bytes());
+    // code limit
+    blob.extend_from_slice(&(signature_offset as u32).to_be_bytes());
+    blob.push(CS_SHA256_LEN as u8);
+    blob.push(CS_HASHTYPE_SHA256);
+    // platform
+    blob.push(0);
+    blob.push(CODE_SIGNATURE_PAGE_SIZE.trailing_zeros() as u8);
+    // spare2, scatter offset, team offset, spare3, 64 bit code limit
+    blob.extend_from_slice(&[0; 4 * 4 + 8]);
+    blob.extend_from_slice(&text_segment.0.to_be_bytes());
+    blob.extend_from_slice(&text_segment.1.to_be_bytes());
+    blob.extend_from_slice(&CS_EXECSEG_MAIN_BINARY.to_be_bytes());
+    debug_assert_eq!(
+        blob.len(),
+        SUPER_BLOB_HEADER_SIZE + CODE_DIRECTORY_HEADER_SIZE
+    );
 
-                if cmd.lazy_bind_size.get(LE) > 0 {
-                    cmd.lazy_bind_off
-                        .set(LE, cmd.lazy_bind_off.get(LE) + md.added_byte_count as u32);
-                }
+    blob.extend_from_slice(identifier.as_bytes());
+    blob.push(0);
 
-                // TODO: Parse and update the related tables here.
-                // It is possible we may just need to delete things that point to stuff that will be in the roc app.
-                // We also may just be able to ignore it (lazy bindings should never run).
-                // This definitely has a list of virtual address that need to be updated.
-                // Some of them definitely will point to the roc app and should probably be removed.
-                // Also `xcrun dyldinfo` is useful for debugging this.
-            }
-            macho::LC_SYMSEG => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::SymsegCommand<LE>>(&mut out_mmap, offset);
+    for page in exec_mmap[..signature_offset].chunks(CODE_SIGNATURE_PAGE_SIZE) {
+        blob.extend_from_slice(&Sha256::digest(page));
+    }
+    debug_assert_eq!(blob.len(), size);
 
-                if cmd.size.get(LE) > 0 {
-                    cmd.offset
-                        .set(LE, cmd.offset.get(LE) + md.added_byte_count as u32);
-                }
-            }
-            macho::LC_MAIN => {
-                let cmd =
-                    load_struct_inplace_mut::<macho::EntryPointCommand<LE>>(&mut out_mmap, offset);
+    exec_mmap[signature_offset..][..size].copy_from_slice(&blob);
+}
 
-                cmd.entryoff
-                    .set(LE, cmd.entryoff.get(LE) + md.added_byte_count);
-            }
-            macho::LC_NOTE => {
-                let cmd = load_struct_inplace_mut::<macho::NoteCommand<LE>>(&mut out_mmap, offset);
+/// Sections of the app that only matter to debuggers and unwinders. We don't copy those (yet).
+fn is_unwind_or_debug_section(sec: &Section) -> bool {
+    matches!(
+        sec.name().unwrap_or_default(),
+        "__eh_frame" | "__compact_unwind" | "__gcc_except_tab"
+    ) || sec.kind() == SectionKind::Debug
+}
 
-                if cmd.size.get(LE) > 0 {
-                    cmd.offset.set(LE, cmd.offset.get(LE) + md.added_byte_count);
-                }
-            }
-            macho::LC_ID_DYLIB
-            | macho::LC_LOAD_WEAK_DYLIB
-            | macho::LC_LOAD_DYLIB
-            | macho::LC_REEXPORT_DYLIB
-            | macho::LC_SOURCE_VERSION
-            | macho::LC_IDENT
-            | macho::LC_LINKER_OPTION
-            | macho::LC_BUILD_VERSION
-            | macho::LC_VERSION_MIN_MACOSX
-            | macho::LC_VERSION_MIN_IPHONEOS
-            | macho::LC_VERSION_MIN_WATCHOS
-            | macho::LC_VERSION_MIN_TVOS
-            | macho::LC_UUID
-            | macho::LC_RPATH
-            | macho::LC_ID_DYLINKER
-            | macho::LC_LOAD_DYLINKER
-            | macho::LC_DYLD_ENVIRONMENT
-            | macho::LC_ROUTINES_64
-            | macho::LC_THREAD
-            | macho::LC_UNIXTHREAD
-            | macho::LC_PREBOUND_DYLIB
-            | macho::LC_SUB_FRAMEWORK
-            | macho::LC_SUB_CLIENT
-            | macho::LC_SUB_UMBRELLA
-            | macho::LC_SUB_LIBRARY => {
-                // These don't involve file offsets, so no change is needed for these.
-                // Just continue the loop!
-            }
-            macho::LC_PREBIND_CKSUM => {
-                // We don't *think* we need to change this, but
-                // maybe what we're doing will break checksums?
-            }
-            macho::LC_SEGMENT | macho::LC_ROUTINES | macho::LC_ENCRYPTION_INFO => {
-                // These are 32-bit and unsuppoted
-                unreachable!();
-            }
-            macho::LC_FVMFILE | macho::LC_IDFVMLIB | macho::LC_LOADFVMLIB => {
-                // These are obsolete and unsupported
-                unreachable!()
-            }
-            cmd => {
-                eprintln!(
-                    "- - - Unrecognized Mach-O command during linker preprocessing: 0x{:x?}",
-                    cmd
-                );
-                // panic!(
-                //     "Unrecognized Mach-O command during linker preprocessing: 0x{:x?}",
-                //     cmd
-                // );
-            }
+/// Where the sections of the app go in the final executable.
+struct AppLayout {
+    rodata_sections: Vec<SectionIndex>,
+    text_sections: Vec<SectionIndex>,
+    bss_sections: Vec<SectionIndex>,
+    /// File offset and virtual address of each section.
+    section_offset_map: MutMap<SectionIndex, (usize, u64)>,
+    segment_offset: usize,
+    segment_vaddr: u64,
+    text_offset: usize,
+    bss_vaddr: u64,
+    end_offset: usize,
+    end_vaddr: u64,
+    /// __LINKEDIT moves to after the app.
+    linkedit_offset: usize,
+    linkedit_vaddr: u64,
+}
+
+impl AppLayout {
+    fn new(
+        app_obj: &object::File,
+        linkedit_offset: usize,
+        linkedit_vaddr: u64,
+        md: &Metadata,
+    ) -> Self {
+        let sections_of_kind = |kinds: &[SectionKind]| -> Vec<SectionIndex> {
+            app_obj
+                .sections()
+                .filter(|sec| kinds.contains(&sec.kind()) && !is_unwind_or_debug_section(sec))
+                .map(|sec| sec.index())
+                .collect()
+        };
+
+        // TODO: In the future Roc may use a data section to store memoized toplevel thunks
+        // in development builds for caching the results of top-level constants
+        let rodata_sections =
+            sections_of_kind(&[SectionKind::ReadOnlyData, SectionKind::ReadOnlyString]);
+        let text_sections = sections_of_kind(&[SectionKind::Text]);
+        // bss sections have no data in the file, so they go last in the segment.
+        // TODO: the segment is not writable, so neither is the bss.
+        let bss_sections = sections_of_kind(&[SectionKind::UninitializedData]);
+
+        if text_sections.is_empty() {
+            internal_error!("No text sections found. This application has no code.");
+        }
+
+        // __LINKEDIT starts on a page, so the app does too.
+        let segment_offset = linkedit_offset;
+        let segment_vaddr = linkedit_vaddr;
+
+        let mut section_offset_map = MutMap::default();
+        let mut offset = segment_offset;
+        let place = |offset: usize, sec: &Section| {
+            align_by_constraint(offset, MIN_SECTION_ALIGNMENT.max(sec.align() as usize))
+        };
+
+        for index in rodata_sections.iter() {
+            let sec = app_obj.section_by_index(*index).unwrap();
+            offset = place(offset, &sec);
+            section_offset_map.insert(
+                *index,
+                (offset, segment_vaddr + (offset - segment_offset) as u64),
+            );
+            offset += sec.size() as usize;
+        }
+
+        offset = align_by_constraint(offset, MIN_SECTION_ALIGNMENT);
+        let text_offset = offset;
+        for index in text_sections.iter() {
+            let sec = app_obj.section_by_index(*index).unwrap();
+            offset = place(offset, &sec);
+            section_offset_map.insert(
+                *index,
+                (offset, segment_vaddr + (offset - segment_offset) as u64),
+            );
+            offset += sec.size() as usize;
+        }
+
+        let end_offset = align_by_constraint(offset, MIN_SECTION_ALIGNMENT);
+        let bss_vaddr = segment_vaddr + (end_offset - segment_offset) as u64;
+        let mut vaddr = bss_vaddr;
+        for index in bss_sections.iter() {
+            let sec = app_obj.section_by_index(*index).unwrap();
+            vaddr = align_by_constraint(
+                vaddr as usize,
+                MIN_SECTION_ALIGNMENT.max(sec.align() as usize),
+            ) as u64;
+            // Zero-fill sections have no file offset.
+            section_offset_map.insert(*index, (0, vaddr));
+            vaddr += sec.size();
         }
+        let end_vaddr = vaddr;
+
+        let page_size = md.load_align_constraint as usize;
 
-        offset += dbg!(cmd_size);
+        Self {
+            rodata_sections,
+            text_sections,
+            bss_sections,
+            section_offset_map,
+            segment_offset,
+            segment_vaddr,
+            text_offset,
+            bss_vaddr,
+            end_offset,
+            end_vaddr,
+            linkedit_offset: align_by_constraint(end_offset, page_size),
+            linkedit_vaddr: align_by_constraint(end_vaddr as usize, page_size) as u64,
+        }
     }
 
-    // cmd_loc should be where the last offset ended
-    md.macho_cmd_loc = offset as u64;
+    fn copied_sections(&self) -> impl Iterator<Item = &SectionIndex> {
+        self.rodata_sections.iter().chain(self.text_sections.iter())
+    }
+}
 
-    out_mmap
+/// The raw relocations of each section of the app, by section index.
+/// `object` interprets Mach-O relocations in a way that loses what we need for pairs
+/// like `SUBTRACTOR`/`UNSIGNED`, and to tell symbol relocations from section relocations.
+fn app_relocations(roc_app_bytes: &[u8]) -> MutMap<SectionIndex, &[macho::Relocation<LE>]> {
+    let header = macho::MachHeader64::<LE>::parse(roc_app_bytes, 0)
+        .unwrap_or_else(|e| internal_error!("Failed to parse application file: {}", e));
+    let mut commands = header
+        .load_commands(LE, roc_app_bytes, 0)
+        .unwrap_or_else(|e| internal_error!("Failed to parse application file: {}", e));
+
+    let mut relocations = MutMap::default();
+    // Section indices start at 1 in Mach-O.
+    let mut index = 1;
+
+    while let Ok(Some(command)) = commands.next() {
+        if let Ok(Some((segment, section_data))) = command.segment_64() {
+            let sections = segment
+                .sections(LE, section_data)
+                .unwrap_or_else(|e| internal_error!("Failed to parse application file: {}", e));
+
+            for section in sections {
+                let section_relocations = section
+                    .relocations(LE, roc_app_bytes)
+                    .unwrap_or_else(|e| internal_error!("Failed to parse application file: {}", e));
+                relocations.insert(SectionIndex(index), section_relocations);
+                index += 1;
+            }
+        }
+    }
+
+    relocations
 }
 
-// fn scan_macho_dynamic_deps(
-//     _exec_obj: &object::File,
-//     _md: &mut Metadata,
-//     _app_syms: &[Symbol],
-//     _shared_lib: &str,
-//     _exec_data: &[u8],
-//     _verbose: bool,
-// ) {
-//     // TODO
-// }
+/// Relocations that need dyld to slide the pointer they make, which we can't add yet.
+fn has_absolute_relocations(cpu: MachoCpu, relocations: &[macho::Relocation<LE>]) -> bool {
+    let mut after_subtractor = false;
+
+    for relocation in relocations {
+        let info = relocation.info(LE);
+        // Both x86_64 and aarch64 call them UNSIGNED, and they are both 0.
+        if info.r_type == macho::X86_64_RELOC_UNSIGNED && !after_subtractor {
+            return true;
+        }
+        after_subtractor = info.r_type == cpu.reloc_subtractor();
+    }
+
+    false
+}
 
 pub(crate) fn surgery_macho(
     roc_app_bytes: &[u8],
@@ -1154,22 +2126,55 @@ pub(crate) fn surgery_macho(
     let loading_metadata_duration = loading_metadata_start.elapsed();
 
     let load_and_mmap_start = Instant::now();
-    let max_out_len = md.exec_len + roc_app_bytes.len() as u64 + md.load_align_constraint;
-    let mut exec_mmap = open_mmap_mut(executable_path, max_out_len as usize);
+
+    let cpu = MachoCpu::from_architecture(app_obj.architecture());
+    let app_relocations = app_relocations(roc_app_bytes);
+
+    // The load commands of the preprocessed host say where __LINKEDIT is, which is where the app goes.
+    let (linkedit_offset, linkedit_vaddr) = {
+        let exec_data = open_mmap(executable_path);
+        let linkedit = load_struct_inplace::<macho::SegmentCommand64<LE>>(
+            &exec_data,
+            md.linkedit_cmd_offset as usize,
+        );
+        (linkedit.fileoff.get(LE) as usize, linkedit.vmaddr.get(LE))
+    };
+    let layout = AppLayout::new(&app_obj, linkedit_offset, linkedit_vaddr, &md);
+
+    if layout
+        .copied_sections()
+        .filter_map(|index| app_relocations.get(index))
+        .any(|relocations| has_absolute_relocations(cpu, relocations))
+    {
+        eprintln!("The surgical linker currently has issue #3609 and would fail linking your app.");
+        eprintln!("Please use `--linker=legacy` to avoid the issue for now.");
+        std::process::exit(1);
+    }
+
+    let identifier = executable_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("roc_app");
+    let linkedit_size = md.exec_len as usize - linkedit_offset;
+    let signature_offset = align_by_constraint(layout.linkedit_offset + linkedit_size, 16);
+    let signature_size = align_by_constraint(code_signature_size(signature_offset, identifier), 16);
+    let out_len = signature_offset + signature_size;
+
+    let mut exec_mmap = open_mmap_mut(executable_path, out_len);
     let load_and_mmap_duration = load_and_mmap_start.elapsed();
 
     let out_gen_start = Instant::now();
     let mut offset = 0;
 
     surgery_macho_help(
-        metadata_path,
-        executable_path,
+        identifier,
         verbose,
-        time,
         &md,
         &mut exec_mmap,
         &mut offset,
         app_obj,
+        &layout,
+        &app_relocations,
     );
 
     let out_gen_duration = out_gen_start.elapsed();
@@ -1216,130 +2221,224 @@ pub(crate) fn surgery_macho(
     }
 }
 
+/// Where the symbols and sections of the app end up.
+struct AppAddresses<'a> {
+    app_obj: &'a object::File<'a>,
+    layout: &'a AppLayout,
+    symbol_vaddr_map: MutMap<SymbolIndex, u64>,
+    roc_symbol_vaddresses: &'a MutMap<String, u64>,
+}
+
+impl<'a> AppAddresses<'a> {
+    /// The address of what a relocation refers to, in the app object and in the final executable.
+    /// For a section relocation, that is the start of the section.
+    fn target(&self, info: &macho::RelocationInfo) -> Option<(i64, i64)> {
+        if info.r_extern {
+            let index = SymbolIndex(info.r_symbolnum as usize);
+            let sym = self.app_obj.symbol_by_index(index).ok()?;
+
+            if let Some(vaddr) = self.symbol_vaddr_map.get(&index) {
+                Some((sym.address() as i64, *vaddr as i64))
+            } else {
+                let name = sym.name().ok()?.trim_start_matches('_');
+                let vaddr = self.roc_symbol_vaddresses.get(name)?;
+                Some((0, *vaddr as i64))
+            }
+        } else {
+            let index = SectionIndex(info.r_symbolnum as usize);
+            let sec = self.app_obj.section_by_index(index).ok()?;
+            let (_, vaddr) = self.layout.section_offset_map.get(&index)?;
+
+            Some((sec.address() as i64, *vaddr as i64))
+        }
+    }
+
+    fn is_ignored(&self, info: &macho::RelocationInfo) -> bool {
+        // Explicitly ignore some symbols that are currently always linked.
+        const ALWAYS_LINKED: &[&str] = &["__divti3", "__udivti3", "___divti3", "___udivti3"];
+
+        if !info.r_extern {
+            return false;
+        }
+
+        match self
+            .app_obj
+            .symbol_by_index(SymbolIndex(info.r_symbolnum as usize))
+        {
+            Ok(sym) => ALWAYS_LINKED.contains(&sym.name().unwrap_or_default()),
+            Err(_) => false,
+        }
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn surgery_macho_help(
-    _metadata_filename: &Path,
-    _out_filename: &Path,
+    identifier: &str,
     verbose: bool,
-    _time: bool,
     md: &Metadata,
     exec_mmap: &mut MmapMut,
     offset_ref: &mut usize, // TODO return this instead of taking a mutable reference to it
     app_obj: object::File,
+    layout: &AppLayout,
+    app_relocations: &MutMap<SectionIndex, &[macho::Relocation<LE>]>,
 ) {
-    let mut offset = align_by_constraint(md.exec_len as usize, MIN_SECTION_ALIGNMENT);
-    // let new_rodata_section_offset = offset;
-
-    // Align physical and virtual address of new segment.
-    let mut virt_offset = align_to_offset_by_constraint(
-        md.last_vaddr as usize,
-        offset,
-        md.load_align_constraint as usize,
-    );
-    let new_rodata_section_vaddr = virt_offset;
+    let cpu = {
+        let exec_header = load_struct_inplace::<macho::MachHeader64<LE>>(exec_mmap, 0);
+        MachoCpu::from_cputype(exec_header.cputype.get(LE))
+    };
+
+    let (old_linkedit_offset, linkedit_size) = {
+        let linkedit = load_struct_inplace::<macho::SegmentCommand64<LE>>(
+            exec_mmap,
+            md.linkedit_cmd_offset as usize,
+        );
+        (
+            linkedit.fileoff.get(LE) as usize,
+            linkedit.filesize.get(LE) as usize,
+        )
+    };
+
     if verbose {
         println!();
+        println!("Cpu: {:?}", cpu);
+        println!(
+            "New Segment Address: {:+x} (virt: {:+x})",
+            layout.segment_offset, layout.segment_vaddr
+        );
         println!(
-            "New Virtual Rodata Section Address: {:+x?}",
-            new_rodata_section_vaddr
+            "Moving __LINKEDIT from {:+x} to {:+x} (virt: {:+x})",
+            old_linkedit_offset, layout.linkedit_offset, layout.linkedit_vaddr
         );
     }
 
-    // First decide on sections locations and then recode every exact symbol locations.
+    // Move __LINKEDIT out of the way first; the app goes where it was.
+    exec_mmap.copy_within(
+        old_linkedit_offset..old_linkedit_offset + linkedit_size,
+        layout.linkedit_offset,
+    );
+    exec_mmap[layout.segment_offset..layout.linkedit_offset].fill(0);
 
-    // Copy sections and resolve their symbols/relocations.
-    let symbols = app_obj.symbols().collect::<Vec<Symbol>>();
-    let mut section_offset_map: MutMap<SectionIndex, (usize, usize)> = MutMap::default();
-    let mut symbol_vaddr_map: MutMap<SymbolIndex, usize> = MutMap::default();
-    let mut app_func_vaddr_map: MutMap<String, usize> = MutMap::default();
-    let mut app_func_size_map: MutMap<String, u64> = MutMap::default();
+    shift_linkedit_offsets(
+        exec_mmap,
+        (layout.linkedit_offset - old_linkedit_offset) as u32,
+    );
 
-    // TODO: In the future Roc may use a data section to store memoized toplevel thunks
-    // in development builds for caching the results of top-level constants
+    {
+        let linkedit = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(
+            exec_mmap,
+            md.linkedit_cmd_offset as usize,
+        );
+        linkedit.fileoff.set(LE, layout.linkedit_offset as u64);
+        linkedit.vmaddr.set(LE, layout.linkedit_vaddr);
+    }
 
-    let rodata_sections: Vec<Section> = app_obj
-        .sections()
-        .filter(|sec| sec.kind() == SectionKind::ReadOnlyData)
-        .collect();
+    {
+        let segment = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(
+            exec_mmap,
+            md.roc_segment_cmd_offset as usize,
+        );
+        segment.vmaddr.set(LE, layout.segment_vaddr);
+        segment
+            .vmsize
+            .set(LE, layout.linkedit_vaddr - layout.segment_vaddr);
+        segment.fileoff.set(LE, layout.segment_offset as u64);
+        segment
+            .filesize
+            .set(LE, (layout.linkedit_offset - layout.segment_offset) as u64);
+        segment
+            .maxprot
+            .set(LE, macho::VM_PROT_READ | macho::VM_PROT_EXECUTE);
+        segment
+            .initprot
+            .set(LE, macho::VM_PROT_READ | macho::VM_PROT_EXECUTE);
+    }
 
-    // bss section is like rodata section, but it has zero file size and non-zero virtual size.
-    let bss_sections: Vec<Section> = app_obj
-        .sections()
-        .filter(|sec| sec.kind() == SectionKind::UninitializedData)
-        .collect();
+    {
+        let sections = load_structs_inplace_mut::<macho::Section64<LE>>(
+            exec_mmap,
+            md.roc_segment_cmd_offset as usize + mem::size_of::<macho::SegmentCommand64<LE>>(),
+            ROC_SECTION_NAMES.len(),
+        );
+        let align = MIN_SECTION_ALIGNMENT.trailing_zeros();
+
+        let rodata = &mut sections[0];
+        rodata.addr.set(LE, layout.segment_vaddr);
+        rodata
+            .size
+            .set(LE, (layout.text_offset - layout.segment_offset) as u64);
+        rodata.offset.set(LE, layout.segment_offset as u32);
+        rodata.align.set(LE, align);
+        rodata.flags.set(LE, macho::S_REGULAR);
+
+        let text = &mut sections[1];
+        text.addr.set(
+            LE,
+            layout.segment_vaddr + (layout.text_offset - layout.segment_offset) as u64,
+        );
+        text.size
+            .set(LE, (layout.end_offset - layout.text_offset) as u64);
+        text.offset.set(LE, layout.text_offset as u32);
+        text.align.set(LE, align);
+        text.flags.set(
+            LE,
+            macho::S_REGULAR | macho::S_ATTR_PURE_INSTRUCTIONS | macho::S_ATTR_SOME_INSTRUCTIONS,
+        );
 
-    let text_sections: Vec<Section> = app_obj
-        .sections()
-        .filter(|sec| sec.kind() == SectionKind::Text)
-        .collect();
-    if text_sections.is_empty() {
-        internal_error!("No text sections found. This application has no code.");
+        let bss = &mut sections[2];
+        bss.addr.set(LE, layout.bss_vaddr);
+        bss.size.set(LE, layout.end_vaddr - layout.bss_vaddr);
+        bss.offset.set(LE, 0);
+        bss.align.set(LE, align);
+        bss.flags.set(LE, macho::S_ZEROFILL);
     }
 
     // Calculate addresses and load symbols.
-    // Note, it is important the bss sections come after the rodata sections.
-    for sec in rodata_sections
-        .iter()
-        .chain(bss_sections.iter())
-        .chain(text_sections.iter())
-    {
-        offset = align_by_constraint(offset, MIN_SECTION_ALIGNMENT);
-        virt_offset =
-            align_to_offset_by_constraint(virt_offset, offset, md.load_align_constraint as usize);
-        if verbose {
-            println!(
-                "Section, {}, is being put at offset: {:+x}(virt: {:+x})",
-                sec.name().unwrap(),
-                offset,
-                virt_offset
-            )
-        }
-        section_offset_map.insert(sec.index(), (offset, virt_offset));
-        for sym in symbols.iter() {
-            if sym.section() == SymbolSection::Section(sec.index()) {
-                let name = sym.name().unwrap_or_default().to_string();
-                if !md.roc_symbol_vaddresses.contains_key(&name) {
-                    symbol_vaddr_map.insert(sym.index(), virt_offset + sym.address() as usize);
-                }
-                if md.app_functions.contains(&name) {
-                    app_func_vaddr_map.insert(name.clone(), virt_offset + sym.address() as usize);
-                    app_func_size_map.insert(name, sym.size());
-                }
-            }
+    let mut symbol_vaddr_map: MutMap<SymbolIndex, u64> = MutMap::default();
+    let mut app_func_vaddr_map: MutMap<String, u64> = MutMap::default();
+
+    for sym in app_obj.symbols() {
+        let index = match sym.section() {
+            SymbolSection::Section(index) => index,
+            _ => continue,
+        };
+
+        let (sec, (_, section_vaddr)) = match (
+            app_obj.section_by_index(index),
+            layout.section_offset_map.get(&index),
+        ) {
+            (Ok(sec), Some(placement)) => (sec, placement),
+            _ => continue,
+        };
+
+        // Mach-O object files give symbols addresses, rather than offsets into their section.
+        let vaddr = section_vaddr + (sym.address() - sec.address());
+        let name = sym.name().unwrap_or_default().to_string();
+        if !md
+            .roc_symbol_vaddresses
+            .contains_key(name.trim_start_matches('_'))
+        {
+            symbol_vaddr_map.insert(sym.index(), vaddr);
         }
-        let section_size = match sec.file_range() {
-            Some((_, size)) => size,
-            None => 0,
-        };
-        if sec.name().unwrap_or_default().starts_with("__BSS") {
-            // bss sections only modify the virtual size.
-            virt_offset += sec.size() as usize;
-        } else if section_size != sec.size() {
-            internal_error!( "We do not deal with non bss sections that have different on disk and in memory sizes");
-        } else {
-            offset += section_size as usize;
-            virt_offset += sec.size() as usize;
+        if md.app_functions.contains(&name) {
+            app_func_vaddr_map.insert(name, vaddr);
         }
     }
+
     if verbose {
         println!("Data Relocation Offsets: {:+x?}", symbol_vaddr_map);
         println!("Found App Function Symbols: {:+x?}", app_func_vaddr_map);
     }
 
-    // let (new_text_section_offset, new_text_section_vaddr) = text_sections
-    //     .iter()
-    //     .map(|sec| section_offset_map.get(&sec.index()).unwrap())
-    //     .min()
-    //     .unwrap();
-    // let (new_text_section_offset, new_text_section_vaddr) =
-    //     (*new_text_section_offset, *new_text_section_vaddr);
+    let addresses = AppAddresses {
+        app_obj: &app_obj,
+        layout,
+        symbol_vaddr_map,
+        roc_symbol_vaddresses: &md.roc_symbol_vaddresses,
+    };
 
     // Move data and deal with relocations.
-    for sec in rodata_sections
-        .iter()
-        .chain(bss_sections.iter())
-        .chain(text_sections.iter())
-    {
+    for index in layout.copied_sections() {
+        let sec = app_obj.section_by_index(*index).unwrap();
         let data = match sec.data() {
             Ok(data) => data,
             Err(err) => {
@@ -1350,11 +2449,9 @@ fn surgery_macho_help(
                 );
             }
         };
-        let (section_offset, section_virtual_offset) =
-            section_offset_map.get(&sec.index()).unwrap();
-        let (section_offset, section_virtual_offset) = (*section_offset, *section_virtual_offset);
+        let (section_offset, section_virtual_offset) = layout.section_offset_map[index];
         exec_mmap[section_offset..section_offset + data.len()].copy_from_slice(data);
-        // Deal with definitions and relocations for this section.
+
         if verbose {
             println!();
             println!(
@@ -1362,193 +2459,25 @@ fn surgery_macho_help(
                 sec, section_offset, section_virtual_offset
             );
         }
-        for rel in sec.relocations() {
-            if verbose {
-                println!("\tFound Relocation: {:+x?}", rel);
-            }
-            match rel.1.target() {
-                RelocationTarget::Symbol(index) => {
-                    let target_offset = if let Some(target_offset) = symbol_vaddr_map.get(&index) {
-                        if verbose {
-                            println!(
-                                "\t\tRelocation targets symbol in app at: {:+x}",
-                                target_offset
-                            );
-                        }
-                        Some(*target_offset as i64)
-                    } else {
-                        app_obj
-                            .symbol_by_index(index)
-                            .and_then(|sym| sym.name())
-                            .ok()
-                            .and_then(|name| {
-                                md.roc_symbol_vaddresses.get(name).map(|address| {
-                                    let vaddr = (*address + md.added_byte_count) as i64;
-                                    if verbose {
-                                        println!(
-                                            "\t\tRelocation targets symbol in host: {} @ {:+x}",
-                                            name, vaddr
-                                        );
-                                    }
-                                    vaddr
-                                })
-                            })
-                    };
-
-                    if let Some(target_offset) = target_offset {
-                        let virt_base = section_virtual_offset + rel.0 as usize;
-                        let base = section_offset + rel.0 as usize;
-                        let target: i64 = match rel.1.kind() {
-                            RelocationKind::Relative | RelocationKind::PltRelative => {
-                                target_offset - virt_base as i64 + rel.1.addend()
-                            }
-                            x => {
-                                internal_error!("Relocation Kind not yet support: {:?}", x);
-                            }
-                        };
-                        if verbose {
-                            println!(
-                                "\t\tRelocation base location: {:+x} (virt: {:+x})",
-                                base, virt_base
-                            );
-                            println!("\t\tFinal relocation target offset: {:+x}", target);
-                        }
-                        match rel.1.size() {
-                            32 => {
-                                let data = (target as i32).to_le_bytes();
-                                exec_mmap[base..base + 4].copy_from_slice(&data);
-                            }
-                            64 => {
-                                let data = target.to_le_bytes();
-                                exec_mmap[base..base + 8].copy_from_slice(&data);
-                            }
-                            x => {
-                                internal_error!("Relocation size not yet supported: {}", x);
-                            }
-                        }
-                    } else if matches!(app_obj.symbol_by_index(index), Ok(sym) if ["__divti3", "__udivti3", "___divti3", "___udivti3"].contains(&sym.name().unwrap_or_default()))
-                    {
-                        // Explicitly ignore some symbols that are currently always linked.
-                        continue;
-                    } else {
-                        internal_error!(
-                            "Undefined Symbol in relocation, {:+x?}: {:+x?}",
-                            rel,
-                            app_obj.symbol_by_index(index)
-                        );
-                    }
-                }
 
-                _ => {
-                    internal_error!("Relocation target not yet support: {:+x?}", rel);
-                }
-            }
-        }
+        let relocations = app_relocations.get(index).copied().unwrap_or_default();
+        relocate_app_section(
+            cpu,
+            exec_mmap,
+            &addresses,
+            (sec.address(), section_offset, section_virtual_offset),
+            data,
+            relocations,
+            verbose,
+        );
     }
 
-    // Flush app only data to speed up write to disk.
-    exec_mmap
-        .flush_async_range(md.exec_len as usize, offset - md.exec_len as usize)
-        .unwrap_or_else(|e| internal_error!("{}", e));
-
     // TODO: look into merging symbol tables, debug info, and eh frames to enable better debugger experience.
 
-    // let mut cmd_offset = md.macho_cmd_loc as usize;
-
-    // // Load this section (we made room for it earlier) and then mutate all its data to make it the desired command
-    // {
-    //     let cmd =
-    //         load_struct_inplace_mut::<macho::SegmentCommand64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_section = mem::size_of::<macho::Section64<LE >>() as u32;
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.cmd.set(LE , macho::LC_SEGMENT_64);
-    //     cmd.cmdsize
-    //         .set(LE , size_of_section + size_of_cmd as u32);
-    //     cmd.segname = *b"__DATA_CONST\0\0\0\0";
-    //     cmd.vmaddr
-    //         .set(LE , new_rodata_section_vaddr as u64);
-    //     cmd.vmsize.set(
-    //         LE ,
-    //         (new_text_section_vaddr - new_rodata_section_vaddr) as u64,
-    //     );
-    //     cmd.fileoff
-    //         .set(LE , new_rodata_section_offset as u64);
-    //     cmd.filesize.set(
-    //         LE ,
-    //         (new_text_section_offset - new_rodata_section_offset) as u64,
-    //     );
-    //     cmd.nsects.set(LE , 1);
-    //     cmd.maxprot.set(LE , 0x00000003);
-    //     cmd.initprot.set(LE , 0x00000003);
-
-    //     // TODO set protection
-    // }
-
-    // {
-    //     let cmd = load_struct_inplace_mut::<macho::Section64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.sectname = *b"__const\0\0\0\0\0\0\0\0\0";
-    //     cmd.segname = *b"__DATA_CONST\0\0\0\0";
-    //     cmd.addr.set(LE , new_rodata_section_vaddr as u64);
-    //     cmd.size.set(
-    //         LE ,
-    //         (new_text_section_offset - new_rodata_section_offset) as u64,
-    //     );
-    //     cmd.offset.set(LE , 0); // TODO is this offset since the start of the file, or segment offset?
-    //     cmd.align.set(LE , 12); // TODO should this be 4096?
-    //     cmd.reloff.set(LE , 264); // TODO this should NOT be hardcoded! Should get it from somewhere.
-    // }
-
-    // {
-    //     let cmd =
-    //         load_struct_inplace_mut::<macho::SegmentCommand64<LE >>(exec_mmap, cmd_offset);
-    //     let size_of_section = mem::size_of::<macho::Section64<LE >>() as u32;
-    //     let size_of_cmd = mem::size_of_val(cmd);
-
-    //     cmd_offset += size_of_cmd;
-
-    //     cmd.cmd.set(LE , macho::LC_SEGMENT_64);
-    //     cmd.cmdsize
-    //         .set(LE , size_of_section + size_of_cmd as u32);
-    //     cmd.segname = *b"__TEXT\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.vmaddr.set(LE , new_text_section_vaddr as u64);
-    //     cmd.vmsize
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.fileoff
-    //         .set(LE , new_text_section_offset as u64);
-    //     cmd.filesize
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.nsects.set(LE , 1);
-    //     cmd.maxprot.set(LE , 0x00000005); // this is what a zig-generated host had
-    //     cmd.initprot.set(LE , 0x00000005); // this is what a zig-generated host had
-    // }
-
-    // {
-    //     let cmd = load_struct_inplace_mut::<macho::Section64<LE >>(exec_mmap, cmd_offset);
-
-    //     cmd.segname = *b"__TEXT\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.sectname = *b"__text\0\0\0\0\0\0\0\0\0\0";
-    //     cmd.addr.set(LE , new_text_section_vaddr as u64);
-    //     cmd.size
-    //         .set(LE , (offset - new_text_section_offset) as u64);
-    //     cmd.offset.set(LE , 0); // TODO is this offset since the start of the file, or segment offset?
-    //     cmd.align.set(LE , 12); // TODO this is 4096 (2^12) - which load_align_constraint does, above - but should it?
-    //     cmd.flags.set(LE , 0x80000400); // TODO this is what a zig-generated host had
-    //     cmd.reloff.set(LE , 264); // TODO this should NOT be hardcoded! Should get it from somewhere.
-    // }
-
     // Update calls from platform and dynamic symbols.
-    // let dynsym_offset = md.dynamic_symbol_table_section_offset + md.added_byte_count;
-
     for func_name in md.app_functions.iter() {
         let func_virt_offset = match app_func_vaddr_map.get(func_name) {
-            Some(offset) => *offset as u64,
+            Some(offset) => *offset,
             None => {
                 internal_error!("Function, {}, was not defined by the app", &func_name);
             }
@@ -1565,19 +2494,31 @@ fn surgery_macho_help(
                 println!("\tPerforming surgery: {:+x?}", s);
             }
             let surgery_virt_offset = match s.virtual_offset {
-                VirtualOffset::Relative(vs) => (vs + md.added_byte_count) as i64,
+                VirtualOffset::Relative(vs) => vs as i64,
                 VirtualOffset::Absolute => 0,
             };
+            let file_offset = s.file_offset as usize;
             match s.size {
+                4 if cpu == MachoCpu::Aarch64 => {
+                    let inst =
+                        u32::from_le_bytes(exec_mmap[file_offset..][..4].try_into().unwrap());
+                    let inst = Aarch64Immediate::Branch26.encode(
+                        inst,
+                        surgery_virt_offset as u64,
+                        func_virt_offset,
+                    );
+                    if verbose {
+                        println!("\tBranch Instruction: {:+x}", inst);
+                    }
+                    exec_mmap[file_offset..][..4].copy_from_slice(&inst.to_le_bytes());
+                }
                 4 => {
                     let target = (func_virt_offset as i64 - surgery_virt_offset) as i32;
                     if verbose {
                         println!("\tTarget Jump: {:+x}", target);
                     }
                     let data = target.to_le_bytes();
-                    exec_mmap[(s.file_offset + md.added_byte_count) as usize
-                        ..(s.file_offset + md.added_byte_count) as usize + 4]
-                        .copy_from_slice(&data);
+                    exec_mmap[file_offset..file_offset + 4].copy_from_slice(&data);
                 }
                 8 => {
                     let target = func_virt_offset as i64 - surgery_virt_offset;
@@ -1585,9 +2526,7 @@ fn surgery_macho_help(
                         println!("\tTarget Jump: {:+x}", target);
                     }
                     let data = target.to_le_bytes();
-                    exec_mmap[(s.file_offset + md.added_byte_count) as usize
-                        ..(s.file_offset + md.added_byte_count) as usize + 8]
-                        .copy_from_slice(&data);
+                    exec_mmap[file_offset..file_offset + 8].copy_from_slice(&data);
                 }
                 x => {
                     internal_error!("Surgery size not yet supported: {}", x);
@@ -1595,44 +2534,755 @@ fn surgery_macho_help(
             }
         }
 
-        // Replace plt call code with just a jump.
-        // This is a backup incase we missed a call to the plt.
+        // Replace the stub with just a jump.
+        // This is a backup incase we missed a call to the stub.
         if let Some((plt_off, plt_vaddr)) = md.plt_addresses.get(func_name) {
-            let plt_off = (*plt_off + md.added_byte_count) as usize;
-            let plt_vaddr = *plt_vaddr + md.added_byte_count;
-            let jmp_inst_len = 5;
-            let target =
-                (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+            let plt_off = *plt_off as usize;
+            let plt_vaddr = *plt_vaddr;
+            let stub = &mut exec_mmap[plt_off..][..md.stub_size as usize];
+            if verbose {
+                println!("\tStub: {:+x}, {:+x}", plt_off, plt_vaddr);
+            }
+            match cpu {
+                MachoCpu::X86_64 => {
+                    let jmp_inst_len = 5;
+                    let target =
+                        (func_virt_offset as i64 - (plt_vaddr as i64 + jmp_inst_len as i64)) as i32;
+                    if verbose {
+                        println!("\tTarget Jump: {:+x}", target);
+                    }
+                    let data = target.to_le_bytes();
+                    stub[0] = 0xE9;
+                    stub[1..jmp_inst_len].copy_from_slice(&data);
+                    stub[jmp_inst_len..].fill(0x90);
+                }
+                MachoCpu::Aarch64 => {
+                    let inst =
+                        Aarch64Immediate::Branch26.encode(AARCH64_B, plt_vaddr, func_virt_offset);
+                    if verbose {
+                        println!("\tBranch Instruction: {:+x}", inst);
+                    }
+                    stub[..4].copy_from_slice(&inst.to_le_bytes());
+                    for nop in stub[4..].chunks_exact_mut(4) {
+                        nop.copy_from_slice(&AARCH64_NOP.to_le_bytes());
+                    }
+                }
+            }
+        }
+
+        // Point whatever dyld used to bind to the app at the app itself.
+        for pointer_offset in md.app_pointers.get(func_name).unwrap_or(&vec![]) {
+            let pointer_offset = *pointer_offset as usize;
+            let pointer = match md.pointer_format {
+                PointerFormat::Raw => func_virt_offset,
+                PointerFormat::Chained64 | PointerFormat::Chained64Offset => {
+                    let target = if md.pointer_format == PointerFormat::Chained64 {
+                        func_virt_offset
+                    } else {
+                        func_virt_offset - md.image_base
+                    };
+                    // Keep the link to the next fixup in the chain, but make this a rebase.
+                    let next = read_u64(exec_mmap, pointer_offset) & (0xfff << 51);
+                    next | (target & 0xf_ffff_ffff)
+                }
+            };
             if verbose {
-                println!("\tPLT: {:+x}, {:+x}", plt_off, plt_vaddr);
-                println!("\tTarget Jump: {:+x}", target);
+                println!("\tPointer at {:+x}: {:+x}", pointer_offset, pointer);
+            }
+            write_u64(exec_mmap, pointer_offset, pointer);
+        }
+    }
+
+    // Finally, sign everything.
+    let signature_offset = align_by_constraint(layout.linkedit_offset + linkedit_size, 16);
+    let signature_size = align_by_constraint(code_signature_size(signature_offset, identifier), 16);
+
+    {
+        let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
+            exec_mmap,
+            md.code_signature_cmd_offset as usize,
+        );
+        cmd.dataoff.set(LE, signature_offset as u32);
+        cmd.datasize.set(LE, signature_size as u32);
+    }
+
+    {
+        let linkedit = load_struct_inplace_mut::<macho::SegmentCommand64<LE>>(
+            exec_mmap,
+            md.linkedit_cmd_offset as usize,
+        );
+        let filesize = (signature_offset + signature_size - layout.linkedit_offset) as u64;
+        linkedit.filesize.set(LE, filesize);
+        linkedit.vmsize.set(
+            LE,
+            align_by_constraint(filesize as usize, md.load_align_constraint as usize) as u64,
+        );
+    }
+
+    let text_segment = load_commands(exec_mmap)
+        .into_iter()
+        .filter(|(_, cmd, _)| *cmd == macho::LC_SEGMENT_64)
+        .map(|(offset, _, _)| load_struct_inplace::<macho::SegmentCommand64<LE>>(exec_mmap, offset))
+        .find(|segment| segment_name(&segment.segname) == b"__TEXT")
+        .map(|segment| (segment.fileoff.get(LE), segment.filesize.get(LE)))
+        .unwrap_or_else(|| internal_error!("The host has no __TEXT segment"));
+
+    if verbose {
+        println!();
+        println!(
+            "Signing {} bytes as `{}`, with a signature at {:+x}",
+            signature_offset, identifier, signature_offset
+        );
+    }
+
+    write_code_signature(exec_mmap, signature_offset, identifier, text_segment);
+
+    *offset_ref = signature_offset + signature_size;
+}
+
+fn relocate_app_section(
+    cpu: MachoCpu,
+    exec_mmap: &mut [u8],
+    addresses: &AppAddresses,
+    (section_address, section_offset, section_vaddr): (u64, usize, u64),
+    data: &[u8],
+    relocations: &[macho::Relocation<LE>],
+    verbose: bool,
+) {
+    // arm64 can't fit an addend in most instructions, so it gets a relocation of its own, before
+    // the one it's for. The first relocation of a SUBTRACTOR/UNSIGNED pair is the one subtracted.
+    let mut addend = 0;
+    let mut subtrahend = None;
+
+    for relocation in relocations {
+        let info = relocation.info(LE);
+        if verbose {
+            println!("\tFound Relocation: {:+x?}", info);
+        }
+
+        let offset = info.r_address as usize;
+        let base = section_offset + offset;
+        let virt_base = section_vaddr + offset as u64;
+        // Where the relocation was in the app object, which implicit addends are relative to.
+        let old_virt_base = section_address + offset as u64;
+
+        if cpu == MachoCpu::Aarch64 && info.r_type == macho::ARM64_RELOC_ADDEND {
+            // A 24 bit signed addend.
+            addend = ((info.r_symbolnum << 8) as i32 >> 8) as i64;
+            continue;
+        }
+
+        let target = match addresses.target(&info) {
+            Some(target) => target,
+            None if addresses.is_ignored(&info) => continue,
+            None => {
+                internal_error!(
+                    "Undefined Symbol in relocation, {:+x?}: {:+x?}",
+                    info,
+                    addresses
+                        .app_obj
+                        .symbol_by_index(SymbolIndex(info.r_symbolnum as usize))
+                );
+            }
+        };
+        let (old_target, new_target) = target;
+
+        if info.r_type == cpu.reloc_subtractor() {
+            subtrahend = Some(target);
+            continue;
+        }
+
+        if verbose {
+            println!(
+                "\t\tRelocation base location: {:+x} (virt: {:+x})",
+                base, virt_base
+            );
+            println!(
+                "\t\tRelocation target: {:+x} (was: {:+x})",
+                new_target, old_target
+            );
+        }
+
+        match (cpu, info.r_type) {
+            (_, macho::X86_64_RELOC_UNSIGNED) => {
+                // Only differences are left; absolute addresses were ruled out before we started.
+                let (old_minus, new_minus) = subtrahend.take().unwrap_or_else(|| {
+                    internal_error!("Unpaired absolute relocation: {:+x?}", info)
+                });
+                let shift = (new_target - old_target) - (new_minus - old_minus);
+
+                match info.r_length {
+                    2 => {
+                        let value = i32::from_le_bytes(data[offset..][..4].try_into().unwrap());
+                        let value = (value as i64 + shift) as i32;
+                        exec_mmap[base..][..4].copy_from_slice(&value.to_le_bytes());
+                    }
+                    3 => {
+                        let value = i64::from_le_bytes(data[offset..][..8].try_into().unwrap());
+                        let value = value + shift;
+                        exec_mmap[base..][..8].copy_from_slice(&value.to_le_bytes());
+                    }
+                    x => {
+                        internal_error!("Relocation size not yet supported: {}", 1 << x);
+                    }
+                }
+            }
+            (
+                MachoCpu::X86_64,
+                macho::X86_64_RELOC_SIGNED
+                | macho::X86_64_RELOC_BRANCH
+                | macho::X86_64_RELOC_GOT_LOAD
+                | macho::X86_64_RELOC_SIGNED_1
+                | macho::X86_64_RELOC_SIGNED_2
+                | macho::X86_64_RELOC_SIGNED_4,
+            ) => {
+                if info.r_type == macho::X86_64_RELOC_GOT_LOAD {
+                    // Everything is linked statically, so there is no GOT.
+                    // Turn the `movq foo@GOTPCREL(%rip)` into `leaq foo(%rip)`.
+                    if exec_mmap[base - 2] != 0x8b {
+                        internal_error!("Expected a movq for relocation {:+x?}", info);
+                    }
+                    exec_mmap[base - 2] = 0x8d;
+                }
+
+                // The SIGNED_N relocations are for instructions with an N byte immediate after the displacement.
+                let trailing = match info.r_type {
+                    macho::X86_64_RELOC_SIGNED_1 => 1,
+                    macho::X86_64_RELOC_SIGNED_2 => 2,
+                    macho::X86_64_RELOC_SIGNED_4 => 4,
+                    _ => 0,
+                };
+                let implicit = i32::from_le_bytes(data[offset..][..4].try_into().unwrap()) as i64;
+
+                let displacement = if info.r_extern {
+                    new_target + implicit - (virt_base as i64 + 4)
+                } else {
+                    // The displacement points into a section of the app, as laid out in the object.
+                    let old_address = old_virt_base as i64 + 4 + trailing + implicit;
+                    let new_address = old_address + (new_target - old_target);
+                    new_address - (virt_base as i64 + 4 + trailing)
+                };
+
+                if verbose {
+                    println!("\t\tFinal relocation displacement: {:+x}", displacement);
+                }
+
+                exec_mmap[base..][..4].copy_from_slice(&(displacement as i32).to_le_bytes());
+            }
+            (MachoCpu::Aarch64, r_type) => {
+                let inst = u32::from_le_bytes(data[offset..][..4].try_into().unwrap());
+                let immediate = match Aarch64Immediate::from_macho_relocation(r_type, inst) {
+                    Some(immediate) => immediate,
+                    None => {
+                        internal_error!("Relocation Kind not yet support: {:+x?}", info);
+                    }
+                };
+
+                let target = if info.r_extern {
+                    new_target + addend
+                } else if immediate == Aarch64Immediate::Branch26 {
+                    let old_address = aarch64_branch_target(inst, old_virt_base).unwrap();
+                    old_address as i64 + (new_target - old_target)
+                } else {
+                    internal_error!(
+                        "Section relocations are only supported for branches: {:+x?}",
+                        info
+                    );
+                };
+
+                let inst = if r_type == macho::ARM64_RELOC_GOT_LOAD_PAGEOFF12 {
+                    // Everything is linked statically, so there is no GOT.
+                    aarch64_relax_got_load(inst)
+                } else {
+                    inst
+                };
+                let inst = immediate.encode(inst, virt_base, target as u64);
+
+                if verbose {
+                    println!("\t\tFinal instruction: {:+x}", inst);
+                }
+
+                exec_mmap[base..][..4].copy_from_slice(&inst.to_le_bytes());
+            }
+            (_, r_type) => {
+                internal_error!("Relocation Kind not yet support: {:?}", r_type);
+            }
+        }
+
+        addend = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::preprocessed_host_filename;
+    use indoc::indoc;
+
+    #[test]
+    fn uleb128() {
+        for value in [0, 1, 0x7f, 0x80, 0x3fff, 0x4000, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_uleb128(&mut bytes, value);
+
+            let mut pos = 0;
+            assert_eq!(read_uleb128(&bytes, &mut pos), value);
+            assert_eq!(pos, bytes.len());
+        }
+
+        for value in [0, 1, -1, 0x3f, 0x40, -0x40, -0x41, i64::MIN, i64::MAX] {
+            let mut bytes = Vec::new();
+            write_sleb128(&mut bytes, value);
+
+            let mut pos = 0;
+            assert_eq!(read_sleb128(&bytes, &mut pos), value);
+            assert_eq!(pos, bytes.len());
+        }
+
+        // a 3 byte ULEB128 stays 3 bytes
+        let mut bytes = vec![0x80, 0x80, 0x01];
+        overwrite_uleb128(&mut bytes, 0, 5);
+        assert_eq!(bytes, [0x85, 0x80, 0x00]);
+        assert_eq!(read_uleb128(&bytes, &mut 0), 5);
+    }
+
+    #[test]
+    fn bind_opcodes() {
+        let binds = [
+            Bind {
+                segment: 2,
+                offset: 0x10,
+                ordinal: 1,
+                ordinal_at: 0,
+                symbol: b"_roc__mainForHost_1_exposed_generic",
+                flags: 0,
+                kind: macho::BIND_TYPE_POINTER,
+                addend: 0,
+            },
+            Bind {
+                segment: 3,
+                offset: 0x28,
+                ordinal: 17,
+                ordinal_at: 0,
+                symbol: b"_malloc",
+                flags: 0,
+                kind: macho::BIND_TYPE_POINTER,
+                addend: -8,
+            },
+            Bind {
+                segment: 3,
+                offset: 0x30,
+                ordinal: macho::BIND_SPECIAL_DYLIB_FLAT_LOOKUP as i64,
+                ordinal_at: 0,
+                symbol: b"_free",
+                flags: macho::BIND_SYMBOL_FLAGS_WEAK_IMPORT,
+                kind: macho::BIND_TYPE_POINTER,
+                addend: 0,
+            },
+        ];
+
+        let stream = encode_binds(&binds);
+        let decoded = decode_binds(&stream, false);
+
+        assert_eq!(decoded.len(), binds.len());
+        for (bind, decoded) in binds.iter().zip(decoded.iter()) {
+            assert_eq!(
+                Bind {
+                    ordinal_at: decoded.ordinal_at,
+                    ..bind.clone()
+                },
+                *decoded
+            );
+        }
+
+        // ld packs consecutive binds together
+        let stream = [
+            macho::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM | 2,
+            macho::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM,
+            b'_',
+            b'f',
+            0,
+            macho::BIND_OPCODE_SET_TYPE_IMM | macho::BIND_TYPE_POINTER,
+            macho::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | 2,
+            0x08,
+            macho::BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB,
+            3,
+            8,
+            macho::BIND_OPCODE_DONE,
+        ];
+        let offsets: Vec<_> = decode_binds(&stream, false)
+            .iter()
+            .map(|bind| bind.offset)
+            .collect();
+        assert_eq!(offsets, [0x08, 0x18, 0x28]);
+
+        let mut stream = stream;
+        decrement_bind_ordinal(&mut stream, 0);
+        assert_eq!(decode_binds(&stream, false)[0].ordinal, 1);
+    }
+
+    #[test]
+    fn rebase_opcodes() {
+        let stream = [
+            macho::REBASE_OPCODE_SET_TYPE_IMM | macho::REBASE_TYPE_POINTER,
+            macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | 2,
+            0x80,
+            0x01,
+            macho::REBASE_OPCODE_DO_REBASE_IMM_TIMES | 3,
+            macho::REBASE_OPCODE_DONE,
+            // padding
+            0,
+            0,
+        ];
+
+        let extended = append_rebases(&stream, &[(3, 0x10)]);
+
+        assert_eq!(&extended[..5], &stream[..5]);
+        assert_eq!(
+            &extended[5..],
+            &[
+                macho::REBASE_OPCODE_SET_TYPE_IMM | macho::REBASE_TYPE_POINTER,
+                macho::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB | 3,
+                0x10,
+                macho::REBASE_OPCODE_DO_REBASE_IMM_TIMES | 1,
+                macho::REBASE_OPCODE_DONE,
+            ]
+        );
+    }
+
+    /// Chained fixups for 3 segments, where only the middle one has fixups,
+    /// importing `_roc_foo` from dylib 1 and `_malloc` from dylib 2.
+    fn chained_fixups() -> Vec<u8> {
+        let mut fixups = Vec::new();
+
+        // header
+        let starts_offset = 28u32;
+        let imports_offset = 72u32;
+        let symbols_offset = 80u32;
+        for field in [0, starts_offset, imports_offset, symbols_offset, 2] {
+            fixups.extend_from_slice(&field.to_le_bytes());
+        }
+        fixups.extend_from_slice(&DYLD_CHAINED_IMPORT.to_le_bytes());
+        fixups.extend_from_slice(&0u32.to_le_bytes());
+
+        // starts in image
+        fixups.extend_from_slice(&3u32.to_le_bytes());
+        fixups.extend_from_slice(&0u32.to_le_bytes());
+        fixups.extend_from_slice(&20u32.to_le_bytes());
+        fixups.extend_from_slice(&0u32.to_le_bytes());
+        fixups.extend_from_slice(&0u32.to_le_bytes());
+
+        // starts in segment
+        assert_eq!(fixups.len(), 48);
+        fixups.extend_from_slice(&24u32.to_le_bytes());
+        fixups.extend_from_slice(&0x4000u16.to_le_bytes());
+        fixups.extend_from_slice(&DYLD_CHAINED_PTR_64_OFFSET.to_le_bytes());
+        fixups.extend_from_slice(&0x4000u64.to_le_bytes());
+        fixups.extend_from_slice(&0u32.to_le_bytes());
+        fixups.extend_from_slice(&1u16.to_le_bytes());
+        fixups.extend_from_slice(&0u16.to_le_bytes());
+
+        // imports
+        assert_eq!(fixups.len(), imports_offset as usize);
+        fixups.extend_from_slice(&(1u32 | (1 << 9)).to_le_bytes());
+        fixups.extend_from_slice(&(2u32 | (10 << 9)).to_le_bytes());
+
+        // symbols
+        assert_eq!(fixups.len(), symbols_offset as usize);
+        fixups.extend_from_slice(b"\0_roc_foo\0_malloc\0");
+
+        fixups
+    }
+
+    #[test]
+    fn chained_fixups_imports() {
+        let mut fixups = chained_fixups();
+
+        assert_eq!(
+            chained_imports(&fixups),
+            [(1, &b"_roc_foo"[..]), (2, &b"_malloc"[..])]
+        );
+
+        fix_chained_import_ordinals(&mut fixups, 1);
+
+        let imports_offset = read_u32(&fixups, 8) as usize;
+        // a weak flat lookup
+        assert_eq!(read_u32(&fixups, imports_offset), 0x1fe | (1 << 9));
+        assert_eq!(read_u32(&fixups, imports_offset + 4), 1 | (10 << 9));
+        assert_eq!(
+            chained_imports(&fixups),
+            [(0xfe, &b"_roc_foo"[..]), (1, &b"_malloc"[..])]
+        );
+    }
+
+    #[test]
+    fn chained_fixups_new_segment() {
+        let fixups = chained_fixups();
+        let grown = add_chained_fixups_segment(&fixups, 2);
+
+        assert_eq!(grown.len(), fixups.len() + 8);
+
+        let starts_offset = read_u32(&grown, 4) as usize;
+        assert_eq!(read_u32(&grown, starts_offset), 4);
+        let seg_info_offsets: Vec<_> = (0..4)
+            .map(|i| read_u32(&grown, starts_offset + 4 + i * 4))
+            .collect();
+        assert_eq!(seg_info_offsets, [0, 28, 0, 0]);
+
+        // the segment starts moved along with everything else
+        let starts = starts_offset + 28;
+        assert_eq!(read_u16(&grown, starts + 6), DYLD_CHAINED_PTR_64_OFFSET);
+        assert_eq!(read_u64(&grown, starts + 8), 0x4000);
+
+        assert_eq!(
+            chained_imports(&grown),
+            [(1, &b"_roc_foo"[..]), (2, &b"_malloc"[..])]
+        );
+    }
+
+    /// Check the code signature of a Mach-O file against its contents, like the kernel would.
+    fn verify_code_signature(data: &[u8]) {
+        let signature_offset = load_commands(data)
+            .into_iter()
+            .find(|(_, cmd, _)| *cmd == macho::LC_CODE_SIGNATURE)
+            .map(|(offset, _, _)| read_u32(data, offset + 8) as usize)
+            .expect("no code signature");
+
+        let read_be = |offset: usize| u32::from_be_bytes(data[offset..][..4].try_into().unwrap());
+
+        assert_eq!(read_be(signature_offset), CSMAGIC_EMBEDDED_SIGNATURE);
+        let code_directory = signature_offset + read_be(signature_offset + 16) as usize;
+        assert_eq!(read_be(code_directory), CSMAGIC_CODEDIRECTORY);
+
+        let hash_offset = read_be(code_directory + 16) as usize;
+        let page_count = read_be(code_directory + 28) as usize;
+        let code_limit = read_be(code_directory + 32) as usize;
+        assert_eq!(code_limit, signature_offset);
+
+        for (i, page) in data[..code_limit]
+            .chunks(CODE_SIGNATURE_PAGE_SIZE)
+            .enumerate()
+        {
+            let hash = &data[code_directory + hash_offset + i * CS_SHA256_LEN..][..CS_SHA256_LEN];
+            assert_eq!(hash, Sha256::digest(page).as_slice(), "page {}", i);
+        }
+        assert_eq!(
+            page_count,
+            (code_limit + CODE_SIGNATURE_PAGE_SIZE - 1) / CODE_SIGNATURE_PAGE_SIZE
+        );
+    }
+
+    #[test]
+    fn code_signature() {
+        let code_limit = 3 * CODE_SIGNATURE_PAGE_SIZE + 0x10;
+        let signature_size = code_signature_size(code_limit, "app");
+
+        // Just enough of a Mach-O file for `verify_code_signature` to find the signature.
+        let mut data = vec![0u8; code_limit + signature_size];
+        {
+            let header = load_struct_inplace_mut::<macho::MachHeader64<LE>>(&mut data, 0);
+            header.ncmds.set(LE, 1);
+            header
+                .sizeofcmds
+                .set(LE, mem::size_of::<macho::LinkeditDataCommand<LE>>() as u32);
+        }
+        {
+            let cmd = load_struct_inplace_mut::<macho::LinkeditDataCommand<LE>>(
+                &mut data,
+                mem::size_of::<macho::MachHeader64<LE>>(),
+            );
+            cmd.cmd.set(LE, macho::LC_CODE_SIGNATURE);
+            cmd.cmdsize
+                .set(LE, mem::size_of::<macho::LinkeditDataCommand<LE>>() as u32);
+            cmd.dataoff.set(LE, code_limit as u32);
+            cmd.datasize.set(LE, signature_size as u32);
+        }
+        for (i, byte) in data[0x100..code_limit].iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        write_code_signature(&mut data, code_limit, "app", (0, 0x4000));
+
+        verify_code_signature(&data);
+    }
+
+    #[allow(dead_code)]
+    fn zig_host_app_help(dir: &Path, target: &Triple) {
+        let host_zig = indoc!(
+            r#"
+            const std = @import("std");
+
+            extern fn roc_magic1(usize) callconv(.C) [*]const u8;
+
+            pub fn main() !void {
+                const stdout = std.io.getStdOut().writer();
+                try stdout.print("Hello {s}\n", .{roc_magic1(0)[0..3]});
             }
-            let data = target.to_le_bytes();
-            exec_mmap[plt_off] = 0xE9;
-            exec_mmap[plt_off + 1..plt_off + jmp_inst_len].copy_from_slice(&data);
-            for i in jmp_inst_len..PLT_ADDRESS_OFFSET as usize {
-                exec_mmap[plt_off + i] = 0x90;
+            "#
+        );
+
+        let app_zig = indoc!(
+            r#"
+            const X = "foo";
+
+            export fn roc_magic1(index: usize) [*]const u8 {
+                return X[index..].ptr;
+            }
+            "#
+        );
+
+        let zig = std::env::var("ROC_ZIG").unwrap_or_else(|_| "zig".into());
+        let zig_target = match target.architecture {
+            target_lexicon::Architecture::Aarch64(_) => "aarch64-macos",
+            _ => "x86_64-macos",
+        };
+
+        std::fs::write(dir.join("host.zig"), host_zig.as_bytes()).unwrap();
+        std::fs::write(dir.join("app.zig"), app_zig.as_bytes()).unwrap();
+
+        let run_zig = |args: &[&str]| {
+            let output = std::process::Command::new(&zig)
+                .current_dir(dir)
+                .args(args)
+                .args(["-target", zig_target, "-OReleaseFast"])
+                .output()
+                .unwrap();
+
+            if !output.status.success() {
+                use std::io::Write;
+
+                std::io::stdout().write_all(&output.stdout).unwrap();
+                std::io::stderr().write_all(&output.stderr).unwrap();
+
+                panic!("zig {} failed", args[0]);
             }
+        };
+
+        // we need to compile the app first
+        run_zig(&["build-obj", "app.zig"]);
+
+        // `ld` makes the stub dylib on macOS, but zig can make one anywhere.
+        run_zig(&["build-lib", "-dynamic", "app.zig", "--name", "app"]);
+
+        // now we can compile the host (it uses libapp.dylib, hence the order here)
+        run_zig(&["build-exe", "libapp.dylib", "host.zig"]);
+
+        let roc_app = std::fs::read(dir.join("app.o")).unwrap();
+
+        let preprocessed_host_filename = dir.join(preprocessed_host_filename(target).unwrap());
+
+        preprocess_macho(
+            target,
+            &dir.join("host"),
+            &dir.join("metadata"),
+            &preprocessed_host_filename,
+            &dir.join("libapp.dylib"),
+            false,
+            false,
+        );
+
+        std::fs::copy(&preprocessed_host_filename, dir.join("final")).unwrap();
+
+        surgery_macho(
+            &roc_app,
+            &dir.join("metadata"),
+            &dir.join("final"),
+            false,
+            false,
+        );
+    }
+
+    /// We can't run a macOS executable here, so check that it is put together the way dyld expects.
+    #[allow(dead_code)]
+    fn check_zig_host_app(dir: &Path, architecture: object::Architecture) {
+        let data = std::fs::read(dir.join("final")).unwrap();
+        let object = object::File::parse(data.as_slice()).unwrap();
+        assert_eq!(object.architecture(), architecture);
+
+        // The stub dylib is gone.
+        let commands = LoadCommands::parse(&data);
+        for (offset, size) in commands.dylibs.iter() {
+            assert_ne!(
+                dylib_path(&data, *offset, *size).file_name(),
+                Some(std::ffi::OsStr::new("libapp.dylib"))
+            );
         }
 
-        // Commented out because it doesn't apply to mach-o
-        // if let Some(i) = md.dynamic_symbol_indices.get(func_name) {
-        //     let sym = load_struct_inplace_mut::<elf::Sym64<LE >>(
-        //         exec_mmap,
-        //         dynsym_offset as usize + *i as usize * mem::size_of::<elf::Sym64<LE >>(),
-        //     );
-        //     sym.st_value.set(LE , func_virt_offset as u64);
-        //     sym.st_size.set(
-        //         LE ,
-        //         match app_func_size_map.get(func_name) {
-        //             Some(size) => *size,
-        //             None => {
-        //                 internal_error!("Size missing for: {}", func_name);
-        //             }
-        //         },
-        //     );
-        // }
-    }
-
-    *offset_ref = offset;
+        // The app has a segment of its own, and __LINKEDIT moved to the end of the file after it.
+        let names: Vec<_> = commands
+            .segments
+            .iter()
+            .map(|segment| segment_name(&segment.name))
+            .collect();
+        assert_eq!(
+            &names[names.len() - 2..],
+            [&b"__ROC"[..], &b"__LINKEDIT"[..]]
+        );
+
+        let roc = &commands.segments[names.len() - 2];
+        let linkedit = &commands.segments[names.len() - 1];
+        assert_eq!(roc.fileoff + roc.filesize, linkedit.fileoff);
+        assert_eq!(linkedit.fileoff + linkedit.filesize, data.len() as u64);
+        assert!(roc.vmaddr < linkedit.vmaddr);
+
+        // One of the stubs now jumps straight into the app's code.
+        let app_text = object
+            .sections()
+            .find(|sec| sec.segment_name() == Ok(Some("__ROC")) && sec.name() == Ok("__text"))
+            .unwrap();
+        let app_code = app_text.address()..app_text.address() + app_text.size();
+
+        let stubs = commands.stubs.as_ref().unwrap();
+        let stub_targets: Vec<_> = (0..stubs.size / stubs.stub_size as u64)
+            .filter_map(|i| {
+                let stub = &data[(stubs.offset + i * stubs.stub_size as u64) as usize..];
+                let address = stubs.address + i * stubs.stub_size as u64;
+
+                match architecture {
+                    object::Architecture::Aarch64 => {
+                        let inst = u32::from_le_bytes(stub[..4].try_into().unwrap());
+                        aarch64_branch_target(inst, address)
+                    }
+                    _ => (stub[0] == 0xE9).then(|| {
+                        let displacement = i32::from_le_bytes(stub[1..5].try_into().unwrap());
+                        (address as i64 + 5 + displacement as i64) as u64
+                    }),
+                }
+            })
+            .collect();
+        assert!(
+            stub_targets.iter().any(|target| app_code.contains(target)),
+            "no stub jumps into the app: {:+x?}",
+            stub_targets
+        );
+
+        verify_code_signature(&data);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_x86_64() {
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        zig_host_app_help(dir, &Triple::from_str("x86_64-apple-darwin").unwrap());
+
+        check_zig_host_app(dir, object::Architecture::X86_64);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn zig_host_app_aarch64() {
+        use std::str::FromStr;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+
+        zig_host_app_help(dir, &Triple::from_str("aarch64-apple-darwin").unwrap());
+
+        check_zig_host_app(dir, object::Architecture::Aarch64);
+    }
 }
@@ -5,7 +5,7 @@ pub mod repl_state;
 
 use colors::{BLUE, END_COL, PINK};
 use const_format::concatcp;
-use repl_state::ReplState;
+use repl_state::{ReplBackend, ReplState};
 
 use crate::repl_state::PROMPT;
 
@@ -25,7 +25,7 @@ pub const WELCOME_MESSAGE: &str = concatcp!(
 // TODO add link to repl tutorial(does not yet exist).
 pub const SHORT_INSTRUCTIONS: &str = "Enter an expression, or :help, or :q to quit.\n\n";
 
-pub fn main() -> i32 {
+pub fn main(backend: ReplBackend) -> i32 {
     use rustyline::error::ReadlineError;
     use rustyline::Editor;
 
@@ -35,7 +35,7 @@ pub fn main() -> i32 {
     print!("{}{}", WELCOME_MESSAGE, SHORT_INSTRUCTIONS);
 
     let mut editor = Editor::<ReplState>::new();
-    let repl_helper = ReplState::new();
+    let repl_helper = ReplState::with_backend(backend);
     editor.set_helper(Some(repl_helper));
 
     loop {
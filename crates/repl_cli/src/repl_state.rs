@@ -13,6 +13,9 @@ use roc_parse::state::State;
 use roc_parse::{join_alias_to_body, join_ann_to_body};
 use roc_region::all::Loc;
 use roc_repl_eval::gen::{Problems, ReplOutput};
+use roc_repl_eval::interp::eval_interp;
+use roc_reporting::report::DEFAULT_PALETTE;
+use roc_target::TargetInfo;
 use rustyline::highlight::{Highlighter, PromptInfo};
 use rustyline::validate::{self, ValidationContext, ValidationResult, Validator};
 use rustyline_derive::{Completer, Helper, Hinter};
@@ -68,12 +71,23 @@ struct PastDef {
     src: String,
 }
 
+/// How the REPL evaluates expressions
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReplBackend {
+    /// Compile them with LLVM, and run the machine code
+    #[default]
+    Llvm,
+    /// Run their mono IR in the interpreter, which needs no code generation
+    Interpreter,
+}
+
 #[derive(Completer, Helper, Hinter)]
 pub struct ReplState {
     validator: InputValidator,
     past_defs: Vec<PastDef>,
     past_def_idents: MutSet<String>,
     last_auto_ident: u64,
+    backend: ReplBackend,
 }
 
 impl Default for ReplState {
@@ -84,11 +98,16 @@ impl Default for ReplState {
 
 impl ReplState {
     pub fn new() -> Self {
+        Self::with_backend(ReplBackend::default())
+    }
+
+    pub fn with_backend(backend: ReplBackend) -> Self {
         Self {
             validator: InputValidator::new(),
             past_defs: Default::default(),
             past_def_idents: Default::default(),
             last_auto_ident: 0,
+            backend,
         }
     }
 
@@ -231,20 +250,10 @@ impl ReplState {
                 Some(existing_ident) => {
                     opt_var_name = Some(existing_ident);
 
-                    gen_and_eval_llvm(
-                        self.past_defs.iter().map(|def| def.src.as_str()),
-                        src,
-                        Triple::host(),
-                        OptLevel::Normal,
-                    )
+                    self.eval(src)
                 }
                 None => {
-                    let (output, problems) = gen_and_eval_llvm(
-                        self.past_defs.iter().map(|def| def.src.as_str()),
-                        src,
-                        Triple::host(),
-                        OptLevel::Normal,
-                    );
+                    let (output, problems) = self.eval(src);
 
                     // Don't persist defs that have compile errors
                     if problems.errors.is_empty() {
@@ -269,6 +278,20 @@ impl ReplState {
         format_output(output, problems, opt_var_name, dimensions)
     }
 
+    fn eval(&self, src: &str) -> (Option<ReplOutput>, Problems) {
+        let defs = self.past_defs.iter().map(|def| def.src.as_str());
+
+        match self.backend {
+            ReplBackend::Llvm => gen_and_eval_llvm(defs, src, Triple::host(), OptLevel::Normal),
+            ReplBackend::Interpreter => eval_interp(
+                defs,
+                src,
+                TargetInfo::from(&Triple::host()),
+                DEFAULT_PALETTE,
+            ),
+        }
+    }
+
     fn next_auto_ident(&mut self) -> u64 {
         self.last_auto_ident += 1;
         self.last_auto_ident
@@ -0,0 +1,484 @@
+//! Evaluates REPL expressions with the [mono IR interpreter][roc_mono_interp] instead of
+//! generated code, so the REPL also works where no code generation backend is available.
+//!
+//! The interpreter's result is written into a buffer, laid out the way generated code would
+//! have laid it out in memory. That way [jit_to_ast] reads it back like the result of any
+//! other [ReplApp].
+use bumpalo::Bump;
+use std::mem::size_of;
+
+use roc_builtins::bitcode::FloatWidth;
+use roc_collections::all::MutMap;
+use roc_mono::layout::{Builtin, InLayout, Layout, LayoutInterner, TagIdIntType, UnionLayout};
+use roc_mono_interp::{CellId, Error, Heap, HeapData, Interpreter, Value};
+use roc_parse::ast::Expr;
+use roc_reporting::report::Palette;
+use roc_target::TargetInfo;
+use roc_types::pretty_print::{name_and_print_var, DebugPrint};
+
+use crate::eval::jit_to_ast;
+use crate::gen::{compile_to_mono, format_answer, Problems, ReplOutput};
+use crate::{ReplApp, ReplAppMemory};
+
+/// Allocations are aligned to this, so that the low bits of a pointer are free for a tag id.
+/// Address 0 is never allocated; it is the null pointer.
+const ALLOCATION_ALIGNMENT: usize = 16;
+
+/// Values written by [write_value]. Addresses are offsets into the buffer.
+pub struct InterpMemory {
+    bytes: Vec<u8>,
+}
+
+macro_rules! deref_number {
+    ($name: ident, $t: ty) => {
+        fn $name(&self, address: usize) -> $t {
+            const N: usize = size_of::<$t>();
+            let mut array = [0; N];
+            array.copy_from_slice(&self.bytes[address..][..N]);
+            <$t>::from_le_bytes(array)
+        }
+    };
+}
+
+impl ReplAppMemory for InterpMemory {
+    fn deref_bool(&self, address: usize) -> bool {
+        self.bytes[address] != 0
+    }
+
+    deref_number!(deref_u8, u8);
+    deref_number!(deref_u16, u16);
+    deref_number!(deref_u32, u32);
+    deref_number!(deref_u64, u64);
+    deref_number!(deref_u128, u128);
+    deref_number!(deref_usize, usize);
+
+    deref_number!(deref_i8, i8);
+    deref_number!(deref_i16, i16);
+    deref_number!(deref_i32, i32);
+    deref_number!(deref_i64, i64);
+    deref_number!(deref_i128, i128);
+    deref_number!(deref_isize, isize);
+
+    deref_number!(deref_f32, f32);
+    deref_number!(deref_f64, f64);
+
+    fn deref_str(&self, addr: usize) -> &str {
+        // Strings are always written as big strings; there is no small string to detect
+        let bytes_index = self.deref_usize(addr);
+        let len = self.deref_usize(addr + size_of::<usize>());
+        let str_bytes = &self.bytes[bytes_index..][..len];
+
+        std::str::from_utf8(str_bytes).expect("the interpreter only produces valid UTF-8")
+    }
+
+    fn deref_pointer_with_tag_id(&self, addr: usize) -> (u16, u64) {
+        let addr_with_id = self.deref_usize(addr);
+        let tag_id_mask = ALLOCATION_ALIGNMENT - 1;
+
+        let tag_id = addr_with_id & tag_id_mask;
+        let data_addr = addr_with_id & !tag_id_mask;
+        (tag_id as _, data_addr as _)
+    }
+}
+
+/// Write `value` into a fresh [InterpMemory], and return the address it was written to.
+///
+/// The memory uses the pointer width of the compiler itself, so `target_info` must match it.
+pub fn write_value<'a, I: LayoutInterner<'a>>(
+    interner: &I,
+    heap: &Heap,
+    target_info: TargetInfo,
+    value: &Value,
+    layout: InLayout<'a>,
+) -> Result<(InterpMemory, usize), Error> {
+    debug_assert_eq!(target_info.ptr_size(), size_of::<usize>());
+
+    let mut writer = Writer {
+        interner,
+        heap,
+        target_info,
+        bytes: vec![0; ALLOCATION_ALIGNMENT],
+        cells: MutMap::default(),
+    };
+
+    let addr = writer.alloc(interner.stack_size(layout) as usize);
+    writer.write(addr, value, layout)?;
+
+    Ok((
+        InterpMemory {
+            bytes: writer.bytes,
+        },
+        addr,
+    ))
+}
+
+struct Writer<'r, I> {
+    interner: &'r I,
+    heap: &'r Heap,
+    target_info: TargetInfo,
+    bytes: Vec<u8>,
+    /// The addresses of the heap cells that were written already, so that shared values are
+    /// written only once
+    cells: MutMap<CellId, usize>,
+}
+
+fn mismatch(expected: &'static str, found: &impl std::fmt::Debug) -> Error {
+    Error::TypeMismatch {
+        expected,
+        found: format!("{:?}", found),
+    }
+}
+
+impl<'a, 'r, I: LayoutInterner<'a>> Writer<'r, I> {
+    fn alloc(&mut self, size: usize) -> usize {
+        let addr = (self.bytes.len() + ALLOCATION_ALIGNMENT - 1) / ALLOCATION_ALIGNMENT
+            * ALLOCATION_ALIGNMENT;
+        self.bytes.resize(addr + size, 0);
+        addr
+    }
+
+    fn write_bytes(&mut self, addr: usize, bytes: &[u8]) {
+        self.bytes[addr..][..bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn write_usize(&mut self, addr: usize, n: usize) {
+        self.write_bytes(addr, &n.to_le_bytes());
+    }
+
+    /// The pointer, length and capacity of a list or a (big) string
+    fn write_list_header(&mut self, addr: usize, elements: usize, len: usize) {
+        self.write_usize(addr, elements);
+        self.write_usize(addr + size_of::<usize>(), len);
+        self.write_usize(addr + 2 * size_of::<usize>(), len);
+    }
+
+    fn write(&mut self, addr: usize, value: &Value, layout: InLayout<'a>) -> Result<(), Error> {
+        let heap = self.heap;
+
+        match (self.interner.get(layout), value) {
+            (Layout::Builtin(Builtin::Bool), Value::Bool(b)) => self.bytes[addr] = *b as u8,
+            (Layout::Builtin(Builtin::Int(width)), Value::Int(n, _)) => {
+                let size = width.stack_size() as usize;
+                self.write_bytes(addr, &n.to_le_bytes()[..size]);
+            }
+            (Layout::Builtin(Builtin::Float(FloatWidth::F32)), Value::Float(n, _)) => {
+                self.write_bytes(addr, &(*n as f32).to_le_bytes());
+            }
+            (Layout::Builtin(Builtin::Float(FloatWidth::F64)), Value::Float(n, _)) => {
+                self.write_bytes(addr, &n.to_le_bytes());
+            }
+            (Layout::Builtin(Builtin::Decimal), Value::Dec(n)) => {
+                self.write_bytes(addr, &n.to_le_bytes());
+            }
+            (Layout::Builtin(Builtin::Str), Value::Str(_)) => {
+                let string = heap.str(value)?;
+                let str_bytes = self.alloc(string.len());
+                self.write_bytes(str_bytes, string.as_bytes());
+                self.write_list_header(addr, str_bytes, string.len());
+            }
+            (Layout::Builtin(Builtin::List(elem_layout)), Value::List(_)) => {
+                let elements = heap.list(value)?;
+                let elem_size = self.interner.stack_size(elem_layout) as usize;
+                let elements_addr = self.alloc(elements.len() * elem_size);
+
+                for (i, element) in elements.iter().enumerate() {
+                    self.write(elements_addr + i * elem_size, element, elem_layout)?;
+                }
+
+                self.write_list_header(addr, elements_addr, elements.len());
+            }
+            (Layout::Struct { field_layouts, .. }, Value::Struct(fields)) => {
+                self.write_fields(addr, fields, field_layouts)?;
+            }
+            (Layout::Boxed(inner_layout), Value::Ptr(id)) => {
+                let ptr = match self.cells.get(id) {
+                    Some(ptr) => *ptr,
+                    None => {
+                        let inner = match heap.get(*id)? {
+                            HeapData::Box(inner) => inner,
+                            other => return Err(mismatch("a box", other)),
+                        };
+
+                        let ptr = self.alloc(self.interner.stack_size(inner_layout) as usize);
+                        self.cells.insert(*id, ptr);
+                        self.write(ptr, inner, inner_layout)?;
+                        ptr
+                    }
+                };
+
+                self.write_usize(addr, ptr);
+            }
+            (Layout::Union(union_layout), _) => self.write_union(addr, value, union_layout)?,
+            (Layout::RecursivePointer(_), _) => {
+                self.write(addr, value, self.interner.chase_recursive_in(layout))?;
+            }
+            (Layout::LambdaSet(_), _) => {
+                // Functions are only ever shown as `<function>`, their captures are not read
+            }
+            (_, other) => return Err(mismatch("a value of the layout", other)),
+        }
+
+        Ok(())
+    }
+
+    /// Struct fields and tag payloads are laid out one after another, in layout order
+    fn write_fields(
+        &mut self,
+        addr: usize,
+        fields: &[Value],
+        field_layouts: &[InLayout<'a>],
+    ) -> Result<(), Error> {
+        if fields.len() != field_layouts.len() {
+            return Err(mismatch("one value per field", &fields));
+        }
+
+        let mut field_addr = addr;
+        for (field, field_layout) in fields.iter().zip(field_layouts) {
+            self.write(field_addr, field, *field_layout)?;
+            field_addr += self.interner.stack_size(*field_layout) as usize;
+        }
+
+        Ok(())
+    }
+
+    fn write_union(
+        &mut self,
+        addr: usize,
+        value: &Value,
+        union_layout: UnionLayout<'a>,
+    ) -> Result<(), Error> {
+        let heap = self.heap;
+
+        let id = match (union_layout, value) {
+            (UnionLayout::NonRecursive(tags), _) => {
+                let (tag_id, fields) = match heap.tag(value)? {
+                    Some(tag) => tag,
+                    None => return Err(mismatch("a non-recursive tag", value)),
+                };
+
+                return self.write_tag(addr, union_layout, tag_id, fields, tags[tag_id as usize]);
+            }
+            (_, Value::Null) => {
+                self.write_usize(addr, 0);
+                return Ok(());
+            }
+            (_, Value::Ptr(id)) => *id,
+            (_, other) => return Err(mismatch("a pointer to a tag", other)),
+        };
+
+        let ptr = match self.cells.get(&id) {
+            Some(ptr) => *ptr,
+            None => {
+                let (tag_id, fields) = match heap.get(id)? {
+                    HeapData::Tag { tag_id, fields } => (*tag_id, fields),
+                    other => return Err(mismatch("a tag", other)),
+                };
+
+                let field_layouts = match union_layout {
+                    UnionLayout::NonRecursive(_) => unreachable!(),
+                    UnionLayout::Recursive(tags) => tags[tag_id as usize],
+                    UnionLayout::NonNullableUnwrapped(fields) => fields,
+                    UnionLayout::NullableWrapped {
+                        nullable_id,
+                        other_tags,
+                    } => {
+                        // the null tag has no entry in `other_tags`
+                        if tag_id > nullable_id {
+                            other_tags[tag_id as usize - 1]
+                        } else {
+                            other_tags[tag_id as usize]
+                        }
+                    }
+                    UnionLayout::NullableUnwrapped { other_fields, .. } => other_fields,
+                };
+
+                let size =
+                    match union_layout.data_size_without_tag_id(self.interner, self.target_info) {
+                        Some(data_size) => data_size as usize + size_of::<u16>(),
+                        None => field_layouts
+                            .iter()
+                            .map(|layout| self.interner.stack_size(*layout) as usize)
+                            .sum(),
+                    };
+
+                let data = self.alloc(size);
+                self.write_tag(data, union_layout, tag_id, fields, field_layouts)?;
+
+                let ptr = if union_layout.stores_tag_id_in_pointer(self.target_info) {
+                    data | tag_id as usize
+                } else {
+                    data
+                };
+
+                self.cells.insert(id, ptr);
+                ptr
+            }
+        };
+
+        self.write_usize(addr, ptr);
+
+        Ok(())
+    }
+
+    /// Write the payload of a tag, followed by its tag id if the layout stores it as data
+    fn write_tag(
+        &mut self,
+        data: usize,
+        union_layout: UnionLayout<'a>,
+        tag_id: TagIdIntType,
+        fields: &[Value],
+        field_layouts: &[InLayout<'a>],
+    ) -> Result<(), Error> {
+        self.write_fields(data, fields, field_layouts)?;
+
+        if let Some(offset) = union_layout.data_size_without_tag_id(self.interner, self.target_info)
+        {
+            let tag_id_addr = data + offset as usize;
+
+            use roc_mono::layout::Discriminant::*;
+            match union_layout.discriminant() {
+                U0 => {}
+                U1 | U8 => self.bytes[tag_id_addr] = tag_id as u8,
+                U16 => self.write_bytes(tag_id_addr, &tag_id.to_le_bytes()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A [ReplApp] whose "call" reads the interpreter's result back from memory
+pub struct InterpApp<'a> {
+    memory: &'a InterpMemory,
+    addr: usize,
+}
+
+impl<'a> InterpApp<'a> {
+    pub fn new(memory: &'a InterpMemory, addr: usize) -> Self {
+        Self { memory, addr }
+    }
+}
+
+impl<'a> ReplApp<'a> for InterpApp<'a> {
+    type Memory = InterpMemory;
+
+    /// The value was computed already; this only reads it
+    fn call_function<Return, F>(&mut self, _main_fn_name: &str, mut transform: F) -> Expr<'a>
+    where
+        F: FnMut(&'a Self::Memory, Return) -> Expr<'a>,
+        Self::Memory: 'a,
+    {
+        let result_bytes = &self.memory.bytes[self.addr..][..size_of::<Return>()];
+        let result: Return = unsafe {
+            let ptr = result_bytes.as_ptr() as *const Return;
+            ptr.read_unaligned()
+        };
+
+        transform(self.memory, result)
+    }
+
+    fn call_function_dynamic_size<T, F>(
+        &mut self,
+        _main_fn_name: &str,
+        _ret_bytes: usize,
+        mut transform: F,
+    ) -> T
+    where
+        F: FnMut(&'a Self::Memory, usize) -> T,
+        Self::Memory: 'a,
+    {
+        transform(self.memory, self.addr)
+    }
+}
+
+/// Evaluate an expression in the interpreter. Like the generated code, the interpreter runs on
+/// the host, so `target_info` must be the host's.
+pub fn eval_interp<'a, I: Iterator<Item = &'a str>>(
+    defs: I,
+    src: &str,
+    target_info: TargetInfo,
+    palette: Palette,
+) -> (Option<ReplOutput>, Problems) {
+    let arena = Bump::new();
+
+    let (mut loaded, mut problems) = match compile_to_mono(&arena, defs, src, target_info, palette)
+    {
+        (Some(mono), problems) => (mono, problems),
+        (None, problems) => return (None, problems),
+    };
+
+    debug_assert_eq!(loaded.exposed_to_host.top_level_values.len(), 1);
+    let (main_fn_symbol, main_fn_var) = loaded
+        .exposed_to_host
+        .top_level_values
+        .iter()
+        .next()
+        .unwrap();
+    let main_fn_symbol = *main_fn_symbol;
+    let main_fn_var = *main_fn_var;
+
+    let expr_type_str = name_and_print_var(
+        main_fn_var,
+        &mut loaded.subs,
+        loaded.module_id,
+        &loaded.interns,
+        DebugPrint::NOTHING,
+    );
+
+    let (_, main_fn_layout) = match loaded.procedures.keys().find(|(s, _)| *s == main_fn_symbol) {
+        Some(layout) => *layout,
+        None => return (None, problems),
+    };
+
+    // Functions are not called, jit_to_ast only shows their arity
+    let (memory, addr) = if main_fn_layout.arguments.is_empty() {
+        let mut interpreter = Interpreter::new(&loaded.layout_interner, &loaded.procedures);
+
+        let written = interpreter
+            .call(main_fn_symbol, main_fn_layout, Vec::new())
+            .and_then(|value| {
+                write_value(
+                    &loaded.layout_interner,
+                    &interpreter.heap,
+                    target_info,
+                    &value,
+                    main_fn_layout.result,
+                )
+            });
+
+        match written {
+            Ok(written) => written,
+            Err(error) => {
+                problems.errors.push(error.to_string());
+                return (None, problems);
+            }
+        }
+    } else {
+        (InterpMemory { bytes: Vec::new() }, 0)
+    };
+
+    let mut app = InterpApp::new(&memory, addr);
+
+    let layout_interner = loaded.layout_interner.into_global().fork();
+
+    let expr = jit_to_ast(
+        &arena,
+        &mut app,
+        "",
+        main_fn_layout,
+        main_fn_var,
+        &loaded.subs,
+        &loaded.interns,
+        layout_interner,
+        target_info,
+    );
+    let expr_str = format_answer(&arena, expr).to_string();
+
+    (
+        Some(ReplOutput {
+            expr: expr_str,
+            expr_type: expr_type_str,
+        }),
+        problems,
+    )
+}
@@ -0,0 +1,165 @@
+//! Runs the top-level `expect`s in the [mono IR interpreter][roc_mono_interp] instead of
+//! compiling them, for `roc test --interpret`.
+use bumpalo::Bump;
+use roc_collections::VecMap;
+use roc_error_macros::internal_error;
+use roc_load::{Expectations, MonomorphizedModule};
+use roc_module::symbol::{Interns, ModuleId, Symbol};
+use roc_mono::ir::ProcLayout;
+use roc_mono::layout::{LayoutCache, LayoutInterner, Niche};
+use roc_mono_interp::{Error, FailedExpect, Heap, Interpreter};
+use roc_region::all::Region;
+use roc_repl_eval::eval::jit_to_ast;
+use roc_repl_eval::interp::{write_value, InterpApp};
+use roc_reporting::{error::expect::Renderer, report::RenderTarget};
+
+use crate::run::split_expect_lookups;
+
+/// What happened when an expect was run
+struct Outcome<'a> {
+    symbol: Symbol,
+    region: Region,
+    /// On success, the heap the lookups of the failed inline expects live in
+    result: Result<(Heap, Vec<FailedExpect<'a>>), Error>,
+}
+
+/// Run every top-level expect, and report the failures like `run::run_toplevel_expects` does.
+/// Returns the number of failed and passed expects.
+pub fn run_toplevel_expects<'a, W: std::io::Write>(
+    writer: &mut W,
+    render_target: RenderTarget,
+    arena: &'a Bump,
+    interns: &'a Interns,
+    loaded: MonomorphizedModule<'a>,
+    expectations: &mut VecMap<ModuleId, Expectations>,
+) -> std::io::Result<(usize, usize)> {
+    let MonomorphizedModule {
+        toplevel_expects,
+        procedures,
+        layout_interner,
+        ..
+    } = loaded;
+
+    // The interpreter runs on the single-threaded layout interner, but showing the values of
+    // the failed expects needs the global one. So first run everything, then report.
+    let mut outcomes = Vec::new();
+    for (symbol, region) in toplevel_expects
+        .fx
+        .iter()
+        .chain(toplevel_expects.pure.iter())
+    {
+        let layout: ProcLayout = match procedures.keys().find(|(s, _)| s == symbol) {
+            Some((_, layout)) => *layout,
+            None => continue,
+        };
+
+        let mut interpreter = Interpreter::new(&layout_interner, &procedures);
+        let result = interpreter
+            .call(*symbol, layout, Vec::new())
+            .and_then(|value| interpreter.heap.dec(&value))
+            .map(|()| {
+                (
+                    std::mem::take(&mut interpreter.heap),
+                    interpreter.failed_expects,
+                )
+            });
+
+        outcomes.push(Outcome {
+            symbol: *symbol,
+            region: *region,
+            result,
+        });
+    }
+
+    let target_info = layout_interner.target_info();
+    let layout_interner = layout_interner.into_global();
+
+    let mut failed = 0;
+    let mut passed = 0;
+
+    for outcome in outcomes {
+        let (heap, failures) = match &outcome.result {
+            Ok((_, failures)) if failures.is_empty() => {
+                passed += 1;
+                continue;
+            }
+            Ok((heap, failures)) => (Some(heap), failures.as_slice()),
+            Err(_) => (None, &[]),
+        };
+
+        failed += 1;
+
+        let module_id = outcome.symbol.module_id();
+        let data = expectations.get_mut(&module_id).unwrap();
+
+        let filename = data.path.to_owned();
+        let source = std::fs::read_to_string(&data.path).unwrap();
+
+        let renderer = Renderer::new(arena, interns, render_target, module_id, filename, &source);
+
+        if let Err(error) = &outcome.result {
+            let message = match error {
+                Error::Crash { message, .. } => message.clone(),
+                other => other.to_string(),
+            };
+
+            renderer.render_panic(writer, &message, outcome.region)?;
+        }
+
+        for failure in failures {
+            let heap = heap.unwrap();
+
+            let lookups = match data.expectations.get(&failure.region) {
+                None => panic!("region {:?} not in list of expects", failure.region),
+                Some(lookups) => lookups,
+            };
+
+            let symbols = split_expect_lookups(&data.subs, lookups);
+
+            let mut expressions = Vec::with_capacity(failure.lookups.len());
+            for (value, variable) in failure.lookups.iter().zip(failure.variables) {
+                let mut layout_cache = LayoutCache::new(layout_interner.fork(), target_info);
+                let layout = layout_cache.from_var(arena, *variable, &data.subs).unwrap();
+
+                let (memory, addr) =
+                    match write_value(&layout_cache.interner, heap, target_info, value, layout) {
+                        Ok(written) => written,
+                        Err(error) => internal_error!("cannot show an expect lookup: {}", error),
+                    };
+                let memory = arena.alloc(memory);
+
+                let proc_layout = ProcLayout {
+                    arguments: &[],
+                    result: layout,
+                    niche: Niche::NONE,
+                };
+
+                expressions.push(jit_to_ast(
+                    arena,
+                    &mut InterpApp::new(memory, addr),
+                    "expect_repl_main_fn",
+                    proc_layout,
+                    *variable,
+                    &data.subs,
+                    interns,
+                    layout_interner.fork(),
+                    target_info,
+                ));
+            }
+
+            renderer.render_failure(
+                writer,
+                &mut data.subs,
+                &symbols,
+                failure.variables,
+                &expressions,
+                Some(outcome.region),
+                failure.region,
+            )?;
+        }
+
+        writeln!(writer)?;
+    }
+
+    Ok((failed, passed))
+}
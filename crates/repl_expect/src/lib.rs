@@ -15,6 +15,8 @@ use {
 #[cfg(not(windows))]
 mod app;
 #[cfg(not(windows))]
+pub mod interp;
+#[cfg(not(windows))]
 pub mod run;
 
 #[cfg(not(windows))]
@@ -94,7 +96,10 @@ mod test {
     use indoc::indoc;
     use pretty_assertions::assert_eq;
     use roc_gen_llvm::{llvm::build::LlvmBackendMode, run_roc::RocCallResult, run_roc_dylib};
-    use roc_load::{ExecutionMode, LoadConfig, LoadMonomorphizedError, Threading};
+    use roc_load::{
+        ExecutionMode, LoadConfig, LoadMonomorphizedError, MonoDiagnostics, MonoOptimizations,
+        Threading,
+    };
     use roc_packaging::cache::RocCacheDir;
     use roc_reporting::report::{RenderTarget, DEFAULT_PALETTE};
     use target_lexicon::Triple;
@@ -125,6 +130,8 @@ mod test {
             palette: DEFAULT_PALETTE,
             threading: Threading::Single,
             exec_mode: ExecutionMode::Test,
+            mono_optimizations: MonoOptimizations::Disabled,
+            mono_diagnostics: MonoDiagnostics::default(),
         };
         let loaded = match roc_load::load_and_monomorphize_from_str(
             arena,
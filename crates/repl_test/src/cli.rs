@@ -44,6 +44,9 @@ pub fn repl_eval(input: &str) -> Out {
 
     cmd.arg("repl");
 
+    #[cfg(feature = "interpreter")]
+    cmd.arg("--interpret");
+
     let mut child = cmd
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
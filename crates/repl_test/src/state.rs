@@ -1,5 +1,5 @@
 use indoc::indoc;
-use roc_repl_cli::repl_state::{is_incomplete, ReplState, TIPS};
+use roc_repl_cli::repl_state::{is_incomplete, ReplBackend, ReplState, TIPS};
 
 // These are tests of the REPL state machine. They work without actually
 // running the CLI, and without using rustyline, and instead verify
@@ -30,6 +30,21 @@ fn persisted_defs() {
     complete("val1 + x + y", &mut state, Ok(("15 : Num *", "val2")));
 }
 
+#[test]
+fn interpreted_defs() {
+    let mut state = ReplState::with_backend(ReplBackend::Interpreter);
+
+    complete("x = 5", &mut state, Ok(("5 : Num *", "x")));
+    complete(
+        "{ a: Str.concat \"ab\" \"c\", b: [x, 6] }",
+        &mut state,
+        Ok((
+            "{ a: \"abc\", b: [5, 6] } : { a : Str, b : List (Num *) }",
+            "val1",
+        )),
+    );
+}
+
 #[test]
 fn annotated_body() {
     let mut input = "t : [A, B, C]".to_string();
@@ -57,8 +57,11 @@ pub fn report_problems(
     interns: &Interns,
     can_problems: &mut MutMap<ModuleId, Vec<roc_problem::can::Problem>>,
     type_problems: &mut MutMap<ModuleId, Vec<TypeError>>,
+    mono_problems: &mut MutMap<ModuleId, Vec<roc_problem::mono::Problem>>,
 ) -> Problems {
-    use crate::report::{can_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE};
+    use crate::report::{
+        can_problem, mono_problem, type_problem, Report, RocDocAllocator, DEFAULT_PALETTE,
+    };
     use roc_problem::Severity::*;
     let palette = DEFAULT_PALETTE;
 
@@ -124,6 +127,19 @@ pub fn report_problems(
                 }
             }
         }
+
+        let mut problems = mono_problems.remove(home).unwrap_or_default();
+        problems.sort_by_key(|problem| problem.region());
+
+        for problem in problems {
+            // these are always warnings
+            let report = mono_problem(&alloc, &lines, module_path.clone(), problem);
+            let mut buf = String::new();
+
+            report.render_color_terminal(&mut buf, &alloc, &palette);
+
+            warnings.push(buf);
+        }
     }
 
     let problems_reported;
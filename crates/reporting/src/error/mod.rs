@@ -1,4 +1,5 @@
 pub mod canonicalize;
 pub mod expect;
+pub mod mono;
 pub mod parse;
 pub mod r#type;
@@ -0,0 +1,115 @@
+use roc_module::symbol::Symbol;
+use roc_problem::mono::Problem;
+use roc_region::all::LineInfo;
+use std::path::PathBuf;
+
+use crate::report::{Report, RocDocAllocator, RocDocBuilder};
+use ven_pretty::DocAllocator;
+
+const MISSED_IN_PLACE_UPDATE: &str = "MISSED IN-PLACE UPDATE";
+const MISSED_REUSE: &str = "MISSED MEMORY REUSE";
+
+pub fn mono_problem<'b>(
+    alloc: &'b RocDocAllocator<'b>,
+    lines: &LineInfo,
+    filename: PathBuf,
+    problem: Problem,
+) -> Report<'b> {
+    let doc;
+    let title;
+    let severity = problem.severity();
+
+    match problem {
+        Problem::SharedUpdate {
+            region,
+            function,
+            value,
+            later_use,
+        } => {
+            let update = match function {
+                Some(function) => alloc.concat([
+                    alloc.reflow("This "),
+                    alloc.symbol_foreign_qualified(function),
+                    alloc.reflow(" call"),
+                ]),
+                None => alloc.reflow("This update"),
+            };
+
+            let mut stack = vec![
+                alloc.concat([
+                    update,
+                    alloc.reflow(" always copies "),
+                    value_name(alloc, value),
+                    alloc.reflow(" instead of updating it in place:"),
+                ]),
+                alloc.region(lines.convert_region(region)),
+            ];
+
+            match later_use {
+                Some(later_use) => {
+                    stack.push(alloc.reflow(
+                        "That is because it is still used afterwards, here, so it is shared at the time of the update:",
+                    ));
+                    stack.push(alloc.region(lines.convert_region(later_use)));
+                }
+                None => {
+                    stack.push(alloc.reflow(
+                        "That is because it is still used afterwards, so it is shared at the time of the update.",
+                    ));
+                }
+            }
+
+            stack.push(alloc.tip().append(alloc.reflow(
+                "If the later use only needs part of the value, like its length, get that before the update.",
+            )));
+
+            doc = alloc.stack(stack);
+            title = MISSED_IN_PLACE_UPDATE.to_string();
+        }
+        Problem::MissedReuse {
+            region,
+            freed,
+            freed_region,
+        } => {
+            let mut stack = vec![
+                alloc.reflow("This allocates new memory:"),
+                alloc.region(lines.convert_region(region)),
+                alloc.concat([
+                    alloc.reflow("It could have reused the memory of "),
+                    value_name(alloc, freed),
+                    alloc.reflow(", which has the same shape, but that is still used afterwards."),
+                ]),
+            ];
+
+            if let Some(freed_region) = freed_region {
+                stack.push(alloc.region(lines.convert_region(freed_region)));
+            }
+
+            stack.push(alloc.tip().append(alloc.reflow(
+                "Values can only be reused after their last use, so try to finish using the old value before building the new one.",
+            )));
+
+            doc = alloc.stack(stack);
+            title = MISSED_REUSE.to_string();
+        }
+    }
+
+    Report {
+        title,
+        filename,
+        doc,
+        severity,
+    }
+}
+
+/// Values introduced by the compiler have numbers for names
+fn value_name<'b>(alloc: &'b RocDocAllocator<'b>, symbol: Symbol) -> RocDocBuilder<'b> {
+    if symbol
+        .as_str(alloc.interns)
+        .starts_with(|c: char| c.is_ascii_digit())
+    {
+        alloc.reflow("this value")
+    } else {
+        alloc.symbol_unqualified(symbol)
+    }
+}
@@ -2,7 +2,7 @@ use bumpalo::{collections::Vec, Bump};
 use std::fmt::{self, Write};
 use std::iter::{self, once, Iterator};
 
-use roc_wasm_module::opcodes::OpCode;
+use roc_wasm_module::opcodes::{MiscOp, OpCode, SimdOp};
 use roc_wasm_module::parse::{Parse, SkipBytes};
 use roc_wasm_module::sections::{ImportDesc, MemorySection, SignatureParamsIter};
 use roc_wasm_module::{ExportType, WasmModule};
@@ -166,13 +166,35 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         })
     }
 
+    /// Call an exported function that returns at most one value
     pub fn call_export<A>(&mut self, fn_name: &str, arg_values: A) -> Result<Option<Value>, String>
     where
         A: IntoIterator<Item = Value>,
     {
-        let (fn_index, param_type_iter, ret_type) =
+        let mut return_values = self.call_export_multi_value(fn_name, arg_values)?;
+        if return_values.len() > 1 {
+            return Err(format!(
+                "{} returned {} values. Use call_export_multi_value to get all of them.",
+                fn_name,
+                return_values.len()
+            ));
+        }
+        Ok(return_values.pop())
+    }
+
+    /// Call an exported function that may return several values (Wasm multi-value proposal)
+    pub fn call_export_multi_value<A>(
+        &mut self,
+        fn_name: &str,
+        arg_values: A,
+    ) -> Result<std::vec::Vec<Value>, String>
+    where
+        A: IntoIterator<Item = Value>,
+    {
+        let (fn_index, param_type_iter, return_type_iter) =
             self.call_export_help_before_arg_load(self.module, fn_name)?;
         let n_args = param_type_iter.len();
+        let n_return_values = return_type_iter.len();
 
         for (i, (value, expected_type)) in arg_values.into_iter().zip(param_type_iter).enumerate() {
             let actual_type = ValueType::from(value);
@@ -185,7 +207,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             self.value_store.push(value);
         }
 
-        self.call_export_help_after_arg_load(self.module, fn_index, n_args, ret_type)
+        self.call_export_help_after_arg_load(self.module, fn_index, n_args, n_return_values)
     }
 
     pub fn call_export_from_cli(
@@ -207,9 +229,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
 
         // Implement the "basic numbers" CLI
         // Check if the called Wasm function takes numeric arguments, and if so, try to parse them from the CLI.
-        let (fn_index, param_type_iter, ret_type) =
+        let (fn_index, param_type_iter, return_type_iter) =
             self.call_export_help_before_arg_load(module, fn_name)?;
         let n_args = param_type_iter.len();
+        let n_return_values = return_type_iter.len();
         for (value_bytes, value_type) in arg_strings
             .iter()
             .skip(1) // first string is the .wasm filename
@@ -222,18 +245,21 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 I64 => Value::I64(value_str.parse::<i64>().map_err(|e| e.to_string())?),
                 F32 => Value::F32(value_str.parse::<f32>().map_err(|e| e.to_string())?),
                 F64 => Value::F64(value_str.parse::<f64>().map_err(|e| e.to_string())?),
+                V128 => Value::V128(value_str.parse::<u128>().map_err(|e| e.to_string())?),
             };
             self.value_store.push(value);
         }
 
-        self.call_export_help_after_arg_load(module, fn_index, n_args, ret_type)
+        let mut return_values =
+            self.call_export_help_after_arg_load(module, fn_index, n_args, n_return_values)?;
+        Ok(return_values.pop())
     }
 
     fn call_export_help_before_arg_load<'m>(
         &mut self,
         module: &'m WasmModule<'a>,
         fn_name: &str,
-    ) -> Result<(usize, SignatureParamsIter<'m>, Option<ValueType>), String> {
+    ) -> Result<(usize, SignatureParamsIter<'m>, SignatureParamsIter<'m>), String> {
         let fn_index = {
             let mut export_iter = module.export.exports.iter();
             export_iter
@@ -276,7 +302,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             cursor
         };
 
-        let (param_type_iter, return_type) = {
+        let (param_type_iter, return_type_iter) = {
             let signature_index = module.function.signatures[internal_fn_index];
             module.types.look_up(signature_index)
         };
@@ -290,7 +316,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             );
         }
 
-        Ok((fn_index, param_type_iter, return_type))
+        Ok((fn_index, param_type_iter, return_type_iter))
     }
 
     fn call_export_help_after_arg_load(
@@ -298,8 +324,8 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         module: &WasmModule<'a>,
         fn_index: usize,
         n_args: usize,
-        return_type: Option<ValueType>,
-    ) -> Result<Option<Value>, String> {
+        n_return_values: usize,
+    ) -> Result<std::vec::Vec<Value>, String> {
         self.previous_frames.clear();
         self.blocks.clear();
         self.blocks.push(Block {
@@ -311,7 +337,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             0, // return_addr
             self.blocks.len(),
             n_args,
-            return_type,
+            n_return_values,
             &module.code.bytes,
             &mut self.value_store,
             &mut self.program_counter,
@@ -336,13 +362,11 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             };
         }
 
-        let return_value = if !self.value_store.is_empty() {
-            Some(self.value_store.pop())
-        } else {
-            None
-        };
+        let return_values_start = self.value_store.depth().saturating_sub(n_return_values);
+        let return_values = self.value_store.get_slice(return_values_start).to_vec();
+        self.value_store.truncate(return_values_start);
 
-        Ok(return_value)
+        Ok(return_values)
     }
 
     fn fetch_immediate_u32(&mut self, module: &WasmModule<'a>) -> u32 {
@@ -359,21 +383,20 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         let Frame {
             return_addr,
             body_block_index,
-            return_type,
+            return_count,
             ..
         } = self.current_frame;
 
-        // Throw away all locals and values except the return value
+        // Throw away all locals and values except the return values
         let locals_block_index = body_block_index - 1;
         let locals_block = &self.blocks[locals_block_index];
-        let new_stack_depth = if return_type.is_some() {
-            self.value_store
-                .set(locals_block.vstack, self.value_store.peek());
-            locals_block.vstack + 1
-        } else {
-            locals_block.vstack
-        };
-        self.value_store.truncate(new_stack_depth);
+        let return_values_start = self.value_store.depth() - return_count;
+        for i in 0..return_count {
+            let value = *self.value_store.get(return_values_start + i).unwrap();
+            self.value_store.set(locals_block.vstack + i, value);
+        }
+        self.value_store
+            .truncate(locals_block.vstack + return_count);
 
         // Resume executing at the next instruction in the caller function
         let new_block_len = locals_block_index; // don't need a -1 because one is a length and the other is an index!
@@ -506,10 +529,11 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
             );
         }
 
-        let (arg_type_iter, ret_type) = module.types.look_up(signature_index);
+        let (arg_type_iter, return_type_iter) = module.types.look_up(signature_index);
         let n_args = arg_type_iter.len();
+        let n_return_values = return_type_iter.len();
         if self.debug_string.is_some() {
-            self.debug_call(n_args, ret_type);
+            self.debug_call(n_args, return_type_iter);
         }
 
         if let Some(import) = opt_import {
@@ -556,7 +580,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 return_addr,
                 body_block_index,
                 n_args,
-                ret_type,
+                n_return_values,
                 &module.code.bytes,
                 &mut self.value_store,
                 &mut self.program_counter,
@@ -574,7 +598,7 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         Ok(())
     }
 
-    fn debug_call(&mut self, n_args: usize, return_type: Option<ValueType>) {
+    fn debug_call(&mut self, n_args: usize, return_types: SignatureParamsIter) {
         if let Some(debug_string) = self.debug_string.as_mut() {
             write!(debug_string, "         args=[").unwrap();
             let arg_iter = self
@@ -590,7 +614,8 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 }
                 write!(debug_string, "{:x?}", arg).unwrap();
             }
-            writeln!(debug_string, "] return_type={:?}", return_type).unwrap();
+            let return_types: std::vec::Vec<ValueType> = return_types.collect();
+            writeln!(debug_string, "] return_types={:?}", return_types).unwrap();
         }
     }
 
@@ -1692,6 +1717,19 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
                 self.value_store
                     .push(Value::F64(f64::from_ne_bytes(x.to_ne_bytes())));
             }
+
+            MISCPREFIX => {
+                let misc_op =
+                    MiscOp::parse((), &module.code.bytes, &mut self.program_counter).unwrap();
+                self.write_debug(misc_op);
+                self.execute_misc_instruction(misc_op, module)?;
+            }
+            SIMDPREFIX => {
+                let simd_op =
+                    SimdOp::parse((), &module.code.bytes, &mut self.program_counter).unwrap();
+                self.write_debug(simd_op);
+                self.execute_simd_instruction(simd_op, module)?;
+            }
         }
 
         if let Some(debug_string) = &self.debug_string {
@@ -1717,6 +1755,150 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         Ok(action)
     }
 
+    fn execute_misc_instruction(
+        &mut self,
+        misc_op: MiscOp,
+        module: &WasmModule<'a>,
+    ) -> Result<(), Error> {
+        match misc_op {
+            MiscOp::MEMORYCOPY => {
+                let dst_memory = self.fetch_immediate_u32(module);
+                let src_memory = self.fetch_immediate_u32(module);
+                assert_eq!((dst_memory, src_memory), (0, 0));
+                let size = self.value_store.pop_u32()? as usize;
+                let src = self.value_store.pop_u32()? as usize;
+                let dst = self.value_store.pop_u32()? as usize;
+                // Regions may overlap, so this is a memmove rather than a memcpy
+                self.memory.copy_within(src..src + size, dst);
+            }
+            MiscOp::MEMORYFILL => {
+                let memory_index = self.fetch_immediate_u32(module);
+                assert_eq!(memory_index, 0);
+                let size = self.value_store.pop_u32()? as usize;
+                let value = self.value_store.pop_u32()?;
+                let dst = self.value_store.pop_u32()? as usize;
+                self.memory[dst..][..size].fill(value as u8);
+            }
+        }
+        Ok(())
+    }
+
+    fn execute_simd_instruction(
+        &mut self,
+        simd_op: SimdOp,
+        module: &WasmModule<'a>,
+    ) -> Result<(), Error> {
+        use SimdOp::*;
+
+        match simd_op {
+            V128LOAD => {
+                let addr = self.get_load_address(module)? as usize;
+                let mut bytes = [0; 16];
+                bytes.copy_from_slice(&self.memory[addr..][..16]);
+                self.value_store
+                    .push(Value::V128(u128::from_le_bytes(bytes)));
+            }
+            V128STORE => {
+                let (addr, value) = self.get_store_addr_value(module)?;
+                let unwrapped = value.expect_v128().map_err(Error::from)?;
+                let target = &mut self.memory[addr..][..16];
+                target.copy_from_slice(&unwrapped.to_le_bytes());
+            }
+            V128CONST => {
+                let mut bytes = [0; 16];
+                bytes.copy_from_slice(&module.code.bytes[self.program_counter..][..16]);
+                let value = u128::from_le_bytes(bytes);
+                self.write_debug(value);
+                self.value_store.push(Value::V128(value));
+                self.program_counter += 16;
+            }
+            I8X16SPLAT => {
+                let lane = self.value_store.pop_u32()? as u8;
+                self.value_store.push(Value::V128(splat(lane as u64, 1)));
+            }
+            I16X8SPLAT => {
+                let lane = self.value_store.pop_u32()? as u16;
+                self.value_store.push(Value::V128(splat(lane as u64, 2)));
+            }
+            I32X4SPLAT => {
+                let lane = self.value_store.pop_u32()?;
+                self.value_store.push(Value::V128(splat(lane as u64, 4)));
+            }
+            I64X2SPLAT => {
+                let lane = self.value_store.pop_u64()?;
+                self.value_store.push(Value::V128(splat(lane, 8)));
+            }
+            I8X16EQ => self.simd_binop(1, |x, y| if x == y { u64::MAX } else { 0 })?,
+            I8X16NE => self.simd_binop(1, |x, y| if x != y { u64::MAX } else { 0 })?,
+            V128NOT => {
+                let x = self.value_store.pop_v128()?;
+                self.value_store.push(Value::V128(!x));
+            }
+            V128AND => {
+                let y = self.value_store.pop_v128()?;
+                let x = self.value_store.pop_v128()?;
+                self.value_store.push(Value::V128(x & y));
+            }
+            V128ANDNOT => {
+                let y = self.value_store.pop_v128()?;
+                let x = self.value_store.pop_v128()?;
+                self.value_store.push(Value::V128(x & !y));
+            }
+            V128OR => {
+                let y = self.value_store.pop_v128()?;
+                let x = self.value_store.pop_v128()?;
+                self.value_store.push(Value::V128(x | y));
+            }
+            V128XOR => {
+                let y = self.value_store.pop_v128()?;
+                let x = self.value_store.pop_v128()?;
+                self.value_store.push(Value::V128(x ^ y));
+            }
+            V128ANYTRUE => {
+                let x = self.value_store.pop_v128()?;
+                self.value_store.push(Value::I32((x != 0) as i32));
+            }
+            I8X16ALLTRUE => {
+                let x = self.value_store.pop_v128()?;
+                let all_true = x.to_le_bytes().iter().all(|lane| *lane != 0);
+                self.value_store.push(Value::I32(all_true as i32));
+            }
+            I8X16BITMASK => {
+                let x = self.value_store.pop_v128()?;
+                let mut mask = 0;
+                for (i, lane) in x.to_le_bytes().iter().enumerate() {
+                    mask |= ((lane >> 7) as i32) << i;
+                }
+                self.value_store.push(Value::I32(mask));
+            }
+            I8X16ADD => self.simd_binop(1, u64::wrapping_add)?,
+            I8X16SUB => self.simd_binop(1, u64::wrapping_sub)?,
+            I32X4ADD => self.simd_binop(4, u64::wrapping_add)?,
+            I32X4SUB => self.simd_binop(4, u64::wrapping_sub)?,
+            I64X2ADD => self.simd_binop(8, u64::wrapping_add)?,
+            I64X2SUB => self.simd_binop(8, u64::wrapping_sub)?,
+        }
+        Ok(())
+    }
+
+    /// Apply a binary operation to each lane of two vectors.
+    /// Lanes are zero-extended to u64 and results are truncated back to the lane width.
+    fn simd_binop(&mut self, lane_bytes: u32, op: fn(u64, u64) -> u64) -> Result<(), Error> {
+        let y = self.value_store.pop_v128()?;
+        let x = self.value_store.pop_v128()?;
+        let lane_bits = lane_bytes * 8;
+        let lane_mask = u64::MAX >> (64 - lane_bits);
+        let mut result = 0;
+        for lane in 0..(16 / lane_bytes) {
+            let shift = lane * lane_bits;
+            let x_lane = (x >> shift) as u64 & lane_mask;
+            let y_lane = (y >> shift) as u64 & lane_mask;
+            result |= ((op(x_lane, y_lane) & lane_mask) as u128) << shift;
+        }
+        self.value_store.push(Value::V128(result));
+        Ok(())
+    }
+
     #[allow(dead_code)]
     fn debug_values_and_blocks(&self, label: &str) {
         eprintln!("\n========== {} ==========", label);
@@ -1848,3 +2030,10 @@ impl<'a, I: ImportDispatcher> Instance<'a, I> {
         call_addr
     }
 }
+
+/// Copy one lane value into every lane of a vector
+fn splat(lane: u64, lane_bytes: u32) -> u128 {
+    let lane_mask = u128::MAX >> (128 - lane_bytes * 8);
+    let ones_in_every_lane = u128::MAX / lane_mask;
+    lane as u128 * ones_in_every_lane
+}
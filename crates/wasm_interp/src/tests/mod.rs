@@ -7,12 +7,13 @@ mod test_f64;
 mod test_i32;
 mod test_i64;
 mod test_mem;
+mod test_simd;
 
 use crate::{DefaultImportDispatcher, Instance};
 use bumpalo::{collections::Vec, Bump};
 use roc_wasm_module::{
-    opcodes::OpCode, Export, ExportType, SerialBuffer, Serialize, Signature, Value, ValueType,
-    WasmModule,
+    opcodes::{OpCode, SimdOp},
+    Export, ExportType, SerialBuffer, Serialize, Signature, Value, ValueType, WasmModule,
 };
 
 pub fn default_state(arena: &Bump) -> Instance<DefaultImportDispatcher> {
@@ -47,11 +48,16 @@ pub fn const_value(buf: &mut Vec<'_, u8>, value: Value) {
             buf.push(OpCode::F64CONST as u8);
             buf.encode_f64(x);
         }
+        V128(x) => {
+            SimdOp::V128CONST.serialize(buf);
+            buf.append_slice(&x.to_le_bytes());
+        }
     }
 }
 
-pub fn test_op_example<A>(op: OpCode, args: A, expected: Value)
+pub fn test_op_example<Op, A>(op: Op, args: A, expected: Value)
 where
+    Op: Serialize + std::fmt::Debug,
     A: IntoIterator<Item = Value>,
 {
     let arena = Bump::new();
@@ -65,7 +71,7 @@ where
         for arg in args {
             const_value(buf, arg);
         }
-        buf.push(op as u8);
+        op.serialize(buf);
         buf.push(OpCode::END as u8); // end function
 
         buf.overwrite_padded_u32(func_len_index, (buf.len() - start) as u32);
@@ -74,7 +80,7 @@ where
         module.code.function_offsets.push(0);
         module.add_function_signature(Signature {
             param_types: Vec::new_in(&arena),
-            ret_type: Some(ValueType::from(expected)),
+            ret_types: bumpalo::vec![in &arena; ValueType::from(expected)],
         });
         module.export.append(Export {
             name: "test",
@@ -2,9 +2,9 @@ use super::create_exported_function_no_locals;
 use crate::{DefaultImportDispatcher, Instance};
 use bumpalo::{collections::Vec, Bump};
 use roc_wasm_module::{
-    opcodes::OpCode,
+    opcodes::{MiscOp, OpCode, SimdOp},
     sections::{DataMode, DataSegment, MemorySection},
-    ConstExpr, SerialBuffer, Signature, Value, ValueType, WasmModule,
+    ConstExpr, SerialBuffer, Serialize, Signature, Value, ValueType, WasmModule,
 };
 
 #[test]
@@ -49,7 +49,13 @@ fn test_growmemory() {
     assert_eq!(state.memory.len(), 5 * MemorySection::PAGE_SIZE as usize);
 }
 
-fn test_load(load_op: OpCode, ty: ValueType, data: &[u8], addr: u32, offset: u32) -> Value {
+fn test_load<Op: Serialize>(
+    load_op: Op,
+    ty: ValueType,
+    data: &[u8],
+    addr: u32,
+    offset: u32,
+) -> Value {
     let arena = Bump::new();
     let mut module = WasmModule::new(&arena);
 
@@ -67,13 +73,13 @@ fn test_load(load_op: OpCode, ty: ValueType, data: &[u8], addr: u32, offset: u32
 
     let signature = Signature {
         param_types: bumpalo::vec![in &arena],
-        ret_type: Some(ty),
+        ret_types: bumpalo::vec![in &arena; ty],
     };
 
     create_exported_function_no_locals(&mut module, start_fn_name, signature, |buf| {
         buf.append_u8(OpCode::I32CONST as u8);
         buf.encode_u32(addr);
-        buf.append_u8(load_op as u8);
+        load_op.serialize(buf);
         buf.encode_u32(0); // align
         buf.encode_u32(offset);
         buf.append_u8(OpCode::END as u8);
@@ -241,11 +247,24 @@ fn test_i64load32u() {
     );
 }
 
-fn test_store<'a>(
+#[test]
+fn test_v128load() {
+    let bytes = "abcdefghijklmnopqrst".as_bytes();
+    assert_eq!(
+        test_load(SimdOp::V128LOAD, ValueType::V128, bytes, 0x11, 0),
+        Value::V128(u128::from_le_bytes(*b"abcdefghijklmnop"))
+    );
+    assert_eq!(
+        test_load(SimdOp::V128LOAD, ValueType::V128, bytes, 0x11, 4),
+        Value::V128(u128::from_le_bytes(*b"efghijklmnopqrst"))
+    );
+}
+
+fn test_store<'a, Op: Serialize>(
     arena: &'a Bump,
     module: &'a mut WasmModule<'a>,
     addr: u32,
-    store_op: OpCode,
+    store_op: Op,
     offset: u32,
     value: Value,
 ) -> Vec<'a, u8> {
@@ -255,7 +274,7 @@ fn test_store<'a>(
 
     let signature = Signature {
         param_types: bumpalo::vec![in arena],
-        ret_type: None,
+        ret_types: bumpalo::vec![in arena],
     };
 
     create_exported_function_no_locals(module, start_fn_name, signature, |buf| {
@@ -278,8 +297,12 @@ fn test_store<'a>(
                 buf.append_u8(OpCode::F64CONST as u8);
                 buf.encode_f64(x);
             }
+            Value::V128(x) => {
+                SimdOp::V128CONST.serialize(buf);
+                buf.append_slice(&x.to_le_bytes());
+            }
         }
-        buf.append_u8(store_op as u8);
+        store_op.serialize(buf);
         buf.encode_u32(0); // align
         buf.encode_u32(offset);
         buf.append_u8(OpCode::END as u8);
@@ -446,3 +469,94 @@ fn test_i64store32() {
         &[0xf0, 0xde, 0xbc, 0x9a, 0x00, 0x00, 0x00, 0x00]
     );
 }
+
+#[test]
+fn test_v128store() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let addr: u32 = 0x11;
+    let offset = 1;
+    let inner = u128::from_le_bytes(*b"abcdefghijklmnop");
+    let value = Value::V128(inner);
+    let memory = test_store(&arena, module, addr, SimdOp::V128STORE, offset, value);
+
+    let index = (addr + offset) as usize;
+    assert_eq!(&memory[index..][..16], b"abcdefghijklmnop");
+    assert_eq!(memory[index + 16], 0);
+}
+
+/// Run a bulk memory instruction on "abcdefghijklmnop" at address 0x10
+fn test_bulk_memory<'a>(
+    arena: &'a Bump,
+    module: &'a mut WasmModule<'a>,
+    misc_op: MiscOp,
+    args: [i32; 3],
+) -> Vec<'a, u8> {
+    let start_fn_name = "test";
+
+    module.memory = MemorySection::new(arena, MemorySection::PAGE_SIZE);
+    module.data.append_segment(DataSegment {
+        mode: DataMode::Active {
+            offset: ConstExpr::I32(0x10),
+        },
+        init: Vec::from_iter_in(b"abcdefghijklmnop".iter().copied(), arena),
+    });
+
+    let signature = Signature {
+        param_types: bumpalo::vec![in arena],
+        ret_types: bumpalo::vec![in arena],
+    };
+
+    create_exported_function_no_locals(module, start_fn_name, signature, |buf| {
+        for arg in args {
+            buf.append_u8(OpCode::I32CONST as u8);
+            buf.encode_i32(arg);
+        }
+        misc_op.serialize(buf);
+        match misc_op {
+            MiscOp::MEMORYCOPY => buf.append_slice(&[0, 0]), // destination & source memories
+            MiscOp::MEMORYFILL => buf.append_u8(0),          // memory
+        }
+        buf.append_u8(OpCode::END as u8);
+    });
+
+    let is_debug_mode = false;
+    let mut inst = Instance::for_module(
+        arena,
+        module,
+        DefaultImportDispatcher::default(),
+        is_debug_mode,
+    )
+    .unwrap();
+    inst.call_export(start_fn_name, []).unwrap();
+
+    inst.memory
+}
+
+#[test]
+fn test_memory_copy() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let memory = test_bulk_memory(&arena, module, MiscOp::MEMORYCOPY, [0x30, 0x12, 4]);
+    assert_eq!(&memory[0x30..][..5], b"cdef\0");
+}
+
+#[test]
+fn test_memory_copy_overlapping() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let memory = test_bulk_memory(&arena, module, MiscOp::MEMORYCOPY, [0x12, 0x10, 8]);
+    assert_eq!(&memory[0x10..][..16], b"ababcdefghklmnop");
+}
+
+#[test]
+fn test_memory_fill() {
+    let arena = Bump::new();
+    let module = arena.alloc(WasmModule::new(&arena));
+
+    let memory = test_bulk_memory(&arena, module, MiscOp::MEMORYFILL, [0x12, 0x178, 3]);
+    assert_eq!(&memory[0x10..][..8], b"abxxxfgh");
+}
@@ -0,0 +1,135 @@
+#![cfg(test)]
+
+use super::test_op_example;
+use roc_wasm_module::{opcodes::SimdOp, opcodes::SimdOp::*, Value};
+
+fn v128_from_bytes(bytes: [u8; 16]) -> Value {
+    Value::V128(u128::from_le_bytes(bytes))
+}
+
+fn test_v128_binop(op: SimdOp, arg1: u128, arg2: u128, expected: u128) {
+    test_op_example(
+        op,
+        [Value::V128(arg1), Value::V128(arg2)],
+        Value::V128(expected),
+    )
+}
+
+#[test]
+fn test_v128not() {
+    let value = Value::V128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+    test_op_example(
+        V128NOT,
+        [value],
+        Value::V128(!0x0123_4567_89ab_cdef_fedc_ba98_7654_3210),
+    );
+}
+
+#[test]
+fn test_splat() {
+    test_op_example(
+        I8X16SPLAT,
+        [Value::I32(0x1234)],
+        v128_from_bytes([0x34; 16]),
+    );
+    test_op_example(
+        I16X8SPLAT,
+        [Value::I32(0x1234)],
+        Value::V128(0x1234_1234_1234_1234_1234_1234_1234_1234),
+    );
+    test_op_example(
+        I32X4SPLAT,
+        [Value::I32(-2)],
+        Value::V128(0xffff_fffe_ffff_fffe_ffff_fffe_ffff_fffe),
+    );
+    test_op_example(
+        I64X2SPLAT,
+        [Value::I64(0x0123_4567_89ab_cdef)],
+        Value::V128(0x0123_4567_89ab_cdef_0123_4567_89ab_cdef),
+    );
+}
+
+#[test]
+fn test_i8x16eq_ne() {
+    let a = u128::from_le_bytes(*b"abcdefghijklmnop");
+    let b = u128::from_le_bytes(*b"abcdXfghijklmnoY");
+    let mut expected = [0xff; 16];
+    expected[4] = 0;
+    expected[15] = 0;
+    test_v128_binop(I8X16EQ, a, b, u128::from_le_bytes(expected));
+    test_v128_binop(I8X16NE, a, b, !u128::from_le_bytes(expected));
+    test_v128_binop(I8X16EQ, a, a, u128::MAX);
+}
+
+#[test]
+fn test_v128_bitwise() {
+    let a = 0xff00_ff00_0000_ffff_1234_5678_0000_0000;
+    let b = 0x0ff0_0ff0_ffff_ffff_0000_0000_1111_1111;
+    test_v128_binop(V128AND, a, b, a & b);
+    test_v128_binop(V128ANDNOT, a, b, a & !b);
+    test_v128_binop(V128OR, a, b, a | b);
+    test_v128_binop(V128XOR, a, b, a ^ b);
+}
+
+#[test]
+fn test_v128anytrue() {
+    test_op_example(V128ANYTRUE, [Value::V128(0)], Value::I32(0));
+    test_op_example(V128ANYTRUE, [Value::V128(1 << 127)], Value::I32(1));
+}
+
+#[test]
+fn test_i8x16alltrue() {
+    test_op_example(I8X16ALLTRUE, [v128_from_bytes([1; 16])], Value::I32(1));
+    let mut one_zero = [0x80; 16];
+    one_zero[9] = 0;
+    test_op_example(I8X16ALLTRUE, [v128_from_bytes(one_zero)], Value::I32(0));
+}
+
+#[test]
+fn test_i8x16bitmask() {
+    let mut bytes = [0x7f; 16];
+    bytes[0] = 0x80;
+    bytes[3] = 0xff;
+    bytes[15] = 0x81;
+    test_op_example(I8X16BITMASK, [v128_from_bytes(bytes)], Value::I32(0x8009));
+}
+
+#[test]
+fn test_i8x16add_sub() {
+    let a = v128_from_bytes([0xff; 16]);
+    let b = v128_from_bytes([0x02; 16]);
+    test_op_example(I8X16ADD, [a, b], v128_from_bytes([0x01; 16]));
+    test_op_example(I8X16SUB, [b, a], v128_from_bytes([0x03; 16]));
+}
+
+#[test]
+fn test_i32x4add_sub() {
+    test_v128_binop(
+        I32X4ADD,
+        0x0000_0001_ffff_ffff_7fff_ffff_0000_0010,
+        0x0000_0002_0000_0001_0000_0001_0000_0020,
+        0x0000_0003_0000_0000_8000_0000_0000_0030,
+    );
+    test_v128_binop(
+        I32X4SUB,
+        0x0000_0003_0000_0000_8000_0000_0000_0030,
+        0x0000_0002_0000_0001_0000_0001_0000_0020,
+        0x0000_0001_ffff_ffff_7fff_ffff_0000_0010,
+    );
+}
+
+#[test]
+fn test_i64x2add_sub() {
+    test_v128_binop(
+        I64X2ADD,
+        0xffff_ffff_ffff_ffff_0000_0000_ffff_ffff,
+        0x0000_0000_0000_0001_0000_0000_0000_0001,
+        0x0000_0000_0000_0000_0000_0001_0000_0000,
+    );
+    test_v128_binop(
+        I64X2SUB,
+        0x0000_0000_0000_0000_0000_0001_0000_0000,
+        0x0000_0000_0000_0001_0000_0000_0000_0001,
+        0xffff_ffff_ffff_ffff_0000_0000_ffff_ffff,
+    );
+}
@@ -104,6 +104,14 @@ impl<'a> ValueStore<'a> {
         }
     }
 
+    pub(crate) fn pop_v128(&mut self) -> Result<u128, Error> {
+        match self.values.pop() {
+            Some(Value::V128(x)) => Ok(x),
+            Some(bad) => Err(Error::Type(ValueType::V128, ValueType::from(bad))),
+            None => Err(Error::StackEmpty),
+        }
+    }
+
     pub(crate) fn iter(&self) -> std::slice::Iter<Value> {
         self.values.iter()
     }
@@ -127,11 +135,12 @@ impl Debug for ValueStore<'_> {
 mod tests {
     use super::*;
 
-    const VALUES: [Value; 4] = [
+    const VALUES: [Value; 5] = [
         Value::I32(123),
         Value::I64(123456),
         Value::F32(1.01),
         Value::F64(-1.1),
+        Value::V128(0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10),
     ];
 
     #[test]
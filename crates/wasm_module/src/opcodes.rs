@@ -183,6 +183,12 @@ pub enum OpCode {
     I64REINTERPRETF64 = 0xbd,
     F32REINTERPRETI32 = 0xbe,
     F64REINTERPRETI64 = 0xbf,
+
+    /// Prefix for the "miscellaneous" instructions, including bulk memory.
+    /// The rest of the opcode is a LEB-128 [MiscOp].
+    MISCPREFIX = 0xfc,
+    /// Prefix for fixed-width SIMD instructions. The rest of the opcode is a LEB-128 [SimdOp].
+    SIMDPREFIX = 0xfd,
 }
 
 impl From<u8> for OpCode {
@@ -191,6 +197,95 @@ impl From<u8> for OpCode {
     }
 }
 
+/// Define an enum for the instructions behind a prefix byte, with parsing and serialization.
+/// The sub-opcodes are sparse, so we can't transmute like we do for [OpCode].
+macro_rules! prefixed_opcodes {
+    ($(#[$attr:meta])* $name:ident, $prefix:ident { $($variant:ident = $value:literal,)* }) => {
+        $(#[$attr])*
+        #[repr(u32)]
+        #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+        pub enum $name {
+            $($variant = $value,)*
+        }
+
+        impl $name {
+            pub const PREFIX: OpCode = OpCode::$prefix;
+        }
+
+        impl TryFrom<u32> for $name {
+            type Error = u32;
+
+            fn try_from(x: u32) -> Result<Self, u32> {
+                match x {
+                    $($value => Ok(Self::$variant),)*
+                    _ => Err(x),
+                }
+            }
+        }
+
+        /// Parse the sub-opcode only. The prefix byte must already have been consumed.
+        impl Parse<()> for $name {
+            fn parse(_: (), bytes: &[u8], cursor: &mut usize) -> Result<Self, ParseError> {
+                let offset = *cursor;
+                let x = u32::parse((), bytes, cursor)?;
+                Self::try_from(x).map_err(|x| ParseError {
+                    offset,
+                    message: format!(
+                        "Unknown Wasm instruction 0x{:02x} {}",
+                        OpCode::$prefix as u8,
+                        x
+                    ),
+                })
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<T: crate::SerialBuffer>(&self, buffer: &mut T) {
+                buffer.append_u8(OpCode::$prefix as u8);
+                buffer.encode_u32(*self as u32);
+            }
+        }
+    };
+}
+
+prefixed_opcodes!(
+    /// Instructions with the 0xFC prefix. We only support the bulk memory operations on a single memory.
+    MiscOp, MISCPREFIX {
+        MEMORYCOPY = 10,
+        MEMORYFILL = 11,
+    }
+);
+
+prefixed_opcodes!(
+    /// Instructions with the 0xFD prefix. This is the subset of fixed-width SIMD
+    /// that we need for moving and comparing blocks of memory.
+    SimdOp, SIMDPREFIX {
+        V128LOAD = 0x00,
+        V128STORE = 0x0b,
+        V128CONST = 0x0c,
+        I8X16SPLAT = 0x0f,
+        I16X8SPLAT = 0x10,
+        I32X4SPLAT = 0x11,
+        I64X2SPLAT = 0x12,
+        I8X16EQ = 0x23,
+        I8X16NE = 0x24,
+        V128NOT = 0x4d,
+        V128AND = 0x4e,
+        V128ANDNOT = 0x4f,
+        V128OR = 0x50,
+        V128XOR = 0x51,
+        V128ANYTRUE = 0x53,
+        I8X16ALLTRUE = 0x63,
+        I8X16BITMASK = 0x64,
+        I8X16ADD = 0x6e,
+        I8X16SUB = 0x71,
+        I32X4ADD = 0xae,
+        I32X4SUB = 0xb1,
+        I64X2ADD = 0xce,
+        I64X2SUB = 0xd1,
+    }
+);
+
 /// The format of the *immediate* operands of an operator
 /// Immediates appear directly in the byte stream after the opcode,
 /// rather than being popped off the value stack. These are the possible forms.
@@ -198,8 +293,10 @@ impl From<u8> for OpCode {
 enum OpImmediates {
     NoImmediate,
     Byte1,
+    Byte2,
     Bytes4,
     Bytes8,
+    Bytes16,
     Leb32x1,
     Leb64x1,
     Leb32x2,
@@ -257,6 +354,13 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
             NoImmediate
         }
 
+        MISCPREFIX | SIMDPREFIX => {
+            return Err(format!(
+                "Wasm instruction prefix 0x{:02x} needs a sub-opcode",
+                op as u8
+            ))
+        }
+
         // Catch-all in case of an invalid cast from u8 to OpCode while parsing binary
         // (rustc keeps this code, I verified in Compiler Explorer)
         #[allow(unreachable_patterns)]
@@ -266,47 +370,76 @@ fn immediates_for(op: OpCode) -> Result<OpImmediates, String> {
     Ok(imm)
 }
 
+fn misc_immediates_for(op: MiscOp) -> OpImmediates {
+    use MiscOp::*;
+    use OpImmediates::*;
+
+    match op {
+        MEMORYCOPY => Byte2, // destination and source memory indices
+        MEMORYFILL => Byte1, // memory index
+    }
+}
+
+fn simd_immediates_for(op: SimdOp) -> OpImmediates {
+    use OpImmediates::*;
+    use SimdOp::*;
+
+    match op {
+        V128LOAD | V128STORE => Leb32x2,
+        V128CONST => Bytes16,
+        I8X16SPLAT | I16X8SPLAT | I32X4SPLAT | I64X2SPLAT | I8X16EQ | I8X16NE | V128NOT
+        | V128AND | V128ANDNOT | V128OR | V128XOR | V128ANYTRUE | I8X16ALLTRUE | I8X16BITMASK
+        | I8X16ADD | I8X16SUB | I32X4ADD | I32X4SUB | I64X2ADD | I64X2SUB => NoImmediate,
+    }
+}
+
 impl SkipBytes for OpCode {
     fn skip_bytes(bytes: &[u8], cursor: &mut usize) -> Result<(), ParseError> {
         use OpImmediates::*;
 
+        let opcode_offset = *cursor;
         let opcode_byte: u8 = bytes[*cursor];
+        *cursor += 1;
 
         let opcode: OpCode = OpCode::from(opcode_byte);
-        // will return Err if transmute was invalid
-        let immediates = immediates_for(opcode).map_err(|message| ParseError {
-            message,
-            offset: *cursor,
-        })?;
+        let immediates = match opcode {
+            OpCode::MISCPREFIX => misc_immediates_for(MiscOp::parse((), bytes, cursor)?),
+            OpCode::SIMDPREFIX => simd_immediates_for(SimdOp::parse((), bytes, cursor)?),
+            // will return Err if transmute was invalid
+            _ => immediates_for(opcode).map_err(|message| ParseError {
+                message,
+                offset: opcode_offset,
+            })?,
+        };
 
         match immediates {
-            NoImmediate => {
+            NoImmediate => {}
+            Byte1 => {
                 *cursor += 1;
             }
-            Byte1 => {
-                *cursor += 1 + 1;
+            Byte2 => {
+                *cursor += 2;
             }
             Bytes4 => {
-                *cursor += 1 + 4;
+                *cursor += 4;
             }
             Bytes8 => {
-                *cursor += 1 + 8;
+                *cursor += 8;
+            }
+            Bytes16 => {
+                *cursor += 16;
             }
             Leb32x1 => {
-                *cursor += 1;
                 u32::skip_bytes(bytes, cursor)?;
             }
             Leb64x1 => {
-                *cursor += 1;
                 u64::skip_bytes(bytes, cursor)?;
             }
             Leb32x2 => {
-                *cursor += 1;
                 u32::skip_bytes(bytes, cursor)?;
                 u32::skip_bytes(bytes, cursor)?;
             }
             BrTable => {
-                *cursor += 1;
                 let n_labels = 1 + u32::parse((), bytes, cursor)?;
                 for _ in 0..n_labels {
                     u32::skip_bytes(bytes, cursor)?;
@@ -322,3 +455,43 @@ impl Serialize for OpCode {
         (*self as u8).serialize(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Align, SerialBuffer};
+
+    #[test]
+    fn test_skip_prefixed_instructions() {
+        let mut bytes = std::vec::Vec::new();
+        MiscOp::MEMORYCOPY.serialize(&mut bytes);
+        bytes.extend_from_slice(&[0, 0]);
+        MiscOp::MEMORYFILL.serialize(&mut bytes);
+        bytes.push(0);
+        SimdOp::V128CONST.serialize(&mut bytes);
+        bytes.extend_from_slice(&[0xff; 16]);
+        SimdOp::V128STORE.serialize(&mut bytes);
+        bytes.push(Align::Bytes8 as u8);
+        bytes.encode_u32(1000);
+        SimdOp::I64X2SUB.serialize(&mut bytes); // sub-opcode needs 2 bytes of LEB-128
+        bytes.push(OpCode::END as u8);
+
+        let mut cursor = 0;
+        let mut opcodes = std::vec::Vec::new();
+        while bytes[cursor] != OpCode::END as u8 {
+            opcodes.push(cursor);
+            OpCode::skip_bytes(&bytes, &mut cursor).unwrap();
+        }
+        assert_eq!(opcodes, [0, 4, 7, 25, 30]);
+        assert_eq!(cursor, bytes.len() - 1);
+    }
+
+    #[test]
+    fn test_parse_unknown_prefixed_instruction() {
+        let bytes = [OpCode::SIMDPREFIX as u8, 0xff, 0x01];
+        let mut cursor = 0;
+        let err = OpCode::skip_bytes(&bytes, &mut cursor).unwrap_err();
+        assert_eq!(err.offset, 1);
+        assert_eq!(err.message, "Unknown Wasm instruction 0xfd 255");
+    }
+}
@@ -197,7 +197,8 @@ pub fn update_section_size<T: SerialBuffer>(buffer: &mut T, header_indices: Sect
 #[derive(PartialEq, Eq, Debug)]
 pub struct Signature<'a> {
     pub param_types: Vec<'a, ValueType>,
-    pub ret_type: Option<ValueType>,
+    /// Result types. More than one requires the multi-value proposal.
+    pub ret_types: Vec<'a, ValueType>,
 }
 
 impl Signature<'_> {
@@ -208,7 +209,7 @@ impl<'a> Serialize for Signature<'a> {
     fn serialize<T: SerialBuffer>(&self, buffer: &mut T) {
         buffer.append_u8(Self::SEPARATOR);
         self.param_types.serialize(buffer);
-        self.ret_type.serialize(buffer);
+        self.ret_types.serialize(buffer);
     }
 }
 
@@ -271,7 +272,10 @@ impl<'a> TypeSection<'a> {
 
     /// Find a matching signature or insert a new one. Return the index.
     pub fn insert(&mut self, signature: Signature<'a>) -> u32 {
-        let mut sig_bytes = Vec::with_capacity_in(signature.param_types.len() + 4, self.arena);
+        let mut sig_bytes = Vec::with_capacity_in(
+            signature.param_types.len() + signature.ret_types.len() + 4,
+            self.arena,
+        );
         signature.serialize(&mut sig_bytes);
 
         let sig_len = sig_bytes.len();
@@ -298,23 +302,24 @@ impl<'a> TypeSection<'a> {
         self.bytes.is_empty()
     }
 
-    pub fn look_up(&'a self, sig_index: u32) -> (SignatureParamsIter<'a>, Option<ValueType>) {
+    /// Look up the parameter and result types of a signature
+    pub fn look_up(&'a self, sig_index: u32) -> (SignatureParamsIter<'a>, SignatureParamsIter<'a>) {
         let mut offset = self.offsets[sig_index as usize];
         offset += 1; // separator
-        let param_count = u32::parse((), &self.bytes, &mut offset).unwrap() as usize;
-        let params_iter = SignatureParamsIter {
-            bytes: &self.bytes[offset..][..param_count],
-            index: 0,
-            end: param_count,
-        };
-        offset += param_count;
+        let params_iter = self.value_types_iter(&mut offset);
+        let results_iter = self.value_types_iter(&mut offset);
+        (params_iter, results_iter)
+    }
 
-        let return_type = if self.bytes[offset] == 0 {
-            None
-        } else {
-            Some(ValueType::from(self.bytes[offset + 1]))
+    fn value_types_iter(&'a self, offset: &mut usize) -> SignatureParamsIter<'a> {
+        let count = u32::parse((), &self.bytes, offset).unwrap() as usize;
+        let iter = SignatureParamsIter {
+            bytes: &self.bytes[*offset..][..count],
+            index: 0,
+            end: count,
         };
-        (params_iter, return_type)
+        *offset += count;
+        iter
     }
 }
 
@@ -1897,15 +1902,19 @@ mod tests {
         let signatures = [
             Signature {
                 param_types: bumpalo::vec![in arena],
-                ret_type: None,
+                ret_types: bumpalo::vec![in arena],
             },
             Signature {
                 param_types: bumpalo::vec![in arena; I32, I64, F32, F64],
-                ret_type: None,
+                ret_types: bumpalo::vec![in arena],
             },
             Signature {
                 param_types: bumpalo::vec![in arena; I32, I32, I32],
-                ret_type: Some(I32),
+                ret_types: bumpalo::vec![in arena; I32],
+            },
+            Signature {
+                param_types: bumpalo::vec![in arena; I32],
+                ret_types: bumpalo::vec![in arena; I64, F64, I32],
             },
         ];
         let capacity = signatures.len();
@@ -1919,5 +1928,9 @@ mod tests {
             section.insert(sig);
         }
         test_assert_types_preload(arena, &section);
+
+        let (params, results) = section.look_up(3);
+        assert_eq!(params.collect::<std::vec::Vec<_>>(), [I32]);
+        assert_eq!(results.collect::<std::vec::Vec<_>>(), [I64, F64, I32]);
     }
 }